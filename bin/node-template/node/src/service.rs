@@ -128,6 +128,9 @@ pub fn new_partial(
 			spawner: &task_manager.spawn_essential_handle(),
 			registry: config.prometheus_registry(),
 			check_for_equivocation: Default::default(),
+			offchain_tx_pool_factory: Some(OffchainTransactionPoolFactory::new(
+				transaction_pool.clone(),
+			)),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
 			compatibility_mode: Default::default(),
 		})?;
@@ -81,7 +81,11 @@ fn new_node(tokio_handle: Handle) -> node_cli::service::NewFullBase {
 		prometheus_config: None,
 		telemetry_endpoints: None,
 		default_heap_pages: None,
-		offchain_worker: OffchainWorkerConfig { enabled: true, indexing_enabled: false },
+		offchain_worker: OffchainWorkerConfig {
+			enabled: true,
+			indexing_enabled: false,
+			indexing_pruning: Default::default(),
+		},
 		force_authoring: false,
 		disable_grandpa: false,
 		dev_key_seed: Some(Sr25519Keyring::Alice.to_seed()),
@@ -94,6 +98,7 @@ fn new_node(tokio_handle: Handle) -> node_cli::service::NewFullBase {
 		base_path,
 		informant_output_format: Default::default(),
 		wasm_runtime_overrides: None,
+		block_verification_workers: std::num::NonZeroUsize::MIN,
 	};
 
 	node_cli::service::new_full_base(config, false, |_, _| ()).expect("Creates node")
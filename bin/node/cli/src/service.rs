@@ -32,8 +32,10 @@ use sc_client_api::{Backend, BlockBackend};
 use sc_consensus_babe::{self, SlotProportion};
 use sc_executor::NativeElseWasmExecutor;
 use sc_network::{event::Event, NetworkEventStream, NetworkService};
+use sc_network_bitswap::BitswapClient;
 use sc_network_common::sync::warp::WarpSyncParams;
 use sc_network_sync::SyncingService;
+use sc_rpc::bitswap::{Bitswap, BitswapApiServer};
 use sc_service::{config::Configuration, error::Error as ServiceError, RpcHandlers, TaskManager};
 use sc_statement_store::Store as StatementStore;
 use sc_telemetry::{Telemetry, TelemetryWorker};
@@ -154,7 +156,7 @@ pub fn new_partial(
 			),
 			grandpa::SharedVoterState,
 			Option<Telemetry>,
-			Arc<StatementStore>,
+			Option<Arc<StatementStore>>,
 		),
 	>,
 	ServiceError,
@@ -233,19 +235,30 @@ pub fn new_partial(
 			registry: config.prometheus_registry(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
 			offchain_tx_pool_factory: OffchainTransactionPoolFactory::new(transaction_pool.clone()),
+			verification_workers: config.block_verification_workers,
 		})?;
 
 	let import_setup = (block_import, grandpa_link, babe_link);
 
-	let statement_store = sc_statement_store::Store::new_shared(
-		&config.data_path,
-		Default::default(),
-		client.clone(),
-		keystore_container.local_keystore(),
-		config.prometheus_registry(),
-		&task_manager.spawn_handle(),
-	)
-	.map_err(|e| ServiceError::Other(format!("Statement store error: {:?}", e)))?;
+	// The statement store decrypts statements with the private keys, which never leave a remote
+	// signer. It is disabled, together with its RPC and network protocol, in that case.
+	let statement_store = match keystore_container.local_keystore() {
+		Some(keystore) => Some(
+			sc_statement_store::Store::new_shared(
+				&config.data_path,
+				Default::default(),
+				client.clone(),
+				keystore,
+				config.prometheus_registry(),
+				&task_manager.spawn_handle(),
+			)
+			.map_err(|e| ServiceError::Other(format!("Statement store error: {:?}", e)))?,
+		),
+		None => {
+			log::warn!("Statement store is disabled, because it requires a local keystore.");
+			None
+		},
+	};
 
 	let (rpc_extensions_builder, rpc_setup) = {
 		let (_, grandpa_link, _) = &import_setup;
@@ -286,7 +299,9 @@ pub fn new_partial(
 					subscription_executor,
 					finality_provider: finality_proof_provider.clone(),
 				},
-				statement_store: rpc_statement_store.clone(),
+				statement_store: rpc_statement_store
+					.clone()
+					.map(|store| store as Arc<dyn sp_statement_store::StatementStore>),
 				backend: rpc_backend.clone(),
 			};
 
@@ -363,15 +378,19 @@ pub fn new_full_base(
 		grandpa_protocol_name.clone(),
 	));
 
-	let statement_handler_proto = sc_network_statement::StatementHandlerPrototype::new(
-		client
-			.block_hash(0u32.into())
-			.ok()
-			.flatten()
-			.expect("Genesis block exists; qed"),
-		config.chain_spec.fork_id(),
-	);
-	net_config.add_notification_protocol(statement_handler_proto.set_config());
+	let statement_handler_proto = statement_store.is_some().then(|| {
+		sc_network_statement::StatementHandlerPrototype::new(
+			client
+				.block_hash(0u32.into())
+				.ok()
+				.flatten()
+				.expect("Genesis block exists; qed"),
+			config.chain_spec.fork_id(),
+		)
+	});
+	if let Some(statement_handler_proto) = &statement_handler_proto {
+		net_config.add_notification_protocol(statement_handler_proto.set_config());
+	}
 
 	let warp_sync = Arc::new(grandpa::warp_proof::NetworkProvider::new(
 		backend.clone(),
@@ -391,6 +410,29 @@ pub fn new_full_base(
 			warp_sync_params: Some(WarpSyncParams::WithProvider(warp_sync)),
 		})?;
 
+	// Serve the bitswap RPC when the node takes part in the IPFS network.
+	let rpc_builder = {
+		let bitswap_client = config.network.ipfs_server.then(|| {
+			let (bitswap_client, bitswap_worker) =
+				BitswapClient::new(network.clone(), client.clone());
+			task_manager.spawn_handle().spawn(
+				"bitswap-client",
+				Some("networking"),
+				bitswap_worker.run(),
+			);
+			bitswap_client
+		});
+
+		move |deny_unsafe, subscription_executor| {
+			let mut io = rpc_builder(deny_unsafe, subscription_executor)?;
+			if let Some(bitswap_client) = &bitswap_client {
+				io.merge(Bitswap::new(bitswap_client.clone(), deny_unsafe).into_rpc())
+					.map_err(|e| ServiceError::Application(Box::new(e)))?;
+			}
+			Ok(io)
+		}
+	};
+
 	let role = config.role.clone();
 	let force_authoring = config.force_authoring;
 	let backoff_authoring_blocks =
@@ -568,24 +610,28 @@ pub fn new_full_base(
 	}
 
 	// Spawn statement protocol worker
-	let statement_protocol_executor = {
-		let spawn_handle = task_manager.spawn_handle();
-		Box::new(move |fut| {
-			spawn_handle.spawn("network-statement-validator", Some("networking"), fut);
-		})
-	};
-	let statement_handler = statement_handler_proto.build(
-		network.clone(),
-		sync_service.clone(),
-		statement_store.clone(),
-		prometheus_registry.as_ref(),
-		statement_protocol_executor,
-	)?;
-	task_manager.spawn_handle().spawn(
-		"network-statement-handler",
-		Some("networking"),
-		statement_handler.run(),
-	);
+	if let (Some(statement_handler_proto), Some(statement_store)) =
+		(statement_handler_proto, &statement_store)
+	{
+		let statement_protocol_executor = {
+			let spawn_handle = task_manager.spawn_handle();
+			Box::new(move |fut| {
+				spawn_handle.spawn("network-statement-validator", Some("networking"), fut);
+			})
+		};
+		let statement_handler = statement_handler_proto.build(
+			network.clone(),
+			sync_service.clone(),
+			statement_store.clone(),
+			prometheus_registry.as_ref(),
+			statement_protocol_executor,
+		)?;
+		task_manager.spawn_handle().spawn(
+			"network-statement-handler",
+			Some("networking"),
+			statement_handler.run(),
+		);
+	}
 
 	if enable_offchain_worker {
 		task_manager.spawn_handle().spawn(
@@ -602,7 +648,10 @@ pub fn new_full_base(
 				is_validator: role.is_authority(),
 				enable_http_requests: true,
 				custom_extensions: move |_| {
-					vec![Box::new(statement_store.clone().as_statement_store_ext()) as Box<_>]
+					statement_store
+						.iter()
+						.map(|store| Box::new(store.clone().as_statement_store_ext()) as Box<_>)
+						.collect()
 				},
 			})
 			.run(client.clone(), task_manager.spawn_handle())
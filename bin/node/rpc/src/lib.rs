@@ -88,8 +88,8 @@ pub struct FullDeps<C, P, SC, B> {
 	pub babe: BabeDeps,
 	/// GRANDPA specific dependencies.
 	pub grandpa: GrandpaDeps<B>,
-	/// Shared statement store reference.
-	pub statement_store: Arc<dyn sp_statement_store::StatementStore>,
+	/// Shared statement store reference, if the node runs one.
+	pub statement_store: Option<Arc<dyn sp_statement_store::StatementStore>>,
 	/// The backend used by the node.
 	pub backend: Arc<B>,
 }
@@ -176,7 +176,7 @@ where
 	)?;
 	io.merge(
 		Grandpa::new(
-			subscription_executor,
+			subscription_executor.clone(),
 			shared_authority_set.clone(),
 			shared_voter_state,
 			justification_stream,
@@ -192,9 +192,15 @@ where
 
 	io.merge(StateMigration::new(client.clone(), backend, deny_unsafe).into_rpc())?;
 	io.merge(Dev::new(client, deny_unsafe).into_rpc())?;
-	let statement_store =
-		sc_rpc::statement::StatementStore::new(statement_store, deny_unsafe).into_rpc();
-	io.merge(statement_store)?;
+	if let Some(statement_store) = statement_store {
+		let statement_store = sc_rpc::statement::StatementStore::new(
+			statement_store,
+			deny_unsafe,
+			subscription_executor,
+		)
+		.into_rpc();
+		io.merge(statement_store)?;
+	}
 
 	Ok(io)
 }
@@ -36,10 +36,11 @@ use frame_support::{
 	parameter_types,
 	traits::{
 		fungible::{Balanced, Credit, ItemOf},
+		schedule::DispatchTime,
 		tokens::{nonfungibles_v2::Inspect, GetSalary, PayFromAccount},
 		AsEnsureOriginWithArg, ConstBool, ConstU128, ConstU16, ConstU32, Contains, Currency,
 		EitherOfDiverse, EqualPrivilegeOnly, Imbalance, InsideBoth, InstanceFilter,
-		KeyOwnerProofSystem, LockIdentifier, Nothing, OnUnbalanced, WithdrawReasons,
+		KeyOwnerProofSystem, LockIdentifier, Nothing, OnUnbalanced, StorePreimage, WithdrawReasons,
 	},
 	weights::{
 		constants::{
@@ -930,6 +931,8 @@ impl pallet_referenda::TracksInfo<Balance, BlockNumber> for TracksInfo {
 					floor: Perbill::from_percent(0),
 					ceil: Perbill::from_percent(100),
 				},
+				supermajority: None,
+				max_preparing: None,
 			},
 		)];
 		&DATA[..]
@@ -995,6 +998,7 @@ impl pallet_ranked_collective::Config for Runtime {
 	type Polls = RankedPolls;
 	type MinRankOfClass = traits::Identity;
 	type VoteWeight = pallet_ranked_collective::Geometric;
+	type OnRankChanged = Salary;
 }
 
 impl pallet_remark::Config for Runtime {
@@ -1735,6 +1739,42 @@ impl pallet_salary::Config for Runtime {
 	type Budget = Budget;
 }
 
+/// Opens reviews of fellowship evidence as referenda of the ranked collective. The member under
+/// review places the submission deposit.
+pub struct FellowshipReviews;
+impl pallet_core_fellowship::ReviewScheduler<AccountId, u16, pallet_core_fellowship::Call<Runtime>>
+	for FellowshipReviews
+{
+	fn schedule_review(
+		who: &AccountId,
+		_rank: u16,
+		call: pallet_core_fellowship::Call<Runtime>,
+	) -> sp_runtime::DispatchResult {
+		let proposal = Preimage::bound(RuntimeCall::from(call))?;
+		RankedPolls::submit(
+			RuntimeOrigin::signed(who.clone()),
+			Box::new(frame_system::RawOrigin::Root.into()),
+			proposal,
+			DispatchTime::After(1),
+		)
+	}
+
+	fn schedule_review_weight() -> Weight {
+		// The review calls are small enough to be inlined instead of noted as a preimage.
+		<pallet_referenda::weights::SubstrateWeight<Runtime> as pallet_referenda::WeightInfo>::submit()
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	fn ensure_successful(who: &AccountId, _rank: u16) {
+		let deposit = SubmissionDeposit::get().saturating_add(ExistentialDeposit::get());
+		Balances::make_free_balance_be(who, deposit);
+	}
+}
+
+parameter_types! {
+	pub const FellowshipReviewPeriod: BlockNumber = 7 * DAYS;
+}
+
 impl pallet_core_fellowship::Config for Runtime {
 	type WeightInfo = ();
 	type RuntimeEvent = RuntimeEvent;
@@ -1745,6 +1785,10 @@ impl pallet_core_fellowship::Config for Runtime {
 	type ApproveOrigin = EnsureRootWithSuccess<AccountId, ConstU16<9>>;
 	type PromoteOrigin = EnsureRootWithSuccess<AccountId, ConstU16<9>>;
 	type EvidenceSize = ConstU32<16_384>;
+	// All changes of rank go through `RankedCollective`, which reports them to `Salary` itself.
+	type OnRankChanged = ();
+	type Reviews = FellowshipReviews;
+	type ReviewPeriod = FellowshipReviewPeriod;
 }
 
 parameter_types! {
@@ -2137,6 +2181,7 @@ type Migrations = (
 	pallet_nomination_pools::migration::v2::MigrateToV2<Runtime>,
 	pallet_alliance::migration::Migration<Runtime>,
 	pallet_contracts::Migration<Runtime>,
+	pallet_conviction_voting::migration::v1::MigrateV0ToV1<Runtime>,
 );
 
 type EventRecord = frame_system::EventRecord<
@@ -2348,6 +2393,40 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_referenda_runtime_api::ReferendaApi<Block, BlockNumber> for Runtime {
+		fn projected_outcome(
+			index: pallet_referenda::ReferendumIndex,
+		) -> Option<pallet_referenda::ProjectedOutcome<BlockNumber>> {
+			Referenda::projected_outcome(index).ok()
+		}
+	}
+
+	impl pallet_conviction_voting_runtime_api::ConvictionVotingApi<Block, AccountId, u16, Balance>
+		for Runtime
+	{
+		fn voting_power(
+			who: AccountId,
+			class: u16,
+			conviction: pallet_conviction_voting::Conviction,
+		) -> pallet_conviction_voting::Delegations<Balance> {
+			ConvictionVoting::voting_power(&who, &class, conviction)
+		}
+
+		fn delegated_power(
+			who: AccountId,
+			class: u16,
+		) -> pallet_conviction_voting::Delegations<Balance> {
+			ConvictionVoting::delegated_power(&who, &class)
+		}
+
+		fn delegators(
+			who: AccountId,
+			class: u16,
+		) -> Vec<(AccountId, pallet_conviction_voting::Delegations<Balance>)> {
+			ConvictionVoting::delegators(&who, &class)
+		}
+	}
+
 	impl sp_consensus_babe::BabeApi<Block> for Runtime {
 		fn configuration() -> sp_consensus_babe::BabeConfiguration {
 			let epoch_config = Babe::epoch_config().unwrap_or(BABE_GENESIS_EPOCH_CONFIG);
@@ -21,142 +21,229 @@
 //! A chain-spec is short for `chain-configuration`. See the [`sc-chain-spec`] for more information.
 //!
 //! Note that this binary is analogous to the `build-spec` subcommand, contained in typical
-//! substrate-based nodes. This particular binary is capable of building a more sophisticated chain
-//! specification that can be used with the substrate-node, ie. [`node-cli`].
+//! substrate-based nodes. Unlike `build-spec`, it doesn't depend on any native runtime code: the
+//! genesis config is built by calling the [`GenesisBuilder`] API of the given runtime wasm blob.
+//! The default genesis config of the runtime can be used as is, patched with some JSON, replaced
+//! by a full JSON config or patched with one of the presets provided by the runtime.
 //!
 //! See [`ChainSpecBuilder`] for a list of available commands.
 //!
 //! [`sc-chain-spec`]: ../sc_chain_spec/index.html
-//! [`node-cli`]: ../node_cli/index.html
-
-use std::path::{Path, PathBuf};
-
-use ansi_term::Style;
-use clap::Parser;
+//! [`GenesisBuilder`]: ../sp_genesis_builder/trait.GenesisBuilder.html
+
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+use clap::{Parser, Subcommand};
+use sc_chain_spec::{
+	resolve_state_version_from_wasm, ChainType, GenericChainSpec,
+	GenesisBuildAction as ChainSpecBuildAction, GenesisConfigBuilderRuntimeCaller,
+};
+use sc_executor::WasmExecutor;
+use serde_json::Value;
+use sp_core::H256;
+use sp_runtime::{traits::BlakeTwo256, BuildStorage};
+use sp_state_machine::{Backend, InMemoryBackend};
+
+/// A utility to create chain specs for any runtime out of its wasm blob, and to verify them.
+#[derive(Parser)]
+#[command(rename_all = "kebab-case")]
+pub struct ChainSpecBuilder {
+	#[command(subcommand)]
+	pub command: ChainSpecBuilderCmd,
+	/// The path where the chain spec should be saved.
+	#[arg(long, short, default_value = "./chain_spec.json")]
+	pub chain_spec_path: PathBuf,
+}
 
-use node_cli::chain_spec::{self, AccountId};
-use sc_keystore::LocalKeystore;
-use sp_core::crypto::{ByteArray, Ss58Codec};
-use sp_keystore::KeystorePtr;
+/// The commands of the [`ChainSpecBuilder`].
+#[derive(Subcommand)]
+#[command(rename_all = "kebab-case")]
+pub enum ChainSpecBuilderCmd {
+	/// Create a new chain spec from the given runtime wasm blob.
+	Create(CreateCmd),
+	/// Verify the genesis state root of a raw chain spec.
+	Verify(VerifyCmd),
+	/// List the genesis presets provided by the given runtime wasm blob.
+	ListPresets(ListPresetsCmd),
+	/// Removed, use `create` with a genesis config patch instead.
+	#[command(hide = true)]
+	New(RemovedCmd),
+	/// Removed, use `create` with a genesis config patch instead.
+	#[command(hide = true)]
+	Generate(RemovedCmd),
+}
 
-/// A utility to easily create a testnet chain spec definition with a given set
-/// of authorities and endowed accounts and/or generate random accounts.
+/// Create a new chain spec by calling the `GenesisBuilder` API of the runtime.
 #[derive(Parser)]
+pub struct CreateCmd {
+	/// The name of the chain.
+	#[arg(long, short = 'n', default_value = "Custom")]
+	pub chain_name: String,
+	/// The id of the chain.
+	#[arg(long, short = 'i', default_value = "custom")]
+	pub chain_id: String,
+	/// The type of the chain: `development`, `local`, `live` or any custom type.
+	#[arg(long, short = 't', default_value = "live", value_parser = parse_chain_type)]
+	pub chain_type: ChainType,
+	/// The path to the runtime wasm blob.
+	#[arg(long, short)]
+	pub runtime_wasm_path: PathBuf,
+	/// Export the chain spec with the raw genesis storage instead of the genesis config.
+	#[arg(long, short = 's')]
+	pub raw_storage: bool,
+	#[command(subcommand)]
+	pub action: GenesisBuildAction,
+}
+
+/// How the genesis config of the chain spec is built.
+#[derive(Subcommand)]
 #[command(rename_all = "kebab-case")]
-pub enum ChainSpecBuilder {
-	/// Create a new chain spec with the given authorities, endowed and sudo
-	/// accounts.
-	New {
-		/// Authority key seed.
-		#[arg(long, short, required = true)]
-		authority_seeds: Vec<String>,
-		/// Active nominators (SS58 format), each backing a random subset of the aforementioned
-		/// authorities.
-		#[arg(long, short, default_value = "0")]
-		nominator_accounts: Vec<String>,
-		/// Endowed account address (SS58 format).
-		#[arg(long, short)]
-		endowed_accounts: Vec<String>,
-		/// Sudo account address (SS58 format).
-		#[arg(long, short)]
-		sudo_account: String,
-		/// The path where the chain spec should be saved.
-		#[arg(long, short, default_value = "./chain_spec.json")]
-		chain_spec_path: PathBuf,
+pub enum GenesisBuildAction {
+	/// Use the default genesis config of the runtime.
+	Default,
+	/// Patch the default genesis config of the runtime with the given JSON file.
+	///
+	/// The patch only needs to contain the fields that differ from the default values.
+	Patch {
+		/// The path to the JSON patch.
+		patch_path: PathBuf,
+	},
+	/// Use the given JSON file as the full genesis config.
+	Full {
+		/// The path to the full genesis config.
+		config_path: PathBuf,
 	},
-	/// Create a new chain spec with the given number of authorities and endowed
-	/// accounts. Random keys will be generated as required.
-	Generate {
-		/// The number of authorities.
-		#[arg(long, short)]
-		authorities: usize,
-		/// The number of nominators backing the aforementioned authorities.
-		///
-		/// Will nominate a random subset of `authorities`.
-		#[arg(long, short, default_value_t = 0)]
-		nominators: usize,
-		/// The number of endowed accounts.
-		#[arg(long, short, default_value_t = 0)]
-		endowed: usize,
-		/// The path where the chain spec should be saved.
-		#[arg(long, short, default_value = "./chain_spec.json")]
-		chain_spec_path: PathBuf,
-		/// Path to use when saving generated keystores for each authority.
-		///
-		/// At this path, a new folder will be created for each authority's
-		/// keystore named `auth-$i` where `i` is the authority index, i.e.
-		/// `auth-0`, `auth-1`, etc.
-		#[arg(long, short)]
-		keystore_path: Option<PathBuf>,
+	/// Patch the default genesis config with the named preset provided by the runtime.
+	NamedPreset {
+		/// The name of the preset.
+		preset_name: String,
 	},
 }
 
-impl ChainSpecBuilder {
-	/// Returns the path where the chain spec should be saved.
-	pub fn chain_spec_path(&self) -> &Path {
-		match self {
-			ChainSpecBuilder::New { chain_spec_path, .. } => chain_spec_path.as_path(),
-			ChainSpecBuilder::Generate { chain_spec_path, .. } => chain_spec_path.as_path(),
-		}
-	}
+/// Verify the genesis state root of a raw chain spec.
+#[derive(Parser)]
+pub struct VerifyCmd {
+	/// The path to the raw chain spec to verify.
+	#[arg(long, short)]
+	pub input_chain_spec: PathBuf,
+	/// The expected genesis state root, hex encoded.
+	#[arg(long)]
+	pub expected_state_root: H256,
+}
+
+/// List the genesis presets provided by the runtime.
+#[derive(Parser)]
+pub struct ListPresetsCmd {
+	/// The path to the runtime wasm blob.
+	#[arg(long, short)]
+	pub runtime_wasm_path: PathBuf,
+}
+
+/// Arguments of a command that was removed from the chain spec builder.
+#[derive(Parser)]
+pub struct RemovedCmd {
+	/// The arguments of the removed command, ignored.
+	#[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+	pub args: Vec<String>,
 }
 
-fn genesis_constructor(
-	authority_seeds: &[String],
-	nominator_accounts: &[AccountId],
-	endowed_accounts: &[AccountId],
-	sudo_account: &AccountId,
-) -> chain_spec::RuntimeGenesisConfig {
-	let authorities = authority_seeds
-		.iter()
-		.map(AsRef::as_ref)
-		.map(chain_spec::authority_keys_from_seed)
-		.collect::<Vec<_>>();
-
-	chain_spec::testnet_genesis(
-		authorities,
-		nominator_accounts.to_vec(),
-		sudo_account.clone(),
-		Some(endowed_accounts.to_vec()),
+/// Returns the error explaining how to replace the removed `command`.
+///
+/// The `new` and `generate` commands built the genesis config of `node-cli` from the given
+/// accounts. The chain spec builder no longer depends on any runtime, the same chain spec is
+/// created by patching the runtime genesis config.
+pub fn removed_command_error(command: &str) -> String {
+	format!(
+		"The `{command}` command was removed, the chain spec builder no longer depends on the \
+		node runtime. Use `chain-spec-builder create --runtime-wasm-path <PATH> patch <PATCH>` \
+		with a genesis config patch setting the authorities and accounts instead."
 	)
 }
 
-/// Generate the chain spec using the given seeds and accounts.
-pub fn generate_chain_spec(
-	authority_seeds: Vec<String>,
-	nominator_accounts: Vec<String>,
-	endowed_accounts: Vec<String>,
-	sudo_account: String,
-) -> Result<String, String> {
-	let parse_account = |address: String| {
-		AccountId::from_string(&address)
-			.map_err(|err| format!("Failed to parse account address: {:?}", err))
-	};
+fn parse_chain_type(chain_type: &str) -> Result<ChainType, String> {
+	Ok(match chain_type {
+		"development" => ChainType::Development,
+		"local" => ChainType::Local,
+		"live" => ChainType::Live,
+		custom => ChainType::Custom(custom.to_string()),
+	})
+}
+
+fn read_json(path: &Path) -> Result<Value, String> {
+	let content = fs::read(path)
+		.map_err(|e| format!("Failed to read JSON file `{}`: {}", path.display(), e))?;
+	serde_json::from_slice(&content)
+		.map_err(|e| format!("Failed to parse JSON file `{}`: {}", path.display(), e))
+}
 
-	let nominator_accounts = nominator_accounts
-		.into_iter()
-		.map(parse_account)
-		.collect::<Result<Vec<_>, String>>()?;
-
-	let endowed_accounts = endowed_accounts
-		.into_iter()
-		.map(parse_account)
-		.collect::<Result<Vec<_>, String>>()?;
-
-	let sudo_account = parse_account(sudo_account)?;
-
-	let chain_spec = chain_spec::ChainSpec::from_genesis(
-		"Custom",
-		"custom",
-		sc_chain_spec::ChainType::Live,
-		move || {
-			genesis_constructor(
-				&authority_seeds,
-				&nominator_accounts,
-				&endowed_accounts,
-				&sudo_account,
-			)
+fn read_runtime_code(path: &Path) -> Result<Vec<u8>, String> {
+	fs::read(path).map_err(|e| format!("Failed to read runtime wasm `{}`: {}", path.display(), e))
+}
+
+/// Check that all the keys of `patch` exist in the `default` genesis config.
+///
+/// Keys are only checked as long as the default config contains objects: the content of the
+/// fields which are e.g. `null` by default can't be checked.
+pub fn check_patch_keys(default: &Value, patch: &Value) -> Result<(), String> {
+	fn check(default: &Value, patch: &Value, path: &mut Vec<String>) -> Result<(), String> {
+		let (Value::Object(default), Value::Object(patch)) = (default, patch) else {
+			return Ok(())
+		};
+
+		for (key, value) in patch {
+			path.push(key.clone());
+			match default.get(key) {
+				Some(default) => check(default, value, path)?,
+				None =>
+					return Err(format!(
+						"Key `{}` doesn't exist in the default genesis config",
+						path.join(".")
+					)),
+			}
+			path.pop();
+		}
+
+		Ok(())
+	}
+
+	check(default, patch, &mut Vec::new())
+}
+
+/// Generate the chain spec of the runtime as described by `cmd`.
+///
+/// Returns the chain spec as JSON, with the raw genesis storage if `cmd.raw_storage` is set.
+pub fn generate_chain_spec_for_runtime(cmd: &CreateCmd) -> Result<String, String> {
+	let code = read_runtime_code(&cmd.runtime_wasm_path)?;
+	let caller = GenesisConfigBuilderRuntimeCaller::new(&code[..]);
+
+	let action = match &cmd.action {
+		GenesisBuildAction::Default => ChainSpecBuildAction::Full(caller.get_default_config()?),
+		GenesisBuildAction::Patch { patch_path } => {
+			let patch = read_json(patch_path)?;
+			check_patch_keys(&caller.get_default_config()?, &patch)?;
+			ChainSpecBuildAction::Patch(patch)
+		},
+		GenesisBuildAction::Full { config_path } => {
+			let config = read_json(config_path)?;
+			check_patch_keys(&caller.get_default_config()?, &config)?;
+			ChainSpecBuildAction::Full(config)
 		},
+		GenesisBuildAction::NamedPreset { preset_name } => {
+			let preset = caller.get_named_preset(Some(preset_name))?;
+			check_patch_keys(&caller.get_default_config()?, &preset)?;
+			ChainSpecBuildAction::NamedPreset(preset_name.clone())
+		},
+	};
+
+	let chain_spec = GenericChainSpec::<()>::from_runtime_code(
+		&cmd.chain_name,
+		&cmd.chain_id,
+		cmd.chain_type.clone(),
+		&code[..],
+		action,
 		vec![],
 		None,
 		None,
@@ -165,77 +252,173 @@ pub fn generate_chain_spec(
 		Default::default(),
 	);
 
-	chain_spec.as_json(false)
+	chain_spec.as_json(cmd.raw_storage)
 }
 
-/// Generate the authority keys and store them in the given `keystore_path`.
-pub fn generate_authority_keys_and_store(
-	seeds: &[String],
-	keystore_path: &Path,
-) -> Result<(), String> {
-	for (n, seed) in seeds.iter().enumerate() {
-		let keystore: KeystorePtr =
-			LocalKeystore::open(keystore_path.join(format!("auth-{}", n)), None)
-				.map_err(|err| err.to_string())?
-				.into();
-
-		let (_, _, grandpa, babe, im_online, authority_discovery) =
-			chain_spec::authority_keys_from_seed(seed);
-
-		let insert_key = |key_type, public| {
-			keystore
-				.insert(key_type, &format!("//{}", seed), public)
-				.map_err(|_| format!("Failed to insert key: {}", grandpa))
-		};
+/// Compute the genesis state root of the raw chain spec at `path`.
+///
+/// Fails if the chain spec isn't raw.
+pub fn genesis_state_root(path: &Path) -> Result<H256, String> {
+	let json = read_json(path)?;
+	if json.pointer("/genesis/raw").is_none() {
+		return Err(format!("`{}` is not a raw chain spec", path.display()))
+	}
 
-		insert_key(sp_core::crypto::key_types::BABE, babe.as_slice())?;
+	let chain_spec = GenericChainSpec::<()>::from_json_file(path.to_path_buf())?;
+	let storage = chain_spec.build_storage()?;
 
-		insert_key(sp_core::crypto::key_types::GRANDPA, grandpa.as_slice())?;
+	let executor = WasmExecutor::<sp_io::SubstrateHostFunctions>::builder()
+		.with_allow_missing_host_functions(true)
+		.build();
+	let state_version =
+		resolve_state_version_from_wasm(&storage, &executor).map_err(|e| e.to_string())?;
 
-		insert_key(sp_core::crypto::key_types::IM_ONLINE, im_online.as_slice())?;
+	let backend = InMemoryBackend::<BlakeTwo256>::from((storage, state_version));
+	Ok(backend.storage_root(std::iter::empty(), state_version).0)
+}
 
-		insert_key(
-			sp_core::crypto::key_types::AUTHORITY_DISCOVERY,
-			authority_discovery.as_slice(),
-		)?;
+/// Verify that the genesis state root of the raw chain spec at `cmd.input_chain_spec` matches
+/// `cmd.expected_state_root`.
+pub fn verify_chain_spec(cmd: &VerifyCmd) -> Result<(), String> {
+	let state_root = genesis_state_root(&cmd.input_chain_spec)?;
+	if state_root != cmd.expected_state_root {
+		return Err(format!(
+			"Genesis state root mismatch: expected {:?}, got {:?}",
+			cmd.expected_state_root, state_root
+		))
 	}
-
 	Ok(())
 }
 
-/// Print the given seeds
-pub fn print_seeds(
-	authority_seeds: &[String],
-	nominator_seeds: &[String],
-	endowed_seeds: &[String],
-	sudo_seed: &str,
-) {
-	let header = Style::new().bold().underline();
-	let entry = Style::new().bold();
+/// Returns the names of the genesis presets provided by the runtime.
+pub fn list_presets(cmd: &ListPresetsCmd) -> Result<Vec<String>, String> {
+	let code = read_runtime_code(&cmd.runtime_wasm_path)?;
+	GenesisConfigBuilderRuntimeCaller::new(&code[..]).preset_names()
+}
 
-	println!("{}", header.paint("Authority seeds"));
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
 
-	for (n, seed) in authority_seeds.iter().enumerate() {
-		println!("{} //{}", entry.paint(format!("auth-{}:", n)), seed);
+	fn write_runtime(dir: &Path) -> PathBuf {
+		let path = dir.join("runtime.wasm");
+		fs::write(&path, substrate_test_runtime::wasm_binary_unwrap()).unwrap();
+		path
 	}
 
-	println!("{}", header.paint("Nominator seeds"));
+	fn create_cmd(runtime_wasm_path: PathBuf, action: GenesisBuildAction) -> CreateCmd {
+		CreateCmd {
+			chain_name: "Test".into(),
+			chain_id: "test".into(),
+			chain_type: ChainType::Local,
+			runtime_wasm_path,
+			raw_storage: true,
+			action,
+		}
+	}
 
-	for (n, seed) in nominator_seeds.iter().enumerate() {
-		println!("{} //{}", entry.paint(format!("nom-{}:", n)), seed);
+	#[test]
+	fn check_patch_keys_accepts_existing_keys() {
+		let default = json!({ "balances": { "balances": [] }, "sudo": { "key": null } });
+
+		assert_eq!(check_patch_keys(&default, &json!({})), Ok(()));
+		assert_eq!(
+			check_patch_keys(&default, &json!({ "balances": { "balances": [[1, 2]] } })),
+			Ok(())
+		);
+		// The content of fields that are `null` by default can't be checked.
+		assert_eq!(check_patch_keys(&default, &json!({ "sudo": { "key": { "any": 1 } } })), Ok(()));
 	}
 
-	println!();
+	#[test]
+	fn check_patch_keys_rejects_unknown_keys() {
+		let default = json!({ "balances": { "balances": [] } });
+
+		assert_eq!(
+			check_patch_keys(&default, &json!({ "babe": {} })),
+			Err("Key `babe` doesn't exist in the default genesis config".into())
+		);
+		assert_eq!(
+			check_patch_keys(&default, &json!({ "balances": { "endowed": [] } })),
+			Err("Key `balances.endowed` doesn't exist in the default genesis config".into())
+		);
+	}
 
-	if !endowed_seeds.is_empty() {
-		println!("{}", header.paint("Endowed seeds"));
-		for (n, seed) in endowed_seeds.iter().enumerate() {
-			println!("{} //{}", entry.paint(format!("endowed-{}:", n)), seed);
-		}
+	#[test]
+	fn verify_chain_spec_checks_the_state_root() {
+		let dir = tempfile::tempdir().unwrap();
+		let runtime = write_runtime(dir.path());
+		let input_chain_spec = dir.path().join("raw.json");
+		let json =
+			generate_chain_spec_for_runtime(&create_cmd(runtime, GenesisBuildAction::Default))
+				.unwrap();
+		fs::write(&input_chain_spec, json).unwrap();
+
+		let state_root = genesis_state_root(&input_chain_spec).unwrap();
+		let cmd = VerifyCmd { input_chain_spec, expected_state_root: state_root };
+		assert_eq!(verify_chain_spec(&cmd), Ok(()));
+
+		let cmd = VerifyCmd { expected_state_root: H256::repeat_byte(1), ..cmd };
+		assert_eq!(
+			verify_chain_spec(&cmd),
+			Err(format!(
+				"Genesis state root mismatch: expected {:?}, got {:?}",
+				H256::repeat_byte(1),
+				state_root
+			))
+		);
+	}
+
+	#[test]
+	fn verify_chain_spec_rejects_plain_chain_specs() {
+		let dir = tempfile::tempdir().unwrap();
+		let runtime = write_runtime(dir.path());
+		let input_chain_spec = dir.path().join("plain.json");
+		let mut cmd = create_cmd(runtime, GenesisBuildAction::Default);
+		cmd.raw_storage = false;
+		fs::write(&input_chain_spec, generate_chain_spec_for_runtime(&cmd).unwrap()).unwrap();
+
+		let cmd = VerifyCmd { input_chain_spec, expected_state_root: H256::zero() };
+		assert!(verify_chain_spec(&cmd).unwrap_err().ends_with("is not a raw chain spec"));
+	}
+
+	#[test]
+	fn named_preset_keys_are_checked() {
+		let dir = tempfile::tempdir().unwrap();
+		let runtime = write_runtime(dir.path());
 
-		println!();
+		let preset = GenesisBuildAction::NamedPreset {
+			preset_name: sp_genesis_builder::DEV_RUNTIME_PRESET.into(),
+		};
+		assert!(generate_chain_spec_for_runtime(&create_cmd(runtime.clone(), preset)).is_ok());
+
+		let unknown = GenesisBuildAction::NamedPreset { preset_name: "unknown".into() };
+		assert!(generate_chain_spec_for_runtime(&create_cmd(runtime, unknown)).is_err());
 	}
 
-	println!("{}", header.paint("Sudo seed"));
-	println!("//{}", sudo_seed);
+	#[test]
+	fn parses_chain_type() {
+		let parse = |chain_type: &str| {
+			let args =
+				["chain-spec-builder", "create", "-t", chain_type, "-r", "a.wasm", "default"];
+			match ChainSpecBuilder::try_parse_from(args).unwrap().command {
+				ChainSpecBuilderCmd::Create(cmd) => cmd.chain_type,
+				_ => panic!("Unexpected command"),
+			}
+		};
+
+		assert_eq!(parse("development"), ChainType::Development);
+		assert_eq!(parse("live"), ChainType::Live);
+		assert_eq!(parse("staging"), ChainType::Custom("staging".into()));
+	}
+
+	#[test]
+	fn removed_commands_are_parsed() {
+		let args = ["chain-spec-builder", "generate", "--authorities", "2", "-e", "1"];
+		assert!(matches!(
+			ChainSpecBuilder::try_parse_from(args).unwrap().command,
+			ChainSpecBuilderCmd::Generate(_)
+		));
+	}
 }
@@ -0,0 +1,76 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Reference signer for nodes that are started with `--keystore-remote-signer`.
+//!
+//! The signer keeps the keys in a local keystore on its own disk and answers the requests of the
+//! node on a unix socket. Keys can be added with the node's `key insert` command, pointing it to
+//! the same socket.
+
+use clap::Parser;
+use std::path::PathBuf;
+
+/// Serve the keys of a keystore to a node over a unix socket.
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct RemoteSigner {
+	/// Path of the keystore directory.
+	#[arg(long, value_name = "PATH")]
+	keystore_path: PathBuf,
+
+	/// Path of the unix socket to listen on.
+	#[arg(long, value_name = "PATH")]
+	socket: PathBuf,
+
+	/// File that contains the password used by the keystore.
+	#[arg(long, value_name = "PATH")]
+	password_filename: Option<PathBuf>,
+}
+
+#[cfg(unix)]
+fn main() -> Result<(), String> {
+	use sc_keystore::{
+		remote::{bind, serve},
+		LocalKeystore,
+	};
+	use sp_core::crypto::SecretString;
+	use std::{fs, sync::Arc};
+
+	let args = RemoteSigner::parse();
+
+	let password = args
+		.password_filename
+		.map(|file| fs::read_to_string(file).map(SecretString::new))
+		.transpose()
+		.map_err(|e| format!("Failed to read the password file: {}", e))?;
+	let keystore = LocalKeystore::open(args.keystore_path, password)
+		.map_err(|e| format!("Failed to open the keystore: {}", e))?;
+
+	// The socket of a previous run is replaced.
+	let listener = bind(&args.socket)
+		.map_err(|e| format!("Failed to bind to {}: {}", args.socket.display(), e))?;
+
+	println!("Listening on {}", args.socket.display());
+	serve(listener, Arc::new(keystore)).map_err(|e| e.to_string())
+}
+
+#[cfg(not(unix))]
+fn main() -> Result<(), String> {
+	let _ = RemoteSigner::parse();
+	Err("The remote signer is only supported on unix platforms".into())
+}
@@ -0,0 +1,72 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+#![cfg(unix)]
+
+use assert_cmd::cargo::cargo_bin;
+use sc_keystore::RemoteKeystore;
+use sp_core::{crypto::KeyTypeId, sr25519, Pair};
+use sp_keystore::Keystore;
+use std::{
+	os::unix::fs::PermissionsExt,
+	process::Command,
+	thread,
+	time::{Duration, Instant},
+};
+use tempfile::tempdir;
+
+const KEY_TYPE: KeyTypeId = KeyTypeId(*b"test");
+
+#[test]
+fn remote_signer_works() {
+	let dir = tempdir().expect("could not create a temp dir");
+	let keystore_path = dir.path().join("keystore");
+	let socket = dir.path().join("signer.sock");
+	// A socket left behind by a previous run is replaced.
+	std::fs::write(&socket, b"").unwrap();
+
+	let mut signer = Command::new(cargo_bin("remote-signer"))
+		.arg("--keystore-path")
+		.arg(&keystore_path)
+		.arg("--socket")
+		.arg(&socket)
+		.spawn()
+		.unwrap();
+
+	let started = Instant::now();
+	let remote = loop {
+		match RemoteKeystore::open(&socket) {
+			Ok(remote) => break remote,
+			Err(_) if started.elapsed() < Duration::from_secs(30) =>
+				thread::sleep(Duration::from_millis(100)),
+			Err(e) => panic!("Signer did not start: {}", e),
+		}
+	};
+
+	let public = remote.sr25519_generate_new(KEY_TYPE, None).unwrap();
+	let signature = remote.sr25519_sign(KEY_TYPE, &public, b"message").unwrap().unwrap();
+	assert!(sr25519::Pair::verify(&signature, b"message", &public));
+
+	// The key is stored by the signer.
+	assert_eq!(std::fs::read_dir(&keystore_path).unwrap().count(), 1);
+	let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+	assert_eq!(mode & 0o777, 0o600);
+
+	signer.kill().unwrap();
+	signer.wait().unwrap();
+}
@@ -238,6 +238,15 @@ pub struct IoInfo {
 	pub state_writes_nodes: u64,
 }
 
+/// Offchain indexing statistics for client instance.
+#[derive(Default, Clone, Debug)]
+pub struct OffchainIndexingInfo {
+	/// Number of keys written by offchain indexing that are still stored.
+	pub keys: u64,
+	/// Total size in bytes of the values stored under these keys.
+	pub bytes: u64,
+}
+
 /// Usage statistics for running client instance.
 ///
 /// Returning backend determines the scope of these stats,
@@ -249,6 +258,8 @@ pub struct UsageInfo {
 	pub memory: MemoryInfo,
 	/// I/O statistics.
 	pub io: IoInfo,
+	/// Offchain indexing statistics.
+	pub offchain_indexing: OffchainIndexingInfo,
 }
 
 impl fmt::Display for UsageInfo {
@@ -490,6 +490,11 @@ where
 
 				debug!(target: LOG_TARGET, "Failed to put hash '{:?}' on Dht.", hash)
 			},
+			// Content providers are not used by the authority discovery.
+			DhtEvent::ProvidersFound(..) |
+			DhtEvent::ProvidersNotFound(_) |
+			DhtEvent::StartedProviding(_) |
+			DhtEvent::StartProvidingFailed(_) => {},
 		}
 	}
 
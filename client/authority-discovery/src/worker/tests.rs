@@ -179,6 +179,15 @@ impl NetworkDHTProvider for TestNetwork {
 			.unbounded_send(TestNetworkEvent::GetCalled(key.clone()))
 			.unwrap();
 	}
+	fn start_providing(&self, _: KademliaKey) {
+		unimplemented!();
+	}
+	fn stop_providing(&self, _: KademliaKey) {
+		unimplemented!();
+	}
+	fn get_providers(&self, _: &KademliaKey) {
+		unimplemented!();
+	}
 }
 
 impl NetworkStateInfo for TestNetwork {
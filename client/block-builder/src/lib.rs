@@ -38,6 +38,7 @@ use sp_runtime::{
 	traits::{Block as BlockT, Hash, HashingFor, Header as HeaderT, NumberFor, One},
 	Digest,
 };
+use sp_trie::proof_size_extension::ProofSizeExt;
 
 use sc_client_api::backend;
 pub use sp_block_builder::BlockBuilder as BlockBuilderApi;
@@ -151,6 +152,9 @@ where
 	/// While proof recording is enabled, all accessed trie nodes are saved.
 	/// These recorded trie nodes can be used by a third party to prove the
 	/// output of this block builder without having access to the full storage.
+	/// The runtime can read the size of the recorded proof through the `storage_proof_size` host
+	/// function. Runtimes that store this size, e.g. through `StorageWeightReclaim`, need their
+	/// blocks to be built with proof recording.
 	pub fn new(
 		api: &'a A,
 		parent_hash: Block::Hash,
@@ -173,6 +177,10 @@ where
 
 		if record_proof.yes() {
 			api.record_proof();
+			let recorder = api
+				.proof_recorder()
+				.expect("Proof recording is enabled in the line above; qed.");
+			api.register_extension(ProofSizeExt::new(recorder));
 		}
 
 		api.set_call_context(CallContext::Onchain);
@@ -294,7 +302,8 @@ mod tests {
 	use sp_core::Blake2Hasher;
 	use sp_state_machine::Backend;
 	use substrate_test_runtime_client::{
-		runtime::ExtrinsicBuilder, DefaultTestClientBuilderExt, TestClientBuilderExt,
+		runtime::{ExtrinsicBuilder, TestAPI},
+		DefaultTestClientBuilderExt, TestClientBuilderExt,
 	};
 
 	#[test]
@@ -387,4 +396,45 @@ mod tests {
 		assert!(proof_without_panic > proof_empty_block);
 		assert_eq!(proof_empty_block, proof_with_panic);
 	}
+
+	#[test]
+	fn storage_proof_size_is_reported_to_the_runtime() {
+		let builder = substrate_test_runtime_client::TestClientBuilder::new();
+		let backend = builder.backend();
+		let client = builder.build();
+
+		let mut block_builder = BlockBuilder::new(
+			&client,
+			client.info().best_hash,
+			client.info().best_number,
+			RecordProof::Yes,
+			Default::default(),
+			&*backend,
+		)
+		.unwrap();
+
+		block_builder.push(ExtrinsicBuilder::new_read(8).build()).unwrap();
+
+		let proof_size = block_builder.api.storage_proof_size(block_builder.parent_hash).unwrap();
+		let recorded_size =
+			block_builder.api.proof_recorder().unwrap().estimate_encoded_size() as u64;
+
+		assert!(proof_size > 0);
+		assert!(proof_size <= recorded_size);
+
+		let block_builder = BlockBuilder::new(
+			&client,
+			client.info().best_hash,
+			client.info().best_number,
+			RecordProof::No,
+			Default::default(),
+			&*backend,
+		)
+		.unwrap();
+
+		assert_eq!(
+			block_builder.api.storage_proof_size(block_builder.parent_hash).unwrap(),
+			sp_io::PROOF_RECORDING_DISABLED,
+		);
+	}
 }
@@ -19,23 +19,39 @@
 //! Substrate chain configurations.
 #![warn(missing_docs)]
 
-use crate::{extension::GetExtension, ChainType, Properties, RuntimeGenesis};
+use crate::{
+	extension::GetExtension, genesis_config_builder::GenesisConfigBuilderRuntimeCaller, ChainType,
+	Properties, RuntimeGenesis,
+};
 use sc_network::config::MultiaddrWithPeerId;
 use sc_telemetry::TelemetryEndpoints;
 use serde::{Deserialize, Serialize};
 use serde_json as json;
 use sp_core::{
-	storage::{ChildInfo, Storage, StorageChild, StorageData, StorageKey},
+	storage::{well_known_keys, ChildInfo, Storage, StorageChild, StorageData, StorageKey},
 	Bytes,
 };
 use sp_runtime::BuildStorage;
 use std::{borrow::Cow, collections::BTreeMap, fs::File, path::PathBuf, sync::Arc};
 
+/// The way the genesis `GenesisConfig` of a chain spec created from a runtime wasm blob is built.
+#[derive(Clone, Debug)]
+pub enum GenesisBuildAction {
+	/// Patch the default `GenesisConfig` of the runtime with the given JSON.
+	Patch(json::Value),
+	/// Use the given JSON as the full `GenesisConfig`.
+	Full(json::Value),
+	/// Patch the default `GenesisConfig` with the named preset provided by the runtime.
+	NamedPreset(String),
+}
+
 enum GenesisSource<G> {
 	File(PathBuf),
 	Binary(Cow<'static, [u8]>),
 	Factory(Arc<dyn Fn() -> G + Send + Sync>),
 	Storage(Storage),
+	/// The genesis is built by the `GenesisBuilder` API of the given runtime code.
+	GenesisBuilderApi(GenesisBuildAction, Vec<u8>),
 }
 
 impl<G> Clone for GenesisSource<G> {
@@ -45,6 +61,8 @@ impl<G> Clone for GenesisSource<G> {
 			Self::Binary(ref d) => Self::Binary(d.clone()),
 			Self::Factory(ref f) => Self::Factory(f.clone()),
 			Self::Storage(ref s) => Self::Storage(s.clone()),
+			Self::GenesisBuilderApi(ref action, ref code) =>
+				Self::GenesisBuilderApi(action.clone(), code.clone()),
 		}
 	}
 }
@@ -104,6 +122,20 @@ impl<G: RuntimeGenesis> GenesisSource<G> {
 
 				Ok(Genesis::Raw(RawGenesis { top, children_default }))
 			},
+			Self::GenesisBuilderApi(action, code) => {
+				let json_blob = match action {
+					GenesisBuildAction::Patch(patch) =>
+						RuntimeGenesisConfigJson::Patch(patch.clone()),
+					GenesisBuildAction::Full(config) =>
+						RuntimeGenesisConfigJson::Config(config.clone()),
+					GenesisBuildAction::NamedPreset(name) => RuntimeGenesisConfigJson::Patch(
+						GenesisConfigBuilderRuntimeCaller::new(&code[..])
+							.get_named_preset(Some(name.as_str()))?,
+					),
+				};
+
+				Ok(Genesis::RuntimeGenesis(RuntimeGenesisInner { code: code.clone(), json_blob }))
+			},
 		}
 	}
 }
@@ -112,6 +144,22 @@ impl<G: RuntimeGenesis, E> BuildStorage for ChainSpec<G, E> {
 	fn assimilate_storage(&self, storage: &mut Storage) -> Result<(), String> {
 		match self.genesis.resolve()? {
 			Genesis::Runtime(gc) => gc.assimilate_storage(storage),
+			Genesis::RuntimeGenesis(inner) => {
+				let genesis_storage = inner.build_storage()?;
+				storage.top.extend(genesis_storage.top);
+				for (key, child) in genesis_storage.children_default {
+					storage
+						.children_default
+						.entry(key)
+						.or_insert_with(|| StorageChild {
+							data: Default::default(),
+							child_info: child.child_info.clone(),
+						})
+						.data
+						.extend(child.data);
+				}
+				Ok(())
+			},
 			Genesis::Raw(RawGenesis { top: map, children_default: children_map }) => {
 				storage.top.extend(map.into_iter().map(|(k, v)| (k.0, v.0)));
 				children_map.into_iter().for_each(|(k, v)| {
@@ -144,6 +192,61 @@ pub struct RawGenesis {
 	pub children_default: BTreeMap<StorageKey, GenesisStorage>,
 }
 
+impl From<Storage> for RawGenesis {
+	fn from(storage: Storage) -> Self {
+		let top = storage.top.into_iter().map(|(k, v)| (StorageKey(k), StorageData(v))).collect();
+		let children_default = storage
+			.children_default
+			.into_iter()
+			.map(|(sk, child)| {
+				(
+					StorageKey(sk),
+					child.data.into_iter().map(|(k, v)| (StorageKey(k), StorageData(v))).collect(),
+				)
+			})
+			.collect();
+
+		RawGenesis { top, children_default }
+	}
+}
+
+/// The `GenesisConfig` of a [`RuntimeGenesisInner`], given in full or as a patch of the default
+/// one.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+enum RuntimeGenesisConfigJson {
+	/// The full `GenesisConfig`, passed as is to the `GenesisBuilder::build_config` runtime API.
+	Config(json::Value),
+	/// A patch of the default `GenesisConfig` returned by the
+	/// `GenesisBuilder::create_default_config` runtime API.
+	Patch(json::Value),
+}
+
+/// Genesis built by the `GenesisBuilder` API of the runtime code it contains.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RuntimeGenesisInner {
+	/// The runtime wasm code, hex encoded.
+	#[serde(with = "sp_core::bytes")]
+	code: Vec<u8>,
+	/// The `GenesisConfig` to build.
+	#[serde(flatten)]
+	json_blob: RuntimeGenesisConfigJson,
+}
+
+impl RuntimeGenesisInner {
+	/// Build the genesis storage by calling the `GenesisBuilder` API of the runtime.
+	fn build_storage(self) -> Result<Storage, String> {
+		let caller = GenesisConfigBuilderRuntimeCaller::new(&self.code[..]);
+		let mut storage = match self.json_blob {
+			RuntimeGenesisConfigJson::Config(config) => caller.get_storage_for_config(config)?,
+			RuntimeGenesisConfigJson::Patch(patch) => caller.get_storage_for_patch(patch)?,
+		};
+		storage.top.insert(well_known_keys::CODE.to_vec(), self.code);
+		Ok(storage)
+	}
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
@@ -152,6 +255,8 @@ enum Genesis<G> {
 	Raw(RawGenesis),
 	/// State root hash of the genesis storage.
 	StateRootHash(StorageData),
+	/// `GenesisConfig` built by the `GenesisBuilder` API of the runtime code.
+	RuntimeGenesis(RuntimeGenesisInner),
 }
 
 /// A configuration of a client. Does not include runtime storage initialization.
@@ -290,6 +395,45 @@ impl<G, E> ChainSpec<G, E> {
 		ChainSpec { client_spec, genesis: GenesisSource::Factory(Arc::new(constructor)) }
 	}
 
+	/// Create a spec whose genesis is built by the `GenesisBuilder` API of the given runtime
+	/// code, without any native runtime code.
+	///
+	/// The genesis is resolved by calling into `code`, so a chain spec can be created from a
+	/// runtime blob and one of the presets it provides, see [`GenesisBuildAction::NamedPreset`].
+	pub fn from_runtime_code(
+		name: &str,
+		id: &str,
+		chain_type: ChainType,
+		code: &[u8],
+		build_action: GenesisBuildAction,
+		boot_nodes: Vec<MultiaddrWithPeerId>,
+		telemetry_endpoints: Option<TelemetryEndpoints>,
+		protocol_id: Option<&str>,
+		fork_id: Option<&str>,
+		properties: Option<Properties>,
+		extensions: E,
+	) -> Self {
+		let client_spec = ClientSpec {
+			name: name.to_owned(),
+			id: id.to_owned(),
+			chain_type,
+			boot_nodes,
+			telemetry_endpoints,
+			protocol_id: protocol_id.map(str::to_owned),
+			fork_id: fork_id.map(str::to_owned),
+			properties,
+			extensions,
+			consensus_engine: (),
+			genesis: Default::default(),
+			code_substitutes: BTreeMap::new(),
+		};
+
+		ChainSpec {
+			client_spec,
+			genesis: GenesisSource::GenesisBuilderApi(build_action, code.to_vec()),
+		}
+	}
+
 	/// Type of the chain.
 	fn chain_type(&self) -> ChainType {
 		self.client_spec.chain_type.clone()
@@ -335,27 +479,9 @@ struct JsonContainer<G, E> {
 impl<G: RuntimeGenesis, E: serde::Serialize + Clone + 'static> ChainSpec<G, E> {
 	fn json_container(&self, raw: bool) -> Result<JsonContainer<G, E>, String> {
 		let genesis = match (raw, self.genesis.resolve()?) {
-			(true, Genesis::Runtime(g)) => {
-				let storage = g.build_storage()?;
-				let top =
-					storage.top.into_iter().map(|(k, v)| (StorageKey(k), StorageData(v))).collect();
-				let children_default = storage
-					.children_default
-					.into_iter()
-					.map(|(sk, child)| {
-						(
-							StorageKey(sk),
-							child
-								.data
-								.into_iter()
-								.map(|(k, v)| (StorageKey(k), StorageData(v)))
-								.collect(),
-						)
-					})
-					.collect();
-
-				Genesis::Raw(RawGenesis { top, children_default })
-			},
+			(true, Genesis::Runtime(g)) => Genesis::Raw(RawGenesis::from(g.build_storage()?)),
+			(true, Genesis::RuntimeGenesis(inner)) =>
+				Genesis::Raw(RawGenesis::from(inner.build_storage()?)),
 			(_, genesis) => genesis,
 		};
 		Ok(JsonContainer { client_spec: self.client_spec.clone(), genesis })
@@ -536,4 +662,60 @@ mod tests {
 			);
 		}
 	}
+
+	fn runtime_code_spec(build_action: GenesisBuildAction) -> ChainSpec<()> {
+		ChainSpec::from_runtime_code(
+			"TestName",
+			"test",
+			ChainType::Local,
+			substrate_test_runtime::wasm_binary_unwrap(),
+			build_action,
+			Vec::new(),
+			None,
+			None,
+			None,
+			None,
+			None,
+		)
+	}
+
+	#[test]
+	fn chain_spec_from_named_preset_works() {
+		let spec = runtime_code_spec(GenesisBuildAction::NamedPreset(
+			sp_genesis_builder::DEV_RUNTIME_PRESET.to_string(),
+		));
+
+		let json: json::Value = json::from_str(&spec.as_json(false).unwrap()).unwrap();
+		let genesis = &json["genesis"]["runtimeGenesis"];
+		assert!(genesis["code"].is_string());
+		assert!(genesis["patch"]["substrateTest"]["authorities"].is_array());
+
+		let storage = spec.build_storage().unwrap();
+		assert_eq!(
+			storage.top.get(well_known_keys::CODE).map(|code| &code[..]),
+			Some(substrate_test_runtime::wasm_binary_unwrap()),
+		);
+
+		// The plain spec is parsed back into the same storage.
+		let spec = TestSpec::from_json_bytes(spec.as_json(false).unwrap().into_bytes()).unwrap();
+		assert_eq!(spec.build_storage().unwrap().top, storage.top);
+	}
+
+	#[test]
+	fn chain_spec_from_unknown_preset_fails() {
+		let spec = runtime_code_spec(GenesisBuildAction::NamedPreset("unknown".to_string()));
+		assert!(spec.build_storage().is_err());
+	}
+
+	#[test]
+	fn chain_spec_from_patch_matches_raw_spec() {
+		let spec = runtime_code_spec(GenesisBuildAction::Patch(json::json!({
+			"substrateTest": {
+				"authorities": [],
+			},
+		})));
+
+		let raw = TestSpec::from_json_bytes(spec.as_json(true).unwrap().into_bytes()).unwrap();
+		assert_eq!(raw.build_storage().unwrap().top, spec.build_storage().unwrap().top);
+	}
 }
@@ -0,0 +1,158 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! A helper module for calling the [`GenesisBuilder`] API of a runtime wasm blob.
+//!
+//! [`GenesisBuilder`]: sp_genesis_builder::GenesisBuilder
+
+use codec::{Decode, Encode};
+use sc_executor::{error::Result, WasmExecutor};
+use serde_json::{from_slice, Value};
+use sp_core::{
+	storage::Storage,
+	traits::{CallContext, CodeExecutor, Externalities, FetchRuntimeCode, RuntimeCode},
+};
+use sp_genesis_builder::{PresetId, Result as BuildResult};
+use sp_state_machine::BasicExternalities;
+use std::borrow::Cow;
+
+/// A utility that facilitates calling the [`GenesisBuilder`] API of the runtime wasm blob.
+///
+/// Only the host functions of [`sp_io::SubstrateHostFunctions`] are provided. Other host
+/// functions imported by the runtime are allowed to be missing, as long as they are not called
+/// while building the genesis state.
+///
+/// [`GenesisBuilder`]: sp_genesis_builder::GenesisBuilder
+pub struct GenesisConfigBuilderRuntimeCaller<'a> {
+	code: Cow<'a, [u8]>,
+	code_hash: Vec<u8>,
+	executor: WasmExecutor<sp_io::SubstrateHostFunctions>,
+}
+
+impl<'a> FetchRuntimeCode for GenesisConfigBuilderRuntimeCaller<'a> {
+	fn fetch_runtime_code(&self) -> Option<Cow<[u8]>> {
+		Some(self.code.as_ref().into())
+	}
+}
+
+impl<'a> GenesisConfigBuilderRuntimeCaller<'a> {
+	/// Creates new instance using the provided code blob.
+	///
+	/// This code is later referred to as `runtime`.
+	pub fn new(code: &'a [u8]) -> Self {
+		GenesisConfigBuilderRuntimeCaller {
+			code: code.into(),
+			code_hash: sp_core::blake2_256(code).to_vec(),
+			executor: WasmExecutor::<sp_io::SubstrateHostFunctions>::builder()
+				.with_allow_missing_host_functions(true)
+				.build(),
+		}
+	}
+
+	fn call(&self, ext: &mut dyn Externalities, method: &str, data: &[u8]) -> Result<Vec<u8>> {
+		self.executor
+			.call(
+				ext,
+				&RuntimeCode { heap_pages: None, code_fetcher: self, hash: self.code_hash.clone() },
+				method,
+				data,
+				false,
+				CallContext::Offchain,
+			)
+			.0
+	}
+
+	/// Returns the default `GenesisConfig` provided by the `runtime`.
+	///
+	/// Calls [`GenesisBuilder::create_default_config`](sp_genesis_builder::GenesisBuilder::create_default_config)
+	/// in the `runtime`.
+	pub fn get_default_config(&self) -> core::result::Result<Value, String> {
+		let mut t = BasicExternalities::new_empty();
+		let call_result = self
+			.call(&mut t, "GenesisBuilder_create_default_config", &[])
+			.map_err(|e| format!("wasm call error {e}"))?;
+		let default_config = Vec::<u8>::decode(&mut &call_result[..])
+			.map_err(|e| format!("scale codec error: {e}"))?;
+		from_slice(&default_config[..]).map_err(|e| format!("json from slice failed: {e}"))
+	}
+
+	/// Returns the JSON patch of the preset named `id`, or the default `GenesisConfig` if `id` is
+	/// `None`.
+	///
+	/// Calls [`GenesisBuilder::get_preset`](sp_genesis_builder::GenesisBuilder::get_preset) in
+	/// the `runtime`.
+	pub fn get_named_preset(&self, id: Option<&str>) -> core::result::Result<Value, String> {
+		let mut t = BasicExternalities::new_empty();
+		let call_result = self
+			.call(&mut t, "GenesisBuilder_get_preset", &id.map(PresetId::from).encode())
+			.map_err(|e| format!("wasm call error {e}"))?;
+		let preset = Option::<Vec<u8>>::decode(&mut &call_result[..])
+			.map_err(|e| format!("scale codec error: {e}"))?
+			.ok_or_else(|| format!("The preset with name {id:?} is not available."))?;
+		from_slice(&preset[..]).map_err(|e| format!("json from slice failed: {e}"))
+	}
+
+	/// Returns the names of the presets provided by the `runtime`.
+	///
+	/// Calls [`GenesisBuilder::preset_names`](sp_genesis_builder::GenesisBuilder::preset_names)
+	/// in the `runtime`.
+	pub fn preset_names(&self) -> core::result::Result<Vec<String>, String> {
+		let mut t = BasicExternalities::new_empty();
+		let call_result = self
+			.call(&mut t, "GenesisBuilder_preset_names", &[])
+			.map_err(|e| format!("wasm call error {e}"))?;
+		let names = Vec::<PresetId>::decode(&mut &call_result[..])
+			.map_err(|e| format!("scale codec error: {e}"))?;
+		names
+			.iter()
+			.map(|id| {
+				<&str>::try_from(id)
+					.map(String::from)
+					.map_err(|e| format!("invalid preset name: {e}"))
+			})
+			.collect()
+	}
+
+	/// Builds the genesis storage from the full `GenesisConfig` given as JSON.
+	///
+	/// Calls [`GenesisBuilder::build_config`](sp_genesis_builder::GenesisBuilder::build_config)
+	/// in the `runtime`.
+	pub fn get_storage_for_config(&self, config: Value) -> core::result::Result<Storage, String> {
+		let mut ext = BasicExternalities::new_empty();
+
+		let call_result = self
+			.call(&mut ext, "GenesisBuilder_build_config", &config.to_string().encode())
+			.map_err(|e| format!("wasm call error {e}"))?;
+
+		BuildResult::decode(&mut &call_result[..])
+			.map_err(|e| format!("scale codec error: {e}"))?
+			.map_err(|e| format!("{e}"))?;
+
+		Ok(ext.into_storages())
+	}
+
+	/// Builds the genesis storage from the default `GenesisConfig` patched with `patch`.
+	///
+	/// The patch is merged into the default `GenesisConfig` following RFC 7386, so it only needs
+	/// to contain the fields that differ from the default values.
+	pub fn get_storage_for_patch(&self, patch: Value) -> core::result::Result<Storage, String> {
+		let mut config = self.get_default_config()?;
+		json_patch::merge(&mut config, &patch);
+		self.get_storage_for_config(config)
+	}
+}
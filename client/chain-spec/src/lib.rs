@@ -157,7 +157,8 @@
 //!
 //! // The genesis declaration of the chain.
 //! //
-//! // `runtime`, `raw`, `stateRootHash` denote the type of the genesis declaration.
+//! // `runtime`, `raw`, `stateRootHash`, `runtimeGenesis` denote the type of the genesis
+//! // declaration.
 //! //
 //! // These declarations are in the following formats:
 //! // - `runtime` is a `json` object that can be parsed by a compatible `GenesisConfig`. This
@@ -166,6 +167,10 @@
 //! //   fields is a map of `key => value`. These key/value pairs represent the genesis storage.
 //! // - `stateRootHash` is a single hex encoded hash that represents the genesis hash. The hash
 //! //   type depends on the hash used by the chain.
+//! // - `runtimeGenesis` is a `json` object with the hex encoded runtime `code` and either a full
+//! //   `config` or a `patch` of the default `GenesisConfig` of the runtime. The genesis storage
+//! //   is built by calling the `GenesisBuilder` API of the runtime, no native runtime code is
+//! //   needed.
 //! //
 //! "genesis": { "runtime": {} },
 //!
@@ -190,14 +195,16 @@
 mod chain_spec;
 mod extension;
 mod genesis;
+mod genesis_config_builder;
 
 pub use self::{
-	chain_spec::{ChainSpec as GenericChainSpec, NoExtension},
+	chain_spec::{ChainSpec as GenericChainSpec, GenesisBuildAction, NoExtension},
 	extension::{get_extension, get_extension_mut, Extension, Fork, Forks, GetExtension, Group},
 	genesis::{
 		construct_genesis_block, resolve_state_version_from_wasm, BuildGenesisBlock,
 		GenesisBlockBuilder,
 	},
+	genesis_config_builder::GenesisConfigBuilderRuntimeCaller,
 };
 pub use sc_chain_spec_derive::{ChainSpecExtension, ChainSpecGroup};
 
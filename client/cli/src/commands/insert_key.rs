@@ -71,7 +71,15 @@ impl InsertKeyCmd {
 				let keystore: KeystorePtr = LocalKeystore::open(path, password)?.into();
 				(keystore, public)
 			},
-			_ => unreachable!("keystore_config always returns path and password; qed"),
+			#[cfg(unix)]
+			KeystoreConfig::Remote { socket } => {
+				let public = with_crypto_scheme!(self.scheme, to_vec(&suri, None))?;
+				let keystore: KeystorePtr = std::sync::Arc::new(
+					sc_keystore::RemoteKeystore::open(socket).map_err(sc_keystore::Error::from)?,
+				);
+				(keystore, public)
+			},
+			_ => unreachable!("keystore_config always returns a path or a remote signer; qed"),
 		};
 
 		let key_type =
@@ -24,7 +24,7 @@ use crate::{
 	params::{DatabaseParams, PruningParams},
 };
 use clap::Args;
-use std::path::PathBuf;
+use std::{num::NonZeroUsize, path::PathBuf};
 
 /// Parameters for block import.
 #[derive(Debug, Clone, Args)]
@@ -78,6 +78,14 @@ pub struct ImportParams {
 	#[arg(long, value_name = "Bytes", default_value_t = 67108864)]
 	pub trie_cache_size: usize,
 
+	/// Number of tasks verifying blocks in parallel.
+	/// Blocks are still imported one after the other, only their verification is spread over
+	/// the tasks. Whether consecutive blocks of a chain are verified concurrently depends on the
+	/// consensus engine. BABE only verifies blocks of different forks concurrently, so a major
+	/// sync isn't sped up by more tasks.
+	#[arg(long, value_name = "COUNT", default_value = "1")]
+	pub block_verification_workers: NonZeroUsize,
+
 	/// DEPRECATED
 	/// Switch to `--trie-cache-size`.
 	#[arg(long)]
@@ -110,6 +118,11 @@ impl ImportParams {
 	pub fn wasm_runtime_overrides(&self) -> Option<PathBuf> {
 		self.wasm_runtime_overrides.clone()
 	}
+
+	/// Number of tasks verifying blocks in parallel.
+	pub fn block_verification_workers(&self) -> NonZeroUsize {
+		self.block_verification_workers
+	}
 }
 
 /// Execution strategies parameters.
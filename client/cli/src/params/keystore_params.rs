@@ -39,6 +39,19 @@ pub struct KeystoreParams {
 	#[arg(long, value_name = "PATH")]
 	pub keystore_path: Option<PathBuf>,
 
+	/// Use an external signer listening on the given unix socket instead of a local keystore.
+	///
+	/// Signing requests are forwarded to the signer, the node never sees the private keys. VRF
+	/// outputs and ring VRF signatures can't be forwarded, so e.g. BABE is supported but not
+	/// Sassafras. The statement store is disabled, as it needs the private keys.
+	#[cfg(unix)]
+	#[arg(
+		long,
+		value_name = "PATH",
+		conflicts_with_all = &["keystore_path", "password_interactive", "password", "password_filename"]
+	)]
+	pub keystore_remote_signer: Option<PathBuf>,
+
 	/// Use interactive shell for entering the password used by the keystore.
 	#[arg(long, conflicts_with_all = &["password", "password_filename"])]
 	pub password_interactive: bool,
@@ -69,6 +82,11 @@ pub fn secret_string_from_str(s: &str) -> std::result::Result<SecretString, Stri
 impl KeystoreParams {
 	/// Get the keystore configuration for the parameters
 	pub fn keystore_config(&self, config_dir: &Path) -> Result<KeystoreConfig> {
+		#[cfg(unix)]
+		if let Some(socket) = self.keystore_remote_signer.clone() {
+			return Ok(KeystoreConfig::Remote { socket })
+		}
+
 		let password = if self.password_interactive {
 			Some(SecretString::new(input_keystore_password()?))
 		} else if let Some(ref file) = self.password_filename {
@@ -67,6 +67,13 @@ pub struct NetworkParams {
 	#[arg(long, value_name = "PORT", conflicts_with_all = &[ "listen_addr" ])]
 	pub port: Option<u16>,
 
+	/// Enable the QUIC transport.
+	///
+	/// Unless `--listen-addr` is passed, the node additionally listens on
+	/// `/ip4/0.0.0.0/udp/<port>/quic-v1` and `/ip6/[::]/udp/<port>/quic-v1`.
+	#[arg(long)]
+	pub enable_quic: bool,
+
 	/// Always forbid connecting to private IPv4/IPv6 addresses (as specified in
 	/// [RFC1918](https://tools.ietf.org/html/rfc1918)), unless the address was passed with
 	/// `--reserved-nodes` or `--bootnodes`. Enabled by default for chains marked as "live" in
@@ -167,7 +174,7 @@ impl NetworkParams {
 		let port = self.port.unwrap_or(default_listen_port);
 
 		let listen_addresses = if self.listen_addr.is_empty() {
-			if is_validator || is_dev {
+			let mut listen_addresses = if is_validator || is_dev {
 				vec![
 					Multiaddr::empty()
 						.with(Protocol::Ip6([0, 0, 0, 0, 0, 0, 0, 0].into()))
@@ -187,7 +194,20 @@ impl NetworkParams {
 						.with(Protocol::Tcp(port))
 						.with(Protocol::Ws(Cow::Borrowed("/"))),
 				]
+			};
+			if self.enable_quic {
+				listen_addresses.extend([
+					Multiaddr::empty()
+						.with(Protocol::Ip6([0, 0, 0, 0, 0, 0, 0, 0].into()))
+						.with(Protocol::Udp(port))
+						.with(Protocol::QuicV1),
+					Multiaddr::empty()
+						.with(Protocol::Ip4([0, 0, 0, 0].into()))
+						.with(Protocol::Udp(port))
+						.with(Protocol::QuicV1),
+				]);
 			}
+			listen_addresses
 		} else {
 			self.listen_addr.clone()
 		};
@@ -243,6 +263,7 @@ impl NetworkParams {
 			kademlia_replication_factor: self.kademlia_replication_factor,
 			yamux_window_size: None,
 			ipfs_server: self.ipfs_server,
+			enable_quic: self.enable_quic,
 			sync_mode: self.sync.into(),
 		}
 	}
@@ -27,7 +27,7 @@ use clap::{ArgAction, Args};
 use sc_network::config::Role;
 use sc_service::config::OffchainWorkerConfig;
 
-use crate::{error, OffchainWorkerEnabled};
+use crate::{error, DatabasePruningMode, OffchainWorkerEnabled};
 
 /// Offchain worker related parameters.
 #[derive(Debug, Clone, Args)]
@@ -47,6 +47,15 @@ pub struct OffchainWorkerParams {
 	/// Enables a runtime to write directly to a offchain workers DB during block import.
 	#[arg(long = "enable-offchain-indexing", value_name = "ENABLE_OFFCHAIN_INDEXING", default_value_t = false, action = ArgAction::Set)]
 	pub indexing_enabled: bool,
+
+	/// Specify the pruning mode of the data written by offchain indexing.
+	/// Possible values:
+	///  - 'archive' Keep all indexed data.
+	///  - 'archive-canonical' Keep only the data indexed by finalized blocks.
+	///  - number
+	///  Keep only the data indexed by the last `number` of finalized blocks.
+	#[arg(long, value_name = "PRUNING_MODE", default_value = "archive")]
+	pub offchain_indexing_pruning: DatabasePruningMode,
 }
 
 impl OffchainWorkerParams {
@@ -60,6 +69,7 @@ impl OffchainWorkerParams {
 		};
 
 		let indexing_enabled = self.indexing_enabled;
-		Ok(OffchainWorkerConfig { enabled, indexing_enabled })
+		let indexing_pruning = self.offchain_indexing_pruning.into();
+		Ok(OffchainWorkerConfig { enabled, indexing_enabled, indexing_pruning })
 	}
 }
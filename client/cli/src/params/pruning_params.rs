@@ -18,7 +18,7 @@
 
 use crate::error;
 use clap::Args;
-use sc_service::{BlocksPruning, PruningMode};
+use sc_service::{BlocksPruning, OffchainIndexingPruning, PruningMode};
 
 /// Parameters to define the pruning mode
 #[derive(Debug, Clone, Args)]
@@ -115,3 +115,13 @@ impl Into<BlocksPruning> for DatabasePruningMode {
 		}
 	}
 }
+
+impl Into<OffchainIndexingPruning> for DatabasePruningMode {
+	fn into(self) -> OffchainIndexingPruning {
+		match self {
+			DatabasePruningMode::Archive => OffchainIndexingPruning::KeepAll,
+			DatabasePruningMode::ArchiveCanonical => OffchainIndexingPruning::KeepCanonical,
+			DatabasePruningMode::Custom(n) => OffchainIndexingPruning::Some(n),
+		}
+	}
+}
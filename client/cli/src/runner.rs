@@ -265,6 +265,7 @@ mod tests {
 				)),
 				wasm_method: Default::default(),
 				wasm_runtime_overrides: None,
+				block_verification_workers: std::num::NonZeroUsize::MIN,
 				rpc_addr: None,
 				rpc_max_connections: Default::default(),
 				rpc_cors: None,
@@ -23,7 +23,7 @@ use crate::{
 	LOG_TARGET,
 };
 use codec::Codec;
-use log::{debug, info, trace};
+use log::{debug, info, trace, warn};
 use prometheus_endpoint::Registry;
 use sc_client_api::{backend::AuxStore, BlockOf, UsageProvider};
 use sc_consensus::{
@@ -32,10 +32,11 @@ use sc_consensus::{
 };
 use sc_consensus_slots::{check_equivocation, CheckedHeader, InherentDataProviderExt};
 use sc_telemetry::{telemetry, TelemetryHandle, CONSENSUS_DEBUG, CONSENSUS_TRACE};
+use sc_transaction_pool_api::OffchainTransactionPoolFactory;
 use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_block_builder::BlockBuilder as BlockBuilderApi;
 use sp_blockchain::HeaderBackend;
-use sp_consensus::Error as ConsensusError;
+use sp_consensus::{BlockOrigin, Error as ConsensusError};
 use sp_consensus_aura::{inherents::AuraInherentData, AuraApi};
 use sp_consensus_slots::Slot;
 use sp_core::crypto::Pair;
@@ -51,43 +52,21 @@ use std::{fmt::Debug, marker::PhantomData, sync::Arc};
 /// containing the seal.
 ///
 /// This digest item will always return `Some` when used with `as_aura_seal`.
-fn check_header<C, B: BlockT, P: Pair>(
-	client: &C,
+fn check_header<B: BlockT, P: Pair>(
 	slot_now: Slot,
 	header: B::Header,
 	hash: B::Hash,
 	authorities: &[AuthorityId<P>],
-	check_for_equivocation: CheckForEquivocation,
 ) -> Result<CheckedHeader<B::Header, (Slot, DigestItem)>, Error<B>>
 where
 	P::Public: Codec,
 	P::Signature: Codec,
-	C: sc_client_api::backend::AuxStore,
 {
 	let check_result =
 		crate::standalone::check_header_slot_and_seal::<B, P>(slot_now, header, authorities);
 
 	match check_result {
-		Ok((header, slot, seal)) => {
-			let expected_author = crate::standalone::slot_author::<P>(slot, &authorities);
-			let should_equiv_check = check_for_equivocation.check_for_equivocation();
-			if let (true, Some(expected)) = (should_equiv_check, expected_author) {
-				if let Some(equivocation_proof) =
-					check_equivocation(client, slot_now, slot, &header, expected)
-						.map_err(Error::Client)?
-				{
-					info!(
-						target: LOG_TARGET,
-						"Slot author is equivocating at slot {} with headers {:?} and {:?}",
-						slot,
-						equivocation_proof.first_header.hash(),
-						equivocation_proof.second_header.hash(),
-					);
-				}
-			}
-
-			Ok(CheckedHeader::Checked(header, (slot, seal)))
-		},
+		Ok((header, slot, seal)) => Ok(CheckedHeader::Checked(header, (slot, seal))),
 		Err(SealVerificationError::Deferred(header, slot)) =>
 			Ok(CheckedHeader::Deferred(header, slot)),
 		Err(SealVerificationError::Unsealed) => Err(Error::HeaderUnsealed(hash)),
@@ -99,39 +78,165 @@ where
 }
 
 /// A verifier for Aura blocks.
-pub struct AuraVerifier<C, P, CIDP, N> {
+pub struct AuraVerifier<C, P, CIDP, B: BlockT> {
 	client: Arc<C>,
 	create_inherent_data_providers: CIDP,
 	check_for_equivocation: CheckForEquivocation,
+	offchain_tx_pool_factory: Option<OffchainTransactionPoolFactory<B>>,
 	telemetry: Option<TelemetryHandle>,
-	compatibility_mode: CompatibilityMode<N>,
+	compatibility_mode: CompatibilityMode<NumberFor<B>>,
 	_phantom: PhantomData<fn() -> P>,
 }
 
-impl<C, P, CIDP, N> AuraVerifier<C, P, CIDP, N> {
+impl<C, P, CIDP, B: BlockT> AuraVerifier<C, P, CIDP, B> {
 	pub(crate) fn new(
 		client: Arc<C>,
 		create_inherent_data_providers: CIDP,
 		check_for_equivocation: CheckForEquivocation,
 		telemetry: Option<TelemetryHandle>,
-		compatibility_mode: CompatibilityMode<N>,
+		compatibility_mode: CompatibilityMode<NumberFor<B>>,
 	) -> Self {
 		Self {
 			client,
 			create_inherent_data_providers,
 			check_for_equivocation,
+			offchain_tx_pool_factory: None,
 			telemetry,
 			compatibility_mode,
 			_phantom: PhantomData,
 		}
 	}
+
+	/// Submit reports of the equivocations detected while verifying blocks through the offchain
+	/// transaction pool of `offchain_tx_pool_factory`.
+	///
+	/// Without it, equivocations are only logged.
+	pub fn with_offchain_tx_pool_factory(
+		mut self,
+		offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+	) -> Self {
+		self.offchain_tx_pool_factory = Some(offchain_tx_pool_factory);
+		self
+	}
+
+	/// Check whether the author of `header` has already authored another block in the same slot
+	/// and, if so, report the equivocation to the runtime.
+	///
+	/// Reports are only submitted if an offchain transaction pool was provided and the runtime
+	/// supports them (i.e. `AuraApi` version 2 or later).
+	fn check_and_report_equivocation(
+		&self,
+		slot_now: Slot,
+		slot: Slot,
+		header: &B::Header,
+		author: &AuthorityId<P>,
+		origin: &BlockOrigin,
+	) -> Result<(), Error<B>>
+	where
+		C: ProvideRuntimeApi<B> + HeaderBackend<B> + AuxStore,
+		C::Api: AuraApi<B, AuthorityId<P>>,
+		P: Pair,
+		P::Public: Codec + Debug,
+	{
+		if !self.check_for_equivocation.check_for_equivocation() {
+			return Ok(())
+		}
+
+		// don't report any equivocations during initial sync
+		// as they are most likely stale.
+		if *origin == BlockOrigin::NetworkInitialSync {
+			return Ok(())
+		}
+
+		// check if authorship of this header is an equivocation and return a proof if so.
+		let equivocation_proof =
+			match check_equivocation(&*self.client, slot_now, slot, header, author)
+				.map_err(Error::Client)?
+			{
+				Some(proof) => proof,
+				None => return Ok(()),
+			};
+
+		info!(
+			target: LOG_TARGET,
+			"Slot author {:?} is equivocating at slot {} with headers {:?} and {:?}",
+			author,
+			slot,
+			equivocation_proof.first_header.hash(),
+			equivocation_proof.second_header.hash(),
+		);
+
+		let Some(offchain_tx_pool_factory) = &self.offchain_tx_pool_factory else { return Ok(()) };
+
+		// get the best block on which we will build and send the equivocation report.
+		let best_hash = self.client.info().best_hash;
+
+		if !self
+			.client
+			.runtime_api()
+			.has_api_with::<dyn AuraApi<B, AuthorityId<P>>, _>(best_hash, |v| v >= 2)
+			.map_err(|e| Error::Client(e.into()))?
+		{
+			debug!(target: LOG_TARGET, "Runtime does not support equivocation reports.");
+			return Ok(())
+		}
+
+		// generate a key ownership proof. we start by trying to generate the
+		// key ownership proof at the parent of the equivocating header, this
+		// will make sure that proof generation is successful since it happens
+		// during the on-going session (i.e. session keys are available in the
+		// state to be able to generate the proof). this might fail if the
+		// equivocation happens on the first block of the session, in which case
+		// its parent would be on the previous session. if generation on the
+		// parent header fails we try with best block as well.
+		let generate_key_owner_proof = |at_hash: B::Hash| {
+			self.client
+				.runtime_api()
+				.generate_key_ownership_proof(at_hash, slot, equivocation_proof.offender.clone())
+				.map_err(|e| Error::Client(e.into()))
+		};
+
+		let parent_hash = *header.parent_hash();
+		let key_owner_proof = match generate_key_owner_proof(parent_hash)? {
+			Some(proof) => proof,
+			None => match generate_key_owner_proof(best_hash)? {
+				Some(proof) => proof,
+				None => {
+					debug!(
+						target: LOG_TARGET,
+						"Equivocation offender is not part of the authority set."
+					);
+					return Ok(())
+				},
+			},
+		};
+
+		// submit equivocation report at best block.
+		let mut runtime_api = self.client.runtime_api();
+
+		// Register the offchain tx pool to be able to use it from the runtime.
+		runtime_api
+			.register_extension(offchain_tx_pool_factory.offchain_transaction_pool(best_hash));
+
+		runtime_api
+			.submit_report_equivocation_unsigned_extrinsic(
+				best_hash,
+				equivocation_proof,
+				key_owner_proof,
+			)
+			.map_err(|e| Error::Client(e.into()))?;
+
+		info!(target: LOG_TARGET, "Submitted equivocation report for author {:?}", author);
+
+		Ok(())
+	}
 }
 
-impl<C, P, CIDP, N> AuraVerifier<C, P, CIDP, N>
+impl<C, P, CIDP, B: BlockT> AuraVerifier<C, P, CIDP, B>
 where
 	CIDP: Send,
 {
-	async fn check_inherents<B: BlockT>(
+	async fn check_inherents(
 		&self,
 		block: B,
 		at_hash: B::Hash,
@@ -163,9 +268,9 @@ where
 }
 
 #[async_trait::async_trait]
-impl<B: BlockT, C, P, CIDP> Verifier<B> for AuraVerifier<C, P, CIDP, NumberFor<B>>
+impl<B: BlockT, C, P, CIDP> Verifier<B> for AuraVerifier<C, P, CIDP, B>
 where
-	C: ProvideRuntimeApi<B> + Send + Sync + sc_client_api::backend::AuxStore,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + AuxStore,
 	C::Api: BlockBuilderApi<B> + AuraApi<B, AuthorityId<P>> + ApiExt<B>,
 	P: Pair,
 	P::Public: Codec + Debug,
@@ -215,17 +320,30 @@ where
 		// we add one to allow for some small drift.
 		// FIXME #1019 in the future, alter this queue to allow deferring of
 		// headers
-		let checked_header = check_header::<C, B, P>(
-			&self.client,
-			slot_now + 1,
-			block.header,
-			hash,
-			&authorities[..],
-			self.check_for_equivocation,
-		)
-		.map_err(|e| e.to_string())?;
+		let checked_header =
+			check_header::<B, P>(slot_now + 1, block.header.clone(), hash, &authorities[..])
+				.map_err(|e| e.to_string())?;
 		match checked_header {
 			CheckedHeader::Checked(pre_header, (slot, seal)) => {
+				// the header is valid but let's check if there was something else already
+				// proposed at the same slot by the given author. the sealed header is used, so
+				// that the proof can be checked by the runtime.
+				if let Some(author) = crate::standalone::slot_author::<P>(slot, &authorities) {
+					if let Err(err) = self.check_and_report_equivocation(
+						slot_now + 1,
+						slot,
+						&block.header,
+						author,
+						&block.origin,
+					) {
+						warn!(
+							target: LOG_TARGET,
+							"Error checking/reporting Aura equivocation: {}",
+							err
+						);
+					}
+				}
+
 				// if the body is passed through, we need to use the runtime
 				// to check that the internally-set timestamp in the inherents
 				// actually matches the slot set in the seal.
@@ -327,6 +445,10 @@ pub struct ImportQueueParams<'a, Block: BlockT, I, C, S, CIDP> {
 	pub registry: Option<&'a Registry>,
 	/// Should we check for equivocation?
 	pub check_for_equivocation: CheckForEquivocation,
+	/// The offchain transaction pool factory, used to submit reports of detected equivocations.
+	///
+	/// If `None`, equivocations are only logged.
+	pub offchain_tx_pool_factory: Option<OffchainTransactionPoolFactory<Block>>,
 	/// Telemetry instance used to report telemetry metrics.
 	pub telemetry: Option<TelemetryHandle>,
 	/// Compatibility mode that should be used.
@@ -345,6 +467,7 @@ pub fn import_queue<P, Block, I, C, S, CIDP>(
 		spawner,
 		registry,
 		check_for_equivocation,
+		offchain_tx_pool_factory,
 		telemetry,
 		compatibility_mode,
 	}: ImportQueueParams<Block, I, C, S, CIDP>,
@@ -368,13 +491,16 @@ where
 	CIDP: CreateInherentDataProviders<Block, ()> + Sync + Send + 'static,
 	CIDP::InherentDataProviders: InherentDataProviderExt + Send + Sync,
 {
-	let verifier = build_verifier::<P, _, _, _>(BuildVerifierParams {
+	let mut verifier = build_verifier::<P, _, _, _>(BuildVerifierParams {
 		client,
 		create_inherent_data_providers,
 		check_for_equivocation,
 		telemetry,
 		compatibility_mode,
 	});
+	if let Some(offchain_tx_pool_factory) = offchain_tx_pool_factory {
+		verifier = verifier.with_offchain_tx_pool_factory(offchain_tx_pool_factory);
+	}
 
 	Ok(BasicQueue::new(verifier, Box::new(block_import), justification_import, spawner, registry))
 }
@@ -396,15 +522,18 @@ pub struct BuildVerifierParams<C, CIDP, N> {
 }
 
 /// Build the [`AuraVerifier`]
-pub fn build_verifier<P, C, CIDP, N>(
+///
+/// Use [`AuraVerifier::with_offchain_tx_pool_factory`] on the result to submit reports of the
+/// detected equivocations.
+pub fn build_verifier<P, C, CIDP, B: BlockT>(
 	BuildVerifierParams {
 		client,
 		create_inherent_data_providers,
 		check_for_equivocation,
 		telemetry,
 		compatibility_mode,
-	}: BuildVerifierParams<C, CIDP, N>,
-) -> AuraVerifier<C, P, CIDP, N> {
+	}: BuildVerifierParams<C, CIDP, NumberFor<B>>,
+) -> AuraVerifier<C, P, CIDP, B> {
 	AuraVerifier::<_, P, _, _>::new(
 		client,
 		create_inherent_data_providers,
@@ -547,22 +547,27 @@ where
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use codec::Encode;
 	use parking_lot::Mutex;
 	use sc_block_builder::BlockBuilderProvider;
 	use sc_client_api::BlockchainEvents;
-	use sc_consensus::BoxJustificationImport;
+	use sc_consensus::{BoxJustificationImport, Verifier};
 	use sc_consensus_slots::{BackoffAuthoringOnFinalizedHeadLagging, SimpleSlotWorker};
 	use sc_keystore::LocalKeystore;
 	use sc_network_test::{Block as TestBlock, *};
+	use sc_transaction_pool_api::{LocalTransactionPool, OffchainTransactionPoolFactory};
 	use sp_application_crypto::{key_types::AURA, AppCrypto};
 	use sp_consensus::{DisableProofRecording, NoNetwork as DummyOracle, Proposal};
-	use sp_consensus_aura::sr25519::AuthorityPair;
+	use sp_consensus_aura::{
+		sr25519::{AuthorityPair, AuthoritySignature},
+		EquivocationProof,
+	};
 	use sp_inherents::InherentData;
 	use sp_keyring::sr25519::Keyring;
 	use sp_keystore::Keystore;
 	use sp_runtime::{
 		traits::{Block as BlockT, Header as _},
-		Digest,
+		Digest, DigestItem,
 	};
 	use sp_timestamp::Timestamp;
 	use std::{
@@ -570,7 +575,7 @@ mod tests {
 		time::{Duration, Instant},
 	};
 	use substrate_test_runtime_client::{
-		runtime::{Header, H256},
+		runtime::{substrate_test_pallet::pallet::Call as PalletCall, Extrinsic, Header, H256},
 		TestClient,
 	};
 
@@ -624,7 +629,7 @@ mod tests {
 				InherentDataProviders = (InherentDataProvider,),
 			>,
 		>,
-		u64,
+		TestBlock,
 	>;
 	type AuraPeer = Peer<(), PeersClient>;
 
@@ -873,4 +878,95 @@ mod tests {
 		// The returned block should be imported and we should be able to get its header by now.
 		assert!(client.header(res.block.hash()).unwrap().is_some());
 	}
+
+	/// A transaction pool which only records the transactions submitted to it.
+	#[derive(Default)]
+	struct RecordingTransactionPool(Mutex<Vec<<TestBlock as BlockT>::Extrinsic>>);
+
+	impl LocalTransactionPool for RecordingTransactionPool {
+		type Block = TestBlock;
+		type Hash = H256;
+		type Error = sc_transaction_pool_api::error::Error;
+
+		fn submit_local(
+			&self,
+			_: H256,
+			xt: <TestBlock as BlockT>::Extrinsic,
+		) -> Result<H256, Self::Error> {
+			self.0.lock().push(xt);
+			Ok(Default::default())
+		}
+	}
+
+	#[tokio::test]
+	async fn detects_and_reports_equivocations() {
+		let mut net = AuraTestNet::new(1);
+		let client = net.peer(0).client().as_client();
+		let genesis_hash = client.info().genesis_hash;
+
+		let pool = Arc::new(RecordingTransactionPool::default());
+		let mut verifier: AuraVerifier = import_queue::AuraVerifier::new(
+			client.clone(),
+			Box::new(|_, _| async {
+				let slot = InherentDataProvider::from_timestamp_and_slot_duration(
+					Timestamp::current(),
+					SlotDuration::from_millis(SLOT_DURATION_MS),
+				);
+				Ok((slot,))
+			}),
+			CheckForEquivocation::Yes,
+			None,
+			CompatibilityMode::None,
+		)
+		.with_offchain_tx_pool_factory(OffchainTransactionPoolFactory::new(pool.clone()));
+
+		let slot = *InherentDataProvider::from_timestamp_and_slot_duration(
+			Timestamp::current(),
+			SlotDuration::from_millis(SLOT_DURATION_MS),
+		);
+		let authorities = client.runtime_api().authorities(genesis_hash).unwrap();
+		let author = standalone::slot_author::<AuthorityPair>(slot, &authorities).unwrap();
+		let pair: AuthorityPair = Keyring::iter()
+			.find(|k| k.public() == *author.as_ref())
+			.expect("the slot author is a well-known account")
+			.pair()
+			.into();
+
+		// Two different blocks sealed by the slot author for the same slot.
+		let sealed_header = |salt: u8| {
+			let pre_digest =
+				<DigestItem as CompatibleDigestItem<AuthoritySignature>>::aura_pre_digest(slot);
+			let mut header = Header::new(
+				1,
+				Default::default(),
+				H256::repeat_byte(salt),
+				genesis_hash,
+				Digest { logs: vec![pre_digest] },
+			);
+			let signature = pair.sign(header.hash().as_ref());
+			header.digest_mut().push(DigestItem::aura_seal(signature));
+			header
+		};
+		let first_header = sealed_header(1);
+		let second_header = sealed_header(2);
+
+		let params = BlockImportParams::new(BlockOrigin::NetworkBroadcast, first_header.clone());
+		verifier.verify(params).await.unwrap();
+		assert!(pool.0.lock().is_empty());
+
+		let params = BlockImportParams::new(BlockOrigin::NetworkBroadcast, second_header.clone());
+		verifier.verify(params).await.unwrap();
+
+		let proof =
+			EquivocationProof { offender: author.clone(), slot, first_header, second_header };
+		assert!(sp_consensus_aura::check_equivocation_proof(proof.clone(), &authorities));
+		let report = Extrinsic::new_unsigned(
+			PalletCall::storage_change {
+				key: b"aura_equivocation".encode(),
+				value: Some(proof.encode()),
+			}
+			.into(),
+		);
+		assert_eq!(*pool.0.lock(), vec![report]);
+	}
 }
@@ -69,6 +69,7 @@
 use std::{
 	collections::HashSet,
 	future::Future,
+	num::NonZeroUsize,
 	pin::Pin,
 	sync::Arc,
 	task::{Context, Poll},
@@ -983,11 +984,31 @@ impl<Block: BlockT> BabeLink<Block> {
 pub struct BabeVerifier<Block: BlockT, Client, SelectChain, CIDP> {
 	client: Arc<Client>,
 	select_chain: SelectChain,
-	create_inherent_data_providers: CIDP,
+	create_inherent_data_providers: Arc<CIDP>,
 	config: BabeConfiguration,
 	epoch_changes: SharedEpochChanges<Block, Epoch>,
 	telemetry: Option<TelemetryHandle>,
 	offchain_tx_pool_factory: OffchainTransactionPoolFactory<Block>,
+	/// Serializes the equivocation checks of all clones of this verifier, which may verify
+	/// blocks at the same time and would otherwise race on the slot header map.
+	equivocation_lock: Arc<Mutex<()>>,
+}
+
+impl<Block: BlockT, Client, SelectChain: Clone, CIDP> Clone
+	for BabeVerifier<Block, Client, SelectChain, CIDP>
+{
+	fn clone(&self) -> Self {
+		Self {
+			client: self.client.clone(),
+			select_chain: self.select_chain.clone(),
+			create_inherent_data_providers: self.create_inherent_data_providers.clone(),
+			config: self.config.clone(),
+			epoch_changes: self.epoch_changes.clone(),
+			telemetry: self.telemetry.clone(),
+			offchain_tx_pool_factory: self.offchain_tx_pool_factory.clone(),
+			equivocation_lock: self.equivocation_lock.clone(),
+		}
+	}
 }
 
 impl<Block, Client, SelectChain, CIDP> BabeVerifier<Block, Client, SelectChain, CIDP>
@@ -1038,13 +1059,12 @@ where
 		}
 
 		// check if authorship of this header is an equivocation and return a proof if so.
-		let equivocation_proof =
-			match check_equivocation(&*self.client, slot_now, slot, header, author)
+		let equivocation_proof = {
+			let _lock = self.equivocation_lock.lock();
+			check_equivocation(&*self.client, slot_now, slot, header, author)
 				.map_err(Error::Client)?
-			{
-				Some(proof) => proof,
-				None => return Ok(()),
-			};
+		};
+		let Some(equivocation_proof) = equivocation_proof else { return Ok(()) };
 
 		info!(
 			"Slot author {:?} is equivocating at slot {} with headers {:?} and {:?}",
@@ -1796,6 +1816,12 @@ pub struct ImportQueueParams<'a, Block: BlockT, BI, Client, CIDP, SelectChain, S
 	///
 	/// Will be used when sending equivocation reports.
 	pub offchain_tx_pool_factory: OffchainTransactionPoolFactory<Block>,
+	/// Number of tasks verifying blocks in parallel.
+	///
+	/// Verification of a BABE block requires its parent to be imported, so only blocks of
+	/// different forks are verified concurrently. A linear chain, e.g. the blocks downloaded
+	/// during a major sync, is still verified one block at a time.
+	pub verification_workers: NonZeroUsize,
 }
 
 /// Start an import queue for the BABE consensus algorithm.
@@ -1819,6 +1845,7 @@ pub fn import_queue<Block: BlockT, Client, SelectChain, BI, CIDP, Spawn>(
 		registry,
 		telemetry,
 		offchain_tx_pool_factory,
+		verification_workers,
 	}: ImportQueueParams<'_, Block, BI, Client, CIDP, SelectChain, Spawn>,
 ) -> ClientResult<(DefaultImportQueue<Block>, BabeWorkerHandle<Block>)>
 where
@@ -1840,12 +1867,13 @@ where
 
 	let verifier = BabeVerifier {
 		select_chain,
-		create_inherent_data_providers,
+		create_inherent_data_providers: Arc::new(create_inherent_data_providers),
 		config: babe_link.config.clone(),
 		epoch_changes: babe_link.epoch_changes.clone(),
 		telemetry,
 		client: client.clone(),
 		offchain_tx_pool_factory,
+		equivocation_lock: Default::default(),
 	};
 
 	let (worker_tx, worker_rx) = channel(HANDLE_BUFFER_SIZE);
@@ -1856,7 +1884,14 @@ where
 	spawner.spawn_essential("babe-worker", Some("babe"), answer_requests.boxed());
 
 	Ok((
-		BasicQueue::new(verifier, Box::new(block_import), justification_import, spawner, registry),
+		BasicQueue::with_parallel_verification(
+			verifier,
+			Box::new(block_import),
+			justification_import,
+			spawner,
+			registry,
+			verification_workers,
+		),
 		BabeWorkerHandle(worker_tx),
 	))
 }
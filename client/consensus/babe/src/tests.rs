@@ -254,19 +254,20 @@ impl TestNetFactory for BabeTestNet {
 			inner: BabeVerifier {
 				client: client.clone(),
 				select_chain: longest_chain,
-				create_inherent_data_providers: Box::new(|_, _| async {
+				create_inherent_data_providers: Arc::new(Box::new(|_, _| async {
 					let slot = InherentDataProvider::from_timestamp_and_slot_duration(
 						Timestamp::current(),
 						SlotDuration::from_millis(SLOT_DURATION_MS),
 					);
 					Ok((slot,))
-				}),
+				})),
 				config: data.link.config.clone(),
 				epoch_changes: data.link.epoch_changes.clone(),
 				telemetry: None,
 				offchain_tx_pool_factory: OffchainTransactionPoolFactory::new(
 					RejectAllTxPool::default(),
 				),
+				equivocation_lock: Default::default(),
 			},
 			mutator: MUTATOR.with(|m| m.borrow().clone()),
 		}
@@ -1348,3 +1349,108 @@ async fn allows_skipping_epochs_on_some_forks() {
 
 	assert_eq!(epoch_data, epoch3);
 }
+
+#[derive(Default)]
+struct RecordingTransactionPool(Mutex<Vec<<TestBlock as BlockT>::Extrinsic>>);
+
+impl sc_transaction_pool_api::LocalTransactionPool for RecordingTransactionPool {
+	type Block = TestBlock;
+	type Hash = Hash;
+	type Error = sc_transaction_pool_api::error::Error;
+
+	fn submit_local(
+		&self,
+		_: Hash,
+		xt: <TestBlock as BlockT>::Extrinsic,
+	) -> Result<Hash, Self::Error> {
+		self.0.lock().push(xt);
+		Ok(Default::default())
+	}
+}
+
+#[tokio::test]
+async fn reports_equivocations_of_siblings_verified_in_parallel() {
+	const SLOTS: u64 = 16;
+
+	let mut net = BabeTestNet::new(1);
+	let peer = net.peer(0);
+	let data = peer.data.as_ref().expect("babe link set up during initialization");
+	let client = peer.client().as_client();
+	let genesis_hash = client.chain_info().genesis_hash;
+	let select_chain = peer.select_chain().expect("Full client has select_chain");
+
+	let pool = Arc::new(RecordingTransactionPool::default());
+	let verifier = BabeVerifier {
+		client: client.clone(),
+		select_chain,
+		create_inherent_data_providers: Arc::new(|_: Hash, _: ()| async {
+			let slot = InherentDataProvider::from_timestamp_and_slot_duration(
+				Timestamp::current(),
+				SlotDuration::from_millis(SLOT_DURATION_MS),
+			);
+			Ok((slot,))
+		}),
+		config: data.link.config.clone(),
+		epoch_changes: data.link.epoch_changes.clone(),
+		telemetry: None,
+		offchain_tx_pool_factory: OffchainTransactionPoolFactory::new(pool.clone()),
+		equivocation_lock: Default::default(),
+	};
+
+	let keystore: KeystorePtr = MemoryKeystore::new().into();
+	for authority in [Sr25519Keyring::Alice, Sr25519Keyring::Bob, Sr25519Keyring::Charlie] {
+		keystore.sr25519_generate_new(BABE, Some(&authority.to_seed())).unwrap();
+	}
+
+	// Two different children of genesis sealed by the same author for the same slot.
+	let sealed_headers = |slot: Slot| {
+		let epoch = Epoch::genesis(&data.link.config, slot);
+		let (pre_digest, author) =
+			claim_slot(slot, &epoch, &keystore).expect("all authorities are in the keystore");
+		[1, 2].map(|salt| {
+			let mut header = TestHeader::new(
+				1,
+				Default::default(),
+				Hash::repeat_byte(salt),
+				genesis_hash,
+				Digest { logs: vec![Item::babe_pre_digest(pre_digest.clone())] },
+			);
+			let signature = keystore
+				.sr25519_sign(BABE, author.as_ref(), header.hash().as_ref())
+				.unwrap()
+				.unwrap();
+			header.digest_mut().push(Item::babe_seal(signature.into()));
+			header
+		})
+	};
+
+	let slot_now = *InherentDataProvider::from_timestamp_and_slot_duration(
+		Timestamp::current(),
+		SlotDuration::from_millis(SLOT_DURATION_MS),
+	);
+
+	// Verify the siblings of each slot at the same time, as the verification workers of the
+	// import queue do.
+	for (verified, slot) in ((*slot_now - SLOTS)..*slot_now).enumerate() {
+		let headers = sealed_headers(slot.into());
+		let barrier = Arc::new(std::sync::Barrier::new(headers.len()));
+		let workers = headers
+			.into_iter()
+			.map(|header| {
+				let mut verifier = verifier.clone();
+				let barrier = barrier.clone();
+				std::thread::spawn(move || {
+					barrier.wait();
+					let params = BlockImportParams::new(BlockOrigin::NetworkBroadcast, header);
+					futures::executor::block_on(verifier.verify(params)).unwrap();
+				})
+			})
+			.collect::<Vec<_>>();
+		for worker in workers {
+			worker.join().unwrap();
+		}
+
+		// Whichever sibling was checked last is reported, exactly once.
+		assert_eq!(pool.0.lock().len(), verified + 1);
+	}
+}
@@ -40,6 +40,9 @@ use sc_consensus_beefy::communication::notification::{
 };
 
 mod notification;
+mod proof_bundle;
+
+pub use proof_bundle::{BeefyProofBundle, BeefyProofBundleApiServer};
 
 #[derive(Debug, thiserror::Error)]
 /// Top-level error type for the RPC handler
@@ -50,6 +53,12 @@ pub enum Error {
 	/// The BEEFY RPC background task failed to spawn.
 	#[error("BEEFY RPC background task failed to spawn")]
 	RpcTaskFailure(#[from] SpawnError),
+	/// The requested block is not finalized by BEEFY yet.
+	#[error("Block not finalized by BEEFY")]
+	BlockNotFinalized,
+	/// The BEEFY proof bundle could not be generated.
+	#[error("BEEFY proof bundle generation failed: {0}")]
+	ProofGeneration(String),
 }
 
 /// The error codes returned by jsonrpc.
@@ -58,6 +67,10 @@ pub enum ErrorCode {
 	NotReady = 1,
 	/// Returned on BEEFY RPC background task failure.
 	TaskFailure = 2,
+	/// Returned when the block to be proven is not finalized by BEEFY.
+	NotFinalized = 3,
+	/// Returned when a proof bundle can't be generated.
+	ProofFailure = 4,
 }
 
 impl From<Error> for ErrorCode {
@@ -65,6 +78,8 @@ impl From<Error> for ErrorCode {
 		match error {
 			Error::EndpointNotReady => ErrorCode::NotReady,
 			Error::RpcTaskFailure(_) => ErrorCode::TaskFailure,
+			Error::BlockNotFinalized => ErrorCode::NotFinalized,
+			Error::ProofGeneration(_) => ErrorCode::ProofFailure,
 		}
 	}
 }
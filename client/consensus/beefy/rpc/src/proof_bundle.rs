@@ -0,0 +1,489 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! RPC API for assembling BEEFY + MMR proof bundles for light clients.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::{Decode, Encode};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+use sc_client_api::BlockBackend;
+use sc_consensus_beefy::justification::BeefyVersionedFinalityProof;
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_blockchain::HeaderBackend;
+use sp_consensus_beefy::{
+	ecdsa_crypto::AuthorityId,
+	proof_bundle::{AuthorityProof, ProofBundle},
+	BeefyApi as BeefyRuntimeApi, MmrRootHash, VersionedFinalityProof, BEEFY_ENGINE_ID,
+};
+use sp_core::{
+	offchain::{storage::OffchainDb, OffchainDbExt, OffchainStorage},
+	Bytes,
+};
+use sp_mmr_primitives::MmrApi as MmrRuntimeApi;
+use sp_runtime::traits::{Block as BlockT, Keccak256, NumberFor, One, Saturating};
+
+use crate::Error;
+
+/// The maximum number of blocks searched for a BEEFY justification, starting at the block to be
+/// proven.
+///
+/// BEEFY justifications are only stored for some of the finalized blocks, but at least for the
+/// first block of every session, so the search only needs to span a session.
+const MAX_SEARCHED_BLOCKS: u32 = 4096;
+
+/// Provides RPC methods for building self-contained BEEFY + MMR proofs.
+#[rpc(client, server)]
+pub trait BeefyProofBundleApi<Hash> {
+	/// Generate a proof bundle for the finalized block `block`.
+	///
+	/// The bundle contains the signed commitment of the nearest BEEFY-finalized block at or above
+	/// `block`, merkle proofs of the signing authorities against the authority set commitment
+	/// and the MMR proof for the leaf of `block` against the MMR root in that commitment.
+	///
+	/// Returns the SCALE-encoded [`ProofBundle`], which can be checked with
+	/// [`sp_consensus_beefy::proof_bundle::verify_proof_bundle`].
+	#[method(name = "beefy_generateProofBundle")]
+	fn generate_proof_bundle(&self, block: Hash) -> RpcResult<Bytes>;
+}
+
+/// Implements the [`BeefyProofBundleApiServer`] RPC trait.
+pub struct BeefyProofBundle<Client, Block, S> {
+	client: Arc<Client>,
+	offchain_db: OffchainDb<S>,
+	authority_to_leaf: fn(&AuthorityId) -> Vec<u8>,
+	_marker: PhantomData<Block>,
+}
+
+impl<Client, Block, S> BeefyProofBundle<Client, Block, S> {
+	/// Creates a new proof bundle RPC handler.
+	///
+	/// `authority_to_leaf` must match the runtime's conversion of BEEFY authorities into the
+	/// leaves of the authority set merkle tree (e.g. `pallet_beefy_mmr::BeefyEcdsaToEthereum`).
+	pub fn new(
+		client: Arc<Client>,
+		offchain_storage: S,
+		authority_to_leaf: fn(&AuthorityId) -> Vec<u8>,
+	) -> Self {
+		Self {
+			client,
+			offchain_db: OffchainDb::new(offchain_storage),
+			authority_to_leaf,
+			_marker: Default::default(),
+		}
+	}
+}
+
+impl<Client, Block, S> BeefyProofBundleApiServer<Block::Hash> for BeefyProofBundle<Client, Block, S>
+where
+	Block: BlockT,
+	Client: ProvideRuntimeApi<Block>
+		+ HeaderBackend<Block>
+		+ BlockBackend<Block>
+		+ Send
+		+ Sync
+		+ 'static,
+	Client::Api:
+		BeefyRuntimeApi<Block, AuthorityId> + MmrRuntimeApi<Block, MmrRootHash, NumberFor<Block>>,
+	S: OffchainStorage + 'static,
+{
+	fn generate_proof_bundle(&self, block: Block::Hash) -> RpcResult<Bytes> {
+		let number = self
+			.client
+			.number(block)
+			.map_err(|e| Error::ProofGeneration(e.to_string()))?
+			.ok_or(Error::BlockNotFinalized)?;
+		if self.client.hash(number).ok().flatten() != Some(block) {
+			return Err(Error::BlockNotFinalized.into())
+		}
+
+		let (commitment_hash, finality_proof) = nearest_finality_proof(&*self.client, number)?;
+		let VersionedFinalityProof::V1(signed_commitment) = finality_proof;
+
+		let mut api = self.client.runtime_api();
+		let validator_set = api
+			.validator_set(commitment_hash)
+			.map_err(|e| Error::ProofGeneration(e.to_string()))?
+			.filter(|set| set.id() == signed_commitment.commitment.validator_set_id)
+			.ok_or_else(|| Error::ProofGeneration("signing validator set not found".into()))?;
+
+		let leaves: Vec<_> =
+			validator_set.validators().iter().map(self.authority_to_leaf).collect();
+		let authority_proofs = signed_commitment
+			.signatures
+			.iter()
+			.enumerate()
+			.filter(|(_, signature)| signature.is_some())
+			.map(|(position, _)| AuthorityProof {
+				position: position as u32,
+				authority_id: validator_set.validators()[position].clone(),
+				proof: binary_merkle_tree::merkle_proof::<Keccak256, _, _>(&leaves, position).proof,
+			})
+			.collect();
+
+		api.register_extension(OffchainDbExt::new(self.offchain_db.clone()));
+		let (mut leaves, leaf_proof) = api
+			.generate_proof(
+				commitment_hash,
+				vec![number],
+				Some(signed_commitment.commitment.block_number),
+			)
+			.map_err(|e| Error::ProofGeneration(e.to_string()))?
+			.map_err(|e| Error::ProofGeneration(format!("{:?}", e)))?;
+		let leaf = leaves.pop().ok_or_else(|| Error::ProofGeneration("missing MMR leaf".into()))?;
+
+		let bundle = ProofBundle { signed_commitment, authority_proofs, leaf: leaf.0, leaf_proof };
+		Ok(Bytes(bundle.encode()))
+	}
+}
+
+/// Find the BEEFY finality proof of the lowest finalized block at or above `number`.
+///
+/// At most [`MAX_SEARCHED_BLOCKS`] blocks are searched.
+fn nearest_finality_proof<Block, Client>(
+	client: &Client,
+	number: NumberFor<Block>,
+) -> Result<(Block::Hash, BeefyVersionedFinalityProof<Block>), Error>
+where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + BlockBackend<Block>,
+{
+	let finalized_number = client.info().finalized_number;
+	if number > finalized_number {
+		return Err(Error::BlockNotFinalized)
+	}
+	let last = finalized_number.min(number.saturating_add((MAX_SEARCHED_BLOCKS - 1).into()));
+
+	let mut current = number;
+	while current <= last {
+		let hash = client
+			.hash(current)
+			.map_err(|e| Error::ProofGeneration(e.to_string()))?
+			.ok_or(Error::BlockNotFinalized)?;
+		let justification = client
+			.justifications(hash)
+			.map_err(|e| Error::ProofGeneration(e.to_string()))?
+			.and_then(|justifications| justifications.into_justification(BEEFY_ENGINE_ID));
+		if let Some(encoded) = justification {
+			let proof = BeefyVersionedFinalityProof::<Block>::decode(&mut &*encoded)
+				.map_err(|e| Error::ProofGeneration(e.to_string()))?;
+			return Ok((hash, proof))
+		}
+		current += One::one();
+	}
+
+	if last < finalized_number {
+		return Err(Error::ProofGeneration(format!(
+			"no BEEFY justification within {} blocks",
+			MAX_SEARCHED_BLOCKS
+		)))
+	}
+	Err(Error::BlockNotFinalized)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use sc_block_builder::BlockBuilderProvider;
+	use sp_api::ApiRef;
+	use sp_blockchain::{BlockStatus, Info};
+	use sp_consensus::BlockOrigin;
+	use sp_consensus_beefy::{
+		ecdsa_crypto::Signature, known_payloads, mmr::BeefyAuthoritySet,
+		proof_bundle::verify_proof_bundle, Commitment, Keyring, Payload, SignedCommitment,
+		ValidatorSet,
+	};
+	use sp_core::{offchain::storage::InMemOffchainStorage, H256};
+	use sp_mmr_primitives::{EncodableOpaqueLeaf, Error as MmrError, Proof};
+	use sp_runtime::{
+		generic::SignedBlock,
+		traits::{Hash, Header as _},
+		Justifications,
+	};
+	use substrate_test_runtime_client::{
+		prelude::*,
+		runtime::{Block, Extrinsic, Header},
+	};
+
+	fn finality_proof(block_number: u64) -> BeefyVersionedFinalityProof<Block> {
+		let payload = Payload::from_single_entry(known_payloads::MMR_ROOT_ID, vec![42]);
+		VersionedFinalityProof::V1(SignedCommitment {
+			commitment: Commitment { payload, block_number, validator_set_id: 0 },
+			signatures: vec![None],
+		})
+	}
+
+	#[tokio::test]
+	async fn finds_nearest_beefy_justification() {
+		let mut client = substrate_test_runtime_client::new();
+
+		let mut hashes = Vec::new();
+		for number in 1..=4u64 {
+			let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+			hashes.push(block.hash());
+			if number == 3 {
+				let justifications =
+					Justifications::from((BEEFY_ENGINE_ID, finality_proof(3).encode()));
+				client.import_justified(BlockOrigin::Own, block, justifications).await.unwrap();
+			} else {
+				client.import(BlockOrigin::Own, block).await.unwrap();
+			}
+		}
+
+		// Block 4 is not finalized yet, hence can't be proven.
+		assert!(matches!(
+			nearest_finality_proof::<Block, _>(&client, 4),
+			Err(Error::BlockNotFinalized)
+		));
+
+		client.finalize_block(hashes[3], None).unwrap();
+		for number in 1..=3 {
+			let (hash, proof) = nearest_finality_proof::<Block, _>(&client, number).unwrap();
+			assert_eq!(hash, hashes[2]);
+			assert_eq!(proof, finality_proof(3));
+		}
+		// Block 4 is finalized, but there is no BEEFY justification at or above it.
+		assert!(matches!(
+			nearest_finality_proof::<Block, _>(&client, 4),
+			Err(Error::BlockNotFinalized)
+		));
+	}
+
+	const AUTHORITIES: [Keyring; 4] =
+		[Keyring::Alice, Keyring::Bob, Keyring::Charlie, Keyring::Dave];
+	const LEAF: &[u8] = b"leaf for block 1";
+
+	fn authority_leaf(id: &AuthorityId) -> Vec<u8> {
+		id.encode()
+	}
+
+	fn leaf_sibling() -> H256 {
+		Keccak256::hash(b"leaf for block 2")
+	}
+
+	// Root of a two-leaf MMR containing `LEAF` and its sibling.
+	fn mmr_root() -> MmrRootHash {
+		let mut concat = Keccak256::hash(LEAF).as_ref().to_vec();
+		concat.extend_from_slice(leaf_sibling().as_ref());
+		Keccak256::hash(&concat)
+	}
+
+	fn block_hash(number: u64) -> H256 {
+		H256::from_low_u64_be(number + 1)
+	}
+
+	/// Finalized chain with a single BEEFY justification, signed by all authorities but
+	/// `Charlie`, and a runtime providing the authority set and the MMR proofs.
+	#[derive(Clone)]
+	struct TestClient {
+		finalized_number: u64,
+		justified_number: u64,
+	}
+
+	impl TestClient {
+		fn signed_commitment(&self) -> SignedCommitment<u64, Signature> {
+			let commitment = Commitment {
+				payload: Payload::from_single_entry(
+					known_payloads::MMR_ROOT_ID,
+					mmr_root().encode(),
+				),
+				block_number: self.justified_number,
+				validator_set_id: 3,
+			};
+			let signatures = AUTHORITIES
+				.iter()
+				.map(|k| (*k != Keyring::Charlie).then(|| k.sign(&commitment.encode())))
+				.collect();
+			SignedCommitment { commitment, signatures }
+		}
+	}
+
+	impl HeaderBackend<Block> for TestClient {
+		fn header(&self, _hash: H256) -> sp_blockchain::Result<Option<Header>> {
+			unimplemented!()
+		}
+
+		fn info(&self) -> Info<Block> {
+			Info {
+				best_hash: block_hash(self.finalized_number),
+				best_number: self.finalized_number,
+				genesis_hash: block_hash(0),
+				finalized_hash: block_hash(self.finalized_number),
+				finalized_number: self.finalized_number,
+				finalized_state: None,
+				number_leaves: 1,
+				block_gap: None,
+			}
+		}
+
+		fn status(&self, _hash: H256) -> sp_blockchain::Result<BlockStatus> {
+			unimplemented!()
+		}
+
+		fn number(&self, hash: H256) -> sp_blockchain::Result<Option<u64>> {
+			let number = hash.to_low_u64_be().checked_sub(1);
+			Ok(number.filter(|number| *number <= self.finalized_number))
+		}
+
+		fn hash(&self, number: u64) -> sp_blockchain::Result<Option<H256>> {
+			Ok((number <= self.finalized_number).then(|| block_hash(number)))
+		}
+	}
+
+	impl BlockBackend<Block> for TestClient {
+		fn block_body(&self, _hash: H256) -> sp_blockchain::Result<Option<Vec<Extrinsic>>> {
+			unimplemented!()
+		}
+
+		fn block_indexed_body(&self, _hash: H256) -> sp_blockchain::Result<Option<Vec<Vec<u8>>>> {
+			unimplemented!()
+		}
+
+		fn block(&self, _hash: H256) -> sp_blockchain::Result<Option<SignedBlock<Block>>> {
+			unimplemented!()
+		}
+
+		fn block_status(&self, _hash: H256) -> sp_blockchain::Result<sp_consensus::BlockStatus> {
+			unimplemented!()
+		}
+
+		fn justifications(&self, hash: H256) -> sp_blockchain::Result<Option<Justifications>> {
+			Ok((hash == block_hash(self.justified_number)).then(|| {
+				let proof = VersionedFinalityProof::V1(self.signed_commitment());
+				Justifications::from((BEEFY_ENGINE_ID, proof.encode()))
+			}))
+		}
+
+		fn block_hash(&self, number: u64) -> sp_blockchain::Result<Option<H256>> {
+			self.hash(number)
+		}
+
+		fn indexed_transaction(&self, _hash: H256) -> sp_blockchain::Result<Option<Vec<u8>>> {
+			unimplemented!()
+		}
+
+		fn requires_full_sync(&self) -> bool {
+			false
+		}
+	}
+
+	// compiler gets confused and warns us about unused inner
+	#[allow(dead_code)]
+	struct RuntimeApi {
+		inner: TestClient,
+	}
+
+	impl ProvideRuntimeApi<Block> for TestClient {
+		type Api = RuntimeApi;
+		fn runtime_api(&self) -> ApiRef<Self::Api> {
+			RuntimeApi { inner: self.clone() }.into()
+		}
+	}
+
+	sp_api::mock_impl_runtime_apis! {
+		impl BeefyRuntimeApi<Block, AuthorityId> for RuntimeApi {
+			fn validator_set() -> Option<ValidatorSet<AuthorityId>> {
+				ValidatorSet::new(AUTHORITIES.iter().map(|k| k.public()), 3)
+			}
+		}
+
+		impl MmrRuntimeApi<Block, MmrRootHash, u64> for RuntimeApi {
+			fn generate_proof(
+				block_numbers: Vec<u64>,
+				best_known_block_number: Option<u64>,
+			) -> Result<(Vec<EncodableOpaqueLeaf>, Proof<MmrRootHash>), MmrError> {
+				assert_eq!(block_numbers, vec![1]);
+				assert_eq!(best_known_block_number, Some(self.inner.justified_number));
+				let proof = Proof { leaf_indices: vec![0], leaf_count: 2, items: vec![leaf_sibling()] };
+				Ok((vec![EncodableOpaqueLeaf(LEAF.to_vec())], proof))
+			}
+		}
+	}
+
+	#[test]
+	fn generated_proof_bundle_is_valid() {
+		let client = TestClient { finalized_number: 5, justified_number: 3 };
+		let api = BeefyProofBundle::new(
+			Arc::new(client),
+			InMemOffchainStorage::default(),
+			authority_leaf,
+		);
+
+		// Block 6 is not finalized yet.
+		assert!(api.generate_proof_bundle(block_hash(6)).is_err());
+
+		let encoded = api.generate_proof_bundle(block_hash(1)).unwrap();
+		let bundle =
+			ProofBundle::<u64, AuthorityId, Signature, MmrRootHash>::decode(&mut &*encoded)
+				.unwrap();
+		let positions: Vec<_> = bundle.authority_proofs.iter().map(|p| p.position).collect();
+		assert_eq!(positions, vec![0, 1, 3]);
+
+		let leaves: Vec<_> = AUTHORITIES.iter().map(|k| authority_leaf(&k.public())).collect();
+		let authority_set = BeefyAuthoritySet {
+			id: 3,
+			len: AUTHORITIES.len() as u32,
+			keyset_commitment: binary_merkle_tree::merkle_root::<Keccak256, _>(leaves),
+		};
+		let leaf =
+			verify_proof_bundle::<_, _, Keccak256>(bundle, &authority_set, authority_leaf).unwrap();
+		assert_eq!(leaf.0, LEAF.to_vec());
+	}
+
+	#[tokio::test]
+	async fn serves_proof_bundles_over_rpc() {
+		let client = TestClient { finalized_number: 5, justified_number: 3 };
+		let api = BeefyProofBundle::new(
+			Arc::new(client),
+			InMemOffchainStorage::default(),
+			authority_leaf,
+		);
+		let expected = api.generate_proof_bundle(block_hash(1)).unwrap();
+		let rpc = api.into_rpc();
+
+		let bundle: Bytes = rpc.call("beefy_generateProofBundle", [block_hash(1)]).await.unwrap();
+		assert_eq!(bundle, expected);
+		assert!(rpc
+			.call::<_, Bytes>("beefy_generateProofBundle", [block_hash(6)])
+			.await
+			.is_err());
+	}
+
+	#[test]
+	fn justification_search_is_bounded() {
+		let client =
+			TestClient { finalized_number: 2 * MAX_SEARCHED_BLOCKS as u64, justified_number: 3 };
+		assert!(nearest_finality_proof::<Block, _>(&client, 4).is_err());
+		assert_eq!(nearest_finality_proof::<Block, _>(&client, 1).unwrap().0, block_hash(3),);
+
+		let client = TestClient {
+			finalized_number: 2 * MAX_SEARCHED_BLOCKS as u64,
+			justified_number: MAX_SEARCHED_BLOCKS as u64,
+		};
+		assert_eq!(
+			nearest_finality_proof::<Block, _>(&client, 1).unwrap().0,
+			block_hash(MAX_SEARCHED_BLOCKS as u64),
+		);
+		assert!(matches!(
+			nearest_finality_proof::<Block, _>(&client, 0),
+			Err(Error::ProofGeneration(_))
+		));
+	}
+}
@@ -98,6 +98,15 @@ pub trait Verifier<B: BlockT>: Send {
 	/// continue the block import process.
 	async fn verify(&mut self, block: BlockImportParams<B>)
 		-> Result<BlockImportParams<B>, String>;
+
+	/// Whether the verification of a block requires its parent to be imported.
+	///
+	/// Verifiers that only look at the block itself, e.g. checking a seal against a fixed set of
+	/// authorities, can return `false`. The import queue is then allowed to verify consecutive
+	/// blocks of a chain concurrently, instead of waiting for each parent to be imported.
+	fn requires_imported_parent(&self) -> bool {
+		true
+	}
 }
 
 /// Blocks import queue API.
@@ -234,27 +243,59 @@ pub(crate) async fn import_single_block_metered<B: BlockT, V: Verifier<B>>(
 	verifier: &mut V,
 	metrics: Option<Metrics>,
 ) -> BlockImportResult<B> {
-	let peer = block.origin;
-
-	let (header, justifications) = match (block.header, block.justifications) {
-		(Some(header), justifications) => (header, justifications),
-		(None, _) => {
-			if let Some(ref peer) = peer {
-				debug!(target: LOG_TARGET, "Header {} was not provided by {} ", block.hash, peer);
-			} else {
-				debug!(target: LOG_TARGET, "Header {} was not provided ", block.hash);
-			}
-			return Err(BlockImportError::IncompleteHeader(peer))
-		},
+	let block = match prepare_single_block(import_handle, block_origin, block, false).await? {
+		PreparedBlock::Known(status) => return Ok(status),
+		PreparedBlock::Unverified(block) => *block,
 	};
 
-	trace!(target: LOG_TARGET, "Header {} has {:?} logs", block.hash, header.digest().logs().len());
+	let block = verify_single_block_metered(verifier, block, metrics.as_ref()).await?;
 
-	let number = *header.number();
-	let hash = block.hash;
-	let parent_hash = *header.parent_hash();
+	import_verified_block_metered(import_handle, block, metrics.as_ref()).await
+}
+
+/// Outcome of [`prepare_single_block`].
+pub(crate) enum PreparedBlock<B: BlockT> {
+	/// The block doesn't need to be imported, e.g. it is already in the chain.
+	Known(BlockImportStatus<NumberFor<B>>),
+	/// The block passed the import checks and should now be verified.
+	Unverified(Box<UnverifiedBlock<B>>),
+}
+
+/// A block that passed the import checks but is not yet verified.
+pub(crate) struct UnverifiedBlock<B: BlockT> {
+	peer: Option<RuntimeOrigin>,
+	number: NumberFor<B>,
+	hash: B::Hash,
+	parent_hash: B::Hash,
+	import_block: BlockImportParams<B>,
+}
+
+impl<B: BlockT> UnverifiedBlock<B> {
+	/// Hash of the parent block.
+	pub(crate) fn parent_hash(&self) -> &B::Hash {
+		&self.parent_hash
+	}
+}
+
+/// A block that has been verified and is ready to be imported.
+pub(crate) struct VerifiedBlock<B: BlockT> {
+	peer: Option<RuntimeOrigin>,
+	number: NumberFor<B>,
+	hash: B::Hash,
+	parent_hash: B::Hash,
+	import_block: BlockImportParams<B>,
+	/// Time spent in the verifier, excluding any time the block waited to be imported.
+	verification_time: std::time::Duration,
+}
 
-	let import_handler = |import| match import {
+fn import_result_to_status<B: BlockT>(
+	import: Result<ImportResult, ConsensusError>,
+	number: NumberFor<B>,
+	hash: B::Hash,
+	parent_hash: B::Hash,
+	peer: Option<RuntimeOrigin>,
+) -> BlockImportResult<B> {
+	match import {
 		Ok(ImportResult::AlreadyInChain) => {
 			trace!(target: LOG_TARGET, "Block already in chain {}: {:?}", number, hash);
 			Ok(BlockImportStatus::ImportedKnown(number, peer))
@@ -283,26 +324,58 @@ pub(crate) async fn import_single_block_metered<B: BlockT, V: Verifier<B>>(
 			debug!(target: LOG_TARGET, "Error importing block {}: {:?}: {}", number, hash, e);
 			Err(BlockImportError::Other(e))
 		},
+	}
+}
+
+/// First stage of a single block import.
+///
+/// Checks that the block is complete and that its parent is known to the block import, then
+/// builds the [`BlockImportParams`] that are going to be passed to the verifier.
+///
+/// `parent_in_flight` must be set when the parent is not imported yet but is going to be imported
+/// right before this block, so the parent is allowed to be missing from the block import.
+pub(crate) async fn prepare_single_block<B: BlockT>(
+	import_handle: &mut impl BlockImport<B, Error = ConsensusError>,
+	block_origin: BlockOrigin,
+	block: IncomingBlock<B>,
+	parent_in_flight: bool,
+) -> Result<PreparedBlock<B>, BlockImportError> {
+	let peer = block.origin;
+
+	let (header, justifications) = match (block.header, block.justifications) {
+		(Some(header), justifications) => (header, justifications),
+		(None, _) => {
+			if let Some(ref peer) = peer {
+				debug!(target: LOG_TARGET, "Header {} was not provided by {} ", block.hash, peer);
+			} else {
+				debug!(target: LOG_TARGET, "Header {} was not provided ", block.hash);
+			}
+			return Err(BlockImportError::IncompleteHeader(peer))
+		},
 	};
 
-	match import_handler(
-		import_handle
-			.check_block(BlockCheckParams {
-				hash,
-				number,
-				parent_hash,
-				allow_missing_state: block.allow_missing_state,
-				import_existing: block.import_existing,
-				allow_missing_parent: block.state.is_some(),
-			})
-			.await,
-	)? {
+	trace!(target: LOG_TARGET, "Header {} has {:?} logs", block.hash, header.digest().logs().len());
+
+	let number = *header.number();
+	let hash = block.hash;
+	let parent_hash = *header.parent_hash();
+
+	let check_result = import_handle
+		.check_block(BlockCheckParams {
+			hash,
+			number,
+			parent_hash,
+			allow_missing_state: block.allow_missing_state,
+			import_existing: block.import_existing,
+			allow_missing_parent: parent_in_flight || block.state.is_some(),
+		})
+		.await;
+	match import_result_to_status::<B>(check_result, number, hash, parent_hash, peer)? {
 		BlockImportStatus::ImportedUnknown { .. } => (),
-		r => return Ok(r), // Any other successful result means that the block is already imported.
+		// Any other successful result means that the block is already imported.
+		r => return Ok(PreparedBlock::Known(r)),
 	}
 
-	let started = std::time::Instant::now();
-
 	let mut import_block = BlockImportParams::new(block_origin, header);
 	import_block.body = block.body;
 	import_block.justifications = justifications;
@@ -319,6 +392,28 @@ pub(crate) async fn import_single_block_metered<B: BlockT, V: Verifier<B>>(
 		import_block.state_action = StateAction::ExecuteIfPossible;
 	}
 
+	Ok(PreparedBlock::Unverified(Box::new(UnverifiedBlock {
+		peer,
+		number,
+		hash,
+		parent_hash,
+		import_block,
+	})))
+}
+
+/// Second stage of a single block import: run the verifier.
+///
+/// This stage doesn't touch the block import, thus it can run concurrently for blocks that
+/// don't depend on each other.
+pub(crate) async fn verify_single_block_metered<B: BlockT, V: Verifier<B>>(
+	verifier: &mut V,
+	block: UnverifiedBlock<B>,
+	metrics: Option<&Metrics>,
+) -> Result<VerifiedBlock<B>, BlockImportError> {
+	let UnverifiedBlock { peer, number, hash, parent_hash, import_block } = block;
+
+	let started = std::time::Instant::now();
+
 	let import_block = verifier.verify(import_block).await.map_err(|msg| {
 		if let Some(ref peer) = peer {
 			trace!(
@@ -332,19 +427,34 @@ pub(crate) async fn import_single_block_metered<B: BlockT, V: Verifier<B>>(
 		} else {
 			trace!(target: LOG_TARGET, "Verifying {}({}) failed: {}", number, hash, msg);
 		}
-		if let Some(metrics) = metrics.as_ref() {
+		if let Some(metrics) = metrics {
 			metrics.report_verification(false, started.elapsed());
 		}
 		BlockImportError::VerificationFailed(peer, msg)
 	})?;
 
-	if let Some(metrics) = metrics.as_ref() {
-		metrics.report_verification(true, started.elapsed());
+	let verification_time = started.elapsed();
+	if let Some(metrics) = metrics {
+		metrics.report_verification(true, verification_time);
 	}
 
+	Ok(VerifiedBlock { peer, number, hash, parent_hash, import_block, verification_time })
+}
+
+/// Last stage of a single block import: pass the verified block to the block import.
+pub(crate) async fn import_verified_block_metered<B: BlockT>(
+	import_handle: &mut impl BlockImport<B, Error = ConsensusError>,
+	block: VerifiedBlock<B>,
+	metrics: Option<&Metrics>,
+) -> BlockImportResult<B> {
+	let VerifiedBlock { peer, number, hash, parent_hash, import_block, verification_time } = block;
+
+	// Blocks verified ahead of time may have waited for their turn to be imported, only the time
+	// actually spent in the verifier and in the block import is reported.
+	let started = std::time::Instant::now();
 	let imported = import_handle.import_block(import_block).await;
-	if let Some(metrics) = metrics.as_ref() {
-		metrics.report_verification_and_import(started.elapsed());
+	if let Some(metrics) = metrics {
+		metrics.report_verification_and_import(verification_time + started.elapsed());
 	}
-	import_handler(imported)
+	import_result_to_status::<B>(imported, number, hash, parent_hash, peer)
 }
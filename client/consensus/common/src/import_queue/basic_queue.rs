@@ -16,6 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 use futures::{
+	channel::oneshot,
 	prelude::*,
 	task::{Context, Poll},
 };
@@ -28,14 +29,15 @@ use sp_runtime::{
 	traits::{Block as BlockT, Header as HeaderT, NumberFor},
 	Justification, Justifications,
 };
-use std::{pin::Pin, time::Duration};
+use std::{iter::Peekable, num::NonZeroUsize, pin::Pin, time::Duration};
 
 use crate::{
 	import_queue::{
 		buffered_link::{self, BufferedLinkReceiver, BufferedLinkSender},
-		import_single_block_metered, BlockImportError, BlockImportStatus, BoxBlockImport,
-		BoxJustificationImport, ImportQueue, ImportQueueService, IncomingBlock, Link,
-		RuntimeOrigin, Verifier, LOG_TARGET,
+		import_verified_block_metered, prepare_single_block, verify_single_block_metered,
+		BlockImportError, BlockImportStatus, BoxBlockImport, BoxJustificationImport, ImportQueue,
+		ImportQueueService, IncomingBlock, Link, PreparedBlock, RuntimeOrigin, UnverifiedBlock,
+		VerifiedBlock, Verifier, LOG_TARGET,
 	},
 	metrics::Metrics,
 };
@@ -68,23 +70,78 @@ impl<B: BlockT> BasicQueue<B> {
 		spawner: &impl sp_core::traits::SpawnEssentialNamed,
 		prometheus_registry: Option<&Registry>,
 	) -> Self {
-		let (result_sender, result_port) = buffered_link::buffered_link(100_000);
+		let metrics = Self::register_metrics(prometheus_registry);
+		Self::with_verification(
+			Verification::Sequential(verifier),
+			block_import,
+			justification_import,
+			spawner,
+			metrics,
+		)
+	}
 
-		let metrics = prometheus_registry.and_then(|r| {
+	/// Instantiate a new basic queue which verifies blocks on a pool of `verification_workers`
+	/// background tasks, each one owning a clone of the given verifier.
+	///
+	/// Blocks are still imported one at a time and in the order they were scheduled. The
+	/// verification of consecutive blocks of an import batch runs concurrently as long as their
+	/// parents are already known to the client, i.e. the parent is not a block of the same
+	/// batch still waiting to be imported. This mostly helps with forks: the blocks of a linear
+	/// chain, e.g. during a major sync, are only verified concurrently by verifiers which don't
+	/// need the parent to be imported (see [`Verifier::requires_imported_parent`]). None of the
+	/// verifiers in this repository opts out of that requirement yet.
+	pub fn with_parallel_verification<V: 'static + Verifier<B> + Clone>(
+		verifier: V,
+		block_import: BoxBlockImport<B>,
+		justification_import: Option<BoxJustificationImport<B>>,
+		spawner: &impl sp_core::traits::SpawnEssentialNamed,
+		prometheus_registry: Option<&Registry>,
+		verification_workers: NonZeroUsize,
+	) -> Self {
+		let metrics = Self::register_metrics(prometheus_registry);
+		let verification = if verification_workers.get() == 1 {
+			Verification::Sequential(verifier)
+		} else {
+			let pool = VerifierPool::new(verifier, verification_workers, metrics.clone(), |task| {
+				spawner.spawn_essential_blocking(
+					"block-verification-worker",
+					Some("block-import"),
+					task,
+				)
+			});
+			Verification::Parallel(pool)
+		};
+
+		Self::with_verification(verification, block_import, justification_import, spawner, metrics)
+	}
+
+	fn register_metrics(prometheus_registry: Option<&Registry>) -> Option<Metrics> {
+		prometheus_registry.and_then(|r| {
 			Metrics::register(r)
 				.map_err(|err| {
 					log::warn!("Failed to register Prometheus metrics: {}", err);
 				})
 				.ok()
-		});
+		})
+	}
 
-		let (future, justification_sender, block_import_sender) = BlockImportWorker::new(
-			result_sender,
-			verifier,
-			block_import,
-			justification_import,
-			metrics,
-		);
+	fn with_verification<V: 'static + Verifier<B>>(
+		verification: Verification<B, V>,
+		block_import: BoxBlockImport<B>,
+		justification_import: Option<BoxJustificationImport<B>>,
+		spawner: &impl sp_core::traits::SpawnEssentialNamed,
+		metrics: Option<Metrics>,
+	) -> Self {
+		let (result_sender, result_port) = buffered_link::buffered_link(100_000);
+
+		let (future, justification_sender, block_import_sender) =
+			BlockImportWorker::with_verification(
+				result_sender,
+				verification,
+				block_import,
+				justification_import,
+				metrics,
+			);
 
 		spawner.spawn_essential_blocking(
 			"basic-block-import-worker",
@@ -218,9 +275,9 @@ mod worker_messages {
 /// to give other futures the possibility to be run.
 ///
 /// Returns when `block_import` ended.
-async fn block_import_process<B: BlockT>(
+async fn block_import_process<B: BlockT, V: Verifier<B>>(
 	mut block_import: BoxBlockImport<B>,
-	mut verifier: impl Verifier<B>,
+	mut verification: Verification<B, V>,
 	mut result_sender: BufferedLinkSender<B>,
 	mut block_import_receiver: TracingUnboundedReceiver<worker_messages::ImportBlocks<B>>,
 	metrics: Option<Metrics>,
@@ -243,7 +300,7 @@ async fn block_import_process<B: BlockT>(
 			&mut block_import,
 			origin,
 			blocks,
-			&mut verifier,
+			&mut verification,
 			delay_between_blocks,
 			metrics.clone(),
 		)
@@ -260,6 +317,7 @@ struct BlockImportWorker<B: BlockT> {
 }
 
 impl<B: BlockT> BlockImportWorker<B> {
+	#[cfg(test)]
 	fn new<V: 'static + Verifier<B>>(
 		result_sender: BufferedLinkSender<B>,
 		verifier: V,
@@ -270,6 +328,26 @@ impl<B: BlockT> BlockImportWorker<B> {
 		impl Future<Output = ()> + Send,
 		TracingUnboundedSender<worker_messages::ImportJustification<B>>,
 		TracingUnboundedSender<worker_messages::ImportBlocks<B>>,
+	) {
+		Self::with_verification(
+			result_sender,
+			Verification::Sequential(verifier),
+			block_import,
+			justification_import,
+			metrics,
+		)
+	}
+
+	fn with_verification<V: 'static + Verifier<B>>(
+		result_sender: BufferedLinkSender<B>,
+		verification: Verification<B, V>,
+		block_import: BoxBlockImport<B>,
+		justification_import: Option<BoxJustificationImport<B>>,
+		metrics: Option<Metrics>,
+	) -> (
+		impl Future<Output = ()> + Send,
+		TracingUnboundedSender<worker_messages::ImportJustification<B>>,
+		TracingUnboundedSender<worker_messages::ImportBlocks<B>>,
 	) {
 		use worker_messages::*;
 
@@ -293,7 +371,7 @@ impl<B: BlockT> BlockImportWorker<B> {
 
 			let block_import_process = block_import_process(
 				block_import,
-				verifier,
+				verification,
 				worker.result_sender.clone(),
 				block_import_port,
 				worker.metrics.clone(),
@@ -385,15 +463,194 @@ struct ImportManyBlocksResult<B: BlockT> {
 	results: Vec<(Result<BlockImportStatus<NumberFor<B>>, BlockImportError>, B::Hash)>,
 }
 
+/// Where and how blocks get verified.
+enum Verification<B: BlockT, V> {
+	/// Blocks are verified one after the other on the import task.
+	Sequential(V),
+	/// Blocks are verified concurrently by a pool of background tasks.
+	Parallel(VerifierPool<B>),
+}
+
+impl<B: BlockT, V: Verifier<B>> Verification<B, V> {
+	/// Maximum number of blocks verified at the same time.
+	fn max_concurrency(&self) -> usize {
+		match self {
+			Verification::Sequential(_) => 1,
+			Verification::Parallel(pool) => pool.workers.len(),
+		}
+	}
+
+	/// Whether the verification of a block requires its parent to be imported.
+	fn requires_imported_parent(&self) -> bool {
+		match self {
+			Verification::Sequential(verifier) => verifier.requires_imported_parent(),
+			Verification::Parallel(pool) => pool.requires_imported_parent,
+		}
+	}
+
+	/// Start the verification of the given block.
+	async fn start(
+		&mut self,
+		block: UnverifiedBlock<B>,
+		metrics: Option<&Metrics>,
+	) -> PendingVerification<B> {
+		match self {
+			Verification::Sequential(verifier) => PendingVerification::Ready(Box::new(
+				verify_single_block_metered(verifier, block, metrics).await,
+			)),
+			Verification::Parallel(pool) => pool.dispatch(block),
+		}
+	}
+}
+
+/// Verification started by [`Verification::start`].
+enum PendingVerification<B: BlockT> {
+	/// The block doesn't need to be verified nor imported, e.g. it is already in the chain.
+	Skipped(BlockImportStatus<NumberFor<B>>),
+	/// Verification has already completed.
+	Ready(Box<Result<VerifiedBlock<B>, BlockImportError>>),
+	/// Verification is running on one of the pool workers.
+	Dispatched(oneshot::Receiver<Result<VerifiedBlock<B>, BlockImportError>>),
+}
+
+impl<B: BlockT> PendingVerification<B> {
+	async fn wait(self) -> Result<VerifiedBlock<B>, BlockImportError> {
+		match self {
+			PendingVerification::Skipped(_) =>
+				unreachable!("skipped blocks are never waited for; qed"),
+			PendingVerification::Ready(result) => *result,
+			// The worker went away without answering, this only happens on shutdown.
+			PendingVerification::Dispatched(receiver) =>
+				receiver.await.unwrap_or(Err(BlockImportError::Cancelled)),
+		}
+	}
+}
+
+/// Message sent to the verification workers.
+struct VerificationJob<B: BlockT> {
+	block: UnverifiedBlock<B>,
+	result: oneshot::Sender<Result<VerifiedBlock<B>, BlockImportError>>,
+}
+
+/// A pool of background tasks verifying blocks, each one with its own verifier instance.
+struct VerifierPool<B: BlockT> {
+	workers: Vec<TracingUnboundedSender<VerificationJob<B>>>,
+	next_worker: usize,
+	requires_imported_parent: bool,
+	metrics: Option<Metrics>,
+}
+
+impl<B: BlockT> VerifierPool<B> {
+	/// Create a pool of `workers` verification tasks, started using `spawn`.
+	fn new<V: 'static + Verifier<B> + Clone>(
+		verifier: V,
+		workers: NonZeroUsize,
+		metrics: Option<Metrics>,
+		spawn: impl Fn(future::BoxFuture<'static, ()>),
+	) -> Self {
+		let requires_imported_parent = verifier.requires_imported_parent();
+		let workers = (0..workers.get())
+			.map(|_| {
+				let (sender, receiver) =
+					tracing_unbounded("mpsc_import_queue_verification_worker", 100_000);
+				spawn(verification_worker(verifier.clone(), receiver, metrics.clone()).boxed());
+				sender
+			})
+			.collect::<Vec<_>>();
+
+		if let Some(metrics) = metrics.as_ref() {
+			metrics.block_verification_workers.set(workers.len() as u64);
+		}
+
+		Self { workers, next_worker: 0, requires_imported_parent, metrics }
+	}
+
+	/// Send the block to the next worker, in a round-robin fashion.
+	fn dispatch(&mut self, block: UnverifiedBlock<B>) -> PendingVerification<B> {
+		let (result, receiver) = oneshot::channel();
+		let worker = &self.workers[self.next_worker];
+		self.next_worker = (self.next_worker + 1) % self.workers.len();
+
+		if let Some(metrics) = self.metrics.as_ref() {
+			metrics.block_verification_pending.inc();
+		}
+
+		match worker.unbounded_send(VerificationJob { block, result }) {
+			Ok(()) => PendingVerification::Dispatched(receiver),
+			Err(err) => {
+				log::error!(target: LOG_TARGET, "Block verification worker is no longer alive");
+				if let Some(metrics) = self.metrics.as_ref() {
+					metrics.block_verification_pending.dec();
+				}
+				let peer = err.into_inner().block.peer;
+				PendingVerification::Ready(Box::new(Err(BlockImportError::VerificationFailed(
+					peer,
+					"Verification worker is no longer alive".into(),
+				))))
+			},
+		}
+	}
+}
+
+/// Verify the blocks received from the import task until the jobs channel is closed.
+async fn verification_worker<B: BlockT, V: Verifier<B>>(
+	mut verifier: V,
+	mut jobs: TracingUnboundedReceiver<VerificationJob<B>>,
+	metrics: Option<Metrics>,
+) {
+	while let Some(VerificationJob { block, result }) = jobs.next().await {
+		let verified = verify_single_block_metered(&mut verifier, block, metrics.as_ref()).await;
+		if let Some(metrics) = metrics.as_ref() {
+			metrics.block_verification_pending.dec();
+		}
+		// The import task may be gone, nothing to do about it.
+		let _ = result.send(verified);
+	}
+}
+
+/// Take from `blocks` the next group of blocks that can be verified concurrently.
+///
+/// If `requires_imported_parent` is set, the group is made of consecutive blocks where none is the
+/// parent of another one, as the verification of a block requires its parent to be imported.
+/// Otherwise any `max_len` consecutive blocks are verified together.
+fn next_verification_group<B: BlockT>(
+	blocks: &mut Peekable<impl Iterator<Item = IncomingBlock<B>>>,
+	max_len: usize,
+	requires_imported_parent: bool,
+) -> Vec<IncomingBlock<B>> {
+	let mut group: Vec<IncomingBlock<B>> = Vec::new();
+
+	while group.len() < max_len {
+		let depends_on_group = match blocks.peek() {
+			Some(_) if !requires_imported_parent => false,
+			Some(block) => block.header.as_ref().map_or(false, |header| {
+				group.iter().any(|queued| queued.hash == *header.parent_hash())
+			}),
+			None => break,
+		};
+
+		if depends_on_group {
+			break
+		}
+
+		group.extend(blocks.next());
+	}
+
+	group
+}
+
 /// Import several blocks at once, returning import result for each block.
 ///
+/// Blocks are imported in the given order. Groups of blocks not depending on each other are
+/// verified concurrently if `verification` is backed by a verifier pool.
+///
 /// This will yield after each imported block once, to ensure that other futures can
 /// be called as well.
 async fn import_many_blocks<B: BlockT, V: Verifier<B>>(
 	import_handle: &mut BoxBlockImport<B>,
 	blocks_origin: BlockOrigin,
 	blocks: Vec<IncomingBlock<B>>,
-	verifier: &mut V,
+	verification: &mut Verification<B, V>,
 	delay_between_blocks: Duration,
 	metrics: Option<Metrics>,
 ) -> ImportManyBlocksResult<B> {
@@ -413,57 +670,100 @@ async fn import_many_blocks<B: BlockT, V: Verifier<B>>(
 	let mut imported = 0;
 	let mut results = vec![];
 	let mut has_error = false;
-	let mut blocks = blocks.into_iter();
+	let mut blocks = blocks.into_iter().peekable();
+	let max_concurrency = verification.max_concurrency();
+	let requires_imported_parent = verification.requires_imported_parent();
 
 	// Blocks in the response/drain should be in ascending order.
 	loop {
 		// Is there any block left to import?
-		let block = match blocks.next() {
-			Some(b) => b,
-			None => {
-				// No block left to import, success!
-				return ImportManyBlocksResult { block_count: count, imported, results }
-			},
-		};
-
-		let block_number = block.header.as_ref().map(|h| *h.number());
-		let block_hash = block.hash;
-		let import_result = if has_error {
-			Err(BlockImportError::Cancelled)
-		} else {
-			// The actual import.
-			import_single_block_metered(
-				import_handle,
-				blocks_origin,
-				block,
-				verifier,
-				metrics.clone(),
-			)
-			.await
-		};
+		let group = next_verification_group(&mut blocks, max_concurrency, requires_imported_parent);
+		if group.is_empty() {
+			// No block left to import, success!
+			return ImportManyBlocksResult { block_count: count, imported, results }
+		}
 
 		if let Some(metrics) = metrics.as_ref() {
-			metrics.report_import::<B>(&import_result);
+			metrics.block_verification_group_size.observe(group.len() as f64);
 		}
 
-		if import_result.is_ok() {
-			trace!(
-				target: LOG_TARGET,
-				"Block imported successfully {:?} ({})",
-				block_number,
-				block_hash,
-			);
-			imported += 1;
-		} else {
-			has_error = true;
+		// Checks against the block import are done sequentially, then verification of the
+		// whole group is started before importing any block. A block whose parent is part of
+		// the group is checked as if its parent was already imported, if the parent fails to
+		// import the block is cancelled anyway.
+		let mut pending = Vec::with_capacity(group.len());
+		let mut group_failed = false;
+		for block in group {
+			let block_number = block.header.as_ref().map(|h| *h.number());
+			let block_hash = block.hash;
+			let parent_in_flight = block.header.as_ref().map_or(false, |header| {
+				pending.iter().any(|(_, hash, _)| hash == header.parent_hash())
+			});
+
+			let prepared = if has_error || group_failed {
+				Err(BlockImportError::Cancelled)
+			} else {
+				match prepare_single_block(import_handle, blocks_origin, block, parent_in_flight)
+					.await
+				{
+					Ok(PreparedBlock::Known(status)) => Ok(PendingVerification::Skipped(status)),
+					Ok(PreparedBlock::Unverified(block)) => {
+						trace!(
+							target: LOG_TARGET,
+							"Verifying block {:?} ({}) with parent {}",
+							block_number,
+							block_hash,
+							block.parent_hash(),
+						);
+						Ok(verification.start(*block, metrics.as_ref()).await)
+					},
+					Err(err) => {
+						group_failed = true;
+						Err(err)
+					},
+				}
+			};
+
+			pending.push((block_number, block_hash, prepared));
 		}
 
-		results.push((import_result, block_hash));
+		for (block_number, block_hash, prepared) in pending {
+			let import_result = match prepared {
+				_ if has_error => Err(BlockImportError::Cancelled),
+				Err(err) => Err(err),
+				Ok(PendingVerification::Skipped(status)) => Ok(status),
+				Ok(pending_verification) => match pending_verification.wait().await {
+					// The actual import.
+					Ok(verified) =>
+						import_verified_block_metered(import_handle, verified, metrics.as_ref())
+							.await,
+					Err(err) => Err(err),
+				},
+			};
 
-		if delay_between_blocks != Duration::default() && !has_error {
-			Delay::new(delay_between_blocks).await;
-		} else {
-			Yield::new().await
+			if let Some(metrics) = metrics.as_ref() {
+				metrics.report_import::<B>(&import_result);
+			}
+
+			if import_result.is_ok() {
+				trace!(
+					target: LOG_TARGET,
+					"Block imported successfully {:?} ({})",
+					block_number,
+					block_hash,
+				);
+				imported += 1;
+			} else {
+				has_error = true;
+			}
+
+			results.push((import_result, block_hash));
+
+			if delay_between_blocks != Duration::default() && !has_error {
+				Delay::new(delay_between_blocks).await;
+			} else {
+				Yield::new().await
+			}
 		}
 	}
 }
@@ -588,6 +888,277 @@ mod tests {
 		}
 	}
 
+	fn incoming_block(header: Header) -> IncomingBlock<Block> {
+		IncomingBlock {
+			hash: header.hash(),
+			header: Some(header),
+			body: None,
+			indexed_body: None,
+			justifications: None,
+			origin: None,
+			allow_missing_state: false,
+			import_existing: false,
+			state: None,
+			skip_execution: false,
+		}
+	}
+
+	fn header(number: BlockNumber, parent_hash: Hash) -> Header {
+		Header {
+			parent_hash,
+			number,
+			extrinsics_root: Hash::random(),
+			state_root: Default::default(),
+			digest: Default::default(),
+		}
+	}
+
+	#[test]
+	fn verification_groups_do_not_contain_dependent_blocks() {
+		let a1 = header(1, Hash::random());
+		let a2 = header(2, a1.hash());
+		let b1 = header(1, Hash::random());
+		let c1 = header(1, Hash::random());
+		let hashes = [a1.hash(), b1.hash(), a2.hash(), c1.hash()];
+
+		let blocks = vec![a1, b1, a2, c1].into_iter().map(incoming_block).collect::<Vec<_>>();
+		let mut blocks = blocks.into_iter().peekable();
+
+		let group = next_verification_group(&mut blocks, 8, true);
+		assert_eq!(group.iter().map(|b| b.hash).collect::<Vec<_>>(), hashes[..2]);
+
+		let group = next_verification_group(&mut blocks, 1, true);
+		assert_eq!(group.iter().map(|b| b.hash).collect::<Vec<_>>(), hashes[2..3]);
+
+		let group = next_verification_group(&mut blocks, 8, true);
+		assert_eq!(group.iter().map(|b| b.hash).collect::<Vec<_>>(), hashes[3..]);
+
+		assert!(next_verification_group(&mut blocks, 8, true).is_empty());
+	}
+
+	#[test]
+	fn verification_groups_contain_descendants_if_parent_is_not_required() {
+		let a1 = header(1, Hash::random());
+		let a2 = header(2, a1.hash());
+		let a3 = header(3, a2.hash());
+		let hashes = [a1.hash(), a2.hash(), a3.hash()];
+
+		let blocks = vec![a1, a2, a3].into_iter().map(incoming_block).collect::<Vec<_>>();
+		let mut blocks = blocks.into_iter().peekable();
+
+		let group = next_verification_group(&mut blocks, 2, false);
+		assert_eq!(group.iter().map(|b| b.hash).collect::<Vec<_>>(), hashes[..2]);
+
+		let group = next_verification_group(&mut blocks, 2, false);
+		assert_eq!(group.iter().map(|b| b.hash).collect::<Vec<_>>(), hashes[2..]);
+	}
+
+	#[test]
+	fn linear_chain_is_verified_concurrently() {
+		use std::{
+			collections::HashSet,
+			sync::{
+				atomic::{AtomicUsize, Ordering},
+				Arc,
+			},
+		};
+
+		/// Verifier only looking at the header, keeping track of concurrent verifications.
+		#[derive(Clone, Default)]
+		struct HeaderOnlyVerifier {
+			in_flight: Arc<AtomicUsize>,
+			max_in_flight: Arc<AtomicUsize>,
+		}
+
+		#[async_trait::async_trait]
+		impl Verifier<Block> for HeaderOnlyVerifier {
+			async fn verify(
+				&mut self,
+				block: BlockImportParams<Block>,
+			) -> Result<BlockImportParams<Block>, String> {
+				let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+				self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+				std::thread::sleep(Duration::from_millis(50));
+				self.in_flight.fetch_sub(1, Ordering::SeqCst);
+				Ok(BlockImportParams::new(block.origin, block.header))
+			}
+
+			fn requires_imported_parent(&self) -> bool {
+				false
+			}
+		}
+
+		/// Block import rejecting blocks whose parent was not imported.
+		struct ChainImport {
+			imported: HashSet<Hash>,
+		}
+
+		#[async_trait::async_trait]
+		impl BlockImport<Block> for ChainImport {
+			type Error = sp_consensus::Error;
+
+			async fn check_block(
+				&mut self,
+				block: BlockCheckParams<Block>,
+			) -> Result<ImportResult, Self::Error> {
+				if block.allow_missing_parent || self.imported.contains(&block.parent_hash) {
+					Ok(ImportResult::imported(false))
+				} else {
+					Ok(ImportResult::UnknownParent)
+				}
+			}
+
+			async fn import_block(
+				&mut self,
+				block: BlockImportParams<Block>,
+			) -> Result<ImportResult, Self::Error> {
+				if !self.imported.contains(block.header.parent_hash()) {
+					return Ok(ImportResult::UnknownParent)
+				}
+				self.imported.insert(block.header.hash());
+				Ok(ImportResult::imported(true))
+			}
+		}
+
+		#[derive(Default)]
+		struct ImportedLink {
+			imported: Vec<Hash>,
+		}
+
+		impl Link<Block> for ImportedLink {
+			fn blocks_processed(
+				&mut self,
+				_imported: usize,
+				_count: usize,
+				results: Vec<(Result<BlockImportStatus<BlockNumber>, BlockImportError>, Hash)>,
+			) {
+				for (result, hash) in results {
+					assert!(result.is_ok(), "{:?}", result.err());
+					self.imported.push(hash);
+				}
+			}
+		}
+
+		let thread_pool = futures::executor::ThreadPool::builder().pool_size(4).create().unwrap();
+		let (result_sender, mut result_port) = buffered_link::buffered_link(100_000);
+
+		let verifier = HeaderOnlyVerifier::default();
+		let max_in_flight = verifier.max_in_flight.clone();
+		let pool = VerifierPool::new(verifier, NonZeroUsize::new(4).unwrap(), None, |task| {
+			thread_pool.spawn_ok(task)
+		});
+
+		let genesis = Hash::random();
+		let block_import = ChainImport { imported: [genesis].into_iter().collect() };
+		let (worker, _finality_sender, block_import_sender) = BlockImportWorker::with_verification(
+			result_sender,
+			Verification::<Block, HeaderOnlyVerifier>::Parallel(pool),
+			Box::new(block_import),
+			None,
+			None,
+		);
+		futures::pin_mut!(worker);
+
+		let mut headers = vec![header(1, genesis)];
+		for number in 2..=8 {
+			let parent_hash = headers.last().unwrap().hash();
+			headers.push(header(number, parent_hash));
+		}
+		let expected = headers.iter().map(|h| h.hash()).collect::<Vec<_>>();
+
+		block_import_sender
+			.unbounded_send(worker_messages::ImportBlocks(
+				BlockOrigin::NetworkInitialSync,
+				headers.into_iter().map(incoming_block).collect(),
+			))
+			.unwrap();
+
+		let mut link = ImportedLink::default();
+		block_on(futures::future::poll_fn(|cx| {
+			while link.imported.len() < expected.len() {
+				match Future::poll(Pin::new(&mut worker), cx) {
+					Poll::Pending => {},
+					Poll::Ready(()) => panic!("import queue worker should not conclude."),
+				}
+
+				result_port.poll_actions(cx, &mut link).unwrap();
+			}
+
+			Poll::Ready(())
+		}));
+
+		assert_eq!(link.imported, expected);
+		assert!(max_in_flight.load(Ordering::SeqCst) > 1);
+	}
+
+	#[test]
+	fn parallel_verification_keeps_import_order() {
+		#[derive(Default)]
+		struct ImportOrderLink {
+			imported: Vec<Hash>,
+		}
+
+		impl Link<Block> for ImportOrderLink {
+			fn blocks_processed(
+				&mut self,
+				_imported: usize,
+				_count: usize,
+				results: Vec<(Result<BlockImportStatus<BlockNumber>, BlockImportError>, Hash)>,
+			) {
+				for (result, hash) in results {
+					assert!(result.is_ok());
+					self.imported.push(hash);
+				}
+			}
+		}
+
+		let thread_pool = futures::executor::ThreadPool::new().unwrap();
+		let (result_sender, mut result_port) = buffered_link::buffered_link(100_000);
+
+		let pool = VerifierPool::new((), NonZeroUsize::new(4).unwrap(), None, |task| {
+			thread_pool.spawn_ok(task)
+		});
+		let (worker, _finality_sender, block_import_sender) = BlockImportWorker::with_verification(
+			result_sender,
+			Verification::<Block, ()>::Parallel(pool),
+			Box::new(()),
+			None,
+			None,
+		);
+		futures::pin_mut!(worker);
+
+		let a1 = header(1, Hash::random());
+		let a2 = header(2, a1.hash());
+		let a3 = header(3, a2.hash());
+		let b1 = header(1, Hash::random());
+		let c1 = header(1, Hash::random());
+		let headers = vec![a1, b1, c1, a2, a3];
+		let expected = headers.iter().map(|h| h.hash()).collect::<Vec<_>>();
+
+		block_import_sender
+			.unbounded_send(worker_messages::ImportBlocks(
+				BlockOrigin::NetworkInitialSync,
+				headers.into_iter().map(incoming_block).collect(),
+			))
+			.unwrap();
+
+		let mut link = ImportOrderLink::default();
+		block_on(futures::future::poll_fn(|cx| {
+			while link.imported.len() < expected.len() {
+				match Future::poll(Pin::new(&mut worker), cx) {
+					Poll::Pending => {},
+					Poll::Ready(()) => panic!("import queue worker should not conclude."),
+				}
+
+				result_port.poll_actions(cx, &mut link).unwrap();
+			}
+
+			Poll::Ready(())
+		}));
+
+		assert_eq!(link.imported, expected);
+	}
+
 	#[test]
 	fn prioritizes_finality_work_over_block_import() {
 		let (result_sender, mut result_port) = buffered_link::buffered_link(100_000);
@@ -33,7 +33,12 @@ pub use import_queue::{
 };
 
 mod longest_chain;
+mod select_chain;
 
 pub mod shared_data;
 
 pub use longest_chain::LongestChain;
+pub use select_chain::{
+	AuthorDiversity, AuxWeight, ChainLength, ChainSelectionRule, ChainWeight, FinalizedAncestry,
+	RuleBasedSelectChain,
+};
@@ -82,49 +82,13 @@ where
 		base_hash: Block::Hash,
 		maybe_max_number: Option<NumberFor<Block>>,
 	) -> sp_blockchain::Result<Block::Hash> {
-		use sp_blockchain::Error::{Application, MissingHeader};
-		let blockchain = self.backend.blockchain();
-
-		let mut current_head = self.best_header()?;
-		let mut best_hash = current_head.hash();
-
-		let base_header = blockchain
-			.header(base_hash)?
-			.ok_or_else(|| MissingHeader(base_hash.to_string()))?;
-		let base_number = *base_header.number();
-
-		if let Some(max_number) = maybe_max_number {
-			if max_number < base_number {
-				let msg = format!(
-					"Requested a finality target using max number {} below the base number {}",
-					max_number, base_number
-				);
-				return Err(Application(msg.into()))
-			}
-
-			while current_head.number() > &max_number {
-				best_hash = *current_head.parent_hash();
-				current_head = blockchain
-					.header(best_hash)?
-					.ok_or_else(|| MissingHeader(format!("{best_hash:?}")))?;
-			}
-		}
-
-		while current_head.hash() != base_hash {
-			if *current_head.number() < base_number {
-				let msg = format!(
-					"Requested a finality target using a base {:?} not in the best chain {:?}",
-					base_hash, best_hash,
-				);
-				return Err(Application(msg.into()))
-			}
-			let current_hash = *current_head.parent_hash();
-			current_head = blockchain
-				.header(current_hash)?
-				.ok_or_else(|| MissingHeader(format!("{best_hash:?}")))?;
-		}
-
-		Ok(best_hash)
+		let best_header = self.best_header()?;
+		finality_target_on_chain(
+			self.backend.blockchain(),
+			best_header,
+			base_hash,
+			maybe_max_number,
+		)
 	}
 
 	fn leaves(&self) -> Result<Vec<<Block as BlockT>::Hash>, sp_blockchain::Error> {
@@ -155,3 +119,61 @@ where
 			.map_err(|e| ConsensusError::ChainLookup(e.to_string()))
 	}
 }
+
+/// Returns the highest ancestor of `best_header` (inclusive) that is a descendant of
+/// `base_hash` and not above `maybe_max_number`.
+///
+/// Fails if `base_hash` is not an ancestor of `best_header`.
+pub(crate) fn finality_target_on_chain<Block, BC>(
+	blockchain: &BC,
+	best_header: Block::Header,
+	base_hash: Block::Hash,
+	maybe_max_number: Option<NumberFor<Block>>,
+) -> sp_blockchain::Result<Block::Hash>
+where
+	Block: BlockT,
+	BC: HeaderBackend<Block> + ?Sized,
+{
+	use sp_blockchain::Error::{Application, MissingHeader};
+
+	let mut current_head = best_header;
+	let mut best_hash = current_head.hash();
+
+	let base_header = blockchain
+		.header(base_hash)?
+		.ok_or_else(|| MissingHeader(base_hash.to_string()))?;
+	let base_number = *base_header.number();
+
+	if let Some(max_number) = maybe_max_number {
+		if max_number < base_number {
+			let msg = format!(
+				"Requested a finality target using max number {} below the base number {}",
+				max_number, base_number
+			);
+			return Err(Application(msg.into()))
+		}
+
+		while current_head.number() > &max_number {
+			best_hash = *current_head.parent_hash();
+			current_head = blockchain
+				.header(best_hash)?
+				.ok_or_else(|| MissingHeader(format!("{best_hash:?}")))?;
+		}
+	}
+
+	while current_head.hash() != base_hash {
+		if *current_head.number() < base_number {
+			let msg = format!(
+				"Requested a finality target using a base {:?} not in the best chain {:?}",
+				base_hash, best_hash,
+			);
+			return Err(Application(msg.into()))
+		}
+		let current_hash = *current_head.parent_hash();
+		current_head = blockchain
+			.header(current_hash)?
+			.ok_or_else(|| MissingHeader(format!("{best_hash:?}")))?;
+	}
+
+	Ok(best_hash)
+}
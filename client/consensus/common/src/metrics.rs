@@ -19,8 +19,8 @@
 //! Metering tools for consensus
 
 use prometheus_endpoint::{
-	register, CounterVec, Histogram, HistogramOpts, HistogramVec, Opts, PrometheusError, Registry,
-	U64,
+	register, CounterVec, Gauge, Histogram, HistogramOpts, HistogramVec, Opts, PrometheusError,
+	Registry, U64,
 };
 
 use sp_runtime::traits::{Block as BlockT, NumberFor};
@@ -34,6 +34,9 @@ pub(crate) struct Metrics {
 	pub block_verification_time: HistogramVec,
 	pub block_verification_and_import_time: Histogram,
 	pub justification_import_time: Histogram,
+	pub block_verification_workers: Gauge<U64>,
+	pub block_verification_pending: Gauge<U64>,
+	pub block_verification_group_size: Histogram,
 }
 
 impl Metrics {
@@ -73,6 +76,30 @@ impl Metrics {
 				))?,
 				registry,
 			)?,
+			block_verification_workers: register(
+				Gauge::new(
+					"substrate_block_verification_workers",
+					"Number of tasks verifying blocks in parallel",
+				)?,
+				registry,
+			)?,
+			block_verification_pending: register(
+				Gauge::new(
+					"substrate_block_verification_pending",
+					"Number of blocks waiting to be verified by the verification workers",
+				)?,
+				registry,
+			)?,
+			block_verification_group_size: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"substrate_block_verification_group_size",
+						"Number of blocks whose verification was started concurrently",
+					)
+					.buckets(vec![1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0]),
+				)?,
+				registry,
+			)?,
 		})
 	}
 
@@ -0,0 +1,278 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Rule based chain selection.
+//!
+//! [`RuleBasedSelectChain`] picks the best chain among the current leaves by weighing each of
+//! them with a list of [`ChainSelectionRule`]s. Rules are applied in the order they were added:
+//! the chain with the highest weight according to the first rule wins, ties are broken by the
+//! next rule and so on. A rule may also exclude a chain from selection altogether.
+
+use crate::longest_chain::finality_target_on_chain;
+use codec::Decode;
+use sc_client_api::backend;
+use sp_blockchain::{Backend, HeaderBackend};
+use sp_consensus::{Error as ConsensusError, SelectChain};
+use sp_runtime::traits::{Block as BlockT, Header, NumberFor, UniqueSaturatedInto, Zero};
+use std::{collections::HashSet, marker::PhantomData, sync::Arc};
+
+/// Weight of a chain as computed by a [`ChainSelectionRule`].
+pub type ChainWeight = u128;
+
+/// A single rule used by [`RuleBasedSelectChain`] to weigh chains.
+pub trait ChainSelectionRule<B, Block: BlockT>: Send + Sync {
+	/// Returns the weight of the chain ending in `leaf`.
+	///
+	/// Chains with higher weights are preferred. Returning `None` excludes the chain from
+	/// selection.
+	fn weight(
+		&self,
+		backend: &B,
+		leaf: &Block::Header,
+	) -> sp_blockchain::Result<Option<ChainWeight>>;
+}
+
+/// Only allows chains that contain the last finalized block.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FinalizedAncestry;
+
+impl<B, Block> ChainSelectionRule<B, Block> for FinalizedAncestry
+where
+	B: backend::Backend<Block>,
+	Block: BlockT,
+{
+	fn weight(
+		&self,
+		backend: &B,
+		leaf: &Block::Header,
+	) -> sp_blockchain::Result<Option<ChainWeight>> {
+		let blockchain = backend.blockchain();
+		let finalized_hash = blockchain.info().finalized_hash;
+		let ancestor =
+			sp_blockchain::lowest_common_ancestor(blockchain, leaf.hash(), finalized_hash)?;
+		Ok((ancestor.hash == finalized_hash).then_some(0))
+	}
+}
+
+/// Prefers longer chains.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChainLength;
+
+impl<B, Block> ChainSelectionRule<B, Block> for ChainLength
+where
+	B: backend::Backend<Block>,
+	Block: BlockT,
+{
+	fn weight(&self, _: &B, leaf: &Block::Header) -> sp_blockchain::Result<Option<ChainWeight>> {
+		Ok(Some((*leaf.number()).unique_saturated_into()))
+	}
+}
+
+/// Prefers chains with a higher weight stored in the aux storage for their head.
+///
+/// This can be used to reuse weights that consensus engines already track, e.g. the cumulative
+/// block weight written by BABE. Heads without a stored weight have a weight of zero.
+pub struct AuxWeight<Block: BlockT, W> {
+	key: fn(&Block::Hash) -> Vec<u8>,
+	_phantom: PhantomData<fn() -> W>,
+}
+
+impl<Block: BlockT, W> AuxWeight<Block, W> {
+	/// Create a new rule reading the SCALE-encoded weight of a block at `key(block_hash)`.
+	pub fn new(key: fn(&Block::Hash) -> Vec<u8>) -> Self {
+		AuxWeight { key, _phantom: Default::default() }
+	}
+}
+
+impl<B, Block, W> ChainSelectionRule<B, Block> for AuxWeight<Block, W>
+where
+	B: backend::Backend<Block>,
+	Block: BlockT,
+	W: Decode + Into<ChainWeight>,
+{
+	fn weight(
+		&self,
+		backend: &B,
+		leaf: &Block::Header,
+	) -> sp_blockchain::Result<Option<ChainWeight>> {
+		let weight = match backend::Backend::get_aux(backend, &(self.key)(&leaf.hash()))? {
+			Some(encoded) => W::decode(&mut &encoded[..])
+				.map_err(|e| sp_blockchain::Error::Backend(format!("Invalid chain weight: {e}")))?
+				.into(),
+			None => 0,
+		};
+		Ok(Some(weight))
+	}
+}
+
+/// Prefers chains whose most recent blocks were authored by more distinct authors.
+pub struct AuthorDiversity<Block: BlockT> {
+	depth: u32,
+	author_of: Box<dyn Fn(&Block::Header) -> Option<Vec<u8>> + Send + Sync>,
+}
+
+impl<Block: BlockT> AuthorDiversity<Block> {
+	/// Create a new rule counting distinct authors among the last `depth` blocks of a chain.
+	///
+	/// `author_of` extracts an identifier of the author from a header, usually from its
+	/// pre-runtime digest. Blocks without a known author are not counted.
+	pub fn new(
+		depth: u32,
+		author_of: impl Fn(&Block::Header) -> Option<Vec<u8>> + Send + Sync + 'static,
+	) -> Self {
+		AuthorDiversity { depth, author_of: Box::new(author_of) }
+	}
+}
+
+impl<B, Block> ChainSelectionRule<B, Block> for AuthorDiversity<Block>
+where
+	B: backend::Backend<Block>,
+	Block: BlockT,
+{
+	fn weight(
+		&self,
+		backend: &B,
+		leaf: &Block::Header,
+	) -> sp_blockchain::Result<Option<ChainWeight>> {
+		let blockchain = backend.blockchain();
+		let mut authors = HashSet::new();
+		let mut current = leaf.clone();
+		for _ in 0..self.depth {
+			authors.extend((self.author_of)(&current));
+			if current.number().is_zero() {
+				break
+			}
+			let parent_hash = *current.parent_hash();
+			current = blockchain
+				.header(parent_hash)?
+				.ok_or_else(|| sp_blockchain::Error::MissingHeader(format!("{parent_hash:?}")))?;
+		}
+		Ok(Some(authors.len() as ChainWeight))
+	}
+}
+
+/// A [`SelectChain`] composed of [`ChainSelectionRule`]s.
+///
+/// Among the leaves allowed by all rules, the one with the highest weights (compared rule by
+/// rule, in the order the rules were added) is selected as best. Remaining ties are resolved in
+/// favour of the current best block. If no leaf is allowed, the last finalized block is used.
+pub struct RuleBasedSelectChain<B, Block: BlockT> {
+	backend: Arc<B>,
+	rules: Vec<Arc<dyn ChainSelectionRule<B, Block>>>,
+}
+
+impl<B, Block: BlockT> Clone for RuleBasedSelectChain<B, Block> {
+	fn clone(&self) -> Self {
+		RuleBasedSelectChain { backend: self.backend.clone(), rules: self.rules.clone() }
+	}
+}
+
+impl<B, Block> RuleBasedSelectChain<B, Block>
+where
+	B: backend::Backend<Block>,
+	Block: BlockT,
+{
+	/// Create a new instance without any rules.
+	///
+	/// Until rules are added with [`Self::with_rule`], all leaves weigh the same and the current
+	/// best block is kept.
+	pub fn new(backend: Arc<B>) -> Self {
+		RuleBasedSelectChain { backend, rules: Vec::new() }
+	}
+
+	/// Add a rule with a lower priority than all rules added before.
+	pub fn with_rule(mut self, rule: impl ChainSelectionRule<B, Block> + 'static) -> Self {
+		self.rules.push(Arc::new(rule));
+		self
+	}
+
+	fn weigh(&self, leaf: &Block::Header) -> sp_blockchain::Result<Option<Vec<ChainWeight>>> {
+		self.rules.iter().map(|rule| rule.weight(&self.backend, leaf)).collect()
+	}
+
+	fn best_header(&self) -> sp_blockchain::Result<<Block as BlockT>::Header> {
+		use sp_blockchain::Error::MissingHeader;
+		let blockchain = self.backend.blockchain();
+		let _import_lock = self.backend.get_import_lock().read();
+		let info = blockchain.info();
+
+		let mut best: Option<(Vec<ChainWeight>, Block::Header)> = None;
+		for leaf in blockchain.leaves()? {
+			let header =
+				blockchain.header(leaf)?.ok_or_else(|| MissingHeader(format!("{leaf:?}")))?;
+			let Some(weights) = self.weigh(&header)? else { continue };
+			let is_better = match &best {
+				None => true,
+				Some((best_weights, _)) =>
+					weights > *best_weights || (weights == *best_weights && leaf == info.best_hash),
+			};
+			if is_better {
+				best = Some((weights, header));
+			}
+		}
+
+		match best {
+			Some((_, header)) => Ok(header),
+			None => blockchain
+				.header(info.finalized_hash)?
+				.ok_or_else(|| MissingHeader(format!("{:?}", info.finalized_hash))),
+		}
+	}
+
+	fn finality_target(
+		&self,
+		base_hash: Block::Hash,
+		maybe_max_number: Option<NumberFor<Block>>,
+	) -> sp_blockchain::Result<Block::Hash> {
+		let best_header = self.best_header()?;
+		finality_target_on_chain(
+			self.backend.blockchain(),
+			best_header,
+			base_hash,
+			maybe_max_number,
+		)
+	}
+}
+
+#[async_trait::async_trait]
+impl<B, Block> SelectChain<Block> for RuleBasedSelectChain<B, Block>
+where
+	B: backend::Backend<Block>,
+	Block: BlockT,
+{
+	async fn leaves(&self) -> Result<Vec<<Block as BlockT>::Hash>, ConsensusError> {
+		self.backend
+			.blockchain()
+			.leaves()
+			.map_err(|e| ConsensusError::ChainLookup(e.to_string()))
+	}
+
+	async fn best_chain(&self) -> Result<<Block as BlockT>::Header, ConsensusError> {
+		RuleBasedSelectChain::best_header(self)
+			.map_err(|e| ConsensusError::ChainLookup(e.to_string()))
+	}
+
+	async fn finality_target(
+		&self,
+		base_hash: Block::Hash,
+		maybe_max_number: Option<NumberFor<Block>>,
+	) -> Result<Block::Hash, ConsensusError> {
+		RuleBasedSelectChain::finality_target(self, base_hash, maybe_max_number)
+			.map_err(|e| ConsensusError::ChainLookup(e.to_string()))
+	}
+}
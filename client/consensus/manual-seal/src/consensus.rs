@@ -44,4 +44,12 @@ pub trait ConsensusDataProvider<B: BlockT>: Send + Sync {
 		inherents: &InherentData,
 		proof: Self::Proof,
 	) -> Result<(), Error>;
+
+	/// The minimal time in milliseconds between the timestamps of two consecutive blocks.
+	///
+	/// Slot based engines return their slot duration, so that every block is in a later slot
+	/// than the one before.
+	fn min_block_time(&self) -> u64 {
+		0
+	}
 }
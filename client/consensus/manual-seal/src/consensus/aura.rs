@@ -76,8 +76,9 @@ where
 		_parent: &B::Header,
 		inherents: &InherentData,
 	) -> Result<Digest, Error> {
-		let timestamp =
-			inherents.timestamp_inherent_data()?.expect("Timestamp is always present; qed");
+		let timestamp = inherents
+			.timestamp_inherent_data()?
+			.ok_or_else(|| Error::StringError("No timestamp inherent data".into()))?;
 
 		// we always calculate the new slot number based on the current time-stamp and the slot
 		// duration.
@@ -97,4 +98,8 @@ where
 	) -> Result<(), Error> {
 		Ok(())
 	}
+
+	fn min_block_time(&self) -> u64 {
+		self.slot_duration.as_millis()
+	}
 }
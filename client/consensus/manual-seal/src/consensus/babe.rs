@@ -37,7 +37,6 @@ use sp_api::ProvideRuntimeApi;
 use sp_blockchain::{HeaderBackend, HeaderMetadata};
 use sp_consensus_babe::{
 	digests::{NextEpochDescriptor, PreDigest, SecondaryPlainPreDigest},
-	inherents::BabeInherentData,
 	AuthorityId, BabeApi, BabeAuthorityWeight, BabeConfiguration, ConsensusLog, BABE_ENGINE_ID,
 };
 use sp_consensus_slots::Slot;
@@ -163,6 +162,16 @@ where
 		})
 	}
 
+	/// The slot of the block being authored, derived from the timestamp inherent so that it
+	/// follows timestamps set through `EngineCommand::SetNextTimestamp`.
+	fn slot(&self, inherents: &InherentData) -> Result<Slot, Error> {
+		let timestamp = inherents
+			.timestamp_inherent_data()?
+			.ok_or_else(|| Error::StringError("No timestamp inherent data".into()))?;
+
+		Ok(Slot::from_timestamp(timestamp, self.config.slot_duration()))
+	}
+
 	fn epoch(&self, parent: &B::Header, slot: Slot) -> Result<Epoch, Error> {
 		let epoch_changes = self.epoch_changes.shared_data();
 		let epoch_descriptor = epoch_changes
@@ -200,9 +209,7 @@ where
 	type Proof = P;
 
 	fn create_digest(&self, parent: &B::Header, inherents: &InherentData) -> Result<Digest, Error> {
-		let slot = inherents
-			.babe_inherent_data()?
-			.ok_or_else(|| Error::StringError("No babe inherent data".into()))?;
+		let slot = self.slot(inherents)?;
 		let epoch = self.epoch(parent, slot)?;
 
 		// this is a dev node environment, we should always be able to claim a slot.
@@ -267,9 +274,7 @@ where
 		inherents: &InherentData,
 		_proof: Self::Proof,
 	) -> Result<(), Error> {
-		let slot = inherents
-			.babe_inherent_data()?
-			.ok_or_else(|| Error::StringError("No babe inherent data".into()))?;
+		let slot = self.slot(inherents)?;
 		let epoch_changes = self.epoch_changes.shared_data();
 		let mut epoch_descriptor = epoch_changes
 			.epoch_descriptor_for_child_of(
@@ -289,12 +294,6 @@ where
 
 		if !has_authority {
 			log::info!(target: LOG_TARGET, "authority not found");
-			let timestamp = inherents
-				.timestamp_inherent_data()?
-				.ok_or_else(|| Error::StringError("No timestamp inherent data".into()))?;
-
-			let slot = Slot::from_timestamp(timestamp, self.config.slot_duration());
-
 			// manually hard code epoch descriptor
 			epoch_descriptor = match epoch_descriptor {
 				ViableEpochDescriptor::Signaled(identifier, _header) =>
@@ -315,4 +314,8 @@ where
 
 		Ok(())
 	}
+
+	fn min_block_time(&self) -> u64 {
+		self.config.slot_duration().as_millis()
+	}
 }
@@ -0,0 +1,153 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Utilities for manipulating the state and history of a manual seal chain, used to implement
+//! the development RPCs.
+
+use crate::Error;
+use sc_client_api::backend::{Backend as ClientBackend, StateBackend};
+use sp_api::{Core, ProvideRuntimeApi, StorageChanges};
+use sp_blockchain::HeaderBackend;
+use sp_core::storage::{well_known_keys, ChildInfo, StorageData, StorageKey};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, Zero};
+use std::collections::BTreeMap;
+
+/// Something that can rewrite the post-state of a block proposed by the manual seal engine.
+pub trait StateOverride<B: BlockT>: Send + Sync {
+	/// Modify `changes`, the storage changes of the block built on top of `parent`, and update
+	/// the state root in `header` accordingly.
+	fn apply(
+		&self,
+		parent: &B::Header,
+		header: &mut B::Header,
+		changes: &mut StorageChanges<B>,
+	) -> Result<(), Error>;
+}
+
+/// A [`StateOverride`] that sets the given top-level storage values.
+///
+/// The storage root is recomputed from the parent state, so the resulting block can not be
+/// re-executed by other nodes. This is only meant to be used on development chains.
+pub struct StorageOverride<'a, C, CB> {
+	/// Client used to fetch the state version of the runtime.
+	pub client: &'a C,
+	/// Backend used to access the parent state.
+	pub backend: &'a CB,
+	/// The storage values to set, `None` removes the key.
+	pub changes: Vec<(StorageKey, Option<StorageData>)>,
+}
+
+impl<'a, B, C, CB> StateOverride<B> for StorageOverride<'a, C, CB>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + Send + Sync,
+	C::Api: Core<B>,
+	CB: ClientBackend<B>,
+{
+	fn apply(
+		&self,
+		parent: &B::Header,
+		header: &mut B::Header,
+		changes: &mut StorageChanges<B>,
+	) -> Result<(), Error> {
+		if self
+			.changes
+			.iter()
+			.any(|(key, _)| well_known_keys::is_child_storage_key(&key.0))
+		{
+			return Err(Error::StringError("Overriding child storage is not supported".into()))
+		}
+
+		let parent_hash = parent.hash();
+		let state_version = self
+			.client
+			.runtime_api()
+			.version(parent_hash)
+			.map_err(|e| Error::StringError(e.to_string()))?
+			.state_version();
+		let state = self.backend.state_at(parent_hash)?;
+
+		let mut main_changes = changes.main_storage_changes.drain(..).collect::<BTreeMap<_, _>>();
+		main_changes.extend(
+			self.changes
+				.iter()
+				.map(|(key, value)| (key.0.clone(), value.clone().map(|v| v.0))),
+		);
+		changes.main_storage_changes = main_changes.into_iter().collect();
+
+		let child_infos = changes
+			.child_storage_changes
+			.iter()
+			.map(|(storage_key, _)| ChildInfo::new_default(storage_key))
+			.collect::<Vec<_>>();
+		let (root, transaction) = state.full_storage_root(
+			changes.main_storage_changes.iter().map(|(k, v)| (&k[..], v.as_deref())),
+			child_infos.iter().zip(changes.child_storage_changes.iter()).map(
+				|(child_info, (_, delta))| {
+					(child_info, delta.iter().map(|(k, v)| (&k[..], v.as_deref())))
+				},
+			),
+			state_version,
+		);
+
+		changes.transaction = transaction;
+		changes.transaction_storage_root = root;
+		header.set_state_root(root);
+
+		Ok(())
+	}
+}
+
+/// Revert the best chain back to the block with the given `hash`.
+///
+/// The block must be part of the best chain and finalized blocks are never reverted.
+pub fn revert_to<B, C, CB>(client: &C, backend: &CB, hash: B::Hash) -> Result<(), Error>
+where
+	B: BlockT,
+	C: HeaderBackend<B>,
+	CB: ClientBackend<B>,
+{
+	let info = client.info();
+	let number = client.number(hash)?.ok_or_else(|| Error::BlockNotFound(format!("{}", hash)))?;
+
+	if client.hash(number)? != Some(hash) {
+		return Err(Error::StringError(format!("Block {} is not part of the best chain", hash)))
+	}
+
+	if number < info.finalized_number {
+		return Err(Error::StringError(format!(
+			"Can not revert past the last finalized block #{}",
+			info.finalized_number,
+		)))
+	}
+
+	let to_revert = info.best_number - number;
+	if to_revert.is_zero() {
+		return Ok(())
+	}
+
+	let (reverted, _) = backend.revert(to_revert, false)?;
+	if reverted != to_revert {
+		return Err(Error::StringError(format!(
+			"Only reverted {} out of {} blocks",
+			reverted, to_revert,
+		)))
+	}
+
+	Ok(())
+}
@@ -64,6 +64,9 @@ pub enum Error {
 	/// Supplied parent_hash doesn't exist in chain
 	#[error("Supplied parent_hash: {0} doesn't exist in chain")]
 	BlockNotFound(String),
+	/// The engine was started without access to the client backend
+	#[error("Client backend is not available to the manual seal engine")]
+	BackendUnavailable,
 	/// Some string error
 	#[error("{0}")]
 	StringError(String),
@@ -42,23 +42,28 @@ mod finalize_block;
 mod seal_block;
 
 pub mod consensus;
+pub mod dev;
 pub mod rpc;
 
 pub use self::{
 	consensus::ConsensusDataProvider,
+	dev::{StateOverride, StorageOverride},
 	error::Error,
 	finalize_block::{finalize_block, FinalizeBlockParams},
 	rpc::{CreatedBlock, EngineCommand},
-	seal_block::{seal_block, SealBlockParams, MAX_PROPOSAL_DURATION},
+	seal_block::{seal_block, BlockTime, SealBlockParams, MAX_PROPOSAL_DURATION},
 };
 use sc_transaction_pool_api::TransactionPool;
-use sp_api::ProvideRuntimeApi;
+use sp_api::{Core, ProvideRuntimeApi};
 
 const LOG_TARGET: &str = "manual-seal";
 
 /// The `ConsensusEngineId` of Manual Seal.
 pub const MANUAL_SEAL_ENGINE_ID: ConsensusEngineId = [b'm', b'a', b'n', b'l'];
 
+/// The maximum number of blocks a single [`EngineCommand::JumpBlocks`] may create.
+pub const MAX_JUMP_BLOCKS: u32 = 1024;
+
 /// The verifier for the manual seal engine; instantly finalizes.
 struct ManualSealVerifier;
 
@@ -87,7 +92,7 @@ where
 }
 
 /// Params required to start the instant sealing authorship task.
-pub struct ManualSealParams<B: BlockT, BI, E, C: ProvideRuntimeApi<B>, TP, SC, CS, CIDP, P> {
+pub struct ManualSealParams<B: BlockT, BI, E, C: ProvideRuntimeApi<B>, CB, TP, SC, CS, CIDP, P> {
 	/// Block import instance.
 	pub block_import: BI,
 
@@ -97,6 +102,12 @@ pub struct ManualSealParams<B: BlockT, BI, E, C: ProvideRuntimeApi<B>, TP, SC, C
 	/// Client instance
 	pub client: Arc<C>,
 
+	/// Client backend, required by the commands manipulating the state and history of the chain
+	/// ([`EngineCommand::SetStorage`] and [`EngineCommand::RevertTo`]).
+	///
+	/// If `None`, these commands fail with [`Error::BackendUnavailable`].
+	pub backend: Option<Arc<CB>>,
+
 	/// Shared reference to the transaction pool.
 	pub pool: Arc<TP>,
 
@@ -156,16 +167,18 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 		mut block_import,
 		mut env,
 		client,
+		backend,
 		pool,
 		mut commands_stream,
 		select_chain,
 		consensus_data_provider,
 		create_inherent_data_providers,
-	}: ManualSealParams<B, BI, E, C, TP, SC, CS, CIDP, P>,
+	}: ManualSealParams<B, BI, E, C, CB, TP, SC, CS, CIDP, P>,
 ) where
 	B: BlockT + 'static,
 	BI: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
-	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + 'static,
+	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + Send + Sync + 'static,
+	C::Api: Core<B>,
 	CB: ClientBackend<B> + 'static,
 	E: Environment<B> + 'static,
 	E::Proposer: Proposer<B, Proof = P>,
@@ -175,6 +188,9 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 	CIDP: CreateInherentDataProviders<B, ()>,
 	P: codec::Encode + Send + Sync + 'static,
 {
+	// keeps the time moving forward, also after `EngineCommand::SetNextTimestamp`.
+	let mut block_time = BlockTime::default();
+
 	while let Some(command) = commands_stream.next().await {
 		match command {
 			EngineCommand::SealNewBlock { create_empty, finalize, parent_hash, sender } => {
@@ -190,6 +206,8 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 					pool: pool.clone(),
 					client: client.clone(),
 					create_inherent_data_providers: &create_inherent_data_providers,
+					block_time: Some(&mut block_time),
+					state_override: None,
 				})
 				.await;
 			},
@@ -204,6 +222,81 @@ pub async fn run_manual_seal<B, BI, CB, E, C, TP, SC, CS, CIDP, P>(
 				})
 				.await
 			},
+			EngineCommand::SetNextTimestamp { timestamp, mut sender } => {
+				block_time.set_next_timestamp(timestamp);
+				rpc::send_result(&mut sender, Ok(()))
+			},
+			EngineCommand::JumpBlocks { count, finalize, mut sender } => {
+				if count > MAX_JUMP_BLOCKS {
+					rpc::send_result(
+						&mut sender,
+						Err(Error::StringError(format!(
+							"Can not jump more than {} blocks at once",
+							MAX_JUMP_BLOCKS
+						))),
+					);
+					continue
+				}
+
+				let mut result = Err(Error::StringError("Can not jump zero blocks".into()));
+				for _ in 0..count {
+					let (block_sender, receiver) = futures::channel::oneshot::channel();
+					seal_block(SealBlockParams {
+						sender: Some(block_sender),
+						parent_hash: None,
+						finalize,
+						create_empty: true,
+						env: &mut env,
+						select_chain: &select_chain,
+						block_import: &mut block_import,
+						consensus_data_provider: consensus_data_provider.as_deref(),
+						pool: pool.clone(),
+						client: client.clone(),
+						create_inherent_data_providers: &create_inherent_data_providers,
+						block_time: Some(&mut block_time),
+						state_override: None,
+					})
+					.await;
+
+					result = receiver.await.map_err(Error::from).and_then(|created| created);
+					if result.is_err() {
+						break
+					}
+				}
+				rpc::send_result(&mut sender, result)
+			},
+			EngineCommand::SetStorage { changes, mut sender } => {
+				let Some(backend) = &backend else {
+					rpc::send_result(&mut sender, Err(Error::BackendUnavailable));
+					continue
+				};
+
+				let state_override =
+					StorageOverride { client: &*client, backend: &**backend, changes };
+				seal_block(SealBlockParams {
+					sender,
+					parent_hash: None,
+					finalize: false,
+					create_empty: true,
+					env: &mut env,
+					select_chain: &select_chain,
+					block_import: &mut block_import,
+					consensus_data_provider: consensus_data_provider.as_deref(),
+					pool: pool.clone(),
+					client: client.clone(),
+					create_inherent_data_providers: &create_inherent_data_providers,
+					block_time: Some(&mut block_time),
+					state_override: Some(&state_override),
+				})
+				.await;
+			},
+			EngineCommand::RevertTo { hash, mut sender } => {
+				let result = match &backend {
+					Some(backend) => dev::revert_to(&*client, &**backend, hash),
+					None => Err(Error::BackendUnavailable),
+				};
+				rpc::send_result(&mut sender, result)
+			},
 		}
 	}
 }
@@ -224,7 +317,8 @@ pub async fn run_instant_seal<B, BI, CB, E, C, TP, SC, CIDP, P>(
 ) where
 	B: BlockT + 'static,
 	BI: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
-	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + 'static,
+	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + Send + Sync + 'static,
+	C::Api: Core<B>,
 	CB: ClientBackend<B> + 'static,
 	E: Environment<B> + 'static,
 	E::Proposer: Proposer<B, Proof = P>,
@@ -246,6 +340,7 @@ pub async fn run_instant_seal<B, BI, CB, E, C, TP, SC, CIDP, P>(
 		block_import,
 		env,
 		client,
+		backend: None,
 		pool,
 		commands_stream,
 		select_chain,
@@ -274,7 +369,8 @@ pub async fn run_instant_seal_and_finalize<B, BI, CB, E, C, TP, SC, CIDP, P>(
 ) where
 	B: BlockT + 'static,
 	BI: BlockImport<B, Error = sp_consensus::Error> + Send + Sync + 'static,
-	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + 'static,
+	C: HeaderBackend<B> + Finalizer<B, CB> + ProvideRuntimeApi<B> + Send + Sync + 'static,
+	C::Api: Core<B>,
 	CB: ClientBackend<B> + 'static,
 	E: Environment<B> + 'static,
 	E::Proposer: Proposer<B, Proof = P>,
@@ -296,6 +392,7 @@ pub async fn run_instant_seal_and_finalize<B, BI, CB, E, C, TP, SC, CIDP, P>(
 		block_import,
 		env,
 		client,
+		backend: None,
 		pool,
 		commands_stream,
 		select_chain,
@@ -347,9 +444,11 @@ pub async fn run_delayed_finalize<B, CB, C, S>(
 mod tests {
 	use super::*;
 	use sc_basic_authorship::ProposerFactory;
+	use sc_client_api::StorageProvider;
 	use sc_consensus::ImportedAux;
 	use sc_transaction_pool::{BasicPool, FullChainApi, Options, RevalidationType};
 	use sc_transaction_pool_api::{MaintainedTransactionPool, TransactionPool, TransactionSource};
+	use sp_core::storage::{StorageData, StorageKey};
 	use sp_inherents::InherentData;
 	use sp_runtime::generic::{BlockId, Digest, DigestItem};
 	use substrate_test_runtime_client::{
@@ -432,6 +531,7 @@ mod tests {
 			block_import: client.clone(),
 			env,
 			client: client.clone(),
+			backend: None,
 			pool: pool.clone(),
 			commands_stream,
 			select_chain,
@@ -510,6 +610,7 @@ mod tests {
 			commands_stream,
 			env,
 			client: client.clone(),
+			backend: None,
 			pool: pool.clone(),
 			select_chain,
 			create_inherent_data_providers: |_, _| async { Ok(()) },
@@ -590,6 +691,7 @@ mod tests {
 			block_import: client.clone(),
 			env,
 			client: client.clone(),
+			backend: None,
 			pool: pool.clone(),
 			commands_stream,
 			select_chain,
@@ -676,6 +778,7 @@ mod tests {
 			block_import: client.clone(),
 			env,
 			client: client.clone(),
+			backend: None,
 			pool: pool.clone(),
 			commands_stream,
 			select_chain,
@@ -782,6 +885,7 @@ mod tests {
 			block_import: client.clone(),
 			env,
 			client: client.clone(),
+			backend: None,
 			pool: pool.clone(),
 			commands_stream,
 			select_chain,
@@ -808,4 +912,197 @@ mod tests {
 		let header = client.header(created_block.hash).unwrap().unwrap();
 		assert_eq!(header.number, 1);
 	}
+
+	#[tokio::test]
+	async fn manual_seal_dev_commands() {
+		let builder = TestClientBuilder::new();
+		let backend = builder.backend();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			api(),
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+
+		let (mut sink, commands_stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(ManualSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			backend: Some(backend),
+			pool: pool.clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: None,
+			create_inherent_data_providers: |_, _| async { Ok(()) },
+		});
+		std::thread::spawn(|| {
+			let rt = tokio::runtime::Runtime::new().unwrap();
+			rt.block_on(future);
+		});
+
+		// create multiple blocks with a single command.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::JumpBlocks { count: 3, finalize: false, sender: Some(tx) })
+			.await
+			.unwrap();
+		let created_block = rx.await.unwrap().unwrap();
+		assert_eq!(client.info().best_number, 3);
+		assert_eq!(client.info().best_hash, created_block.hash);
+
+		// set a storage value in the post-state of a new block.
+		let key = StorageKey(b"manual_seal_key".to_vec());
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SetStorage {
+			changes: vec![(key.clone(), Some(StorageData(vec![42])))],
+			sender: Some(tx),
+		})
+		.await
+		.unwrap();
+		let created_block = rx.await.unwrap().unwrap();
+		assert_eq!(client.storage(created_block.hash, &key).unwrap(), Some(StorageData(vec![42])));
+
+		// blocks can be built on top of the modified state.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealNewBlock {
+			parent_hash: None,
+			sender: Some(tx),
+			create_empty: true,
+			finalize: false,
+		})
+		.await
+		.unwrap();
+		let created_block = rx.await.unwrap().unwrap();
+		assert_eq!(client.header(created_block.hash).unwrap().unwrap().number, 5);
+		assert_eq!(client.storage(created_block.hash, &key).unwrap(), Some(StorageData(vec![42])));
+
+		// revert back to block #2.
+		let block_2 = client.hash(2).unwrap().unwrap();
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::RevertTo { hash: block_2, sender: Some(tx) })
+			.await
+			.unwrap();
+		rx.await.unwrap().unwrap();
+		assert_eq!(client.info().best_number, 2);
+		assert_eq!(client.info().best_hash, block_2);
+	}
+
+	#[tokio::test]
+	async fn manual_seal_time_travel_rpc() {
+		use crate::{
+			consensus::aura::AuraConsensusDataProvider,
+			rpc::{ManualSeal, ManualSealApiServer},
+		};
+		use sc_rpc_api::DenyUnsafe;
+		use sp_consensus_aura::{digests::CompatibleDigestItem, sr25519::AuthoritySignature, Slot};
+		use substrate_test_runtime_client::runtime::Hash;
+
+		let builder = TestClientBuilder::new();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let genesis_hash = client.info().genesis_hash;
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(),
+			true.into(),
+			api(),
+			None,
+			RevalidationType::Full,
+			spawner.clone(),
+			0,
+			genesis_hash,
+			genesis_hash,
+		));
+		let env = ProposerFactory::new(spawner.clone(), client.clone(), pool.clone(), None, None);
+
+		let (sink, commands_stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(ManualSealParams {
+			block_import: client.clone(),
+			env,
+			client: client.clone(),
+			backend: None,
+			pool: pool.clone(),
+			commands_stream,
+			select_chain,
+			consensus_data_provider: Some(Box::new(AuraConsensusDataProvider::new(client.clone()))),
+			create_inherent_data_providers: |_, _| async {
+				Ok(sp_timestamp::InherentDataProvider::new(1_000.into()))
+			},
+		});
+		std::thread::spawn(|| {
+			let rt = tokio::runtime::Runtime::new().unwrap();
+			rt.block_on(future);
+		});
+		let rpc = ManualSeal::new(sink, DenyUnsafe::No).into_rpc();
+		let slot_of = |hash| {
+			client
+				.header(hash)
+				.unwrap()
+				.unwrap()
+				.digest
+				.logs
+				.iter()
+				.find_map(CompatibleDigestItem::<AuthoritySignature>::as_aura_pre_digest)
+				.unwrap()
+		};
+
+		// the slot of the next block follows the overridden timestamp (slot duration is 1s). the
+		// test runtime requires the slots of blocks authored with Aura to increase.
+		assert!(rpc.call::<_, bool>("engine_setNextTimestamp", [42_000u64]).await.unwrap());
+		let created_block: CreatedBlock<_> =
+			rpc.call("engine_createBlock", (true, false, None::<Hash>)).await.unwrap();
+		assert_eq!(slot_of(created_block.hash), Slot::from(42));
+
+		// the time keeps moving forward from there, by at least a slot per block.
+		let created_block: CreatedBlock<_> =
+			rpc.call("engine_createBlock", (true, false, None::<Hash>)).await.unwrap();
+		assert_eq!(slot_of(created_block.hash), Slot::from(43));
+
+		// jump a number of blocks at once, each of them in the next slot.
+		assert!(rpc.call::<_, bool>("engine_setNextTimestamp", [100_000u64]).await.unwrap());
+		let created_block: CreatedBlock<Hash> =
+			rpc.call("engine_jumpBlocks", (10u32, true)).await.unwrap();
+		assert_eq!(client.info().best_number, 12);
+		assert_eq!(client.info().best_hash, created_block.hash);
+		assert_eq!(client.info().finalized_hash, created_block.hash);
+		for number in 3..=12u64 {
+			assert_eq!(slot_of(client.hash(number).unwrap().unwrap()), Slot::from(97 + number));
+		}
+
+		// the time does not run backwards.
+		assert!(rpc.call::<_, bool>("engine_setNextTimestamp", [7_000u64]).await.unwrap());
+		let created_block: CreatedBlock<_> =
+			rpc.call("engine_createBlock", (true, false, None::<Hash>)).await.unwrap();
+		assert_eq!(slot_of(created_block.hash), Slot::from(110));
+
+		// jumps are capped.
+		assert!(rpc
+			.call::<_, CreatedBlock<Hash>>("engine_jumpBlocks", (MAX_JUMP_BLOCKS + 1, false))
+			.await
+			.is_err());
+		assert!(rpc
+			.call::<_, CreatedBlock<Hash>>("engine_jumpBlocks", (0u32, false))
+			.await
+			.is_err());
+		assert_eq!(client.info().best_number, 13);
+
+		// time travel requires unsafe RPCs to be allowed.
+		let (sink, _commands_stream) = futures::channel::mpsc::channel(1024);
+		let rpc = ManualSeal::<Hash>::new(sink, DenyUnsafe::Yes).into_rpc();
+		assert!(rpc.call::<_, bool>("engine_setNextTimestamp", [0u64]).await.is_err());
+		assert!(rpc
+			.call::<_, CreatedBlock<Hash>>("engine_jumpBlocks", (1u32, false))
+			.await
+			.is_err());
+	}
 }
@@ -28,7 +28,9 @@ use jsonrpsee::{
 	proc_macros::rpc,
 };
 use sc_consensus::ImportedAux;
+use sc_rpc_api::DenyUnsafe;
 use serde::{Deserialize, Serialize};
+use sp_core::storage::{StorageData, StorageKey};
 use sp_runtime::EncodedJustification;
 
 /// Sender passed to the authorship task to report errors or successes.
@@ -62,6 +64,45 @@ pub enum EngineCommand<Hash> {
 		/// finalization justification
 		justification: Option<EncodedJustification>,
 	},
+	/// Tells the engine to use the given timestamp for the next block it creates.
+	///
+	/// The timestamps of the blocks after it are shifted by the same amount, so that the time
+	/// keeps moving forward from there. See [`BlockTime`](crate::BlockTime).
+	SetNextTimestamp {
+		/// timestamp in milliseconds since the unix epoch.
+		timestamp: u64,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<()>,
+	},
+	/// Tells the engine to create `count` empty blocks on top of the best block.
+	JumpBlocks {
+		/// number of blocks to create.
+		count: u32,
+		/// instantly finalize the created blocks?
+		finalize: bool,
+		/// sender to report the last created block or an error to the rpc.
+		sender: Sender<CreatedBlock<Hash>>,
+	},
+	/// Tells the engine to create a new block on top of the best block, with the given storage
+	/// changes applied on top of its post-state.
+	///
+	/// Requires the engine to have access to the client backend.
+	SetStorage {
+		/// storage changes, a `None` value removes the key.
+		changes: Vec<(StorageKey, Option<StorageData>)>,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<CreatedBlock<Hash>>,
+	},
+	/// Tells the engine to revert the best chain back to the block with the supplied hash.
+	///
+	/// Finalized blocks are never reverted. Requires the engine to have access to the client
+	/// backend.
+	RevertTo {
+		/// hash of the block that should become the new best block.
+		hash: Hash,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<()>,
+	},
 }
 
 /// RPC trait that provides methods for interacting with the manual-seal authorship task over rpc.
@@ -83,11 +124,41 @@ pub trait ManualSealApi<Hash> {
 		hash: Hash,
 		justification: Option<EncodedJustification>,
 	) -> RpcResult<bool>;
+
+	/// Sets the timestamp (in milliseconds) of the next block created by the manual-seal
+	/// authorship task. The blocks after it continue from this time.
+	///
+	/// Slot based consensus data providers derive the slot of the block from this timestamp.
+	/// Every block is in a later slot than the one before, so a timestamp in the past of the
+	/// last block only takes effect once the chain caught up with it.
+	#[method(name = "engine_setNextTimestamp")]
+	async fn set_next_timestamp(&self, timestamp: u64) -> RpcResult<bool>;
+
+	/// Instructs the manual-seal authorship task to create `count` empty blocks in one go.
+	///
+	/// At most [`MAX_JUMP_BLOCKS`](crate::MAX_JUMP_BLOCKS) blocks can be created per call.
+	/// Returns the last created block.
+	#[method(name = "engine_jumpBlocks")]
+	async fn jump_blocks(&self, count: u32, finalize: bool) -> RpcResult<CreatedBlock<Hash>>;
+
+	/// Instructs the manual-seal authorship task to create a new block with the given storage
+	/// values set in its post-state. A `null` value removes the key.
+	#[method(name = "engine_setStorage")]
+	async fn set_storage(
+		&self,
+		changes: Vec<(StorageKey, Option<StorageData>)>,
+	) -> RpcResult<CreatedBlock<Hash>>;
+
+	/// Instructs the manual-seal authorship task to revert the best chain back to the block with
+	/// the supplied hash.
+	#[method(name = "engine_revertTo")]
+	async fn revert_to(&self, hash: Hash) -> RpcResult<bool>;
 }
 
 /// A struct that implements the [`ManualSealApiServer`].
 pub struct ManualSeal<Hash> {
 	import_block_channel: mpsc::Sender<EngineCommand<Hash>>,
+	deny_unsafe: DenyUnsafe,
 }
 
 /// return type of `engine_createBlock`
@@ -103,8 +174,30 @@ pub struct CreatedBlock<Hash> {
 
 impl<Hash> ManualSeal<Hash> {
 	/// Create new `ManualSeal` with the given reference to the client.
-	pub fn new(import_block_channel: mpsc::Sender<EngineCommand<Hash>>) -> Self {
-		Self { import_block_channel }
+	///
+	/// The methods manipulating time and state of the chain are only available if `deny_unsafe`
+	/// is [`DenyUnsafe::No`].
+	pub fn new(
+		import_block_channel: mpsc::Sender<EngineCommand<Hash>>,
+		deny_unsafe: DenyUnsafe,
+	) -> Self {
+		Self { import_block_channel, deny_unsafe }
+	}
+
+	/// Send the command built by `command` to the authorship task and wait for its result.
+	async fn send_command<T>(
+		&self,
+		command: impl FnOnce(Sender<T>) -> EngineCommand<Hash>,
+	) -> RpcResult<T> {
+		let mut sink = self.import_block_channel.clone();
+		let (sender, receiver) = oneshot::channel();
+		sink.send(command(Some(sender))).await?;
+
+		match receiver.await {
+			Ok(Ok(rx)) => Ok(rx),
+			Ok(Err(e)) => Err(e.into()),
+			Err(e) => Err(JsonRpseeError::to_call_error(e)),
+		}
 	}
 }
 
@@ -146,6 +239,34 @@ impl<Hash: Send + 'static> ManualSealApiServer<Hash> for ManualSeal<Hash> {
 		sink.send(command).await?;
 		receiver.await.map(|_| true).map_err(|e| JsonRpseeError::to_call_error(e))
 	}
+
+	async fn set_next_timestamp(&self, timestamp: u64) -> RpcResult<bool> {
+		self.deny_unsafe.check_if_safe()?;
+		self.send_command(|sender| EngineCommand::SetNextTimestamp { timestamp, sender })
+			.await
+			.map(|_| true)
+	}
+
+	async fn jump_blocks(&self, count: u32, finalize: bool) -> RpcResult<CreatedBlock<Hash>> {
+		self.deny_unsafe.check_if_safe()?;
+		self.send_command(|sender| EngineCommand::JumpBlocks { count, finalize, sender })
+			.await
+	}
+
+	async fn set_storage(
+		&self,
+		changes: Vec<(StorageKey, Option<StorageData>)>,
+	) -> RpcResult<CreatedBlock<Hash>> {
+		self.deny_unsafe.check_if_safe()?;
+		self.send_command(|sender| EngineCommand::SetStorage { changes, sender }).await
+	}
+
+	async fn revert_to(&self, hash: Hash) -> RpcResult<bool> {
+		self.deny_unsafe.check_if_safe()?;
+		self.send_command(|sender| EngineCommand::RevertTo { hash, sender })
+			.await
+			.map(|_| true)
+	}
 }
 
 /// report any errors or successes encountered by the authorship task back
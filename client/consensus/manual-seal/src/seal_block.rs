@@ -18,7 +18,7 @@
 
 //! Block sealing utilities
 
-use crate::{rpc, ConsensusDataProvider, CreatedBlock, Error};
+use crate::{dev::StateOverride, rpc, ConsensusDataProvider, CreatedBlock, Error};
 use futures::prelude::*;
 use sc_consensus::{BlockImport, BlockImportParams, ForkChoiceStrategy, ImportResult, StateAction};
 use sc_transaction_pool_api::TransactionPool;
@@ -27,11 +27,60 @@ use sp_blockchain::HeaderBackend;
 use sp_consensus::{self, BlockOrigin, Environment, Proposer, SelectChain};
 use sp_inherents::{CreateInherentDataProviders, InherentDataProvider};
 use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use sp_timestamp::TimestampInherentData;
 use std::{sync::Arc, time::Duration};
 
 /// max duration for creating a proposal in secs
 pub const MAX_PROPOSAL_DURATION: u64 = 10;
 
+/// Keeps the timestamps of the sealed blocks moving forward.
+///
+/// The timestamp of a block is the one of the inherent data providers, shifted by the offset
+/// set through [`Self::set_next_timestamp`], and at least the minimal block time of the
+/// consensus data provider after the timestamp of the block sealed before.
+#[derive(Debug, Default)]
+pub struct BlockTime {
+	/// Added to the timestamps of the inherent data providers.
+	offset: i128,
+	/// The timestamp requested for the next block.
+	next: Option<u64>,
+	/// The timestamp of the last sealed block.
+	last: Option<u64>,
+}
+
+impl BlockTime {
+	/// Use `timestamp` for the next block and shift the time of all blocks after it by the
+	/// same amount.
+	///
+	/// Time does not run backwards though: if `timestamp` is not past the last block, the time
+	/// stands still until it is.
+	pub fn set_next_timestamp(&mut self, timestamp: u64) {
+		self.next = Some(timestamp);
+	}
+
+	/// The timestamp of a block for which the inherent data providers returned `provided`.
+	fn timestamp(&self, provided: Option<u64>, min_block_time: u64) -> Option<u64> {
+		let timestamp = self.next.or_else(|| {
+			provided.map(|provided| {
+				(i128::from(provided) + self.offset).clamp(0, u64::MAX.into()) as u64
+			})
+		})?;
+		match self.last {
+			Some(last) => Some(timestamp.max(last.saturating_add(min_block_time))),
+			None => Some(timestamp),
+		}
+	}
+
+	/// Note that a block was sealed with `timestamp`, while the inherent data providers
+	/// returned `provided`.
+	fn note_sealed(&mut self, provided: Option<u64>, timestamp: u64) {
+		if let (Some(next), Some(provided)) = (self.next.take(), provided) {
+			self.offset = i128::from(next) - i128::from(provided);
+		}
+		self.last = Some(timestamp);
+	}
+}
+
 /// params for sealing a new block
 pub struct SealBlockParams<'a, B: BlockT, BI, SC, C: ProvideRuntimeApi<B>, E, TP, CIDP, P> {
 	/// if true, empty blocks(without extrinsics) will be created.
@@ -57,6 +106,10 @@ pub struct SealBlockParams<'a, B: BlockT, BI, SC, C: ProvideRuntimeApi<B>, E, TP
 	pub block_import: &'a mut BI,
 	/// Something that can create the inherent data providers.
 	pub create_inherent_data_providers: &'a CIDP,
+	/// Keeps track of the block timestamps, if they should be moved forward by the sealing.
+	pub block_time: Option<&'a mut BlockTime>,
+	/// Rewrites the post-state of the block before it is imported.
+	pub state_override: Option<&'a dyn StateOverride<B>>,
 }
 
 /// seals a new block with the given params
@@ -72,6 +125,8 @@ pub async fn seal_block<B, BI, SC, C, E, TP, CIDP, P>(
 		env,
 		create_inherent_data_providers,
 		consensus_data_provider: digest_provider,
+		mut block_time,
+		state_override,
 		mut sender,
 	}: SealBlockParams<'_, B, BI, SC, C, E, TP, CIDP, P>,
 ) where
@@ -104,7 +159,19 @@ pub async fn seal_block<B, BI, SC, C, E, TP, CIDP, P>(
 			.await
 			.map_err(|e| Error::Other(e))?;
 
-		let inherent_data = inherent_data_providers.create_inherent_data().await?;
+		let mut inherent_data = inherent_data_providers.create_inherent_data().await?;
+
+		let provided_timestamp = inherent_data.timestamp_inherent_data()?.map(|t| *t);
+		let min_block_time = digest_provider.map_or(0, |provider| provider.min_block_time());
+		let timestamp = block_time
+			.as_ref()
+			.and_then(|block_time| block_time.timestamp(provided_timestamp, min_block_time));
+		if let Some(timestamp) = timestamp {
+			inherent_data.replace_data(
+				sp_timestamp::INHERENT_IDENTIFIER,
+				&sp_timestamp::Timestamp::new(timestamp),
+			);
+		}
 
 		let proposer = env.init(&parent).map_err(|err| Error::StringError(err.to_string())).await?;
 		let inherents_len = inherent_data.len();
@@ -129,16 +196,20 @@ pub async fn seal_block<B, BI, SC, C, E, TP, CIDP, P>(
 			return Err(Error::EmptyTransactionPool)
 		}
 
-		let (header, body) = proposal.block.deconstruct();
+		let (mut header, body) = proposal.block.deconstruct();
+		let mut storage_changes = proposal.storage_changes;
+		if let Some(state_override) = state_override {
+			state_override.apply(&parent, &mut header, &mut storage_changes)?;
+		}
+
 		let proof = proposal.proof;
 		let proof_size = proof.encoded_size();
 		let mut params = BlockImportParams::new(BlockOrigin::Own, header.clone());
 		params.body = Some(body);
 		params.finalized = finalize;
 		params.fork_choice = Some(ForkChoiceStrategy::LongestChain);
-		params.state_action = StateAction::ApplyChanges(sc_consensus::StorageChanges::Changes(
-			proposal.storage_changes,
-		));
+		params.state_action =
+			StateAction::ApplyChanges(sc_consensus::StorageChanges::Changes(storage_changes));
 
 		if let Some(digest_provider) = digest_provider {
 			digest_provider.append_block_import(&parent, &mut params, &inherent_data, proof)?;
@@ -150,11 +221,16 @@ pub async fn seal_block<B, BI, SC, C, E, TP, CIDP, P>(
 		post_header.digest_mut().logs.extend(params.post_digests.iter().cloned());
 
 		match block_import.import_block(params).await? {
-			ImportResult::Imported(aux) => Ok(CreatedBlock {
-				hash: <B as BlockT>::Header::hash(&post_header),
-				aux,
-				proof_size,
-			}),
+			ImportResult::Imported(aux) => {
+				if let (Some(block_time), Some(timestamp)) = (block_time, timestamp) {
+					block_time.note_sealed(provided_timestamp, timestamp);
+				}
+				Ok(CreatedBlock {
+					hash: <B as BlockT>::Header::hash(&post_header),
+					aux,
+					proof_size,
+				})
+			},
 			other => Err(other.into()),
 		}
 	};
@@ -0,0 +1,608 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! # Sassafras
+//! Types and functions related to block authoring and tickets generation.
+
+use crate::{
+	find_slot_claim, slot_claim_sign_data, ticket_claim_message, Epoch, SassafrasIntermediate,
+	SassafrasLink, INTERMEDIATE_KEY, LOG_TARGET,
+};
+
+use std::{collections::BTreeMap, pin::Pin, sync::Arc, task::Context, time::Duration};
+
+use futures::prelude::*;
+use log::{debug, error, info, warn};
+
+use sc_client_api::BlockchainEvents;
+use sc_consensus::{
+	block_import::{BlockImport, BlockImportParams, StateAction},
+	JustificationSyncLink,
+};
+use sc_consensus_epochs::{
+	descendent_query, Epoch as EpochT, EpochIdentifier, EpochIdentifierPosition,
+	SharedEpochChanges, ViableEpochDescriptor,
+};
+use sc_consensus_slots::{InherentDataProviderExt, SlotInfo, SlotProportion, StorageChanges};
+use sc_telemetry::TelemetryHandle;
+use sc_transaction_pool_api::OffchainTransactionPoolFactory;
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_application_crypto::{AppCrypto, ByteArray};
+use sp_blockchain::{Error as ClientError, HeaderBackend, HeaderMetadata};
+use sp_consensus::{
+	BlockOrigin, Environment, Error as ConsensusError, Proposer, SelectChain, SyncOracle,
+};
+use sp_consensus_sassafras::{
+	digests::{CompatibleDigestItem, SlotClaim},
+	secondary_authority_index, ticket_id_threshold,
+	vrf::{self, RingContext},
+	AuthorityId, AuthorityIndex, SassafrasApi, Slot, TicketBody, TicketClaim, TicketEnvelope,
+	TicketId, TicketSecret,
+};
+use sp_core::{bandersnatch, ed25519, Pair};
+use sp_inherents::CreateInherentDataProviders;
+use sp_keystore::KeystorePtr;
+use sp_runtime::{
+	traits::{Block as BlockT, Header, NumberFor, One},
+	DigestItem,
+};
+
+/// Get secondary authority index for the given epoch and slot.
+pub(crate) fn secondary_authority_idx(slot: Slot, epoch: &Epoch) -> Option<AuthorityIndex> {
+	secondary_authority_index(slot, &epoch.randomness, epoch.authorities.len())
+}
+
+/// Try to claim an epoch slot.
+///
+/// If the slot has been assigned to a ticket then the claim succeeds only if we
+/// own the ticket secret. Otherwise the slot is claimed by the secondary author,
+/// if we have its key in the keystore.
+fn claim_slot(
+	slot: Slot,
+	epoch: &Epoch,
+	maybe_ticket: Option<(TicketId, TicketBody)>,
+	keystore: &KeystorePtr,
+) -> Option<(SlotClaim, AuthorityId)> {
+	if epoch.authorities.is_empty() {
+		return None
+	}
+
+	let mut epoch_idx = epoch.epoch_idx;
+	if epoch.end_slot() <= slot {
+		// Slot doesn't strictly belong to the epoch, create a clone with fixed values.
+		epoch_idx = epoch.clone_for_slot(slot).epoch_idx;
+	}
+
+	let (authority_idx, ticket_secret, ticket_body) = match maybe_ticket {
+		Some((ticket_id, ticket_body)) => {
+			debug!(target: LOG_TARGET, "[TRY PRIMARY (slot {slot}, tkt = {ticket_id:032x})]");
+			let (authority_idx, ticket_secret) = epoch.tickets_aux.get(&ticket_id)?.clone();
+			debug!(target: LOG_TARGET, "Ticket = [ticket: {ticket_id:032x}, auth: {authority_idx}]");
+			(authority_idx, Some(ticket_secret), Some(ticket_body))
+		},
+		None => {
+			debug!(target: LOG_TARGET, "[TRY SECONDARY (slot {slot})]");
+			(secondary_authority_idx(slot, epoch)?, None, None)
+		},
+	};
+
+	let authority_id = epoch.authorities.get(authority_idx as usize)?;
+
+	let sign_data = slot_claim_sign_data(&epoch.randomness, slot, epoch_idx, ticket_body.as_ref());
+
+	let vrf_signature = keystore
+		.bandersnatch_vrf_sign(AuthorityId::ID, authority_id.as_ref(), &sign_data)
+		.ok()
+		.flatten()?;
+
+	let ticket_claim = ticket_secret.map(|secret| {
+		let erased_pair = ed25519::Pair::from_seed(&secret.erased_secret);
+		let erased_signature = erased_pair.sign(&ticket_claim_message(&sign_data));
+		TicketClaim { erased_signature }
+	});
+
+	let claim = SlotClaim { authority_idx, slot, vrf_signature, ticket_claim };
+
+	Some((claim, authority_id.clone()))
+}
+
+/// Generate the tickets for the given epoch.
+///
+/// Tickets additional information will be returned indexed by ticket identifier.
+/// This information should be stored in the epoch tickets auxiliary data once the
+/// tickets have been successfully submitted on-chain.
+fn generate_epoch_tickets(
+	epoch: &Epoch,
+	keystore: &KeystorePtr,
+	ring_ctx: &RingContext,
+) -> (Vec<TicketEnvelope>, BTreeMap<TicketId, (AuthorityIndex, TicketSecret)>) {
+	let mut tickets = Vec::new();
+	let mut tickets_aux = BTreeMap::new();
+
+	let threshold = ticket_id_threshold(
+		epoch.config.redundancy_factor,
+		epoch.epoch_duration as u32,
+		epoch.config.attempts_number,
+		epoch.authorities.len() as u32,
+	);
+	debug!(target: LOG_TARGET, "Generating tickets for epoch {}", epoch.epoch_idx);
+	debug!(target: LOG_TARGET, "    threshold: {threshold:032x}");
+
+	let public_keys: Vec<bandersnatch::Public> =
+		epoch.authorities.iter().map(|authority_id| *authority_id.as_ref()).collect();
+
+	for (authority_idx, authority_id) in epoch.authorities.iter().enumerate() {
+		if !keystore.has_keys(&[(authority_id.to_raw_vec(), AuthorityId::ID)]) {
+			continue
+		}
+
+		debug!(target: LOG_TARGET, ">>> Creating ring prover for authority {}", authority_idx);
+		let Some(prover) = ring_ctx.prover(&public_keys, authority_idx) else {
+			warn!(target: LOG_TARGET, "Unable to build ring prover for authority {authority_idx}");
+			continue
+		};
+
+		let make_ticket = |attempt_idx| {
+			let ticket_id_input =
+				vrf::ticket_id_input(&epoch.randomness, attempt_idx, epoch.epoch_idx);
+			let ticket_id_output = keystore
+				.bandersnatch_vrf_output(AuthorityId::ID, authority_id.as_ref(), &ticket_id_input)
+				.ok()??;
+
+			let ticket_id = vrf::make_ticket_id(&ticket_id_input, &ticket_id_output);
+			if ticket_id >= threshold {
+				return None
+			}
+
+			let (erased_pair, erased_secret) = ed25519::Pair::generate();
+			let body = TicketBody { attempt_idx, erased_public: erased_pair.public() };
+
+			debug!(target: LOG_TARGET, ">>> Creating ring proof for attempt {}", attempt_idx);
+			let sign_data = vrf::ticket_body_sign_data(&body, ticket_id_input);
+
+			let signature = keystore
+				.bandersnatch_ring_vrf_sign(
+					AuthorityId::ID,
+					authority_id.as_ref(),
+					&sign_data,
+					&prover,
+				)
+				.ok()??;
+			debug!(target: LOG_TARGET, ">>> ...done");
+
+			let secret = TicketSecret { attempt_idx, erased_secret };
+			Some((ticket_id, TicketEnvelope { body, signature }, secret))
+		};
+
+		for attempt in 0..epoch.config.attempts_number {
+			if let Some((ticket_id, envelope, secret)) = make_ticket(attempt) {
+				debug!(target: LOG_TARGET, "    → {ticket_id:032x}");
+				tickets.push(envelope);
+				tickets_aux.insert(ticket_id, (authority_idx as AuthorityIndex, secret));
+			}
+		}
+	}
+
+	(tickets, tickets_aux)
+}
+
+struct SlotWorker<B: BlockT, C, E, I, SO, L> {
+	client: Arc<C>,
+	block_import: I,
+	env: E,
+	sync_oracle: SO,
+	justification_sync_link: L,
+	force_authoring: bool,
+	keystore: KeystorePtr,
+	epoch_changes: SharedEpochChanges<B, Epoch>,
+	genesis_config: sp_consensus_sassafras::Epoch,
+	block_proposal_slot_portion: SlotProportion,
+	max_block_proposal_slot_portion: Option<SlotProportion>,
+	telemetry: Option<TelemetryHandle>,
+}
+
+#[async_trait::async_trait]
+impl<B, C, E, I, ER, SO, L> sc_consensus_slots::SimpleSlotWorker<B>
+	for SlotWorker<B, C, E, I, SO, L>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + HeaderMetadata<B, Error = ClientError>,
+	C::Api: SassafrasApi<B>,
+	E: Environment<B, Error = ER> + Sync,
+	E::Proposer: Proposer<B, Error = ER>,
+	I: BlockImport<B> + Send + Sync + 'static,
+	SO: SyncOracle + Send + Clone + Sync,
+	L: JustificationSyncLink<B>,
+	ER: std::error::Error + Send + From<ConsensusError> + From<I::Error> + 'static,
+{
+	type Claim = (SlotClaim, AuthorityId);
+	type SyncOracle = SO;
+	type JustificationSyncLink = L;
+	type CreateProposer =
+		Pin<Box<dyn Future<Output = Result<E::Proposer, ConsensusError>> + Send + 'static>>;
+	type Proposer = E::Proposer;
+	type BlockImport = I;
+	type AuxData = ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>;
+
+	fn logging_target(&self) -> &'static str {
+		LOG_TARGET
+	}
+
+	fn block_import(&mut self) -> &mut Self::BlockImport {
+		&mut self.block_import
+	}
+
+	fn aux_data(&self, parent: &B::Header, slot: Slot) -> Result<Self::AuxData, ConsensusError> {
+		self.epoch_changes
+			.shared_data()
+			.epoch_descriptor_for_child_of(
+				descendent_query(&*self.client),
+				&parent.hash(),
+				*parent.number(),
+				slot,
+			)
+			.map_err(|e| ConsensusError::ChainLookup(e.to_string()))?
+			.ok_or(ConsensusError::InvalidAuthoritiesSet)
+	}
+
+	fn authorities_len(&self, epoch_descriptor: &Self::AuxData) -> Option<usize> {
+		self.epoch_changes
+			.shared_data()
+			.viable_epoch(epoch_descriptor, |slot| Epoch::genesis(&self.genesis_config, slot))
+			.map(|epoch| epoch.as_ref().authorities.len())
+	}
+
+	async fn claim_slot(
+		&self,
+		parent_header: &B::Header,
+		slot: Slot,
+		epoch_descriptor: &ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>,
+	) -> Option<Self::Claim> {
+		debug!(target: LOG_TARGET, "Attempting to claim slot {}", slot);
+
+		// Get the next slot ticket from the runtime.
+		let maybe_ticket =
+			self.client.runtime_api().slot_ticket(parent_header.hash(), slot).ok()?;
+
+		let claim = claim_slot(
+			slot,
+			self.epoch_changes
+				.shared_data()
+				.viable_epoch(epoch_descriptor, |slot| Epoch::genesis(&self.genesis_config, slot))?
+				.as_ref(),
+			maybe_ticket,
+			&self.keystore,
+		);
+
+		if claim.is_some() {
+			debug!(target: LOG_TARGET, "Claimed slot {}", slot);
+		}
+
+		claim
+	}
+
+	fn pre_digest_data(&self, _slot: Slot, claim: &Self::Claim) -> Vec<DigestItem> {
+		vec![<DigestItem as CompatibleDigestItem>::sassafras_pre_digest(claim.0.clone())]
+	}
+
+	async fn block_import_params(
+		&self,
+		header: B::Header,
+		header_hash: &B::Hash,
+		body: Vec<B::Extrinsic>,
+		storage_changes: StorageChanges<B>,
+		(_, public): Self::Claim,
+		epoch_descriptor: Self::AuxData,
+	) -> Result<BlockImportParams<B>, ConsensusError> {
+		let signature = self
+			.keystore
+			.bandersnatch_sign(
+				<AuthorityId as AppCrypto>::ID,
+				public.as_ref(),
+				header_hash.as_ref(),
+			)
+			.map_err(|e| ConsensusError::CannotSign(format!("{}. Key: {:?}", e, public)))?
+			.ok_or_else(|| {
+				ConsensusError::CannotSign(format!(
+					"Could not find key in keystore. Key: {:?}",
+					public
+				))
+			})?;
+
+		let digest_item = <DigestItem as CompatibleDigestItem>::sassafras_seal(signature.into());
+
+		let mut import_block = BlockImportParams::new(BlockOrigin::Own, header);
+		import_block.post_digests.push(digest_item);
+		import_block.body = Some(body);
+		import_block.state_action =
+			StateAction::ApplyChanges(sc_consensus::StorageChanges::Changes(storage_changes));
+		import_block
+			.insert_intermediate(INTERMEDIATE_KEY, SassafrasIntermediate::<B> { epoch_descriptor });
+
+		Ok(import_block)
+	}
+
+	fn force_authoring(&self) -> bool {
+		self.force_authoring
+	}
+
+	fn sync_oracle(&mut self) -> &mut Self::SyncOracle {
+		&mut self.sync_oracle
+	}
+
+	fn justification_sync_link(&mut self) -> &mut Self::JustificationSyncLink {
+		&mut self.justification_sync_link
+	}
+
+	fn proposer(&mut self, block: &B::Header) -> Self::CreateProposer {
+		Box::pin(self.env.init(block).map_err(|e| ConsensusError::ClientImport(e.to_string())))
+	}
+
+	fn telemetry(&self) -> Option<TelemetryHandle> {
+		self.telemetry.clone()
+	}
+
+	fn proposing_remaining_duration(&self, slot_info: &SlotInfo<B>) -> Duration {
+		let parent_slot = find_slot_claim::<B>(&slot_info.chain_head).ok().map(|d| d.slot);
+
+		sc_consensus_slots::proposing_remaining_duration(
+			parent_slot,
+			slot_info,
+			&self.block_proposal_slot_portion,
+			self.max_block_proposal_slot_portion.as_ref(),
+			sc_consensus_slots::SlotLenienceType::Exponential,
+			self.logging_target(),
+		)
+	}
+}
+
+/// Authoring tickets generation worker.
+///
+/// Listens on the client's import notification stream for blocks which contain new epoch
+/// information, that is blocks that signals the begin of a new epoch.
+/// This event here triggers the begin of the generation of tickets for the next epoch.
+/// The tickets generated by the worker are saved within the epoch changes tree
+/// and are volatile.
+async fn start_tickets_worker<B, C, SC>(
+	client: Arc<C>,
+	keystore: KeystorePtr,
+	epoch_changes: SharedEpochChanges<B, Epoch>,
+	select_chain: SC,
+	offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+) where
+	B: BlockT,
+	C: BlockchainEvents<B> + ProvideRuntimeApi<B>,
+	C::Api: SassafrasApi<B>,
+	SC: SelectChain<B> + 'static,
+{
+	let mut notifications = client.import_notification_stream();
+
+	while let Some(notification) = notifications.next().await {
+		let epoch_desc = match crate::find_next_epoch_digest::<B>(&notification.header) {
+			Ok(Some(epoch_desc)) => epoch_desc,
+			Err(err) => {
+				warn!(target: LOG_TARGET, "Error fetching next epoch digest: {}", err);
+				continue
+			},
+			_ => continue,
+		};
+
+		debug!(target: LOG_TARGET, "New epoch announced {:x?}", epoch_desc);
+
+		let number = *notification.header.number();
+		let position = if number == One::one() {
+			EpochIdentifierPosition::Genesis1
+		} else {
+			EpochIdentifierPosition::Regular
+		};
+		let epoch_identifier = EpochIdentifier { position, hash: notification.hash, number };
+
+		let epoch = match epoch_changes.shared_data().epoch(&epoch_identifier).cloned() {
+			Some(epoch) => epoch,
+			None => {
+				warn!(
+					target: LOG_TARGET,
+					"Unexpected missing epoch data for {:?}", notification.hash
+				);
+				continue
+			},
+		};
+
+		// Get the best block on which we will publish the tickets.
+		let best_hash = match select_chain.best_chain().await {
+			Ok(header) => header.hash(),
+			Err(err) => {
+				error!(target: LOG_TARGET, "Error fetching best chain block id: {}", err);
+				continue
+			},
+		};
+
+		let ring_ctx = match client.runtime_api().ring_context(best_hash) {
+			Ok(Some(ctx)) => ctx,
+			Ok(None) => {
+				error!(target: LOG_TARGET, "Ring context not initialized at {:?}", best_hash);
+				continue
+			},
+			Err(err) => {
+				error!(target: LOG_TARGET, "Unable to read ring context: {}", err);
+				continue
+			},
+		};
+
+		let (tickets, tickets_aux) = generate_epoch_tickets(&epoch, &keystore, &ring_ctx);
+		if tickets.is_empty() {
+			continue
+		}
+
+		let mut runtime_api = client.runtime_api();
+		runtime_api
+			.register_extension(offchain_tx_pool_factory.offchain_transaction_pool(best_hash));
+
+		let err = match runtime_api.submit_tickets_unsigned_extrinsic(best_hash, tickets) {
+			Err(err) => Some(err.to_string()),
+			Ok(false) => Some("Unknown reason".to_string()),
+			_ => None,
+		};
+
+		match err {
+			None => {
+				// Cache tickets secret information in the epoch changes tree.
+				// The tree is persisted together with the next imported block.
+				if let Some(target_epoch) = epoch_changes.shared_data().epoch_mut(&epoch_identifier)
+				{
+					target_epoch.tickets_aux.extend(tickets_aux);
+				}
+			},
+			Some(err) => {
+				error!(target: LOG_TARGET, "Unable to submit tickets: {}", err);
+			},
+		}
+	}
+}
+
+/// Worker for Sassafras which implements `Future<Output=()>`. This must be polled.
+#[must_use]
+pub struct SassafrasWorker<B: BlockT> {
+	inner: Pin<Box<dyn Future<Output = ()> + Send + 'static>>,
+	_phantom: std::marker::PhantomData<B>,
+}
+
+impl<B: BlockT> Future for SassafrasWorker<B> {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> std::task::Poll<Self::Output> {
+		self.inner.as_mut().poll(cx)
+	}
+}
+
+/// Parameters for Sassafras.
+pub struct SassafrasParams<B: BlockT, C, SC, EN, I, SO, L, CIDP> {
+	/// The client to use
+	pub client: Arc<C>,
+	/// The keystore that manages the keys of the node.
+	pub keystore: KeystorePtr,
+	/// The chain selection strategy
+	pub select_chain: SC,
+	/// The environment we are producing blocks for.
+	pub env: EN,
+	/// The underlying block-import object to supply our produced blocks to.
+	/// This must be a `SassafrasBlockImport` or a wrapper of it, otherwise
+	/// critical consensus logic will be omitted.
+	pub block_import: I,
+	/// A sync oracle
+	pub sync_oracle: SO,
+	/// Hook into the sync module to control the justification sync process.
+	pub justification_sync_link: L,
+	/// Something that can create the inherent data providers.
+	pub create_inherent_data_providers: CIDP,
+	/// Force authoring of blocks even if we are offline
+	pub force_authoring: bool,
+	/// State shared between import queue and authoring worker.
+	pub sassafras_link: SassafrasLink<B>,
+	/// The proportion of the slot dedicated to proposing.
+	///
+	/// The block proposing will be limited to this proportion of the slot from the starting of the
+	/// slot. However, the proposing can still take longer when there is some lenience factor
+	/// applied, because there were no blocks produced for some slots.
+	pub block_proposal_slot_portion: SlotProportion,
+	/// The maximum proportion of the slot dedicated to proposing with any lenience factor applied
+	/// due to no blocks being produced.
+	pub max_block_proposal_slot_portion: Option<SlotProportion>,
+	/// Handle use to report telemetries.
+	pub telemetry: Option<TelemetryHandle>,
+	/// The offchain transaction pool factory.
+	///
+	/// Will be used when submitting the epoch tickets.
+	pub offchain_tx_pool_factory: OffchainTransactionPoolFactory<B>,
+}
+
+/// Start the Sassafras worker.
+pub fn start_sassafras<B, C, SC, EN, I, SO, L, CIDP, ER>(
+	SassafrasParams {
+		client,
+		keystore,
+		select_chain,
+		env,
+		block_import,
+		sync_oracle,
+		justification_sync_link,
+		create_inherent_data_providers,
+		force_authoring,
+		sassafras_link,
+		block_proposal_slot_portion,
+		max_block_proposal_slot_portion,
+		telemetry,
+		offchain_tx_pool_factory,
+	}: SassafrasParams<B, C, SC, EN, I, SO, L, CIDP>,
+) -> Result<SassafrasWorker<B>, ConsensusError>
+where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>
+		+ HeaderBackend<B>
+		+ HeaderMetadata<B, Error = ClientError>
+		+ BlockchainEvents<B>
+		+ Send
+		+ Sync
+		+ 'static,
+	C::Api: SassafrasApi<B>,
+	SC: SelectChain<B> + 'static,
+	EN: Environment<B, Error = ER> + Send + Sync + 'static,
+	EN::Proposer: Proposer<B, Error = ER>,
+	I: BlockImport<B, Error = ConsensusError> + Send + Sync + 'static,
+	SO: SyncOracle + Send + Sync + Clone + 'static,
+	L: JustificationSyncLink<B> + 'static,
+	CIDP: CreateInherentDataProviders<B, ()> + Send + Sync + 'static,
+	CIDP::InherentDataProviders: InherentDataProviderExt + Send,
+	ER: std::error::Error + Send + From<ConsensusError> + From<I::Error> + 'static,
+{
+	info!(target: LOG_TARGET, "🍁 Starting Sassafras Authorship worker");
+
+	let slot_worker = SlotWorker {
+		client: client.clone(),
+		block_import,
+		env,
+		sync_oracle: sync_oracle.clone(),
+		justification_sync_link,
+		force_authoring,
+		keystore: keystore.clone(),
+		epoch_changes: sassafras_link.epoch_changes.clone(),
+		genesis_config: sassafras_link.genesis_config.clone(),
+		block_proposal_slot_portion,
+		max_block_proposal_slot_portion,
+		telemetry,
+	};
+
+	let slot_worker = sc_consensus_slots::start_slot_worker(
+		sassafras_link.genesis_config.slot_duration,
+		select_chain.clone(),
+		sc_consensus_slots::SimpleSlotWorkerToSlotWorker(slot_worker),
+		sync_oracle,
+		create_inherent_data_providers,
+	);
+
+	let tickets_worker = start_tickets_worker(
+		client,
+		keystore,
+		sassafras_link.epoch_changes,
+		select_chain,
+		offchain_tx_pool_factory,
+	);
+
+	let inner = future::select(Box::pin(slot_worker), Box::pin(tickets_worker));
+
+	Ok(SassafrasWorker { inner: Box::pin(inner.map(|_| ())), _phantom: Default::default() })
+}
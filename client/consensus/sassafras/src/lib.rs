@@ -0,0 +1,408 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! # Sassafras
+//!
+//! Sassafras (Semi Anonymous Sortition of Staked Assignees For Fixed-time Rhythmic
+//! Assignment of Slots) is a constant-time block production protocol.
+//!
+//! The protocol runs a lottery, one epoch in advance, to distribute the epoch's
+//! block production slots. Every validator generates a set of tickets using a VRF
+//! evaluated on some epoch randomness. Tickets whose identifier falls below a
+//! threshold are anonymously submitted on-chain together with a ring VRF proof,
+//! i.e. a proof that the ticket was generated by one of the epoch's validators
+//! without revealing which one.
+//!
+//! Before the epoch starts, the runtime sorts the received tickets and assigns
+//! them to the epoch's slots following an *outside-in* strategy. When a slot
+//! comes, the ticket's owner claims it by revealing the ticket ownership.
+//!
+//! Slots not covered by any ticket are assigned to a secondary author picked
+//! deterministically using the epoch randomness, similarly to BABE secondary
+//! slots.
+//!
+//! The fork choice rule is weight-based, where weight equals the number of
+//! blocks claimed using a ticket (primary blocks). We pick the heaviest chain
+//! and go with the longest one in case of a tie.
+//!
+//! An in-depth description of the protocol can be found here:
+//! <https://research.web3.foundation/Polkadot/protocols/block-production/SASSAFRAS>
+
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+
+use std::collections::BTreeMap;
+
+use codec::{Decode, Encode};
+use log::{debug, trace};
+
+use sc_client_api::{backend::AuxStore, UsageProvider};
+use sc_consensus_epochs::{Epoch as EpochT, ViableEpochDescriptor};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::Result as ClientResult;
+use sp_consensus_slots::Slot;
+use sp_runtime::{
+	generic::OpaqueDigestItemId,
+	traits::{Block as BlockT, Header, NumberFor, Zero},
+};
+
+pub use sc_consensus_slots::SlotProportion;
+pub use sp_consensus::SyncOracle;
+pub use sp_consensus_sassafras::{
+	digests::{CompatibleDigestItem, ConsensusLog, NextEpochDescriptor, SlotClaim},
+	vrf, AuthorityId, AuthorityIndex, AuthorityPair, AuthoritySignature, EpochConfiguration,
+	Randomness, SassafrasApi, SassafrasBlockWeight, TicketBody, TicketClaim, TicketEnvelope,
+	TicketId, TicketSecret, RANDOMNESS_LENGTH, SASSAFRAS_ENGINE_ID,
+};
+
+pub use authorship::{start_sassafras, SassafrasParams, SassafrasWorker};
+pub use aux_schema::load_block_weight as block_weight;
+pub use block_import::{block_import, SassafrasBlockImport};
+pub use verification::{import_queue, ImportQueueParams, SassafrasVerifier};
+
+mod authorship;
+mod aux_schema;
+mod block_import;
+mod verification;
+
+const LOG_TARGET: &str = "sassafras 🌳";
+
+/// Intermediate key for Sassafras engine.
+pub const INTERMEDIATE_KEY: &[u8] = b"sass1";
+
+/// Errors encountered by the Sassafras routines.
+#[derive(Debug, thiserror::Error)]
+pub enum Error<B: BlockT> {
+	/// Multiple Sassafras pre-runtime digests
+	#[error("Multiple Sassafras pre-runtime digests")]
+	MultiplePreRuntimeDigests,
+	/// No Sassafras pre-runtime digest found
+	#[error("No Sassafras pre-runtime digest found")]
+	NoPreRuntimeDigest,
+	/// Multiple Sassafras epoch change digests
+	#[error("Multiple Sassafras epoch change digests")]
+	MultipleEpochChangeDigests,
+	/// Could not fetch epoch
+	#[error("Could not fetch epoch at {0:?}")]
+	FetchEpoch(B::Hash),
+	/// Header rejected: too far in the future
+	#[error("Header {0:?} rejected: too far in the future")]
+	TooFarInFuture(B::Hash),
+	/// Parent unavailable. Cannot import
+	#[error("Parent ({0}) of {1} unavailable. Cannot import")]
+	ParentUnavailable(B::Hash, B::Hash),
+	/// Slot number must increase
+	#[error("Slot number must increase: parent slot: {0}, this slot: {1}")]
+	SlotMustIncrease(Slot, Slot),
+	/// Header has a bad seal
+	#[error("Header {0:?} has a bad seal")]
+	HeaderBadSeal(B::Hash),
+	/// Header is unsealed
+	#[error("Header {0:?} is unsealed")]
+	HeaderUnsealed(B::Hash),
+	/// Slot author not found
+	#[error("Slot author not found")]
+	SlotAuthorNotFound,
+	/// Bad signature
+	#[error("Bad signature on {0:?}")]
+	BadSignature(B::Hash),
+	/// VRF verification failed
+	#[error("VRF verification failed")]
+	VrfVerificationFailed,
+	/// Missing VRF output entry in the signature
+	#[error("Missing signed VRF output")]
+	MissingSignedVrfOutput,
+	/// Ticket ownership verification failed
+	#[error("Ticket verification failed")]
+	TicketVerificationFailed,
+	/// Unexpected authoring mechanism
+	#[error("Unexpected authoring mechanism")]
+	UnexpectedAuthoringMechanism,
+	/// Could not fetch parent header
+	#[error("Could not fetch parent header: {0}")]
+	FetchParentHeader(sp_blockchain::Error),
+	/// Expected epoch change to happen.
+	#[error("Expected epoch change to happen at {0:?}, s{1}")]
+	ExpectedEpochChange(B::Hash, Slot),
+	/// Unexpected epoch change
+	#[error("Unexpected epoch change")]
+	UnexpectedEpochChange,
+	/// Parent block has no associated weight
+	#[error("Parent block of {0} has no associated weight")]
+	ParentBlockNoAssociatedWeight(B::Hash),
+	/// Check inherents error
+	#[error("Checking inherents failed: {0}")]
+	CheckInherents(sp_inherents::Error),
+	/// Unhandled check inherents error
+	#[error("Checking inherents unhandled error: {}", String::from_utf8_lossy(.0))]
+	CheckInherentsUnhandled(sp_inherents::InherentIdentifier),
+	/// Create inherents error.
+	#[error("Creating inherents failed: {0}")]
+	CreateInherents(sp_inherents::Error),
+	/// Client error
+	#[error(transparent)]
+	Client(sp_blockchain::Error),
+	/// Runtime Api error.
+	#[error(transparent)]
+	RuntimeApi(sp_api::ApiError),
+	/// Fork tree error
+	#[error(transparent)]
+	ForkTree(Box<fork_tree::Error<sp_blockchain::Error>>),
+}
+
+impl<B: BlockT> From<Error<B>> for String {
+	fn from(error: Error<B>) -> String {
+		error.to_string()
+	}
+}
+
+fn sassafras_err<B: BlockT>(error: Error<B>) -> Error<B> {
+	debug!(target: LOG_TARGET, "{}", error);
+	error
+}
+
+/// Sassafras epoch information augmented with private tickets information.
+#[derive(Encode, Decode, PartialEq, Eq, Clone, Debug)]
+pub struct Epoch {
+	/// Epoch information as exposed by the runtime.
+	pub inner: sp_consensus_sassafras::Epoch,
+	/// Secret data of the tickets owned by this node, indexed by ticket identifier.
+	///
+	/// Each entry also keeps the index of the owning authority within the epoch
+	/// authorities set.
+	pub tickets_aux: BTreeMap<TicketId, (AuthorityIndex, TicketSecret)>,
+}
+
+impl std::ops::Deref for Epoch {
+	type Target = sp_consensus_sassafras::Epoch;
+
+	fn deref(&self) -> &Self::Target {
+		&self.inner
+	}
+}
+
+impl std::ops::DerefMut for Epoch {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.inner
+	}
+}
+
+impl From<sp_consensus_sassafras::Epoch> for Epoch {
+	fn from(epoch: sp_consensus_sassafras::Epoch) -> Self {
+		Epoch { inner: epoch, tickets_aux: Default::default() }
+	}
+}
+
+impl EpochT for Epoch {
+	type NextEpochDescriptor = (NextEpochDescriptor, EpochConfiguration);
+	type Slot = Slot;
+
+	fn increment(&self, (descriptor, config): Self::NextEpochDescriptor) -> Epoch {
+		sp_consensus_sassafras::Epoch {
+			epoch_idx: self.epoch_idx + 1,
+			start_slot: self.start_slot + self.epoch_duration,
+			slot_duration: self.slot_duration,
+			epoch_duration: self.epoch_duration,
+			authorities: descriptor.authorities,
+			randomness: descriptor.randomness,
+			config,
+		}
+		.into()
+	}
+
+	fn start_slot(&self) -> Slot {
+		self.start_slot
+	}
+
+	fn end_slot(&self) -> Slot {
+		self.start_slot + self.epoch_duration
+	}
+}
+
+impl Epoch {
+	/// Create the genesis epoch (epoch #0).
+	///
+	/// This is defined to start at the slot of the first block, so that has to be provided.
+	pub fn genesis(config: &sp_consensus_sassafras::Epoch, slot: Slot) -> Epoch {
+		let mut epoch: Epoch = config.clone().into();
+		epoch.epoch_idx = 0;
+		epoch.start_slot = slot;
+		epoch
+	}
+
+	/// Clone and tweak epoch information to refer to the specified slot.
+	///
+	/// All the information which depends on the slot value is recomputed and assigned
+	/// to the returned epoch instance.
+	///
+	/// The `slot` must be greater than or equal the original epoch start slot,
+	/// if is less this operation is equivalent to a simple clone.
+	pub fn clone_for_slot(&self, slot: Slot) -> Epoch {
+		let mut epoch = self.clone();
+
+		let skipped_epochs = *slot.saturating_sub(self.start_slot) / self.epoch_duration;
+
+		let epoch_idx = epoch.epoch_idx.checked_add(skipped_epochs).expect(
+			"epoch number is u64; it should be strictly smaller than number of slots; \
+				slots relate in some way to wall clock time; \
+				if u64 is not enough we should crash for safety; qed.",
+		);
+
+		let start_slot = skipped_epochs
+			.checked_mul(epoch.epoch_duration)
+			.and_then(|skipped_slots| epoch.start_slot.checked_add(skipped_slots))
+			.expect(
+				"slot number is u64; it should relate in some way to wall clock time; \
+				 if u64 is not enough we should crash for safety; qed.",
+			);
+
+		epoch.epoch_idx = epoch_idx;
+		epoch.start_slot = Slot::from(start_slot);
+
+		epoch
+	}
+}
+
+/// Intermediate value passed to block importer.
+pub struct SassafrasIntermediate<B: BlockT> {
+	/// The epoch descriptor.
+	pub epoch_descriptor: ViableEpochDescriptor<B::Hash, NumberFor<B>, Epoch>,
+}
+
+/// Read protocol configuration from the runtime state at the best block.
+///
+/// The returned value is the current epoch information, used by the client
+/// to construct the genesis epoch.
+pub fn configuration<B: BlockT, C>(client: &C) -> ClientResult<sp_consensus_sassafras::Epoch>
+where
+	C: AuxStore + ProvideRuntimeApi<B> + UsageProvider<B>,
+	C::Api: SassafrasApi<B>,
+{
+	let at_hash = if client.usage_info().chain.finalized_state.is_some() {
+		client.usage_info().chain.best_hash
+	} else {
+		debug!(target: LOG_TARGET, "No finalized state is available. Reading config from genesis");
+		client.usage_info().chain.genesis_hash
+	};
+
+	let mut config = client.runtime_api().current_epoch(at_hash)?;
+	if config.authorities.is_empty() {
+		return Err(sp_blockchain::Error::VersionInvalid(
+			"Sassafras runtime configuration has an empty authorities set".into(),
+		))
+	}
+	// The genesis epoch always starts with the first block.
+	config.epoch_idx = 0;
+	Ok(config)
+}
+
+/// Extract the Sassafras slot claim from the given header.
+///
+/// Slot claim digest is mandatory, the function will return `Err` if none is found.
+pub fn find_slot_claim<B: BlockT>(header: &B::Header) -> Result<SlotClaim, Error<B>> {
+	if header.number().is_zero() {
+		// Genesis block doesn't contain a slot claim so let's generate a
+		// dummy one to not break any invariants in the rest of the code.
+		use sp_core::crypto::{Pair, VrfSecret};
+		let pair = sp_consensus_sassafras::AuthorityPair::from_seed(&[0u8; 32]);
+		let data = vrf::slot_claim_sign_data(&Default::default(), 0.into(), 0);
+		return Ok(SlotClaim {
+			authority_idx: 0,
+			slot: 0.into(),
+			ticket_claim: None,
+			vrf_signature: pair.as_ref().vrf_sign(&data),
+		})
+	}
+
+	let mut claim: Option<_> = None;
+	for log in header.digest().logs() {
+		trace!(target: LOG_TARGET, "Checking log {:?}, looking for slot claim digest", log);
+		match (log.as_sassafras_pre_digest(), claim.is_some()) {
+			(Some(_), true) => return Err(sassafras_err(Error::MultiplePreRuntimeDigests)),
+			(None, _) => trace!(target: LOG_TARGET, "Ignoring digest not meant for us"),
+			(s, false) => claim = s,
+		}
+	}
+	claim.ok_or_else(|| sassafras_err(Error::NoPreRuntimeDigest))
+}
+
+/// Extract the Sassafras epoch change digest from the given header, if it exists.
+fn find_next_epoch_digest<B: BlockT>(
+	header: &B::Header,
+) -> Result<Option<NextEpochDescriptor>, Error<B>> {
+	let mut epoch_digest: Option<_> = None;
+	for log in header.digest().logs() {
+		trace!(target: LOG_TARGET, "Checking log {:?}, looking for epoch change digest.", log);
+		let log = log.try_to::<ConsensusLog>(OpaqueDigestItemId::Consensus(&SASSAFRAS_ENGINE_ID));
+		match (log, epoch_digest.is_some()) {
+			(Some(ConsensusLog::NextEpochData(_)), true) =>
+				return Err(sassafras_err(Error::MultipleEpochChangeDigests)),
+			(Some(ConsensusLog::NextEpochData(epoch)), false) => epoch_digest = Some(epoch),
+			_ => trace!(target: LOG_TARGET, "Ignoring digest not meant for us"),
+		}
+	}
+
+	Ok(epoch_digest)
+}
+
+/// State that must be shared between the import queue and the authoring logic.
+#[derive(Clone)]
+pub struct SassafrasLink<B: BlockT> {
+	/// Epoch changes tree.
+	epoch_changes: sc_consensus_epochs::SharedEpochChanges<B, Epoch>,
+	/// Genesis epoch configuration.
+	genesis_config: sp_consensus_sassafras::Epoch,
+}
+
+impl<B: BlockT> SassafrasLink<B> {
+	/// Get the epoch changes of this link.
+	pub fn epoch_changes(&self) -> &sc_consensus_epochs::SharedEpochChanges<B, Epoch> {
+		&self.epoch_changes
+	}
+
+	/// Get the genesis configuration of this link.
+	pub fn genesis_config(&self) -> &sp_consensus_sassafras::Epoch {
+		&self.genesis_config
+	}
+}
+
+/// Message signed by the ticket ephemeral key when claiming a slot.
+fn ticket_claim_message(sign_data: &vrf::VrfSignData) -> [u8; 32] {
+	sign_data.challenge::<32>()
+}
+
+/// Construct the slot claim signing data.
+///
+/// If the slot is claimed using a ticket then the ticket body is appended to the
+/// transcript and the ticket identifier VRF input is signed as well. This allows
+/// the verifier to check that the claimer actually owns the ticket.
+fn slot_claim_sign_data(
+	randomness: &Randomness,
+	slot: Slot,
+	epoch_idx: u64,
+	ticket_body: Option<&TicketBody>,
+) -> vrf::VrfSignData {
+	let mut sign_data = vrf::slot_claim_sign_data(randomness, slot, epoch_idx);
+	if let Some(body) = ticket_body {
+		sign_data.push_transcript_data(&body.encode());
+		let ticket_id_input = vrf::ticket_id_input(randomness, body.attempt_idx, epoch_idx);
+		sign_data
+			.push_vrf_input(ticket_id_input)
+			.expect("Sign data has at most two vrf inputs; qed");
+	}
+	sign_data
+}
@@ -0,0 +1,493 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+//! # Sassafras
+//! Types and functions related to block verification.
+
+use crate::{
+	authorship::secondary_authority_idx, find_slot_claim, sassafras_err, slot_claim_sign_data,
+	ticket_claim_message, Epoch, Error, SassafrasIntermediate, SassafrasLink, INTERMEDIATE_KEY,
+	LOG_TARGET,
+};
+
+use std::sync::Arc;
+
+use log::{debug, info, trace, warn};
+use prometheus_endpoint::Registry;
+
+use sc_client_api::backend::AuxStore;
+use sc_consensus::{
+	block_import::{BlockImport, BlockImportParams},
+	import_queue::{BasicQueue, BoxJustificationImport, DefaultImportQueue, Verifier},
+};
+use sc_consensus_epochs::{descendent_query, Epoch as EpochT, SharedEpochChanges};
+use sc_consensus_slots::{check_equivocation, CheckedHeader, InherentDataProviderExt};
+use sc_telemetry::{telemetry, TelemetryHandle, CONSENSUS_DEBUG, CONSENSUS_TRACE};
+use sp_api::{ApiExt, ProvideRuntimeApi};
+use sp_block_builder::BlockBuilder as BlockBuilderApi;
+use sp_blockchain::{HeaderBackend, HeaderMetadata, Result as ClientResult};
+use sp_consensus::{BlockOrigin, Error as ConsensusError};
+use sp_consensus_sassafras::{
+	digests::{CompatibleDigestItem, SlotClaim},
+	vrf, AuthorityId, AuthorityPair, SassafrasApi, Slot, TicketBody, TicketId,
+};
+use sp_core::{
+	bandersnatch,
+	crypto::{Pair, VrfPublic},
+	ed25519,
+};
+use sp_inherents::{CreateInherentDataProviders, InherentData, InherentDataProvider};
+use sp_runtime::{
+	traits::{Block as BlockT, Header},
+	DigestItem,
+};
+
+/// Verification parameters
+struct VerificationParams<'a, B: 'a + BlockT> {
+	/// The header being verified.
+	header: B::Header,
+	/// The slot claim of the header being verified.
+	claim: SlotClaim,
+	/// The slot number of the current time.
+	slot_now: Slot,
+	/// Epoch descriptor of the epoch this block _should_ be under, if it's valid.
+	epoch: &'a Epoch,
+	/// Expected ticket for this block, as reported by the runtime.
+	maybe_ticket: Option<(TicketId, TicketBody)>,
+}
+
+/// Verified information
+struct VerifiedHeaderInfo {
+	/// Authority index.
+	authority_id: AuthorityId,
+	/// Seal digest found within the header.
+	seal: DigestItem,
+}
+
+/// Check a header has been signed by the right key. If the slot is too far in
+/// the future, an error will be returned. If successful, returns the pre-header
+/// and the digest item containing the seal.
+///
+/// The seal must be the last digest. Otherwise, the whole header is considered
+/// unsigned. This is required for security and must not be changed.
+///
+/// The claimed slot must be either assigned to the ticket found in the runtime
+/// or, if no ticket is assigned to the slot, to the deterministic secondary author.
+fn check_header<B: BlockT + Sized>(
+	params: VerificationParams<B>,
+) -> Result<CheckedHeader<B::Header, VerifiedHeaderInfo>, Error<B>> {
+	let VerificationParams { mut header, claim, slot_now, epoch, maybe_ticket } = params;
+
+	let seal = header
+		.digest_mut()
+		.pop()
+		.ok_or_else(|| sassafras_err(Error::HeaderUnsealed(header.hash())))?;
+
+	// Check that the slot is not in the future, with some drift being allowed.
+	if claim.slot > slot_now {
+		header.digest_mut().push(seal);
+		return Ok(CheckedHeader::Deferred(header, claim.slot))
+	}
+
+	let Some(authority_id) = epoch.authorities.get(claim.authority_idx as usize) else {
+		return Err(sassafras_err(Error::SlotAuthorNotFound))
+	};
+
+	// Check header signature (aka the Seal)
+
+	let signature = seal
+		.as_sassafras_seal()
+		.ok_or_else(|| sassafras_err(Error::HeaderBadSeal(header.hash())))?;
+
+	let pre_hash = header.hash();
+	if !AuthorityPair::verify(&signature, &pre_hash, authority_id) {
+		return Err(sassafras_err(Error::BadSignature(pre_hash)))
+	}
+
+	// Optionally check ticket ownership
+
+	let mut epoch_idx = epoch.epoch_idx;
+	if epoch.end_slot() <= claim.slot {
+		// Slot doesn't strictly belong to the epoch, create a clone with fixed values.
+		epoch_idx = epoch.clone_for_slot(claim.slot).epoch_idx;
+	}
+
+	let ticket_body = maybe_ticket.as_ref().map(|(_, body)| body);
+	let sign_data = slot_claim_sign_data(&epoch.randomness, claim.slot, epoch_idx, ticket_body);
+
+	match (&maybe_ticket, &claim.ticket_claim) {
+		(Some((ticket_id, ticket_body)), Some(ticket_claim)) => {
+			debug!(target: LOG_TARGET, "checking primary");
+
+			// Check that the ticket identifier is bound to the claimer's VRF output.
+			let ticket_id_input =
+				vrf::ticket_id_input(&epoch.randomness, ticket_body.attempt_idx, epoch_idx);
+			let ticket_id_output = claim
+				.vrf_signature
+				.vrf_outputs
+				.get(1)
+				.ok_or_else(|| sassafras_err(Error::MissingSignedVrfOutput))?;
+			if vrf::make_ticket_id(&ticket_id_input, ticket_id_output) != *ticket_id {
+				return Err(sassafras_err(Error::TicketVerificationFailed))
+			}
+
+			// Check that the claimer knows the erased ephemeral secret.
+			let message = ticket_claim_message(&sign_data);
+			if !ed25519::Pair::verify(
+				&ticket_claim.erased_signature,
+				&message,
+				&ticket_body.erased_public,
+			) {
+				return Err(sassafras_err(Error::TicketVerificationFailed))
+			}
+		},
+		(None, None) => {
+			debug!(target: LOG_TARGET, "checking secondary");
+			let idx = secondary_authority_idx(claim.slot, epoch);
+			if idx != Some(claim.authority_idx) {
+				return Err(sassafras_err(Error::SlotAuthorNotFound))
+			}
+		},
+		(Some(_), None) => {
+			warn!(target: LOG_TARGET, "Unexpected secondary authoring mechanism");
+			return Err(sassafras_err(Error::UnexpectedAuthoringMechanism))
+		},
+		(None, Some(_)) => {
+			warn!(target: LOG_TARGET, "Unexpected primary authoring mechanism");
+			return Err(sassafras_err(Error::UnexpectedAuthoringMechanism))
+		},
+	}
+
+	// Check slot-vrf proof
+
+	let public: &bandersnatch::Public = authority_id.as_ref();
+	if !public.vrf_verify(&sign_data, &claim.vrf_signature) {
+		return Err(sassafras_err(Error::VrfVerificationFailed))
+	}
+
+	let info = VerifiedHeaderInfo { authority_id: authority_id.clone(), seal };
+
+	Ok(CheckedHeader::Checked(header, info))
+}
+
+/// A verifier for Sassafras blocks.
+pub struct SassafrasVerifier<Block: BlockT, Client, SelectChain, CIDP> {
+	client: Arc<Client>,
+	select_chain: SelectChain,
+	create_inherent_data_providers: CIDP,
+	epoch_changes: SharedEpochChanges<Block, Epoch>,
+	genesis_config: sp_consensus_sassafras::Epoch,
+	telemetry: Option<TelemetryHandle>,
+}
+
+impl<Block, Client, SelectChain, CIDP> SassafrasVerifier<Block, Client, SelectChain, CIDP>
+where
+	Block: BlockT,
+	Client: AuxStore + HeaderBackend<Block> + HeaderMetadata<Block> + ProvideRuntimeApi<Block>,
+	Client::Api: BlockBuilderApi<Block> + SassafrasApi<Block>,
+	SelectChain: sp_consensus::SelectChain<Block>,
+	CIDP: CreateInherentDataProviders<Block, ()>,
+{
+	async fn check_inherents(
+		&self,
+		block: Block,
+		at_hash: Block::Hash,
+		inherent_data: InherentData,
+		create_inherent_data_providers: CIDP::InherentDataProviders,
+	) -> Result<(), Error<Block>> {
+		let inherent_res = self
+			.client
+			.runtime_api()
+			.check_inherents(at_hash, block, inherent_data)
+			.map_err(Error::RuntimeApi)?;
+
+		if !inherent_res.ok() {
+			for (i, e) in inherent_res.into_errors() {
+				match create_inherent_data_providers.try_handle_error(&i, &e).await {
+					Some(res) => res.map_err(Error::CheckInherents)?,
+					None => return Err(Error::CheckInherentsUnhandled(i)),
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	fn check_equivocation(
+		&self,
+		slot_now: Slot,
+		slot: Slot,
+		header: &Block::Header,
+		author: &AuthorityId,
+		origin: &BlockOrigin,
+	) -> Result<(), Error<Block>> {
+		// Don't report any equivocations during initial sync
+		// as they are most likely stale.
+		if *origin == BlockOrigin::NetworkInitialSync {
+			return Ok(())
+		}
+
+		// Check if authorship of this header is an equivocation and return a proof if so.
+		let Some(equivocation_proof) =
+			check_equivocation(&*self.client, slot_now, slot, header, author)
+				.map_err(Error::Client)?
+		else {
+			return Ok(())
+		};
+
+		info!(
+			target: LOG_TARGET,
+			"Slot author {:?} is equivocating at slot {} with headers {:?} and {:?}",
+			author,
+			slot,
+			equivocation_proof.first_header.hash(),
+			equivocation_proof.second_header.hash(),
+		);
+
+		// Equivocation reporting is not supported by the runtime yet.
+
+		Ok(())
+	}
+}
+
+#[async_trait::async_trait]
+impl<Block, Client, SelectChain, CIDP> Verifier<Block>
+	for SassafrasVerifier<Block, Client, SelectChain, CIDP>
+where
+	Block: BlockT,
+	Client: HeaderMetadata<Block, Error = sp_blockchain::Error>
+		+ HeaderBackend<Block>
+		+ ProvideRuntimeApi<Block>
+		+ Send
+		+ Sync
+		+ AuxStore,
+	Client::Api: BlockBuilderApi<Block> + SassafrasApi<Block>,
+	SelectChain: sp_consensus::SelectChain<Block>,
+	CIDP: CreateInherentDataProviders<Block, ()> + Send + Sync,
+	CIDP::InherentDataProviders: InherentDataProviderExt + Send + Sync,
+{
+	async fn verify(
+		&mut self,
+		mut block: BlockImportParams<Block>,
+	) -> Result<BlockImportParams<Block>, String> {
+		trace!(
+			target: LOG_TARGET,
+			"Verifying origin: {:?} header: {:?} justification(s): {:?} body: {:?}",
+			block.origin,
+			block.header,
+			block.justifications,
+			block.body,
+		);
+
+		if block.with_state() {
+			// When importing whole state we don't calculate epoch descriptor, but rather
+			// read it from the state after import. We also skip all verifications
+			// because there's no parent state and we trust the sync module to verify
+			// that the state is correct and finalized.
+			return Ok(block)
+		}
+
+		let hash = block.header.hash();
+		let parent_hash = *block.header.parent_hash();
+
+		let create_inherent_data_providers = self
+			.create_inherent_data_providers
+			.create_inherent_data_providers(parent_hash, ())
+			.await
+			.map_err(|e| Error::<Block>::Client(ConsensusError::from(e).into()))?;
+
+		let slot_now = create_inherent_data_providers.slot();
+
+		let parent_header_metadata = self
+			.client
+			.header_metadata(parent_hash)
+			.map_err(Error::<Block>::FetchParentHeader)?;
+
+		let claim = find_slot_claim::<Block>(&block.header)?;
+
+		// Fetch the expected slot ticket from the parent state.
+		let maybe_ticket = self
+			.client
+			.runtime_api()
+			.slot_ticket(parent_hash, claim.slot)
+			.map_err(Error::<Block>::RuntimeApi)?;
+
+		let (checked_header, epoch_descriptor) = {
+			let epoch_changes = self.epoch_changes.shared_data();
+			let epoch_descriptor = epoch_changes
+				.epoch_descriptor_for_child_of(
+					descendent_query(&*self.client),
+					&parent_hash,
+					parent_header_metadata.number,
+					claim.slot,
+				)
+				.map_err(|e| Error::<Block>::ForkTree(Box::new(e)))?
+				.ok_or(Error::<Block>::FetchEpoch(parent_hash))?;
+			let viable_epoch = epoch_changes
+				.viable_epoch(&epoch_descriptor, |slot| Epoch::genesis(&self.genesis_config, slot))
+				.ok_or(Error::<Block>::FetchEpoch(parent_hash))?;
+
+			let verification_params = VerificationParams {
+				header: block.header.clone(),
+				claim: claim.clone(),
+				// We add one to the current slot to allow for some small drift.
+				slot_now: slot_now + 1,
+				epoch: viable_epoch.as_ref(),
+				maybe_ticket,
+			};
+
+			(check_header::<Block>(verification_params)?, epoch_descriptor)
+		};
+
+		match checked_header {
+			CheckedHeader::Checked(pre_header, verified_info) => {
+				// The header is valid but let's check if there was something else already
+				// proposed at the same slot by the given author.
+				if let Err(err) = self.check_equivocation(
+					slot_now,
+					claim.slot,
+					&block.header,
+					&verified_info.authority_id,
+					&block.origin,
+				) {
+					warn!(target: LOG_TARGET, "Error checking/reporting equivocation: {}", err);
+				}
+
+				if let Some(inner_body) = block.body {
+					let new_block = Block::new(pre_header.clone(), inner_body);
+					if !block.state_action.skip_execution_checks() {
+						// If the body is passed through and the block was executed,
+						// we need to use the runtime to check that the internally-set
+						// timestamp in the inherents actually matches the slot set in the seal.
+						let inherent_data = create_inherent_data_providers
+							.create_inherent_data()
+							.await
+							.map_err(Error::<Block>::CreateInherents)?;
+
+						self.check_inherents(
+							new_block.clone(),
+							parent_hash,
+							inherent_data,
+							create_inherent_data_providers,
+						)
+						.await?;
+					}
+
+					let (_, inner_body) = new_block.deconstruct();
+					block.body = Some(inner_body);
+				}
+
+				trace!(target: LOG_TARGET, "Checked {:?}; importing.", pre_header);
+				telemetry!(
+					self.telemetry;
+					CONSENSUS_TRACE;
+					"sassafras.checked_and_importing";
+					"pre_header" => ?pre_header,
+				);
+
+				block.header = pre_header;
+				block.post_digests.push(verified_info.seal);
+				block.insert_intermediate(
+					INTERMEDIATE_KEY,
+					SassafrasIntermediate::<Block> { epoch_descriptor },
+				);
+				block.post_hash = Some(hash);
+
+				Ok(block)
+			},
+			CheckedHeader::Deferred(a, b) => {
+				debug!(target: LOG_TARGET, "Checking {:?} failed; {:?}, {:?}.", hash, a, b);
+				telemetry!(
+					self.telemetry;
+					CONSENSUS_DEBUG;
+					"sassafras.header_too_far_in_future";
+					"hash" => ?hash, "a" => ?a, "b" => ?b
+				);
+				Err(Error::<Block>::TooFarInFuture(hash).into())
+			},
+		}
+	}
+}
+
+/// Parameters passed to [`import_queue`].
+pub struct ImportQueueParams<'a, Block: BlockT, BI, Client, CIDP, SelectChain, Spawn> {
+	/// The Sassafras link that is created by [`crate::block_import`].
+	pub link: SassafrasLink<Block>,
+	/// The block import that should be wrapped.
+	pub block_import: BI,
+	/// Optional justification import.
+	pub justification_import: Option<BoxJustificationImport<Block>>,
+	/// The client to interact with the internals of the node.
+	pub client: Arc<Client>,
+	/// A [`SelectChain`](sp_consensus::SelectChain) implementation.
+	pub select_chain: SelectChain,
+	/// Used to crate the inherent data providers.
+	///
+	/// These inherent data providers are then used to create the inherent data that is
+	/// passed to the `check_inherents` runtime call.
+	pub create_inherent_data_providers: CIDP,
+	/// Spawner for spawning futures.
+	pub spawner: &'a Spawn,
+	/// Registry for prometheus metrics.
+	pub registry: Option<&'a Registry>,
+	/// Optional telemetry handle to report telemetry events.
+	pub telemetry: Option<TelemetryHandle>,
+}
+
+/// Start an import queue for the Sassafras consensus algorithm.
+///
+/// The block import object provided must be the `SassafrasBlockImport` or a wrapper
+/// of it, otherwise crucial import logic will be omitted.
+pub fn import_queue<Block: BlockT, Client, SelectChain, BI, CIDP, Spawn>(
+	ImportQueueParams {
+		link: sassafras_link,
+		block_import,
+		justification_import,
+		client,
+		select_chain,
+		create_inherent_data_providers,
+		spawner,
+		registry,
+		telemetry,
+	}: ImportQueueParams<'_, Block, BI, Client, CIDP, SelectChain, Spawn>,
+) -> ClientResult<DefaultImportQueue<Block>>
+where
+	BI: BlockImport<Block, Error = ConsensusError> + Send + Sync + 'static,
+	Client: ProvideRuntimeApi<Block>
+		+ HeaderBackend<Block>
+		+ HeaderMetadata<Block, Error = sp_blockchain::Error>
+		+ AuxStore
+		+ Send
+		+ Sync
+		+ 'static,
+	Client::Api: BlockBuilderApi<Block> + SassafrasApi<Block> + ApiExt<Block>,
+	SelectChain: sp_consensus::SelectChain<Block> + 'static,
+	CIDP: CreateInherentDataProviders<Block, ()> + Send + Sync + 'static,
+	CIDP::InherentDataProviders: InherentDataProviderExt + Send + Sync,
+	Spawn: sp_core::traits::SpawnEssentialNamed,
+{
+	let verifier = SassafrasVerifier {
+		select_chain,
+		create_inherent_data_providers,
+		genesis_config: sassafras_link.genesis_config,
+		epoch_changes: sassafras_link.epoch_changes,
+		telemetry,
+		client,
+	};
+
+	Ok(BasicQueue::new(verifier, Box::new(block_import), justification_import, spawner, registry))
+}
@@ -50,6 +50,9 @@ where
 /// Checks if the header is an equivocation and returns the proof in that case.
 ///
 /// Note: it detects equivocations only when slot_now - slot <= MAX_SLOT_CAPACITY.
+///
+/// The slot header map is read and written back, so callers verifying blocks concurrently must
+/// not call this for the same backend at the same time.
 pub fn check_equivocation<C, H, P>(
 	backend: &C,
 	slot_now: Slot,
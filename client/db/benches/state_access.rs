@@ -19,7 +19,9 @@
 use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
 use rand::{distributions::Uniform, rngs::StdRng, Rng, SeedableRng};
 use sc_client_api::{Backend as _, BlockImportOperation, NewBlockState, StateBackend};
-use sc_client_db::{Backend, BlocksPruning, DatabaseSettings, DatabaseSource, PruningMode};
+use sc_client_db::{
+	Backend, BlocksPruning, DatabaseSettings, DatabaseSource, OffchainIndexingPruning, PruningMode,
+};
 use sp_core::H256;
 use sp_runtime::{
 	testing::{Block as RawBlock, ExtrinsicWrapper, Header},
@@ -122,6 +124,7 @@ fn create_backend(config: BenchmarkConfig, temp_dir: &TempDir) -> Backend<Block>
 		state_pruning: Some(PruningMode::ArchiveAll),
 		source: DatabaseSource::ParityDb { path },
 		blocks_pruning: BlocksPruning::KeepAll,
+		offchain_indexing_pruning: OffchainIndexingPruning::KeepAll,
 	};
 
 	Backend::new(settings, 100).expect("Creates backend")
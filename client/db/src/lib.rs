@@ -71,10 +71,7 @@ use sp_blockchain::{
 	Backend as _, CachedHeaderMetadata, Error as ClientError, HeaderBackend, HeaderMetadata,
 	HeaderMetadataCache, Result as ClientResult,
 };
-use sp_core::{
-	offchain::OffchainOverlayedChange,
-	storage::{well_known_keys, ChildInfo},
-};
+use sp_core::storage::{well_known_keys, ChildInfo};
 use sp_database::Transaction;
 use sp_runtime::{
 	generic::BlockId,
@@ -97,6 +94,7 @@ pub use sc_state_db::PruningMode;
 pub use sp_database::Database;
 
 pub use bench::BenchmarkingState;
+pub use offchain::OffchainIndexingPruning;
 
 const CACHE_HEADERS: usize = 8;
 
@@ -292,6 +290,8 @@ pub struct DatabaseSettings {
 	///
 	/// NOTE: only finalized blocks are subject for removal!
 	pub blocks_pruning: BlocksPruning,
+	/// Pruning mode of the data written by offchain indexing.
+	pub offchain_indexing_pruning: OffchainIndexingPruning,
 }
 
 /// Block pruning settings.
@@ -830,17 +830,21 @@ pub struct BlockImportOperation<Block: BlockT> {
 }
 
 impl<Block: BlockT> BlockImportOperation<Block> {
-	fn apply_offchain(&mut self, transaction: &mut Transaction<DbHash>) {
-		let mut count = 0;
-		for ((prefix, key), value_operation) in self.offchain_storage_updates.drain(..) {
-			count += 1;
-			let key = crate::offchain::concatenate_prefix_and_key(&prefix, &key);
-			match value_operation {
-				OffchainOverlayedChange::SetValue(val) =>
-					transaction.set_from_vec(columns::OFFCHAIN, &key, val),
-				OffchainOverlayedChange::Remove => transaction.remove(columns::OFFCHAIN, &key),
-			}
-		}
+	fn apply_offchain(
+		&mut self,
+		transaction: &mut Transaction<DbHash>,
+		offchain_indexing: &offchain::OffchainIndexing,
+		finalized: NumberFor<Block>,
+	) {
+		let block = self.pending_block.as_ref().map(|pending_block| {
+			((*pending_block.header.number()).saturated_into::<u64>(), pending_block.header.hash())
+		});
+		let count = offchain_indexing.apply_changes(
+			transaction,
+			self.offchain_storage_updates.drain(..),
+			block.as_ref().map(|(number, hash)| (*number, hash.as_ref())),
+			finalized.saturated_into(),
+		);
 
 		if count > 0 {
 			log::debug!(target: "sc_offchain", "Applied {} offchain indexing changes.", count);
@@ -1093,6 +1097,7 @@ impl<T: Clone> FrozenForDuration<T> {
 pub struct Backend<Block: BlockT> {
 	storage: Arc<StorageDb<Block>>,
 	offchain_storage: offchain::LocalStorage,
+	offchain_indexing: offchain::OffchainIndexing,
 	blockchain: BlockchainDb<Block>,
 	canonicalization_delay: u64,
 	import_lock: Arc<RwLock<()>>,
@@ -1158,6 +1163,7 @@ impl<Block: BlockT> Backend<Block> {
 			state_pruning: Some(state_pruning),
 			source: DatabaseSource::Custom { db, require_create_flag: true },
 			blocks_pruning,
+			offchain_indexing_pruning: OffchainIndexingPruning::KeepAll,
 		};
 
 		Self::new(db_setting, canonicalization_delay).expect("failed to create test-db")
@@ -1210,10 +1216,13 @@ impl<Block: BlockT> Backend<Block> {
 			StorageDb { db: db.clone(), state_db, prefix_keys: !db.supports_ref_counting() };
 
 		let offchain_storage = offchain::LocalStorage::new(db.clone());
+		let offchain_indexing =
+			offchain::OffchainIndexing::new(db.clone(), config.offchain_indexing_pruning);
 
 		let backend = Backend {
 			storage: Arc::new(storage_db),
 			offchain_storage,
+			offchain_indexing,
 			blockchain,
 			canonicalization_delay,
 			import_lock: Default::default(),
@@ -1429,7 +1438,8 @@ impl<Block: BlockT> Backend<Block> {
 		let mut transaction = Transaction::new();
 
 		operation.apply_aux(&mut transaction);
-		operation.apply_offchain(&mut transaction);
+		let finalized = self.blockchain.meta.read().finalized_number;
+		operation.apply_offchain(&mut transaction, &self.offchain_indexing, finalized);
 
 		let mut meta_updates = Vec::with_capacity(operation.finalized_blocks.len());
 		let (best_num, mut last_finalized_hash, mut last_finalized_num, mut block_gap) = {
@@ -1747,10 +1757,22 @@ impl<Block: BlockT> Backend<Block> {
 			self.blockchain.update_meta(m);
 		}
 		self.blockchain.update_block_gap(block_gap);
+		self.prune_offchain_indexing();
 
 		Ok(())
 	}
 
+	// prune the offchain indexing data of the blocks finalized since the last call.
+	// this happens in its own transaction, so failures are only logged.
+	fn prune_offchain_indexing(&self) {
+		let finalized = self.blockchain.meta.read().finalized_number;
+		if let Err(e) = self.offchain_indexing.prune(finalized.saturated_into(), |number| {
+			self.blockchain.hash(number.saturated_into())
+		}) {
+			warn!(target: "db", "Failed to prune offchain indexing data: {}", e);
+		}
+	}
+
 	// write stuff to a transaction after a new block is finalized.
 	// this canonicalizes finalized blocks. Fails if called with a block which
 	// was not a child of the last finalized block.
@@ -2109,6 +2131,7 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 
 		self.storage.db.commit(transaction)?;
 		self.blockchain.update_meta(m);
+		self.prune_offchain_indexing();
 		Ok(())
 	}
 
@@ -2171,6 +2194,8 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 			self.shared_trie_cache.as_ref().map_or(0, |c| c.used_memory_size()),
 		);
 
+		let offchain_indexing = self.offchain_indexing.stats();
+
 		Some(UsageInfo {
 			memory: MemoryInfo { state_cache, database_cache },
 			io: IoInfo {
@@ -2186,6 +2211,7 @@ impl<Block: BlockT> sc_client_api::backend::Backend<Block> for Backend<Block> {
 				state_reads_cache: state_stats.cache_reads.ops,
 				state_writes_nodes: state_stats.nodes_writes.ops,
 			},
+			offchain_indexing,
 		})
 	}
 
@@ -2676,6 +2702,7 @@ pub(crate) mod tests {
 				state_pruning: Some(PruningMode::blocks_pruning(1)),
 				source: DatabaseSource::Custom { db: backing, require_create_flag: false },
 				blocks_pruning: BlocksPruning::KeepFinalized,
+				offchain_indexing_pruning: OffchainIndexingPruning::KeepAll,
 			},
 			0,
 		)
@@ -17,12 +17,365 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 //! RocksDB-based offchain workers local storage.
+//!
+//! Besides the storage itself, this module keeps track of the data written by offchain indexing
+//! during block import. Every indexed key has a record of the block that last wrote it, which
+//! allows to enumerate indexed keys and to prune them according to [`OffchainIndexingPruning`].
 
 use std::{collections::HashMap, sync::Arc};
 
 use crate::{columns, Database, DbHash, Transaction};
+use codec::{Decode, Encode};
 use log::error;
 use parking_lot::Mutex;
+use sc_client_api::OffchainIndexingInfo;
+use sp_core::offchain::OffchainOverlayedChange;
+
+/// Prefix of the records kept for every key written by offchain indexing.
+const INDEXED_KEY_PREFIX: &[u8] = b":offchain_indexing:key:";
+/// Prefix of the journals listing the keys indexed by the blocks of a given height.
+const JOURNAL_PREFIX: &[u8] = b":offchain_indexing:journal:";
+/// Key of the offchain indexing bookkeeping record.
+const META_KEY: &[u8] = b":offchain_indexing:meta";
+
+/// Offchain indexing pruning settings.
+///
+/// Offchain indexing is not fork-aware: a key keeps the value written by the last imported block,
+/// regardless of the fork that block is on. When the data of a block is pruned, the keys it wrote
+/// get back the value written by the previous block that wrote them, or are removed if there is
+/// no such block.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum OffchainIndexingPruning {
+	/// Keep all the data written by offchain indexing.
+	#[default]
+	KeepAll,
+	/// Remove the data indexed by blocks that did not become canonical, once their height is
+	/// finalized.
+	KeepCanonical,
+	/// Like [`Self::KeepCanonical`], and also remove the data indexed by canonical blocks that
+	/// are more than N blocks behind the last finalized block.
+	Some(u32),
+}
+
+/// Record of the block that last wrote an indexed key.
+#[derive(Encode, Decode)]
+struct IndexedKey {
+	number: u64,
+	hash: Vec<u8>,
+	size: u32,
+	/// Values written by the blocks that wrote the key before, in import order.
+	///
+	/// Only the last value written by a finalized block is kept, along with the values written
+	/// by the blocks that are not finalized yet.
+	previous: Vec<PreviousValue>,
+}
+
+/// Value of an indexed key written by a block that was overwritten by another block.
+#[derive(Encode, Decode)]
+struct PreviousValue {
+	number: u64,
+	hash: Vec<u8>,
+	value: Vec<u8>,
+}
+
+/// Hashes of the blocks of a given height, with the keys they indexed.
+type Journal = Vec<(Vec<u8>, Vec<Vec<u8>>)>;
+
+/// Offchain indexing bookkeeping.
+#[derive(Encode, Decode)]
+struct IndexingMeta {
+	/// Number of indexed keys that are still stored.
+	keys: u64,
+	/// Total size of the values stored under these keys.
+	bytes: u64,
+	/// Last finalized block the journals were canonicalized for.
+	last_finalized: u64,
+	/// Last block whose journal was pruned because of its age.
+	last_expired: u64,
+}
+
+/// Keeps track of the data written by offchain indexing and prunes it.
+pub(crate) struct OffchainIndexing {
+	db: Arc<dyn Database<DbHash>>,
+	pruning: OffchainIndexingPruning,
+	/// Last finalized and last expired blocks the pruning ran for.
+	///
+	/// The bookkeeping record is only updated when something is pruned, so these may be ahead of
+	/// the ones stored in it.
+	pruned: Mutex<(u64, u64)>,
+}
+
+impl OffchainIndexing {
+	/// Create a new instance with the given pruning mode.
+	pub(crate) fn new(db: Arc<dyn Database<DbHash>>, pruning: OffchainIndexingPruning) -> Self {
+		Self { db, pruning, pruned: Mutex::new((0, 0)) }
+	}
+
+	/// Current offchain indexing statistics.
+	pub(crate) fn stats(&self) -> OffchainIndexingInfo {
+		self.read_meta()
+			.map(|meta| OffchainIndexingInfo { keys: meta.keys, bytes: meta.bytes })
+			.unwrap_or_default()
+	}
+
+	/// Write the offchain indexing changes of the block with given number and hash to the
+	/// transaction.
+	///
+	/// Changes that don't come with a block are written without being tracked.
+	pub(crate) fn apply_changes(
+		&self,
+		transaction: &mut Transaction<DbHash>,
+		changes: impl IntoIterator<Item = ((Vec<u8>, Vec<u8>), OffchainOverlayedChange)>,
+		block: Option<(u64, &[u8])>,
+		finalized: u64,
+	) -> usize {
+		let mut meta = None;
+		let mut indexed = Vec::new();
+		let mut count = 0;
+
+		for ((prefix, key), value_operation) in changes {
+			count += 1;
+			let key = concatenate_prefix_and_key(&prefix, &key);
+			let record_key = concatenate_prefix_and_key(INDEXED_KEY_PREFIX, &key);
+			let meta = meta.get_or_insert_with(|| {
+				self.read_meta().unwrap_or(IndexingMeta {
+					keys: 0,
+					bytes: 0,
+					last_finalized: finalized,
+					last_expired: finalized,
+				})
+			});
+
+			let record = self.read_record(&record_key);
+			if let Some(record) = &record {
+				meta.keys = meta.keys.saturating_sub(1);
+				meta.bytes = meta.bytes.saturating_sub(record.size.into());
+				transaction.remove(columns::OFFCHAIN, &record_key);
+			}
+
+			match value_operation {
+				OffchainOverlayedChange::SetValue(val) => {
+					if let Some((number, hash)) = block {
+						let previous = match record {
+							Some(record) if self.pruning != OffchainIndexingPruning::KeepAll =>
+								self.previous_values(record, &key, finalized),
+							_ => Vec::new(),
+						};
+						let record = IndexedKey {
+							number,
+							hash: hash.to_vec(),
+							size: val.len() as u32,
+							previous,
+						};
+						meta.keys += 1;
+						meta.bytes += val.len() as u64;
+						transaction.set_from_vec(columns::OFFCHAIN, &record_key, record.encode());
+						indexed.push(key.clone());
+					}
+					transaction.set_from_vec(columns::OFFCHAIN, &key, val);
+				},
+				OffchainOverlayedChange::Remove => transaction.remove(columns::OFFCHAIN, &key),
+			}
+		}
+
+		if let Some(meta) = meta {
+			transaction.set_from_vec(columns::OFFCHAIN, META_KEY, meta.encode());
+		}
+
+		if let (Some((number, hash)), false) = (block, indexed.is_empty()) {
+			if self.pruning != OffchainIndexingPruning::KeepAll {
+				let mut journal = self.read_journal(number);
+				journal.retain(|(h, _)| h.as_slice() != hash);
+				journal.push((hash.to_vec(), indexed));
+				transaction.set_from_vec(columns::OFFCHAIN, &journal_key(number), journal.encode());
+			}
+		}
+
+		count
+	}
+
+	/// Returns the previous values to keep in the record of `key` when it is overwritten.
+	fn previous_values(
+		&self,
+		record: IndexedKey,
+		key: &[u8],
+		finalized: u64,
+	) -> Vec<PreviousValue> {
+		let mut previous = record.previous;
+		if let Some(value) = self.db.get(columns::OFFCHAIN, key) {
+			previous.push(PreviousValue { number: record.number, hash: record.hash, value });
+		}
+
+		// The values written by the finalized blocks are canonical, only the last one may need to
+		// be restored.
+		let last_finalized = previous.iter().rposition(|value| value.number <= finalized);
+		previous
+			.into_iter()
+			.enumerate()
+			.filter(|(index, value)| value.number > finalized || Some(*index) == last_finalized)
+			.map(|(_, value)| value)
+			.collect()
+	}
+
+	/// Prune the data indexed by the blocks finalized since the last call.
+	///
+	/// `canonical_hash` returns the hash of the finalized block with the given number.
+	pub(crate) fn prune<H: AsRef<[u8]>>(
+		&self,
+		finalized: u64,
+		canonical_hash: impl Fn(u64) -> sp_blockchain::Result<Option<H>>,
+	) -> sp_blockchain::Result<()> {
+		if self.pruning == OffchainIndexingPruning::KeepAll {
+			return Ok(())
+		}
+
+		let mut pruned = self.pruned.lock();
+		let mut meta = match self.read_meta() {
+			Some(meta) => meta,
+			None => return Ok(()),
+		};
+		meta.last_finalized = std::cmp::max(meta.last_finalized, pruned.0);
+		meta.last_expired = std::cmp::max(meta.last_expired, pruned.1);
+		if meta.last_finalized >= finalized {
+			return Ok(())
+		}
+
+		let mut transaction = Transaction::new();
+		let mut records = HashMap::new();
+		let mut changed = false;
+
+		for number in meta.last_finalized + 1..=finalized {
+			let mut journal = self.read_journal(number);
+			if journal.is_empty() {
+				continue
+			}
+
+			let canonical = canonical_hash(number)?.map(|hash| hash.as_ref().to_vec());
+			for (hash, keys) in &journal {
+				if canonical.as_ref() != Some(hash) {
+					self.prune_keys(&mut transaction, &mut records, &mut meta, number, hash, keys);
+				}
+			}
+
+			// The canonical entry is only needed for pruning by age.
+			let len = journal.len();
+			journal.retain(|(hash, _)| {
+				canonical.as_ref() == Some(hash) &&
+					matches!(self.pruning, OffchainIndexingPruning::Some(_))
+			});
+			if journal.is_empty() {
+				changed = true;
+				transaction.remove(columns::OFFCHAIN, &journal_key(number));
+			} else if journal.len() != len {
+				changed = true;
+				transaction.set_from_vec(columns::OFFCHAIN, &journal_key(number), journal.encode());
+			}
+		}
+
+		let expire_to = match self.pruning {
+			OffchainIndexingPruning::Some(keep) =>
+				finalized.saturating_sub(std::cmp::max(keep, 1).into()),
+			_ => finalized,
+		};
+		if let OffchainIndexingPruning::Some(_) = self.pruning {
+			for number in meta.last_expired + 1..=expire_to {
+				let journal = self.read_journal(number);
+				for (hash, keys) in &journal {
+					self.prune_keys(&mut transaction, &mut records, &mut meta, number, hash, keys);
+				}
+				if !journal.is_empty() {
+					changed = true;
+					transaction.remove(columns::OFFCHAIN, &journal_key(number));
+				}
+			}
+		}
+
+		meta.last_finalized = finalized;
+		meta.last_expired = std::cmp::max(meta.last_expired, expire_to);
+		if changed {
+			for (record_key, record) in records {
+				match record {
+					Some(record) =>
+						transaction.set_from_vec(columns::OFFCHAIN, &record_key, record.encode()),
+					None => transaction.remove(columns::OFFCHAIN, &record_key),
+				}
+			}
+			transaction.set_from_vec(columns::OFFCHAIN, META_KEY, meta.encode());
+			self.db.commit(transaction)?;
+		}
+		*pruned = (meta.last_finalized, meta.last_expired);
+
+		Ok(())
+	}
+
+	/// Remove the values of the keys written by the given block.
+	///
+	/// The keys that were last written by this block get back their previous value, if any. The
+	/// updated records are kept in `records`, to be written once all the blocks are pruned.
+	fn prune_keys(
+		&self,
+		transaction: &mut Transaction<DbHash>,
+		records: &mut HashMap<Vec<u8>, Option<IndexedKey>>,
+		meta: &mut IndexingMeta,
+		number: u64,
+		hash: &[u8],
+		keys: &[Vec<u8>],
+	) {
+		for key in keys {
+			let record = records
+				.entry(concatenate_prefix_and_key(INDEXED_KEY_PREFIX, key))
+				.or_insert_with_key(|record_key| self.read_record(record_key));
+			let Some(current) = record else { continue };
+
+			if current.number != number || current.hash != hash {
+				current
+					.previous
+					.retain(|previous| previous.number != number || previous.hash != hash);
+				continue
+			}
+
+			meta.keys = meta.keys.saturating_sub(1);
+			meta.bytes = meta.bytes.saturating_sub(current.size.into());
+			match current.previous.pop() {
+				Some(PreviousValue { number, hash, value }) => {
+					meta.keys += 1;
+					meta.bytes += value.len() as u64;
+					current.number = number;
+					current.hash = hash;
+					current.size = value.len() as u32;
+					transaction.set_from_vec(columns::OFFCHAIN, key, value);
+				},
+				None => {
+					*record = None;
+					transaction.remove(columns::OFFCHAIN, key);
+				},
+			}
+		}
+	}
+
+	fn read_meta(&self) -> Option<IndexingMeta> {
+		self.db
+			.get(columns::OFFCHAIN, META_KEY)
+			.and_then(|meta| IndexingMeta::decode(&mut &meta[..]).ok())
+	}
+
+	fn read_record(&self, record_key: &[u8]) -> Option<IndexedKey> {
+		self.db
+			.get(columns::OFFCHAIN, record_key)
+			.and_then(|record| IndexedKey::decode(&mut &record[..]).ok())
+	}
+
+	fn read_journal(&self, number: u64) -> Journal {
+		self.db
+			.get(columns::OFFCHAIN, &journal_key(number))
+			.and_then(|journal| Journal::decode(&mut &journal[..]).ok())
+			.unwrap_or_default()
+	}
+}
+
+fn journal_key(number: u64) -> Vec<u8> {
+	concatenate_prefix_and_key(JOURNAL_PREFIX, &number.to_be_bytes())
+}
 
 /// Offchain local storage
 #[derive(Clone)]
@@ -75,6 +428,37 @@ impl sp_core::offchain::OffchainStorage for LocalStorage {
 		self.db.get(columns::OFFCHAIN, &concatenate_prefix_and_key(prefix, key))
 	}
 
+	fn indexed_keys(
+		&self,
+		prefix: &[u8],
+		key_prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: usize,
+	) -> Option<Vec<Vec<u8>>> {
+		// ParityDb doesn't keep the keys of the offchain column ordered.
+		if !self.db.supports_ordered_iteration() {
+			return None
+		}
+
+		let mut keys = Vec::new();
+		if count == 0 {
+			return Some(keys)
+		}
+
+		let skip = INDEXED_KEY_PREFIX.len() + prefix.len();
+		let iter_prefix = [INDEXED_KEY_PREFIX, prefix, key_prefix].concat();
+		let start = start_key.map(|start_key| [INDEXED_KEY_PREFIX, prefix, start_key].concat());
+		self.db
+			.iter_prefix(columns::OFFCHAIN, &iter_prefix, start.as_deref(), &mut |key, _| {
+				let key = &key[skip..];
+				if start_key != Some(key) {
+					keys.push(key.to_vec());
+				}
+				keys.len() < count
+			});
+		Some(keys)
+	}
+
 	fn compare_and_set(
 		&mut self,
 		prefix: &[u8],
@@ -120,7 +504,228 @@ pub(crate) fn concatenate_prefix_and_key(prefix: &[u8], key: &[u8]) -> Vec<u8> {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use sp_core::offchain::OffchainStorage;
+	use sp_core::offchain::{OffchainStorage, STORAGE_PREFIX};
+
+	fn new_indexing(pruning: OffchainIndexingPruning) -> (LocalStorage, OffchainIndexing) {
+		let db = kvdb_memorydb::create(crate::utils::NUM_COLUMNS);
+		let db = sp_database::as_database(db);
+		(LocalStorage::new(db.clone()), OffchainIndexing::new(db, pruning))
+	}
+
+	fn index(
+		indexing: &OffchainIndexing,
+		number: u64,
+		hash: &[u8],
+		changes: &[(&[u8], Option<&[u8]>)],
+	) {
+		index_with_finalized(indexing, number, hash, changes, 0)
+	}
+
+	fn index_with_finalized(
+		indexing: &OffchainIndexing,
+		number: u64,
+		hash: &[u8],
+		changes: &[(&[u8], Option<&[u8]>)],
+		finalized: u64,
+	) {
+		let changes = changes.iter().map(|(key, value)| {
+			let change = match value {
+				Some(value) => OffchainOverlayedChange::SetValue(value.to_vec()),
+				None => OffchainOverlayedChange::Remove,
+			};
+			((STORAGE_PREFIX.to_vec(), key.to_vec()), change)
+		});
+		let mut tx = Transaction::new();
+		indexing.apply_changes(&mut tx, changes, Some((number, hash)), finalized);
+		indexing.db.commit(tx).unwrap();
+	}
+
+	fn prune(indexing: &OffchainIndexing, finalized: u64, canonical: &[&[u8]]) {
+		indexing
+			.prune(finalized, |number| {
+				Ok(canonical.get(number as usize - 1).map(|hash| hash.to_vec()))
+			})
+			.unwrap();
+	}
+
+	#[test]
+	fn should_track_and_list_indexed_keys() {
+		let (mut storage, indexing) = new_indexing(OffchainIndexingPruning::KeepAll);
+		storage.set(STORAGE_PREFIX, b"a_worker", b"v");
+		index(
+			&indexing,
+			1,
+			b"h1",
+			&[(b"a1", Some(b"v1")), (b"a2", Some(b"v2")), (b"b", Some(b"v"))],
+		);
+		index(&indexing, 2, b"h2", &[(b"a2", Some(b"value2")), (b"b", None)]);
+
+		assert_eq!(storage.get(STORAGE_PREFIX, b"a2"), Some(b"value2".to_vec()));
+		assert_eq!(storage.get(STORAGE_PREFIX, b"b"), None);
+		assert_eq!(indexing.stats(), OffchainIndexingInfo { keys: 2, bytes: 8 });
+
+		let keys = |start_key: Option<&[u8]>, count| {
+			storage.indexed_keys(STORAGE_PREFIX, b"a", start_key, count).unwrap()
+		};
+		assert_eq!(keys(None, 10), vec![b"a1".to_vec(), b"a2".to_vec()]);
+		assert_eq!(keys(None, 1), vec![b"a1".to_vec()]);
+		assert_eq!(keys(Some(b"a1"), 1), vec![b"a2".to_vec()]);
+		assert!(keys(Some(b"a2"), 10).is_empty());
+		assert!(keys(None, 0).is_empty());
+	}
+
+	#[test]
+	fn should_page_through_indexed_keys() {
+		let (storage, indexing) = new_indexing(OffchainIndexingPruning::KeepAll);
+		let mut expected = (0..=255u8)
+			.step_by(51)
+			.flat_map(|a| [vec![b'k', a], vec![b'k', a, 0], vec![b'k', a, 255, 7]])
+			.collect::<Vec<_>>();
+		let changes = expected.iter().map(|key| (&key[..], Some(&b"v"[..]))).collect::<Vec<_>>();
+		index(&indexing, 1, b"h1", &changes);
+		index(&indexing, 1, b"h1", &[(b"j", Some(b"v")), (b"l", Some(b"v"))]);
+		expected.sort();
+
+		let mut keys = Vec::new();
+		loop {
+			let page = storage
+				.indexed_keys(STORAGE_PREFIX, b"k", keys.last().map(|key: &Vec<u8>| &key[..]), 7)
+				.unwrap();
+			if page.is_empty() {
+				break
+			}
+			keys.extend(page);
+		}
+		assert_eq!(keys, expected);
+
+		// The start key doesn't need to be an indexed key.
+		assert_eq!(
+			storage.indexed_keys(STORAGE_PREFIX, b"k", Some(&[b'k', 51, 1]), 2).unwrap(),
+			vec![vec![b'k', 51, 255, 7], vec![b'k', 102]],
+		);
+		assert!(storage.indexed_keys(STORAGE_PREFIX, b"k", Some(b"l"), 2).unwrap().is_empty());
+		assert_eq!(
+			storage.indexed_keys(STORAGE_PREFIX, b"k", Some(b"a"), 1).unwrap(),
+			vec![vec![b'k', 0]],
+		);
+	}
+
+	#[test]
+	fn should_prune_data_indexed_by_non_canonical_blocks() {
+		let (storage, indexing) = new_indexing(OffchainIndexingPruning::KeepCanonical);
+		index(&indexing, 1, b"h1", &[(b"canonical", Some(b"v"))]);
+		index(&indexing, 1, b"f1", &[(b"fork", Some(b"v")), (b"shared", Some(b"fork"))]);
+		index(&indexing, 2, b"h2", &[(b"shared", Some(b"canonical"))]);
+		index(&indexing, 2, b"f2", &[(b"fork2", Some(b"v"))]);
+
+		prune(&indexing, 1, &[b"h1"]);
+		assert_eq!(storage.get(STORAGE_PREFIX, b"canonical"), Some(b"v".to_vec()));
+		assert_eq!(storage.get(STORAGE_PREFIX, b"fork"), None);
+		// Overwritten by a canonical block, so kept.
+		assert_eq!(storage.get(STORAGE_PREFIX, b"shared"), Some(b"canonical".to_vec()));
+		assert_eq!(storage.get(STORAGE_PREFIX, b"fork2"), Some(b"v".to_vec()));
+		assert_eq!(indexing.stats().keys, 3);
+
+		prune(&indexing, 2, &[b"h1", b"h2"]);
+		assert_eq!(storage.get(STORAGE_PREFIX, b"fork2"), None);
+		assert_eq!(indexing.stats(), OffchainIndexingInfo { keys: 2, bytes: 10 });
+		assert!(indexing.read_journal(1).is_empty());
+		assert!(indexing.read_journal(2).is_empty());
+	}
+
+	#[test]
+	fn should_restore_values_overwritten_by_pruned_blocks() {
+		let (storage, indexing) = new_indexing(OffchainIndexingPruning::KeepCanonical);
+		index(&indexing, 1, b"h1", &[(b"finalized", Some(b"h1")), (b"both", Some(b"h1"))]);
+		prune(&indexing, 1, &[b"h1"]);
+
+		index_with_finalized(&indexing, 2, b"h2", &[(b"both", Some(b"h2"))], 1);
+		index_with_finalized(
+			&indexing,
+			2,
+			b"f2",
+			&[(b"finalized", Some(b"fork")), (b"both", Some(b"fork")), (b"new", Some(b"fork"))],
+			1,
+		);
+		index_with_finalized(&indexing, 3, b"f3", &[(b"both", Some(b"fork3"))], 1);
+		assert_eq!(storage.get(STORAGE_PREFIX, b"both"), Some(b"fork3".to_vec()));
+
+		prune(&indexing, 2, &[b"h1", b"h2"]);
+		assert_eq!(storage.get(STORAGE_PREFIX, b"finalized"), Some(b"h1".to_vec()));
+		assert_eq!(storage.get(STORAGE_PREFIX, b"new"), None);
+		// Still written by `f3`, which is pruned once block 3 is finalized.
+		assert_eq!(storage.get(STORAGE_PREFIX, b"both"), Some(b"fork3".to_vec()));
+
+		prune(&indexing, 3, &[b"h1", b"h2", b"h3"]);
+		assert_eq!(storage.get(STORAGE_PREFIX, b"both"), Some(b"h2".to_vec()));
+		assert_eq!(indexing.stats(), OffchainIndexingInfo { keys: 2, bytes: 4 });
+	}
+
+	#[test]
+	fn should_only_keep_the_last_finalized_previous_value() {
+		let (_, indexing) = new_indexing(OffchainIndexingPruning::KeepCanonical);
+		for number in 1..=10u64 {
+			let hash = number.to_be_bytes();
+			index_with_finalized(&indexing, number, &hash, &[(b"key", Some(&hash))], number - 1);
+		}
+
+		let record_key = concatenate_prefix_and_key(
+			INDEXED_KEY_PREFIX,
+			&concatenate_prefix_and_key(STORAGE_PREFIX, b"key"),
+		);
+		let record = indexing.read_record(&record_key).unwrap();
+		assert_eq!(record.number, 10);
+		assert_eq!(record.previous.len(), 1);
+		assert_eq!(record.previous[0].number, 9);
+	}
+
+	#[test]
+	fn should_only_commit_pruned_data() {
+		let (_, indexing) = new_indexing(OffchainIndexingPruning::KeepCanonical);
+		index(&indexing, 1, b"h1", &[(b"key", Some(b"v"))]);
+
+		prune(&indexing, 1, &[b"h1"]);
+		assert_eq!(indexing.read_meta().unwrap().last_finalized, 1);
+
+		// Nothing was indexed by these blocks.
+		prune(&indexing, 5, &[b"h1", b"h2", b"h3", b"h4", b"h5"]);
+		assert_eq!(indexing.read_meta().unwrap().last_finalized, 1);
+		assert_eq!(*indexing.pruned.lock(), (5, 5));
+
+		let (_, indexing) = new_indexing(OffchainIndexingPruning::KeepAll);
+		index(&indexing, 1, b"h1", &[(b"key", Some(b"v"))]);
+		prune(&indexing, 1, &[b"h1"]);
+		assert_eq!(indexing.read_meta().unwrap().last_finalized, 0);
+	}
+
+	#[test]
+	fn should_prune_data_indexed_by_old_blocks() {
+		let (storage, indexing) = new_indexing(OffchainIndexingPruning::Some(2));
+		index(&indexing, 1, b"h1", &[(b"one", Some(b"v")), (b"both", Some(b"v"))]);
+		index(&indexing, 2, b"h2", &[(b"two", Some(b"v")), (b"both", Some(b"v"))]);
+		index(&indexing, 3, b"h3", &[(b"three", Some(b"v"))]);
+
+		prune(&indexing, 2, &[b"h1", b"h2"]);
+		assert_eq!(indexing.stats().keys, 4);
+
+		prune(&indexing, 4, &[b"h1", b"h2", b"h3", b"h4"]);
+		assert_eq!(storage.get(STORAGE_PREFIX, b"one"), None);
+		assert_eq!(storage.get(STORAGE_PREFIX, b"two"), None);
+		// Last written by block 2, which is pruned as well.
+		assert_eq!(storage.get(STORAGE_PREFIX, b"both"), None);
+		assert_eq!(storage.get(STORAGE_PREFIX, b"three"), Some(b"v".to_vec()));
+		assert_eq!(indexing.stats(), OffchainIndexingInfo { keys: 1, bytes: 1 });
+	}
+
+	#[test]
+	fn should_keep_all_indexed_data() {
+		let (storage, indexing) = new_indexing(OffchainIndexingPruning::KeepAll);
+		index(&indexing, 1, b"f1", &[(b"fork", Some(b"v"))]);
+
+		prune(&indexing, 1, &[b"h1"]);
+		assert_eq!(storage.get(STORAGE_PREFIX, b"fork"), Some(b"v".to_vec()));
+		assert_eq!(indexing.stats().keys, 1);
+	}
 
 	#[test]
 	fn should_compare_and_set_and_clear_the_locks_map() {
@@ -26,6 +26,11 @@ use std::io;
 /// Local keystore implementation
 mod local;
 pub use local::LocalKeystore;
+/// Remote keystore implementation
+#[cfg(unix)]
+pub mod remote;
+#[cfg(unix)]
+pub use remote::RemoteKeystore;
 pub use sp_keystore::Keystore;
 
 /// Keystore error.
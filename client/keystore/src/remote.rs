@@ -0,0 +1,915 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+//! Remote keystore implementation.
+//!
+//! [`RemoteKeystore`] forwards all keystore operations over a unix socket to an external signer
+//! process, so the private keys never have to be stored by the node itself. The signer side of
+//! the protocol is implemented by [`serve`], which answers the requests using any other
+//! [`Keystore`] implementation, usually a [`LocalKeystore`](crate::LocalKeystore).
+//!
+//! Every message is a SCALE encoded [`Request`] or [`Response`], prefixed by its length as
+//! little endian `u32`. A connection carries one request at a time, each followed by exactly one
+//! response.
+
+use codec::{Decode, Encode};
+use parking_lot::Mutex;
+#[cfg(feature = "bandersnatch-experimental")]
+use sp_core::bandersnatch;
+#[cfg(feature = "bls-experimental")]
+use sp_core::{bls377, bls381};
+use sp_core::{
+	crypto::{ByteArray, CryptoTypeId, KeyTypeId},
+	ecdsa, ed25519, sr25519,
+};
+use sp_keystore::{Error as TraitError, Keystore, KeystorePtr};
+use std::{
+	ffi::OsString,
+	fs,
+	io::{self, Read, Write},
+	os::unix::{
+		fs::{DirBuilderExt, PermissionsExt},
+		net::{UnixListener, UnixStream},
+	},
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+/// Log target of the remote keystore.
+const LOG_TARGET: &str = "keystore::remote";
+
+/// The maximum size of a single message.
+const MAX_MESSAGE_LEN: u32 = 16 * 1024 * 1024;
+
+/// How long we wait for the signer to answer a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A request sent by the [`RemoteKeystore`] to the signer.
+///
+/// VRF transcripts can not be encoded, so the VRF requests carry the data the sign data was built
+/// from instead and the signer builds it again. Thus only sign data built from its parts can be
+/// forwarded, VRF inputs and ring provers don't keep the data they are built from. The
+/// bandersnatch requests have fixed indices, so the encoding does not depend on the enabled
+/// features.
+#[derive(Clone, Encode, Decode)]
+pub enum Request {
+	/// Return the public keys of `crypto_id` for `key_type`.
+	PublicKeys {
+		/// The crypto scheme of the keys.
+		crypto_id: CryptoTypeId,
+		/// The key type of the keys.
+		key_type: KeyTypeId,
+	},
+	/// Generate a new key pair of `crypto_id` for `key_type`.
+	GenerateNew {
+		/// The crypto scheme of the key pair.
+		crypto_id: CryptoTypeId,
+		/// The key type of the key pair.
+		key_type: KeyTypeId,
+		/// Optional seed of the key pair.
+		seed: Option<String>,
+	},
+	/// Sign `msg` with the private key of `public`, see [`Keystore::sign_with`].
+	Sign {
+		/// The crypto scheme of the key.
+		crypto_id: CryptoTypeId,
+		/// The key type of the key.
+		key_type: KeyTypeId,
+		/// The raw public key.
+		public: Vec<u8>,
+		/// The message to sign.
+		msg: Vec<u8>,
+	},
+	/// Sign the pre-hashed `msg` with the ecdsa private key of `public`.
+	EcdsaSignPrehashed {
+		/// The key type of the key.
+		key_type: KeyTypeId,
+		/// The public key.
+		public: ecdsa::Public,
+		/// The pre-hashed message to sign.
+		msg: [u8; 32],
+	},
+	/// Insert a new key pair.
+	Insert {
+		/// The key type of the key pair.
+		key_type: KeyTypeId,
+		/// The secret URI of the key pair.
+		suri: String,
+		/// The raw public key.
+		public: Vec<u8>,
+	},
+	/// Return the raw public keys of `key_type`.
+	Keys {
+		/// The key type of the keys.
+		key_type: KeyTypeId,
+	},
+	/// Check if all of the given keys are known.
+	HasKeys {
+		/// The raw public keys and their key types.
+		public_keys: Vec<(Vec<u8>, KeyTypeId)>,
+	},
+	/// Produce a VRF signature of `data`, see [`Keystore::sr25519_vrf_sign`].
+	Sr25519VrfSign {
+		/// The key type of the key.
+		key_type: KeyTypeId,
+		/// The public key.
+		public: sr25519::Public,
+		/// The data to sign.
+		data: sr25519::vrf::VrfSignDataParts,
+	},
+	/// Produce a VRF signature of `data`, see [`Keystore::bandersnatch_vrf_sign`].
+	#[cfg(feature = "bandersnatch-experimental")]
+	#[codec(index = 9)]
+	BandersnatchVrfSign {
+		/// The key type of the key.
+		key_type: KeyTypeId,
+		/// The public key.
+		public: bandersnatch::Public,
+		/// The data to sign.
+		data: bandersnatch::vrf::VrfSignDataParts,
+	},
+}
+
+/// The answer of the signer to a [`Request`].
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum Response {
+	/// The raw public keys, answering [`Request::PublicKeys`] and [`Request::Keys`].
+	PublicKeys(Vec<Vec<u8>>),
+	/// The raw public key of a generated key pair.
+	Public(Vec<u8>),
+	/// The SCALE encoded signature or `None` if the key is unknown.
+	Signature(Option<Vec<u8>>),
+	/// Whether the key pair was inserted.
+	Inserted(bool),
+	/// Whether all keys are known.
+	HasKeys(bool),
+	/// The request failed.
+	Error(RemoteError),
+}
+
+/// An error that occurred in the signer.
+///
+/// Mirrors [`sp_keystore::Error`].
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum RemoteError {
+	/// See [`sp_keystore::Error::KeyNotSupported`].
+	KeyNotSupported(KeyTypeId),
+	/// See [`sp_keystore::Error::ValidationError`].
+	ValidationError(String),
+	/// See [`sp_keystore::Error::Unavailable`].
+	Unavailable,
+	/// See [`sp_keystore::Error::Other`].
+	Other(String),
+}
+
+impl From<TraitError> for RemoteError {
+	fn from(error: TraitError) -> Self {
+		match error {
+			TraitError::KeyNotSupported(id) => Self::KeyNotSupported(id),
+			TraitError::ValidationError(e) => Self::ValidationError(e),
+			TraitError::Unavailable => Self::Unavailable,
+			TraitError::Other(e) => Self::Other(e),
+		}
+	}
+}
+
+impl From<RemoteError> for TraitError {
+	fn from(error: RemoteError) -> Self {
+		match error {
+			RemoteError::KeyNotSupported(id) => Self::KeyNotSupported(id),
+			RemoteError::ValidationError(e) => Self::ValidationError(e),
+			RemoteError::Unavailable => Self::Unavailable,
+			RemoteError::Other(e) => Self::Other(e),
+		}
+	}
+}
+
+/// Write `message` as a length prefixed frame.
+fn write_message(stream: &mut impl Write, message: &impl Encode) -> io::Result<()> {
+	let message = message.encode();
+	let len = u32::try_from(message.len())
+		.ok()
+		.filter(|len| *len <= MAX_MESSAGE_LEN)
+		.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Message is too large"))?;
+
+	stream.write_all(&len.to_le_bytes())?;
+	stream.write_all(&message)?;
+	stream.flush()
+}
+
+/// Read a length prefixed frame and decode it as `T`.
+fn read_message<T: Decode>(stream: &mut impl Read) -> io::Result<T> {
+	let mut len = [0u8; 4];
+	stream.read_exact(&mut len)?;
+	let len = u32::from_le_bytes(len);
+	if len > MAX_MESSAGE_LEN {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, "Message is too large"))
+	}
+
+	let mut message = vec![0u8; len as usize];
+	stream.read_exact(&mut message)?;
+	T::decode(&mut &message[..]).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Decode the SCALE encoded value of a [`Response`].
+fn decode_response<T: Decode>(encoded: Option<Vec<u8>>) -> Result<Option<T>, TraitError> {
+	encoded
+		.map(|encoded| {
+			T::decode(&mut &encoded[..])
+				.map_err(|_| TraitError::Other("Invalid response format".into()))
+		})
+		.transpose()
+}
+
+/// A keystore that forwards all operations to an external signer process.
+///
+/// If the connection to the signer fails, it is re-established with the next request. While the
+/// signer is not reachable, all operations fail with [`TraitError::Unavailable`].
+pub struct RemoteKeystore {
+	path: PathBuf,
+	connection: Mutex<Option<UnixStream>>,
+}
+
+impl RemoteKeystore {
+	/// Create a keystore that connects to the signer listening on the unix socket at `path`.
+	///
+	/// Fails if the signer is not reachable.
+	pub fn open<T: Into<PathBuf>>(path: T) -> io::Result<Self> {
+		let path = path.into();
+		let connection = Self::connect(&path)?;
+
+		Ok(Self { path, connection: Mutex::new(Some(connection)) })
+	}
+
+	/// Returns the path of the socket of the signer.
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	fn connect(path: &Path) -> io::Result<UnixStream> {
+		let stream = UnixStream::connect(path)?;
+		stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+		stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+		Ok(stream)
+	}
+
+	/// Send `request` over `connection`, connecting first if required.
+	fn exchange(
+		&self,
+		connection: &mut Option<UnixStream>,
+		request: &Request,
+	) -> io::Result<Response> {
+		if connection.is_none() {
+			*connection = Some(Self::connect(&self.path)?);
+		}
+
+		let stream = connection.as_mut().expect("Connection was established above; qed");
+		write_message(stream, request)?;
+		read_message(stream)
+	}
+
+	/// Send `request` to the signer and wait for the response.
+	fn request(&self, request: Request) -> Result<Response, TraitError> {
+		let mut connection = self.connection.lock();
+
+		match self.exchange(&mut connection, &request) {
+			Ok(Response::Error(error)) => Err(error.into()),
+			Ok(response) => Ok(response),
+			Err(e) => {
+				log::warn!(
+					target: LOG_TARGET,
+					"Request to the remote signer at {} failed: {}",
+					self.path.display(),
+					e,
+				);
+				// Start with a fresh connection on the next request.
+				*connection = None;
+				Err(TraitError::Unavailable)
+			},
+		}
+	}
+
+	fn public_keys<T: ByteArray>(&self, crypto_id: CryptoTypeId, key_type: KeyTypeId) -> Vec<T> {
+		match self.request(Request::PublicKeys { crypto_id, key_type }) {
+			Ok(Response::PublicKeys(keys)) =>
+				keys.iter().filter_map(|key| T::from_slice(key).ok()).collect(),
+			Ok(response) => {
+				log::warn!(target: LOG_TARGET, "Unexpected response: {:?}", response);
+				Vec::new()
+			},
+			Err(e) => {
+				log::debug!(target: LOG_TARGET, "Failed to fetch public keys: {}", e);
+				Vec::new()
+			},
+		}
+	}
+
+	fn generate_new<T: ByteArray>(
+		&self,
+		crypto_id: CryptoTypeId,
+		key_type: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<T, TraitError> {
+		let seed = seed.map(Into::into);
+		match self.request(Request::GenerateNew { crypto_id, key_type, seed })? {
+			Response::Public(public) => T::from_slice(&public)
+				.map_err(|_| TraitError::ValidationError("Invalid public key format".into())),
+			response => Err(unexpected_response(response)),
+		}
+	}
+
+	fn sign<S: Decode>(
+		&self,
+		crypto_id: CryptoTypeId,
+		key_type: KeyTypeId,
+		public: &[u8],
+		msg: &[u8],
+	) -> Result<Option<S>, TraitError> {
+		let request =
+			Request::Sign { crypto_id, key_type, public: public.to_vec(), msg: msg.to_vec() };
+		self.signature(request)
+	}
+
+	/// Send a request that is answered with [`Response::Signature`].
+	fn signature<S: Decode>(&self, request: Request) -> Result<Option<S>, TraitError> {
+		match self.request(request)? {
+			Response::Signature(signature) => decode_response(signature),
+			response => Err(unexpected_response(response)),
+		}
+	}
+}
+
+fn unexpected_response(response: Response) -> TraitError {
+	TraitError::Other(format!("Unexpected response from the remote signer: {:?}", response))
+}
+
+fn not_forwardable() -> TraitError {
+	TraitError::ValidationError(
+		"VRF data which isn't built from its parts can't be forwarded to the remote signer".into(),
+	)
+}
+
+impl Keystore for RemoteKeystore {
+	fn sr25519_public_keys(&self, key_type: KeyTypeId) -> Vec<sr25519::Public> {
+		self.public_keys(sr25519::CRYPTO_ID, key_type)
+	}
+
+	fn sr25519_generate_new(
+		&self,
+		key_type: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, TraitError> {
+		self.generate_new(sr25519::CRYPTO_ID, key_type, seed)
+	}
+
+	fn sr25519_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &sr25519::Public,
+		msg: &[u8],
+	) -> Result<Option<sr25519::Signature>, TraitError> {
+		self.sign(sr25519::CRYPTO_ID, key_type, public.as_slice(), msg)
+	}
+
+	fn sr25519_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &sr25519::Public,
+		data: &sr25519::vrf::VrfSignData,
+	) -> Result<Option<sr25519::vrf::VrfSignature>, TraitError> {
+		let data = data.parts().ok_or_else(not_forwardable)?.clone();
+		self.signature(Request::Sr25519VrfSign { key_type, public: *public, data })
+	}
+
+	fn sr25519_vrf_output(
+		&self,
+		_: KeyTypeId,
+		_: &sr25519::Public,
+		_: &sr25519::vrf::VrfInput,
+	) -> Result<Option<sr25519::vrf::VrfOutput>, TraitError> {
+		Err(not_forwardable())
+	}
+
+	fn ed25519_public_keys(&self, key_type: KeyTypeId) -> Vec<ed25519::Public> {
+		self.public_keys(ed25519::CRYPTO_ID, key_type)
+	}
+
+	fn ed25519_generate_new(
+		&self,
+		key_type: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, TraitError> {
+		self.generate_new(ed25519::CRYPTO_ID, key_type, seed)
+	}
+
+	fn ed25519_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &ed25519::Public,
+		msg: &[u8],
+	) -> Result<Option<ed25519::Signature>, TraitError> {
+		self.sign(ed25519::CRYPTO_ID, key_type, public.as_slice(), msg)
+	}
+
+	fn ecdsa_public_keys(&self, key_type: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.public_keys(ecdsa::CRYPTO_ID, key_type)
+	}
+
+	fn ecdsa_generate_new(
+		&self,
+		key_type: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, TraitError> {
+		self.generate_new(ecdsa::CRYPTO_ID, key_type, seed)
+	}
+
+	fn ecdsa_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8],
+	) -> Result<Option<ecdsa::Signature>, TraitError> {
+		self.sign(ecdsa::CRYPTO_ID, key_type, public.as_slice(), msg)
+	}
+
+	fn ecdsa_sign_prehashed(
+		&self,
+		key_type: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, TraitError> {
+		self.signature(Request::EcdsaSignPrehashed { key_type, public: *public, msg: *msg })
+	}
+
+	#[cfg(feature = "bandersnatch-experimental")]
+	fn bandersnatch_public_keys(&self, key_type: KeyTypeId) -> Vec<bandersnatch::Public> {
+		self.public_keys(bandersnatch::CRYPTO_ID, key_type)
+	}
+
+	#[cfg(feature = "bandersnatch-experimental")]
+	fn bandersnatch_generate_new(
+		&self,
+		key_type: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<bandersnatch::Public, TraitError> {
+		self.generate_new(bandersnatch::CRYPTO_ID, key_type, seed)
+	}
+
+	#[cfg(feature = "bandersnatch-experimental")]
+	fn bandersnatch_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &bandersnatch::Public,
+		msg: &[u8],
+	) -> Result<Option<bandersnatch::Signature>, TraitError> {
+		self.sign(bandersnatch::CRYPTO_ID, key_type, public.as_slice(), msg)
+	}
+
+	#[cfg(feature = "bandersnatch-experimental")]
+	fn bandersnatch_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &bandersnatch::Public,
+		data: &bandersnatch::vrf::VrfSignData,
+	) -> Result<Option<bandersnatch::vrf::VrfSignature>, TraitError> {
+		let data = data.parts().ok_or_else(not_forwardable)?.clone();
+		self.signature(Request::BandersnatchVrfSign { key_type, public: *public, data })
+	}
+
+	#[cfg(feature = "bandersnatch-experimental")]
+	fn bandersnatch_vrf_output(
+		&self,
+		_: KeyTypeId,
+		_: &bandersnatch::Public,
+		_: &bandersnatch::vrf::VrfInput,
+	) -> Result<Option<bandersnatch::vrf::VrfOutput>, TraitError> {
+		Err(not_forwardable())
+	}
+
+	#[cfg(feature = "bandersnatch-experimental")]
+	fn bandersnatch_ring_vrf_sign(
+		&self,
+		_: KeyTypeId,
+		_: &bandersnatch::Public,
+		_: &bandersnatch::vrf::VrfSignData,
+		_: &bandersnatch::ring_vrf::RingProver,
+	) -> Result<Option<bandersnatch::ring_vrf::RingVrfSignature>, TraitError> {
+		Err(not_forwardable())
+	}
+
+	#[cfg(feature = "bls-experimental")]
+	fn bls381_public_keys(&self, key_type: KeyTypeId) -> Vec<bls381::Public> {
+		self.public_keys(bls381::CRYPTO_ID, key_type)
+	}
+
+	#[cfg(feature = "bls-experimental")]
+	fn bls377_public_keys(&self, key_type: KeyTypeId) -> Vec<bls377::Public> {
+		self.public_keys(bls377::CRYPTO_ID, key_type)
+	}
+
+	#[cfg(feature = "bls-experimental")]
+	fn bls381_generate_new(
+		&self,
+		key_type: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<bls381::Public, TraitError> {
+		self.generate_new(bls381::CRYPTO_ID, key_type, seed)
+	}
+
+	#[cfg(feature = "bls-experimental")]
+	fn bls377_generate_new(
+		&self,
+		key_type: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<bls377::Public, TraitError> {
+		self.generate_new(bls377::CRYPTO_ID, key_type, seed)
+	}
+
+	#[cfg(feature = "bls-experimental")]
+	fn bls381_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &bls381::Public,
+		msg: &[u8],
+	) -> Result<Option<bls381::Signature>, TraitError> {
+		self.sign(bls381::CRYPTO_ID, key_type, public.as_slice(), msg)
+	}
+
+	#[cfg(feature = "bls-experimental")]
+	fn bls377_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &bls377::Public,
+		msg: &[u8],
+	) -> Result<Option<bls377::Signature>, TraitError> {
+		self.sign(bls377::CRYPTO_ID, key_type, public.as_slice(), msg)
+	}
+
+	fn insert(&self, key_type: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		let request = Request::Insert { key_type, suri: suri.into(), public: public.to_vec() };
+		match self.request(request) {
+			Ok(Response::Inserted(true)) => Ok(()),
+			_ => Err(()),
+		}
+	}
+
+	fn keys(&self, key_type: KeyTypeId) -> Result<Vec<Vec<u8>>, TraitError> {
+		match self.request(Request::Keys { key_type })? {
+			Response::PublicKeys(keys) => Ok(keys),
+			response => Err(unexpected_response(response)),
+		}
+	}
+
+	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		let request = Request::HasKeys { public_keys: public_keys.to_vec() };
+		matches!(self.request(request), Ok(Response::HasKeys(true)))
+	}
+}
+
+/// Answer `request` using `keystore`.
+pub fn handle_request(keystore: &dyn Keystore, request: Request) -> Response {
+	let result = match request {
+		Request::PublicKeys { crypto_id, key_type } =>
+			public_keys(keystore, crypto_id, key_type).map(Response::PublicKeys),
+		Request::GenerateNew { crypto_id, key_type, seed } =>
+			generate_new(keystore, crypto_id, key_type, seed.as_deref()).map(Response::Public),
+		Request::Sign { crypto_id, key_type, public, msg } =>
+			keystore.sign_with(key_type, crypto_id, &public, &msg).map(Response::Signature),
+		Request::EcdsaSignPrehashed { key_type, public, msg } => keystore
+			.ecdsa_sign_prehashed(key_type, &public, &msg)
+			.map(|signature| Response::Signature(signature.map(|s| s.encode()))),
+		Request::Insert { key_type, suri, public } =>
+			Ok(Response::Inserted(keystore.insert(key_type, &suri, &public).is_ok())),
+		Request::Keys { key_type } => keystore.keys(key_type).map(Response::PublicKeys),
+		Request::HasKeys { public_keys } => Ok(Response::HasKeys(keystore.has_keys(&public_keys))),
+		Request::Sr25519VrfSign { key_type, public, data } => keystore
+			.sr25519_vrf_sign(key_type, &public, &data.to_sign_data())
+			.map(|signature| Response::Signature(signature.map(|s| s.encode()))),
+		#[cfg(feature = "bandersnatch-experimental")]
+		Request::BandersnatchVrfSign { key_type, public, data } => data
+			.to_sign_data()
+			.map_err(|_| invalid_vrf_data())
+			.and_then(|data| keystore.bandersnatch_vrf_sign(key_type, &public, &data))
+			.map(|signature| Response::Signature(signature.map(|s| s.encode()))),
+	};
+
+	result.unwrap_or_else(|e| Response::Error(e.into()))
+}
+
+#[cfg(feature = "bandersnatch-experimental")]
+fn invalid_vrf_data() -> TraitError {
+	TraitError::ValidationError("Invalid VRF sign data".into())
+}
+
+fn to_raw<T: ByteArray>(keys: Vec<T>) -> Vec<Vec<u8>> {
+	keys.iter().map(|key| key.to_raw_vec()).collect()
+}
+
+fn public_keys(
+	keystore: &dyn Keystore,
+	crypto_id: CryptoTypeId,
+	key_type: KeyTypeId,
+) -> Result<Vec<Vec<u8>>, TraitError> {
+	let keys = match crypto_id {
+		sr25519::CRYPTO_ID => to_raw(keystore.sr25519_public_keys(key_type)),
+		ed25519::CRYPTO_ID => to_raw(keystore.ed25519_public_keys(key_type)),
+		ecdsa::CRYPTO_ID => to_raw(keystore.ecdsa_public_keys(key_type)),
+		#[cfg(feature = "bandersnatch-experimental")]
+		bandersnatch::CRYPTO_ID => to_raw(keystore.bandersnatch_public_keys(key_type)),
+		#[cfg(feature = "bls-experimental")]
+		bls381::CRYPTO_ID => to_raw(keystore.bls381_public_keys(key_type)),
+		#[cfg(feature = "bls-experimental")]
+		bls377::CRYPTO_ID => to_raw(keystore.bls377_public_keys(key_type)),
+		_ => return Err(TraitError::KeyNotSupported(key_type)),
+	};
+	Ok(keys)
+}
+
+fn generate_new(
+	keystore: &dyn Keystore,
+	crypto_id: CryptoTypeId,
+	key_type: KeyTypeId,
+	seed: Option<&str>,
+) -> Result<Vec<u8>, TraitError> {
+	let public = match crypto_id {
+		sr25519::CRYPTO_ID => keystore.sr25519_generate_new(key_type, seed)?.to_raw_vec(),
+		ed25519::CRYPTO_ID => keystore.ed25519_generate_new(key_type, seed)?.to_raw_vec(),
+		ecdsa::CRYPTO_ID => keystore.ecdsa_generate_new(key_type, seed)?.to_raw_vec(),
+		#[cfg(feature = "bandersnatch-experimental")]
+		bandersnatch::CRYPTO_ID => keystore.bandersnatch_generate_new(key_type, seed)?.to_raw_vec(),
+		#[cfg(feature = "bls-experimental")]
+		bls381::CRYPTO_ID => keystore.bls381_generate_new(key_type, seed)?.to_raw_vec(),
+		#[cfg(feature = "bls-experimental")]
+		bls377::CRYPTO_ID => keystore.bls377_generate_new(key_type, seed)?.to_raw_vec(),
+		_ => return Err(TraitError::KeyNotSupported(key_type)),
+	};
+	Ok(public)
+}
+
+/// Answer the requests of a single connection until it is closed.
+fn serve_connection(mut stream: UnixStream, keystore: &dyn Keystore) -> io::Result<()> {
+	loop {
+		let request = match read_message::<Request>(&mut stream) {
+			Ok(request) => request,
+			Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+			Err(e) => return Err(e),
+		};
+
+		write_message(&mut stream, &handle_request(keystore, request))?;
+	}
+}
+
+/// Create the unix socket at `path` for [`serve`], replacing any file left there.
+///
+/// Anyone who can connect to the socket can use the keys, so only the owner of the process can
+/// access it. The socket is created in a private directory and only moved to `path` once its
+/// permissions are restricted, so it is never accessible by anyone else.
+pub fn bind(path: impl AsRef<Path>) -> io::Result<UnixListener> {
+	let path = path.as_ref();
+	let file_name = path.file_name().ok_or_else(|| {
+		io::Error::new(io::ErrorKind::InvalidInput, "Socket path has no file name")
+	})?;
+	let parent = path.parent().filter(|parent| !parent.as_os_str().is_empty());
+	let mut private_dir = OsString::from(".");
+	private_dir.push(file_name);
+	private_dir.push(format!(".{}", std::process::id()));
+	let private_dir = parent.unwrap_or_else(|| Path::new(".")).join(private_dir);
+	let private_path = private_dir.join("socket");
+
+	fs::DirBuilder::new().mode(0o700).create(&private_dir)?;
+	let listener = UnixListener::bind(&private_path).and_then(|listener| {
+		fs::set_permissions(&private_path, fs::Permissions::from_mode(0o600))?;
+		fs::rename(&private_path, path)?;
+		Ok(listener)
+	});
+	// Only left over if moving the socket failed.
+	let _ = fs::remove_file(&private_path);
+	fs::remove_dir(&private_dir)?;
+	listener
+}
+
+/// Serve the requests of [`RemoteKeystore`]s connecting to `listener` using `keystore`.
+///
+/// The `listener` should be created with [`bind`], which makes sure only the owner of the process
+/// can use the keys. Every connection is handled on its own thread. This function only returns if
+/// accepting a new connection fails.
+pub fn serve(listener: UnixListener, keystore: KeystorePtr) -> io::Result<()> {
+	loop {
+		let (stream, _) = listener.accept()?;
+		let keystore = keystore.clone();
+
+		std::thread::spawn(move || {
+			if let Err(e) = serve_connection(stream, &*keystore) {
+				log::debug!(target: LOG_TARGET, "Connection to the keystore failed: {}", e);
+			}
+		});
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::LocalKeystore;
+	use sp_core::{testing::SR25519, Pair};
+	use std::sync::Arc;
+	use tempfile::TempDir;
+
+	const TEST_KEY_TYPE: KeyTypeId = KeyTypeId(*b"test");
+
+	/// Start a signer backed by a keystore in a temporary directory.
+	fn start_signer() -> (TempDir, PathBuf, Arc<LocalKeystore>) {
+		let temp_dir = TempDir::new().unwrap();
+		let path = temp_dir.path().join("signer.sock");
+		let listener = bind(&path).unwrap();
+		let keystore = Arc::new(LocalKeystore::open(temp_dir.path().join("keys"), None).unwrap());
+
+		let signer = keystore.clone();
+		std::thread::spawn(move || serve(listener, signer));
+
+		(temp_dir, path, keystore)
+	}
+
+	#[test]
+	fn keys_are_generated_by_the_signer() {
+		let (_dir, path, local) = start_signer();
+		let remote = RemoteKeystore::open(&path).unwrap();
+
+		let sr25519 = remote.sr25519_generate_new(SR25519, None).unwrap();
+		let ed25519 = remote.ed25519_generate_new(TEST_KEY_TYPE, Some("//Alice")).unwrap();
+		let ecdsa = remote.ecdsa_generate_new(TEST_KEY_TYPE, None).unwrap();
+
+		assert_eq!(remote.sr25519_public_keys(SR25519), vec![sr25519]);
+		assert_eq!(local.sr25519_public_keys(SR25519), vec![sr25519]);
+		assert_eq!(remote.ed25519_public_keys(TEST_KEY_TYPE), vec![ed25519]);
+		assert_eq!(remote.ecdsa_public_keys(TEST_KEY_TYPE), vec![ecdsa]);
+		assert!(remote.sr25519_public_keys(KeyTypeId(*b"none")).is_empty());
+	}
+
+	#[test]
+	fn signing_is_forwarded() {
+		let (_dir, path, _) = start_signer();
+		let remote = RemoteKeystore::open(&path).unwrap();
+		let msg = b"Hello remote signer";
+
+		let public = remote.sr25519_generate_new(TEST_KEY_TYPE, None).unwrap();
+		let signature = remote.sr25519_sign(TEST_KEY_TYPE, &public, msg).unwrap().unwrap();
+		assert!(sr25519::Pair::verify(&signature, msg, &public));
+
+		let public = remote.ed25519_generate_new(TEST_KEY_TYPE, None).unwrap();
+		let signature = remote.ed25519_sign(TEST_KEY_TYPE, &public, msg).unwrap().unwrap();
+		assert!(ed25519::Pair::verify(&signature, msg, &public));
+
+		let public = remote.ecdsa_generate_new(TEST_KEY_TYPE, None).unwrap();
+		let signature = remote.ecdsa_sign(TEST_KEY_TYPE, &public, msg).unwrap().unwrap();
+		assert!(ecdsa::Pair::verify(&signature, msg, &public));
+
+		let prehashed = sp_core::hashing::blake2_256(msg);
+		let signature = remote
+			.ecdsa_sign_prehashed(TEST_KEY_TYPE, &public, &prehashed)
+			.unwrap()
+			.unwrap();
+		assert!(ecdsa::Pair::verify_prehashed(&signature, &prehashed, &public));
+
+		let unknown = sr25519::Pair::generate().0.public();
+		assert_eq!(remote.sr25519_sign(TEST_KEY_TYPE, &unknown, msg).unwrap(), None);
+	}
+
+	#[test]
+	fn insert_and_has_keys_are_forwarded() {
+		let (_dir, path, _) = start_signer();
+		let remote = RemoteKeystore::open(&path).unwrap();
+
+		let public = sr25519::Pair::from_string("//Bob", None).unwrap().public();
+		assert!(!remote.has_keys(&[(public.to_raw_vec(), TEST_KEY_TYPE)]));
+
+		remote.insert(TEST_KEY_TYPE, "//Bob", public.as_slice()).unwrap();
+
+		assert!(remote.has_keys(&[(public.to_raw_vec(), TEST_KEY_TYPE)]));
+		assert_eq!(remote.keys(TEST_KEY_TYPE).unwrap(), vec![public.to_raw_vec()]);
+	}
+
+	#[test]
+	fn sr25519_vrf_is_forwarded() {
+		use sp_core::crypto::VrfPublic;
+		use sr25519::vrf::{VrfSignDataParts, VrfTranscript, VrfTranscriptParts};
+
+		let (_dir, path, local) = start_signer();
+		let remote = RemoteKeystore::open(&path).unwrap();
+
+		let public = remote.sr25519_generate_new(TEST_KEY_TYPE, None).unwrap();
+		let transcript_parts = |label: &[u8], data: &[u8]| VrfTranscriptParts {
+			label: label.to_vec(),
+			data: vec![(b"domain".to_vec(), data.to_vec())],
+		};
+		let data = VrfSignDataParts {
+			transcript: transcript_parts(b"label", b"data"),
+			extra: Some(transcript_parts(b"extra", b"more data")),
+		}
+		.to_sign_data();
+
+		let signature = remote.sr25519_vrf_sign(TEST_KEY_TYPE, &public, &data).unwrap().unwrap();
+		assert!(public.vrf_verify(&data, &signature));
+
+		let input = VrfTranscript::new(b"label", &[(b"domain", b"data")]);
+		let output = local.sr25519_vrf_output(TEST_KEY_TYPE, &public, &input).unwrap();
+		assert_eq!(output, Some(signature.output));
+
+		let unknown = sr25519::Pair::generate().0.public();
+		assert_eq!(remote.sr25519_vrf_sign(TEST_KEY_TYPE, &unknown, &data).unwrap(), None);
+
+		// Transcripts don't keep the data they were built from.
+		assert!(remote.sr25519_vrf_output(TEST_KEY_TYPE, &public, &input).is_err());
+		let data = input.into_sign_data();
+		assert!(remote.sr25519_vrf_sign(TEST_KEY_TYPE, &public, &data).is_err());
+	}
+
+	#[cfg(feature = "bandersnatch-experimental")]
+	#[test]
+	fn bandersnatch_vrf_is_forwarded() {
+		use bandersnatch::{
+			ring_vrf::RingContext,
+			vrf::{VrfInput, VrfSignData, VrfSignDataParts},
+		};
+		use sp_core::crypto::VrfPublic;
+
+		let (_dir, path, local) = start_signer();
+		let remote = RemoteKeystore::open(&path).unwrap();
+
+		let public = remote.bandersnatch_generate_new(TEST_KEY_TYPE, None).unwrap();
+		let data = VrfSignDataParts {
+			transcript_label: b"label".to_vec(),
+			transcript_data: vec![b"data".to_vec()],
+			vrf_inputs: vec![(b"domain".to_vec(), b"data".to_vec())],
+		}
+		.to_sign_data()
+		.unwrap();
+
+		let signature =
+			remote.bandersnatch_vrf_sign(TEST_KEY_TYPE, &public, &data).unwrap().unwrap();
+		assert!(public.vrf_verify(&data, &signature));
+
+		let input = VrfInput::new(b"domain", b"data");
+		let output = local.bandersnatch_vrf_output(TEST_KEY_TYPE, &public, &input).unwrap();
+		assert_eq!(output.as_ref(), signature.vrf_outputs.first());
+
+		// Neither VRF inputs nor ring provers keep the data they were built from.
+		assert!(remote.bandersnatch_vrf_output(TEST_KEY_TYPE, &public, &input).is_err());
+		let other = VrfSignData::new_unchecked(b"label", &[b"data"], [input]);
+		assert!(remote.bandersnatch_vrf_sign(TEST_KEY_TYPE, &public, &other).is_err());
+
+		let ring_ctx = RingContext::new_testing();
+		let mut ring: Vec<_> =
+			(0..8u8).map(|i| bandersnatch::Pair::from_seed(&[i; 32]).public()).collect();
+		ring[3] = public;
+		let prover = ring_ctx.prover(&ring, 3).unwrap();
+		assert!(remote
+			.bandersnatch_ring_vrf_sign(TEST_KEY_TYPE, &public, &data, &prover)
+			.is_err());
+	}
+
+	#[test]
+	fn socket_is_only_accessible_by_the_owner() {
+		let temp_dir = TempDir::new().unwrap();
+		let path = temp_dir.path().join("signer.sock");
+		// A socket left behind by a previous run is replaced.
+		fs::write(&path, b"").unwrap();
+
+		let _listener = bind(&path).unwrap();
+		let mode = fs::metadata(&path).unwrap().permissions().mode();
+		assert_eq!(mode & 0o777, 0o600);
+		// Nothing is left behind next to the socket.
+		assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+		assert!(RemoteKeystore::open(&path).is_ok());
+	}
+
+	#[test]
+	fn unavailable_signer_is_reported() {
+		let (dir, path, _) = start_signer();
+		assert!(RemoteKeystore::open(dir.path().join("missing.sock")).is_err());
+
+		let remote = RemoteKeystore::open(&path).unwrap();
+		// Pretend the connection was lost and the signer is gone.
+		*remote.connection.lock() = None;
+		std::fs::remove_file(&path).unwrap();
+
+		assert!(matches!(
+			remote.sr25519_generate_new(TEST_KEY_TYPE, None),
+			Err(TraitError::Unavailable),
+		));
+		assert!(remote.sr25519_public_keys(TEST_KEY_TYPE).is_empty());
+	}
+}
@@ -0,0 +1,592 @@
+// Copyright Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bitswap client.
+//!
+//! Fetches content from the connected peers first and falls back to the providers of the content
+//! announced in the DHT. Content retained by the local node is served by the
+//! [`BitswapRequestHandler`](crate::BitswapRequestHandler) and announced in the DHT.
+
+use crate::{
+	schema::bitswap::{
+		message::{
+			wantlist::{Entry, WantType},
+			Wantlist,
+		},
+		Message as BitswapMessage,
+	},
+	store, BitswapBackend, BitswapError, LOG_TARGET, PROTOCOL_NAME,
+};
+use cid::{
+	multihash::{Code, MultihashDigest},
+	Cid,
+};
+use futures::{channel::oneshot, stream, Stream, StreamExt};
+use libp2p_identity::PeerId;
+use log::{debug, trace, warn};
+use parking_lot::Mutex;
+use prost::Message;
+use sc_network::{
+	types::ProtocolName, DhtEvent, Event, IfDisconnected, KademliaKey, NetworkDHTProvider,
+	NetworkEventStream, NetworkRequest,
+};
+use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedSender};
+use sp_runtime::traits::Block as BlockT;
+use std::{
+	collections::{hash_map::Entry as MapEntry, HashMap},
+	pin::Pin,
+	sync::Arc,
+};
+
+/// Maximum number of peers the content is requested from at the same time.
+const MAX_CONCURRENT_REQUESTS: usize = 8;
+
+/// Network services used by the bitswap client.
+pub trait Network: NetworkRequest + NetworkDHTProvider + NetworkEventStream + Send + Sync {}
+
+impl<T> Network for T where T: NetworkRequest + NetworkDHTProvider + NetworkEventStream + Send + Sync
+{}
+
+/// Returns the DHT key under which the providers of `cid` are announced.
+///
+/// Like in IPFS, this is the multihash of the content, so that the same content is found
+/// regardless of the CID version and codec.
+pub fn provider_key(cid: &Cid) -> KademliaKey {
+	KademliaKey::new(&cid.hash().to_bytes())
+}
+
+/// Check that `content` is referenced by `cid`.
+fn verify(cid: &Cid, content: &[u8]) -> Result<(), BitswapError> {
+	let code = Code::try_from(cid.hash().code())
+		.map_err(|_| BitswapError::UnsupportedHash(cid.hash().code()))?;
+	if code.digest(content) != *cid.hash() {
+		return Err(BitswapError::InvalidContent)
+	}
+	Ok(())
+}
+
+/// Messages from the [`BitswapClient`] to the [`BitswapClientWorker`].
+enum ToWorker {
+	/// Look up the providers of the content with the given key.
+	FindProviders(KademliaKey, oneshot::Sender<Vec<PeerId>>),
+}
+
+/// Handle to fetch content over bitswap and to manage the content retained by the local node.
+pub struct BitswapClient<B: BlockT> {
+	network: Arc<dyn Network>,
+	backend: Arc<dyn BitswapBackend<B> + Send + Sync>,
+	connected_peers: Arc<Mutex<HashMap<PeerId, usize>>>,
+	to_worker: TracingUnboundedSender<ToWorker>,
+	/// Serializes the updates of the index of the retained content.
+	retain_lock: Arc<Mutex<()>>,
+}
+
+impl<B: BlockT> Clone for BitswapClient<B> {
+	fn clone(&self) -> Self {
+		BitswapClient {
+			network: self.network.clone(),
+			backend: self.backend.clone(),
+			connected_peers: self.connected_peers.clone(),
+			to_worker: self.to_worker.clone(),
+			retain_lock: self.retain_lock.clone(),
+		}
+	}
+}
+
+impl<B: BlockT> BitswapClient<B> {
+	/// Create a new [`BitswapClient`].
+	///
+	/// The returned [`BitswapClientWorker`] must be spawned for the client to find the providers
+	/// of content and to keep track of the connected peers. The bitswap protocol must be
+	/// registered with the network, see [`BitswapRequestHandler`](crate::BitswapRequestHandler).
+	pub fn new(
+		network: Arc<dyn Network>,
+		backend: Arc<dyn BitswapBackend<B> + Send + Sync>,
+	) -> (Self, BitswapClientWorker) {
+		let (to_worker, from_client) = tracing_unbounded("mpsc_bitswap_client", 100_000);
+		let connected_peers = Arc::new(Mutex::new(HashMap::new()));
+
+		let retained = store::retained(&*backend).unwrap_or_else(|e| {
+			warn!(target: LOG_TARGET, "Failed to read the retained content: {e}");
+			Vec::new()
+		});
+
+		let worker = BitswapClientWorker {
+			network: network.clone(),
+			events: Box::pin(stream::select(
+				network.event_stream("bitswap-client").map(WorkerEvent::Network),
+				from_client.map(WorkerEvent::Client),
+			)),
+			connected_peers: connected_peers.clone(),
+			provider_queries: HashMap::new(),
+			retained,
+		};
+		let client = BitswapClient {
+			network,
+			backend,
+			connected_peers,
+			to_worker,
+			retain_lock: Default::default(),
+		};
+
+		(client, worker)
+	}
+
+	/// Fetch the content referenced by `cid`.
+	///
+	/// Content available locally is returned right away. Otherwise the content is requested from
+	/// the connected peers and then from the providers found in the DHT.
+	pub async fn fetch(&self, cid: &Cid) -> Result<Vec<u8>, BitswapError> {
+		if let Some(content) = store::load(&*self.backend, cid)? {
+			return Ok(content)
+		}
+
+		let request = BitswapMessage {
+			wantlist: Some(Wantlist {
+				entries: vec![Entry {
+					block: cid.to_bytes(),
+					priority: 1,
+					cancel: false,
+					want_type: WantType::Block as i32,
+					send_dont_have: true,
+				}],
+				full: false,
+			}),
+			..Default::default()
+		}
+		.encode_to_vec();
+
+		let connected_peers = self.connected_peers.lock().keys().cloned().collect::<Vec<_>>();
+		if let Some(content) = self
+			.fetch_from_any(connected_peers, cid, &request, IfDisconnected::ImmediateError)
+			.await
+		{
+			return Ok(content)
+		}
+
+		let (tx, rx) = oneshot::channel();
+		self.to_worker
+			.unbounded_send(ToWorker::FindProviders(provider_key(cid), tx))
+			.map_err(|_| BitswapError::WorkerTerminated)?;
+		let providers = rx.await.map_err(|_| BitswapError::WorkerTerminated)?;
+		trace!(target: LOG_TARGET, "Found {} providers of {cid}", providers.len());
+
+		self.fetch_from_any(providers, cid, &request, IfDisconnected::TryConnect)
+			.await
+			.ok_or(BitswapError::NotFound)
+	}
+
+	/// Request the content referenced by `cid` from `peers`.
+	///
+	/// At most [`MAX_CONCURRENT_REQUESTS`] requests are in flight at the same time. The remaining
+	/// requests are cancelled as soon as one of the peers returns the content.
+	async fn fetch_from_any(
+		&self,
+		peers: Vec<PeerId>,
+		cid: &Cid,
+		request: &[u8],
+		connect: IfDisconnected,
+	) -> Option<Vec<u8>> {
+		let mut responses = stream::iter(peers)
+			.map(|peer| self.fetch_from(peer, cid, request, connect))
+			.buffer_unordered(MAX_CONCURRENT_REQUESTS);
+		while let Some(response) = responses.next().await {
+			if response.is_some() {
+				return response
+			}
+		}
+		None
+	}
+
+	/// Request the content referenced by `cid` from `peer`.
+	async fn fetch_from(
+		&self,
+		peer: PeerId,
+		cid: &Cid,
+		request: &[u8],
+		connect: IfDisconnected,
+	) -> Option<Vec<u8>> {
+		let response = match self
+			.network
+			.request(peer, ProtocolName::from(PROTOCOL_NAME), request.to_vec(), connect)
+			.await
+		{
+			Ok(response) => response,
+			Err(e) => {
+				trace!(target: LOG_TARGET, "Failed to request {cid} from {peer}: {e}");
+				return None
+			},
+		};
+
+		let response = match BitswapMessage::decode(&response[..]) {
+			Ok(response) => response,
+			Err(e) => {
+				debug!(target: LOG_TARGET, "Invalid bitswap response from {peer}: {e}");
+				return None
+			},
+		};
+
+		let content = response
+			.payload
+			.into_iter()
+			.map(|block| block.data)
+			.find(|content| verify(cid, content).is_ok());
+		if content.is_none() {
+			trace!(target: LOG_TARGET, "{peer} doesn't have {cid}");
+		}
+		content
+	}
+
+	/// Retain `content` referenced by `cid`, serve it to other peers and announce it in the DHT.
+	///
+	/// The content is kept until it is released with [`Self::release`].
+	pub fn retain(&self, cid: &Cid, content: &[u8]) -> Result<(), BitswapError> {
+		verify(cid, content)?;
+
+		{
+			let _lock = self.retain_lock.lock();
+			store::retain(&*self.backend, cid, content)?;
+		}
+
+		self.network.start_providing(provider_key(cid));
+		Ok(())
+	}
+
+	/// Stop retaining the content referenced by `cid`.
+	///
+	/// Returns `false` if the content wasn't retained.
+	pub fn release(&self, cid: &Cid) -> Result<bool, BitswapError> {
+		let released = {
+			let _lock = self.retain_lock.lock();
+			store::release(&*self.backend, cid)?
+		};
+
+		if released {
+			self.network.stop_providing(provider_key(cid));
+		}
+		Ok(released)
+	}
+
+	/// Returns the CIDs of all retained content.
+	pub fn retained(&self) -> Result<Vec<Cid>, BitswapError> {
+		Ok(store::retained(&*self.backend)?)
+	}
+}
+
+/// Background task of the [`BitswapClient`].
+pub struct BitswapClientWorker {
+	network: Arc<dyn Network>,
+	/// Network events merged with the messages from the [`BitswapClient`].
+	events: Pin<Box<dyn Stream<Item = WorkerEvent> + Send>>,
+	/// Connected peers along with the number of notification substreams open with them.
+	connected_peers: Arc<Mutex<HashMap<PeerId, usize>>>,
+	/// Pending provider lookups.
+	provider_queries: HashMap<KademliaKey, Vec<oneshot::Sender<Vec<PeerId>>>>,
+	/// Content to announce in the DHT on start.
+	retained: Vec<Cid>,
+}
+
+/// Events processed by the [`BitswapClientWorker`].
+enum WorkerEvent {
+	Network(Event),
+	Client(ToWorker),
+}
+
+impl BitswapClientWorker {
+	/// Run [`BitswapClientWorker`].
+	pub async fn run(mut self) {
+		for cid in std::mem::take(&mut self.retained) {
+			self.network.start_providing(provider_key(&cid));
+		}
+
+		while let Some(event) = self.events.next().await {
+			match event {
+				WorkerEvent::Network(event) => self.handle_network_event(event),
+				WorkerEvent::Client(ToWorker::FindProviders(key, tx)) =>
+					match self.provider_queries.entry(key.clone()) {
+						MapEntry::Occupied(mut queries) => queries.get_mut().push(tx),
+						MapEntry::Vacant(queries) => {
+							queries.insert(vec![tx]);
+							self.network.get_providers(&key);
+						},
+					},
+			}
+		}
+	}
+
+	fn handle_network_event(&mut self, event: Event) {
+		match event {
+			Event::NotificationStreamOpened { remote, .. } => {
+				*self.connected_peers.lock().entry(remote).or_default() += 1;
+			},
+			Event::NotificationStreamClosed { remote, .. } => {
+				let mut connected_peers = self.connected_peers.lock();
+				if let MapEntry::Occupied(mut streams) = connected_peers.entry(remote) {
+					*streams.get_mut() -= 1;
+					if *streams.get() == 0 {
+						streams.remove();
+					}
+				}
+			},
+			Event::Dht(DhtEvent::ProvidersFound(key, providers)) =>
+				for tx in self.provider_queries.remove(&key).unwrap_or_default() {
+					let _ = tx.send(providers.clone());
+				},
+			Event::Dht(DhtEvent::ProvidersNotFound(key)) =>
+				for tx in self.provider_queries.remove(&key).unwrap_or_default() {
+					let _ = tx.send(Vec::new());
+				},
+			Event::Dht(DhtEvent::StartProvidingFailed(key)) => {
+				debug!(target: LOG_TARGET, "Failed to announce content {key:?} in the DHT");
+			},
+			_ => {},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::schema::bitswap::message::Block as MessageBlock;
+	use futures::Future;
+	use sc_network::{ObservedRole, RequestFailure};
+	use std::{
+		collections::HashSet,
+		sync::atomic::{AtomicUsize, Ordering},
+		time::Duration,
+	};
+	use substrate_test_runtime::Block;
+
+	#[derive(Default)]
+	struct TestNetwork {
+		/// Content served by the peers.
+		content: HashMap<PeerId, Vec<u8>>,
+		/// Providers returned by the DHT lookups.
+		providers: Vec<PeerId>,
+		events: Mutex<Vec<TracingUnboundedSender<Event>>>,
+		requests: Mutex<Vec<(PeerId, IfDisconnected)>>,
+		provider_lookups: AtomicUsize,
+		provided: Mutex<HashSet<KademliaKey>>,
+		in_flight: AtomicUsize,
+		max_in_flight: AtomicUsize,
+	}
+
+	impl TestNetwork {
+		fn send_event(&self, event: Event) {
+			for tx in self.events.lock().iter() {
+				let _ = tx.unbounded_send(event.clone());
+			}
+		}
+	}
+
+	#[async_trait::async_trait]
+	impl NetworkRequest for TestNetwork {
+		async fn request(
+			&self,
+			target: PeerId,
+			_protocol: ProtocolName,
+			_request: Vec<u8>,
+			connect: IfDisconnected,
+		) -> Result<Vec<u8>, RequestFailure> {
+			self.requests.lock().push((target, connect));
+
+			let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+			self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+			tokio::time::sleep(Duration::from_millis(10)).await;
+			self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+			let payload = self
+				.content
+				.get(&target)
+				.map(|data| vec![MessageBlock { prefix: Vec::new(), data: data.clone() }])
+				.unwrap_or_default();
+			Ok(BitswapMessage { payload, ..Default::default() }.encode_to_vec())
+		}
+
+		fn start_request(
+			&self,
+			_: PeerId,
+			_: ProtocolName,
+			_: Vec<u8>,
+			_: oneshot::Sender<Result<Vec<u8>, RequestFailure>>,
+			_: IfDisconnected,
+		) {
+			unimplemented!();
+		}
+	}
+
+	impl NetworkDHTProvider for TestNetwork {
+		fn get_value(&self, _: &KademliaKey) {
+			unimplemented!();
+		}
+
+		fn put_value(&self, _: KademliaKey, _: Vec<u8>) {
+			unimplemented!();
+		}
+
+		fn start_providing(&self, key: KademliaKey) {
+			self.provided.lock().insert(key);
+		}
+
+		fn stop_providing(&self, key: KademliaKey) {
+			self.provided.lock().remove(&key);
+		}
+
+		fn get_providers(&self, key: &KademliaKey) {
+			self.provider_lookups.fetch_add(1, Ordering::SeqCst);
+			let event = if self.providers.is_empty() {
+				DhtEvent::ProvidersNotFound(key.clone())
+			} else {
+				DhtEvent::ProvidersFound(key.clone(), self.providers.clone())
+			};
+			self.send_event(Event::Dht(event));
+		}
+	}
+
+	impl NetworkEventStream for TestNetwork {
+		fn event_stream(&self, _: &'static str) -> Pin<Box<dyn Stream<Item = Event> + Send>> {
+			let (tx, rx) = tracing_unbounded("mpsc_test_network_events", 100);
+			self.events.lock().push(tx);
+			Box::pin(rx)
+		}
+	}
+
+	fn content_and_cid(content: &[u8]) -> (Vec<u8>, Cid) {
+		(content.to_vec(), Cid::new_v1(0x55, Code::Sha2_256.digest(content)))
+	}
+
+	fn bitswap_client(network: Arc<TestNetwork>) -> BitswapClient<Block> {
+		let backend = Arc::new(substrate_test_runtime_client::new());
+		let (client, worker) = BitswapClient::new(network, backend);
+		tokio::spawn(worker.run());
+		client
+	}
+
+	async fn connect(network: &TestNetwork, client: &BitswapClient<Block>, peer: PeerId) {
+		network.send_event(Event::NotificationStreamOpened {
+			remote: peer,
+			protocol: "/block-announces/1".into(),
+			negotiated_fallback: None,
+			role: ObservedRole::Full,
+			received_handshake: Vec::new(),
+		});
+		wait_until(|| client.connected_peers.lock().contains_key(&peer)).await;
+	}
+
+	fn wait_until(condition: impl Fn() -> bool) -> impl Future<Output = ()> {
+		async move {
+			while !condition() {
+				tokio::time::sleep(Duration::from_millis(1)).await;
+			}
+		}
+	}
+
+	#[tokio::test]
+	async fn fetch_from_connected_peers() {
+		let (content, cid) = content_and_cid(b"connected");
+		let (peer, other_peer) = (PeerId::random(), PeerId::random());
+		let network = Arc::new(TestNetwork {
+			content: [(peer, content.clone()), (other_peer, b"other content".to_vec())].into(),
+			..Default::default()
+		});
+		let client = bitswap_client(network.clone());
+		connect(&network, &client, peer).await;
+		connect(&network, &client, other_peer).await;
+
+		assert_eq!(client.fetch(&cid).await.unwrap(), content);
+		assert!(network
+			.requests
+			.lock()
+			.iter()
+			.all(|(_, connect)| *connect == IfDisconnected::ImmediateError));
+		assert_eq!(network.provider_lookups.load(Ordering::SeqCst), 0);
+	}
+
+	#[tokio::test]
+	async fn fetch_from_providers() {
+		let (content, cid) = content_and_cid(b"provided");
+		let (connected, provider) = (PeerId::random(), PeerId::random());
+		let network = Arc::new(TestNetwork {
+			content: [(provider, content.clone())].into(),
+			providers: vec![PeerId::random(), provider],
+			..Default::default()
+		});
+		let client = bitswap_client(network.clone());
+		connect(&network, &client, connected).await;
+
+		assert_eq!(client.fetch(&cid).await.unwrap(), content);
+		assert_eq!(network.provider_lookups.load(Ordering::SeqCst), 1);
+		assert!(network.requests.lock().contains(&(provider, IfDisconnected::TryConnect)));
+	}
+
+	#[tokio::test]
+	async fn fetch_fails_without_providers() {
+		let (_, cid) = content_and_cid(b"missing");
+		let network = Arc::new(TestNetwork::default());
+		let client = bitswap_client(network.clone());
+
+		assert!(matches!(client.fetch(&cid).await, Err(BitswapError::NotFound)));
+		assert_eq!(network.provider_lookups.load(Ordering::SeqCst), 1);
+	}
+
+	#[tokio::test]
+	async fn providers_are_queried_concurrently() {
+		let (content, cid) = content_and_cid(b"popular");
+		let mut providers =
+			(0..3 * MAX_CONCURRENT_REQUESTS).map(|_| PeerId::random()).collect::<Vec<_>>();
+		let provider = *providers.last().unwrap();
+		let network = Arc::new(TestNetwork {
+			content: [(provider, content.clone())].into(),
+			providers: providers.clone(),
+			..Default::default()
+		});
+		let client = bitswap_client(network.clone());
+
+		assert_eq!(client.fetch(&cid).await.unwrap(), content);
+		assert_eq!(network.max_in_flight.load(Ordering::SeqCst), MAX_CONCURRENT_REQUESTS);
+
+		let mut requested =
+			network.requests.lock().iter().map(|(peer, _)| *peer).collect::<Vec<_>>();
+		requested.sort();
+		providers.sort();
+		assert_eq!(requested, providers);
+	}
+
+	#[tokio::test]
+	async fn retained_content_is_provided() {
+		let (content, cid) = content_and_cid(b"retained");
+		let network = Arc::new(TestNetwork::default());
+		let client = bitswap_client(network.clone());
+
+		assert!(matches!(
+			client.retain(&cid, b"something else"),
+			Err(BitswapError::InvalidContent)
+		));
+		let (large, large_cid) = content_and_cid(&vec![0; store::MAX_CONTENT_SIZE + 1]);
+		assert!(matches!(client.retain(&large_cid, &large), Err(BitswapError::ContentTooLarge(_))));
+		assert!(network.provided.lock().is_empty());
+
+		client.retain(&cid, &content).unwrap();
+		assert_eq!(client.retained().unwrap(), vec![cid]);
+		assert!(network.provided.lock().contains(&provider_key(&cid)));
+		assert_eq!(client.fetch(&cid).await.unwrap(), content);
+		assert!(network.requests.lock().is_empty());
+
+		assert!(client.release(&cid).unwrap());
+		assert!(network.provided.lock().is_empty());
+		assert!(client.retained().unwrap().is_empty());
+	}
+}
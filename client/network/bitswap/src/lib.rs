@@ -14,18 +14,22 @@
 // You should have received a copy of the GNU General Public License
 // along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Bitswap server for Substrate.
+//! Bitswap server and client for Substrate.
 //!
 //! Allows querying transactions by hash over standard bitswap protocol
 //! Only supports bitswap 1.2.0.
-//! CID is expected to reference 256-bit Blake2b transaction hash.
+//! CID of a transaction is expected to reference 256-bit Blake2b transaction hash.
+//!
+//! Besides indexed transactions, arbitrary content can be retained by the local node with the
+//! [`BitswapClient`], which also fetches content from other peers and announces the retained
+//! content in the DHT.
 
 use cid::{self, Version};
 use futures::StreamExt;
 use libp2p_identity::PeerId;
 use log::{debug, error, trace};
 use prost::Message;
-use sc_client_api::BlockBackend;
+use sc_client_api::{AuxStore, BlockBackend};
 use sc_network::{
 	request_responses::{IncomingRequest, OutgoingResponse, ProtocolConfig},
 	types::ProtocolName,
@@ -38,7 +42,12 @@ use sp_runtime::traits::Block as BlockT;
 use std::{io, sync::Arc, time::Duration};
 use unsigned_varint::encode as varint_encode;
 
+mod client;
 mod schema;
+mod store;
+
+pub use cid::Cid;
+pub use client::{provider_key, BitswapClient, BitswapClientWorker, Network};
 
 const LOG_TARGET: &str = "bitswap";
 
@@ -90,15 +99,34 @@ impl Prefix {
 	}
 }
 
+/// Client backend used to look up and retain the content served over bitswap.
+pub trait BitswapBackend<B: BlockT>: BlockBackend<B> {
+	/// Insert and delete auxiliary data. Deletions occur after insertions.
+	fn update_aux(&self, insert: &[(&[u8], &[u8])], delete: &[&[u8]]) -> sp_blockchain::Result<()>;
+
+	/// Query auxiliary data.
+	fn read_aux(&self, key: &[u8]) -> sp_blockchain::Result<Option<Vec<u8>>>;
+}
+
+impl<B: BlockT, T: BlockBackend<B> + AuxStore> BitswapBackend<B> for T {
+	fn update_aux(&self, insert: &[(&[u8], &[u8])], delete: &[&[u8]]) -> sp_blockchain::Result<()> {
+		self.insert_aux(insert, delete)
+	}
+
+	fn read_aux(&self, key: &[u8]) -> sp_blockchain::Result<Option<Vec<u8>>> {
+		self.get_aux(key)
+	}
+}
+
 /// Bitswap request handler
 pub struct BitswapRequestHandler<B> {
-	client: Arc<dyn BlockBackend<B> + Send + Sync>,
+	client: Arc<dyn BitswapBackend<B> + Send + Sync>,
 	request_receiver: async_channel::Receiver<IncomingRequest>,
 }
 
 impl<B: BlockT> BitswapRequestHandler<B> {
 	/// Create a new [`BitswapRequestHandler`].
-	pub fn new(client: Arc<dyn BlockBackend<B> + Send + Sync>) -> (Self, ProtocolConfig) {
+	pub fn new(client: Arc<dyn BitswapBackend<B> + Send + Sync>) -> (Self, ProtocolConfig) {
 		let (tx, request_receiver) = async_channel::bounded(MAX_REQUEST_QUEUE);
 
 		let config = ProtocolConfig {
@@ -194,27 +222,17 @@ impl<B: BlockT> BitswapRequestHandler<B> {
 				},
 			};
 
-			if cid.version() != cid::Version::V1 ||
-				cid.hash().code() != u64::from(cid::multihash::Code::Blake2b256) ||
-				cid.hash().size() != 32
-			{
-				debug!(target: LOG_TARGET, "Ignoring unsupported CID {}: {}", peer, cid);
-				continue
-			}
-
-			let mut hash = B::Hash::default();
-			hash.as_mut().copy_from_slice(&cid.hash().digest()[0..32]);
-			let transaction = match self.client.indexed_transaction(hash) {
-				Ok(ex) => ex,
+			let content = match store::load(&*self.client, &cid) {
+				Ok(content) => content,
 				Err(e) => {
-					error!(target: LOG_TARGET, "Error retrieving transaction {}: {}", hash, e);
+					error!(target: LOG_TARGET, "Error retrieving content {}: {}", cid, e);
 					None
 				},
 			};
 
-			match transaction {
-				Some(transaction) => {
-					trace!(target: LOG_TARGET, "Found CID {:?}, hash {:?}", cid, hash);
+			match content {
+				Some(content) => {
+					trace!(target: LOG_TARGET, "Found CID {:?}", cid);
 
 					if entry.want_type == WantType::Block as i32 {
 						let prefix = Prefix {
@@ -225,7 +243,7 @@ impl<B: BlockT> BitswapRequestHandler<B> {
 						};
 						response
 							.payload
-							.push(MessageBlock { prefix: prefix.to_bytes(), data: transaction });
+							.push(MessageBlock { prefix: prefix.to_bytes(), data: content });
 					} else {
 						response.block_presences.push(BlockPresence {
 							r#type: BlockPresenceType::Have as i32,
@@ -234,7 +252,7 @@ impl<B: BlockT> BitswapRequestHandler<B> {
 					}
 				},
 				None => {
-					trace!(target: LOG_TARGET, "Missing CID {:?}, hash {:?}", cid, hash);
+					trace!(target: LOG_TARGET, "Missing CID {:?}", cid);
 
 					if entry.send_dont_have {
 						response.block_presences.push(BlockPresence {
@@ -284,6 +302,30 @@ pub enum BitswapError {
 	/// Too many blocks requested.
 	#[error("Too many block entries in the request.")]
 	TooManyEntries,
+
+	/// Multihash of the CID is not supported.
+	#[error("Unsupported multihash code: {0:#x}.")]
+	UnsupportedHash(u64),
+
+	/// Content doesn't match the CID.
+	#[error("Content doesn't match the CID.")]
+	InvalidContent,
+
+	/// Content was found neither locally nor on the network.
+	#[error("Content not found.")]
+	NotFound,
+
+	/// Content is too large to be retained.
+	#[error("Content of {0} bytes is too large to be retained.")]
+	ContentTooLarge(usize),
+
+	/// Retaining the content would exceed the size limit of the retained content.
+	#[error("Size limit of the retained content exceeded.")]
+	RetainedSizeExceeded,
+
+	/// The bitswap client worker has terminated.
+	#[error("Bitswap client worker terminated.")]
+	WorkerTerminated,
 }
 
 #[cfg(test)]
@@ -524,4 +566,59 @@ mod tests {
 			panic!("invalid event received");
 		}
 	}
+
+	#[tokio::test]
+	async fn retained_content_found() {
+		use cid::multihash::{Code, MultihashDigest};
+
+		let client: Arc<dyn BitswapBackend<substrate_test_runtime::Block> + Send + Sync> =
+			Arc::new(substrate_test_runtime_client::new());
+		let content = vec![0x13, 0x37, 0x13, 0x38];
+		let cid = cid::Cid::new_v1(0x55, Code::Sha2_256.digest(&content));
+
+		let (bitswap, config) = BitswapRequestHandler::new(client.clone());
+		tokio::spawn(async move { bitswap.run().await });
+
+		let request = |tx| IncomingRequest {
+			peer: PeerId::random(),
+			payload: BitswapMessage {
+				wantlist: Some(Wantlist {
+					entries: vec![Entry {
+						block: cid.to_bytes(),
+						send_dont_have: true,
+						..Default::default()
+					}],
+					full: false,
+				}),
+				..Default::default()
+			}
+			.encode_to_vec(),
+			pending_response: tx,
+		};
+		let inbound_queue = config.inbound_queue.unwrap();
+
+		store::retain(&*client, &cid, &content).unwrap();
+		assert_eq!(store::retained(&*client).unwrap(), vec![cid]);
+
+		let (tx, rx) = oneshot::channel();
+		inbound_queue.send(request(tx)).await.unwrap();
+		let response = schema::bitswap::Message::decode(
+			&rx.await.unwrap().result.expect("fetch to succeed")[..],
+		)
+		.unwrap();
+		assert_eq!(response.payload[0].data, content);
+
+		assert!(store::release(&*client, &cid).unwrap());
+		assert!(!store::release(&*client, &cid).unwrap());
+		assert!(store::retained(&*client).unwrap().is_empty());
+
+		let (tx, rx) = oneshot::channel();
+		inbound_queue.send(request(tx)).await.unwrap();
+		let response = schema::bitswap::Message::decode(
+			&rx.await.unwrap().result.expect("fetch to succeed")[..],
+		)
+		.unwrap();
+		assert!(response.payload.is_empty());
+		assert_eq!(response.block_presences[0].r#type, BlockPresenceType::DontHave as i32);
+	}
 }
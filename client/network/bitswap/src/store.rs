@@ -0,0 +1,144 @@
+// Copyright Parity Technologies (UK) Ltd.
+// This file is part of Substrate.
+
+// Substrate is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Substrate is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Substrate.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Lookup of the content served over bitswap.
+//!
+//! Content is either a transaction indexed on chain, addressed by its 256-bit Blake2b hash, or
+//! arbitrary data explicitly retained by the local node, which is kept in the aux storage.
+
+use crate::{BitswapBackend, BitswapError};
+use cid::{multihash::Code, Cid};
+use sp_runtime::{
+	codec::{Decode, Encode},
+	traits::Block as BlockT,
+};
+
+/// Aux storage prefix of the retained content.
+const RETAINED_CONTENT_PREFIX: &[u8] = b"bitswap_retained";
+
+/// Aux storage key of the list of retained CIDs along with the size of their content.
+const RETAINED_INDEX_KEY: &[u8] = b"bitswap_retained_index";
+
+/// Maximum size of a single piece of retained content.
+///
+/// Same as the maximum block size of IPFS, larger content wouldn't be accepted by other
+/// implementations anyway.
+pub(crate) const MAX_CONTENT_SIZE: usize = 2 * 1024 * 1024;
+
+/// Maximum total size of the retained content.
+pub(crate) const MAX_RETAINED_SIZE: u64 = 1024 * 1024 * 1024;
+
+fn retained_content_key(cid: &Cid) -> Vec<u8> {
+	let mut key = RETAINED_CONTENT_PREFIX.to_vec();
+	key.extend(cid.to_bytes());
+	key
+}
+
+fn retained_index<B: BlockT>(
+	backend: &dyn BitswapBackend<B>,
+) -> sp_blockchain::Result<Vec<(Vec<u8>, u32)>> {
+	match backend.read_aux(RETAINED_INDEX_KEY)? {
+		Some(encoded) => Vec::<(Vec<u8>, u32)>::decode(&mut &encoded[..]).map_err(|e| {
+			sp_blockchain::Error::Backend(format!("Invalid bitswap retained index: {e}"))
+		}),
+		None => Ok(Vec::new()),
+	}
+}
+
+/// Returns `true` if `cid` can reference an indexed transaction.
+fn is_transaction_cid(cid: &Cid) -> bool {
+	cid.version() == cid::Version::V1 &&
+		cid.hash().code() == u64::from(Code::Blake2b256) &&
+		cid.hash().size() == 32
+}
+
+/// Load the content referenced by `cid`, if it is available locally.
+pub(crate) fn load<B: BlockT>(
+	backend: &dyn BitswapBackend<B>,
+	cid: &Cid,
+) -> sp_blockchain::Result<Option<Vec<u8>>> {
+	if let Some(content) = backend.read_aux(&retained_content_key(cid))? {
+		return Ok(Some(content))
+	}
+
+	if !is_transaction_cid(cid) {
+		return Ok(None)
+	}
+
+	let mut hash = B::Hash::default();
+	hash.as_mut().copy_from_slice(&cid.hash().digest()[0..32]);
+	backend.indexed_transaction(hash)
+}
+
+/// Returns the CIDs of all retained content.
+pub(crate) fn retained<B: BlockT>(
+	backend: &dyn BitswapBackend<B>,
+) -> sp_blockchain::Result<Vec<Cid>> {
+	Ok(retained_index(backend)?
+		.into_iter()
+		.filter_map(|(cid, _)| Cid::read_bytes(&cid[..]).ok())
+		.collect())
+}
+
+/// Store `content` as referenced by `cid`.
+///
+/// The caller is responsible for checking that `content` matches `cid`. Fails if `content` is
+/// larger than [`MAX_CONTENT_SIZE`] or if retaining it would exceed [`MAX_RETAINED_SIZE`].
+pub(crate) fn retain<B: BlockT>(
+	backend: &dyn BitswapBackend<B>,
+	cid: &Cid,
+	content: &[u8],
+) -> Result<(), BitswapError> {
+	if content.len() > MAX_CONTENT_SIZE {
+		return Err(BitswapError::ContentTooLarge(content.len()))
+	}
+
+	let mut index = retained_index(backend)?;
+	let cid_bytes = cid.to_bytes();
+	index.retain(|(retained, _)| *retained != cid_bytes);
+
+	let retained_size = index.iter().map(|(_, size)| u64::from(*size)).sum::<u64>();
+	if retained_size + content.len() as u64 > MAX_RETAINED_SIZE {
+		return Err(BitswapError::RetainedSizeExceeded)
+	}
+	index.push((cid_bytes, content.len() as u32));
+
+	let index = index.encode();
+	Ok(backend.update_aux(
+		&[(&retained_content_key(cid)[..], content), (RETAINED_INDEX_KEY, &index[..])],
+		&[],
+	)?)
+}
+
+/// Remove the content referenced by `cid`.
+///
+/// Returns `false` if the content wasn't retained.
+pub(crate) fn release<B: BlockT>(
+	backend: &dyn BitswapBackend<B>,
+	cid: &Cid,
+) -> sp_blockchain::Result<bool> {
+	let mut index = retained_index(backend)?;
+	let cid_bytes = cid.to_bytes();
+	let len = index.len();
+	index.retain(|(retained, _)| *retained != cid_bytes);
+	if index.len() == len {
+		return Ok(false)
+	}
+
+	let index = index.encode();
+	backend.update_aux(&[(RETAINED_INDEX_KEY, &index[..])], &[&retained_content_key(cid)[..]])?;
+	Ok(true)
+}
@@ -270,6 +270,23 @@ impl<B: BlockT> Behaviour<B> {
 	pub fn put_value(&mut self, key: RecordKey, value: Vec<u8>) {
 		self.discovery.put_value(key, value);
 	}
+
+	/// Starts announcing the local node as a provider of `key`. Will later produce either a
+	/// `StartedProviding` or a `StartProvidingFailed` event.
+	pub fn start_providing(&mut self, key: RecordKey) {
+		self.discovery.start_providing(key);
+	}
+
+	/// Stops announcing the local node as a provider of `key`.
+	pub fn stop_providing(&mut self, key: &RecordKey) {
+		self.discovery.stop_providing(key);
+	}
+
+	/// Start querying the providers of `key` from the DHT. Will later produce either a
+	/// `ProvidersFound` or a `ProvidersNotFound` event.
+	pub fn get_providers(&mut self, key: RecordKey) {
+		self.discovery.get_providers(key);
+	}
 }
 
 fn reported_roles_to_observed_role(roles: Roles) -> ObservedRole {
@@ -353,6 +370,14 @@ impl From<DiscoveryOut> for BehaviourOut {
 				BehaviourOut::Dht(DhtEvent::ValuePut(key), duration),
 			DiscoveryOut::ValuePutFailed(key, duration) =>
 				BehaviourOut::Dht(DhtEvent::ValuePutFailed(key), duration),
+			DiscoveryOut::ProvidersFound(key, providers, duration) =>
+				BehaviourOut::Dht(DhtEvent::ProvidersFound(key, providers), duration),
+			DiscoveryOut::ProvidersNotFound(key, duration) =>
+				BehaviourOut::Dht(DhtEvent::ProvidersNotFound(key), duration),
+			DiscoveryOut::StartedProviding(key, duration) =>
+				BehaviourOut::Dht(DhtEvent::StartedProviding(key), duration),
+			DiscoveryOut::StartProvidingFailed(key, duration) =>
+				BehaviourOut::Dht(DhtEvent::StartProvidingFailed(key), duration),
 			DiscoveryOut::RandomKademliaStarted => BehaviourOut::RandomKademliaStarted,
 		}
 	}
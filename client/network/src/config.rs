@@ -608,6 +608,11 @@ pub struct NetworkConfiguration {
 	/// a modification of the way the implementation works. Different nodes with different
 	/// configured values remain compatible with each other.
 	pub yamux_window_size: Option<u32>,
+
+	/// Enable the QUIC transport, used for `/udp/<port>/quic-v1` addresses.
+	///
+	/// Ignored with [`TransportConfig::MemoryOnly`].
+	pub enable_quic: bool,
 }
 
 impl NetworkConfiguration {
@@ -640,6 +645,7 @@ impl NetworkConfiguration {
 				.expect("value is a constant; constant is non-zero; qed."),
 			yamux_window_size: None,
 			ipfs_server: false,
+			enable_quic: false,
 		}
 	}
 
@@ -57,8 +57,8 @@ use libp2p::{
 	kad::{
 		handler::KademliaHandler,
 		record::store::{MemoryStore, RecordStore},
-		GetClosestPeersError, GetRecordOk, Kademlia, KademliaBucketInserts, KademliaConfig,
-		KademliaEvent, QueryId, QueryResult, Quorum, Record, RecordKey,
+		GetClosestPeersError, GetProvidersOk, GetRecordOk, Kademlia, KademliaBucketInserts,
+		KademliaConfig, KademliaEvent, QueryId, QueryResult, Quorum, Record, RecordKey,
 	},
 	mdns::{self, tokio::Behaviour as TokioMdns},
 	multiaddr::Protocol,
@@ -265,6 +265,7 @@ impl DiscoveryConfig {
 					.expect("value is a constant; constant is non-zero; qed."),
 			),
 			records_to_publish: Default::default(),
+			pending_provider_queries: Default::default(),
 		}
 	}
 }
@@ -308,6 +309,11 @@ pub struct DiscoveryBehaviour {
 	/// did not return the record(in `FinishedWithNoAdditionalRecord`). We will then put the record
 	/// to these peers.
 	records_to_publish: HashMap<QueryId, Record>,
+	/// Keys of the provider queries that haven't yielded any provider yet.
+	///
+	/// `FinishedWithNoAdditionalRecord` doesn't contain the key, so it is tracked here in order
+	/// to report it as not found.
+	pending_provider_queries: HashMap<QueryId, RecordKey>,
 }
 
 impl DiscoveryBehaviour {
@@ -408,6 +414,37 @@ impl DiscoveryBehaviour {
 		}
 	}
 
+	/// Start announcing the local node as a provider of the content identified by `key`.
+	///
+	/// A corresponding `StartedProviding` or `StartProvidingFailed` event will later be generated.
+	/// The announcement is republished periodically until [`Self::stop_providing`] is called.
+	pub fn start_providing(&mut self, key: RecordKey) {
+		if let Some(k) = self.kademlia.as_mut() {
+			if let Err(e) = k.start_providing(key.clone()) {
+				warn!(target: "sub-libp2p", "Libp2p => Failed to start providing: {:?}", e);
+				self.pending_events
+					.push_back(DiscoveryOut::StartProvidingFailed(key, Duration::from_secs(0)));
+			}
+		}
+	}
+
+	/// Stop announcing the local node as a provider of the content identified by `key`.
+	pub fn stop_providing(&mut self, key: &RecordKey) {
+		if let Some(k) = self.kademlia.as_mut() {
+			k.stop_providing(key);
+		}
+	}
+
+	/// Start looking for providers of the content identified by `key`.
+	///
+	/// A corresponding `ProvidersFound` or `ProvidersNotFound` event will later be generated.
+	pub fn get_providers(&mut self, key: RecordKey) {
+		if let Some(k) = self.kademlia.as_mut() {
+			let id = k.get_providers(key.clone());
+			self.pending_provider_queries.insert(id, key);
+		}
+	}
+
 	/// Returns the number of nodes in each Kademlia kbucket for each Kademlia instance.
 	///
 	/// Identifies Kademlia instances by their [`ProtocolId`] and kbuckets by the base 2 logarithm
@@ -491,6 +528,26 @@ pub enum DiscoveryOut {
 	/// Returning the corresponding key as well as the request duration.
 	ValuePutFailed(RecordKey, Duration),
 
+	/// The DHT yielded providers for the content with a given key.
+	///
+	/// Returning the corresponding key, the providers as well as the request duration.
+	ProvidersFound(RecordKey, Vec<PeerId>, Duration),
+
+	/// No providers were found for the content with a given key.
+	///
+	/// Returning the corresponding key as well as the request duration.
+	ProvidersNotFound(RecordKey, Duration),
+
+	/// The local node was successfully announced as a provider of the content with a given key.
+	///
+	/// Returning the corresponding key as well as the request duration.
+	StartedProviding(RecordKey, Duration),
+
+	/// Announcing the local node as a provider failed.
+	///
+	/// Returning the corresponding key as well as the request duration.
+	StartProvidingFailed(RecordKey, Duration),
+
 	/// Started a random Kademlia query.
 	///
 	/// Only happens if [`DiscoveryConfig::with_dht_random_walk`] has been configured to `true`.
@@ -867,6 +924,100 @@ impl NetworkBehaviour for DiscoveryBehaviour {
 						};
 						return Poll::Ready(ToSwarm::GenerateEvent(ev))
 					},
+					KademliaEvent::OutboundQueryProgressed {
+						result: QueryResult::GetProviders(res),
+						stats,
+						id,
+						..
+					} => {
+						let ev = match res {
+							Ok(GetProvidersOk::FoundProviders { key, providers }) => {
+								debug!(
+									target: "sub-libp2p",
+									"Libp2p => Found {} providers for {:?}",
+									providers.len(),
+									key,
+								);
+
+								// Report the first batch of providers only and finish the query,
+								// the same way as for records.
+								if self.pending_provider_queries.remove(&id).is_none() {
+									continue
+								}
+								if let Some(kad) = self.kademlia.as_mut() {
+									if let Some(mut query) = kad.query_mut(&id) {
+										query.finish();
+									}
+								}
+
+								DiscoveryOut::ProvidersFound(
+									key,
+									providers.into_iter().collect(),
+									stats.duration().unwrap_or_default(),
+								)
+							},
+							Ok(GetProvidersOk::FinishedWithNoAdditionalRecord { .. }) =>
+								match self.pending_provider_queries.remove(&id) {
+									Some(key) => DiscoveryOut::ProvidersNotFound(
+										key,
+										stats.duration().unwrap_or_default(),
+									),
+									None => continue,
+								},
+							Err(e) => {
+								debug!(
+									target: "sub-libp2p",
+									"Libp2p => Failed to get providers: {:?}",
+									e,
+								);
+								self.pending_provider_queries.remove(&id);
+								DiscoveryOut::ProvidersNotFound(
+									e.into_key(),
+									stats.duration().unwrap_or_default(),
+								)
+							},
+						};
+						return Poll::Ready(ToSwarm::GenerateEvent(ev))
+					},
+					KademliaEvent::OutboundQueryProgressed {
+						result: QueryResult::StartProviding(res),
+						stats,
+						..
+					} => {
+						let ev = match res {
+							Ok(ok) => DiscoveryOut::StartedProviding(
+								ok.key,
+								stats.duration().unwrap_or_default(),
+							),
+							Err(e) => {
+								debug!(
+									target: "sub-libp2p",
+									"Libp2p => Failed to start providing: {:?}",
+									e,
+								);
+								DiscoveryOut::StartProvidingFailed(
+									e.into_key(),
+									stats.duration().unwrap_or_default(),
+								)
+							},
+						};
+						return Poll::Ready(ToSwarm::GenerateEvent(ev))
+					},
+					KademliaEvent::OutboundQueryProgressed {
+						result: QueryResult::RepublishProvider(res),
+						..
+					} => match res {
+						Ok(ok) => debug!(
+							target: "sub-libp2p",
+							"Libp2p => Provider record republished: {:?}",
+							ok.key,
+						),
+						Err(e) => debug!(
+							target: "sub-libp2p",
+							"Libp2p => Republishing of provider record {:?} failed with: {:?}",
+							e.key(), e,
+						),
+					},
 					KademliaEvent::OutboundQueryProgressed {
 						result: QueryResult::RepublishRecord(res),
 						..
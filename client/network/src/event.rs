@@ -28,7 +28,8 @@ use libp2p::{kad::record::Key, PeerId};
 use sc_network_common::{role::ObservedRole, sync::message::BlockAnnouncesHandshake};
 use sp_runtime::traits::Block as BlockT;
 
-/// Events generated by DHT as a response to get_value and put_value requests.
+/// Events generated by DHT as a response to get_value, put_value, get_providers and
+/// start_providing requests.
 #[derive(Debug, Clone)]
 #[must_use]
 pub enum DhtEvent {
@@ -43,6 +44,18 @@ pub enum DhtEvent {
 
 	/// An error has occurred while putting a record into the DHT.
 	ValuePutFailed(Key),
+
+	/// Providers of the requested key have been found.
+	ProvidersFound(Key, Vec<PeerId>),
+
+	/// No providers of the requested key have been found in the DHT.
+	ProvidersNotFound(Key),
+
+	/// The local node has been successfully announced as a provider of the key.
+	StartedProviding(Key),
+
+	/// An error has occurred while announcing the local node as a provider of the key.
+	StartProvidingFailed(Key),
 }
 
 /// Type for events generated by networking layer.
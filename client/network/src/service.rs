@@ -263,6 +263,7 @@ where
 			transport::build_transport(
 				local_identity.clone(),
 				config_mem,
+				network_config.enable_quic,
 				network_config.yamux_window_size,
 				yamux_maximum_buffer_size,
 			)
@@ -829,6 +830,27 @@ where
 	fn put_value(&self, key: KademliaKey, value: Vec<u8>) {
 		let _ = self.to_worker.unbounded_send(ServiceToWorkerMsg::PutValue(key, value));
 	}
+
+	/// Start announcing the local node as a provider of `key` in the DHT.
+	///
+	/// This will generate either a `StartedProviding` or a `StartProvidingFailed` event and pass
+	/// it as an item on the [`NetworkWorker`] stream.
+	fn start_providing(&self, key: KademliaKey) {
+		let _ = self.to_worker.unbounded_send(ServiceToWorkerMsg::StartProviding(key));
+	}
+
+	/// Stop announcing the local node as a provider of `key` in the DHT.
+	fn stop_providing(&self, key: KademliaKey) {
+		let _ = self.to_worker.unbounded_send(ServiceToWorkerMsg::StopProviding(key));
+	}
+
+	/// Start looking for providers of `key` in the DHT.
+	///
+	/// This will generate either a `ProvidersFound` or a `ProvidersNotFound` event and pass it
+	/// as an item on the [`NetworkWorker`] stream.
+	fn get_providers(&self, key: &KademliaKey) {
+		let _ = self.to_worker.unbounded_send(ServiceToWorkerMsg::GetProviders(key.clone()));
+	}
 }
 
 #[async_trait::async_trait]
@@ -1190,6 +1212,9 @@ impl<'a> NotificationSenderReadyT for NotificationSenderReady<'a> {
 enum ServiceToWorkerMsg {
 	GetValue(KademliaKey),
 	PutValue(KademliaKey, Vec<u8>),
+	StartProviding(KademliaKey),
+	StopProviding(KademliaKey),
+	GetProviders(KademliaKey),
 	AddKnownAddress(PeerId, Multiaddr),
 	ReportPeer(PeerId, ReputationChange),
 	EventStream(out_events::Sender),
@@ -1320,6 +1345,12 @@ where
 				self.network_service.behaviour_mut().get_value(key),
 			ServiceToWorkerMsg::PutValue(key, value) =>
 				self.network_service.behaviour_mut().put_value(key, value),
+			ServiceToWorkerMsg::StartProviding(key) =>
+				self.network_service.behaviour_mut().start_providing(key),
+			ServiceToWorkerMsg::StopProviding(key) =>
+				self.network_service.behaviour_mut().stop_providing(&key),
+			ServiceToWorkerMsg::GetProviders(key) =>
+				self.network_service.behaviour_mut().get_providers(key),
 			ServiceToWorkerMsg::AddKnownAddress(peer_id, addr) =>
 				self.network_service.behaviour_mut().add_known_address(peer_id, addr),
 			ServiceToWorkerMsg::ReportPeer(peer_id, reputation_change) =>
@@ -1566,6 +1597,10 @@ where
 						DhtEvent::ValueNotFound(_) => "value-not-found",
 						DhtEvent::ValuePut(_) => "value-put",
 						DhtEvent::ValuePutFailed(_) => "value-put-failed",
+						DhtEvent::ProvidersFound(..) => "providers-found",
+						DhtEvent::ProvidersNotFound(_) => "providers-not-found",
+						DhtEvent::StartedProviding(_) => "started-providing",
+						DhtEvent::StartProvidingFailed(_) => "start-providing-failed",
 					};
 					metrics
 						.kademlia_query_duration
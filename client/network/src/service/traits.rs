@@ -57,6 +57,15 @@ pub trait NetworkDHTProvider {
 
 	/// Start putting a value in the DHT.
 	fn put_value(&self, key: KademliaKey, value: Vec<u8>);
+
+	/// Start announcing the local node as a provider of the content with the given key.
+	fn start_providing(&self, key: KademliaKey);
+
+	/// Stop announcing the local node as a provider of the content with the given key.
+	fn stop_providing(&self, key: KademliaKey);
+
+	/// Start looking for providers of the content with the given key in the DHT.
+	fn get_providers(&self, key: &KademliaKey);
 }
 
 impl<T> NetworkDHTProvider for Arc<T>
@@ -71,6 +80,18 @@ where
 	fn put_value(&self, key: KademliaKey, value: Vec<u8>) {
 		T::put_value(self, key, value)
 	}
+
+	fn start_providing(&self, key: KademliaKey) {
+		T::start_providing(self, key)
+	}
+
+	fn stop_providing(&self, key: KademliaKey) {
+		T::stop_providing(self, key)
+	}
+
+	fn get_providers(&self, key: &KademliaKey) {
+		T::get_providers(self, key)
+	}
 }
 
 /// Provides an ability to set a fork sync request for a particular block.
@@ -25,7 +25,7 @@ use libp2p::{
 		transport::{Boxed, OptionalTransport},
 		upgrade,
 	},
-	dns, identity, noise, tcp, websocket, PeerId, Transport, TransportExt,
+	dns, identity, noise, quic, tcp, websocket, PeerId, Transport, TransportExt,
 };
 use std::{sync::Arc, time::Duration};
 
@@ -36,6 +36,9 @@ pub use libp2p::bandwidth::BandwidthSinks;
 /// If `memory_only` is true, then only communication within the same process are allowed. Only
 /// addresses with the format `/memory/...` are allowed.
 ///
+/// If `enable_quic` is true and `memory_only` is false, QUIC (`/udp/.../quic-v1` addresses) is
+/// supported alongside TCP. QUIC connections come with their own encryption and multiplexing.
+///
 /// `yamux_window_size` is the maximum size of the Yamux receive windows. `None` to leave the
 /// default (256kiB).
 ///
@@ -49,6 +52,7 @@ pub use libp2p::bandwidth::BandwidthSinks;
 pub fn build_transport(
 	keypair: identity::Keypair,
 	memory_only: bool,
+	enable_quic: bool,
 	yamux_window_size: Option<u32>,
 	yamux_maximum_buffer_size: usize,
 ) -> (Boxed<(PeerId, StreamMuxerBox)>, Arc<BandwidthSinks>) {
@@ -100,6 +104,18 @@ pub fn build_transport(
 		.authenticate(authentication_config)
 		.multiplex(multiplexing_config)
 		.timeout(Duration::from_secs(20))
+		.map(|(peer_id, muxer), _| (peer_id, StreamMuxerBox::new(muxer)));
+
+	let quic_transport = if enable_quic && !memory_only {
+		OptionalTransport::some(quic::tokio::Transport::new(quic::Config::new(&keypair)))
+	} else {
+		OptionalTransport::none()
+	};
+
+	let transport = quic_transport
+		.map(|(peer_id, connection), _| (peer_id, StreamMuxerBox::new(connection)))
+		.or_transport(transport)
+		.map(|output, _| output.into_inner())
 		.boxed();
 
 	transport.with_bandwidth_logging()
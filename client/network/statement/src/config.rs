@@ -31,3 +31,6 @@ pub(crate) const MAX_STATEMENT_SIZE: u64 = 256 * 1024;
 
 /// Maximum number of statement validation request we keep at any moment.
 pub(crate) const MAX_PENDING_STATEMENTS: usize = 8192;
+
+/// Maximum number of topics a peer may register interest in.
+pub(crate) const MAX_INTEREST_TOPICS: usize = 1024;
@@ -20,7 +20,8 @@
 //!
 //! Usage:
 //!
-//! - Use [`StatementHandlerPrototype::new`] to create a prototype.
+//! - Use [`StatementHandlerPrototype::new`] to create a prototype, optionally restricting the
+//! statements gossiped to this node with [`StatementHandlerPrototype::with_topic_interest`].
 //! - Pass the return value of [`StatementHandlerPrototype::set_config`] to the network
 //! configuration as an extra peers set.
 //! - Use [`StatementHandlerPrototype::build`] then [`StatementHandler::run`] to obtain a
@@ -44,7 +45,7 @@ use sc_network_common::{
 	sync::{SyncEvent, SyncEventStream},
 };
 use sp_statement_store::{
-	Hash, NetworkPriority, Statement, StatementSource, StatementStore, SubmitResult,
+	Hash, NetworkPriority, Statement, StatementSource, StatementStore, SubmitResult, TopicFilter,
 };
 use std::{
 	collections::{hash_map::Entry, HashMap, HashSet},
@@ -61,6 +62,17 @@ pub type Statements = Vec<Statement>;
 /// Future resolving to statement import result.
 pub type StatementImportFuture = oneshot::Receiver<SubmitResult>;
 
+/// Notification of the statement protocol.
+///
+/// Peers that negotiated the legacy protocol exchange plain [`Statements`] instead.
+#[derive(Encode, Decode)]
+enum StatementMessage<S> {
+	/// Statements sent to the peer.
+	Statements(Vec<S>),
+	/// Topics of the statements the sender wants to receive.
+	TopicInterest(TopicFilter),
+}
+
 mod rep {
 	use sc_network::ReputationChange as Rep;
 	/// Reputation change when a peer sends us any statement.
@@ -78,6 +90,8 @@ mod rep {
 	pub const DUPLICATE_STATEMENT: Rep = Rep::new(-(1 << 7), "Duplicate statement");
 	/// Reputation change when a peer sends us particularly useful statement
 	pub const EXCELLENT_STATEMENT: Rep = Rep::new(1 << 8, "High priority statement");
+	/// Reputation change when a peer registers interest in too many topics.
+	pub const BAD_TOPIC_INTEREST: Rep = Rep::new(-(1 << 12), "Bad topic interest");
 }
 
 const LOG_TARGET: &str = "statement-gossip";
@@ -103,26 +117,48 @@ impl Metrics {
 /// Prototype for a [`StatementHandler`].
 pub struct StatementHandlerPrototype {
 	protocol_name: ProtocolName,
+	legacy_protocol_name: ProtocolName,
+	topic_interest: TopicFilter,
 }
 
 impl StatementHandlerPrototype {
 	/// Create a new instance.
 	pub fn new<Hash: AsRef<[u8]>>(genesis_hash: Hash, fork_id: Option<&str>) -> Self {
 		let genesis_hash = genesis_hash.as_ref();
-		let protocol_name = if let Some(fork_id) = fork_id {
-			format!("/{}/{}/statement/1", array_bytes::bytes2hex("", genesis_hash), fork_id)
-		} else {
-			format!("/{}/statement/1", array_bytes::bytes2hex("", genesis_hash))
+		let protocol_name = |version: u32| {
+			if let Some(fork_id) = fork_id {
+				format!(
+					"/{}/{}/statement/{}",
+					array_bytes::bytes2hex("", genesis_hash),
+					fork_id,
+					version
+				)
+			} else {
+				format!("/{}/statement/{}", array_bytes::bytes2hex("", genesis_hash), version)
+			}
 		};
 
-		Self { protocol_name: protocol_name.into() }
+		Self {
+			protocol_name: protocol_name(2).into(),
+			legacy_protocol_name: protocol_name(1).into(),
+			topic_interest: Default::default(),
+		}
+	}
+
+	/// Only ask peers for statements that pass `topic_interest`.
+	///
+	/// By default peers gossip all statements. Peers that only support the legacy protocol
+	/// ignore the interest and keep sending everything.
+	pub fn with_topic_interest(mut self, topic_interest: TopicFilter) -> Self {
+		self.topic_interest = topic_interest;
+		self
 	}
 
 	/// Returns the configuration of the set to put in the network configuration.
 	pub fn set_config(&self) -> NonDefaultSetConfig {
 		NonDefaultSetConfig {
 			notifications_protocol: self.protocol_name.clone(),
-			fallback_names: Vec::new(),
+			fallback_names: vec![self.legacy_protocol_name.clone()],
 			max_notification_size: MAX_STATEMENT_SIZE,
 			handshake: None,
 			set_config: SetConfig {
@@ -178,6 +214,7 @@ impl StatementHandlerPrototype {
 
 		let handler = StatementHandler {
 			protocol_name: self.protocol_name,
+			topic_interest: self.topic_interest,
 			propagate_timeout: (Box::pin(interval(PROPAGATE_TIMEOUT))
 				as Pin<Box<dyn Stream<Item = ()> + Send>>)
 				.fuse(),
@@ -207,6 +244,8 @@ pub struct StatementHandler<
 	S: SyncEventStream + sp_consensus::SyncOracle,
 > {
 	protocol_name: ProtocolName,
+	/// Topics of the statements we ask peers to send us.
+	topic_interest: TopicFilter,
 	/// Interval at which we call `propagate_statements`.
 	propagate_timeout: stream::Fuse<Pin<Box<dyn Stream<Item = ()> + Send>>>,
 	/// Pending statements verification tasks.
@@ -239,6 +278,10 @@ struct Peer {
 	/// Holds a set of statements known to this peer.
 	known_statements: LruHashSet<Hash>,
 	role: ObservedRole,
+	/// Whether the peer negotiated the legacy protocol, which has no topic interest.
+	legacy: bool,
+	/// Topics of the statements the peer wants to receive.
+	topic_interest: TopicFilter,
 }
 
 impl<N, S> StatementHandler<N, S>
@@ -311,9 +354,10 @@ where
 	async fn handle_network_event(&mut self, event: Event) {
 		match event {
 			Event::Dht(_) => {},
-			Event::NotificationStreamOpened { remote, protocol, role, .. }
-				if protocol == self.protocol_name =>
-			{
+			Event::NotificationStreamOpened {
+				remote, protocol, role, negotiated_fallback, ..
+			} if protocol == self.protocol_name => {
+				let legacy = negotiated_fallback.is_some();
 				let _was_in = self.peers.insert(
 					remote,
 					Peer {
@@ -321,9 +365,21 @@ where
 							NonZeroUsize::new(MAX_KNOWN_STATEMENTS).expect("Constant is nonzero"),
 						),
 						role,
+						legacy,
+						topic_interest: Default::default(),
 					},
 				);
 				debug_assert!(_was_in.is_none());
+
+				if !legacy && self.topic_interest != TopicFilter::default() {
+					let interest =
+						StatementMessage::<Statement>::TopicInterest(self.topic_interest.clone());
+					self.network.write_notification(
+						remote,
+						self.protocol_name.clone(),
+						interest.encode(),
+					);
+				}
 			},
 			Event::NotificationStreamClosed { remote, protocol }
 				if protocol == self.protocol_name =>
@@ -337,21 +393,35 @@ where
 					if protocol != self.protocol_name {
 						continue
 					}
-					// Accept statements only when node is not major syncing
-					if self.sync.is_major_syncing() {
-						log::trace!(
-							target: LOG_TARGET,
-							"{remote}: Ignoring statements while major syncing or offline"
-						);
+					let Some(legacy) = self.peers.get(&remote).map(|peer| peer.legacy) else {
 						continue
-					}
-					if let Ok(statements) = <Statements as Decode>::decode(&mut message.as_ref()) {
-						self.on_statements(remote, statements);
+					};
+					let message = if legacy {
+						<Statements as Decode>::decode(&mut message.as_ref())
+							.map(StatementMessage::Statements)
 					} else {
-						log::debug!(
-							target: LOG_TARGET,
-							"Failed to decode statement list from {remote}"
-						);
+						<StatementMessage<Statement> as Decode>::decode(&mut message.as_ref())
+					};
+					match message {
+						Ok(StatementMessage::Statements(statements)) => {
+							// Accept statements only when node is not major syncing
+							if self.sync.is_major_syncing() {
+								log::trace!(
+									target: LOG_TARGET,
+									"{remote}: Ignoring statements while major syncing or offline"
+								);
+								continue
+							}
+							self.on_statements(remote, statements);
+						},
+						Ok(StatementMessage::TopicInterest(topic_interest)) =>
+							self.on_topic_interest(remote, topic_interest),
+						Err(_) => {
+							log::debug!(
+								target: LOG_TARGET,
+								"Failed to decode statement message from {remote}"
+							);
+						},
 					}
 				}
 			},
@@ -419,6 +489,25 @@ where
 		}
 	}
 
+	/// Called when peer tells us which statements it wants to receive.
+	fn on_topic_interest(&mut self, who: PeerId, topic_interest: TopicFilter) {
+		let (TopicFilter::MatchAll(topics) | TopicFilter::MatchAny(topics)) = &topic_interest;
+		if topics.len() > MAX_INTEREST_TOPICS {
+			log::debug!(
+				target: LOG_TARGET,
+				"{who}: Ignoring interest in {} topics, exceeding `MAX_INTEREST_TOPICS`({})",
+				topics.len(),
+				MAX_INTEREST_TOPICS,
+			);
+			self.network.report_peer(who, rep::BAD_TOPIC_INTEREST);
+			return
+		}
+		if let Some(peer) = self.peers.get_mut(&who) {
+			log::trace!(target: LOG_TARGET, "{who}: Topic interest {:?}", topic_interest);
+			peer.topic_interest = topic_interest;
+		}
+	}
+
 	fn on_handle_statement_import(&mut self, who: PeerId, import: &SubmitResult) {
 		match import {
 			SubmitResult::New(NetworkPriority::High) =>
@@ -457,6 +546,7 @@ where
 
 			let to_send = statements
 				.iter()
+				.filter(|(_, stmt)| peer.topic_interest.matches(stmt))
 				.filter_map(|(hash, stmt)| peer.known_statements.insert(*hash).then(|| stmt))
 				.collect::<Vec<_>>();
 
@@ -464,8 +554,12 @@ where
 
 			if !to_send.is_empty() {
 				log::trace!(target: LOG_TARGET, "Sending {} statements to {}", to_send.len(), who);
-				self.network
-					.write_notification(*who, self.protocol_name.clone(), to_send.encode());
+				let message = if peer.legacy {
+					to_send.encode()
+				} else {
+					StatementMessage::Statements(to_send).encode()
+				};
+				self.network.write_notification(*who, self.protocol_name.clone(), message);
 			}
 		}
 
@@ -487,3 +581,295 @@ where
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use libp2p::Multiaddr;
+	use sc_network::{
+		config::MultiaddrWithPeerId, NotificationSenderError, NotificationSenderT, ReputationChange,
+	};
+	use sp_statement_store::{Result, Topic};
+	use std::sync::Mutex;
+
+	#[derive(Clone, Default)]
+	struct TestNetwork {
+		notifications: Arc<Mutex<Vec<(PeerId, ProtocolName, Vec<u8>)>>>,
+		reports: Arc<Mutex<Vec<(PeerId, ReputationChange)>>>,
+	}
+
+	impl NetworkPeers for TestNetwork {
+		fn set_authorized_peers(&self, _peers: HashSet<PeerId>) {}
+
+		fn set_authorized_only(&self, _reserved_only: bool) {}
+
+		fn add_known_address(&self, _peer_id: PeerId, _addr: Multiaddr) {}
+
+		fn report_peer(&self, who: PeerId, cost_benefit: ReputationChange) {
+			self.reports.lock().unwrap().push((who, cost_benefit));
+		}
+
+		fn disconnect_peer(&self, _who: PeerId, _protocol: ProtocolName) {}
+
+		fn accept_unreserved_peers(&self) {}
+
+		fn deny_unreserved_peers(&self) {}
+
+		fn add_reserved_peer(&self, _peer: MultiaddrWithPeerId) -> std::result::Result<(), String> {
+			Ok(())
+		}
+
+		fn remove_reserved_peer(&self, _peer_id: PeerId) {}
+
+		fn set_reserved_peers(
+			&self,
+			_protocol: ProtocolName,
+			_peers: HashSet<Multiaddr>,
+		) -> std::result::Result<(), String> {
+			Ok(())
+		}
+
+		fn add_peers_to_reserved_set(
+			&self,
+			_protocol: ProtocolName,
+			_peers: HashSet<Multiaddr>,
+		) -> std::result::Result<(), String> {
+			Ok(())
+		}
+
+		fn remove_peers_from_reserved_set(
+			&self,
+			_protocol: ProtocolName,
+			_peers: Vec<PeerId>,
+		) -> std::result::Result<(), String> {
+			Ok(())
+		}
+
+		fn sync_num_connected(&self) -> usize {
+			0
+		}
+	}
+
+	impl NetworkEventStream for TestNetwork {
+		fn event_stream(&self, _name: &'static str) -> Pin<Box<dyn Stream<Item = Event> + Send>> {
+			Box::pin(stream::pending())
+		}
+	}
+
+	impl NetworkNotification for TestNetwork {
+		fn write_notification(&self, target: PeerId, protocol: ProtocolName, message: Vec<u8>) {
+			self.notifications.lock().unwrap().push((target, protocol, message));
+		}
+
+		fn notification_sender(
+			&self,
+			_target: PeerId,
+			_protocol: ProtocolName,
+		) -> std::result::Result<Box<dyn NotificationSenderT>, NotificationSenderError> {
+			unimplemented!()
+		}
+
+		fn set_notification_handshake(&self, _protocol: ProtocolName, _handshake: Vec<u8>) {}
+	}
+
+	struct TestSync;
+
+	impl SyncEventStream for TestSync {
+		fn event_stream(
+			&self,
+			_name: &'static str,
+		) -> Pin<Box<dyn Stream<Item = SyncEvent> + Send>> {
+			Box::pin(stream::pending())
+		}
+	}
+
+	impl sp_consensus::SyncOracle for TestSync {
+		fn is_major_syncing(&self) -> bool {
+			false
+		}
+
+		fn is_offline(&self) -> bool {
+			false
+		}
+	}
+
+	#[derive(Default)]
+	struct TestStore {
+		statements: Vec<(Hash, Statement)>,
+	}
+
+	impl StatementStore for TestStore {
+		fn statements(&self) -> Result<Vec<(Hash, Statement)>> {
+			Ok(self.statements.clone())
+		}
+
+		fn statement(&self, hash: &Hash) -> Result<Option<Statement>> {
+			Ok(self.statements.iter().find(|(h, _)| h == hash).map(|(_, s)| s.clone()))
+		}
+
+		fn broadcasts(&self, _match_all_topics: &[Topic]) -> Result<Vec<Vec<u8>>> {
+			Ok(Vec::new())
+		}
+
+		fn posted(&self, _match_all_topics: &[Topic], _dest: [u8; 32]) -> Result<Vec<Vec<u8>>> {
+			Ok(Vec::new())
+		}
+
+		fn posted_clear(
+			&self,
+			_match_all_topics: &[Topic],
+			_dest: [u8; 32],
+		) -> Result<Vec<Vec<u8>>> {
+			Ok(Vec::new())
+		}
+
+		fn submit(&self, _statement: Statement, _source: StatementSource) -> SubmitResult {
+			SubmitResult::New(NetworkPriority::High)
+		}
+
+		fn remove(&self, _hash: &Hash) -> Result<()> {
+			Ok(())
+		}
+	}
+
+	fn statement(data: u8, topics: &[Topic]) -> Statement {
+		let mut statement = Statement::new();
+		statement.set_plain_data(vec![data]);
+		for (i, topic) in topics.iter().enumerate() {
+			statement.set_topic(i, *topic);
+		}
+		statement
+	}
+
+	fn handler(
+		prototype: StatementHandlerPrototype,
+		statements: &[Statement],
+	) -> (StatementHandler<TestNetwork, TestSync>, TestNetwork) {
+		let network = TestNetwork::default();
+		let store =
+			TestStore { statements: statements.iter().map(|s| (s.hash(), s.clone())).collect() };
+		let handler = prototype
+			// Keep the import queue open without processing it.
+			.build(network.clone(), TestSync, Arc::new(store), None, std::mem::forget)
+			.unwrap();
+		(handler, network)
+	}
+
+	fn open(
+		handler: &mut StatementHandler<TestNetwork, TestSync>,
+		remote: PeerId,
+		negotiated_fallback: Option<ProtocolName>,
+	) {
+		futures::executor::block_on(handler.handle_network_event(
+			Event::NotificationStreamOpened {
+				remote,
+				protocol: handler.protocol_name.clone(),
+				negotiated_fallback,
+				role: ObservedRole::Full,
+				received_handshake: Vec::new(),
+			},
+		));
+	}
+
+	#[test]
+	fn topic_interest_is_sent_to_peers_on_current_protocol() {
+		let interest = TopicFilter::MatchAny(vec![[1; 32]]);
+		let prototype =
+			StatementHandlerPrototype::new([0; 32], None).with_topic_interest(interest.clone());
+		let legacy_protocol_name = prototype.legacy_protocol_name.clone();
+		let (mut handler, network) = handler(prototype, &[]);
+		let (peer, legacy_peer) = (PeerId::random(), PeerId::random());
+
+		open(&mut handler, peer, None);
+		open(&mut handler, legacy_peer, Some(legacy_protocol_name));
+
+		assert_eq!(
+			*network.notifications.lock().unwrap(),
+			vec![(
+				peer,
+				handler.protocol_name.clone(),
+				StatementMessage::<Statement>::TopicInterest(interest).encode()
+			)],
+		);
+	}
+
+	#[test]
+	fn statements_are_propagated_according_to_topic_interest() {
+		let statements = [statement(0, &[[0; 32]]), statement(1, &[[1; 32]])];
+		let prototype = StatementHandlerPrototype::new([0; 32], None);
+		let legacy_protocol_name = prototype.legacy_protocol_name.clone();
+		let (mut handler, network) = handler(prototype, &statements);
+		let (peer, legacy_peer) = (PeerId::random(), PeerId::random());
+
+		open(&mut handler, peer, None);
+		open(&mut handler, legacy_peer, Some(legacy_protocol_name));
+		handler.on_topic_interest(peer, TopicFilter::MatchAll(vec![[1; 32]]));
+		handler.propagate_statements();
+
+		let mut notifications = network.notifications.lock().unwrap().clone();
+		notifications.sort_by_key(|(who, _, _)| *who != peer);
+		assert_eq!(
+			notifications,
+			vec![
+				(
+					peer,
+					handler.protocol_name.clone(),
+					StatementMessage::Statements(vec![&statements[1]]).encode()
+				),
+				// Legacy peers receive plain statements and ignore topic interest.
+				(legacy_peer, handler.protocol_name.clone(), statements.to_vec().encode()),
+			],
+		);
+	}
+
+	#[test]
+	fn legacy_peers_send_plain_statements() {
+		let prototype = StatementHandlerPrototype::new([0; 32], None);
+		let legacy_protocol_name = prototype.legacy_protocol_name.clone();
+		let (mut handler, _network) = handler(prototype, &[]);
+		let (peer, legacy_peer) = (PeerId::random(), PeerId::random());
+		let (statement0, statement1) = (statement(0, &[]), statement(1, &[]));
+
+		open(&mut handler, peer, None);
+		open(&mut handler, legacy_peer, Some(legacy_protocol_name));
+		futures::executor::block_on(handler.handle_network_event(Event::NotificationsReceived {
+			remote: legacy_peer,
+			messages: vec![(
+				handler.protocol_name.clone(),
+				vec![statement0.clone()].encode().into(),
+			)],
+		}));
+		futures::executor::block_on(handler.handle_network_event(Event::NotificationsReceived {
+			remote: peer,
+			messages: vec![(
+				handler.protocol_name.clone(),
+				StatementMessage::Statements(vec![statement1.clone()]).encode().into(),
+			)],
+		}));
+
+		assert_eq!(
+			handler.pending_statements_peers,
+			HashMap::from([
+				(statement0.hash(), HashSet::from([legacy_peer])),
+				(statement1.hash(), HashSet::from([peer])),
+			]),
+		);
+	}
+
+	#[test]
+	fn topic_interest_is_limited() {
+		let (mut handler, network) = handler(StatementHandlerPrototype::new([0; 32], None), &[]);
+		let peer = PeerId::random();
+		open(&mut handler, peer, None);
+
+		let topics = vec![[1; 32]; MAX_INTEREST_TOPICS];
+		handler.on_topic_interest(peer, TopicFilter::MatchAny(topics.clone()));
+		assert_eq!(handler.peers[&peer].topic_interest, TopicFilter::MatchAny(topics.clone()));
+		assert!(network.reports.lock().unwrap().is_empty());
+
+		let too_many = vec![[2; 32]; MAX_INTEREST_TOPICS + 1];
+		handler.on_topic_interest(peer, TopicFilter::MatchAll(too_many));
+		assert_eq!(handler.peers[&peer].topic_interest, TopicFilter::MatchAny(topics));
+		assert_eq!(*network.reports.lock().unwrap(), vec![(peer, rep::BAD_TOPIC_INTEREST)]);
+	}
+}
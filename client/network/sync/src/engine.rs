@@ -33,7 +33,7 @@ use prometheus_endpoint::{
 };
 use schnellru::{ByLength, LruMap};
 
-use sc_client_api::{BlockBackend, HeaderBackend, ProofProvider};
+use sc_client_api::{AuxStore, BlockBackend, HeaderBackend, ProofProvider};
 use sc_consensus::import_queue::ImportQueueService;
 use sc_network::{
 	config::{FullNetworkConfiguration, NonDefaultSetConfig, ProtocolId},
@@ -268,6 +268,7 @@ where
 		+ BlockBackend<B>
 		+ HeaderMetadata<B, Error = sp_blockchain::Error>
 		+ ProofProvider<B>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
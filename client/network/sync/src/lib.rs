@@ -45,7 +45,7 @@ use log::{debug, error, info, trace, warn};
 use prost::Message;
 
 use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
-use sc_client_api::{BlockBackend, ProofProvider};
+use sc_client_api::{AuxStore, BlockBackend, ProofProvider};
 use sc_consensus::{
 	import_queue::ImportQueueService, BlockImportError, BlockImportStatus, IncomingBlock,
 };
@@ -131,6 +131,10 @@ const MAX_CONCURRENT_BLOCK_ANNOUNCE_VALIDATIONS_PER_PEER: usize = 4;
 /// Pick the state to sync as the latest finalized number minus this.
 const STATE_SYNC_FINALITY_THRESHOLD: u32 = 8;
 
+/// Move the state sync to the latest finalized block once this many peers refused to serve the
+/// state of its target, e.g. because they pruned it.
+const STATE_SYNC_RETARGET_FAILURES: usize = 3;
+
 /// We use a heuristic that with a high likelihood, by the time
 /// `MAJOR_SYNC_BLOCKS` have been imported we'll be on the same
 /// chain as (or at least closer to) the peer so we want to delay
@@ -322,6 +326,10 @@ pub struct ChainSync<B: BlockT, Client> {
 	block_announce_validation_per_peer_stats: HashMap<PeerId, usize>,
 	/// State sync in progress, if any.
 	state_sync: Option<StateSync<B, Client>>,
+	/// Peers with a pending state request for a previous state sync target.
+	stale_state_requests: HashSet<PeerId>,
+	/// Peers that refused to serve the state of the current state sync target.
+	state_sync_failed_peers: HashSet<PeerId>,
 	/// Warp sync in progress, if any.
 	warp_sync: Option<WarpSync<B, Client>>,
 	/// Warp sync params.
@@ -476,6 +484,7 @@ where
 		+ BlockBackend<B>
 		+ HeaderMetadata<B, Error = sp_blockchain::Error>
 		+ ProofProvider<B>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
@@ -1085,13 +1094,9 @@ where
 							number,
 							hash,
 						);
-						self.state_sync = Some(StateSync::new(
-							self.client.clone(),
-							header,
-							None,
-							None,
-							*skip_proofs,
-						));
+						self.state_sync =
+							Some(StateSync::resume(self.client.clone(), header, *skip_proofs));
+						self.state_sync_failed_peers.clear();
 						self.allowed_requests.set_all();
 					}
 				}
@@ -1230,6 +1235,7 @@ where
 		}
 		self.peers.remove(who);
 		self.pending_responses.remove(who);
+		self.stale_state_requests.remove(who);
 		self.extra_justifications.peer_disconnected(who);
 		self.allowed_requests.set_all();
 		self.fork_targets.retain(|_, target| {
@@ -1384,6 +1390,7 @@ where
 		+ BlockBackend<B>
 		+ HeaderMetadata<B, Error = sp_blockchain::Error>
 		+ ProofProvider<B>
+		+ AuxStore
 		+ Send
 		+ Sync
 		+ 'static,
@@ -1437,6 +1444,8 @@ where
 			block_announce_validation: Default::default(),
 			block_announce_validation_per_peer_stats: Default::default(),
 			state_sync: None,
+			stale_state_requests: HashSet::new(),
+			state_sync_failed_peers: HashSet::new(),
 			warp_sync: None,
 			import_existing: false,
 			gap_sync: None,
@@ -2221,6 +2230,10 @@ where
 				Ok(Err(e)) => {
 					debug!(target: "sync", "Request to peer {:?} failed: {:?}.", id, e);
 
+					if let (PeerRequest::State, RequestFailure::Refused) = (&request, &e) {
+						self.on_state_request_refused(id);
+					}
+
 					match e {
 						RequestFailure::Network(OutboundFailure::Timeout) => {
 							self.network_service.report_peer(id, rep::TIMEOUT);
@@ -2558,6 +2571,10 @@ where
 				self.allowed_requests.set_all();
 			}
 		}
+		if self.stale_state_requests.remove(who) {
+			debug!(target: "sync", "Ignored state response from {} for a previous target", who);
+			return Ok(OnStateData::Continue)
+		}
 		let import_result = if let Some(sync) = &mut self.state_sync {
 			debug!(
 				target: "sync",
@@ -2566,7 +2583,7 @@ where
 				response.entries.len(),
 				response.proof.len(),
 			);
-			sync.import(*response)
+			sync.import_and_persist(*response)
 		} else if let Some(sync) = &mut self.warp_sync {
 			debug!(
 				target: "sync",
@@ -2599,7 +2616,10 @@ where
 				debug!(target: "sync", "State download is complete. Import is queued");
 				Ok(OnStateData::Import(origin, block))
 			},
-			state::ImportResult::Continue => Ok(OnStateData::Continue),
+			state::ImportResult::Continue => {
+				self.state_sync_failed_peers.clear();
+				Ok(OnStateData::Continue)
+			},
 			state::ImportResult::BadResponse => {
 				debug!(target: "sync", "Bad state data received from {}", who);
 				Err(BadPeer(*who, rep::BAD_BLOCK))
@@ -2607,6 +2627,40 @@ where
 		}
 	}
 
+	/// A peer refused to serve a state request, move the state sync to the latest finalized block
+	/// if enough peers don't serve its target anymore.
+	fn on_state_request_refused(&mut self, who: PeerId) {
+		let sync = match &mut self.state_sync {
+			Some(sync) => sync,
+			None => return,
+		};
+		if sync.is_complete() || self.stale_state_requests.contains(&who) {
+			return
+		}
+
+		self.state_sync_failed_peers.insert(who);
+		if self.state_sync_failed_peers.len() < STATE_SYNC_RETARGET_FAILURES.min(self.peers.len()) {
+			return
+		}
+
+		let info = self.client.info();
+		if info.finalized_number <= sync.target_block_num() {
+			return
+		}
+		if let Ok(Some(header)) = self.client.header(info.finalized_hash) {
+			sync.retarget(header);
+			self.state_sync_failed_peers.clear();
+			// Responses to pending requests are for the previous target.
+			self.stale_state_requests.extend(
+				self.peers
+					.iter()
+					.filter(|(_, peer)| peer.state == PeerSyncState::DownloadingState)
+					.map(|(id, _)| *id),
+			);
+			self.allowed_requests.set_all();
+		}
+	}
+
 	fn on_warp_sync_data(&mut self, who: &PeerId, response: EncodedProof) -> Result<(), BadPeer> {
 		if let Some(peer) = self.peers.get_mut(who) {
 			if let PeerSyncState::DownloadingWarpProof = peer.state {
@@ -2727,7 +2781,9 @@ where
 							"State sync is complete ({} MiB), restarting block sync.",
 							self.state_sync.as_ref().map_or(0, |s| s.progress().size / (1024 * 1024)),
 						);
-						self.state_sync = None;
+						if let Some(mut sync) = self.state_sync.take() {
+							sync.clear_persisted();
+						}
 						self.mode = SyncMode::Full;
 						output.extend(self.restart());
 					}
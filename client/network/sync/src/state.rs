@@ -20,8 +20,8 @@
 
 use crate::schema::v1::{StateEntry, StateRequest, StateResponse};
 use codec::{Decode, Encode};
-use log::debug;
-use sc_client_api::{CompactProof, ProofProvider};
+use log::{debug, warn};
+use sc_client_api::{AuxStore, CompactProof, ProofProvider};
 use sc_consensus::ImportedState;
 use sc_network_common::sync::StateDownloadProgress;
 use smallvec::SmallVec;
@@ -30,7 +30,43 @@ use sp_runtime::{
 	traits::{Block as BlockT, Header, NumberFor},
 	Justifications,
 };
-use std::{collections::HashMap, sync::Arc};
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+};
+
+/// Aux-db key of the persisted state sync progress.
+const STATE_SYNC_PROGRESS_KEY: &[u8] = b"sync_state_sync_progress";
+/// Aux-db key of the child tries kept over from a previous target.
+const STATE_SYNC_REUSED_KEY: &[u8] = b"sync_state_sync_reused";
+/// Aux-db key prefix of the persisted state chunks.
+const STATE_SYNC_CHUNK_PREFIX: &[u8] = b"sync_state_sync_chunk";
+
+/// Key values and child trie storage keys imported from one state response, by trie root.
+type StateChunk = Vec<(Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>, Vec<Vec<u8>>)>;
+
+fn chunk_key(index: u32) -> Vec<u8> {
+	(STATE_SYNC_CHUNK_PREFIX, index).encode()
+}
+
+/// State sync progress as persisted in the aux-db.
+#[derive(Encode, Decode)]
+struct PersistedProgress<H> {
+	/// Header of the block the state is downloaded for.
+	target_header: H,
+	/// Whether the responses were requested without proofs.
+	skip_proof: bool,
+	/// Number of state chunks stored under [`STATE_SYNC_CHUNK_PREFIX`].
+	chunks: u32,
+	/// Key to continue the download from.
+	last_key: Vec<Vec<u8>>,
+	/// Imported bytes so far, for progress reporting.
+	imported_bytes: u64,
+	/// Last top trie key downloaded for a previous target, see [`StateSync::retarget`].
+	refresh_until: Option<Vec<u8>>,
+	/// Whether the keys up to `refresh_until` are being downloaded again.
+	refreshing: bool,
+}
 
 /// State sync state machine. Accumulates partial state data until it
 /// is ready to be imported.
@@ -46,6 +82,15 @@ pub struct StateSync<B: BlockT, Client> {
 	client: Arc<Client>,
 	imported_bytes: u64,
 	skip_proof: bool,
+	/// Roots of fully downloaded child tries carried over from a previous target.
+	reused_roots: HashSet<Vec<u8>>,
+	/// Number of state chunks persisted for the current target.
+	persisted_chunks: u32,
+	/// Last top trie key downloaded for a previous target. The keys up to it are downloaded
+	/// again for the current target once the end of the top trie is reached.
+	refresh_until: Option<Vec<u8>>,
+	/// Download of the keys up to `refresh_until` for the current target.
+	refresh: Option<Box<StateSync<B, Client>>>,
 }
 
 /// Import state chunk result.
@@ -83,18 +128,57 @@ where
 			complete: false,
 			imported_bytes: 0,
 			skip_proof,
+			reused_roots: HashSet::default(),
+			persisted_chunks: 0,
+			refresh_until: None,
+			refresh: None,
 		}
 	}
 
 	///  Validate and import a state response.
 	pub fn import(&mut self, response: StateResponse) -> ImportResult<B> {
+		if let Some(refresh) = &mut self.refresh {
+			let complete = match refresh.import_response(response) {
+				Some(complete) => complete,
+				None => return ImportResult::BadResponse,
+			};
+			let refresh_until = self
+				.refresh_until
+				.as_ref()
+				.expect("Refresh only runs with a refresh range; qed");
+			if !complete && refresh.last_key.first().map_or(true, |key| key <= refresh_until) {
+				return ImportResult::Continue
+			}
+			self.finish_refresh();
+			return self.finish()
+		}
+
+		match self.import_response(response) {
+			None => ImportResult::BadResponse,
+			Some(false) => ImportResult::Continue,
+			Some(true) if self.refresh_until.is_some() => {
+				debug!(
+					target: "sync",
+					"Downloading the state of #{} again up to the key of the previous target",
+					self.target_block_num(),
+				);
+				self.start_refresh();
+				ImportResult::Continue
+			},
+			Some(true) => self.finish(),
+		}
+	}
+
+	/// Validate a state response and add its content to the state, returning whether the download
+	/// is complete or `None` for a bad response.
+	fn import_response(&mut self, response: StateResponse) -> Option<bool> {
 		if response.entries.is_empty() && response.proof.is_empty() {
 			debug!(target: "sync", "Bad state response");
-			return ImportResult::BadResponse
+			return None
 		}
 		if !self.skip_proof && response.proof.is_empty() {
 			debug!(target: "sync", "Missing proof");
-			return ImportResult::BadResponse
+			return None
 		}
 		let complete = if !self.skip_proof {
 			debug!(target: "sync", "Importing state from {} trie nodes", response.proof.len());
@@ -103,7 +187,7 @@ where
 				Ok(proof) => proof,
 				Err(e) => {
 					debug!(target: "sync", "Error decoding proof: {:?}", e);
-					return ImportResult::BadResponse
+					return None
 				},
 			};
 			let (values, completed) = match self.client.verify_range_proof(
@@ -117,7 +201,7 @@ where
 						"StateResponse failed proof verification: {}",
 						e,
 					);
-					return ImportResult::BadResponse
+					return None
 				},
 				Ok(values) => values,
 			};
@@ -150,8 +234,11 @@ where
 				} else {
 					values.key_values
 				};
+				let reused = self.reused_roots.contains(&values.state_root);
 				let entry = self.state.entry(values.state_root).or_default();
-				if entry.0.len() > 0 && entry.1.len() > 1 {
+				if reused {
+					// Child trie downloaded for a previous target, content is unchanged.
+				} else if entry.0.len() > 0 && entry.1.len() > 1 {
 					// Already imported child_trie with same root.
 					// Warning this will not work with parallel download.
 				} else if entry.0.is_empty() {
@@ -196,8 +283,9 @@ where
 					complete = false;
 				}
 				let is_top = state.state_root.is_empty();
+				let reused = self.reused_roots.contains(&state.state_root);
 				let entry = self.state.entry(state.state_root).or_default();
-				if entry.0.len() > 0 && entry.1.len() > 1 {
+				if reused || (entry.0.len() > 0 && entry.1.len() > 1) {
 					// Already imported child trie with same root.
 				} else {
 					let mut child_roots = Vec::new();
@@ -217,25 +305,86 @@ where
 			}
 			complete
 		};
-		if complete {
-			self.complete = true;
-			ImportResult::Import(
-				self.target_block,
-				self.target_header.clone(),
-				ImportedState {
-					block: self.target_block,
-					state: std::mem::take(&mut self.state).into(),
-				},
-				self.target_body.clone(),
-				self.target_justifications.clone(),
-			)
-		} else {
-			ImportResult::Continue
+		if !complete && self.last_key.len() == 2 && self.is_reused_child_trie(&self.last_key[0]) {
+			// Continue after the child trie in the top trie, its content is already known.
+			self.last_key.pop();
+		}
+		Some(complete)
+	}
+
+	/// Mark the download as complete and hand out the state.
+	fn finish(&mut self) -> ImportResult<B> {
+		self.complete = true;
+		// Drop kept over child tries that are not referenced by the new target.
+		self.state
+			.retain(|root, (_, storage_keys)| root.is_empty() || !storage_keys.is_empty());
+		ImportResult::Import(
+			self.target_block,
+			self.target_header.clone(),
+			ImportedState {
+				block: self.target_block,
+				state: std::mem::take(&mut self.state).into(),
+			},
+			self.target_body.clone(),
+			self.target_justifications.clone(),
+		)
+	}
+
+	/// Start downloading the keys up to `refresh_until` again.
+	fn start_refresh(&mut self) {
+		let mut refresh =
+			Self::new(self.client.clone(), self.target_header.clone(), None, None, self.skip_proof);
+		// The child tries downloaded so far are complete, there is no need to download them again.
+		for (root, (key_values, _)) in self.state.iter() {
+			if !root.is_empty() && !key_values.is_empty() {
+				refresh.reused_roots.insert(root.clone());
+				refresh.state.insert(root.clone(), Default::default());
+			}
+		}
+		self.refresh = Some(Box::new(refresh));
+	}
+
+	/// Replace the state downloaded up to `refresh_until` for previous targets with the refreshed
+	/// one.
+	fn finish_refresh(&mut self) {
+		let refresh = self.refresh.take().expect("Only called while refreshing; qed");
+		let refresh_until = self.refresh_until.take().expect("Refresh has a refresh range; qed");
+		self.imported_bytes += refresh.imported_bytes;
+		for (root, (key_values, storage_keys)) in refresh.state {
+			if root.is_empty() {
+				// The keys after `refresh_until` were already downloaded for this target.
+				let top = self.state.entry(root).or_default();
+				let mut refreshed: Vec<_> =
+					key_values.into_iter().filter(|(key, _)| *key <= refresh_until).collect();
+				refreshed.append(&mut top.0);
+				top.0 = refreshed;
+				continue
+			}
+			let storage_keys: Vec<_> =
+				storage_keys.into_iter().filter(|key| *key <= refresh_until).collect();
+			if storage_keys.is_empty() {
+				continue
+			}
+			let entry = self.state.entry(root).or_default();
+			if entry.0.is_empty() {
+				entry.0 = key_values;
+			}
+			entry.1.extend(storage_keys);
 		}
 	}
 
+	/// Check if the child trie stored under `storage_key` was downloaded for a previous target.
+	fn is_reused_child_trie(&self, storage_key: &[u8]) -> bool {
+		self.state.iter().any(|(root, (_, storage_keys))| {
+			self.reused_roots.contains(root) && storage_keys.iter().any(|key| key == storage_key)
+		})
+	}
+
 	/// Produce next state request.
 	pub fn next_request(&self) -> StateRequest {
+		if let Some(refresh) = &self.refresh {
+			return refresh.next_request()
+		}
 		StateRequest {
 			block: self.target_block.encode(),
 			start: self.last_key.clone().into_vec(),
@@ -260,8 +409,418 @@ where
 
 	/// Returns state sync estimated progress.
 	pub fn progress(&self) -> StateDownloadProgress {
-		let cursor = *self.last_key.get(0).and_then(|last| last.get(0)).unwrap_or(&0u8);
+		let (last_key, refreshed_bytes) = match &self.refresh {
+			Some(refresh) => (&refresh.last_key, refresh.imported_bytes),
+			None => (&self.last_key, 0),
+		};
+		let cursor = *last_key.get(0).and_then(|last| last.get(0)).unwrap_or(&0u8);
 		let percent_done = cursor as u32 * 100 / 256;
-		StateDownloadProgress { percentage: percent_done, size: self.imported_bytes }
+		StateDownloadProgress {
+			percentage: percent_done,
+			size: self.imported_bytes + refreshed_bytes,
+		}
+	}
+}
+
+impl<B, Client> StateSync<B, Client>
+where
+	B: BlockT,
+	Client: ProofProvider<B> + AuxStore + Send + Sync + 'static,
+{
+	/// Create a new instance that persists its progress in the aux-db, picking up a previously
+	/// interrupted state sync if there is one.
+	///
+	/// The persisted state is loaded back and the download continues from the last received key,
+	/// for the persisted target. [`StateSync::retarget`] moves it to `target_header` if peers don't
+	/// serve the persisted target anymore.
+	pub fn resume(client: Arc<Client>, target_header: B::Header, skip_proof: bool) -> Self {
+		let progress = match client.get_aux(STATE_SYNC_PROGRESS_KEY) {
+			Ok(Some(encoded)) => PersistedProgress::<B::Header>::decode(&mut encoded.as_slice())
+				.map_err(|e| debug!(target: "sync", "Error decoding state sync progress: {}", e))
+				.ok(),
+			Ok(None) => None,
+			Err(e) => {
+				warn!(target: "sync", "Error reading state sync progress: {}", e);
+				None
+			},
+		};
+
+		let mut sync = Self::new(client.clone(), target_header, None, None, skip_proof);
+		let progress = match progress {
+			Some(progress) if progress.skip_proof == skip_proof => progress,
+			progress => {
+				// Nothing to resume or persisted with a different sync mode, start from scratch.
+				sync.persisted_chunks = progress.map_or(0, |progress| progress.chunks);
+				sync.clear_persisted();
+				return sync
+			},
+		};
+
+		let mut resumed = Self::new(client, progress.target_header, None, None, skip_proof);
+		resumed.persisted_chunks = progress.chunks;
+		resumed.refresh_until = progress.refresh_until;
+		if let Err(e) = resumed.restore(progress.last_key, progress.imported_bytes) {
+			debug!(target: "sync", "Unable to resume state sync: {}", e);
+			resumed.clear_persisted();
+			return sync
+		}
+		if progress.refreshing && resumed.refresh_until.is_some() {
+			resumed.start_refresh();
+		}
+		debug!(
+			target: "sync",
+			"Resumed state sync for #{} ({}) with {} chunks",
+			resumed.target_block_num(),
+			resumed.target_block,
+			resumed.persisted_chunks,
+		);
+		resumed
+	}
+
+	/// Validate and import a state response, persisting the imported state if it is not complete
+	/// yet.
+	///
+	/// The keys downloaded again after a retarget are not persisted, that download starts over
+	/// when the state sync is resumed.
+	pub fn import_and_persist(&mut self, response: StateResponse) -> ImportResult<B> {
+		if self.refresh.is_some() {
+			return self.import(response)
+		}
+		let imported: HashMap<_, _> = self
+			.state
+			.iter()
+			.map(|(root, (key_values, storage_keys))| {
+				(root.clone(), (key_values.len(), storage_keys.len()))
+			})
+			.collect();
+		let result = self.import(response);
+		if let ImportResult::Continue = result {
+			let chunk: StateChunk = self
+				.state
+				.iter()
+				.filter_map(|(root, (key_values, storage_keys))| {
+					let (key_values_len, storage_keys_len) =
+						imported.get(root).copied().unwrap_or_default();
+					(key_values.len() > key_values_len || storage_keys.len() > storage_keys_len)
+						.then(|| {
+							(
+								root.clone(),
+								key_values[key_values_len..].to_vec(),
+								storage_keys[storage_keys_len..].to_vec(),
+							)
+						})
+				})
+				.collect();
+			let key = chunk_key(self.persisted_chunks);
+			match self.persist_progress(self.persisted_chunks + 1, &[(&key, &chunk.encode())]) {
+				Ok(()) => self.persisted_chunks += 1,
+				Err(e) => warn!(target: "sync", "Error persisting state sync progress: {}", e),
+			}
+		}
+		result
+	}
+
+	/// Switch the download to a newer target block, when peers don't serve the current one anymore.
+	///
+	/// The download of the top trie goes on from the current key for the new target. Once it
+	/// reaches the end of the top trie, the keys downloaded for previous targets are downloaded
+	/// again, as their values may have changed, and the state is complete. Child tries that were
+	/// fully downloaded are kept: the download skips over any of them with an unchanged root in the
+	/// new state.
+	pub fn retarget(&mut self, target_header: B::Header) {
+		if let Some(refresh) = self.refresh.take() {
+			// Everything downloaded since the previous retarget is out of date as well, go on from
+			// the position of the refresh.
+			self.last_key = refresh.last_key;
+			for (root, entry) in refresh.state {
+				if !root.is_empty() && !entry.0.is_empty() {
+					self.state.entry(root).or_insert(entry);
+				}
+			}
+		}
+		let downloading = if self.last_key.len() == 2 { self.last_key.first() } else { None };
+		// Keys up to the top trie cursor are downloaded again for the new target. A partially
+		// downloaded child trie is downloaded again with them.
+		let refresh_until = self.last_key.first().cloned();
+		let reused: HashMap<_, _> = std::mem::take(&mut self.state)
+			.into_iter()
+			.filter(|(root, (key_values, storage_keys))| {
+				!root.is_empty() &&
+					!key_values.is_empty() &&
+					!downloading.map_or(false, |key| storage_keys.contains(key))
+			})
+			.map(|(root, (key_values, _))| (root, (key_values, Vec::new())))
+			.collect();
+		debug!(
+			target: "sync",
+			"Retargeting state sync from #{} to #{} at key {:?}, keeping {} child tries",
+			self.target_block_num(),
+			target_header.number(),
+			refresh_until.as_ref().map(|key| sp_core::hexdisplay::HexDisplay::from(key)),
+			reused.len(),
+		);
+
+		self.clear_persisted();
+		self.reused_roots = reused.keys().cloned().collect();
+		self.imported_bytes = reused
+			.values()
+			.flat_map(|(key_values, _)| key_values.iter())
+			.map(|(key, _)| key.len() as u64)
+			.sum();
+		self.state = reused;
+		self.target_block = target_header.hash();
+		self.target_root = *target_header.state_root();
+		self.target_header = target_header;
+		self.target_body = None;
+		self.target_justifications = None;
+		self.last_key = refresh_until.iter().cloned().collect();
+		self.refresh_until = refresh_until;
+		self.complete = false;
+
+		let reused: Vec<_> =
+			self.state.iter().map(|(root, (key_values, _))| (root, key_values)).collect();
+		if let Err(e) = self.persist_progress(0, &[(STATE_SYNC_REUSED_KEY, &reused.encode())]) {
+			warn!(target: "sync", "Error persisting state sync progress: {}", e);
+		}
+	}
+
+	/// Persist the progress together with `insert`, recording `chunks` persisted state chunks.
+	fn persist_progress(
+		&self,
+		chunks: u32,
+		insert: &[(&[u8], &[u8])],
+	) -> sp_blockchain::Result<()> {
+		let progress = PersistedProgress {
+			target_header: self.target_header.clone(),
+			skip_proof: self.skip_proof,
+			chunks,
+			last_key: self.last_key.to_vec(),
+			imported_bytes: self.imported_bytes,
+			refresh_until: self.refresh_until.clone(),
+			refreshing: self.refresh.is_some(),
+		}
+		.encode();
+		let insert: Vec<_> = insert
+			.iter()
+			.copied()
+			.chain(std::iter::once((STATE_SYNC_PROGRESS_KEY, &progress[..])))
+			.collect();
+		self.client.insert_aux(&insert, &[])
+	}
+
+	/// Remove the persisted progress of this state sync.
+	pub fn clear_persisted(&mut self) {
+		let mut keys = vec![STATE_SYNC_PROGRESS_KEY.to_vec(), STATE_SYNC_REUSED_KEY.to_vec()];
+		keys.extend((0..self.persisted_chunks).map(chunk_key));
+		let keys: Vec<_> = keys.iter().map(|key| key.as_slice()).collect();
+		if let Err(e) = self.client.insert_aux(&[], &keys) {
+			warn!(target: "sync", "Error clearing state sync progress: {}", e);
+		}
+		self.persisted_chunks = 0;
+	}
+
+	/// Load the persisted reused child tries and state chunks.
+	fn restore(&mut self, last_key: Vec<Vec<u8>>, imported_bytes: u64) -> Result<(), String> {
+		let reused = self.client.get_aux(STATE_SYNC_REUSED_KEY).map_err(|e| e.to_string())?;
+		if let Some(encoded) = reused {
+			let reused = <Vec<(Vec<u8>, Vec<(Vec<u8>, Vec<u8>)>)>>::decode(&mut encoded.as_slice())
+				.map_err(|e| e.to_string())?;
+			for (root, key_values) in reused {
+				self.reused_roots.insert(root.clone());
+				self.state.insert(root, (key_values, Vec::new()));
+			}
+		}
+		for index in 0..self.persisted_chunks {
+			let encoded = self
+				.client
+				.get_aux(&chunk_key(index))
+				.map_err(|e| e.to_string())?
+				.ok_or_else(|| format!("Missing state chunk {}", index))?;
+			let chunk = StateChunk::decode(&mut encoded.as_slice()).map_err(|e| e.to_string())?;
+			for (root, key_values, storage_keys) in chunk {
+				let entry = self.state.entry(root).or_default();
+				entry.0.extend(key_values);
+				entry.1.extend(storage_keys);
+			}
+		}
+		self.last_key = last_key.into();
+		self.imported_bytes = imported_bytes;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use futures::executor::block_on;
+	use sc_block_builder::BlockBuilderProvider;
+	use sp_blockchain::HeaderBackend;
+	use sp_consensus::BlockOrigin;
+	use sp_core::storage::ChildInfo;
+	use std::collections::BTreeMap;
+	use substrate_test_runtime_client::{
+		runtime::Block, BlockBuilderExt, ClientBlockImportExt, DefaultTestClientBuilderExt,
+		TestClient, TestClientBuilder, TestClientBuilderExt,
+	};
+
+	/// Key values and parent storage keys by trie root, independently of the download order.
+	type NormalizedState = BTreeMap<Vec<u8>, (BTreeMap<Vec<u8>, Vec<u8>>, Vec<Vec<u8>>)>;
+
+	fn normalize(state: ImportedState<Block>) -> NormalizedState {
+		state
+			.state
+			.0
+			.into_iter()
+			.map(|level| {
+				let mut storage_keys = level.parent_storage_keys;
+				storage_keys.sort();
+				(level.state_root, (level.key_values.into_iter().collect(), storage_keys))
+			})
+			.collect()
+	}
+
+	fn state_response(client: &TestClient, request: &StateRequest) -> StateResponse {
+		let hash = Decode::decode(&mut request.block.as_slice()).unwrap();
+		let (proof, _) = client.read_proof_collection(hash, &request.start, 512).unwrap();
+		StateResponse { entries: Vec::new(), proof: proof.encode() }
+	}
+
+	#[test]
+	fn resumes_from_persisted_responses() {
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let header = client.header(client.info().genesis_hash).unwrap().unwrap();
+
+		let mut sync = StateSync::<Block, _>::resume(client.clone(), header.clone(), false);
+		let response = state_response(&client, &sync.next_request());
+		assert!(matches!(sync.import_and_persist(response), ImportResult::Continue));
+
+		let mut resumed = StateSync::<Block, _>::resume(client.clone(), header.clone(), false);
+		assert_eq!(resumed.next_request(), sync.next_request());
+		assert_eq!(resumed.progress().size, sync.progress().size);
+
+		loop {
+			let response = state_response(&client, &resumed.next_request());
+			match resumed.import_and_persist(response) {
+				ImportResult::Continue => (),
+				ImportResult::Import(hash, ..) => {
+					assert_eq!(hash, header.hash());
+					break
+				},
+				ImportResult::BadResponse => panic!("Unexpected bad response"),
+			}
+		}
+
+		resumed.clear_persisted();
+		assert!(client.get_aux(STATE_SYNC_PROGRESS_KEY).unwrap().is_none());
+		assert!(client.get_aux(&chunk_key(0)).unwrap().is_none());
+	}
+
+	#[test]
+	fn restarts_when_sync_mode_changes() {
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let header = client.header(client.info().genesis_hash).unwrap().unwrap();
+
+		let mut sync = StateSync::<Block, _>::resume(client.clone(), header.clone(), false);
+		let response = state_response(&client, &sync.next_request());
+		assert!(matches!(sync.import_and_persist(response), ImportResult::Continue));
+
+		let resumed = StateSync::<Block, _>::resume(client.clone(), header, true);
+		assert!(resumed.next_request().start.is_empty());
+		assert!(client.get_aux(STATE_SYNC_PROGRESS_KEY).unwrap().is_none());
+	}
+
+	/// Download the state to the end, returning it with the number of requests that started in a
+	/// child trie.
+	fn download(
+		client: &TestClient,
+		sync: &mut StateSync<Block, TestClient>,
+	) -> (ImportedState<Block>, usize) {
+		let mut child_requests = 0;
+		loop {
+			let request = sync.next_request();
+			if request.start.len() == 2 {
+				child_requests += 1;
+			}
+			match sync.import_and_persist(state_response(client, &request)) {
+				ImportResult::Continue => (),
+				ImportResult::Import(_, _, state, ..) => return (state, child_requests),
+				ImportResult::BadResponse => panic!("Unexpected bad response"),
+			}
+		}
+	}
+
+	#[test]
+	fn retarget_skips_reused_child_tries() {
+		let child_info = ChildInfo::new_default(b"child");
+		let child_storage_key = child_info.prefixed_storage_key().into_inner();
+		let client = (0..64u8)
+			.fold(TestClientBuilder::new(), |builder, i| {
+				builder.add_extra_child_storage(&child_info, vec![i; 32], vec![i; 64])
+			})
+			.build();
+		let mut client = Arc::new(client);
+		let genesis = client.header(client.info().genesis_hash).unwrap().unwrap();
+		let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+		block_on(client.import(BlockOrigin::Own, block.clone())).unwrap();
+
+		// Download the state of the new target from scratch.
+		let mut fresh =
+			StateSync::<Block, _>::new(client.clone(), block.header.clone(), None, None, false);
+		let (fresh_state, fresh_child_requests) = download(&client, &mut fresh);
+		assert!(fresh_child_requests > 0);
+
+		// Download the genesis state past the child trie, then move to the new target.
+		let mut sync = StateSync::<Block, _>::resume(client.clone(), genesis, false);
+		while sync.next_request().start.first().map_or(true, |key| *key <= child_storage_key) {
+			let response = state_response(&client, &sync.next_request());
+			assert!(matches!(sync.import_and_persist(response), ImportResult::Continue));
+		}
+		sync.retarget(block.header.clone());
+
+		let (state, child_requests) = download(&client, &mut sync);
+		assert_eq!(child_requests, 0);
+		assert_eq!(normalize(state), normalize(fresh_state));
+		sync.clear_persisted();
+	}
+
+	#[test]
+	fn retarget_in_the_top_trie_keeps_the_position() {
+		let mut client = Arc::new(substrate_test_runtime_client::new());
+		let genesis = client.header(client.info().genesis_hash).unwrap().unwrap();
+		// The new target changes a key at the start of the top trie.
+		let changed_key = vec![0u8];
+		let mut builder = client.new_block(Default::default()).unwrap();
+		builder.push_storage_change(changed_key.clone(), Some(vec![1])).unwrap();
+		let block = builder.build().unwrap().block;
+		block_on(client.import(BlockOrigin::Own, block.clone())).unwrap();
+
+		let mut fresh =
+			StateSync::<Block, _>::new(client.clone(), block.header.clone(), None, None, false);
+		let (fresh_state, _) = download(&client, &mut fresh);
+
+		// Download a part of the genesis top trie, then move to the new target.
+		let mut sync = StateSync::<Block, _>::resume(client.clone(), genesis, false);
+		for _ in 0..2 {
+			let response = state_response(&client, &sync.next_request());
+			assert!(matches!(sync.import_and_persist(response), ImportResult::Continue));
+		}
+		let cursor = sync.next_request().start;
+		assert_eq!(cursor.len(), 1);
+		assert!(cursor[0] > changed_key);
+		sync.retarget(block.header.clone());
+		assert_eq!(sync.next_request().start, cursor);
+
+		// The download goes on from the same key, then the keys before it are downloaded again.
+		let response = state_response(&client, &sync.next_request());
+		assert!(matches!(sync.import_and_persist(response), ImportResult::Continue));
+		let mut resumed =
+			StateSync::<Block, _>::resume(client.clone(), block.header.clone(), false);
+		assert_eq!(resumed.next_request(), sync.next_request());
+
+		let (state, _) = download(&client, &mut resumed);
+		let state = normalize(state);
+		assert_eq!(state[&Vec::new()].0.get(&changed_key), Some(&vec![1]));
+		assert_eq!(state, normalize(fresh_state));
+		resumed.clear_persisted();
 	}
 }
@@ -634,6 +634,68 @@ async fn fallback_name_working() {
 	receiver.await.unwrap();
 }
 
+#[tokio::test]
+async fn nodes_connect_over_quic() {
+	// Node 2 only knows the QUIC address of node 1, so the notification substreams can only be
+	// opened over QUIC.
+	let port = std::net::UdpSocket::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+	let listen_addr = config::build_multiaddr![Ip4([127, 0, 0, 1]), Udp(port), QuicV1];
+
+	let (node1, mut events_stream1) = TestNetworkBuilder::new()
+		.with_config(config::NetworkConfiguration {
+			listen_addresses: vec![listen_addr.clone()],
+			transport: TransportConfig::Normal { enable_mdns: false, allow_private_ip: true },
+			enable_quic: true,
+			..config::NetworkConfiguration::new_local()
+		})
+		.build()
+		.start_network();
+
+	let (node2, mut events_stream2) = TestNetworkBuilder::new()
+		.with_set_config(config::SetConfig {
+			reserved_nodes: vec![MultiaddrWithPeerId {
+				multiaddr: listen_addr,
+				peer_id: node1.local_peer_id(),
+			}],
+			..Default::default()
+		})
+		.with_config(config::NetworkConfiguration {
+			listen_addresses: Vec::new(),
+			transport: TransportConfig::Normal { enable_mdns: false, allow_private_ip: true },
+			enable_quic: true,
+			..config::NetworkConfiguration::new_local()
+		})
+		.build()
+		.start_network();
+
+	let (node1_id, node2_id) = (node1.local_peer_id(), node2.local_peer_id());
+	let receiver = tokio::spawn(async move {
+		loop {
+			match events_stream2.next().await.unwrap() {
+				Event::NotificationStreamOpened { remote, protocol, .. } => {
+					assert_eq!(remote, node1_id);
+					assert_eq!(protocol, PROTOCOL_NAME.into());
+					break
+				},
+				_ => {},
+			};
+		}
+	});
+
+	loop {
+		match events_stream1.next().await.unwrap() {
+			Event::NotificationStreamOpened { remote, protocol, .. } => {
+				assert_eq!(remote, node2_id);
+				assert_eq!(protocol, PROTOCOL_NAME.into());
+				break
+			},
+			_ => {},
+		};
+	}
+
+	receiver.await.unwrap();
+}
+
 #[tokio::test]
 #[should_panic(expected = "don't match the transport")]
 async fn ensure_listen_addresses_consistent_with_transport_memory() {
@@ -0,0 +1,64 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Bitswap RPC errors.
+
+use jsonrpsee::{
+	core::Error as JsonRpseeError,
+	types::error::{CallError, ErrorObject},
+};
+
+/// Bitswap RPC Result type.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Bitswap RPC errors.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	/// The CID couldn't be parsed.
+	#[error("Invalid CID: {0}")]
+	InvalidCid(String),
+	/// Bitswap client error.
+	#[error("Bitswap error: {0}")]
+	Bitswap(String),
+	/// Call to an unsafe RPC was denied.
+	#[error(transparent)]
+	UnsafeRpcCalled(#[from] crate::policy::UnsafeRpcError),
+}
+
+/// Base error code for all bitswap errors.
+const BASE_ERROR: i32 = crate::error::base::BITSWAP;
+
+impl From<Error> for JsonRpseeError {
+	fn from(e: Error) -> Self {
+		match e {
+			Error::InvalidCid(message) => CallError::Custom(ErrorObject::owned(
+				BASE_ERROR + 1,
+				format!("Invalid CID: {message}"),
+				None::<()>,
+			))
+			.into(),
+			Error::Bitswap(message) => CallError::Custom(ErrorObject::owned(
+				BASE_ERROR + 2,
+				format!("Bitswap error: {message}"),
+				None::<()>,
+			))
+			.into(),
+			Error::UnsafeRpcCalled(e) => e.into(),
+		}
+	}
+}
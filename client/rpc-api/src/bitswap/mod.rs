@@ -0,0 +1,48 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Substrate bitswap RPC API.
+
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use sp_core::Bytes;
+
+pub mod error;
+
+/// Substrate bitswap RPC API
+#[rpc(client, server)]
+pub trait BitswapApi {
+	/// Fetch the content referenced by `cid`, either from the local node or from the network.
+	#[method(name = "bitswap_fetch")]
+	async fn fetch(&self, cid: String) -> RpcResult<Bytes>;
+
+	/// Retain the content referenced by `cid`, serve it over bitswap and announce it in the DHT.
+	///
+	/// If `content` isn't given, it is fetched the same way as with `bitswap_fetch`.
+	#[method(name = "bitswap_retain")]
+	async fn retain(&self, cid: String, content: Option<Bytes>) -> RpcResult<()>;
+
+	/// Stop retaining the content referenced by `cid`.
+	///
+	/// Returns `false` if the content wasn't retained.
+	#[method(name = "bitswap_release")]
+	fn release(&self, cid: String) -> RpcResult<bool>;
+
+	/// Return the CIDs of all retained content.
+	#[method(name = "bitswap_retained")]
+	fn retained(&self) -> RpcResult<Vec<String>>;
+}
@@ -32,6 +32,17 @@ pub enum Error {
 	/// Unavailable storage kind error.
 	#[error("This storage kind is not available yet.")]
 	UnavailableStorageKind,
+	/// The offchain storage can't enumerate the keys written by offchain indexing.
+	#[error("Iterating over offchain indexed keys is not supported by this node's database.")]
+	IndexedKeysUnavailable,
+	/// Provided count exceeds maximum value.
+	#[error("count exceeds maximum value. value: {}, max: {}", .value, .max)]
+	InvalidCount {
+		/// Provided value
+		value: u32,
+		/// Maximum allowed value
+		max: u32,
+	},
 	/// Call to an unsafe RPC was denied.
 	#[error(transparent)]
 	UnsafeRpcCalled(#[from] crate::policy::UnsafeRpcError),
@@ -49,6 +60,15 @@ impl From<Error> for JsonRpseeError {
 				None::<()>,
 			))
 			.into(),
+			Error::IndexedKeysUnavailable => CallError::Custom(ErrorObject::owned(
+				BASE_ERROR + 2,
+				"Iterating over offchain indexed keys is not supported by this node's database",
+				None::<()>,
+			))
+			.into(),
+			Error::InvalidCount { .. } =>
+				CallError::Custom(ErrorObject::owned(BASE_ERROR + 3, e.to_string(), None::<()>))
+					.into(),
 			Error::UnsafeRpcCalled(e) => e.into(),
 		}
 	}
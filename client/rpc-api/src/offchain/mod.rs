@@ -33,4 +33,18 @@ pub trait OffchainApi {
 	/// Get offchain local storage under given key and prefix.
 	#[method(name = "offchain_localStorageGet")]
 	fn get_local_storage(&self, kind: StorageKind, key: Bytes) -> RpcResult<Option<Bytes>>;
+
+	/// Returns the keys written by offchain indexing that start with the given prefix, with
+	/// pagination support.
+	///
+	/// Up to `count` keys will be returned. If `start_key` is passed, return next keys in
+	/// storage in lexicographic order.
+	#[method(name = "offchain_localStorageIndexedKeysPaged")]
+	fn get_local_storage_indexed_keys_paged(
+		&self,
+		kind: StorageKind,
+		prefix: Option<Bytes>,
+		count: u32,
+		start_key: Option<Bytes>,
+	) -> RpcResult<Vec<Bytes>>;
 }
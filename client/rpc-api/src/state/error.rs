@@ -52,6 +52,9 @@ pub enum Error {
 	/// Call to an unsafe RPC was denied.
 	#[error(transparent)]
 	UnsafeRpcCalled(#[from] crate::policy::UnsafeRpcError),
+	/// The arguments or the result of a dynamic runtime call couldn't be converted.
+	#[error("Dynamic runtime call failed: {}", .0)]
+	DynamicCall(String),
 }
 
 /// Base code for all state errors.
@@ -66,6 +69,9 @@ impl From<Error> for JsonRpseeError {
 			Error::InvalidCount { .. } =>
 				CallError::Custom(ErrorObject::owned(BASE_ERROR + 2, e.to_string(), None::<()>))
 					.into(),
+			Error::DynamicCall(_) =>
+				CallError::Custom(ErrorObject::owned(BASE_ERROR + 3, e.to_string(), None::<()>))
+					.into(),
 			e => Self::to_call_error(e),
 		}
 	}
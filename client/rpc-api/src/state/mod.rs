@@ -37,6 +37,19 @@ pub trait StateApi<Hash> {
 	#[method(name = "state_call", aliases = ["state_callAt"], blocking)]
 	fn call(&self, name: String, bytes: Bytes, hash: Option<Hash>) -> RpcResult<Bytes>;
 
+	/// Call a method from the runtime API at a block's state, using JSON arguments.
+	///
+	/// `name` is the name of the method prefixed by the name of its runtime API, e.g.
+	/// `Core_version`. The arguments are SCALE encoded and the result is decoded using the types
+	/// of the runtime metadata `V15`, so the runtime needs to support this metadata version.
+	#[method(name = "state_callDynamic", blocking)]
+	fn call_dynamic(
+		&self,
+		name: String,
+		args: Vec<serde_json::Value>,
+		hash: Option<Hash>,
+	) -> RpcResult<serde_json::Value>;
+
 	/// Returns the keys with prefix, leave empty to get all the keys.
 	#[method(name = "state_getKeys", blocking)]
 	#[deprecated(since = "2.0.0", note = "Please use `getKeysPaged` with proper paging support")]
@@ -19,10 +19,21 @@
 //! Substrate Statement Store RPC API.
 
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use serde::{Deserialize, Serialize};
 use sp_core::Bytes;
 
 pub mod error;
 
+/// Topic filter of a statement subscription.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TopicFilter {
+	/// Match statements that include all of the given topics.
+	MatchAll(Vec<[u8; 32]>),
+	/// Match statements that include at least one of the given topics.
+	MatchAny(Vec<[u8; 32]>),
+}
+
 /// Substrate statement RPC API
 #[rpc(client, server)]
 pub trait StatementApi {
@@ -57,4 +68,18 @@ pub trait StatementApi {
 	/// Remove a statement from the store.
 	#[method(name = "statement_remove")]
 	fn remove(&self, statement_hash: [u8; 32]) -> RpcResult<()>;
+
+	/// Subscribe to newly accepted statements matching `topic_filter`, SCALE-encoded.
+	///
+	/// If `dest` is given, only statements whose decryption key is identified as `dest` are sent.
+	/// Otherwise only statements with no `DecryptionKey` field are sent.
+	///
+	/// The number of subscriptions is limited by the store and by the maximum number of
+	/// subscriptions per connection of the RPC server.
+	#[subscription(
+		name = "statement_subscribe" => "statement_statement",
+		unsubscribe = "statement_unsubscribe",
+		item = Bytes,
+	)]
+	fn subscribe_statement(&self, topic_filter: TopicFilter, dest: Option<[u8; 32]>);
 }
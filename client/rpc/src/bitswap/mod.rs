@@ -0,0 +1,86 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Substrate bitswap API.
+
+#[cfg(test)]
+mod tests;
+
+use jsonrpsee::core::{async_trait, RpcResult};
+use sc_network_bitswap::{BitswapClient, Cid};
+/// Re-export the API.
+pub use sc_rpc_api::bitswap::{error::Error, BitswapApiServer};
+use sc_rpc_api::DenyUnsafe;
+use sp_core::Bytes;
+use sp_runtime::traits::Block as BlockT;
+
+/// Bitswap API
+pub struct Bitswap<Block: BlockT> {
+	client: BitswapClient<Block>,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl<Block: BlockT> Bitswap<Block> {
+	/// Create new instance of Bitswap API.
+	pub fn new(client: BitswapClient<Block>, deny_unsafe: DenyUnsafe) -> Self {
+		Bitswap { client, deny_unsafe }
+	}
+}
+
+fn parse_cid(cid: &str) -> Result<Cid, Error> {
+	cid.parse().map_err(|e| Error::InvalidCid(format!("{e}")))
+}
+
+#[async_trait]
+impl<Block: BlockT> BitswapApiServer for Bitswap<Block> {
+	async fn fetch(&self, cid: String) -> RpcResult<Bytes> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let cid = parse_cid(&cid)?;
+		let content = self.client.fetch(&cid).await.map_err(|e| Error::Bitswap(e.to_string()))?;
+		Ok(content.into())
+	}
+
+	async fn retain(&self, cid: String, content: Option<Bytes>) -> RpcResult<()> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let cid = parse_cid(&cid)?;
+		let content = match content {
+			Some(content) => content.0,
+			None => self.client.fetch(&cid).await.map_err(|e| Error::Bitswap(e.to_string()))?,
+		};
+		Ok(self.client.retain(&cid, &content).map_err(|e| Error::Bitswap(e.to_string()))?)
+	}
+
+	fn release(&self, cid: String) -> RpcResult<bool> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let cid = parse_cid(&cid)?;
+		Ok(self.client.release(&cid).map_err(|e| Error::Bitswap(e.to_string()))?)
+	}
+
+	fn retained(&self) -> RpcResult<Vec<String>> {
+		Ok(self
+			.client
+			.retained()
+			.map_err(|e| Error::Bitswap(e.to_string()))?
+			.into_iter()
+			.map(|cid| cid.to_string())
+			.collect())
+	}
+}
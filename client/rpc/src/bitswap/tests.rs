@@ -0,0 +1,154 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+use assert_matches::assert_matches;
+use futures::{channel::oneshot, stream, Stream};
+use jsonrpsee::{
+	core::Error as RpcError,
+	types::{error::CallError, EmptyServerParams as EmptyParams},
+	RpcModule,
+};
+use sc_network::{
+	Event, IfDisconnected, KademliaKey, NetworkDHTProvider, NetworkEventStream, NetworkRequest,
+	PeerId, ProtocolName, RequestFailure,
+};
+use std::{pin::Pin, sync::Arc};
+use substrate_test_runtime_client::runtime::Block;
+
+/// CIDv1 of `CONTENT`, with the raw codec and a SHA2-256 multihash.
+const CID: &str = "bafkreiblndvorysey6b6yhiavvsodinkqgzwqq6xdptv6a4c6xmybycd6e";
+const CONTENT: &[u8] = b"bitswap rpc";
+
+/// Network without any peers.
+struct TestNetwork;
+
+#[async_trait]
+impl NetworkRequest for TestNetwork {
+	async fn request(
+		&self,
+		_: PeerId,
+		_: ProtocolName,
+		_: Vec<u8>,
+		_: IfDisconnected,
+	) -> Result<Vec<u8>, RequestFailure> {
+		Err(RequestFailure::NotConnected)
+	}
+
+	fn start_request(
+		&self,
+		_: PeerId,
+		_: ProtocolName,
+		_: Vec<u8>,
+		tx: oneshot::Sender<Result<Vec<u8>, RequestFailure>>,
+		_: IfDisconnected,
+	) {
+		let _ = tx.send(Err(RequestFailure::NotConnected));
+	}
+}
+
+impl NetworkDHTProvider for TestNetwork {
+	fn get_value(&self, _: &KademliaKey) {}
+	fn put_value(&self, _: KademliaKey, _: Vec<u8>) {}
+	fn start_providing(&self, _: KademliaKey) {}
+	fn stop_providing(&self, _: KademliaKey) {}
+	fn get_providers(&self, _: &KademliaKey) {}
+}
+
+impl NetworkEventStream for TestNetwork {
+	fn event_stream(&self, _: &'static str) -> Pin<Box<dyn Stream<Item = Event> + Send>> {
+		Box::pin(stream::pending())
+	}
+}
+
+fn bitswap(deny_unsafe: DenyUnsafe) -> Bitswap<Block> {
+	let backend = Arc::new(substrate_test_runtime_client::new());
+	let (client, worker) = BitswapClient::new(Arc::new(TestNetwork), backend);
+	tokio::spawn(worker.run());
+	Bitswap::new(client, deny_unsafe)
+}
+
+fn api() -> RpcModule<Bitswap<Block>> {
+	bitswap(DenyUnsafe::No).into_rpc()
+}
+
+#[tokio::test]
+async fn retain_fetch_and_release() {
+	let api = api();
+
+	api.call::<_, ()>("bitswap_retain", (CID, Bytes(CONTENT.to_vec())))
+		.await
+		.unwrap();
+	assert_eq!(
+		api.call::<_, Vec<String>>("bitswap_retained", EmptyParams::new())
+			.await
+			.unwrap(),
+		vec![CID.to_string()],
+	);
+	assert_eq!(
+		api.call::<_, Bytes>("bitswap_fetch", [CID]).await.unwrap(),
+		Bytes(CONTENT.to_vec()),
+	);
+
+	assert!(api.call::<_, bool>("bitswap_release", [CID]).await.unwrap());
+	assert!(!api.call::<_, bool>("bitswap_release", [CID]).await.unwrap());
+	assert!(api
+		.call::<_, Vec<String>>("bitswap_retained", EmptyParams::new())
+		.await
+		.unwrap()
+		.is_empty());
+}
+
+#[tokio::test]
+async fn invalid_requests_are_rejected() {
+	let api = api();
+
+	assert_matches!(
+		api.call::<_, Bytes>("bitswap_fetch", ["not a cid"]).await,
+		Err(RpcError::Call(CallError::Custom(err))) if err.message().starts_with("Invalid CID")
+	);
+	assert_matches!(
+		api.call::<_, ()>("bitswap_retain", (CID, Bytes(b"other content".to_vec()))).await,
+		Err(RpcError::Call(CallError::Custom(err))) if err.message().contains("doesn't match")
+	);
+}
+
+#[tokio::test]
+async fn bitswap_calls_considered_unsafe() {
+	let bitswap = bitswap(DenyUnsafe::Yes);
+
+	assert_matches!(
+		bitswap.fetch(CID.into()).await,
+		Err(RpcError::Call(CallError::Custom(err))) => {
+			assert_eq!(err.message(), "RPC call is unsafe to be called externally")
+		}
+	);
+	assert_matches!(
+		bitswap.retain(CID.into(), Some(Bytes(CONTENT.to_vec()))).await,
+		Err(RpcError::Call(CallError::Custom(err))) => {
+			assert_eq!(err.message(), "RPC call is unsafe to be called externally")
+		}
+	);
+	assert_matches!(
+		bitswap.release(CID.into()),
+		Err(RpcError::Call(CallError::Custom(err))) => {
+			assert_eq!(err.message(), "RPC call is unsafe to be called externally")
+		}
+	);
+	assert_matches!(bitswap.retained(), Ok(cids) if cids.is_empty());
+}
@@ -33,6 +33,9 @@ use sp_core::{
 };
 use std::sync::Arc;
 
+/// Maximum number of keys returned by `offchain_localStorageIndexedKeysPaged`.
+const INDEXED_KEYS_PAGED_MAX_COUNT: u32 = 1000;
+
 /// Offchain API
 #[derive(Debug)]
 pub struct Offchain<T: OffchainStorage> {
@@ -71,4 +74,38 @@ impl<T: OffchainStorage + 'static> OffchainApiServer for Offchain<T> {
 
 		Ok(self.storage.read().get(prefix, &key).map(Into::into))
 	}
+
+	fn get_local_storage_indexed_keys_paged(
+		&self,
+		kind: StorageKind,
+		prefix: Option<Bytes>,
+		count: u32,
+		start_key: Option<Bytes>,
+	) -> RpcResult<Vec<Bytes>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let storage_prefix = match kind {
+			StorageKind::PERSISTENT => sp_offchain::STORAGE_PREFIX,
+			StorageKind::LOCAL => return Err(JsonRpseeError::from(Error::UnavailableStorageKind)),
+		};
+		if count > INDEXED_KEYS_PAGED_MAX_COUNT {
+			return Err(JsonRpseeError::from(Error::InvalidCount {
+				value: count,
+				max: INDEXED_KEYS_PAGED_MAX_COUNT,
+			}))
+		}
+
+		let keys = self
+			.storage
+			.read()
+			.indexed_keys(
+				storage_prefix,
+				prefix.as_deref().unwrap_or_default(),
+				start_key.as_deref(),
+				count as usize,
+			)
+			.ok_or_else(|| JsonRpseeError::from(Error::IndexedKeysUnavailable))?;
+
+		Ok(keys.into_iter().map(Into::into).collect())
+	}
 }
@@ -57,4 +57,111 @@ fn offchain_calls_considered_unsafe() {
 			assert_eq!(err.message(), "RPC call is unsafe to be called externally")
 		}
 	);
+	assert_matches!(
+		offchain.get_local_storage_indexed_keys_paged(StorageKind::PERSISTENT, None, 10, None),
+		Err(JsonRpseeError::Call(CallError::Custom(err))) => {
+			assert_eq!(err.message(), "RPC call is unsafe to be called externally")
+		}
+	);
+}
+
+/// Offchain storage that considers every key as written by offchain indexing.
+#[derive(Clone, Default)]
+struct IndexedOffchainStorage(InMemOffchainStorage);
+
+impl OffchainStorage for IndexedOffchainStorage {
+	fn set(&mut self, prefix: &[u8], key: &[u8], value: &[u8]) {
+		self.0.set(prefix, key, value)
+	}
+
+	fn remove(&mut self, prefix: &[u8], key: &[u8]) {
+		OffchainStorage::remove(&mut self.0, prefix, key)
+	}
+
+	fn get(&self, prefix: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+		self.0.get(prefix, key)
+	}
+
+	fn compare_and_set(
+		&mut self,
+		prefix: &[u8],
+		key: &[u8],
+		old_value: Option<&[u8]>,
+		new_value: &[u8],
+	) -> bool {
+		self.0.compare_and_set(prefix, key, old_value, new_value)
+	}
+
+	fn indexed_keys(
+		&self,
+		prefix: &[u8],
+		key_prefix: &[u8],
+		start_key: Option<&[u8]>,
+		count: usize,
+	) -> Option<Vec<Vec<u8>>> {
+		let mut keys = self
+			.0
+			.iter()
+			.filter_map(|(key, _)| key.strip_prefix(prefix))
+			.filter(|key| key.starts_with(key_prefix))
+			.filter(|key| start_key.map_or(true, |start_key| *key > start_key))
+			.map(|key| key.to_vec())
+			.collect::<Vec<_>>();
+		keys.sort();
+		keys.truncate(count);
+		Some(keys)
+	}
+}
+
+#[test]
+fn indexed_keys_paged_should_work() {
+	let offchain = Offchain::new(IndexedOffchainStorage::default(), DenyUnsafe::No);
+	for key in [&b"key3"[..], b"key1", b"other", b"key2"] {
+		offchain
+			.set_local_storage(StorageKind::PERSISTENT, Bytes(key.to_vec()), Bytes(b"v".to_vec()))
+			.unwrap();
+	}
+	let keys = |count, start_key: Option<&[u8]>| {
+		offchain
+			.get_local_storage_indexed_keys_paged(
+				StorageKind::PERSISTENT,
+				Some(Bytes(b"key".to_vec())),
+				count,
+				start_key.map(|key| Bytes(key.to_vec())),
+			)
+			.unwrap()
+	};
+
+	assert_eq!(
+		keys(10, None),
+		vec![Bytes(b"key1".to_vec()), Bytes(b"key2".to_vec()), Bytes(b"key3".to_vec())]
+	);
+	assert_eq!(keys(2, None), vec![Bytes(b"key1".to_vec()), Bytes(b"key2".to_vec())]);
+	assert_eq!(keys(2, Some(b"key2")), vec![Bytes(b"key3".to_vec())]);
+	assert_matches!(
+		offchain.get_local_storage_indexed_keys_paged(StorageKind::PERSISTENT, None, 10, None),
+		Ok(ref keys) if keys.len() == 4
+	);
+}
+
+#[test]
+fn indexed_keys_paged_should_fail_if_unavailable() {
+	use jsonrpsee::types::error::CallError;
+	let offchain = Offchain::new(InMemOffchainStorage::default(), DenyUnsafe::No);
+
+	assert_matches!(
+		offchain.get_local_storage_indexed_keys_paged(StorageKind::PERSISTENT, None, 10, None),
+		Err(JsonRpseeError::Call(CallError::Custom(err))) => {
+			assert_eq!(
+				err.message(),
+				"Iterating over offchain indexed keys is not supported by this node's database"
+			)
+		}
+	);
+	assert_matches!(
+		offchain.get_local_storage_indexed_keys_paged(StorageKind::PERSISTENT, None, 1001, None),
+		Err(JsonRpseeError::Call(CallError::Custom(err))) => {
+			assert_eq!(err.message(), "count exceeds maximum value. value: 1001, max: 1000")
+		}
+	);
 }
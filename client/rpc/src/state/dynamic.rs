@@ -0,0 +1,430 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Dynamic runtime API calls.
+//!
+//! The arguments of a runtime API call are converted from JSON to SCALE and the result is
+//! converted from SCALE to JSON, using the types described by the runtime metadata `V15`.
+//!
+//! The JSON representation of the SCALE types is:
+//!
+//! - Booleans, strings and chars map to their JSON counterpart.
+//! - Integers up to 64 bits map to JSON numbers. 128 bit integers are accepted as numbers or
+//!   decimal strings and are returned as decimal strings if they don't fit into 64 bits. 256 bit
+//!   integers are `0x` prefixed hex strings of their SCALE encoding.
+//! - Sequences and arrays of `u8` map to `0x` prefixed hex strings. Other sequences, arrays and
+//!   tuples map to JSON arrays, with the exception of the empty tuple that maps to `null`.
+//! - Structs with named fields map to JSON objects, structs with a single unnamed field map to the
+//!   value of this field and structs with multiple unnamed fields map to JSON arrays.
+//! - Enum variants without fields map to their name as a string. Other variants map to a JSON
+//!   object with the variant name as single key and the fields, represented like the fields of a
+//!   struct, as value.
+
+use codec::{Compact, Decode, Encode};
+use frame_metadata::v15::{RuntimeApiMethodMetadata, RuntimeMetadataV15};
+use scale_info::{form::PortableForm, Field, PortableRegistry, TypeDef, TypeDefPrimitive, Variant};
+use serde_json::{Map, Value};
+
+/// The result of the dynamic conversions.
+pub(crate) type Result<T> = std::result::Result<T, String>;
+
+/// Returns the runtime API method `name`, given as `<Api>_<method>`.
+pub(crate) fn runtime_api_method<'a>(
+	metadata: &'a RuntimeMetadataV15,
+	name: &str,
+) -> Result<&'a RuntimeApiMethodMetadata<PortableForm>> {
+	metadata
+		.apis
+		.iter()
+		.flat_map(|api| {
+			api.methods
+				.iter()
+				.map(move |method| (format!("{}_{}", api.name, method.name), method))
+		})
+		.find_map(|(full_name, method)| (full_name == name).then_some(method))
+		.ok_or_else(|| format!("Runtime API method `{}` not found in the metadata", name))
+}
+
+/// Encode the JSON `args` as the inputs of `method`.
+pub(crate) fn encode_args(
+	registry: &PortableRegistry,
+	method: &RuntimeApiMethodMetadata<PortableForm>,
+	args: &[Value],
+) -> Result<Vec<u8>> {
+	if args.len() != method.inputs.len() {
+		return Err(format!(
+			"`{}` expects {} arguments, got {}",
+			method.name,
+			method.inputs.len(),
+			args.len()
+		))
+	}
+
+	let mut out = Vec::new();
+	for (input, arg) in method.inputs.iter().zip(args) {
+		encode_value(registry, input.ty.id, arg, &mut out)
+			.map_err(|e| format!("Invalid argument `{}`: {}", input.name, e))?;
+	}
+
+	Ok(out)
+}
+
+/// Decode the `output` of `method` as JSON.
+pub(crate) fn decode_output(
+	registry: &PortableRegistry,
+	method: &RuntimeApiMethodMetadata<PortableForm>,
+	mut output: &[u8],
+) -> Result<Value> {
+	let value = decode_value(registry, method.output.id, &mut output)?;
+	if !output.is_empty() {
+		return Err(format!("`{}` returned {} unexpected trailing bytes", method.name, output.len()))
+	}
+
+	Ok(value)
+}
+
+/// Returns `true` if the type `id` is `u8`.
+fn is_u8(registry: &PortableRegistry, id: u32) -> bool {
+	matches!(
+		registry.resolve(id).map(|ty| &ty.type_def),
+		Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+	)
+}
+
+fn resolve(registry: &PortableRegistry, id: u32) -> Result<&TypeDef<PortableForm>> {
+	registry
+		.resolve(id)
+		.map(|ty| &ty.type_def)
+		.ok_or_else(|| format!("Type `{}` not found in the metadata", id))
+}
+
+fn hex_to_bytes(value: &Value) -> Result<Vec<u8>> {
+	let hex = value
+		.as_str()
+		.ok_or_else(|| format!("Expected a hex string, got `{}`", value))?;
+	sp_core::bytes::from_hex(hex).map_err(|e| format!("Invalid hex string `{}`: {}", hex, e))
+}
+
+fn bytes_to_hex(bytes: &[u8]) -> Value {
+	Value::String(sp_core::bytes::to_hex(bytes, false))
+}
+
+/// Encode a sequence of `u8` given as hex string or as array of numbers.
+fn encode_bytes(value: &Value) -> Result<Vec<u8>> {
+	match value {
+		Value::Array(items) => items
+			.iter()
+			.map(|item| {
+				item.as_u64()
+					.and_then(|byte| u8::try_from(byte).ok())
+					.ok_or_else(|| format!("Expected a byte, got `{}`", item))
+			})
+			.collect(),
+		value => hex_to_bytes(value),
+	}
+}
+
+fn as_u128(value: &Value) -> Result<u128> {
+	match value {
+		Value::Number(number) => number.as_u64().map(Into::into),
+		Value::String(string) => string.parse().ok(),
+		_ => None,
+	}
+	.ok_or_else(|| format!("Expected an unsigned integer, got `{}`", value))
+}
+
+fn as_i128(value: &Value) -> Result<i128> {
+	match value {
+		Value::Number(number) => number.as_i64().map(Into::into),
+		Value::String(string) => string.parse().ok(),
+		_ => None,
+	}
+	.ok_or_else(|| format!("Expected an integer, got `{}`", value))
+}
+
+fn encode_primitive(primitive: &TypeDefPrimitive, value: &Value, out: &mut Vec<u8>) -> Result<()> {
+	fn narrow<T: TryFrom<N>, N: Copy + std::fmt::Display>(value: N) -> Result<T> {
+		T::try_from(value).map_err(|_| format!("Integer `{}` out of range", value))
+	}
+
+	match primitive {
+		TypeDefPrimitive::Bool => value
+			.as_bool()
+			.ok_or_else(|| format!("Expected a boolean, got `{}`", value))?
+			.encode_to(out),
+		TypeDefPrimitive::Char => {
+			let mut chars = value.as_str().map(str::chars).into_iter().flatten();
+			match (chars.next(), chars.next()) {
+				(Some(c), None) => (c as u32).encode_to(out),
+				_ => return Err(format!("Expected a single char, got `{}`", value)),
+			}
+		},
+		TypeDefPrimitive::Str => value
+			.as_str()
+			.ok_or_else(|| format!("Expected a string, got `{}`", value))?
+			.encode_to(out),
+		TypeDefPrimitive::U8 => narrow::<u8, _>(as_u128(value)?)?.encode_to(out),
+		TypeDefPrimitive::U16 => narrow::<u16, _>(as_u128(value)?)?.encode_to(out),
+		TypeDefPrimitive::U32 => narrow::<u32, _>(as_u128(value)?)?.encode_to(out),
+		TypeDefPrimitive::U64 => narrow::<u64, _>(as_u128(value)?)?.encode_to(out),
+		TypeDefPrimitive::U128 => as_u128(value)?.encode_to(out),
+		TypeDefPrimitive::I8 => narrow::<i8, _>(as_i128(value)?)?.encode_to(out),
+		TypeDefPrimitive::I16 => narrow::<i16, _>(as_i128(value)?)?.encode_to(out),
+		TypeDefPrimitive::I32 => narrow::<i32, _>(as_i128(value)?)?.encode_to(out),
+		TypeDefPrimitive::I64 => narrow::<i64, _>(as_i128(value)?)?.encode_to(out),
+		TypeDefPrimitive::I128 => as_i128(value)?.encode_to(out),
+		TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => {
+			let bytes = hex_to_bytes(value)?;
+			if bytes.len() != 32 {
+				return Err(format!("Expected 32 bytes, got {}", bytes.len()))
+			}
+			out.extend(bytes);
+		},
+	}
+
+	Ok(())
+}
+
+fn encode_fields(
+	registry: &PortableRegistry,
+	fields: &[Field<PortableForm>],
+	value: &Value,
+	out: &mut Vec<u8>,
+) -> Result<()> {
+	match (fields, value) {
+		([], Value::Null) => Ok(()),
+		([field], value) if field.name.is_none() => encode_value(registry, field.ty.id, value, out),
+		(fields, Value::Object(object)) if fields.iter().all(|field| field.name.is_some()) => {
+			if object.len() != fields.len() {
+				return Err(format!("Expected {} fields, got {}", fields.len(), object.len()))
+			}
+
+			for field in fields {
+				let name = field.name.as_deref().unwrap_or_default();
+				let value = object.get(name).ok_or_else(|| format!("Missing field `{}`", name))?;
+				encode_value(registry, field.ty.id, value, out)?;
+			}
+
+			Ok(())
+		},
+		(fields, Value::Array(items)) if items.len() == fields.len() => fields
+			.iter()
+			.zip(items)
+			.try_for_each(|(field, item)| encode_value(registry, field.ty.id, item, out)),
+		(_, value) => Err(format!("Unexpected value `{}` for the fields", value)),
+	}
+}
+
+fn encode_variant(
+	registry: &PortableRegistry,
+	variants: &[Variant<PortableForm>],
+	value: &Value,
+	out: &mut Vec<u8>,
+) -> Result<()> {
+	static NO_FIELDS: Value = Value::Null;
+
+	let (name, fields) = match value {
+		Value::String(name) => (name, &NO_FIELDS),
+		Value::Object(object) if object.len() == 1 =>
+			object.iter().next().expect("The object has one entry; qed"),
+		value => return Err(format!("Expected a variant, got `{}`", value)),
+	};
+
+	let variant = variants
+		.iter()
+		.find(|variant| &variant.name == name)
+		.ok_or_else(|| format!("Unknown variant `{}`", name))?;
+
+	out.push(variant.index);
+	encode_fields(registry, &variant.fields, fields, out)
+}
+
+/// Encode the JSON `value` as the type `id`.
+pub(crate) fn encode_value(
+	registry: &PortableRegistry,
+	id: u32,
+	value: &Value,
+	out: &mut Vec<u8>,
+) -> Result<()> {
+	match resolve(registry, id)? {
+		TypeDef::Composite(composite) => encode_fields(registry, &composite.fields, value, out),
+		TypeDef::Variant(variant) => encode_variant(registry, &variant.variants, value, out),
+		TypeDef::Sequence(sequence) if is_u8(registry, sequence.type_param.id) => {
+			encode_bytes(value)?.encode_to(out);
+			Ok(())
+		},
+		TypeDef::Sequence(sequence) => {
+			let items =
+				value.as_array().ok_or_else(|| format!("Expected an array, got `{}`", value))?;
+			Compact(items.len() as u32).encode_to(out);
+			items
+				.iter()
+				.try_for_each(|item| encode_value(registry, sequence.type_param.id, item, out))
+		},
+		TypeDef::Array(array) if is_u8(registry, array.type_param.id) => {
+			let bytes = encode_bytes(value)?;
+			if bytes.len() != array.len as usize {
+				return Err(format!("Expected {} bytes, got {}", array.len, bytes.len()))
+			}
+			out.extend(bytes);
+			Ok(())
+		},
+		TypeDef::Array(array) => {
+			let items =
+				value.as_array().filter(|items| items.len() == array.len as usize).ok_or_else(
+					|| format!("Expected an array of {} items, got `{}`", array.len, value),
+				)?;
+			items
+				.iter()
+				.try_for_each(|item| encode_value(registry, array.type_param.id, item, out))
+		},
+		TypeDef::Tuple(tuple) => match (tuple.fields.as_slice(), value) {
+			([], Value::Null) => Ok(()),
+			(fields, Value::Array(items)) if items.len() == fields.len() => fields
+				.iter()
+				.zip(items)
+				.try_for_each(|(field, item)| encode_value(registry, field.id, item, out)),
+			(_, value) => Err(format!("Unexpected value `{}` for a tuple", value)),
+		},
+		TypeDef::Primitive(primitive) => encode_primitive(primitive, value, out),
+		TypeDef::Compact(_) => {
+			Compact(as_u128(value)?).encode_to(out);
+			Ok(())
+		},
+		TypeDef::BitSequence(_) => Err("Bit sequences are not supported".into()),
+	}
+}
+
+fn decode_primitive(primitive: &TypeDefPrimitive, input: &mut &[u8]) -> Result<Value> {
+	fn decode<T: Decode>(input: &mut &[u8]) -> Result<T> {
+		T::decode(input).map_err(|e| e.to_string())
+	}
+
+	Ok(match primitive {
+		TypeDefPrimitive::Bool => Value::Bool(decode(input)?),
+		TypeDefPrimitive::Char => {
+			let c = char::from_u32(decode(input)?).ok_or("Invalid char")?;
+			Value::String(c.to_string())
+		},
+		TypeDefPrimitive::Str => Value::String(decode(input)?),
+		TypeDefPrimitive::U8 => decode::<u8>(input)?.into(),
+		TypeDefPrimitive::U16 => decode::<u16>(input)?.into(),
+		TypeDefPrimitive::U32 => decode::<u32>(input)?.into(),
+		TypeDefPrimitive::U64 => decode::<u64>(input)?.into(),
+		TypeDefPrimitive::U128 => u128_to_json(decode(input)?),
+		TypeDefPrimitive::I8 => decode::<i8>(input)?.into(),
+		TypeDefPrimitive::I16 => decode::<i16>(input)?.into(),
+		TypeDefPrimitive::I32 => decode::<i32>(input)?.into(),
+		TypeDefPrimitive::I64 => decode::<i64>(input)?.into(),
+		TypeDefPrimitive::I128 => {
+			let value: i128 = decode(input)?;
+			i64::try_from(value).map_or_else(|_| Value::String(value.to_string()), Into::into)
+		},
+		TypeDefPrimitive::U256 | TypeDefPrimitive::I256 =>
+			bytes_to_hex(&decode::<[u8; 32]>(input)?),
+	})
+}
+
+fn u128_to_json(value: u128) -> Value {
+	u64::try_from(value).map_or_else(|_| Value::String(value.to_string()), Into::into)
+}
+
+fn decode_fields(
+	registry: &PortableRegistry,
+	fields: &[Field<PortableForm>],
+	input: &mut &[u8],
+) -> Result<Value> {
+	match fields {
+		[] => Ok(Value::Null),
+		[field] if field.name.is_none() => decode_value(registry, field.ty.id, input),
+		fields if fields.iter().all(|field| field.name.is_some()) => {
+			let mut object = Map::new();
+			for field in fields {
+				let name = field.name.clone().unwrap_or_default();
+				object.insert(name, decode_value(registry, field.ty.id, input)?);
+			}
+			Ok(Value::Object(object))
+		},
+		fields => fields
+			.iter()
+			.map(|field| decode_value(registry, field.ty.id, input))
+			.collect::<Result<_>>()
+			.map(Value::Array),
+	}
+}
+
+/// Decode a value of the type `id` from `input` as JSON.
+pub(crate) fn decode_value(
+	registry: &PortableRegistry,
+	id: u32,
+	input: &mut &[u8],
+) -> Result<Value> {
+	match resolve(registry, id)? {
+		TypeDef::Composite(composite) => decode_fields(registry, &composite.fields, input),
+		TypeDef::Variant(variant) => {
+			let index = u8::decode(input).map_err(|e| e.to_string())?;
+			let variant = variant
+				.variants
+				.iter()
+				.find(|variant| variant.index == index)
+				.ok_or_else(|| format!("Unknown variant index `{}` of type `{}`", index, id))?;
+
+			if variant.fields.is_empty() {
+				Ok(Value::String(variant.name.clone()))
+			} else {
+				let mut object = Map::new();
+				object
+					.insert(variant.name.clone(), decode_fields(registry, &variant.fields, input)?);
+				Ok(Value::Object(object))
+			}
+		},
+		TypeDef::Sequence(sequence) if is_u8(registry, sequence.type_param.id) =>
+			Ok(bytes_to_hex(&Vec::<u8>::decode(input).map_err(|e| e.to_string())?)),
+		TypeDef::Sequence(sequence) => {
+			let len = Compact::<u32>::decode(input).map_err(|e| e.to_string())?.0;
+			(0..len)
+				.map(|_| decode_value(registry, sequence.type_param.id, input))
+				.collect::<Result<_>>()
+				.map(Value::Array)
+		},
+		TypeDef::Array(array) if is_u8(registry, array.type_param.id) => {
+			let len = array.len as usize;
+			if input.len() < len {
+				return Err("Not enough data to decode the array".into())
+			}
+			let (bytes, rest) = input.split_at(len);
+			*input = rest;
+			Ok(bytes_to_hex(bytes))
+		},
+		TypeDef::Array(array) => (0..array.len)
+			.map(|_| decode_value(registry, array.type_param.id, input))
+			.collect::<Result<_>>()
+			.map(Value::Array),
+		TypeDef::Tuple(tuple) if tuple.fields.is_empty() => Ok(Value::Null),
+		TypeDef::Tuple(tuple) => tuple
+			.fields
+			.iter()
+			.map(|field| decode_value(registry, field.id, input))
+			.collect::<Result<_>>()
+			.map(Value::Array),
+		TypeDef::Primitive(primitive) => decode_primitive(primitive, input),
+		TypeDef::Compact(_) =>
+			Ok(u128_to_json(Compact::<u128>::decode(input).map_err(|e| e.to_string())?.0)),
+		TypeDef::BitSequence(_) => Err("Bit sequences are not supported".into()),
+	}
+}
@@ -18,6 +18,7 @@
 
 //! Substrate state API.
 
+mod dynamic;
 mod state_full;
 mod utils;
 
@@ -68,6 +69,14 @@ where
 		call_data: Bytes,
 	) -> Result<Bytes, Error>;
 
+	/// Call runtime method at given block, using JSON arguments and returning a JSON result.
+	fn call_dynamic(
+		&self,
+		block: Option<Block::Hash>,
+		method: String,
+		args: Vec<serde_json::Value>,
+	) -> Result<serde_json::Value, Error>;
+
 	/// Returns the keys with prefix, leave empty to get all the keys.
 	fn storage_keys(
 		&self,
@@ -211,6 +220,15 @@ where
 		self.backend.call(block, method, data).map_err(Into::into)
 	}
 
+	fn call_dynamic(
+		&self,
+		method: String,
+		args: Vec<serde_json::Value>,
+		block: Option<Block::Hash>,
+	) -> RpcResult<serde_json::Value> {
+		self.backend.call_dynamic(block, method, args).map_err(Into::into)
+	}
+
 	fn storage_keys(
 		&self,
 		key_prefix: StorageKey,
@@ -21,17 +21,20 @@
 use std::{collections::HashMap, marker::PhantomData, sync::Arc, time::Duration};
 
 use super::{
-	client_err,
+	client_err, dynamic,
 	error::{Error, Result},
 	ChildStateBackend, StateBackend,
 };
 use crate::{DenyUnsafe, SubscriptionTaskExecutor};
 
+use codec::Decode;
+use frame_metadata::{v15::RuntimeMetadataV15, RuntimeMetadata, RuntimeMetadataPrefixed};
 use futures::{future, stream, FutureExt, StreamExt};
 use jsonrpsee::{
 	core::{async_trait, Error as JsonRpseeError},
 	SubscriptionSink,
 };
+use parking_lot::Mutex;
 use sc_client_api::{
 	Backend, BlockBackend, BlockchainEvents, CallExecutor, ExecutorProvider, ProofProvider,
 	StorageProvider,
@@ -44,7 +47,8 @@ use sp_blockchain::{
 };
 use sp_core::{
 	storage::{
-		ChildInfo, ChildType, PrefixedStorageKey, StorageChangeSet, StorageData, StorageKey,
+		well_known_keys, ChildInfo, ChildType, PrefixedStorageKey, StorageChangeSet, StorageData,
+		StorageKey,
 	},
 	traits::CallContext,
 	Bytes,
@@ -55,6 +59,9 @@ use sp_version::RuntimeVersion;
 /// The maximum time allowed for an RPC call when running without unsafe RPC enabled.
 const MAXIMUM_SAFE_RPC_CALL_TIMEOUT: Duration = Duration::from_secs(30);
 
+/// The maximum number of runtimes for which the decoded metadata is cached.
+const MAX_CACHED_METADATA: usize = 8;
+
 /// Ranges to query in state_queryStorage.
 struct QueryStorageRange<Block: BlockT> {
 	/// Hashes of all the blocks in the range.
@@ -65,6 +72,8 @@ struct QueryStorageRange<Block: BlockT> {
 pub struct FullState<BE, Block: BlockT, Client> {
 	client: Arc<Client>,
 	executor: SubscriptionTaskExecutor,
+	/// Decoded metadata `V15` used by `state_callDynamic`, keyed by the hash of the runtime code.
+	metadata_cache: Mutex<HashMap<Block::Hash, Arc<RuntimeMetadataV15>>>,
 	_phantom: PhantomData<(BE, Block)>,
 }
 
@@ -79,7 +88,7 @@ where
 {
 	/// Create new state API backend for full nodes.
 	pub fn new(client: Arc<Client>, executor: SubscriptionTaskExecutor) -> Self {
-		Self { client, executor, _phantom: PhantomData }
+		Self { client, executor, metadata_cache: Default::default(), _phantom: PhantomData }
 	}
 
 	/// Returns given block hash or best block hash if None is passed.
@@ -204,6 +213,62 @@ where
 			.map_err(client_err)
 	}
 
+	fn call_dynamic(
+		&self,
+		block: Option<Block::Hash>,
+		method: String,
+		args: Vec<serde_json::Value>,
+	) -> std::result::Result<serde_json::Value, Error> {
+		let block = self.block_or_best(block).map_err(client_err)?;
+
+		let code_hash = self
+			.client
+			.storage_hash(block, &StorageKey(well_known_keys::CODE.to_vec()))
+			.map_err(client_err)?
+			.ok_or_else(|| Error::DynamicCall("The runtime code is not available".into()))?;
+		let cached = self.metadata_cache.lock().get(&code_hash).cloned();
+		let metadata = match cached {
+			Some(metadata) => metadata,
+			None => {
+				let metadata = self
+					.client
+					.runtime_api()
+					.metadata_at_version(block, 15)
+					.map_err(|e| Error::Client(Box::new(e)))?
+					.ok_or_else(|| {
+						Error::DynamicCall("The runtime doesn't support metadata V15".into())
+					})?;
+				let metadata = match RuntimeMetadataPrefixed::decode(&mut &metadata[..]) {
+					Ok(RuntimeMetadataPrefixed(_, RuntimeMetadata::V15(metadata))) =>
+						Arc::new(metadata),
+					Ok(_) => return Err(Error::DynamicCall("Expected metadata V15".into())),
+					Err(e) =>
+						return Err(Error::DynamicCall(format!(
+							"Failed to decode the metadata: {}",
+							e
+						))),
+				};
+
+				let mut cache = self.metadata_cache.lock();
+				if cache.len() >= MAX_CACHED_METADATA {
+					cache.clear();
+				}
+				cache.insert(code_hash, metadata.clone());
+				metadata
+			},
+		};
+
+		let method_metadata =
+			dynamic::runtime_api_method(&metadata, &method).map_err(Error::DynamicCall)?;
+		let call_data = dynamic::encode_args(&metadata.types, method_metadata, &args)
+			.map_err(Error::DynamicCall)?;
+
+		let result = self.call(Some(block), method, call_data.into())?;
+
+		dynamic::decode_output(&metadata.types, method_metadata, &result)
+			.map_err(Error::DynamicCall)
+	}
+
 	// TODO: This is horribly broken; either remove it, or make it streaming.
 	fn storage_keys(
 		&self,
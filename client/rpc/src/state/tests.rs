@@ -550,6 +550,67 @@ async fn should_notify_on_runtime_version_initially() {
 	assert_matches!(timeout_secs(10, sub.next::<RuntimeVersion>()).await, Ok(None));
 }
 
+#[tokio::test]
+async fn should_call_runtime_api_dynamically() {
+	let client = Arc::new(substrate_test_runtime_client::new());
+	let (api, _child) = new_full(client, test_executor(), DenyUnsafe::No);
+	let api = api.into_rpc();
+
+	let version: serde_json::Value = api
+		.call("state_callDynamic", ("Core_version", Vec::<serde_json::Value>::new()))
+		.await
+		.unwrap();
+	assert_eq!(version["spec_name"], "test");
+	assert_eq!(version["spec_version"], 2);
+	assert_eq!(version["state_version"], 1);
+
+	// The metadata is cached after the first call.
+	let again: serde_json::Value = api
+		.call("state_callDynamic", ("Core_version", Vec::<serde_json::Value>::new()))
+		.await
+		.unwrap();
+	assert_eq!(again, version);
+}
+
+#[tokio::test]
+async fn should_reject_invalid_dynamic_calls() {
+	let client = Arc::new(substrate_test_runtime_client::new());
+	let (api, _child) = new_full(client, test_executor(), DenyUnsafe::No);
+	let api = api.into_rpc();
+
+	let result = api
+		.call::<_, serde_json::Value>(
+			"state_callDynamic",
+			("Core_unknown", Vec::<serde_json::Value>::new()),
+		)
+		.await;
+	assert_eq!(
+		result.unwrap_err().to_string(),
+		RpcError::Call(RpcCallError::Custom(ErrorObject::owned(
+			4003,
+			Error::DynamicCall(
+				"Runtime API method `Core_unknown` not found in the metadata".into()
+			)
+			.to_string(),
+			None::<()>,
+		)))
+		.to_string()
+	);
+
+	let result = api
+		.call::<_, serde_json::Value>("state_callDynamic", ("Core_version", [1u32]))
+		.await;
+	assert_eq!(
+		result.unwrap_err().to_string(),
+		RpcError::Call(RpcCallError::Custom(ErrorObject::owned(
+			4003,
+			Error::DynamicCall("`version` expects 0 arguments, got 1".into()).to_string(),
+			None::<()>,
+		)))
+		.to_string()
+	);
+}
+
 #[test]
 fn should_deserialize_storage_key() {
 	let k = "\"0x7f864e18e3dd8b58386310d2fe0919eef27c6e558564b7f67f22d99d20f587b\"";
@@ -579,3 +640,91 @@ async fn concrete_storage_subscriptions_are_rpc_safe() {
 
 	assert!(sub.is_ok());
 }
+
+#[derive(codec::Encode, scale_info::TypeInfo)]
+struct DynamicArgs {
+	who: [u8; 32],
+	amount: u128,
+	#[codec(compact)]
+	nonce: u32,
+	tags: Vec<String>,
+	data: Vec<u8>,
+}
+
+#[derive(codec::Encode, scale_info::TypeInfo)]
+enum DynamicResult {
+	Empty,
+	Value(u64),
+	Pair(u8, bool),
+	Named { first: i32, second: Option<u16> },
+}
+
+fn dynamic_registry<T: scale_info::TypeInfo + 'static>() -> (scale_info::PortableRegistry, u32) {
+	let mut registry = scale_info::Registry::new();
+	let id = registry.register_type(&scale_info::meta_type::<T>()).id;
+	(registry.into(), id)
+}
+
+fn assert_dynamic_round_trip<T: codec::Encode + scale_info::TypeInfo + 'static>(
+	value: T,
+	json: serde_json::Value,
+) {
+	let (registry, id) = dynamic_registry::<T>();
+
+	let mut encoded = Vec::new();
+	dynamic::encode_value(&registry, id, &json, &mut encoded).unwrap();
+	assert_eq!(encoded, codec::Encode::encode(&value));
+
+	let decoded = dynamic::decode_value(&registry, id, &mut &encoded[..]).unwrap();
+	assert_eq!(decoded, json);
+}
+
+#[test]
+fn dynamic_call_struct_round_trip() {
+	assert_dynamic_round_trip(
+		DynamicArgs {
+			who: [1; 32],
+			amount: u128::MAX,
+			nonce: 5,
+			tags: vec!["a".into(), "b".into()],
+			data: vec![1, 2],
+		},
+		serde_json::json!({
+			"who": format!("0x{}", "01".repeat(32)),
+			"amount": u128::MAX.to_string(),
+			"nonce": 5,
+			"tags": ["a", "b"],
+			"data": "0x0102",
+		}),
+	);
+}
+
+#[test]
+fn dynamic_call_enum_round_trip() {
+	assert_dynamic_round_trip(DynamicResult::Empty, serde_json::json!("Empty"));
+	assert_dynamic_round_trip(DynamicResult::Value(9), serde_json::json!({ "Value": 9 }));
+	assert_dynamic_round_trip(
+		DynamicResult::Pair(3, true),
+		serde_json::json!({ "Pair": [3, true] }),
+	);
+	assert_dynamic_round_trip(
+		DynamicResult::Named { first: -1, second: Some(7) },
+		serde_json::json!({ "Named": { "first": -1, "second": { "Some": 7 } } }),
+	);
+	assert_dynamic_round_trip((), serde_json::Value::Null);
+}
+
+#[test]
+fn dynamic_call_rejects_invalid_json() {
+	let (registry, id) = dynamic_registry::<DynamicResult>();
+	let encode = |json: serde_json::Value| {
+		dynamic::encode_value(&registry, id, &json, &mut Vec::new()).unwrap_err()
+	};
+
+	assert_eq!(encode(serde_json::json!("Unknown")), "Unknown variant `Unknown`");
+	assert!(
+		encode(serde_json::json!({ "Value": 1, "Empty": null })).starts_with("Expected a variant")
+	);
+	assert_eq!(encode(serde_json::json!({ "Pair": [300, true] })), "Integer `300` out of range");
+	assert_eq!(encode(serde_json::json!({ "Named": { "first": 1 } })), "Expected 2 fields, got 1");
+}
@@ -18,19 +18,30 @@
 
 //! Substrate statement store API.
 
+#[cfg(test)]
+mod tests;
+
+use crate::SubscriptionTaskExecutor;
 use codec::{Decode, Encode};
-use jsonrpsee::core::{async_trait, RpcResult};
+use futures::{FutureExt, StreamExt};
+use jsonrpsee::{
+	core::{async_trait, Error as JsonRpseeError, RpcResult},
+	types::SubscriptionResult,
+	SubscriptionSink,
+};
 /// Re-export the API for backward compatibility.
-pub use sc_rpc_api::statement::{error::Error, StatementApiServer};
+pub use sc_rpc_api::statement::{error::Error, StatementApiServer, TopicFilter};
 use sc_rpc_api::DenyUnsafe;
 use sp_core::Bytes;
-use sp_statement_store::{StatementSource, SubmitResult};
+use sp_statement_store::{StatementFilter, StatementSource, SubmitResult};
 use std::sync::Arc;
 
 /// Statement store API
 pub struct StatementStore {
 	store: Arc<dyn sp_statement_store::StatementStore>,
 	deny_unsafe: DenyUnsafe,
+	/// Executor to spawn subscriptions.
+	executor: SubscriptionTaskExecutor,
 }
 
 impl StatementStore {
@@ -38,8 +49,9 @@ impl StatementStore {
 	pub fn new(
 		store: Arc<dyn sp_statement_store::StatementStore>,
 		deny_unsafe: DenyUnsafe,
+		executor: SubscriptionTaskExecutor,
 	) -> Self {
-		StatementStore { store, deny_unsafe }
+		StatementStore { store, deny_unsafe, executor }
 	}
 }
 
@@ -105,4 +117,30 @@ impl StatementApiServer for StatementStore {
 	fn remove(&self, hash: [u8; 32]) -> RpcResult<()> {
 		Ok(self.store.remove(&hash).map_err(|e| Error::StatementStore(e.to_string()))?)
 	}
+
+	fn subscribe_statement(
+		&self,
+		mut sink: SubscriptionSink,
+		topic_filter: TopicFilter,
+		dest: Option<[u8; 32]>,
+	) -> SubscriptionResult {
+		let topics = match topic_filter {
+			TopicFilter::MatchAll(topics) => sp_statement_store::TopicFilter::MatchAll(topics),
+			TopicFilter::MatchAny(topics) => sp_statement_store::TopicFilter::MatchAny(topics),
+		};
+		let stream = match self.store.subscribe(StatementFilter { topics, decryption_key: dest }) {
+			Ok(stream) => stream.map(|statement| Bytes::from(statement.encode())),
+			Err(e) => {
+				let _ = sink.reject(JsonRpseeError::from(Error::StatementStore(e.to_string())));
+				return Ok(())
+			},
+		};
+
+		let fut = async move {
+			sink.pipe_from_stream(stream).await;
+		};
+
+		self.executor.spawn("substrate-rpc-subscription", Some("rpc"), fut.boxed());
+		Ok(())
+	}
 }
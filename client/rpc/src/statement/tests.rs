@@ -0,0 +1,148 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::testing::{test_executor, timeout_secs};
+use assert_matches::assert_matches;
+use futures::channel::mpsc;
+use jsonrpsee::{
+	core::{server::rpc_module::Subscription, Error as RpcError},
+	types::error::CallError,
+};
+use parking_lot::Mutex;
+use sp_statement_store::{Hash, Result, Statement, StatementStore as _, StatementStream, Topic};
+
+/// Statement store that only keeps track of subscriptions.
+struct TestStore {
+	max_subscriptions: usize,
+	subscribers: Mutex<Vec<(StatementFilter, mpsc::UnboundedSender<Statement>)>>,
+}
+
+impl TestStore {
+	fn new(max_subscriptions: usize) -> Arc<Self> {
+		Arc::new(TestStore { max_subscriptions, subscribers: Default::default() })
+	}
+}
+
+impl sp_statement_store::StatementStore for TestStore {
+	fn statements(&self) -> Result<Vec<(Hash, Statement)>> {
+		Ok(Vec::new())
+	}
+
+	fn statement(&self, _hash: &Hash) -> Result<Option<Statement>> {
+		Ok(None)
+	}
+
+	fn broadcasts(&self, _match_all_topics: &[Topic]) -> Result<Vec<Vec<u8>>> {
+		Ok(Vec::new())
+	}
+
+	fn posted(&self, _match_all_topics: &[Topic], _dest: [u8; 32]) -> Result<Vec<Vec<u8>>> {
+		Ok(Vec::new())
+	}
+
+	fn posted_clear(&self, _match_all_topics: &[Topic], _dest: [u8; 32]) -> Result<Vec<Vec<u8>>> {
+		Ok(Vec::new())
+	}
+
+	fn submit(&self, statement: Statement, _source: StatementSource) -> SubmitResult {
+		for (filter, sender) in self.subscribers.lock().iter() {
+			if filter.matches(&statement) {
+				let _ = sender.unbounded_send(statement.clone());
+			}
+		}
+		SubmitResult::New(sp_statement_store::NetworkPriority::High)
+	}
+
+	fn remove(&self, _hash: &Hash) -> Result<()> {
+		Ok(())
+	}
+
+	fn subscribe(&self, filter: StatementFilter) -> Result<StatementStream> {
+		let mut subscribers = self.subscribers.lock();
+		if subscribers.len() >= self.max_subscriptions {
+			return Err(sp_statement_store::Error::TooManySubscriptions)
+		}
+		let (sender, receiver) = mpsc::unbounded();
+		subscribers.push((filter, sender));
+		Ok(Box::pin(receiver))
+	}
+}
+
+fn statement(data: u8, topics: &[Topic], dec_key: Option<[u8; 32]>) -> Statement {
+	let mut statement = Statement::new();
+	statement.set_plain_data(vec![data]);
+	for (i, topic) in topics.iter().enumerate() {
+		statement.set_topic(i, *topic);
+	}
+	if let Some(key) = dec_key {
+		statement.set_decryption_key(key);
+	}
+	statement
+}
+
+async fn next(sub: &mut Subscription) -> Statement {
+	let (bytes, _) = timeout_secs(10, sub.next::<Bytes>()).await.unwrap().unwrap().unwrap();
+	Statement::decode(&mut &bytes[..]).unwrap()
+}
+
+#[tokio::test]
+async fn statement_subscription_receives_matching_statements() {
+	let store = TestStore::new(2);
+	let api = StatementStore::new(store.clone(), DenyUnsafe::No, test_executor()).into_rpc();
+
+	let mut broadcasts = api
+		.subscribe(
+			"statement_subscribe",
+			(TopicFilter::MatchAny(vec![[1; 32], [2; 32]]), None::<[u8; 32]>),
+		)
+		.await
+		.unwrap();
+	let mut posted = api
+		.subscribe("statement_subscribe", (TopicFilter::MatchAll(vec![[1; 32]]), Some([9u8; 32])))
+		.await
+		.unwrap();
+
+	let statements = [
+		statement(0, &[[0; 32]], None),
+		statement(1, &[[2; 32]], None),
+		statement(2, &[[1; 32]], Some([9; 32])),
+		statement(3, &[[1; 32], [3; 32]], None),
+	];
+	for statement in &statements {
+		store.submit(statement.clone(), StatementSource::Local);
+	}
+
+	assert_eq!(next(&mut broadcasts).await, statements[1]);
+	assert_eq!(next(&mut broadcasts).await, statements[3]);
+	assert_eq!(next(&mut posted).await, statements[2]);
+}
+
+#[tokio::test]
+async fn statement_subscription_rejected_by_store() {
+	let store = TestStore::new(0);
+	let api = StatementStore::new(store, DenyUnsafe::No, test_executor()).into_rpc();
+
+	let sub = api
+		.subscribe("statement_subscribe", (TopicFilter::MatchAll(Vec::new()), None::<[u8; 32]>))
+		.await;
+	assert_matches!(
+		sub,
+		Err(RpcError::Call(CallError::Custom(err))) if err.message().contains("Too many subscriptions")
+	);
+}
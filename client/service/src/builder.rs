@@ -31,7 +31,7 @@ use log::info;
 use prometheus_endpoint::Registry;
 use sc_chain_spec::get_extension;
 use sc_client_api::{
-	execution_extensions::ExecutionExtensions, proof_provider::ProofProvider, BadBlocks,
+	execution_extensions::ExecutionExtensions, proof_provider::ProofProvider, AuxStore, BadBlocks,
 	BlockBackend, BlockchainEvents, ExecutorProvider, ForkBlocks, StorageProvider, UsageProvider,
 };
 use sc_client_db::{Backend, DatabaseSettings};
@@ -89,29 +89,48 @@ pub type TFullCallExecutor<TBl, TExec> = crate::client::LocalCallExecutor<TBl, B
 type TFullParts<TBl, TRtApi, TExec> =
 	(TFullClient<TBl, TRtApi, TExec>, Arc<TFullBackend<TBl>>, KeystoreContainer, TaskManager);
 
-/// Construct a local keystore shareable container
-pub struct KeystoreContainer(Arc<LocalKeystore>);
+/// Construct a keystore shareable container
+pub struct KeystoreContainer {
+	keystore: KeystorePtr,
+	local: Option<Arc<LocalKeystore>>,
+}
 
 impl KeystoreContainer {
 	/// Construct KeystoreContainer
 	pub fn new(config: &KeystoreConfig) -> Result<Self, Error> {
-		let keystore = Arc::new(match config {
-			KeystoreConfig::Path { path, password } =>
-				LocalKeystore::open(path.clone(), password.clone())?,
-			KeystoreConfig::InMemory => LocalKeystore::in_memory(),
-		});
+		let local = |keystore: LocalKeystore| {
+			let keystore = Arc::new(keystore);
+			Self { keystore: keystore.clone(), local: Some(keystore) }
+		};
 
-		Ok(Self(keystore))
+		match config {
+			KeystoreConfig::Path { path, password } =>
+				Ok(local(LocalKeystore::open(path.clone(), password.clone())?)),
+			KeystoreConfig::InMemory => Ok(local(LocalKeystore::in_memory())),
+			#[cfg(unix)]
+			KeystoreConfig::Remote { socket } => {
+				let keystore = sc_keystore::RemoteKeystore::open(socket.clone())
+					.map_err(sc_keystore::Error::from)?;
+				// The private keys stay with the signer.
+				Ok(Self { keystore: Arc::new(keystore), local: None })
+			},
+			#[cfg(not(unix))]
+			KeystoreConfig::Remote { .. } =>
+				Err(Error::Other("Remote keystores are only supported on unix platforms".into())),
+		}
 	}
 
 	/// Returns a shared reference to a dynamic `Keystore` trait implementation.
 	pub fn keystore(&self) -> KeystorePtr {
-		self.0.clone()
+		self.keystore.clone()
 	}
 
 	/// Returns a shared reference to the local keystore .
-	pub fn local_keystore(&self) -> Arc<LocalKeystore> {
-		self.0.clone()
+	///
+	/// Returns `None` if the node uses a remote keystore, because the private keys never leave
+	/// the signer.
+	pub fn local_keystore(&self) -> Option<Arc<LocalKeystore>> {
+		self.local.clone()
 	}
 }
 
@@ -134,6 +153,25 @@ pub fn new_full_parts<TBl, TRtApi, TExec>(
 	telemetry: Option<TelemetryHandle>,
 	executor: TExec,
 ) -> Result<TFullParts<TBl, TRtApi, TExec>, Error>
+where
+	TBl: BlockT,
+	TExec: CodeExecutor + RuntimeVersionOf + Clone,
+{
+	new_full_parts_record_import(config, telemetry, executor, false)
+}
+
+/// Create the initial parts of a full node with the default genesis block builder.
+///
+/// If `enable_import_proof_recording` is `true`, the storage proof is recorded while importing
+/// blocks. Nodes whose runtime reads the proof size, e.g. through `StorageWeightReclaim`, need
+/// to enable it and to author blocks with proof recording as well. See
+/// [`ClientConfig::enable_import_proof_recording`].
+pub fn new_full_parts_record_import<TBl, TRtApi, TExec>(
+	config: &Configuration,
+	telemetry: Option<TelemetryHandle>,
+	executor: TExec,
+	enable_import_proof_recording: bool,
+) -> Result<TFullParts<TBl, TRtApi, TExec>, Error>
 where
 	TBl: BlockT,
 	TExec: CodeExecutor + RuntimeVersionOf + Clone,
@@ -147,7 +185,14 @@ where
 		executor.clone(),
 	)?;
 
-	new_full_parts_with_genesis_builder(config, telemetry, executor, backend, genesis_block_builder)
+	new_full_parts_with_genesis_builder_inner(
+		config,
+		telemetry,
+		executor,
+		backend,
+		genesis_block_builder,
+		enable_import_proof_recording,
+	)
 }
 
 /// Create the initial parts of a full node.
@@ -158,6 +203,32 @@ pub fn new_full_parts_with_genesis_builder<TBl, TRtApi, TExec, TBuildGenesisBloc
 	backend: Arc<TFullBackend<TBl>>,
 	genesis_block_builder: TBuildGenesisBlock,
 ) -> Result<TFullParts<TBl, TRtApi, TExec>, Error>
+where
+	TBl: BlockT,
+	TExec: CodeExecutor + RuntimeVersionOf + Clone,
+	TBuildGenesisBlock: BuildGenesisBlock<
+		TBl,
+		BlockImportOperation = <Backend<TBl> as sc_client_api::backend::Backend<TBl>>::BlockImportOperation
+	>,
+{
+	new_full_parts_with_genesis_builder_inner(
+		config,
+		telemetry,
+		executor,
+		backend,
+		genesis_block_builder,
+		false,
+	)
+}
+
+fn new_full_parts_with_genesis_builder_inner<TBl, TRtApi, TExec, TBuildGenesisBlock>(
+	config: &Configuration,
+	telemetry: Option<TelemetryHandle>,
+	executor: TExec,
+	backend: Arc<TFullBackend<TBl>>,
+	genesis_block_builder: TBuildGenesisBlock,
+	enable_import_proof_recording: bool,
+) -> Result<TFullParts<TBl, TRtApi, TExec>, Error>
 where
 	TBl: BlockT,
 	TExec: CodeExecutor + RuntimeVersionOf + Clone,
@@ -224,6 +295,7 @@ where
 					SyncMode::LightState { .. } | SyncMode::Warp { .. }
 				),
 				wasm_runtime_substitutes,
+				enable_import_proof_recording,
 			},
 		)?;
 
@@ -720,6 +792,7 @@ where
 		+ ProofProvider<TBl>
 		+ HeaderBackend<TBl>
 		+ BlockchainEvents<TBl>
+		+ AuxStore
 		+ 'static,
 	TExPool: TransactionPool<Block = TBl, Hash = <TBl as BlockT>::Hash> + 'static,
 	TImpQu: ImportQueue<TBl> + 'static,
@@ -18,7 +18,9 @@
 
 //! Service configuration.
 
-pub use sc_client_db::{BlocksPruning, Database, DatabaseSource, PruningMode};
+pub use sc_client_db::{
+	BlocksPruning, Database, DatabaseSource, OffchainIndexingPruning, PruningMode,
+};
 pub use sc_executor::{WasmExecutionMethod, WasmtimeInstantiationStrategy};
 pub use sc_network::{
 	config::{
@@ -39,6 +41,7 @@ use sp_core::crypto::SecretString;
 use std::{
 	io, iter,
 	net::SocketAddr,
+	num::NonZeroUsize,
 	path::{Path, PathBuf},
 };
 use tempfile::TempDir;
@@ -80,6 +83,8 @@ pub struct Configuration {
 	/// over on-chain runtimes when the spec version matches. Set to `None` to
 	/// disable overrides (default).
 	pub wasm_runtime_overrides: Option<PathBuf>,
+	/// Number of tasks verifying blocks in parallel in the import queue.
+	pub block_verification_workers: NonZeroUsize,
 	/// JSON-RPC server binding address.
 	pub rpc_addr: Option<SocketAddr>,
 	/// Maximum number of connections for JSON-RPC server.
@@ -160,6 +165,13 @@ pub enum KeystoreConfig {
 	},
 	/// In-memory keystore. Recommended for in-browser nodes.
 	InMemory,
+	/// Keystore that forwards all operations to an external signer process.
+	///
+	/// Only supported on unix platforms.
+	Remote {
+		/// The path of the unix socket the signer is listening on.
+		socket: PathBuf,
+	},
 }
 
 impl KeystoreConfig {
@@ -167,7 +179,7 @@ impl KeystoreConfig {
 	pub fn path(&self) -> Option<&Path> {
 		match self {
 			Self::Path { path, .. } => Some(path),
-			Self::InMemory => None,
+			Self::InMemory | Self::Remote { .. } => None,
 		}
 	}
 }
@@ -178,6 +190,8 @@ pub struct OffchainWorkerConfig {
 	pub enabled: bool,
 	/// allow writes from the runtime to the offchain worker database.
 	pub indexing_enabled: bool,
+	/// Pruning mode of the data written by offchain indexing.
+	pub indexing_pruning: OffchainIndexingPruning,
 }
 
 /// Configuration of the Prometheus endpoint.
@@ -241,6 +255,7 @@ impl Configuration {
 			state_pruning: self.state_pruning.clone(),
 			source: self.database.clone(),
 			blocks_pruning: self.blocks_pruning,
+			offchain_indexing_pruning: self.offchain_worker.indexing_pruning,
 		}
 	}
 }
@@ -43,6 +43,10 @@ struct PrometheusMetrics {
 	// I/O
 	database_cache: Gauge<U64>,
 	state_cache: Gauge<U64>,
+
+	// offchain indexing
+	offchain_indexing_keys: Gauge<U64>,
+	offchain_indexing_bytes: Gauge<U64>,
 }
 
 impl PrometheusMetrics {
@@ -116,6 +120,22 @@ impl PrometheusMetrics {
 				Gauge::new("substrate_state_cache_bytes", "State cache size in bytes")?,
 				registry,
 			)?,
+
+			// offchain indexing
+			offchain_indexing_keys: register(
+				Gauge::new(
+					"substrate_offchain_indexing_keys",
+					"Number of keys written by offchain indexing that are still stored",
+				)?,
+				registry,
+			)?,
+			offchain_indexing_bytes: register(
+				Gauge::new(
+					"substrate_offchain_indexing_bytes",
+					"Size in bytes of the values written by offchain indexing that are still stored",
+				)?,
+				registry,
+			)?,
 		})
 	}
 }
@@ -252,6 +272,8 @@ impl MetricsService {
 			if let Some(info) = info.usage.as_ref() {
 				metrics.database_cache.set(info.memory.database_cache.as_bytes() as u64);
 				metrics.state_cache.set(info.memory.state_cache.as_bytes() as u64);
+				metrics.offchain_indexing_keys.set(info.offchain_indexing.keys);
+				metrics.offchain_indexing_bytes.set(info.offchain_indexing.bytes);
 			}
 		}
 
@@ -19,14 +19,17 @@
 use async_channel::TryRecvError;
 use futures::executor::block_on;
 use parity_scale_codec::{Decode, Encode, Joiner};
-use sc_block_builder::BlockBuilderProvider;
+use sc_block_builder::{BlockBuilderProvider, RecordProof};
 use sc_client_api::{
-	in_mem, BlockBackend, BlockchainEvents, ExecutorProvider, FinalityNotifications, HeaderBackend,
-	StorageProvider,
+	in_mem, AuxStore, BlockBackend, BlockchainEvents, ExecutorProvider, FinalityNotifications,
+	HeaderBackend, StorageProvider,
+};
+use sc_client_db::{
+	Backend, BlocksPruning, DatabaseSettings, DatabaseSource, OffchainIndexingPruning, PruningMode,
 };
-use sc_client_db::{Backend, BlocksPruning, DatabaseSettings, DatabaseSource, PruningMode};
 use sc_consensus::{
-	BlockCheckParams, BlockImport, BlockImportParams, ForkChoiceStrategy, ImportResult,
+	AuthorDiversity, AuxWeight, BlockCheckParams, BlockImport, BlockImportParams, ChainLength,
+	FinalizedAncestry, ForkChoiceStrategy, ImportResult, RuleBasedSelectChain,
 };
 use sc_service::client::{new_in_mem, Client, LocalCallExecutor};
 use sp_api::ProvideRuntimeApi;
@@ -35,7 +38,7 @@ use sp_core::{testing::TaskExecutor, traits::CallContext, H256};
 use sp_runtime::{
 	generic::BlockId,
 	traits::{BlakeTwo256, Block as BlockT, Header as HeaderT},
-	ConsensusEngineId, Justifications, StateVersion,
+	ConsensusEngineId, DigestItem, Justifications, StateVersion,
 };
 use sp_state_machine::{backend::Backend as _, InMemoryBackend, OverlayedChanges, StateMachine};
 use sp_storage::{ChildInfo, StorageKey};
@@ -48,7 +51,8 @@ use substrate_test_runtime_client::{
 	runtime::{
 		currency::DOLLARS,
 		genesismap::{insert_genesis_block, GenesisStorageBuilder},
-		Block, BlockNumber, Digest, Hash, Header, RuntimeApi, Transfer,
+		substrate_test_pallet::PROOF_SIZE_KEY,
+		Block, BlockNumber, Digest, ExtrinsicBuilder, Hash, Header, RuntimeApi, Transfer,
 	},
 	AccountKeyring, BlockBuilderExt, ClientBlockImportExt, ClientExt, DefaultTestClientBuilderExt,
 	Sr25519Keyring, TestClientBuilder, TestClientBuilderExt,
@@ -313,6 +317,33 @@ fn block_builder_works_with_transactions() {
 	);
 }
 
+#[test]
+fn block_reading_the_proof_size_requires_proof_recording_on_import() {
+	let client = substrate_test_runtime_client::new();
+	let mut importer = TestClientBuilder::new().enable_import_proof_recording().build();
+	let mut non_recording_importer = substrate_test_runtime_client::new();
+
+	let mut builder = client
+		.new_block_at(client.info().best_hash, Default::default(), RecordProof::Yes)
+		.unwrap();
+	builder.push(ExtrinsicBuilder::new_read(8).build()).unwrap();
+	builder.push(ExtrinsicBuilder::new_store_proof_size().build()).unwrap();
+	let block = builder.build().unwrap().block;
+	let hash = block.hash();
+
+	// The import executes the block again and fails if it ends up with another state root.
+	assert!(block_on(non_recording_importer.import(BlockOrigin::Own, block.clone())).is_err());
+	block_on(importer.import(BlockOrigin::Own, block)).unwrap();
+
+	let proof_size = importer
+		.storage(hash, &StorageKey(PROOF_SIZE_KEY.to_vec()))
+		.unwrap()
+		.map(|value| u64::decode(&mut &value.0[..]).unwrap())
+		.unwrap();
+	assert!(proof_size > 0);
+	assert_ne!(proof_size, sp_io::PROOF_RECORDING_DISABLED);
+}
+
 #[test]
 fn block_builder_does_not_include_invalid() {
 	let mut client = substrate_test_runtime_client::new();
@@ -878,6 +909,160 @@ fn finality_target_with_best_not_on_longest_chain() {
 	assert_eq!(b4.hash(), block_on(chain_select.finality_target(b4.hash(), None)).unwrap());
 }
 
+fn authored_by(author: u8) -> Digest {
+	Digest { logs: vec![DigestItem::PreRuntime(TEST_ENGINE_ID, vec![author])] }
+}
+
+fn author_of(header: &Header) -> Option<Vec<u8>> {
+	header
+		.digest()
+		.logs()
+		.iter()
+		.find_map(|log| log.as_pre_runtime())
+		.map(|(_, author)| author.to_vec())
+}
+
+#[test]
+fn rule_based_select_chain_prefers_higher_aux_weight() {
+	// block tree:
+	// G -> A1 -> A2 -> A3
+	//      A1 -> B2
+
+	let (mut client, backend) = TestClientBuilder::new().build_with_backend();
+	let chain_select = RuleBasedSelectChain::new(backend.clone())
+		.with_rule(AuxWeight::<Block, u32>::new(|hash| (b"test_weight", hash).encode()))
+		.with_rule(ChainLength);
+	let genesis_hash = client.chain_info().genesis_hash;
+
+	// G -> A1
+	let a1 = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	block_on(client.import(BlockOrigin::Own, a1.clone())).unwrap();
+
+	// A1 -> A2
+	let a2 = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	block_on(client.import(BlockOrigin::Own, a2.clone())).unwrap();
+
+	// A2 -> A3
+	let a3 = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	block_on(client.import(BlockOrigin::Own, a3.clone())).unwrap();
+
+	// A1 -> B2
+	let b2 = client
+		.new_block_at(a1.hash(), authored_by(1), false)
+		.unwrap()
+		.build()
+		.unwrap()
+		.block;
+	block_on(client.import(BlockOrigin::Own, b2.clone())).unwrap();
+
+	// without any weights, the longest chain wins
+	assert_eq!(a3.hash(), block_on(chain_select.best_chain()).unwrap().hash());
+	assert_eq!(a3.hash(), block_on(chain_select.finality_target(genesis_hash, None)).unwrap());
+
+	let key = (b"test_weight", b2.hash()).encode();
+	backend.insert_aux(&[(&key[..], &10u32.encode()[..])], &[]).unwrap();
+
+	assert_eq!(b2.hash(), block_on(chain_select.best_chain()).unwrap().hash());
+	assert_eq!(b2.hash(), block_on(chain_select.finality_target(genesis_hash, None)).unwrap());
+	assert_eq!(b2.hash(), block_on(chain_select.finality_target(a1.hash(), None)).unwrap());
+	assert!(block_on(chain_select.finality_target(a2.hash(), None)).is_err());
+}
+
+#[test]
+fn rule_based_select_chain_prefers_author_diversity() {
+	// block tree:
+	// G -> A1 -> A2 -> A3 (all authored by 1)
+	// G -> B1 -> B2       (authored by 2 and 3)
+
+	let (mut client, backend) = TestClientBuilder::new().build_with_backend();
+	let chain_select = RuleBasedSelectChain::new(backend)
+		.with_rule(AuthorDiversity::new(3, author_of))
+		.with_rule(ChainLength);
+	let genesis_hash = client.chain_info().genesis_hash;
+
+	// G -> A1
+	let a1 = client.new_block(authored_by(1)).unwrap().build().unwrap().block;
+	block_on(client.import(BlockOrigin::Own, a1.clone())).unwrap();
+
+	// A1 -> A2
+	let a2 = client.new_block(authored_by(1)).unwrap().build().unwrap().block;
+	block_on(client.import(BlockOrigin::Own, a2.clone())).unwrap();
+
+	// A2 -> A3
+	let a3 = client.new_block(authored_by(1)).unwrap().build().unwrap().block;
+	block_on(client.import(BlockOrigin::Own, a3.clone())).unwrap();
+
+	// G -> B1
+	let b1 = client
+		.new_block_at(genesis_hash, authored_by(2), false)
+		.unwrap()
+		.build()
+		.unwrap()
+		.block;
+	block_on(client.import(BlockOrigin::Own, b1.clone())).unwrap();
+
+	// B1 -> B2
+	let b2 = client
+		.new_block_at(b1.hash(), authored_by(3), false)
+		.unwrap()
+		.build()
+		.unwrap()
+		.block;
+	block_on(client.import(BlockOrigin::Own, b2.clone())).unwrap();
+
+	// the longest chain is still the best one according to the client
+	assert_eq!(client.info().best_hash, a3.hash());
+
+	assert_eq!(b2.hash(), block_on(chain_select.best_chain()).unwrap().hash());
+	assert_eq!(b2.hash(), block_on(chain_select.finality_target(genesis_hash, None)).unwrap());
+	assert_eq!(b1.hash(), block_on(chain_select.finality_target(b1.hash(), Some(1))).unwrap());
+	assert!(block_on(chain_select.finality_target(a1.hash(), None)).is_err());
+}
+
+#[test]
+fn rule_based_select_chain_requires_finalized_ancestry() {
+	// block tree:
+	// G -> A1 -> A2
+	// G -> B1 -> B2 -> B3
+
+	let (mut client, backend) = TestClientBuilder::new().build_with_backend();
+	let chain_select = RuleBasedSelectChain::new(backend)
+		.with_rule(FinalizedAncestry)
+		.with_rule(ChainLength);
+	let genesis_hash = client.chain_info().genesis_hash;
+
+	// G -> A1
+	let a1 = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	block_on(client.import(BlockOrigin::Own, a1.clone())).unwrap();
+
+	// A1 -> A2
+	let a2 = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	block_on(client.import(BlockOrigin::Own, a2.clone())).unwrap();
+
+	// G -> B1 -> B2 -> B3
+	let mut parent_hash = genesis_hash;
+	let mut b3 = None;
+	for _ in 0..3 {
+		let block = client
+			.new_block_at(parent_hash, authored_by(1), false)
+			.unwrap()
+			.build()
+			.unwrap()
+			.block;
+		block_on(client.import(BlockOrigin::Own, block.clone())).unwrap();
+		parent_hash = block.hash();
+		b3 = Some(block);
+	}
+	let b3 = b3.unwrap();
+
+	assert_eq!(b3.hash(), block_on(chain_select.best_chain()).unwrap().hash());
+
+	client.finalize_block(a1.hash(), None).unwrap();
+
+	assert_eq!(a2.hash(), block_on(chain_select.best_chain()).unwrap().hash());
+	assert_eq!(a2.hash(), block_on(chain_select.finality_target(a1.hash(), None)).unwrap());
+}
+
 #[test]
 fn import_with_justification() {
 	// block tree:
@@ -1264,6 +1449,7 @@ fn doesnt_import_blocks_that_revert_finality() {
 				trie_cache_maximum_size: Some(1 << 20),
 				state_pruning: Some(PruningMode::ArchiveAll),
 				blocks_pruning: BlocksPruning::KeepAll,
+				offchain_indexing_pruning: OffchainIndexingPruning::KeepAll,
 				source: DatabaseSource::RocksDb { path: tmp.path().into(), cache_size: 1024 },
 			},
 			u64::MAX,
@@ -1518,6 +1704,7 @@ fn returns_status_for_pruned_blocks() {
 				trie_cache_maximum_size: Some(1 << 20),
 				state_pruning: Some(PruningMode::blocks_pruning(1)),
 				blocks_pruning: BlocksPruning::KeepFinalized,
+				offchain_indexing_pruning: OffchainIndexingPruning::KeepAll,
 				source: DatabaseSource::RocksDb { path: tmp.path().into(), cache_size: 1024 },
 			},
 			u64::MAX,
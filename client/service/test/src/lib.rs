@@ -245,6 +245,7 @@ fn node_config<
 		chain_spec: Box::new((*spec).clone()),
 		wasm_method: Default::default(),
 		wasm_runtime_overrides: Default::default(),
+		block_verification_workers: std::num::NonZeroUsize::MIN,
 		rpc_addr: Default::default(),
 		rpc_max_connections: Default::default(),
 		rpc_cors: None,
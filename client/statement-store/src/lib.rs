@@ -52,9 +52,10 @@ mod metrics;
 pub use sp_statement_store::{Error, StatementStore, MAX_TOPICS};
 
 use metrics::MetricsLink as PrometheusMetrics;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use prometheus_endpoint::Registry as PrometheusRegistry;
 use sc_keystore::LocalKeystore;
+use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedSender};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
 use sp_core::{crypto::UncheckedFrom, hexdisplay::HexDisplay, traits::SpawnNamed, Decode, Encode};
@@ -64,7 +65,7 @@ use sp_statement_store::{
 		InvalidStatement, StatementSource, StatementStoreExt, ValidStatement, ValidateStatement,
 	},
 	AccountId, BlockHash, Channel, DecryptionKey, Hash, NetworkPriority, Proof, Result, Statement,
-	SubmitResult, Topic,
+	StatementFilter, StatementStream, SubmitResult, Topic,
 };
 use std::{
 	collections::{BTreeMap, HashMap, HashSet},
@@ -80,6 +81,8 @@ const DEFAULT_PURGE_AFTER_SEC: u64 = 2 * 24 * 60 * 60; //48h
 const DEFAULT_MAX_TOTAL_STATEMENTS: usize = 8192;
 const DEFAULT_MAX_TOTAL_SIZE: usize = 64 * 1024 * 1024;
 
+const MAX_SUBSCRIPTIONS: usize = 1024;
+
 const MAINTENANCE_PERIOD: std::time::Duration = std::time::Duration::from_secs(30);
 
 mod col {
@@ -206,6 +209,8 @@ pub struct Store {
 	// Used for testing
 	time_override: Option<u64>,
 	metrics: PrometheusMetrics,
+	// Subscriptions to newly accepted statements.
+	subscribers: Mutex<Vec<(StatementFilter, TracingUnboundedSender<Statement>)>>,
 }
 
 enum IndexQuery {
@@ -573,6 +578,7 @@ impl Store {
 			keystore,
 			time_override: None,
 			metrics: PrometheusMetrics::new(prometheus),
+			subscribers: Mutex::new(Vec::new()),
 		};
 		store.populate()?;
 		Ok(store)
@@ -671,6 +677,7 @@ impl Store {
 	/// Perform periodic store maintenance
 	pub fn maintain(&self) {
 		log::trace!(target: LOG_TARGET, "Started store maintenance");
+		self.subscribers.lock().retain(|(_, sender)| !sender.is_closed());
 		let deleted = self.index.write().maintain(self.timestamp());
 		let deleted: Vec<_> =
 			deleted.into_iter().map(|hash| (col::EXPIRED, hash.to_vec(), None)).collect();
@@ -703,6 +710,16 @@ impl Store {
 		self.time_override = Some(time);
 	}
 
+	// Send a newly accepted statement to the matching subscribers and drop closed subscriptions.
+	fn notify_subscribers(&self, statement: &Statement) {
+		self.subscribers.lock().retain(|(filter, sender)| {
+			if !filter.matches(statement) {
+				return !sender.is_closed()
+			}
+			sender.unbounded_send(statement.clone()).is_ok()
+		});
+	}
+
 	/// Returns `self` as [`StatementStoreExt`].
 	pub fn as_statement_store_ext(self: Arc<Self>) -> StatementStoreExt {
 		StatementStoreExt::new(self)
@@ -897,6 +914,7 @@ impl StatementStore for Store {
 			}
 		} // Release index lock
 		self.metrics.report(|metrics| metrics.submitted_statements.inc());
+		self.notify_subscribers(&statement);
 		let network_priority = NetworkPriority::High;
 		log::trace!(target: LOG_TARGET, "Statement submitted: {:?}", HexDisplay::from(&hash));
 		SubmitResult::New(network_priority)
@@ -925,6 +943,20 @@ impl StatementStore for Store {
 		}
 		Ok(())
 	}
+
+	/// Subscribe to statements newly accepted by the store that match `filter`.
+	///
+	/// At most `MAX_SUBSCRIPTIONS` subscriptions may be active at the same time.
+	fn subscribe(&self, filter: StatementFilter) -> Result<StatementStream> {
+		let mut subscribers = self.subscribers.lock();
+		subscribers.retain(|(_, sender)| !sender.is_closed());
+		if subscribers.len() >= MAX_SUBSCRIPTIONS {
+			return Err(Error::TooManySubscriptions)
+		}
+		let (sender, receiver) = tracing_unbounded("mpsc_statement_store_subscription", 100_000);
+		subscribers.push((filter, sender));
+		Ok(Box::pin(receiver))
+	}
 }
 
 #[cfg(test)]
@@ -1293,4 +1325,66 @@ mod tests {
 		let posted_clear = store.posted_clear(&[], public.into()).unwrap();
 		assert_eq!(posted_clear, vec![plain]);
 	}
+
+	#[test]
+	fn subscriptions_receive_matching_statements() {
+		use sp_statement_store::{StatementFilter, StatementStream, TopicFilter};
+
+		let (store, _temp) = test_store();
+		let all = store
+			.subscribe(StatementFilter {
+				topics: TopicFilter::MatchAll(vec![topic(0), topic(1)]),
+				decryption_key: None,
+			})
+			.unwrap();
+		let any = store
+			.subscribe(StatementFilter {
+				topics: TopicFilter::MatchAny(vec![topic(1), topic(2)]),
+				decryption_key: None,
+			})
+			.unwrap();
+		let posted = store
+			.subscribe(StatementFilter {
+				topics: Default::default(),
+				decryption_key: Some(dec_key(0)),
+			})
+			.unwrap();
+
+		let statement0 = signed_statement_with_topics(0, &[topic(0)], None);
+		let statement1 = signed_statement_with_topics(1, &[topic(0), topic(1)], None);
+		let statement2 = signed_statement_with_topics(2, &[topic(2)], Some(dec_key(0)));
+		let statement3 = signed_statement_with_topics(3, &[topic(2)], None);
+		for statement in [&statement0, &statement1, &statement2, &statement3] {
+			store.submit(statement.clone(), StatementSource::Network);
+		}
+		// Known statements are not sent again.
+		store.submit(statement1.clone(), StatementSource::Network);
+		drop(store);
+
+		let collect = |stream: StatementStream| {
+			futures::executor::block_on_stream(stream).collect::<Vec<_>>()
+		};
+		assert_eq!(collect(all), vec![statement1.clone()]);
+		assert_eq!(collect(any), vec![statement1, statement3]);
+		assert_eq!(collect(posted), vec![statement2]);
+	}
+
+	#[test]
+	fn subscriptions_are_limited() {
+		let (store, _temp) = test_store();
+		let mut subscriptions = (0..crate::MAX_SUBSCRIPTIONS)
+			.map(|_| store.subscribe(Default::default()).unwrap())
+			.collect::<Vec<_>>();
+		assert_eq!(
+			store.subscribe(Default::default()).err(),
+			Some(sp_statement_store::Error::TooManySubscriptions)
+		);
+
+		// Closed subscriptions are pruned before the limit is checked.
+		subscriptions.pop();
+		assert!(store.subscribe(Default::default()).is_ok());
+		subscriptions.truncate(10);
+		store.maintain();
+		assert_eq!(store.subscribers.lock().len(), 10);
+	}
 }
@@ -0,0 +1,72 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks for the Aura Pallet.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::v1::benchmarks;
+use sp_consensus_aura::sr25519::AuthorityId;
+
+type Header = sp_runtime::generic::Header<u64, sp_runtime::traits::BlakeTwo256>;
+
+benchmarks! {
+	check_equivocation_proof {
+		let x in 0 .. 1;
+
+		// NOTE: generated with the test `generate_equivocation_report_blob`. the output is not
+		// deterministic since sr25519 signatures are randomized. it should not affect the
+		// benchmark.
+		const EQUIVOCATION_PROOF_BLOB: [u8; 406] = [
+			24, 157, 172, 41, 41, 109, 49, 129, 77, 200, 197, 108, 243, 211, 106, 5, 67, 55, 43,
+			186, 117, 56, 250, 50, 42, 74, 235, 254, 188, 57, 224, 86, 5, 0, 0, 0, 0, 0, 0, 0,
+			0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+			0, 0, 0, 0, 4, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+			1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+			0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 8, 6, 97, 117, 114, 97, 32, 5, 0, 0, 0, 0, 0,
+			0, 0, 5, 97, 117, 114, 97, 1, 1, 250, 106, 60, 254, 44, 159, 20, 154, 238, 181, 29,
+			214, 106, 250, 230, 107, 92, 202, 108, 199, 169, 180, 220, 191, 51, 97, 234, 107,
+			198, 209, 194, 83, 114, 39, 131, 119, 38, 40, 161, 80, 79, 25, 153, 178, 172, 233,
+			86, 213, 95, 253, 69, 72, 200, 148, 30, 35, 42, 243, 2, 177, 36, 152, 230, 138, 0,
+			0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+			0, 0, 0, 4, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+			2, 2, 2, 2, 2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+			0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 8, 6, 97, 117, 114, 97, 32, 5, 0, 0, 0, 0, 0, 0,
+			0, 5, 97, 117, 114, 97, 1, 1, 188, 236, 55, 11, 220, 19, 197, 33, 2, 105, 232, 34,
+			162, 191, 6, 85, 126, 255, 192, 39, 195, 88, 210, 242, 125, 228, 101, 82, 46, 106,
+			78, 114, 228, 152, 132, 108, 27, 61, 119, 14, 61, 246, 62, 99, 173, 95, 65, 200, 95,
+			100, 75, 68, 238, 93, 34, 95, 159, 106, 98, 50, 145, 126, 242, 142,
+		];
+
+		let equivocation_proof1: EquivocationProof<Header, AuthorityId> =
+			Decode::decode(&mut &EQUIVOCATION_PROOF_BLOB[..]).unwrap();
+		let authorities = sp_std::vec![equivocation_proof1.offender.clone()];
+
+		let equivocation_proof2 = equivocation_proof1.clone();
+	}: {
+		sp_consensus_aura::check_equivocation_proof(equivocation_proof1, &authorities);
+	} verify {
+		assert!(sp_consensus_aura::check_equivocation_proof(equivocation_proof2, &authorities));
+	}
+
+	impl_benchmark_test_suite!(
+		Pallet,
+		crate::mock::build_ext_with_authorities(vec![]),
+		crate::mock::Test,
+	)
+}
@@ -20,13 +20,20 @@
 #![cfg(test)]
 
 use crate as pallet_aura;
+use crate::EquivocationOffence;
 use frame_support::{
 	parameter_types,
-	traits::{ConstU32, ConstU64, DisabledValidators},
+	traits::{ConstU32, ConstU64, DisabledValidators, KeyOwnerProofSystem},
 };
-use sp_consensus_aura::{ed25519::AuthorityId, AuthorityIndex};
+use sp_consensus_aura::{ed25519::AuthorityId, AuthorityIndex, Slot};
 use sp_core::H256;
-use sp_runtime::{testing::UintAuthorityId, traits::IdentityLookup, BuildStorage};
+use sp_runtime::{
+	testing::{TestXt, UintAuthorityId},
+	traits::IdentityLookup,
+	BuildStorage, KeyTypeId,
+};
+use sp_session::MembershipProof;
+use sp_staking::offence::{OffenceError, ReportOffence};
 
 type Block = frame_system::mocking::MockBlock<Test>;
 
@@ -37,7 +44,8 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system::{Pallet, Call, Config<T>, Storage, Event<T>},
 		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
-		Aura: pallet_aura::{Pallet, Storage, Config<T>},
+		Authorship: pallet_authorship::{Pallet, Storage},
+		Aura: pallet_aura::{Pallet, Call, Storage, Config<T>, ValidateUnsigned},
 	}
 );
 
@@ -67,6 +75,19 @@ impl frame_system::Config for Test {
 	type MaxConsumers = frame_support::traits::ConstU32<16>;
 }
 
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Test
+where
+	RuntimeCall: From<C>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = TestXt<RuntimeCall, ()>;
+}
+
+impl pallet_authorship::Config for Test {
+	type FindAuthor = ();
+	type EventHandler = ();
+}
+
 impl pallet_timestamp::Config for Test {
 	type Moment = u64;
 	type OnTimestampSet = Aura;
@@ -97,6 +118,57 @@ impl DisabledValidators for MockDisabledValidators {
 	}
 }
 
+/// Key ownership proofs for the current authorities, identifying each by its index.
+pub struct MockKeyOwnership;
+
+impl KeyOwnerProofSystem<(KeyTypeId, AuthorityId)> for MockKeyOwnership {
+	type Proof = MembershipProof;
+	type IdentificationTuple = u64;
+
+	fn prove(key: (KeyTypeId, AuthorityId)) -> Option<Self::Proof> {
+		let authorities = Aura::authorities();
+		authorities.iter().position(|a| *a == key.1).map(|_| MembershipProof {
+			session: 0,
+			trie_nodes: vec![],
+			validator_count: authorities.len() as u32,
+		})
+	}
+
+	fn check_proof(key: (KeyTypeId, AuthorityId), proof: Self::Proof) -> Option<u64> {
+		if proof.session != 0 {
+			return None
+		}
+		Aura::authorities().iter().position(|a| *a == key.1).map(|i| i as u64)
+	}
+}
+
+parameter_types! {
+	/// The reporters, offender and slot of each reported offence.
+	pub static Offences: Vec<(Vec<u64>, u64, Slot)> = vec![];
+}
+
+/// Records reported offences.
+pub struct OffenceHandler;
+
+impl ReportOffence<u64, u64, EquivocationOffence<u64>> for OffenceHandler {
+	fn report_offence(
+		reporters: Vec<u64>,
+		offence: EquivocationOffence<u64>,
+	) -> Result<(), OffenceError> {
+		if Self::is_known_offence(&[offence.offender], &offence.slot) {
+			return Err(OffenceError::DuplicateReport)
+		}
+		Offences::mutate(|o| o.push((reporters, offence.offender, offence.slot)));
+		Ok(())
+	}
+
+	fn is_known_offence(offenders: &[u64], time_slot: &Slot) -> bool {
+		Offences::get()
+			.iter()
+			.any(|(_, offender, slot)| slot == time_slot && offenders.contains(offender))
+	}
+}
+
 impl pallet_aura::Config for Test {
 	type AuthorityId = AuthorityId;
 	type DisabledValidators = MockDisabledValidators;
@@ -105,15 +177,25 @@ impl pallet_aura::Config for Test {
 
 	#[cfg(feature = "experimental")]
 	type SlotDuration = ConstU64<SLOT_DURATION>;
+
+	type WeightInfo = ();
+	type MaxNominators = ConstU32<0>;
+	type KeyOwnerProof = MembershipProof;
+	type EquivocationReportSystem =
+		crate::EquivocationReportSystem<Self, OffenceHandler, MockKeyOwnership, ConstU64<10>>;
 }
 
 fn build_ext(authorities: Vec<u64>) -> sp_io::TestExternalities {
+	build_ext_with_authorities(
+		authorities.into_iter().map(|a| UintAuthorityId(a).to_public_key()).collect(),
+	)
+}
+
+pub fn build_ext_with_authorities(authorities: Vec<AuthorityId>) -> sp_io::TestExternalities {
 	let mut storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
-	pallet_aura::GenesisConfig::<Test> {
-		authorities: authorities.into_iter().map(|a| UintAuthorityId(a).to_public_key()).collect(),
-	}
-	.assimilate_storage(&mut storage)
-	.unwrap();
+	pallet_aura::GenesisConfig::<Test> { authorities }
+		.assimilate_storage(&mut storage)
+		.unwrap();
 	storage.into()
 }
 
@@ -19,11 +19,30 @@
 
 #![cfg(test)]
 
-use crate::mock::{build_ext_and_execute_test, Aura, MockDisabledValidators, System};
+use crate::{
+	mock::{
+		build_ext_and_execute_test, build_ext_with_authorities, Aura, MockDisabledValidators,
+		MockKeyOwnership, Offences, RuntimeOrigin, System, Test,
+	},
+	Call, Error,
+};
 use codec::Encode;
-use frame_support::traits::OnInitialize;
-use sp_consensus_aura::{Slot, AURA_ENGINE_ID};
-use sp_runtime::{Digest, DigestItem};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{KeyOwnerProofSystem, OnInitialize},
+};
+use frame_system::pallet_prelude::HeaderFor;
+use sp_consensus_aura::{
+	digests::CompatibleDigestItem,
+	ed25519::{AuthorityId, AuthorityPair, AuthoritySignature},
+	EquivocationProof, Slot, AURA_ENGINE_ID,
+};
+use sp_core::{crypto::key_types::AURA, Pair, H256};
+use sp_runtime::{
+	traits::{Header as _, ValidateUnsigned},
+	transaction_validity::{InvalidTransaction, TransactionPriority, TransactionSource},
+	Digest, DigestItem,
+};
 
 #[test]
 fn initial_values() {
@@ -115,3 +134,219 @@ fn pallet_always_rejects_decreasing_slot() {
 		Aura::on_initialize(43);
 	});
 }
+
+fn authority_pairs(n: u8) -> Vec<AuthorityPair> {
+	(0..n).map(|i| AuthorityPair::from_seed(&[i; 32])).collect()
+}
+
+fn go_to_slot(number: u64, slot: u64) {
+	let pre_digest = Digest {
+		logs: vec![<DigestItem as CompatibleDigestItem<AuthoritySignature>>::aura_pre_digest(
+			slot.into(),
+		)],
+	};
+	System::reset_events();
+	System::initialize(&number, &System::parent_hash(), &pre_digest);
+	Aura::on_initialize(number);
+}
+
+fn sealed_header(pair: &AuthorityPair, slot: u64, salt: u8) -> HeaderFor<Test> {
+	let mut header = HeaderFor::<Test>::new(
+		1,
+		Default::default(),
+		H256::repeat_byte(salt),
+		System::parent_hash(),
+		Digest {
+			logs: vec![<DigestItem as CompatibleDigestItem<AuthoritySignature>>::aura_pre_digest(
+				slot.into(),
+			)],
+		},
+	);
+	let signature = pair.sign(header.hash().as_ref());
+	header.digest_mut().push(DigestItem::aura_seal(signature));
+	header
+}
+
+fn equivocation_proof(
+	pair: &AuthorityPair,
+	slot: u64,
+) -> EquivocationProof<HeaderFor<Test>, AuthorityId> {
+	EquivocationProof {
+		offender: pair.public(),
+		slot: slot.into(),
+		first_header: sealed_header(pair, slot, 1),
+		second_header: sealed_header(pair, slot, 2),
+	}
+}
+
+#[test]
+fn report_equivocation_works() {
+	let pairs = authority_pairs(4);
+	build_ext_with_authorities(pairs.iter().map(|p| p.public()).collect()).execute_with(|| {
+		go_to_slot(1, 5);
+
+		let proof = equivocation_proof(&pairs[1], 5);
+		let key_owner_proof = MockKeyOwnership::prove((AURA, pairs[1].public())).unwrap();
+
+		assert_ok!(Aura::report_equivocation_unsigned(
+			RuntimeOrigin::none(),
+			Box::new(proof.clone()),
+			key_owner_proof.clone(),
+		));
+		assert_eq!(Offences::get(), vec![(vec![], 1, Slot::from(5))]);
+
+		assert_noop!(
+			Aura::report_equivocation_unsigned(
+				RuntimeOrigin::none(),
+				Box::new(proof),
+				key_owner_proof,
+			),
+			Error::<Test>::DuplicateOffenceReport,
+		);
+	});
+}
+
+#[test]
+fn report_equivocation_invalid_equivocation_proof() {
+	let pairs = authority_pairs(4);
+	build_ext_with_authorities(pairs.iter().map(|p| p.public()).collect()).execute_with(|| {
+		go_to_slot(1, 5);
+
+		let key_owner_proof = MockKeyOwnership::prove((AURA, pairs[1].public())).unwrap();
+		let assert_invalid_equivocation_proof = |proof| {
+			assert_noop!(
+				Aura::report_equivocation_unsigned(
+					RuntimeOrigin::none(),
+					Box::new(proof),
+					key_owner_proof.clone(),
+				),
+				Error::<Test>::InvalidEquivocationProof,
+			);
+		};
+
+		// both headers are the same
+		let mut proof = equivocation_proof(&pairs[1], 5);
+		proof.second_header = proof.first_header.clone();
+		assert_invalid_equivocation_proof(proof);
+
+		// the headers are for different slots
+		let mut proof = equivocation_proof(&pairs[1], 5);
+		proof.second_header = sealed_header(&pairs[1], 4, 2);
+		assert_invalid_equivocation_proof(proof);
+
+		// one of the headers is signed by someone else
+		let mut proof = equivocation_proof(&pairs[1], 5);
+		proof.second_header = sealed_header(&pairs[2], 5, 2);
+		assert_invalid_equivocation_proof(proof);
+
+		// the offender isn't the author of the slot
+		assert_invalid_equivocation_proof(equivocation_proof(&pairs[2], 5));
+
+		// the slot is yet to come
+		assert_invalid_equivocation_proof(equivocation_proof(&pairs[1], 6));
+
+		assert!(Offences::get().is_empty());
+	});
+}
+
+#[test]
+fn report_equivocation_invalid_key_owner_proof() {
+	let pairs = authority_pairs(4);
+	build_ext_with_authorities(pairs.iter().map(|p| p.public()).collect()).execute_with(|| {
+		go_to_slot(1, 5);
+
+		let mut key_owner_proof = MockKeyOwnership::prove((AURA, pairs[1].public())).unwrap();
+		key_owner_proof.session = 1;
+
+		assert_noop!(
+			Aura::report_equivocation_unsigned(
+				RuntimeOrigin::none(),
+				Box::new(equivocation_proof(&pairs[1], 5)),
+				key_owner_proof,
+			),
+			Error::<Test>::InvalidKeyOwnershipProof,
+		);
+	});
+}
+
+#[test]
+fn report_equivocation_validate_unsigned_works() {
+	let pairs = authority_pairs(4);
+	build_ext_with_authorities(pairs.iter().map(|p| p.public()).collect()).execute_with(|| {
+		go_to_slot(1, 5);
+
+		let proof = equivocation_proof(&pairs[1], 5);
+		let key_owner_proof = MockKeyOwnership::prove((AURA, pairs[1].public())).unwrap();
+		let call = Call::report_equivocation_unsigned {
+			equivocation_proof: Box::new(proof.clone()),
+			key_owner_proof: key_owner_proof.clone(),
+		};
+
+		// only local/inblock reports are allowed
+		assert_eq!(
+			<Aura as ValidateUnsigned>::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::Call.into(),
+		);
+
+		let valid =
+			<Aura as ValidateUnsigned>::validate_unsigned(TransactionSource::Local, &call).unwrap();
+		assert_eq!(valid.priority, TransactionPriority::max_value());
+		assert_eq!(valid.longevity, 10);
+		assert!(!valid.propagate);
+
+		assert_ok!(<Aura as ValidateUnsigned>::pre_dispatch(&call));
+
+		// once the offence is reported the report becomes stale
+		assert_ok!(Aura::report_equivocation_unsigned(
+			RuntimeOrigin::none(),
+			Box::new(proof),
+			key_owner_proof,
+		));
+		assert_eq!(
+			<Aura as ValidateUnsigned>::validate_unsigned(TransactionSource::Local, &call),
+			InvalidTransaction::Stale.into(),
+		);
+	});
+}
+
+#[test]
+fn generate_equivocation_report_blob() {
+	use sp_consensus_aura::sr25519::{AuthorityPair, AuthoritySignature};
+
+	let pair = AuthorityPair::from_seed(&[1; 32]);
+	let slot = 5;
+
+	let sealed_header = |salt: u8| {
+		let mut header = HeaderFor::<Test>::new(
+			1,
+			Default::default(),
+			H256::repeat_byte(salt),
+			Default::default(),
+			Digest {
+				logs: vec![
+					<DigestItem as CompatibleDigestItem<AuthoritySignature>>::aura_pre_digest(
+						slot.into(),
+					),
+				],
+			},
+		);
+		let signature = pair.sign(header.hash().as_ref());
+		header.digest_mut().push(DigestItem::aura_seal(signature));
+		header
+	};
+
+	let equivocation_proof = EquivocationProof {
+		offender: pair.public(),
+		slot: slot.into(),
+		first_header: sealed_header(1),
+		second_header: sealed_header(2),
+	};
+
+	assert!(sp_consensus_aura::check_equivocation_proof(
+		equivocation_proof.clone(),
+		&[equivocation_proof.offender.clone()],
+	));
+
+	println!("equivocation_proof: {:?}", equivocation_proof);
+	println!("equivocation_proof.encode(): {:?}", equivocation_proof.encode());
+}
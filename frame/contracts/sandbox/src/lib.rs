@@ -0,0 +1,376 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Contracts Sandbox
+//!
+//! A test harness for executing contracts against `pallet-contracts` without running a node.
+//!
+//! The [`Sandbox`] wraps a [`sp_io::TestExternalities`] that holds the state of a minimal
+//! [`runtime::Runtime`] consisting of `frame-system`, `pallet-balances`, `pallet-timestamp` and
+//! `pallet-contracts`. It allows tests to:
+//!
+//! - upload, instantiate and call Wasm blobs;
+//! - advance the chain by one or more blocks, running the hooks of all pallets;
+//! - inspect the emitted events, balances and contract storage;
+//! - take a [`Snapshot`] of the state and restore it later.
+//!
+//! All of this happens within a plain `cargo test`:
+//!
+//! ```ignore
+//! use pallet_contracts_sandbox::{Sandbox, ALICE};
+//!
+//! let mut sandbox = Sandbox::default();
+//! let contract = sandbox.deploy(ALICE, wasm, vec![], vec![], 0).result.unwrap().account_id;
+//! let snapshot = sandbox.snapshot();
+//! assert!(sandbox.call(ALICE, contract.clone(), 0, vec![]).result.is_ok());
+//! sandbox.advance_blocks(10);
+//! sandbox.restore(&snapshot);
+//! ```
+
+pub mod runtime;
+
+#[cfg(test)]
+mod tests;
+
+use crate::runtime::{
+	AccountId, AllPalletsWithSystem, Balance, Balances, BlockNumber, Contracts, Moment, Runtime,
+	RuntimeEvent, System, Timestamp, BLOCK_TIME,
+};
+use frame_support::{
+	traits::{fungible::Mutate, OnFinalize, OnIdle, OnInitialize},
+	weights::Weight,
+};
+use frame_system::EventRecord;
+use pallet_contracts::{CollectEvents, DebugInfo, Determinism};
+use pallet_contracts_primitives::{
+	Code, CodeUploadResult, ContractExecResult, ContractInstantiateResult, GetStorageResult,
+};
+use sp_core::{Blake2Hasher, H256};
+use sp_runtime::{traits::Header as _, BuildStorage};
+use sp_state_machine::InMemoryBackend;
+
+pub use runtime::EXISTENTIAL_DEPOSIT;
+
+/// The account endowed by default and used as the origin in most tests.
+pub const ALICE: AccountId = AccountId::new([1u8; 32]);
+
+/// A second account endowed by default.
+pub const BOB: AccountId = AccountId::new([2u8; 32]);
+
+/// The balance every account passed to [`SandboxBuilder::with_balances`] starts with by default.
+pub const INITIAL_BALANCE: Balance = 1_000_000_000_000_000;
+
+/// The gas limit used for contract executions unless overwritten with
+/// [`Sandbox::set_gas_limit`].
+pub const DEFAULT_GAS_LIMIT: Weight = Weight::from_parts(100_000_000_000, 3 * 1024 * 1024);
+
+/// The event record type of the sandbox runtime.
+pub type EventRecordOf = EventRecord<RuntimeEvent, H256>;
+
+/// Result of uploading code to the sandbox.
+pub type UploadResult = CodeUploadResult<H256, Balance>;
+
+/// Result of instantiating a contract in the sandbox.
+pub type InstantiateResult = ContractInstantiateResult<AccountId, Balance, EventRecordOf>;
+
+/// Result of calling a contract in the sandbox.
+pub type CallResult = ContractExecResult<Balance, EventRecordOf>;
+
+/// A copy of the complete state of a [`Sandbox`].
+///
+/// Obtained by [`Sandbox::snapshot`] and applied with [`Sandbox::restore`]. Restoring a snapshot
+/// is cheap because the trie does not need to be recomputed.
+#[derive(Clone)]
+pub struct Snapshot(InMemoryBackend<Blake2Hasher>);
+
+/// Builder for a [`Sandbox`] with a custom genesis.
+pub struct SandboxBuilder {
+	balances: Vec<(AccountId, Balance)>,
+	gas_limit: Weight,
+	storage_deposit_limit: Option<Balance>,
+}
+
+impl Default for SandboxBuilder {
+	fn default() -> Self {
+		Self {
+			balances: vec![(ALICE, INITIAL_BALANCE), (BOB, INITIAL_BALANCE)],
+			gas_limit: DEFAULT_GAS_LIMIT,
+			storage_deposit_limit: None,
+		}
+	}
+}
+
+impl SandboxBuilder {
+	/// Replace the endowed accounts of the genesis.
+	pub fn with_balances(mut self, balances: Vec<(AccountId, Balance)>) -> Self {
+		self.balances = balances;
+		self
+	}
+
+	/// Set the gas limit used for all contract executions.
+	pub fn with_gas_limit(mut self, gas_limit: Weight) -> Self {
+		self.gas_limit = gas_limit;
+		self
+	}
+
+	/// Set the storage deposit limit used for all contract executions.
+	pub fn with_storage_deposit_limit(mut self, limit: Option<Balance>) -> Self {
+		self.storage_deposit_limit = limit;
+		self
+	}
+
+	/// Build the genesis state and start the chain at block `1`.
+	pub fn build(self) -> Sandbox {
+		let mut storage = frame_system::GenesisConfig::<Runtime>::default()
+			.build_storage()
+			.expect("The genesis of the sandbox runtime is valid; qed");
+		pallet_balances::GenesisConfig::<Runtime> { balances: self.balances }
+			.assimilate_storage(&mut storage)
+			.expect("The genesis of the sandbox runtime is valid; qed");
+
+		let mut sandbox = Sandbox {
+			ext: sp_io::TestExternalities::new(storage),
+			gas_limit: self.gas_limit,
+			storage_deposit_limit: self.storage_deposit_limit,
+		};
+		sandbox.execute_with(|| {
+			use frame_support::traits::OnGenesis;
+
+			Contracts::on_genesis();
+			initialize_block(1, Default::default());
+		});
+		sandbox
+	}
+}
+
+/// A self contained chain state to execute contracts against.
+///
+/// Every method runs in its own externalities context and commits its changes afterwards, so
+/// that the state can be snapshotted at any point in between.
+pub struct Sandbox {
+	ext: sp_io::TestExternalities,
+	gas_limit: Weight,
+	storage_deposit_limit: Option<Balance>,
+}
+
+impl Default for Sandbox {
+	fn default() -> Self {
+		Self::builder().build()
+	}
+}
+
+impl Sandbox {
+	/// Create a builder to configure the genesis of a new sandbox.
+	pub fn builder() -> SandboxBuilder {
+		SandboxBuilder::default()
+	}
+
+	/// Execute `f` with the state of the sandbox set as externalities.
+	///
+	/// This can be used to access any storage or pallet function of the [`runtime::Runtime`]
+	/// directly.
+	pub fn execute_with<R>(&mut self, f: impl FnOnce() -> R) -> R {
+		let result = self.ext.execute_with(f);
+		self.ext.commit_all().expect("No storage transaction is left open; qed");
+		result
+	}
+
+	/// Set the gas limit used for all subsequent contract executions.
+	pub fn set_gas_limit(&mut self, gas_limit: Weight) {
+		self.gas_limit = gas_limit;
+	}
+
+	/// Set the storage deposit limit used for all subsequent contract executions.
+	pub fn set_storage_deposit_limit(&mut self, limit: Option<Balance>) {
+		self.storage_deposit_limit = limit;
+	}
+
+	/// Upload `code` without instantiating it.
+	pub fn upload_code(&mut self, origin: AccountId, code: Vec<u8>) -> UploadResult {
+		let storage_deposit_limit = self.storage_deposit_limit;
+		self.execute_with(|| {
+			Contracts::bare_upload_code(origin, code, storage_deposit_limit, Determinism::Enforced)
+		})
+	}
+
+	/// Upload `code` and instantiate a contract from it.
+	pub fn deploy(
+		&mut self,
+		origin: AccountId,
+		code: Vec<u8>,
+		data: Vec<u8>,
+		salt: Vec<u8>,
+		value: Balance,
+	) -> InstantiateResult {
+		self.instantiate_with_code(origin, Code::Upload(code), data, salt, value)
+	}
+
+	/// Instantiate a contract from code that was uploaded before.
+	pub fn instantiate(
+		&mut self,
+		origin: AccountId,
+		code_hash: H256,
+		data: Vec<u8>,
+		salt: Vec<u8>,
+		value: Balance,
+	) -> InstantiateResult {
+		self.instantiate_with_code(origin, Code::Existing(code_hash), data, salt, value)
+	}
+
+	fn instantiate_with_code(
+		&mut self,
+		origin: AccountId,
+		code: Code<H256>,
+		data: Vec<u8>,
+		salt: Vec<u8>,
+		value: Balance,
+	) -> InstantiateResult {
+		let (gas_limit, storage_deposit_limit) = (self.gas_limit, self.storage_deposit_limit);
+		self.execute_with(|| {
+			Contracts::bare_instantiate(
+				origin,
+				value,
+				gas_limit,
+				storage_deposit_limit,
+				code,
+				data,
+				salt,
+				DebugInfo::UnsafeDebug,
+				CollectEvents::UnsafeCollect,
+			)
+		})
+	}
+
+	/// Call the contract at `dest`.
+	pub fn call(
+		&mut self,
+		origin: AccountId,
+		dest: AccountId,
+		value: Balance,
+		data: Vec<u8>,
+	) -> CallResult {
+		let (gas_limit, storage_deposit_limit) = (self.gas_limit, self.storage_deposit_limit);
+		self.execute_with(|| {
+			Contracts::bare_call(
+				origin,
+				dest,
+				value,
+				gas_limit,
+				storage_deposit_limit,
+				data,
+				DebugInfo::UnsafeDebug,
+				CollectEvents::UnsafeCollect,
+				Determinism::Enforced,
+			)
+		})
+	}
+
+	/// Read the value stored under `key` in the storage of the contract at `address`.
+	pub fn get_storage(&mut self, address: AccountId, key: Vec<u8>) -> GetStorageResult {
+		self.execute_with(|| Contracts::get_storage(address, key))
+	}
+
+	/// Finalize the current block and initialize the next one.
+	///
+	/// The `on_idle` hooks of the finalized block get the full block weight which makes sure
+	/// that the deletion queue of `pallet-contracts` is processed. The timestamp of the new
+	/// block is [`runtime::BLOCK_TIME`] after the one of the finalized block.
+	pub fn advance_block(&mut self) {
+		self.execute_with(|| {
+			let now = System::block_number();
+			let max_weight = <Runtime as frame_system::Config>::BlockWeights::get().max_block;
+			let remaining_weight = max_weight.saturating_sub(System::block_weight().total());
+			AllPalletsWithSystem::on_idle(now, remaining_weight);
+			AllPalletsWithSystem::on_finalize(now);
+			let parent_hash = System::finalize().hash();
+			initialize_block(now + 1, parent_hash);
+		})
+	}
+
+	/// Advance the chain by `n` blocks.
+	pub fn advance_blocks(&mut self, n: BlockNumber) {
+		for _ in 0..n {
+			self.advance_block();
+		}
+	}
+
+	/// The number of the block currently being built.
+	pub fn block_number(&mut self) -> BlockNumber {
+		self.execute_with(System::block_number)
+	}
+
+	/// The timestamp of the block currently being built.
+	pub fn timestamp(&mut self) -> Moment {
+		self.execute_with(Timestamp::get)
+	}
+
+	/// All events emitted in the current block.
+	pub fn events(&mut self) -> Vec<EventRecordOf> {
+		self.execute_with(System::events)
+	}
+
+	/// The data of all events emitted by the contract at `address` in the current block.
+	pub fn contract_events(&mut self, address: &AccountId) -> Vec<Vec<u8>> {
+		self.events()
+			.into_iter()
+			.filter_map(|record| match record.event {
+				RuntimeEvent::Contracts(pallet_contracts::Event::ContractEmitted {
+					contract,
+					data,
+				}) if &contract == address => Some(data),
+				_ => None,
+			})
+			.collect()
+	}
+
+	/// Remove all events emitted so far in the current block.
+	pub fn reset_events(&mut self) {
+		self.execute_with(System::reset_events)
+	}
+
+	/// The free balance of `who`.
+	pub fn free_balance(&mut self, who: &AccountId) -> Balance {
+		self.execute_with(|| Balances::free_balance(who))
+	}
+
+	/// Set the free balance of `who` to `amount`.
+	pub fn set_balance(&mut self, who: &AccountId, amount: Balance) {
+		self.execute_with(|| {
+			let _ = Balances::set_balance(who, amount);
+		})
+	}
+
+	/// Take a copy of the current state.
+	pub fn snapshot(&mut self) -> Snapshot {
+		Snapshot(self.ext.backend.clone())
+	}
+
+	/// Reset the state to the given `snapshot`.
+	///
+	/// Changes made after the snapshot was taken are discarded.
+	pub fn restore(&mut self, snapshot: &Snapshot) {
+		self.ext.backend = snapshot.0.clone();
+	}
+}
+
+/// Start building block `number` on top of `parent_hash`.
+fn initialize_block(number: BlockNumber, parent_hash: H256) {
+	System::initialize(&number, &parent_hash, &Default::default());
+	AllPalletsWithSystem::on_initialize(number);
+	Timestamp::set_timestamp(number * BLOCK_TIME);
+	System::note_finished_initialize();
+}
@@ -0,0 +1,98 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{runtime::BLOCK_TIME, Sandbox, ALICE, BOB};
+use pallet_contracts_primitives::ContractAccessError;
+
+/// Load a fixture of `pallet-contracts` and compile it to Wasm.
+fn compile_fixture(fixture_name: &str) -> Vec<u8> {
+	let fixture_path = [env!("CARGO_MANIFEST_DIR"), "/../fixtures/", fixture_name, ".wat"].concat();
+	wat::parse_file(fixture_path).expect("Fixtures are valid wat")
+}
+
+#[test]
+fn deploy_and_call_works() {
+	let mut sandbox = Sandbox::default();
+	let wasm = compile_fixture("dummy");
+
+	let result = sandbox.deploy(ALICE, wasm, vec![], vec![], 0);
+	let contract = result.result.expect("Instantiation succeeds").account_id;
+
+	let result = sandbox.call(BOB, contract.clone(), 0, vec![]);
+	assert!(!result.result.expect("Call succeeds").did_revert());
+	assert_eq!(sandbox.get_storage(contract, vec![1, 2, 3]), Ok(None));
+	assert_eq!(sandbox.get_storage(BOB, vec![1, 2, 3]), Err(ContractAccessError::DoesntExist));
+}
+
+#[test]
+fn upload_and_instantiate_works() {
+	let mut sandbox = Sandbox::default();
+	let wasm = compile_fixture("dummy");
+
+	let code_hash = sandbox.upload_code(ALICE, wasm).expect("Upload succeeds").code_hash;
+	let first = sandbox.instantiate(ALICE, code_hash, vec![], vec![0], 0);
+	let second = sandbox.instantiate(ALICE, code_hash, vec![], vec![1], 0);
+
+	assert_ne!(first.result.unwrap().account_id, second.result.unwrap().account_id);
+}
+
+#[test]
+fn contract_events_are_collected() {
+	let mut sandbox = Sandbox::default();
+	let wasm = compile_fixture("event_and_return_on_deploy");
+
+	let result = sandbox.deploy(ALICE, wasm, vec![], vec![], 0);
+	let contract = result.result.unwrap().account_id;
+
+	assert_eq!(sandbox.contract_events(&contract), vec![vec![1, 2, 3, 4]]);
+	sandbox.reset_events();
+	assert!(sandbox.events().is_empty());
+}
+
+#[test]
+fn advancing_blocks_works() {
+	let mut sandbox = Sandbox::default();
+	assert_eq!(sandbox.block_number(), 1);
+	assert_eq!(sandbox.timestamp(), BLOCK_TIME);
+
+	let wasm = compile_fixture("dummy");
+	sandbox.deploy(ALICE, wasm, vec![], vec![], 0).result.unwrap();
+	assert!(!sandbox.events().is_empty());
+
+	sandbox.advance_blocks(5);
+	assert_eq!(sandbox.block_number(), 6);
+	assert_eq!(sandbox.timestamp(), 6 * BLOCK_TIME);
+	// Events are reset for every new block.
+	assert!(sandbox.events().is_empty());
+}
+
+#[test]
+fn snapshot_and_restore_works() {
+	let mut sandbox = Sandbox::default();
+	let alice_balance = sandbox.free_balance(&ALICE);
+	let snapshot = sandbox.snapshot();
+
+	let wasm = compile_fixture("dummy");
+	let contract = sandbox.deploy(ALICE, wasm, vec![], vec![], 1_000).result.unwrap().account_id;
+	sandbox.advance_blocks(3);
+	assert!(sandbox.free_balance(&ALICE) < alice_balance);
+
+	sandbox.restore(&snapshot);
+	assert_eq!(sandbox.block_number(), 1);
+	assert_eq!(sandbox.free_balance(&ALICE), alice_balance);
+	assert_eq!(sandbox.get_storage(contract, vec![]), Err(ContractAccessError::DoesntExist));
+}
@@ -31,7 +31,7 @@ use self::{
 use crate::{
 	exec::{AccountIdOf, Key},
 	migration::{
-		codegen::LATEST_MIGRATION_VERSION, v09, v10, v11, v12, v13, v14, v15, MigrationStep,
+		codegen::LATEST_MIGRATION_VERSION, v09, v10, v11, v12, v13, v14, v15, v16, MigrationStep,
 	},
 	wasm::CallFlags,
 	Pallet as Contracts, *,
@@ -311,6 +311,19 @@ benchmarks! {
 		m.step();
 	}
 
+	// This benchmarks the v16 migration step (add deposit payers).
+	#[pov_mode = Measured]
+	v16_migration_step {
+		let contract = <Contract<T>>::with_caller(
+			whitelisted_caller(), WasmModule::dummy(), vec![],
+		)?;
+
+		v16::store_old_contract_info::<T>(contract.account_id.clone(), contract.info()?);
+		let mut m = v16::Migration::<T>::default();
+	}: {
+		m.step();
+	}
+
 	// This benchmarks the weight of executing Migration::migrate to execute a noop migration.
 	#[pov_mode = Measured]
 	migration_noop {
@@ -534,6 +547,39 @@ benchmarks! {
 		assert_eq!(instance.info()?.code_hash, hash);
 	}
 
+	#[pov_mode = Measured]
+	set_deposit_payer {
+		let instance = <Contract<T>>::with_caller(
+			whitelisted_caller(), WasmModule::dummy(), vec![],
+		)?;
+		let sponsor: T::AccountId = account("sponsor", 0, 0);
+		<SponsorAllowance<T>>::insert(&sponsor, &instance.account_id, caller_funding::<T>());
+		let payer = Some(DepositPayer::Sponsor(sponsor.clone()));
+		let origin = RawOrigin::Signed(instance.account_id.clone());
+	}: _(origin, payer)
+	verify {
+		assert_eq!(
+			<DepositPayerOf<T>>::get(&instance.account_id),
+			Some(DepositPayer::Sponsor(sponsor)),
+		);
+	}
+
+	#[pov_mode = Measured]
+	approve_deposit_sponsorship {
+		let instance = <Contract<T>>::with_caller(
+			whitelisted_caller(), WasmModule::dummy(), vec![],
+		)?;
+		let sponsor: T::AccountId = account("sponsor", 0, 0);
+		let origin = RawOrigin::Signed(sponsor.clone());
+		let contract = instance.addr.clone();
+	}: _(origin, contract, caller_funding::<T>())
+	verify {
+		assert_eq!(
+			<SponsorAllowance<T>>::get(&sponsor, &instance.account_id),
+			Some(caller_funding::<T>()),
+		);
+	}
+
 	#[pov_mode = Measured]
 	seal_caller {
 		let r in 0 .. API_BENCHMARK_RUNS;
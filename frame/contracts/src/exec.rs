@@ -20,8 +20,8 @@ use crate::{
 	gas::GasMeter,
 	storage::{self, meter::Diff, WriteOutcome},
 	BalanceOf, CodeHash, CodeInfo, CodeInfoOf, Config, ContractInfo, ContractInfoOf,
-	DebugBufferVec, Determinism, Error, Event, Nonce, Origin, Pallet as Contracts, Schedule,
-	WasmBlob, LOG_TARGET,
+	DebugBufferVec, DepositPayer, DepositPayerOf, Determinism, Error, Event, Nonce, Origin,
+	Pallet as Contracts, Schedule, SponsorAllowance, WasmBlob, LOG_TARGET,
 };
 use frame_support::{
 	crypto::ecdsa::ECDSAExt,
@@ -827,6 +827,7 @@ where
 			return Err(Error::<T>::Indeterministic.into())
 		}
 
+		let nested_storage = storage_meter.nested(deposit_limit).with_payer_of(&account_id);
 		let frame = Frame {
 			delegate_caller,
 			value_transferred,
@@ -834,7 +835,7 @@ where
 			account_id,
 			entry_point,
 			nested_gas: gas_meter.nested(gas_limit)?,
-			nested_storage: storage_meter.nested(deposit_limit),
+			nested_storage,
 			allows_reentry: true,
 		};
 
@@ -1279,18 +1280,33 @@ where
 			return Err(Error::<T>::TerminatedWhileReentrant.into())
 		}
 		let frame = self.top_frame_mut();
-		let info = frame.terminate();
-		frame.nested_storage.terminate(&info, beneficiary.clone());
+		let mut info = frame.terminate();
+
+		// Sponsorships end with the contract. This needs to happen before the recorded payers are
+		// refunded below.
+		let payer = DepositPayerOf::<T>::take(&frame.account_id);
+		let sponsors = payer.iter().chain(info.deposit_payers().iter().map(|(payer, _)| payer));
+		for payer in sponsors {
+			if let DepositPayer::Sponsor(sponsor) = payer {
+				SponsorAllowance::<T>::remove(sponsor, &frame.account_id);
+			}
+		}
+
+		frame
+			.nested_storage
+			.terminate(&frame.account_id, &mut info, beneficiary.clone());
 
 		info.queue_trie_for_deletion();
 		ContractInfoOf::<T>::remove(&frame.account_id);
 		E::decrement_refcount(info.code_hash);
 
-		for (code_hash, deposit) in info.delegate_dependencies() {
-			E::decrement_refcount(*code_hash);
-			frame
-				.nested_storage
-				.charge_deposit(frame.account_id.clone(), StorageDeposit::Refund(*deposit));
+		for (code_hash, deposit) in info.delegate_dependencies().clone() {
+			E::decrement_refcount(code_hash);
+			frame.nested_storage.charge_deposit(
+				frame.account_id.clone(),
+				StorageDeposit::Refund(deposit),
+				&mut info,
+			);
 		}
 
 		Contracts::<T>::deposit_event(
@@ -1472,7 +1488,7 @@ where
 			return Err(<Error<T>>::Indeterministic.into())
 		}
 
-		let info = frame.contract_info();
+		let info = frame.contract_info.get(&frame.account_id);
 
 		let prev_hash = info.code_hash;
 		info.code_hash = hash;
@@ -1484,7 +1500,7 @@ where
 		let deposit = StorageDeposit::Charge(new_base_deposit)
 			.saturating_sub(&StorageDeposit::Charge(old_base_deposit));
 
-		frame.nested_storage.charge_deposit(frame.account_id.clone(), deposit);
+		frame.nested_storage.charge_deposit(frame.account_id.clone(), deposit, info);
 
 		E::increment_refcount(hash)?;
 		E::decrement_refcount(prev_hash);
@@ -1533,9 +1549,11 @@ where
 
 		info.add_delegate_dependency(code_hash, deposit)?;
 		<WasmBlob<T>>::increment_refcount(code_hash)?;
-		frame
-			.nested_storage
-			.charge_deposit(frame.account_id.clone(), StorageDeposit::Charge(deposit));
+		frame.nested_storage.charge_deposit(
+			frame.account_id.clone(),
+			StorageDeposit::Charge(deposit),
+			info,
+		);
 		Ok(())
 	}
 
@@ -1549,9 +1567,11 @@ where
 		let deposit = info.remove_delegate_dependency(code_hash)?;
 		<WasmBlob<T>>::decrement_refcount(*code_hash);
 
-		frame
-			.nested_storage
-			.charge_deposit(frame.account_id.clone(), StorageDeposit::Refund(deposit));
+		frame.nested_storage.charge_deposit(
+			frame.account_id.clone(),
+			StorageDeposit::Refund(deposit),
+			info,
+		);
 		Ok(())
 	}
 }
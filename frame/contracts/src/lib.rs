@@ -122,7 +122,7 @@ use frame_support::{
 		ConstU32, Contains, Get, Randomness, Time,
 	},
 	weights::Weight,
-	BoundedVec, DefaultNoBound, RuntimeDebugNoBound,
+	BoundedVec, DefaultNoBound, RuntimeDebugNoBound, StorageHasher, Twox64Concat,
 };
 use frame_system::{
 	ensure_signed,
@@ -187,6 +187,11 @@ const SENTINEL: u32 = u32::MAX;
 /// Example: `RUST_LOG=runtime::contracts=debug my_code --dev`
 const LOG_TARGET: &str = "runtime::contracts";
 
+/// The maximum number of [`DepositPayer`]s whose deposit a single contract can hold at once.
+///
+/// See [`Pallet::set_deposit_payer`].
+const MAX_DEPOSIT_PAYERS: u32 = 4;
+
 /// Wrapper around `PhantomData` to prevent it being filtered by `scale-info`.
 ///
 /// `scale-info` filters out `PhantomData` fields because usually we are only interested
@@ -226,7 +231,7 @@ pub mod pallet {
 	use sp_runtime::Perbill;
 
 	/// The current storage version.
-	pub(crate) const STORAGE_VERSION: StorageVersion = StorageVersion::new(15);
+	pub(crate) const STORAGE_VERSION: StorageVersion = StorageVersion::new(16);
 
 	#[pallet::pallet]
 	#[pallet::storage_version(STORAGE_VERSION)]
@@ -870,6 +875,81 @@ pub mod pallet {
 				},
 			}
 		}
+
+		/// Set who pays the storage deposit of the calling contract.
+		///
+		/// The origin must be a contract. It is meant to be dispatched by the contract itself
+		/// using `seal_call_runtime`. Passing `None` restores the default behaviour of charging
+		/// the origin of the call stack.
+		///
+		/// # Parameters
+		///
+		/// * `payer`: [`DepositPayer::Contract`] charges the contract's own free balance.
+		///   [`DepositPayer::Sponsor`] charges an account which must have approved the contract
+		///   using [`Self::approve_deposit_sponsorship`] before.
+		///
+		/// # Note
+		///
+		/// The contract records how much deposit every payer other than the origin paid, up to
+		/// [`MAX_DEPOSIT_PAYERS`] payers at once. Refunds are paid back to these payers first,
+		/// starting with the most recent one, and only the rest is refunded to the origin. A new
+		/// payer can't be set while the contract holds deposits of [`MAX_DEPOSIT_PAYERS`] other
+		/// payers. The new payer only takes effect for calls into the contract that start after
+		/// this dispatchable was executed.
+		#[pallet::call_index(10)]
+		#[pallet::weight(<Pallet<T>>::set_deposit_payer_weight())]
+		pub fn set_deposit_payer(
+			origin: OriginFor<T>,
+			payer: Option<DepositPayer<T::AccountId>>,
+		) -> DispatchResult {
+			Migration::<T>::ensure_migrated()?;
+			let contract = ensure_signed(origin)?;
+			let info = <ContractInfoOf<T>>::get(&contract).ok_or(<Error<T>>::ContractNotFound)?;
+			if let Some(payer) = &payer {
+				ensure!(info.can_record_deposit_payer(payer), <Error<T>>::TooManyDepositPayers);
+			}
+			if let Some(DepositPayer::Sponsor(sponsor)) = &payer {
+				ensure!(
+					<SponsorAllowance<T>>::contains_key(sponsor, &contract),
+					<Error<T>>::DepositSponsorNotApproved
+				);
+			}
+			<DepositPayerOf<T>>::set(&contract, payer.clone());
+			Self::deposit_event(
+				vec![T::Hashing::hash_of(&contract)],
+				Event::DepositPayerSet { contract, payer },
+			);
+			Ok(())
+		}
+
+		/// Allow `contract` to charge storage deposits from the origin up to `allowance`.
+		///
+		/// This only takes effect once the contract designates the origin as its sponsor using
+		/// [`Self::set_deposit_payer`]. Every storage deposit charged reduces the allowance while
+		/// every refund restores it. Passing a zero `allowance` revokes the sponsorship which
+		/// makes all further charges of the contract fail until it designates a new payer.
+		#[pallet::call_index(11)]
+		#[pallet::weight(<Pallet<T>>::approve_deposit_sponsorship_weight())]
+		pub fn approve_deposit_sponsorship(
+			origin: OriginFor<T>,
+			contract: AccountIdLookupOf<T>,
+			#[pallet::compact] allowance: BalanceOf<T>,
+		) -> DispatchResult {
+			Migration::<T>::ensure_migrated()?;
+			let sponsor = ensure_signed(origin)?;
+			let contract = T::Lookup::lookup(contract)?;
+			ensure!(<ContractInfoOf<T>>::contains_key(&contract), <Error<T>>::ContractNotFound);
+			if allowance.is_zero() {
+				<SponsorAllowance<T>>::remove(&sponsor, &contract);
+			} else {
+				<SponsorAllowance<T>>::insert(&sponsor, &contract, allowance);
+			}
+			Self::deposit_event(
+				vec![T::Hashing::hash_of(&sponsor), T::Hashing::hash_of(&contract)],
+				Event::DepositSponsorshipApproved { sponsor, contract, allowance },
+			);
+			Ok(())
+		}
 	}
 
 	#[pallet::event]
@@ -957,6 +1037,24 @@ pub mod pallet {
 			to: T::AccountId,
 			amount: BalanceOf<T>,
 		},
+
+		/// A contract changed who pays for its storage deposit.
+		DepositPayerSet {
+			/// The contract whose storage deposit is paid by `payer`.
+			contract: T::AccountId,
+			/// The new payer. `None` means the origin of the call stack pays.
+			payer: Option<DepositPayer<T::AccountId>>,
+		},
+
+		/// An account approved to sponsor the storage deposit of a contract.
+		DepositSponsorshipApproved {
+			/// The account that pays the storage deposit.
+			sponsor: T::AccountId,
+			/// The sponsored contract.
+			contract: T::AccountId,
+			/// The amount of deposit the contract is allowed to charge from `sponsor`.
+			allowance: BalanceOf<T>,
+		},
 	}
 
 	#[pallet::error]
@@ -1050,6 +1148,13 @@ pub mod pallet {
 		DelegateDependencyAlreadyExists,
 		/// Can not add a delegate dependency to the code hash of the contract itself.
 		CannotAddSelfAsDelegateDependency,
+		/// The account designated as storage deposit sponsor did not approve the contract.
+		DepositSponsorNotApproved,
+		/// The storage deposit sponsor's allowance for the contract is too low to pay for the
+		/// storage deposit.
+		DepositAllowanceExhausted,
+		/// The contract holds the deposit of too many payers to accept another one.
+		TooManyDepositPayers,
 	}
 
 	/// A reason for the pallet contracts placing a hold on funds.
@@ -1119,6 +1224,21 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(crate) type MigrationInProgress<T: Config> =
 		StorageValue<_, migration::Cursor, OptionQuery>;
+
+	/// Contracts that do not charge their storage deposit from the origin of the call stack.
+	///
+	/// TWOX-NOTE: SAFE since `AccountId` is a secure hash.
+	#[pallet::storage]
+	pub(crate) type DepositPayerOf<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, DepositPayer<T::AccountId>>;
+
+	/// The remaining storage deposit a sponsor (first key) allows a contract (second key) to
+	/// charge.
+	///
+	/// TWOX-NOTE: SAFE since `AccountId` is a secure hash.
+	#[pallet::storage]
+	pub(crate) type SponsorAllowance<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, T::AccountId, BalanceOf<T>>;
 }
 
 /// The type of origins supported by the contracts pallet.
@@ -1150,6 +1270,17 @@ impl<T: Config> Origin<T> {
 	}
 }
 
+/// Who pays the storage deposit of a contract instead of the origin of the call stack.
+///
+/// See [`Pallet::set_deposit_payer`].
+#[derive(Clone, Encode, Decode, PartialEq, Eq, MaxEncodedLen, TypeInfo, RuntimeDebug)]
+pub enum DepositPayer<AccountId> {
+	/// The contract pays from its own free balance.
+	Contract,
+	/// The account pays within the allowance it approved for the contract.
+	Sponsor(AccountId),
+}
+
 /// Context of a contract invocation.
 struct CommonInput<'a, T: Config> {
 	origin: Origin<T>,
@@ -1654,6 +1785,41 @@ impl<T: Config> Pallet<T> {
 		<T::Currency as Inspect<AccountIdOf<T>>>::minimum_balance()
 	}
 
+	/// The weight of [`Self::set_deposit_payer`].
+	///
+	/// It accesses the same storage as [`Self::set_code`] does, which is measured, plus
+	/// `SponsorAllowance` and `DepositPayerOf`. The proof also covers the whole `ContractInfoOf`
+	/// entry, as it grew by the deposit payers since `set_code` was measured.
+	// TODO: Use the weight of the `set_deposit_payer` benchmark once it has been run.
+	fn set_deposit_payer_weight() -> Weight {
+		let proof_size = Twox64Concat::max_len::<T::AccountId>()
+			.saturating_add(ContractInfo::<T>::max_encoded_len())
+			.saturating_add(Self::sponsor_allowance_max_len())
+			.saturating_add(Twox64Concat::max_len::<T::AccountId>())
+			.saturating_add(DepositPayer::<T::AccountId>::max_encoded_len());
+		T::WeightInfo::set_code()
+			.saturating_add(T::DbWeight::get().reads_writes(1, 1))
+			.saturating_add(Weight::from_parts(0, proof_size as u64))
+	}
+
+	/// The weight of [`Self::approve_deposit_sponsorship`].
+	///
+	/// It accesses less storage than [`Self::set_code`] does, which is measured, plus a write to
+	/// `SponsorAllowance`.
+	// TODO: Use the weight of the `approve_deposit_sponsorship` benchmark once it has been run.
+	fn approve_deposit_sponsorship_weight() -> Weight {
+		T::WeightInfo::set_code()
+			.saturating_add(T::DbWeight::get().writes(1))
+			.saturating_add(Weight::from_parts(0, Self::sponsor_allowance_max_len() as u64))
+	}
+
+	/// The maximum size of a `SponsorAllowance` entry, including its key.
+	fn sponsor_allowance_max_len() -> usize {
+		Twox64Concat::max_len::<T::AccountId>()
+			.saturating_add(Twox64Concat::max_len::<T::AccountId>())
+			.saturating_add(BalanceOf::<T>::max_encoded_len())
+	}
+
 	/// Convert gas_limit from 1D Weight to a 2D Weight.
 	///
 	/// Used by backwards compatible extrinsics. We cannot just set the proof_size weight limit to
@@ -23,14 +23,15 @@ use crate::{
 	exec::{AccountIdOf, Key},
 	weights::WeightInfo,
 	BalanceOf, CodeHash, CodeInfo, Config, ContractInfoOf, DeletionQueue, DeletionQueueCounter,
-	Error, Pallet, TrieId, SENTINEL,
+	DepositPayer, Error, Pallet, TrieId, MAX_DEPOSIT_PAYERS, SENTINEL,
 };
 use codec::{Decode, Encode, MaxEncodedLen};
 use frame_support::{
 	dispatch::DispatchError,
 	storage::child::{self, ChildInfo},
+	traits::ConstU32,
 	weights::Weight,
-	CloneNoBound, DefaultNoBound,
+	BoundedVec, CloneNoBound, DefaultNoBound,
 };
 use scale_info::TypeInfo;
 use sp_core::Get;
@@ -71,6 +72,12 @@ pub struct ContractInfo<T: Config> {
 	/// to the map can not be removed from the chain state and can be safely used for delegate
 	/// calls.
 	delegate_dependencies: BoundedBTreeMap<CodeHash<T>, BalanceOf<T>, T::MaxDelegateDependencies>,
+	/// The part of the deposits above that was not paid by the origin of a call stack.
+	///
+	/// Records every [`DepositPayer`] together with the deposit it paid, the most recent payer
+	/// last. Refunds are paid back to these payers before anything is refunded to the origin.
+	deposit_payers:
+		BoundedVec<(DepositPayer<AccountIdOf<T>>, BalanceOf<T>), ConstU32<MAX_DEPOSIT_PAYERS>>,
 }
 
 impl<T: Config> ContractInfo<T> {
@@ -104,6 +111,7 @@ impl<T: Config> ContractInfo<T> {
 			storage_item_deposit: Zero::zero(),
 			storage_base_deposit: Zero::zero(),
 			delegate_dependencies: Default::default(),
+			deposit_payers: Default::default(),
 		};
 
 		Ok(contract)
@@ -266,6 +274,71 @@ impl<T: Config> ContractInfo<T> {
 		&self.delegate_dependencies
 	}
 
+	/// Returns the payers of the deposit other than the origin and how much each of them paid.
+	pub fn deposit_payers(&self) -> &[(DepositPayer<AccountIdOf<T>>, BalanceOf<T>)] {
+		&self.deposit_payers
+	}
+
+	/// Returns whether a deposit paid by `payer` can be recorded.
+	///
+	/// This is the case if `payer` already paid a deposit or if there is room for another payer.
+	pub fn can_record_deposit_payer(&self, payer: &DepositPayer<AccountIdOf<T>>) -> bool {
+		self.deposit_payers.len() < MAX_DEPOSIT_PAYERS as usize ||
+			self.deposit_payers.iter().any(|(p, _)| p == payer)
+	}
+
+	/// Records that `payer` paid `amount` of the deposit held by the contract.
+	///
+	/// `payer` becomes the most recent payer. Returns an error if `payer` is not yet recorded
+	/// and the maximum number of payers is reached.
+	pub fn record_deposit_payer(
+		&mut self,
+		payer: DepositPayer<AccountIdOf<T>>,
+		amount: BalanceOf<T>,
+	) -> DispatchResult {
+		let paid = match self.deposit_payers.iter().position(|(p, _)| p == &payer) {
+			Some(index) => self.deposit_payers.remove(index).1,
+			None => Zero::zero(),
+		};
+		self.deposit_payers
+			.try_push((payer, paid.saturating_add(amount)))
+			.map_err(|_| Error::<T>::TooManyDepositPayers.into())
+	}
+
+	/// Assigns a refund of `amount` to the recorded payers, the most recent payer first.
+	///
+	/// Returns the refund of every payer and the remainder of `amount` which was not paid by any
+	/// recorded payer and hence belongs to the origin.
+	pub fn take_payer_refunds(
+		&mut self,
+		mut amount: BalanceOf<T>,
+	) -> (Vec<(DepositPayer<AccountIdOf<T>>, BalanceOf<T>)>, BalanceOf<T>) {
+		let mut refunds = Vec::new();
+		while !amount.is_zero() {
+			let Some((payer, paid)) = self.deposit_payers.last_mut() else { break };
+			let refund = amount.min(*paid);
+			*paid = paid.saturating_sub(refund);
+			amount = amount.saturating_sub(refund);
+			refunds.push((payer.clone(), refund));
+			if paid.is_zero() {
+				self.deposit_payers.pop();
+			}
+		}
+		(refunds, amount)
+	}
+
+	/// The part of a refund of `amount` that belongs to the origin.
+	///
+	/// This is what remains of `amount` after [`Self::take_payer_refunds`] without modifying the
+	/// recorded payers.
+	pub fn origin_refund(&self, amount: BalanceOf<T>) -> BalanceOf<T> {
+		let paid = self
+			.deposit_payers
+			.iter()
+			.fold(BalanceOf::<T>::zero(), |acc, (_, paid)| acc.saturating_add(*paid));
+		amount.saturating_sub(paid)
+	}
+
 	/// Push a contract's trie to the deletion queue for lazy removal.
 	///
 	/// You must make sure that the contract is also removed when queuing the trie for deletion.
@@ -18,8 +18,9 @@
 //! This module contains functions to meter the storage deposit.
 
 use crate::{
-	storage::ContractInfo, AccountIdOf, BalanceOf, CodeInfo, Config, Error, Event, HoldReason,
-	Inspect, Origin, Pallet, StorageDeposit as Deposit, System, LOG_TARGET,
+	storage::ContractInfo, AccountIdOf, BalanceOf, CodeInfo, Config, DepositPayer, DepositPayerOf,
+	Error, Event, HoldReason, Inspect, Origin, Pallet, SponsorAllowance, StorageDeposit as Deposit,
+	System, LOG_TARGET,
 };
 
 use frame_support::{
@@ -88,6 +89,17 @@ pub trait Ext<T: Config> {
 		amount: &DepositOf<T>,
 		state: &ContractState<T>,
 	) -> Result<(), DispatchError>;
+	/// Returns who pays the storage deposit of `contract` if it is not the origin.
+	fn deposit_payer(contract: &T::AccountId) -> Option<DepositPayer<T::AccountId>>;
+	/// This is called before `amount` is charged from or refunded to a `sponsor` of `contract`.
+	///
+	/// A charge reduces the allowance the `sponsor` granted to `contract` and fails if the
+	/// allowance is too low. A refund restores the allowance.
+	fn update_allowance(
+		sponsor: &T::AccountId,
+		contract: &T::AccountId,
+		amount: &DepositOf<T>,
+	) -> Result<(), DispatchError>;
 }
 
 /// This [`Ext`] is used for actual on-chain execution when balance needs to be charged.
@@ -122,6 +134,8 @@ pub struct RawMeter<T: Config, E, S: State + Default + Debug> {
 	/// The limit of how much balance this meter is allowed to consume.
 	limit: BalanceOf<T>,
 	/// The amount of balance that was used in this meter and all of its already absorbed children.
+	///
+	/// Only deposits paid by the origin are accounted here as only those are subject to `limit`.
 	total_deposit: DepositOf<T>,
 	/// The amount of storage changes that were recorded in this meter alone.
 	own_contribution: Contribution<T>,
@@ -132,6 +146,8 @@ pub struct RawMeter<T: Config, E, S: State + Default + Debug> {
 	charges: Vec<Charge<T>>,
 	/// We store the nested state to determine if it has a special limit for sub-call.
 	nested: S,
+	/// Who pays the deposit of the contract this meter belongs to. `None` means the origin.
+	payer: Option<DepositPayer<T::AccountId>>,
 	/// Type parameter only used in impls.
 	_phantom: PhantomData<E>,
 }
@@ -245,6 +261,7 @@ struct Charge<T: Config> {
 	contract: T::AccountId,
 	amount: DepositOf<T>,
 	state: ContractState<T>,
+	payer: Option<DepositPayer<T::AccountId>>,
 }
 
 /// Records the storage changes of a storage meter.
@@ -303,6 +320,17 @@ where
 		}
 	}
 
+	/// Charge the deposit of the child's contract to whoever [`Ext::deposit_payer`] designates.
+	///
+	/// This should be called right after [`Self::nested`] with the account of the contract that
+	/// is executed by the sub call. Deposits that are not paid by the origin are not subject to
+	/// the storage deposit limit. Refunds are not affected by the designated payer: they always
+	/// go to whoever paid the deposit, see [`ContractInfo::take_payer_refunds`].
+	pub fn with_payer_of(mut self, contract: &T::AccountId) -> Self {
+		self.payer = E::deposit_payer(contract);
+		self
+	}
+
 	/// Absorb a child that was spawned to handle a sub call.
 	///
 	/// This should be called whenever a sub call comes to its end and it is **not** reverted.
@@ -322,20 +350,73 @@ where
 		&mut self,
 		absorbed: RawMeter<T, E, Nested>,
 		contract: &T::AccountId,
-		info: Option<&mut ContractInfo<T>>,
+		mut info: Option<&mut ContractInfo<T>>,
 	) {
-		let own_deposit = absorbed.own_contribution.update_contract(info);
-		self.total_deposit = self
-			.total_deposit
-			.saturating_add(&absorbed.total_deposit)
-			.saturating_add(&own_deposit);
+		let own_deposit = absorbed.own_contribution.update_contract(info.as_deref_mut());
+		self.total_deposit = self.total_deposit.saturating_add(&absorbed.total_deposit);
 		self.charges.extend_from_slice(&absorbed.charges);
-		if !own_deposit.is_zero() {
-			self.charges.push(Charge {
-				contract: contract.clone(),
-				amount: own_deposit,
-				state: absorbed.contract_state(),
-			});
+		self.record_charge(
+			contract.clone(),
+			own_deposit,
+			absorbed.contract_state(),
+			absorbed.payer.clone(),
+			info,
+		);
+	}
+
+	/// Records a charge or refund of `amount` for `contract`.
+	///
+	/// Charges are paid by `payer` and recorded in `info` unless the origin pays. Refunds go to
+	/// the payers recorded in `info` first and only the rest goes to the origin. Only what is
+	/// charged from or refunded to the origin accounts to the `total_deposit`.
+	fn record_charge(
+		&mut self,
+		contract: T::AccountId,
+		amount: DepositOf<T>,
+		state: ContractState<T>,
+		payer: Option<DepositPayer<T::AccountId>>,
+		info: Option<&mut ContractInfo<T>>,
+	) {
+		let amount = match (amount, payer, info) {
+			(Deposit::Charge(amount), Some(payer), info) => {
+				if let Some(info) = info.filter(|_| !amount.is_zero()) {
+					if let Err(err) = info.record_deposit_payer(payer.clone(), amount) {
+						// `set_deposit_payer` makes sure there is room to record the payer.
+						log::error!(
+							target: LOG_TARGET,
+							"Failed to record {:?} as deposit payer of contract {:?}: {:?}",
+							payer, contract, err,
+						);
+					}
+				}
+				if !amount.is_zero() {
+					self.charges.push(Charge {
+						contract,
+						amount: Deposit::Charge(amount),
+						state,
+						payer: Some(payer),
+					});
+				}
+				return
+			},
+			(Deposit::Refund(amount), _, Some(info)) => {
+				let (refunds, rest) = info.take_payer_refunds(amount);
+				for (payer, refund) in refunds {
+					self.charges.push(Charge {
+						contract: contract.clone(),
+						amount: Deposit::Refund(refund),
+						state: ContractState::Alive,
+						payer: Some(payer),
+					});
+				}
+				Deposit::Refund(rest)
+			},
+			(amount, _, _) => amount,
+		};
+		self.total_deposit = self.total_deposit.saturating_add(&amount);
+		// A termination needs to be applied even without any deposit left to refund.
+		if !amount.is_zero() || matches!(state, ContractState::Terminated { .. }) {
+			self.charges.push(Charge { contract, amount, state, payer: None });
 		}
 	}
 
@@ -381,9 +462,10 @@ where
 		}
 	}
 
-	/// The total amount of deposit that should change hands as result of the execution
-	/// that this meter was passed into. This will also perform all the charges accumulated
-	/// in the whole contract stack.
+	/// The total amount of deposit that should change hands between the origin and the contracts
+	/// as result of the execution that this meter was passed into. This will also perform all
+	/// the charges accumulated in the whole contract stack, including those paid by someone
+	/// other than the origin.
 	///
 	/// This drops the root meter in order to make sure it is only called when the whole
 	/// execution did finish.
@@ -394,13 +476,26 @@ where
 			Origin::Signed(o) => o,
 		};
 		for charge in self.charges.iter().filter(|c| matches!(c.amount, Deposit::Refund(_))) {
-			E::charge(origin, &charge.contract, &charge.amount, &charge.state)?;
+			Self::apply_charge(origin, charge)?;
 		}
 		for charge in self.charges.iter().filter(|c| matches!(c.amount, Deposit::Charge(_))) {
-			E::charge(origin, &charge.contract, &charge.amount, &charge.state)?;
+			Self::apply_charge(origin, charge)?;
 		}
 		Ok(self.total_deposit)
 	}
+
+	/// Charge or refund `charge` from or to its payer.
+	fn apply_charge(origin: &T::AccountId, charge: &Charge<T>) -> Result<(), DispatchError> {
+		let payer = match &charge.payer {
+			None => origin,
+			Some(DepositPayer::Contract) => &charge.contract,
+			Some(DepositPayer::Sponsor(sponsor)) => {
+				E::update_allowance(sponsor, &charge.contract, &charge.amount)?;
+				sponsor
+			},
+		};
+		E::charge(payer, &charge.contract, &charge.amount, &charge.state)
+	}
 }
 
 /// Functions that only apply to the nested state.
@@ -423,9 +518,15 @@ where
 	/// change. This is the case when a `delegate_dependency` is added or removed, or when the
 	/// `code_hash` is updated. [`Self::charge`] cannot be used here because we keep track of the
 	/// deposit charge separately from the storage charge.
-	pub fn charge_deposit(&mut self, contract: T::AccountId, amount: DepositOf<T>) {
-		self.total_deposit = self.total_deposit.saturating_add(&amount);
-		self.charges.push(Charge { contract, amount, state: ContractState::Alive });
+	///
+	/// `info` is the info of `contract`. It records who paid the deposit.
+	pub fn charge_deposit(
+		&mut self,
+		contract: T::AccountId,
+		amount: DepositOf<T>,
+		info: &mut ContractInfo<T>,
+	) {
+		self.record_charge(contract, amount, ContractState::Alive, self.payer.clone(), Some(info));
 	}
 
 	/// Charges from `origin` a storage deposit for contract instantiation.
@@ -460,7 +561,11 @@ where
 		// With the consumer, a correct runtime cannot remove the account.
 		System::<T>::inc_consumers(contract)?;
 
-		self.charge_deposit(contract.clone(), deposit.saturating_sub(&Deposit::Charge(ed)));
+		self.charge_deposit(
+			contract.clone(),
+			deposit.saturating_sub(&Deposit::Charge(ed)),
+			contract_info,
+		);
 
 		Ok(deposit)
 	}
@@ -468,14 +573,27 @@ where
 	/// Call to tell the meter that the currently executing contract was terminated.
 	///
 	/// This will manipulate the meter so that all storage deposit accumulated in
-	/// `contract_info` will be refunded to the `origin` of the meter. And the free
-	/// (`reducible_balance`) will be sent to the `beneficiary`.
-	pub fn terminate(&mut self, info: &ContractInfo<T>, beneficiary: T::AccountId) {
+	/// `contract_info` will be refunded to the payers recorded in `info` and the rest to the
+	/// `origin` of the meter. And the free (`reducible_balance`) will be sent to the
+	/// `beneficiary`.
+	pub fn terminate(
+		&mut self,
+		contract: &T::AccountId,
+		info: &mut ContractInfo<T>,
+		beneficiary: T::AccountId,
+	) {
 		debug_assert!(matches!(self.contract_state(), ContractState::Alive));
-		self.own_contribution = Contribution::Terminated {
-			deposit: Deposit::Refund(info.total_deposit()),
-			beneficiary,
-		};
+		let (refunds, rest) = info.take_payer_refunds(info.total_deposit());
+		for (payer, refund) in refunds {
+			self.charges.push(Charge {
+				contract: contract.clone(),
+				amount: Deposit::Refund(refund),
+				state: ContractState::Alive,
+				payer: Some(payer),
+			});
+		}
+		self.own_contribution =
+			Contribution::Terminated { deposit: Deposit::Refund(rest), beneficiary };
 	}
 
 	/// [`Self::charge`] does not enforce the storage limit since we want to do this check as late
@@ -489,12 +607,20 @@ where
 	/// used.
 	pub fn enforce_limit(
 		&mut self,
-		info: Option<&mut ContractInfo<T>>,
+		mut info: Option<&mut ContractInfo<T>>,
 	) -> Result<(), DispatchError> {
-		let deposit = self.own_contribution.update_contract(info);
-		let total_deposit = self.total_deposit.saturating_add(&deposit);
+		let deposit = self.own_contribution.update_contract(info.as_deref_mut());
+		let alive = matches!(self.contract_state(), ContractState::Alive);
+		// Only what the origin pays or gets refunded is subject to the limit.
+		let origin_deposit = match (&deposit, &self.payer, info) {
+			(Deposit::Charge(_), Some(_), _) => Deposit::Charge(Zero::zero()),
+			(Deposit::Refund(amount), _, Some(info)) if alive =>
+				Deposit::Refund(info.origin_refund(*amount)),
+			(deposit, _, _) => deposit.clone(),
+		};
+		let total_deposit = self.total_deposit.saturating_add(&origin_deposit);
 		// We don't want to override a `Terminated` with a `Checked`.
-		if matches!(self.contract_state(), ContractState::Alive) {
+		if alive {
 			self.own_contribution = Contribution::Checked(deposit);
 		}
 		if let Deposit::Charge(amount) = total_deposit {
@@ -547,7 +673,7 @@ impl<T: Config> Ext<T> for ReservingExt {
 		state: &ContractState<T>,
 	) -> Result<(), DispatchError> {
 		match amount {
-			Deposit::Charge(amount) | Deposit::Refund(amount) if amount.is_zero() => return Ok(()),
+			Deposit::Charge(amount) | Deposit::Refund(amount) if amount.is_zero() => (),
 			Deposit::Charge(amount) => {
 				// This could fail if the `origin` does not have enough liquidity. Ideally, though,
 				// this should have been checked before with `check_limit`.
@@ -614,6 +740,34 @@ impl<T: Config> Ext<T> for ReservingExt {
 		}
 		Ok(())
 	}
+
+	fn deposit_payer(contract: &T::AccountId) -> Option<DepositPayer<T::AccountId>> {
+		<DepositPayerOf<T>>::get(contract)
+	}
+
+	fn update_allowance(
+		sponsor: &T::AccountId,
+		contract: &T::AccountId,
+		amount: &DepositOf<T>,
+	) -> Result<(), DispatchError> {
+		<SponsorAllowance<T>>::try_mutate(sponsor, contract, |allowance| {
+			match (allowance, amount) {
+				(Some(allowance), Deposit::Charge(amount)) => {
+					ensure!(*allowance >= *amount, <Error<T>>::DepositAllowanceExhausted);
+					*allowance = allowance.saturating_sub(*amount);
+					Ok(())
+				},
+				(None, Deposit::Charge(amount)) if !amount.is_zero() =>
+					Err(<Error<T>>::DepositAllowanceExhausted.into()),
+				// Refunds restore the allowance unless the sponsorship was revoked in the meantime.
+				(Some(allowance), Deposit::Refund(amount)) => {
+					*allowance = allowance.saturating_add(*amount);
+					Ok(())
+				},
+				(None, _) => Ok(()),
+			}
+		})
+	}
 }
 
 mod private {
@@ -627,7 +781,7 @@ mod tests {
 	use super::*;
 	use crate::{
 		exec::AccountIdOf,
-		tests::{Test, ALICE, BOB, CHARLIE},
+		tests::{Test, ALICE, BOB, CHARLIE, DJANGO},
 	};
 	use frame_support::parameter_types;
 	use pretty_assertions::assert_eq;
@@ -636,6 +790,16 @@ mod tests {
 
 	parameter_types! {
 		static TestExtTestValue: TestExt = Default::default();
+		static TestDepositPayers: Vec<(AccountIdOf<Test>, DepositPayer<AccountIdOf<Test>>)> =
+			Default::default();
+		static TestAllowanceUpdates: Vec<AllowanceUpdate> = Default::default();
+	}
+
+	#[derive(Debug, PartialEq, Eq, Clone)]
+	struct AllowanceUpdate {
+		sponsor: AccountIdOf<Test>,
+		contract: AccountIdOf<Test>,
+		amount: DepositOf<Test>,
 	}
 
 	#[derive(Debug, PartialEq, Eq, Clone)]
@@ -696,10 +860,33 @@ mod tests {
 			});
 			Ok(())
 		}
+
+		fn deposit_payer(contract: &AccountIdOf<Test>) -> Option<DepositPayer<AccountIdOf<Test>>> {
+			TestDepositPayers::get()
+				.into_iter()
+				.find_map(|(c, payer)| (&c == contract).then_some(payer))
+		}
+
+		fn update_allowance(
+			sponsor: &AccountIdOf<Test>,
+			contract: &AccountIdOf<Test>,
+			amount: &DepositOf<Test>,
+		) -> Result<(), DispatchError> {
+			TestAllowanceUpdates::mutate(|updates| {
+				updates.push(AllowanceUpdate {
+					sponsor: sponsor.clone(),
+					contract: contract.clone(),
+					amount: amount.clone(),
+				})
+			});
+			Ok(())
+		}
 	}
 
 	fn clear_ext() {
-		TestExtTestValue::mutate(|ext| ext.clear())
+		TestExtTestValue::mutate(|ext| ext.clear());
+		TestDepositPayers::mutate(|payers| payers.clear());
+		TestAllowanceUpdates::mutate(|updates| updates.clear());
 	}
 
 	struct ChargingTestCase {
@@ -726,6 +913,7 @@ mod tests {
 			storage_item_deposit: info.items_deposit,
 			storage_base_deposit: Default::default(),
 			delegate_dependencies: Default::default(),
+			deposit_payers: Default::default(),
 		}
 	}
 
@@ -911,7 +1099,7 @@ mod tests {
 			let mut nested1 = nested0.nested(BalanceOf::<Test>::zero());
 			nested1.charge(&Diff { items_removed: 5, ..Default::default() });
 			nested1.charge(&Diff { bytes_added: 20, ..Default::default() });
-			nested1.terminate(&nested1_info, CHARLIE);
+			nested1.terminate(&CHARLIE, &mut nested1_info, CHARLIE);
 			nested0.enforce_limit(Some(&mut nested1_info)).unwrap();
 			nested0.absorb(nested1, &CHARLIE, None);
 
@@ -921,4 +1109,224 @@ mod tests {
 			assert_eq!(TestExtTestValue::get(), test_case.expected)
 		}
 	}
+
+	#[test]
+	fn sponsored_deposits_work() {
+		clear_ext();
+		TestDepositPayers::mutate(|payers| {
+			payers.push((BOB, DepositPayer::Contract));
+			payers.push((CHARLIE, DepositPayer::Sponsor(DJANGO)));
+		});
+
+		let mut meter = TestMeter::new(&Origin::from_account_id(ALICE), Some(10), 0).unwrap();
+
+		// BOB pays for its own storage which is hence not subject to the limit.
+		let mut bob_info = new_info(StorageInfo::default());
+		let mut nested0 = meter.nested(BalanceOf::<Test>::zero()).with_payer_of(&BOB);
+		nested0.charge(&Diff { bytes_added: 50, ..Default::default() });
+
+		// DJANGO sponsors CHARLIE which is also not subject to the limit.
+		let mut charlie_info = new_info(StorageInfo::default());
+		let mut nested1 = nested0.nested(BalanceOf::<Test>::zero()).with_payer_of(&CHARLIE);
+		nested1.charge(&Diff { bytes_added: 20, items_added: 1, ..Default::default() });
+		nested1.enforce_limit(Some(&mut charlie_info)).unwrap();
+		nested0.absorb(nested1, &CHARLIE, Some(&mut charlie_info));
+
+		// The origin pays for the deposit of the unsponsored ALICE.
+		let mut alice_info = new_info(StorageInfo::default());
+		let mut nested2 = nested0.nested(BalanceOf::<Test>::zero()).with_payer_of(&ALICE);
+		nested2.charge(&Diff { bytes_added: 8, ..Default::default() });
+		nested2.enforce_limit(Some(&mut alice_info)).unwrap();
+		nested0.absorb(nested2, &ALICE, Some(&mut alice_info));
+
+		nested0.enforce_limit(Some(&mut bob_info)).unwrap();
+		meter.absorb(nested0, &BOB, Some(&mut bob_info));
+
+		assert_eq!(
+			meter.try_into_deposit(&Origin::from_account_id(ALICE)).unwrap(),
+			Deposit::Charge(8)
+		);
+		assert_eq!(
+			TestExtTestValue::get().charges,
+			vec![
+				Charge {
+					origin: DJANGO,
+					contract: CHARLIE,
+					amount: Deposit::Charge(22),
+					state: ContractState::Alive,
+				},
+				Charge {
+					origin: ALICE,
+					contract: ALICE,
+					amount: Deposit::Charge(8),
+					state: ContractState::Alive,
+				},
+				Charge {
+					origin: BOB,
+					contract: BOB,
+					amount: Deposit::Charge(50),
+					state: ContractState::Alive,
+				},
+			]
+		);
+		assert_eq!(
+			TestAllowanceUpdates::get(),
+			vec![AllowanceUpdate {
+				sponsor: DJANGO,
+				contract: CHARLIE,
+				amount: Deposit::Charge(22)
+			}]
+		);
+	}
+
+	#[test]
+	fn unsponsored_deposits_respect_limit() {
+		clear_ext();
+		TestDepositPayers::mutate(|payers| payers.push((BOB, DepositPayer::Contract)));
+
+		let mut meter = TestMeter::new(&Origin::from_account_id(ALICE), Some(10), 0).unwrap();
+
+		let mut bob_info = new_info(StorageInfo::default());
+		let mut nested0 = meter.nested(BalanceOf::<Test>::zero()).with_payer_of(&BOB);
+		nested0.charge(&Diff { bytes_added: 100, ..Default::default() });
+		nested0.enforce_limit(Some(&mut bob_info)).unwrap();
+		meter.absorb(nested0, &BOB, Some(&mut bob_info));
+
+		let mut charlie_info = new_info(StorageInfo::default());
+		let mut nested1 = meter.nested(BalanceOf::<Test>::zero()).with_payer_of(&CHARLIE);
+		nested1.charge(&Diff { bytes_added: 11, ..Default::default() });
+		assert_eq!(
+			nested1.enforce_limit(Some(&mut charlie_info)),
+			Err(<Error<Test>>::StorageDepositLimitExhausted.into())
+		);
+	}
+
+	#[test]
+	fn refunds_go_to_recorded_payers() {
+		clear_ext();
+		TestDepositPayers::mutate(|payers| payers.push((BOB, DepositPayer::Sponsor(DJANGO))));
+		let mut bob_info = new_info(StorageInfo::default());
+
+		// DJANGO pays for the storage added by BOB.
+		let mut meter = TestMeter::new(&Origin::from_account_id(ALICE), Some(100), 0).unwrap();
+		let mut nested0 = meter.nested(BalanceOf::<Test>::zero()).with_payer_of(&BOB);
+		nested0.charge(&Diff { bytes_added: 40, ..Default::default() });
+		nested0.enforce_limit(Some(&mut bob_info)).unwrap();
+		meter.absorb(nested0, &BOB, Some(&mut bob_info));
+		assert_eq!(
+			meter.try_into_deposit(&Origin::from_account_id(ALICE)).unwrap(),
+			Deposit::Charge(0)
+		);
+
+		// BOB pays for itself from now on.
+		TestDepositPayers::mutate(|payers| payers[0].1 = DepositPayer::Contract);
+		let mut meter = TestMeter::new(&Origin::from_account_id(ALICE), Some(100), 0).unwrap();
+		let mut nested0 = meter.nested(BalanceOf::<Test>::zero()).with_payer_of(&BOB);
+		nested0.charge(&Diff { bytes_added: 10, ..Default::default() });
+		nested0.enforce_limit(Some(&mut bob_info)).unwrap();
+		meter.absorb(nested0, &BOB, Some(&mut bob_info));
+		assert_eq!(
+			meter.try_into_deposit(&Origin::from_account_id(ALICE)).unwrap(),
+			Deposit::Charge(0)
+		);
+
+		// The origin pays once BOB stops delegating its deposit.
+		TestDepositPayers::mutate(|payers| payers.clear());
+		let mut meter = TestMeter::new(&Origin::from_account_id(ALICE), Some(100), 0).unwrap();
+		let mut nested0 = meter.nested(BalanceOf::<Test>::zero()).with_payer_of(&BOB);
+		nested0.charge(&Diff { bytes_added: 10, ..Default::default() });
+		nested0.enforce_limit(Some(&mut bob_info)).unwrap();
+		meter.absorb(nested0, &BOB, Some(&mut bob_info));
+		assert_eq!(
+			meter.try_into_deposit(&Origin::from_account_id(ALICE)).unwrap(),
+			Deposit::Charge(10)
+		);
+		assert_eq!(
+			bob_info.deposit_payers(),
+			&[(DepositPayer::Sponsor(DJANGO), 40), (DepositPayer::Contract, 10)]
+		);
+
+		// Removing all storage refunds every payer what it paid, the most recent payer first.
+		clear_ext();
+		let mut meter = TestMeter::new(&Origin::from_account_id(CHARLIE), Some(100), 0).unwrap();
+		let mut nested0 = meter.nested(BalanceOf::<Test>::zero()).with_payer_of(&BOB);
+		nested0.charge(&Diff { bytes_removed: 60, ..Default::default() });
+		nested0.enforce_limit(Some(&mut bob_info)).unwrap();
+		meter.absorb(nested0, &BOB, Some(&mut bob_info));
+		assert_eq!(
+			meter.try_into_deposit(&Origin::from_account_id(CHARLIE)).unwrap(),
+			Deposit::Refund(10)
+		);
+		assert_eq!(
+			TestExtTestValue::get().charges,
+			vec![
+				Charge {
+					origin: BOB,
+					contract: BOB,
+					amount: Deposit::Refund(10),
+					state: ContractState::Alive,
+				},
+				Charge {
+					origin: DJANGO,
+					contract: BOB,
+					amount: Deposit::Refund(40),
+					state: ContractState::Alive,
+				},
+				Charge {
+					origin: CHARLIE,
+					contract: BOB,
+					amount: Deposit::Refund(10),
+					state: ContractState::Alive,
+				},
+			]
+		);
+		assert_eq!(
+			TestAllowanceUpdates::get(),
+			vec![AllowanceUpdate { sponsor: DJANGO, contract: BOB, amount: Deposit::Refund(40) }]
+		);
+		assert!(bob_info.deposit_payers().is_empty());
+	}
+
+	#[test]
+	fn termination_refunds_recorded_payers() {
+		clear_ext();
+		TestDepositPayers::mutate(|payers| payers.push((BOB, DepositPayer::Sponsor(DJANGO))));
+		let mut bob_info = new_info(StorageInfo::default());
+		// The base deposit is paid by the origin and includes the existential deposit.
+		bob_info.storage_base_deposit = 11;
+
+		let mut meter = TestMeter::new(&Origin::from_account_id(ALICE), Some(100), 0).unwrap();
+		let mut nested0 = meter.nested(BalanceOf::<Test>::zero()).with_payer_of(&BOB);
+		nested0.charge(&Diff { bytes_added: 40, ..Default::default() });
+		nested0.enforce_limit(Some(&mut bob_info)).unwrap();
+		meter.absorb(nested0, &BOB, Some(&mut bob_info));
+		meter.try_into_deposit(&Origin::from_account_id(ALICE)).unwrap();
+
+		clear_ext();
+		let mut meter = TestMeter::new(&Origin::from_account_id(ALICE), Some(100), 0).unwrap();
+		let mut nested0 = meter.nested(BalanceOf::<Test>::zero()).with_payer_of(&BOB);
+		nested0.terminate(&BOB, &mut bob_info, CHARLIE);
+		meter.absorb(nested0, &BOB, None);
+		assert_eq!(
+			meter.try_into_deposit(&Origin::from_account_id(ALICE)).unwrap(),
+			Deposit::Refund(10)
+		);
+		assert_eq!(
+			TestExtTestValue::get().charges,
+			vec![
+				Charge {
+					origin: DJANGO,
+					contract: BOB,
+					amount: Deposit::Refund(40),
+					state: ContractState::Alive,
+				},
+				Charge {
+					origin: ALICE,
+					contract: BOB,
+					amount: Deposit::Refund(10),
+					state: ContractState::Terminated { beneficiary: CHARLIE },
+				},
+			]
+		);
+	}
 }
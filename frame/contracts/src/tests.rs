@@ -35,8 +35,8 @@ use crate::{
 	wasm::{Determinism, ReturnCode as RuntimeReturnCode},
 	weights::WeightInfo,
 	BalanceOf, Code, CodeHash, CodeInfoOf, CollectEvents, Config, ContractInfo, ContractInfoOf,
-	DebugInfo, DefaultAddressGenerator, DeletionQueueCounter, Error, HoldReason,
-	MigrationInProgress, Origin, Pallet, PristineCode, Schedule,
+	DebugInfo, DefaultAddressGenerator, DeletionQueueCounter, DepositPayer, Error, HoldReason,
+	MigrationInProgress, Origin, Pallet, PristineCode, Schedule, SponsorAllowance,
 };
 use assert_matches::assert_matches;
 use codec::Encode;
@@ -4072,6 +4072,133 @@ fn storage_deposit_works() {
 	});
 }
 
+#[test]
+fn sponsored_storage_deposit_works() {
+	let (wasm, _code_hash) = compile_module::<Test>("multi_store").unwrap();
+	ExtBuilder::default().existential_deposit(200).build().execute_with(|| {
+		let _ = <Test as Config>::Currency::set_balance(&ALICE, 1_000_000);
+		let _ = <Test as Config>::Currency::set_balance(&BOB, 1_000_000);
+
+		let addr = Contracts::bare_instantiate(
+			ALICE,
+			0,
+			GAS_LIMIT,
+			None,
+			Code::Upload(wasm),
+			vec![],
+			vec![],
+			DebugInfo::Skip,
+			CollectEvents::Skip,
+		)
+		.result
+		.unwrap()
+		.account_id;
+
+		// Only a contract can designate a payer.
+		assert_noop!(
+			Contracts::set_deposit_payer(RuntimeOrigin::signed(ALICE), None),
+			<Error<Test>>::ContractNotFound,
+		);
+		// The sponsor needs to approve the contract first.
+		assert_noop!(
+			Contracts::set_deposit_payer(
+				RuntimeOrigin::signed(addr.clone()),
+				Some(DepositPayer::Sponsor(BOB))
+			),
+			<Error<Test>>::DepositSponsorNotApproved,
+		);
+		assert_ok!(Contracts::approve_deposit_sponsorship(
+			RuntimeOrigin::signed(BOB),
+			addr.clone(),
+			10_000
+		));
+		assert_ok!(Contracts::set_deposit_payer(
+			RuntimeOrigin::signed(addr.clone()),
+			Some(DepositPayer::Sponsor(BOB))
+		));
+
+		// The sponsor pays for the storage even though the origin set a limit of zero.
+		let alice_balance = test_utils::get_balance(&ALICE);
+		let bob_balance = test_utils::get_balance(&BOB);
+		assert_ok!(Contracts::call(
+			RuntimeOrigin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			Some(codec::Compact(0)),
+			(1_000u32, 5_000u32).encode(),
+		));
+		let charged = 4 + 1_000 + 5_000;
+		assert_eq!(test_utils::get_balance(&ALICE), alice_balance);
+		assert_eq!(test_utils::get_balance(&BOB), bob_balance - charged);
+		assert_eq!(SponsorAllowance::<Test>::get(&BOB, &addr), Some(10_000 - charged));
+
+		// Charges beyond the allowance fail.
+		assert_err_ignore_postinfo!(
+			Contracts::call(
+				RuntimeOrigin::signed(ALICE),
+				addr.clone(),
+				0,
+				GAS_LIMIT,
+				None,
+				(5_000u32, 5_000u32).encode(),
+			),
+			<Error<Test>>::DepositAllowanceExhausted,
+		);
+
+		// Refunds go back to the sponsor and restore the allowance.
+		assert_ok!(Contracts::call(
+			RuntimeOrigin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			(500u32, 500u32).encode(),
+		));
+		assert_eq!(test_utils::get_balance(&ALICE), alice_balance);
+		assert!(test_utils::get_balance(&BOB) > bob_balance - charged);
+		assert!(SponsorAllowance::<Test>::get(&BOB, &addr).unwrap() > 10_000 - charged);
+
+		// The contract can pay for itself.
+		assert_ok!(Contracts::set_deposit_payer(
+			RuntimeOrigin::signed(addr.clone()),
+			Some(DepositPayer::Contract)
+		));
+		let bob_balance = test_utils::get_balance(&BOB);
+		let _ = <Test as Config>::Currency::set_balance(&addr, 10_000);
+		assert_ok!(Contracts::call(
+			RuntimeOrigin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			(1_000u32, 500u32).encode(),
+		));
+		assert_eq!(test_utils::get_balance(&ALICE), alice_balance);
+		assert_eq!(test_utils::get_balance(&BOB), bob_balance);
+		assert_eq!(test_utils::get_balance(&addr), 10_000 - 500);
+
+		// Refunds go to whoever paid the deposit, even after the contract stopped delegating it.
+		// The contract paid last and is refunded first, the rest goes back to the sponsor.
+		assert_ok!(Contracts::set_deposit_payer(RuntimeOrigin::signed(addr.clone()), None));
+		assert_ok!(Contracts::call(
+			RuntimeOrigin::signed(ALICE),
+			addr.clone(),
+			0,
+			GAS_LIMIT,
+			None,
+			(0u32, 0u32).encode(),
+		));
+		assert_eq!(test_utils::get_balance(&ALICE), alice_balance);
+		assert_eq!(test_utils::get_balance(&BOB), bob_balance + 1_000);
+		assert_eq!(test_utils::get_balance(&addr), 10_000);
+		assert_eq!(
+			ContractInfoOf::<Test>::get(&addr).unwrap().deposit_payers(),
+			&[(DepositPayer::Sponsor(BOB), 4)]
+		);
+	});
+}
+
 #[test]
 fn storage_deposit_callee_works() {
 	let (wasm_caller, _code_hash_caller) = compile_module::<Test>("call").unwrap();
@@ -0,0 +1,44 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime API definition for the conviction voting pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+pub use pallet_conviction_voting::{Conviction, Delegations};
+
+sp_api::decl_runtime_apis! {
+	pub trait ConvictionVotingApi<AccountId, Class, Balance>
+		where
+			AccountId: Codec,
+			Class: Codec,
+			Balance: Codec,
+	{
+		/// Returns the effective voting power of `who` in `class` when voting with `conviction`:
+		/// their own conviction-weighted balance plus everything delegated to them.
+		fn voting_power(who: AccountId, class: Class, conviction: Conviction) -> Delegations<Balance>;
+
+		/// Returns the conviction-weighted votes and capital delegated to `who` in `class`.
+		fn delegated_power(who: AccountId, class: Class) -> Delegations<Balance>;
+
+		/// Returns all accounts delegating to `who` in `class`, along with what each delegates.
+		fn delegators(who: AccountId, class: Class) -> Vec<(AccountId, Delegations<Balance>)>;
+	}
+}
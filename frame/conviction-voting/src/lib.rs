@@ -34,6 +34,7 @@ use frame_support::{
 		fungible, Currency, Get, LockIdentifier, LockableCurrency, PollStatus, Polling,
 		ReservableCurrency, WithdrawReasons,
 	},
+	weights::Weight,
 };
 use frame_system::pallet_prelude::BlockNumberFor;
 use sp_runtime::{
@@ -43,6 +44,7 @@ use sp_runtime::{
 use sp_std::prelude::*;
 
 mod conviction;
+pub mod migration;
 mod types;
 mod vote;
 pub mod weights;
@@ -89,7 +91,11 @@ pub mod pallet {
 	use frame_support::{pallet_prelude::*, traits::ClassCountOf};
 	use frame_system::pallet_prelude::*;
 
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T, I = ()>(_);
 
 	#[pallet::config]
@@ -156,6 +162,30 @@ pub mod pallet {
 		ValueQuery,
 	>;
 
+	/// The accounts delegating to a particular account in a particular voting class, along with
+	/// the conviction-weighted votes and capital each of them delegates. Kept in sync with the
+	/// `Delegating` entries of `VotingFor` so that the delegators of an account can be listed
+	/// without iterating over all voters.
+	#[pallet::storage]
+	pub type DelegatorsOf<T: Config<I>, I: 'static = ()> = StorageNMap<
+		_,
+		(
+			NMapKey<Twox64Concat, T::AccountId>,
+			NMapKey<Twox64Concat, ClassOf<T, I>>,
+			NMapKey<Twox64Concat, T::AccountId>,
+		),
+		Delegations<BalanceOf<T, I>>,
+		OptionQuery,
+	>;
+
+	/// The raw `VotingFor` key up to which the delegations have been indexed into `DelegatorsOf`.
+	///
+	/// Present only while [`migration::v1::MigrateV0ToV1`] is still running; an empty key means
+	/// that no entry has been visited yet.
+	#[pallet::storage]
+	pub type DelegatorsMigrationCursor<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, BoundedVec<u8, ConstU32<1024>>, OptionQuery>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config<I>, I: 'static = ()> {
@@ -194,6 +224,13 @@ pub mod pallet {
 		BadClass,
 	}
 
+	#[pallet::hooks]
+	impl<T: Config<I>, I: 'static> Hooks<BlockNumberFor<T>> for Pallet<T, I> {
+		fn on_idle(_: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			migration::v1::MigrateV0ToV1::<T, I>::step(remaining_weight)
+		}
+	}
+
 	#[pallet::call]
 	impl<T: Config<I>, I: 'static> Pallet<T, I> {
 		/// Vote in a poll. If `vote.is_aye()`, the vote is to enact the proposal;
@@ -242,7 +279,7 @@ pub mod pallet {
 		// NOTE: weight must cover an incorrect voting of origin with max votes, this is ensure
 		// because a valid delegation cover decoding a direct voting with max votes.
 		#[pallet::call_index(1)]
-		#[pallet::weight(T::WeightInfo::delegate(T::MaxVotes::get()))]
+		#[pallet::weight(Pallet::<T, I>::delegate_weight(T::MaxVotes::get()))]
 		pub fn delegate(
 			origin: OriginFor<T>,
 			class: ClassOf<T, I>,
@@ -254,7 +291,7 @@ pub mod pallet {
 			let to = T::Lookup::lookup(to)?;
 			let votes = Self::try_delegate(who, class, to, conviction, balance)?;
 
-			Ok(Some(T::WeightInfo::delegate(votes)).into())
+			Ok(Some(Self::delegate_weight(votes)).into())
 		}
 
 		/// Undelegate the voting power of the sending account for a particular class of polls.
@@ -274,14 +311,14 @@ pub mod pallet {
 		// NOTE: weight must cover an incorrect voting of origin with max votes, this is ensure
 		// because a valid delegation cover decoding a direct voting with max votes.
 		#[pallet::call_index(2)]
-		#[pallet::weight(T::WeightInfo::undelegate(T::MaxVotes::get().into()))]
+		#[pallet::weight(Pallet::<T, I>::undelegate_weight(T::MaxVotes::get().into()))]
 		pub fn undelegate(
 			origin: OriginFor<T>,
 			class: ClassOf<T, I>,
 		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
 			let votes = Self::try_undelegate(who, class)?;
-			Ok(Some(T::WeightInfo::undelegate(votes)).into())
+			Ok(Some(Self::undelegate_weight(votes)).into())
 		}
 
 		/// Remove the lock caused by prior voting/delegating which has expired within a particular
@@ -376,10 +413,143 @@ pub mod pallet {
 			Self::try_remove_vote(&target, index, Some(class), scope)?;
 			Ok(())
 		}
+
+		/// Delegate the voting power (with some given conviction) of the sending account for
+		/// several classes of polls at once.
+		///
+		/// This is equivalent to calling `delegate` once for each of `classes` with the same
+		/// `to`, `conviction` and `balance`, and either all of the delegations succeed or none of
+		/// them do.
+		///
+		/// The dispatch origin of this call must be _Signed_, and the signing account must satisfy
+		/// the requirements of `delegate` for every class given.
+		///
+		/// - `classes`: The classes of polls to delegate. Must not contain duplicates.
+		/// - `to`: The account whose voting the `target` account's voting power will follow.
+		/// - `conviction`: The conviction that will be attached to the delegated votes.
+		/// - `balance`: The amount of the account's balance to be used in delegating for each
+		///   class.
+		///
+		/// Emits `Delegated` for each class.
+		///
+		/// Weight: `O(C * R)` where C is the number of classes and R is the number of polls the
+		///   voter delegating to has voted on. Weight is initially charged as if maximum votes,
+		///   but is refunded later.
+		#[pallet::call_index(6)]
+		#[pallet::weight(
+			Pallet::<T, I>::delegate_weight(T::MaxVotes::get()).saturating_mul(classes.len() as u64)
+		)]
+		pub fn batch_delegate(
+			origin: OriginFor<T>,
+			classes: BoundedVec<ClassOf<T, I>, ClassCountOf<T::Polls, TallyOf<T, I>>>,
+			to: AccountIdLookupOf<T>,
+			conviction: Conviction,
+			balance: BalanceOf<T, I>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let to = T::Lookup::lookup(to)?;
+			let mut weight = Weight::zero();
+			for class in classes {
+				let votes =
+					Self::try_delegate(who.clone(), class, to.clone(), conviction, balance)?;
+				weight.saturating_accrue(Self::delegate_weight(votes));
+			}
+			Ok(Some(weight).into())
+		}
+
+		/// Undelegate the voting power of the sending account for several classes of polls at
+		/// once.
+		///
+		/// This is equivalent to calling `undelegate` once for each of `classes`, and either all
+		/// of the undelegations succeed or none of them do.
+		///
+		/// The dispatch origin of this call must be _Signed_ and the signing account must be
+		/// currently delegating in every class given.
+		///
+		/// - `classes`: The classes of polls to remove the delegation from.
+		///
+		/// Emits `Undelegated` for each class.
+		///
+		/// Weight: `O(C * R)` where C is the number of classes and R is the number of polls the
+		///   voter delegating to has voted on. Weight is initially charged as if maximum votes,
+		///   but is refunded later.
+		#[pallet::call_index(7)]
+		#[pallet::weight(
+			Pallet::<T, I>::undelegate_weight(T::MaxVotes::get()).saturating_mul(classes.len() as u64)
+		)]
+		pub fn batch_undelegate(
+			origin: OriginFor<T>,
+			classes: BoundedVec<ClassOf<T, I>, ClassCountOf<T::Polls, TallyOf<T, I>>>,
+		) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			let mut weight = Weight::zero();
+			for class in classes {
+				let votes = Self::try_undelegate(who.clone(), class)?;
+				weight.saturating_accrue(Self::undelegate_weight(votes));
+			}
+			Ok(Some(weight).into())
+		}
 	}
 }
 
 impl<T: Config<I>, I: 'static> Pallet<T, I> {
+	/// The weight of delegating in a single class to an account which voted `votes` times.
+	///
+	/// This is the `delegate` weight plus the write to `DelegatorsOf`.
+	// TODO: Drop the extra write once the `delegate` benchmark has been re-run.
+	fn delegate_weight(votes: u32) -> Weight {
+		T::WeightInfo::delegate(votes).saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	/// The weight of undelegating in a single class from an account which voted `votes` times.
+	///
+	/// This is the `undelegate` weight plus the removal from `DelegatorsOf`.
+	// TODO: Drop the extra write once the `undelegate` benchmark has been re-run.
+	fn undelegate_weight(votes: u32) -> Weight {
+		T::WeightInfo::undelegate(votes).saturating_add(T::DbWeight::get().writes(1))
+	}
+
+	/// The conviction-weighted votes and capital delegated to `who` in `class`.
+	pub fn delegated_power(
+		who: &T::AccountId,
+		class: &ClassOf<T, I>,
+	) -> Delegations<BalanceOf<T, I>> {
+		match VotingFor::<T, I>::get(who, class) {
+			Voting::Casting(Casting { delegations, .. }) => delegations,
+			Voting::Delegating(Delegating { delegations, .. }) => delegations,
+		}
+	}
+
+	/// The effective voting power of `who` in `class` if they were to vote with `conviction`.
+	///
+	/// This is the conviction-weighted total balance of the account plus everything delegated
+	/// to it. An account which is itself delegating in `class` has no voting power of its own
+	/// there, and any delegations it receives are not counted either since second level
+	/// delegation is not supported.
+	pub fn voting_power(
+		who: &T::AccountId,
+		class: &ClassOf<T, I>,
+		conviction: Conviction,
+	) -> Delegations<BalanceOf<T, I>> {
+		match VotingFor::<T, I>::get(who, class) {
+			Voting::Casting(Casting { delegations, .. }) =>
+				conviction.votes(T::Currency::total_balance(who)).saturating_add(delegations),
+			Voting::Delegating(_) => Default::default(),
+		}
+	}
+
+	/// All accounts delegating to `who` in `class`, together with the conviction-weighted votes
+	/// and capital that each of them delegates.
+	///
+	/// The result may be incomplete while `DelegatorsMigrationCursor` is set, since existing
+	/// delegations are only indexed gradually by [`migration::v1::MigrateV0ToV1`].
+	pub fn delegators(
+		who: &T::AccountId,
+		class: &ClassOf<T, I>,
+	) -> Vec<(T::AccountId, Delegations<BalanceOf<T, I>>)> {
+		DelegatorsOf::<T, I>::iter_prefix((who, class)).collect()
+	}
+
 	/// Actually enact a vote, if legit.
 	fn try_vote(
 		who: &T::AccountId,
@@ -572,8 +742,9 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 					},
 				}
 
-				let votes =
-					Self::increase_upstream_delegation(&target, &class, conviction.votes(balance));
+				let delegated = conviction.votes(balance);
+				let votes = Self::increase_upstream_delegation(&target, &class, delegated);
+				DelegatorsOf::<T, I>::insert((&target, &class, &who), delegated);
 				// Extend the lock to `balance` (rather than setting it) since we don't know what
 				// other votes are in place.
 				Self::extend_lock(&who, &class, balance);
@@ -603,6 +774,7 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 							&class,
 							conviction.votes(balance),
 						);
+						DelegatorsOf::<T, I>::remove((&target, &class, &who));
 						let now = frame_system::Pallet::<T>::block_number();
 						let lock_periods = conviction.lock_periods().into();
 						prior.accumulate(
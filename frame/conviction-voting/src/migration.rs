@@ -0,0 +1,225 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage migrations for the conviction voting pallet.
+
+use super::*;
+use frame_support::{defensive, pallet_prelude::*, traits::OnRuntimeUpgrade};
+
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+
+pub mod v1 {
+	use super::*;
+	use frame_support::weights::WeightMeter;
+
+	/// The log target.
+	const TARGET: &'static str = "runtime::conviction-voting::migration::v1";
+
+	/// Builds the `DelegatorsOf` index from the delegations already recorded in `VotingFor`.
+	///
+	/// `VotingFor` holds an entry for every voter and class, so it is too large to be walked in
+	/// the upgrade block. The upgrade itself only bumps the storage version and stores an empty
+	/// `DelegatorsMigrationCursor`, at a cost of one read and two writes. The index is then
+	/// built in `on_idle` by [`Self::step`], which visits as many `VotingFor` entries as fit
+	/// into the remaining weight of each block: one read per entry, plus one write for each
+	/// delegation. The cursor is removed once every entry has been visited.
+	pub struct MigrateV0ToV1<T, I = ()>(PhantomData<(T, I)>);
+
+	impl<T: Config<I>, I: 'static> MigrateV0ToV1<T, I> {
+		/// Indexes as many delegations as fit into `limit`, resuming after the stored cursor.
+		///
+		/// Returns the consumed weight, which is a single read if no migration is running.
+		pub fn step(limit: Weight) -> Weight {
+			let db_weight = T::DbWeight::get();
+			let mut meter = WeightMeter::from_limit(limit);
+			// Reading and, unless we are done, updating the cursor.
+			if meter.try_consume(db_weight.reads_writes(1, 1)).is_err() {
+				return Weight::zero()
+			}
+			let Some(cursor) = DelegatorsMigrationCursor::<T, I>::get() else {
+				return db_weight.reads(1)
+			};
+			let mut iter = if cursor.is_empty() {
+				VotingFor::<T, I>::iter()
+			} else {
+				VotingFor::<T, I>::iter_from(cursor.into_inner())
+			};
+
+			while meter.try_consume(db_weight.reads_writes(1, 1)).is_ok() {
+				let Some((who, class, voting)) = iter.next() else {
+					DelegatorsMigrationCursor::<T, I>::kill();
+					log::info!(target: TARGET, "indexed all delegations.");
+					return meter.consumed()
+				};
+				if let Voting::Delegating(Delegating { balance, target, conviction, .. }) = voting {
+					DelegatorsOf::<T, I>::insert((target, class, who), conviction.votes(balance));
+				}
+			}
+
+			match BoundedVec::try_from(iter.last_raw_key().to_vec()) {
+				Ok(cursor) => DelegatorsMigrationCursor::<T, I>::put(cursor),
+				Err(_) => {
+					defensive!("`VotingFor` keys fit into the migration cursor; qed");
+					DelegatorsMigrationCursor::<T, I>::kill();
+				},
+			}
+			meter.consumed()
+		}
+	}
+
+	impl<T: Config<I>, I: 'static> OnRuntimeUpgrade for MigrateV0ToV1<T, I> {
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			let onchain_version = Pallet::<T, I>::on_chain_storage_version();
+			ensure!(onchain_version == 0, "migration from version 0 to 1.");
+			Ok(Vec::new())
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			let current_version = Pallet::<T, I>::current_storage_version();
+			let onchain_version = Pallet::<T, I>::on_chain_storage_version();
+			let weight = T::DbWeight::get().reads(1);
+			log::info!(
+				target: TARGET,
+				"running migration with current storage version {:?} / onchain {:?}.",
+				current_version,
+				onchain_version
+			);
+			if onchain_version != 0 {
+				log::warn!(target: TARGET, "skipping migration from v0 to v1.");
+				return weight
+			}
+			DelegatorsMigrationCursor::<T, I>::put(BoundedVec::default());
+			StorageVersion::new(1).put::<Pallet<T, I>>();
+			log::info!(target: TARGET, "started indexing delegations.");
+			weight.saturating_add(T::DbWeight::get().writes(2))
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			let onchain_version = Pallet::<T, I>::on_chain_storage_version();
+			ensure!(onchain_version == 1, "must upgrade from version 0 to 1.");
+			ensure!(
+				DelegatorsMigrationCursor::<T, I>::exists(),
+				"must start indexing the delegations."
+			);
+			Ok(())
+		}
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+	use super::*;
+	use crate::tests::{new_test_ext, MockDbWeight, Test};
+	use frame_support::weights::constants::RocksDbWeight;
+
+	#[test]
+	fn migration_v0_to_v1_indexes_delegations() {
+		new_test_ext().execute_with(|| {
+			StorageVersion::new(0).put::<Pallet<Test>>();
+			VotingFor::<Test>::insert(
+				1,
+				0,
+				Voting::Delegating(Delegating {
+					balance: 10,
+					target: 2,
+					conviction: Conviction::Locked2x,
+					delegations: Default::default(),
+					prior: Default::default(),
+				}),
+			);
+			VotingFor::<Test>::insert(
+				3,
+				1,
+				Voting::Delegating(Delegating {
+					balance: 30,
+					target: 2,
+					conviction: Conviction::None,
+					delegations: Default::default(),
+					prior: Default::default(),
+				}),
+			);
+			assert_eq!(DelegatorsOf::<Test>::iter().count(), 0);
+
+			v1::MigrateV0ToV1::<Test>::on_runtime_upgrade();
+			assert_eq!(Pallet::<Test>::on_chain_storage_version(), 1);
+			assert_eq!(DelegatorsOf::<Test>::iter().count(), 0);
+
+			v1::MigrateV0ToV1::<Test>::step(Weight::MAX);
+
+			assert!(!DelegatorsMigrationCursor::<Test>::exists());
+			assert_eq!(
+				Pallet::<Test>::delegators(&2, &0),
+				vec![(1, Delegations { votes: 20, capital: 10 })]
+			);
+			assert_eq!(
+				Pallet::<Test>::delegators(&2, &1),
+				vec![(3, Delegations { votes: 3, capital: 30 })]
+			);
+		});
+	}
+
+	#[test]
+	fn migration_v0_to_v1_spreads_a_large_voter_set_over_blocks() {
+		new_test_ext().execute_with(|| {
+			MockDbWeight::set(RocksDbWeight::get());
+			StorageVersion::new(0).put::<Pallet<Test>>();
+			let voters = 1_000u64;
+			for who in 10..10 + voters {
+				let voting = if who % 2 == 0 {
+					Voting::Delegating(Delegating {
+						balance: 10,
+						target: 1,
+						conviction: Conviction::Locked1x,
+						delegations: Default::default(),
+						prior: Default::default(),
+					})
+				} else {
+					Voting::default()
+				};
+				VotingFor::<Test>::insert(who, 0, voting);
+			}
+
+			let upgrade_weight = v1::MigrateV0ToV1::<Test>::on_runtime_upgrade();
+			assert_eq!(
+				upgrade_weight,
+				<Test as frame_system::Config>::DbWeight::get().reads_writes(1, 2)
+			);
+
+			// Room for the cursor and 100 entries per block.
+			let limit = <Test as frame_system::Config>::DbWeight::get().reads_writes(101, 101);
+			let mut blocks = 0;
+			while DelegatorsMigrationCursor::<Test>::exists() {
+				assert!(Pallet::<Test>::on_idle(blocks, limit).all_lte(limit));
+				blocks += 1;
+			}
+
+			assert_eq!(blocks, 11);
+			assert_eq!(Pallet::<Test>::delegators(&1, &0).len() as u64, voters / 2);
+			assert!(Pallet::<Test>::delegators(&1, &0)
+				.iter()
+				.all(|(_, delegations)| *delegations == Delegations { votes: 10, capital: 10 }));
+			// Nothing left to do.
+			assert_eq!(
+				Pallet::<Test>::on_idle(blocks, limit),
+				<Test as frame_system::Config>::DbWeight::get().reads(1)
+			);
+		});
+	}
+}
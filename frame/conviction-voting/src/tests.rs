@@ -22,6 +22,7 @@ use std::collections::BTreeMap;
 use frame_support::{
 	assert_noop, assert_ok, parameter_types,
 	traits::{ConstU32, ConstU64, Contains, Polling, VoteTally},
+	weights::RuntimeDbWeight,
 };
 use sp_core::H256;
 use sp_runtime::{
@@ -55,7 +56,7 @@ impl frame_system::Config for Test {
 	type BaseCallFilter = BaseFilter;
 	type BlockWeights = ();
 	type BlockLength = ();
-	type DbWeight = ();
+	type DbWeight = MockDbWeight;
 	type RuntimeOrigin = RuntimeOrigin;
 	type Nonce = u64;
 	type RuntimeCall = RuntimeCall;
@@ -106,6 +107,8 @@ parameter_types! {
 		(2, Completed(2, false)),
 		(3, Ongoing(Tally::from_parts(0, 0, 0), 0)),
 	].into_iter().collect();
+	// Free like `()` unless a test which meters storage accesses sets it.
+	pub static MockDbWeight: RuntimeDbWeight = RuntimeDbWeight { read: 0, write: 0 };
 }
 
 pub struct TestPolls;
@@ -880,3 +883,105 @@ fn errors_with_remove_vote_work() {
 		);
 	});
 }
+
+#[test]
+fn batch_delegation_works() {
+	new_test_ext().execute_with(|| {
+		Polls::set(
+			vec![
+				(0, Ongoing(Tally::new(0), 0)),
+				(1, Ongoing(Tally::new(0), 1)),
+				(2, Ongoing(Tally::new(0), 2)),
+			]
+			.into_iter()
+			.collect(),
+		);
+		assert_ok!(Voting::vote(RuntimeOrigin::signed(2), 0, aye(20, 0)));
+		assert_ok!(Voting::vote(RuntimeOrigin::signed(2), 1, aye(20, 0)));
+		assert_ok!(Voting::batch_delegate(
+			RuntimeOrigin::signed(1),
+			vec![0, 1].try_into().unwrap(),
+			2,
+			Conviction::Locked1x,
+			10
+		));
+		assert_eq!(tally(0), Tally::from_parts(12, 0, 30));
+		assert_eq!(tally(1), Tally::from_parts(12, 0, 30));
+		assert_eq!(tally(2), Tally::from_parts(0, 0, 0));
+
+		// Delegating to a class which is already delegated fails as a whole.
+		assert_noop!(
+			Voting::batch_delegate(
+				RuntimeOrigin::signed(1),
+				vec![2, 1].try_into().unwrap(),
+				2,
+				Conviction::Locked1x,
+				10
+			),
+			Error::<Test>::AlreadyDelegating
+		);
+
+		// The mock polls live outside of storage, so the tally changes made before the failure
+		// are not rolled back with the rest of the call.
+		let polls = Polls::get();
+		assert_noop!(
+			Voting::batch_undelegate(RuntimeOrigin::signed(1), vec![0, 2].try_into().unwrap()),
+			Error::<Test>::NotDelegating
+		);
+		Polls::set(polls);
+		assert_ok!(Voting::batch_undelegate(
+			RuntimeOrigin::signed(1),
+			vec![0, 1].try_into().unwrap()
+		));
+		assert_eq!(tally(0), Tally::from_parts(2, 0, 20));
+		assert_eq!(tally(1), Tally::from_parts(2, 0, 20));
+	});
+}
+
+#[test]
+fn delegators_are_tracked() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Voting::delegate(RuntimeOrigin::signed(1), 0, 3, Conviction::Locked2x, 10));
+		assert_ok!(Voting::delegate(RuntimeOrigin::signed(2), 0, 3, Conviction::None, 20));
+		assert_ok!(Voting::delegate(RuntimeOrigin::signed(2), 1, 3, Conviction::Locked1x, 20));
+
+		let mut delegators = Voting::delegators(&3, &0);
+		delegators.sort_by_key(|x| x.0);
+		assert_eq!(
+			delegators,
+			vec![
+				(1, Delegations { votes: 20, capital: 10 }),
+				(2, Delegations { votes: 2, capital: 20 }),
+			]
+		);
+		assert_eq!(Voting::delegators(&3, &1), vec![(2, Delegations { votes: 20, capital: 20 })]);
+		assert_eq!(Voting::delegated_power(&3, &0), Delegations { votes: 22, capital: 30 });
+
+		assert_ok!(Voting::undelegate(RuntimeOrigin::signed(1), 0));
+		assert_eq!(Voting::delegators(&3, &0), vec![(2, Delegations { votes: 2, capital: 20 })]);
+		assert_eq!(Voting::delegated_power(&3, &0), Delegations { votes: 2, capital: 20 });
+		assert!(Voting::delegators(&3, &2).is_empty());
+	});
+}
+
+#[test]
+fn voting_power_includes_delegations() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Voting::delegate(RuntimeOrigin::signed(1), 0, 3, Conviction::Locked2x, 10));
+
+		assert_eq!(
+			Voting::voting_power(&3, &0, Conviction::Locked1x),
+			Delegations { votes: 50, capital: 40 }
+		);
+		assert_eq!(
+			Voting::voting_power(&3, &1, Conviction::None),
+			Delegations { votes: 3, capital: 30 }
+		);
+		// A delegating account has no voting power of its own in that class.
+		assert_eq!(Voting::voting_power(&1, &0, Conviction::Locked6x), Default::default());
+		assert_eq!(
+			Voting::voting_power(&1, &1, Conviction::Locked6x),
+			Delegations { votes: 60, capital: 10 }
+		);
+	});
+}
@@ -23,6 +23,7 @@ use super::*;
 use crate::Pallet as CoreFellowship;
 
 use frame_benchmarking::v2::*;
+use frame_support::weights::Weight;
 use frame_system::{pallet_prelude::BlockNumberFor, RawOrigin};
 use sp_arithmetic::traits::Bounded;
 
@@ -208,6 +209,44 @@ mod benchmarks {
 		Ok(())
 	}
 
+	#[benchmark]
+	fn review_member() -> Result<(), BenchmarkError> {
+		// The member must not be due for demotion, for their evidence to be put under review.
+		let params = ParamsType {
+			active_salary: [100u32.into(); 9],
+			passive_salary: [10u32.into(); 9],
+			demotion_period: [100u32.into(); 9],
+			min_promotion_period: [100u32.into(); 9],
+			offboard_timeout: 1u32.into(),
+		};
+		Params::<T, I>::put(params);
+		let member = make_member::<T, I>(1)?;
+		ensure_evidence::<T, I>(&member)?;
+		T::Reviews::ensure_successful(&member, 1);
+
+		#[block]
+		{
+			CoreFellowship::<T, I>::review(&member);
+		}
+
+		assert_eq!(T::Members::rank_of(&member), Some(1));
+		assert!(UnderReview::<T, I>::contains_key(&member));
+		Ok(())
+	}
+
+	#[benchmark]
+	fn service_review() {
+		// The cycle ends without any member to review, leaving only the cost of each call.
+		Review::<T, I>::put(ReviewCursor::Start);
+
+		#[block]
+		{
+			CoreFellowship::<T, I>::service_review(Weight::MAX);
+		}
+
+		assert!(!Review::<T, I>::exists());
+	}
+
 	impl_benchmark_test_suite! {
 		CoreFellowship,
 		crate::tests::new_test_ext(),
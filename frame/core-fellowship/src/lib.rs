@@ -36,6 +36,10 @@
 //!   `bump` to demote the candidate by one rank.
 //! - If a candidate fails to be promoted to a member within the `offboard_timeout` period, then
 //!   anyone may call `bump` to remove the account's candidacy.
+//! - Every `ReviewPeriod` blocks a review cycle is started. Over the following blocks, idle block
+//!   space is used to go through each tracked member: those who have fallen beyond their
+//!   `demotion_period` are demoted, and those with outstanding evidence have a review opened
+//!   through `Reviews` (usually a referendum) which will `approve` or `promote` them if it passes.
 //! - Pre-existing members may call `import` to have their rank recognised and be inducted into this
 //!   pallet (to gain a salary and allow for eventual promotion).
 //! - If, externally to this pallet, a member or candidate has their rank removed completely, then
@@ -54,6 +58,9 @@
 //!
 //! Only tracked/ranked accounts may submit evidence for their proof and promotion. Candidates
 //! cannot be approved - they must proceed only to promotion prior to the offboard timeout elapsing.
+//!
+//! Any change of rank made by this pallet is reported to `OnRankChanged`, allowing e.g. a salary
+//! pallet to keep its payroll in line with the membership.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![recursion_limit = "128"]
@@ -61,15 +68,17 @@
 use codec::{Decode, Encode, MaxEncodedLen};
 use scale_info::TypeInfo;
 use sp_arithmetic::traits::{Saturating, Zero};
-use sp_runtime::RuntimeDebug;
+use sp_runtime::{DispatchError, RuntimeDebug};
 use sp_std::{marker::PhantomData, prelude::*};
 
 use frame_support::{
-	dispatch::DispatchResultWithPostInfo,
+	dispatch::{DispatchResult, DispatchResultWithPostInfo},
 	ensure, impl_ensure_origin_with_arg_ignoring_arg,
 	traits::{
-		tokens::Balance as BalanceTrait, EnsureOrigin, EnsureOriginWithArg, Get, RankedMembers,
+		tokens::Balance as BalanceTrait, EnsureOrigin, EnsureOriginWithArg, Get, OnRankChanged,
+		RankedMembers,
 	},
+	weights::Weight,
 	BoundedVec,
 };
 
@@ -83,6 +92,8 @@ pub mod weights;
 pub use pallet::*;
 pub use weights::WeightInfo;
 
+const LOG_TARGET: &str = "runtime::core-fellowship";
+
 /// The desired outcome for which evidence is presented.
 #[derive(Encode, Decode, Eq, PartialEq, Copy, Clone, TypeInfo, MaxEncodedLen, RuntimeDebug)]
 pub enum Wish {
@@ -127,6 +138,42 @@ impl<Balance: Default + Copy, BlockNumber: Default + Copy, const RANKS: usize> D
 	}
 }
 
+/// The progress of an ongoing review cycle.
+#[derive(Encode, Decode, Eq, PartialEq, Clone, TypeInfo, MaxEncodedLen, RuntimeDebug)]
+pub enum ReviewCursor<AccountId> {
+	/// The cycle has begun but no member has been reviewed yet.
+	Start,
+	/// All members up to and including this one have been reviewed.
+	After(AccountId),
+}
+
+/// Something able to open a review of the evidence presented by a member.
+///
+/// This would generally submit `call` as the proposal of a referendum, e.g. in `pallet-referenda`.
+pub trait ReviewScheduler<AccountId, Rank, Call> {
+	/// Open a review of `who`, who is currently at `rank`. `call` should be dispatched with root
+	/// origin if the review concludes in their favour.
+	fn schedule_review(who: &AccountId, rank: Rank, call: Call) -> DispatchResult;
+
+	/// The weight of a call to `schedule_review`.
+	fn schedule_review_weight() -> Weight;
+
+	/// Ensure that a call to `schedule_review` for `who` at `rank` will be successful if done
+	/// immediately after this call. Used in benchmarking code.
+	#[cfg(feature = "runtime-benchmarks")]
+	fn ensure_successful(_who: &AccountId, _rank: Rank) {}
+}
+
+impl<AccountId, Rank, Call> ReviewScheduler<AccountId, Rank, Call> for () {
+	fn schedule_review(_: &AccountId, _: Rank, _: Call) -> DispatchResult {
+		Err(DispatchError::Unavailable)
+	}
+
+	fn schedule_review_weight() -> Weight {
+		Weight::zero()
+	}
+}
+
 /// The status of a single member.
 #[derive(Encode, Decode, Eq, PartialEq, Clone, TypeInfo, MaxEncodedLen, RuntimeDebug)]
 pub struct MemberStatus<BlockNumber> {
@@ -144,7 +191,9 @@ pub mod pallet {
 	use frame_support::{
 		dispatch::Pays,
 		pallet_prelude::*,
+		storage::with_storage_layer,
 		traits::{tokens::GetSalary, EnsureOrigin},
+		weights::WeightMeter,
 	};
 	use frame_system::{ensure_root, pallet_prelude::*};
 
@@ -192,6 +241,21 @@ pub mod pallet {
 		/// The maximum size in bytes submitted evidence is allowed to be.
 		#[pallet::constant]
 		type EvidenceSize: Get<u32>;
+
+		/// Handler for any change to the rank of a member made by this pallet.
+		///
+		/// Should be `()` if `Members` already reports all changes of rank itself, as
+		/// `pallet-ranked-collective` does through its own `OnRankChanged`.
+		type OnRankChanged: OnRankChanged<Self::AccountId, RankOf<Self, I>>;
+
+		/// The means by which a review of a member's evidence is opened during a review cycle.
+		/// The given call is either `approve` or `promote` and expects to be dispatched by root.
+		type Reviews: ReviewScheduler<Self::AccountId, RankOf<Self, I>, Call<Self, I>>;
+
+		/// The number of blocks between the beginning of each review cycle. Zero disables review
+		/// cycles.
+		#[pallet::constant]
+		type ReviewPeriod: Get<BlockNumberFor<Self>>;
 	}
 
 	pub type ParamsOf<T, I> = ParamsType<<T as Config<I>>::Balance, BlockNumberFor<T>, RANK_COUNT>;
@@ -213,6 +277,17 @@ pub mod pallet {
 	pub(super) type MemberEvidence<T: Config<I>, I: 'static = ()> =
 		StorageMap<_, Twox64Concat, T::AccountId, (Wish, Evidence<T, I>), OptionQuery>;
 
+	/// The progress of the current review cycle, if one is ongoing.
+	#[pallet::storage]
+	pub(super) type Review<T: Config<I>, I: 'static = ()> =
+		StorageValue<_, ReviewCursor<T::AccountId>, OptionQuery>;
+
+	/// The members whose evidence is currently under review, with the block at which the review
+	/// was opened.
+	#[pallet::storage]
+	pub(super) type UnderReview<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Twox64Concat, T::AccountId, BlockNumberFor<T>, OptionQuery>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config<I>, I: 'static = ()> {
@@ -249,6 +324,12 @@ pub mod pallet {
 		},
 		/// Pre-ranked account has been inducted at their current rank.
 		Imported { who: T::AccountId, rank: RankOf<T, I> },
+		/// A new review cycle has begun.
+		ReviewCycleStarted,
+		/// Every tracked member has been reviewed in the current cycle.
+		ReviewCycleCompleted,
+		/// A review of the evidence of a member has been opened.
+		ReviewOpened { who: T::AccountId, wish: Wish },
 	}
 
 	#[pallet::error]
@@ -275,6 +356,29 @@ pub mod pallet {
 		TooSoon,
 	}
 
+	#[pallet::hooks]
+	impl<T: Config<I>, I: 'static> Hooks<BlockNumberFor<T>> for Pallet<T, I> {
+		fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+			let period = T::ReviewPeriod::get();
+			if period.is_zero() || !(now % period).is_zero() {
+				return Weight::zero()
+			}
+			if Review::<T, I>::exists() {
+				log::warn!(
+					target: LOG_TARGET,
+					"Review cycle did not complete within a review period; restarting it.",
+				);
+			}
+			Review::<T, I>::put(ReviewCursor::Start);
+			Self::deposit_event(Event::<T, I>::ReviewCycleStarted);
+			T::DbWeight::get().reads_writes(1, 1)
+		}
+
+		fn on_idle(_now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			Self::service_review(remaining_weight)
+		}
+	}
+
 	#[pallet::call]
 	impl<T: Config<I>, I: 'static> Pallet<T, I> {
 		/// Bump the state of a member.
@@ -288,37 +392,8 @@ pub mod pallet {
 		#[pallet::call_index(0)]
 		pub fn bump(origin: OriginFor<T>, who: T::AccountId) -> DispatchResultWithPostInfo {
 			let _ = ensure_signed(origin)?;
-			let mut member = Member::<T, I>::get(&who).ok_or(Error::<T, I>::NotTracked)?;
-			let rank = T::Members::rank_of(&who).ok_or(Error::<T, I>::Unranked)?;
-
-			let params = Params::<T, I>::get();
-			let demotion_period = if rank == 0 {
-				params.offboard_timeout
-			} else {
-				let rank_index = Self::rank_to_index(rank).ok_or(Error::<T, I>::InvalidRank)?;
-				params.demotion_period[rank_index]
-			};
-			let demotion_block = member.last_proof.saturating_add(demotion_period);
-
-			// Ensure enough time has passed.
-			let now = frame_system::Pallet::<T>::block_number();
-			if now >= demotion_block {
-				T::Members::demote(&who)?;
-				let maybe_to_rank = T::Members::rank_of(&who);
-				Self::dispose_evidence(who.clone(), rank, maybe_to_rank);
-				let event = if let Some(to_rank) = maybe_to_rank {
-					member.last_proof = now;
-					Member::<T, I>::insert(&who, &member);
-					Event::<T, I>::Demoted { who, to_rank }
-				} else {
-					Member::<T, I>::remove(&who);
-					Event::<T, I>::Offboarded { who }
-				};
-				Self::deposit_event(event);
-				return Ok(Pays::No.into())
-			}
-
-			Err(Error::<T, I>::NothingDoing.into())
+			Self::do_bump(who)?;
+			Ok(Pays::No.into())
 		}
 
 		/// Set the parameters.
@@ -408,6 +483,7 @@ pub mod pallet {
 				&who,
 				MemberStatus { is_active: true, last_promotion: now, last_proof: now },
 			);
+			T::OnRankChanged::on_rank_changed(&who, Some(0));
 			Self::deposit_event(Event::<T, I>::Inducted { who });
 			Ok(())
 		}
@@ -452,6 +528,7 @@ pub mod pallet {
 			member.last_proof = now;
 			Member::<T, I>::insert(&who, &member);
 			Self::dispose_evidence(who.clone(), rank, Some(to_rank));
+			T::OnRankChanged::on_rank_changed(&who, Some(to_rank));
 
 			Self::deposit_event(Event::<T, I>::Promoted { who, to_rank });
 
@@ -471,6 +548,8 @@ pub mod pallet {
 			ensure!(Member::<T, I>::contains_key(&who), Error::<T, I>::NotTracked);
 			Member::<T, I>::remove(&who);
 			MemberEvidence::<T, I>::remove(&who);
+			UnderReview::<T, I>::remove(&who);
+			T::OnRankChanged::on_rank_changed(&who, None);
 			Self::deposit_event(Event::<T, I>::Offboarded { who });
 			Ok(Pays::No.into())
 		}
@@ -496,6 +575,8 @@ pub mod pallet {
 			ensure!(Member::<T, I>::contains_key(&who), Error::<T, I>::NotTracked);
 			let replaced = MemberEvidence::<T, I>::contains_key(&who);
 			MemberEvidence::<T, I>::insert(&who, (wish, evidence));
+			// New evidence deserves a fresh review.
+			UnderReview::<T, I>::remove(&who);
 			Self::deposit_event(Event::<T, I>::Requested { who, wish });
 			Ok(if replaced { Pays::Yes } else { Pays::No }.into())
 		}
@@ -519,6 +600,7 @@ pub mod pallet {
 				&who,
 				MemberStatus { is_active: true, last_promotion: 0u32.into(), last_proof: now },
 			);
+			T::OnRankChanged::on_rank_changed(&who, Some(rank));
 			Self::deposit_event(Event::<T, I>::Imported { who, rank });
 
 			Ok(Pays::No.into())
@@ -537,7 +619,139 @@ pub mod pallet {
 			}
 		}
 
+		/// Demote `who` if their `last_proof` is now beyond their rank's `demotion_period`.
+		fn do_bump(who: T::AccountId) -> DispatchResult {
+			let mut member = Member::<T, I>::get(&who).ok_or(Error::<T, I>::NotTracked)?;
+			let rank = T::Members::rank_of(&who).ok_or(Error::<T, I>::Unranked)?;
+
+			let params = Params::<T, I>::get();
+			let demotion_period = if rank == 0 {
+				params.offboard_timeout
+			} else {
+				let rank_index = Self::rank_to_index(rank).ok_or(Error::<T, I>::InvalidRank)?;
+				params.demotion_period[rank_index]
+			};
+			let demotion_block = member.last_proof.saturating_add(demotion_period);
+
+			// Ensure enough time has passed.
+			let now = frame_system::Pallet::<T>::block_number();
+			if now >= demotion_block {
+				T::Members::demote(&who)?;
+				let maybe_to_rank = T::Members::rank_of(&who);
+				Self::dispose_evidence(who.clone(), rank, maybe_to_rank);
+				T::OnRankChanged::on_rank_changed(&who, maybe_to_rank);
+				let event = if let Some(to_rank) = maybe_to_rank {
+					member.last_proof = now;
+					Member::<T, I>::insert(&who, &member);
+					Event::<T, I>::Demoted { who, to_rank }
+				} else {
+					Member::<T, I>::remove(&who);
+					Event::<T, I>::Offboarded { who }
+				};
+				Self::deposit_event(event);
+				return Ok(())
+			}
+
+			Err(Error::<T, I>::NothingDoing.into())
+		}
+
+		/// Review as many members of the ongoing review cycle as `limit` allows, returning the
+		/// weight used.
+		pub(crate) fn service_review(limit: Weight) -> Weight {
+			let mut meter = WeightMeter::from_limit(limit);
+			if meter.try_consume(Self::service_review_weight()).is_err() {
+				return Weight::zero()
+			}
+			let Some(cursor) = Review::<T, I>::get() else { return T::DbWeight::get().reads(1) };
+			let per_member = Self::review_weight();
+
+			let mut members = match cursor {
+				ReviewCursor::Start => Member::<T, I>::iter_keys(),
+				ReviewCursor::After(ref last) =>
+					Member::<T, I>::iter_keys_from(Member::<T, I>::hashed_key_for(last)),
+			};
+			let mut cursor = cursor;
+			loop {
+				if meter.try_consume(per_member).is_err() {
+					Review::<T, I>::put(cursor);
+					break
+				}
+				match members.next() {
+					Some(who) => {
+						Self::review(&who);
+						cursor = ReviewCursor::After(who);
+					},
+					None => {
+						Review::<T, I>::kill();
+						Self::deposit_event(Event::<T, I>::ReviewCycleCompleted);
+						break
+					},
+				}
+			}
+			meter.consumed()
+		}
+
+		/// The weight of servicing the review cycle, excluding the members reviewed.
+		///
+		/// Reads and writes the `Review` cursor and reads past the last member.
+		// TODO: Use the weight of the `service_review` benchmark once it has been run.
+		pub(crate) fn service_review_weight() -> Weight {
+			T::DbWeight::get().reads_writes(2, 1).saturating_add(Weight::from_parts(
+				0,
+				ReviewCursor::<T::AccountId>::max_encoded_len() as u64,
+			))
+		}
+
+		/// The weight of reviewing a single member.
+		///
+		/// Bumping the member is followed by reading their evidence, which `submit_evidence`
+		/// covers, and by reading their rank and recording the review, which `set_active` covers.
+		/// Opening the review is accounted by `T::Reviews`.
+		// TODO: Use the weight of the `review_member` benchmark once it has been run.
+		pub(crate) fn review_weight() -> Weight {
+			T::WeightInfo::bump_offboard()
+				.max(T::WeightInfo::bump_demote())
+				.saturating_add(T::WeightInfo::submit_evidence())
+				.saturating_add(T::WeightInfo::set_active())
+				.saturating_add(T::Reviews::schedule_review_weight())
+		}
+
+		/// Review a single member: demote them if they are overdue, or otherwise open a review of
+		/// any evidence they have presented which is not already under review.
+		pub(crate) fn review(who: &T::AccountId) {
+			if with_storage_layer(|| Self::do_bump(who.clone())).is_ok() {
+				return
+			}
+			if UnderReview::<T, I>::contains_key(who) {
+				return
+			}
+			let Some((wish, _)) = MemberEvidence::<T, I>::get(who) else { return };
+			let Some(rank) = T::Members::rank_of(who) else { return };
+			let call = match wish {
+				// Candidates cannot be approved.
+				Wish::Retention if rank.is_zero() => return,
+				Wish::Retention => Call::<T, I>::approve { who: who.clone(), at_rank: rank },
+				Wish::Promotion =>
+					Call::<T, I>::promote { who: who.clone(), to_rank: rank.saturating_add(1) },
+			};
+			// A failing implementation must not leave partial changes behind.
+			match with_storage_layer(|| T::Reviews::schedule_review(who, rank, call)) {
+				Ok(()) => {
+					let now = frame_system::Pallet::<T>::block_number();
+					UnderReview::<T, I>::insert(who, now);
+					Self::deposit_event(Event::<T, I>::ReviewOpened { who: who.clone(), wish });
+				},
+				Err(e) => log::debug!(
+					target: LOG_TARGET,
+					"Could not open review of {:?}: {:?}",
+					who,
+					e,
+				),
+			}
+		}
+
 		fn dispose_evidence(who: T::AccountId, old_rank: u16, new_rank: Option<u16>) {
+			UnderReview::<T, I>::remove(&who);
 			if let Some((wish, evidence)) = MemberEvidence::<T, I>::take(&who) {
 				let e = Event::<T, I>::EvidenceJudged { who, wish, evidence, old_rank, new_rank };
 				Self::deposit_event(e);
@@ -23,7 +23,7 @@ use frame_support::{
 	assert_noop, assert_ok, ord_parameter_types,
 	pallet_prelude::Weight,
 	parameter_types,
-	traits::{tokens::GetSalary, ConstU32, ConstU64, Everything, IsInVec, TryMapSuccess},
+	traits::{tokens::GetSalary, ConstU32, ConstU64, Everything, Hooks, IsInVec, TryMapSuccess},
 };
 use frame_system::EnsureSignedBy;
 use sp_core::H256;
@@ -115,6 +115,44 @@ impl RankedMembers for TestClub {
 	}
 }
 
+thread_local! {
+	pub static RANK_CHANGES: RefCell<Vec<(u64, Option<u16>)>> = RefCell::new(vec![]);
+	pub static REVIEWS: RefCell<Vec<(u64, u16, Call<Test>)>> = RefCell::new(vec![]);
+	pub static FAIL_REVIEWS: RefCell<bool> = RefCell::new(false);
+}
+
+pub struct RecordRankChanges;
+impl OnRankChanged<u64, u16> for RecordRankChanges {
+	fn on_rank_changed(who: &u64, new_rank: Option<u16>) {
+		RANK_CHANGES.with(|c| c.borrow_mut().push((*who, new_rank)));
+	}
+}
+
+fn rank_changes() -> Vec<(u64, Option<u16>)> {
+	RANK_CHANGES.with(|c| c.borrow_mut().drain(..).collect())
+}
+
+pub struct RecordReviews;
+impl ReviewScheduler<u64, u16, Call<Test>> for RecordReviews {
+	fn schedule_review(who: &u64, rank: u16, call: Call<Test>) -> DispatchResult {
+		if FAIL_REVIEWS.with(|f| *f.borrow()) {
+			// Leave some changes behind before failing.
+			frame_support::storage::unhashed::put(b":partial_review", who);
+			return Err(DispatchError::Unavailable)
+		}
+		REVIEWS.with(|r| r.borrow_mut().push((*who, rank, call)));
+		Ok(())
+	}
+
+	fn schedule_review_weight() -> Weight {
+		Weight::from_parts(1_000_000, 1_000)
+	}
+}
+
+fn reviews() -> Vec<(u64, u16, Call<Test>)> {
+	REVIEWS.with(|r| r.borrow_mut().drain(..).collect())
+}
+
 fn set_rank(who: u64, rank: u16) {
 	CLUB.with(|club| club.borrow_mut().insert(who, rank));
 }
@@ -140,6 +178,9 @@ impl Config for Test {
 	type ApproveOrigin = TryMapSuccess<EnsureSignedBy<IsInVec<ZeroToNine>, u64>, TryMorphInto<u16>>;
 	type PromoteOrigin = TryMapSuccess<EnsureSignedBy<IsInVec<ZeroToNine>, u64>, TryMorphInto<u16>>;
 	type EvidenceSize = ConstU32<1024>;
+	type OnRankChanged = RecordRankChanges;
+	type Reviews = RecordReviews;
+	type ReviewPeriod = ConstU64<10>;
 }
 
 pub fn new_test_ext() -> sp_io::TestExternalities {
@@ -354,3 +395,165 @@ fn active_changing_get_salary_works() {
 		}
 	});
 }
+
+fn evidence(who: u64, wish: Wish) -> DispatchResultWithPostInfo {
+	CoreFellowship::submit_evidence(signed(who), wish, vec![0; 8].try_into().unwrap())
+}
+
+fn review_weight() -> Weight {
+	CoreFellowship::service_review_weight().saturating_add(CoreFellowship::review_weight())
+}
+
+/// Start a review cycle at the current block, regardless of the review period, and run it to
+/// completion.
+fn run_review_cycle() {
+	Review::<Test>::put(ReviewCursor::Start);
+	CoreFellowship::on_idle(System::block_number(), Weight::MAX);
+}
+
+#[test]
+fn rank_changes_are_reported() {
+	new_test_ext().execute_with(|| {
+		set_rank(10, 1);
+		assert_ok!(CoreFellowship::import(signed(10)));
+		assert_ok!(CoreFellowship::induct(signed(10), 20));
+		assert_eq!(rank_changes(), vec![(10, Some(1)), (20, Some(0))]);
+
+		run_to(4);
+		assert_ok!(CoreFellowship::promote(signed(1), 20, 1));
+		assert_eq!(rank_changes(), vec![(20, Some(1))]);
+
+		run_to(6);
+		assert_ok!(CoreFellowship::bump(signed(0), 20));
+		assert_eq!(rank_changes(), vec![(20, Some(0))]);
+		run_to(7);
+		assert_ok!(CoreFellowship::bump(signed(0), 20));
+		assert_eq!(rank_changes(), vec![(20, None)]);
+
+		unrank(10);
+		assert_ok!(CoreFellowship::offboard(signed(0), 10));
+		assert_eq!(rank_changes(), vec![(10, None)]);
+	});
+}
+
+#[test]
+fn review_cycle_only_starts_each_review_period() {
+	new_test_ext().execute_with(|| {
+		run_to(9);
+		CoreFellowship::on_initialize(9);
+		assert!(Review::<Test>::get().is_none());
+		run_to(10);
+		CoreFellowship::on_initialize(10);
+		assert_eq!(Review::<Test>::get(), Some(ReviewCursor::Start));
+		System::assert_last_event(Event::<Test>::ReviewCycleStarted.into());
+	});
+}
+
+#[test]
+fn review_cycle_opens_reviews_and_demotes() {
+	new_test_ext().execute_with(|| {
+		set_rank(5, 5);
+		assert_ok!(CoreFellowship::import(signed(5)));
+		set_rank(2, 2);
+		assert_ok!(CoreFellowship::import(signed(2)));
+		set_rank(3, 0);
+		assert_ok!(CoreFellowship::import(signed(3)));
+		set_rank(4, 1);
+		assert_ok!(CoreFellowship::import(signed(4)));
+		rank_changes();
+
+		assert_ok!(evidence(2, Wish::Promotion));
+		assert_ok!(evidence(4, Wish::Retention));
+		assert_ok!(evidence(5, Wish::Retention));
+
+		// The rank 1 member and the candidate are now overdue.
+		run_to(3);
+		run_review_cycle();
+		assert!(Review::<Test>::get().is_none());
+		System::assert_last_event(Event::<Test>::ReviewCycleCompleted.into());
+
+		let mut opened = reviews();
+		opened.sort_by_key(|r| r.0);
+		assert_eq!(
+			opened,
+			vec![
+				(2, 2, Call::promote { who: 2, to_rank: 3 }),
+				(5, 5, Call::approve { who: 5, at_rank: 5 }),
+			]
+		);
+		assert!(UnderReview::<Test>::contains_key(2));
+		assert!(UnderReview::<Test>::contains_key(5));
+		// Overdue members were demoted rather than reviewed.
+		assert!(!UnderReview::<Test>::contains_key(4));
+		assert!(!MemberEvidence::<Test>::contains_key(4));
+		assert_eq!(TestClub::rank_of(&4), Some(0));
+		assert!(!Member::<Test>::contains_key(3));
+		let mut changes = rank_changes();
+		changes.sort();
+		assert_eq!(changes, vec![(3, None), (4, Some(0))]);
+
+		// Nothing new to review in the next cycle.
+		run_review_cycle();
+		assert!(reviews().is_empty());
+
+		// Fresh evidence is reviewed again.
+		assert_ok!(evidence(5, Wish::Retention));
+		run_review_cycle();
+		assert_eq!(reviews(), vec![(5, 5, Call::approve { who: 5, at_rank: 5 })]);
+
+		// Judging the evidence closes the review.
+		assert_ok!(CoreFellowship::approve(RuntimeOrigin::root(), 5, 5));
+		assert!(!UnderReview::<Test>::contains_key(5));
+	});
+}
+
+#[test]
+fn failed_review_scheduling_is_rolled_back() {
+	new_test_ext().execute_with(|| {
+		set_rank(5, 5);
+		assert_ok!(CoreFellowship::import(signed(5)));
+		assert_ok!(evidence(5, Wish::Retention));
+
+		FAIL_REVIEWS.with(|f| *f.borrow_mut() = true);
+		run_review_cycle();
+		FAIL_REVIEWS.with(|f| *f.borrow_mut() = false);
+
+		assert!(frame_support::storage::unhashed::get_raw(b":partial_review").is_none());
+		assert!(!UnderReview::<Test>::contains_key(5));
+		assert!(reviews().is_empty());
+
+		// The review is opened once scheduling works again.
+		run_review_cycle();
+		assert_eq!(reviews(), vec![(5, 5, Call::approve { who: 5, at_rank: 5 })]);
+	});
+}
+
+#[test]
+fn review_cycle_resumes_across_blocks() {
+	new_test_ext().execute_with(|| {
+		for i in 1..=3 {
+			set_rank(i, 9);
+			assert_ok!(CoreFellowship::import(signed(i)));
+		}
+		CoreFellowship::on_initialize(10);
+
+		let mut reviewed = vec![];
+		for n in 1..=3 {
+			CoreFellowship::on_idle(10, review_weight());
+			match Review::<Test>::get() {
+				Some(ReviewCursor::After(who)) => reviewed.push(who),
+				other => panic!("unexpected cursor after {} members: {:?}", n, other),
+			}
+		}
+		reviewed.sort();
+		assert_eq!(reviewed, vec![1, 2, 3]);
+
+		// Not enough weight to do anything.
+		CoreFellowship::on_idle(10, Weight::zero());
+		assert!(Review::<Test>::get().is_some());
+
+		CoreFellowship::on_idle(10, review_weight());
+		assert!(Review::<Test>::get().is_none());
+		System::assert_last_event(Event::<Test>::ReviewCycleCompleted.into());
+	});
+}
@@ -1642,4 +1642,163 @@ mod tests {
 			);
 		})
 	}
+
+	/// General transactions are authorized by their transaction extension rather than by a
+	/// signature.
+	mod general_transactions {
+		use super::super::Executive as FrameExecutive;
+		use codec::{Decode, Encode};
+		use frame_support::derive_impl;
+		use frame_system::{ChainContext, RawOrigin};
+		use scale_info::TypeInfo;
+		use sp_core::H256;
+		use sp_runtime::{
+			generic,
+			testing::{Digest, TestSignature},
+			traits::{
+				BlakeTwo256, DispatchInfoOf, Hash, Header as HeaderT, TransactionExtension,
+				ValidateResult,
+			},
+			transaction_validity::{
+				InvalidTransaction, TransactionValidityError, ValidTransaction,
+			},
+			BuildStorage,
+		};
+
+		frame_support::construct_runtime!(
+			pub struct Runtime
+			{
+				System: frame_system::{Pallet, Call, Config<T>, Storage, Event<T>},
+			}
+		);
+
+		#[derive_impl(frame_system::config_preludes::TestDefaultConfig as frame_system::DefaultConfig)]
+		impl frame_system::Config for Runtime {
+			type Block = Block;
+			type RuntimeOrigin = RuntimeOrigin;
+			type RuntimeCall = RuntimeCall;
+			type RuntimeEvent = RuntimeEvent;
+			type PalletInfo = PalletInfo;
+			type OnSetCode = ();
+		}
+
+		/// Transaction extension that authorizes the transaction as coming from the account it
+		/// carries, if any.
+		#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+		pub struct AuthorizeAccount(Option<u64>);
+
+		impl TransactionExtension<RuntimeCall> for AuthorizeAccount {
+			const IDENTIFIER: &'static str = "AuthorizeAccount";
+			type Implicit = ();
+			type Val = ();
+			type Pre = ();
+
+			fn implicit(&self) -> Result<(), TransactionValidityError> {
+				Ok(())
+			}
+
+			fn validate(
+				&self,
+				origin: RuntimeOrigin,
+				_: &RuntimeCall,
+				_: &DispatchInfoOf<RuntimeCall>,
+				_: usize,
+				_: &[u8],
+			) -> ValidateResult<(), RuntimeCall> {
+				let origin = match self.0 {
+					Some(who) => RawOrigin::Signed(who).into(),
+					None => origin,
+				};
+				Ok((ValidTransaction::default(), (), origin))
+			}
+
+			fn prepare(
+				self,
+				_: (),
+				_: &RuntimeOrigin,
+				_: &RuntimeCall,
+				_: &DispatchInfoOf<RuntimeCall>,
+				_: usize,
+			) -> Result<(), TransactionValidityError> {
+				Ok(())
+			}
+		}
+
+		type SignedExtra = frame_system::CheckWeight<Runtime>;
+		type UncheckedExtrinsic = generic::UncheckedExtrinsic<
+			u64,
+			RuntimeCall,
+			TestSignature,
+			SignedExtra,
+			AuthorizeAccount,
+		>;
+		type Header = generic::Header<u64, BlakeTwo256>;
+		type Block = generic::Block<Header, UncheckedExtrinsic>;
+
+		type Executive =
+			FrameExecutive<Runtime, Block, ChainContext<Runtime>, Runtime, AllPalletsWithSystem>;
+
+		fn remark(account: Option<u64>) -> UncheckedExtrinsic {
+			UncheckedExtrinsic::new_general(
+				RuntimeCall::System(frame_system::Call::remark_with_event { remark: vec![1] }),
+				AuthorizeAccount(account),
+			)
+		}
+
+		fn new_test_ext() -> sp_io::TestExternalities {
+			let mut ext: sp_io::TestExternalities =
+				frame_system::GenesisConfig::<Runtime>::default()
+					.build_storage()
+					.unwrap()
+					.into();
+			ext.execute_with(|| {
+				Executive::initialize_block(&Header::new(
+					1,
+					H256::default(),
+					H256::default(),
+					[69u8; 32].into(),
+					Digest::default(),
+				));
+			});
+			ext
+		}
+
+		#[test]
+		fn general_extrinsic_is_dispatched_with_the_transformed_origin() {
+			new_test_ext().execute_with(|| {
+				assert_eq!(Executive::apply_extrinsic(remark(Some(7))), Ok(Ok(())));
+				System::assert_last_event(
+					frame_system::Event::Remarked { sender: 7, hash: BlakeTwo256::hash(&[1]) }
+						.into(),
+				);
+			});
+		}
+
+		#[test]
+		fn general_extrinsic_without_authorization_is_rejected() {
+			new_test_ext().execute_with(|| {
+				assert_eq!(
+					Executive::apply_extrinsic(remark(None)),
+					Err(InvalidTransaction::UnknownOrigin.into()),
+				);
+			});
+		}
+
+		#[test]
+		fn transaction_extensions_are_part_of_the_metadata() {
+			let extrinsic = Runtime::metadata_ir().extrinsic;
+			assert_eq!(
+				extrinsic
+					.transaction_extensions
+					.iter()
+					.map(|e| e.identifier)
+					.collect::<Vec<_>>(),
+				vec!["AuthorizeAccount"],
+			);
+			assert_eq!(
+				extrinsic.signed_extensions.iter().map(|e| e.identifier).collect::<Vec<_>>(),
+				vec!["CheckWeight"],
+			);
+		}
+	}
 }
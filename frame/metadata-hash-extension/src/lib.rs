@@ -0,0 +1,185 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! The [`CheckMetadataHash`] signed extension.
+//!
+//! The extension commits to the hash of the Merkleized metadata of the runtime. Offline signers,
+//! like hardware wallets, use this hash to verify the type information they receive to decode
+//! the extrinsic they are asked to sign. If the signer used different metadata, the signature is
+//! invalid and the transaction is rejected.
+//!
+//! The metadata hash is computed when building the runtime. It is passed to the runtime using
+//! the `RUNTIME_METADATA_HASH` environment variable, which is set by `substrate-wasm-builder` when
+//! `enable_metadata_hash` was called on the builder. The runtime `build.rs` needs to look like:
+//!
+//! ```ignore
+//! substrate_wasm_builder::WasmBuilder::new()
+//! 	.with_current_project()
+//! 	.export_heap_base()
+//! 	.import_memory()
+//! 	.enable_metadata_hash("UNIT", 12)
+//! 	.build()
+//! ```
+//!
+//! The hash is computed using the `merkleized-metadata` crate, which also provides the proofs
+//! of the types that are required to decode an extrinsic.
+
+#[cfg(test)]
+mod tests;
+
+use codec::{Decode, Encode};
+use frame_system::Config;
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{DispatchInfoOf, SignedExtension},
+	transaction_validity::TransactionValidityError,
+};
+
+/// The mode of [`CheckMetadataHash`].
+#[derive(Decode, Encode, PartialEq, Debug, TypeInfo, Clone, Copy, Eq)]
+pub enum Mode {
+	/// The metadata hash is not part of the signed payload.
+	Disabled,
+	/// The metadata hash is part of the signed payload.
+	Enabled,
+}
+
+/// Where the metadata hash comes from.
+#[derive(Default, Debug, PartialEq, Clone, Copy, Eq)]
+enum MetadataHash {
+	/// Use the hash that was passed at compile time using `RUNTIME_METADATA_HASH`.
+	#[default]
+	FetchFromEnv,
+	/// Use the given hash.
+	Custom([u8; 32]),
+}
+
+impl MetadataHash {
+	/// Returns the metadata hash, if there is any.
+	fn hash(&self) -> Option<[u8; 32]> {
+		match self {
+			Self::FetchFromEnv => option_env!("RUNTIME_METADATA_HASH").map(|hash| {
+				decode_hex(hash).expect("`RUNTIME_METADATA_HASH` is a hex encoded 32 byte hash")
+			}),
+			Self::Custom(hash) => Some(*hash),
+		}
+	}
+}
+
+/// Decode a hex encoded 32 byte hash, optionally prefixed with `0x`.
+fn decode_hex(hex: &str) -> Option<[u8; 32]> {
+	let hex = hex.strip_prefix("0x").unwrap_or(hex).as_bytes();
+	if hex.len() != 64 {
+		return None
+	}
+
+	let nibble = |c: u8| match c {
+		b'0'..=b'9' => Some(c - b'0'),
+		b'a'..=b'f' => Some(c - b'a' + 10),
+		b'A'..=b'F' => Some(c - b'A' + 10),
+		_ => None,
+	};
+
+	let mut hash = [0u8; 32];
+	for (byte, pair) in hash.iter_mut().zip(hex.chunks(2)) {
+		*byte = nibble(pair[0])? << 4 | nibble(pair[1])?;
+	}
+
+	Some(hash)
+}
+
+/// Extension for optionally verifying the metadata hash.
+///
+/// The metadata hash is cryptographically representing the metadata of the runtime. If the
+/// [`Mode`] is [`Mode::Enabled`], the metadata hash is added to the signed payload. So, the
+/// transaction is only valid if the signer used the same metadata hash as the runtime.
+///
+/// # Transaction Validity
+///
+/// A transaction that was signed with a different metadata hash has an invalid signature. The
+/// validity is not affected in any other way.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct CheckMetadataHash<T> {
+	_phantom: sp_std::marker::PhantomData<T>,
+	mode: Mode,
+	#[codec(skip)]
+	metadata_hash: MetadataHash,
+}
+
+impl<T> CheckMetadataHash<T> {
+	/// Create a new `SignedExtension` to check the metadata hash.
+	///
+	/// The hash is only part of the signed payload if `enable` is `true`.
+	pub fn new(enable: bool) -> Self {
+		Self {
+			_phantom: Default::default(),
+			mode: if enable { Mode::Enabled } else { Mode::Disabled },
+			metadata_hash: MetadataHash::FetchFromEnv,
+		}
+	}
+
+	/// Create a new `SignedExtension` that uses the given `metadata_hash`.
+	///
+	/// The metadata hash is enabled. This is mainly useful for tests.
+	pub fn new_with_custom_hash(metadata_hash: [u8; 32]) -> Self {
+		Self {
+			_phantom: Default::default(),
+			mode: Mode::Enabled,
+			metadata_hash: MetadataHash::Custom(metadata_hash),
+		}
+	}
+}
+
+impl<T> sp_std::fmt::Debug for CheckMetadataHash<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "CheckMetadataHash {{ mode: {:?} }}", self.mode)
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for CheckMetadataHash<T> {
+	type AccountId = T::AccountId;
+	type Call = <T as Config>::RuntimeCall;
+	type AdditionalSigned = Option<[u8; 32]>;
+	type Pre = ();
+	const IDENTIFIER: &'static str = "CheckMetadataHash";
+
+	fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+		match self.mode {
+			Mode::Disabled => Ok(None),
+			Mode::Enabled => Ok(self.metadata_hash.hash()),
+		}
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		self.validate(who, call, info, len).map(|_| ())
+	}
+}
@@ -0,0 +1,69 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use frame_support::derive_impl;
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test
+	{
+		System: frame_system::{Pallet, Call, Config<T>, Storage, Event<T>},
+	}
+);
+
+#[derive_impl(frame_system::config_preludes::TestDefaultConfig as frame_system::DefaultConfig)]
+impl frame_system::Config for Test {
+	type Block = Block;
+	type RuntimeOrigin = RuntimeOrigin;
+	type RuntimeCall = RuntimeCall;
+	type RuntimeEvent = RuntimeEvent;
+	type BaseCallFilter = frame_support::traits::Everything;
+	type PalletInfo = PalletInfo;
+	type OnSetCode = ();
+}
+
+#[test]
+fn disabled_mode_does_not_add_the_hash() {
+	let ext = CheckMetadataHash::<Test>::new_with_custom_hash([1; 32]);
+	assert_eq!(ext.additional_signed().unwrap(), Some([1; 32]));
+
+	let ext = CheckMetadataHash::<Test> { mode: Mode::Disabled, ..ext };
+	assert_eq!(ext.additional_signed().unwrap(), None);
+}
+
+#[test]
+fn only_the_mode_is_encoded() {
+	let ext = CheckMetadataHash::<Test>::new_with_custom_hash([1; 32]);
+	assert_eq!(ext.encode(), Mode::Enabled.encode());
+
+	let decoded = CheckMetadataHash::<Test>::decode(&mut &ext.encode()[..]).unwrap();
+	assert_eq!(decoded.mode, Mode::Enabled);
+	assert_eq!(decoded.metadata_hash, MetadataHash::FetchFromEnv);
+}
+
+#[test]
+fn decode_hex_works() {
+	let hash = [0xab; 32];
+	let hex = "ab".repeat(32);
+
+	assert_eq!(decode_hex(&hex), Some(hash));
+	assert_eq!(decode_hex(&format!("0x{}", hex.to_uppercase())), Some(hash));
+	assert_eq!(decode_hex(&hex[2..]), None);
+	assert_eq!(decode_hex(&format!("zz{}", &hex[2..])), None);
+}
@@ -54,7 +54,10 @@ use sp_std::{marker::PhantomData, prelude::*};
 use frame_support::{
 	dispatch::{DispatchError, DispatchResultWithPostInfo, PostDispatchInfo},
 	ensure, impl_ensure_origin_with_arg_ignoring_arg,
-	traits::{EnsureOrigin, EnsureOriginWithArg, PollStatus, Polling, RankedMembers, VoteTally},
+	traits::{
+		EnsureOrigin, EnsureOriginWithArg, OnRankChanged, PollStatus, Polling, RankedMembers,
+		VoteTally,
+	},
 	CloneNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound,
 };
 
@@ -403,6 +406,10 @@ pub mod pallet {
 		/// Rank_delta is defined as the number of ranks above the minimum required to take part
 		/// in the poll.
 		type VoteWeight: Convert<Rank, Votes>;
+
+		/// Handler for any change to the rank of a member, including being added to and removed
+		/// from the collective.
+		type OnRankChanged: OnRankChanged<Self::AccountId, Rank>;
 	}
 
 	/// The number of members in the collective who have at least the rank according to the index
@@ -548,6 +555,7 @@ pub mod pallet {
 				Self::remove_from_rank(&who, r)?;
 			}
 			Members::<T, I>::remove(&who);
+			T::OnRankChanged::on_rank_changed(&who, None);
 			Self::deposit_event(Event::MemberRemoved { who, rank });
 			Ok(PostDispatchInfo {
 				actual_weight: Some(T::WeightInfo::remove_member(rank as u32)),
@@ -687,6 +695,7 @@ pub mod pallet {
 			IdToIndex::<T, I>::insert(0, &who, index);
 			IndexToId::<T, I>::insert(0, index, &who);
 			MemberCount::<T, I>::insert(0, count);
+			T::OnRankChanged::on_rank_changed(&who, Some(0));
 			Self::deposit_event(Event::MemberAdded { who });
 			Ok(())
 		}
@@ -709,6 +718,7 @@ pub mod pallet {
 			IdToIndex::<T, I>::insert(rank, &who, index);
 			IndexToId::<T, I>::insert(rank, index, &who);
 			Members::<T, I>::insert(&who, MemberRecord { rank });
+			T::OnRankChanged::on_rank_changed(&who, Some(rank));
 			Self::deposit_event(Event::RankChanged { who, rank });
 			Ok(())
 		}
@@ -726,6 +736,7 @@ pub mod pallet {
 
 			Self::remove_from_rank(&who, rank)?;
 			let maybe_rank = rank.checked_sub(1);
+			T::OnRankChanged::on_rank_changed(&who, maybe_rank);
 			match maybe_rank {
 				None => {
 					Members::<T, I>::remove(&who);
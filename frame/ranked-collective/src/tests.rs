@@ -17,13 +17,15 @@
 
 //! The crate's tests.
 
-use std::collections::BTreeMap;
+use std::{cell::RefCell, collections::BTreeMap};
 
 use frame_support::{
 	assert_noop, assert_ok,
 	error::BadOrigin,
 	parameter_types,
-	traits::{ConstU16, ConstU32, ConstU64, EitherOf, Everything, MapSuccess, Polling},
+	traits::{
+		ConstU16, ConstU32, ConstU64, EitherOf, Everything, MapSuccess, OnRankChanged, Polling,
+	},
 };
 use sp_core::{Get, H256};
 use sp_runtime::{
@@ -173,6 +175,21 @@ parameter_types! {
 	pub static MinRankOfClassDelta: Rank = 0;
 }
 
+thread_local! {
+	pub static RANK_CHANGES: RefCell<Vec<(u64, Option<Rank>)>> = RefCell::new(vec![]);
+}
+
+pub struct RecordRankChanges;
+impl OnRankChanged<u64, Rank> for RecordRankChanges {
+	fn on_rank_changed(who: &u64, new_rank: Option<Rank>) {
+		RANK_CHANGES.with(|c| c.borrow_mut().push((*who, new_rank)));
+	}
+}
+
+fn rank_changes() -> Vec<(u64, Option<Rank>)> {
+	RANK_CHANGES.with(|c| c.borrow_mut().drain(..).collect())
+}
+
 impl Config for Test {
 	type WeightInfo = ();
 	type RuntimeEvent = RuntimeEvent;
@@ -191,6 +208,7 @@ impl Config for Test {
 	type Polls = TestPolls;
 	type MinRankOfClass = MinRankOfClass<MinRankOfClassDelta>;
 	type VoteWeight = Geometric;
+	type OnRankChanged = RecordRankChanges;
 }
 
 pub fn new_test_ext() -> sp_io::TestExternalities {
@@ -282,6 +300,29 @@ fn add_remove_works() {
 	});
 }
 
+#[test]
+fn rank_changes_are_reported() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Club::add_member(RuntimeOrigin::root(), 1));
+		assert_ok!(Club::promote_member(RuntimeOrigin::root(), 1));
+		assert_ok!(Club::promote_member(RuntimeOrigin::root(), 1));
+		assert_eq!(rank_changes(), vec![(1, Some(0)), (1, Some(1)), (1, Some(2))]);
+
+		assert_ok!(Club::demote_member(RuntimeOrigin::root(), 1));
+		assert_eq!(rank_changes(), vec![(1, Some(1))]);
+		assert_ok!(Club::remove_member(RuntimeOrigin::root(), 1, 1));
+		assert_eq!(rank_changes(), vec![(1, None)]);
+
+		assert_ok!(Club::add_member(RuntimeOrigin::root(), 2));
+		assert_ok!(Club::demote_member(RuntimeOrigin::root(), 2));
+		assert_eq!(rank_changes(), vec![(2, Some(0)), (2, None)]);
+
+		// Failed changes are not reported.
+		assert_noop!(Club::demote_member(RuntimeOrigin::root(), 2), Error::<Test>::NotMember);
+		assert_eq!(rank_changes(), vec![]);
+	});
+}
+
 #[test]
 fn promote_demote_works() {
 	new_test_ext().execute_with(|| {
@@ -50,12 +50,19 @@ fn dummy_call<T: Config<I>, I: 'static>() -> Bounded<<T as Config<I>>::RuntimeCa
 }
 
 fn create_referendum<T: Config<I>, I: 'static>(origin: T::RuntimeOrigin) -> ReferendumIndex {
+	create_referendum_with::<T, I>(origin, RawOrigin::Root.into())
+}
+
+fn create_referendum_with<T: Config<I>, I: 'static>(
+	origin: T::RuntimeOrigin,
+	proposal_origin: PalletsOriginOf<T>,
+) -> ReferendumIndex {
 	if let Ok(caller) = frame_system::ensure_signed(origin.clone()) {
 		T::Currency::make_free_balance_be(&caller, BalanceOf::<T, I>::max_value());
 		whitelist_account!(caller);
 	}
 
-	let proposal_origin = Box::new(RawOrigin::Root.into());
+	let proposal_origin = Box::new(proposal_origin);
 	let proposal = dummy_call::<T, I>();
 	let enactment_moment = DispatchTime::After(0u32.into());
 	let call = crate::Call::<T, I>::submit { proposal_origin, proposal, enactment_moment };
@@ -64,6 +71,44 @@ fn create_referendum<T: Config<I>, I: 'static>(origin: T::RuntimeOrigin) -> Refe
 	index
 }
 
+/// Returns the proposal origin of a track matching `filter`, if the runtime provides one.
+fn track_origin<T: Config<I>, I: 'static>(
+	filter: impl Fn(&TrackInfoOf<T, I>) -> bool,
+) -> Option<PalletsOriginOf<T>> {
+	T::Tracks::tracks()
+		.iter()
+		.filter(|(_, info)| filter(info))
+		.find_map(|(id, _)| T::Tracks::benchmark_origin(*id))
+}
+
+/// Returns the proposal origin used by the benchmarks of the confirming branches. Tracks with a
+/// supermajority curve also keep `SupermajorityConfirming` up to date, so one of those is used if
+/// the runtime provides it.
+fn confirming_proposal_origin<T: Config<I>, I: 'static>() -> PalletsOriginOf<T> {
+	track_origin::<T, I>(|info| info.supermajority.is_some())
+		.unwrap_or_else(|| RawOrigin::Root.into())
+}
+
+/// Fills every preparation slot of the track of `proposal_origin` and its preparation queue up to
+/// `MaxQueued - spaces`, returning the referenda which are queued.
+fn fill_preparing<T: Config<I>, I: 'static>(
+	origin: T::RuntimeOrigin,
+	proposal_origin: PalletsOriginOf<T>,
+	spaces: u32,
+) -> Vec<ReferendumIndex> {
+	let track = T::Tracks::track_for(&proposal_origin).expect("origin of a known track");
+	let max_preparing = T::Tracks::info(track).and_then(|info| info.max_preparing).unwrap_or(0);
+	for _ in 0..max_preparing {
+		create_referendum_with::<T, I>(origin.clone(), proposal_origin.clone());
+	}
+	let queued: Vec<_> = (spaces..T::MaxQueued::get())
+		.map(|_| create_referendum_with::<T, I>(origin.clone(), proposal_origin.clone()))
+		.collect();
+	assert_eq!(PreparingCount::<T, I>::get(track), max_preparing);
+	assert_eq!(PreparingQueue::<T, I>::get(track).into_inner(), queued);
+	queued
+}
+
 fn place_deposit<T: Config<I>, I: 'static>(index: ReferendumIndex) {
 	let caller = funded_account::<T, I>("caller", 0);
 	whitelist_account!(caller);
@@ -213,6 +258,26 @@ benchmarks_instance_pallet! {
 		assert_matches!(ReferendumInfoFor::<T, I>::get(index), Some(ReferendumInfo::Ongoing(_)));
 	}
 
+	submit_queued {
+		let proposal_origin = track_origin::<T, I>(|info| info.max_preparing.is_some())
+			.ok_or(BenchmarkError::Weightless)?;
+		let origin = T::SubmitOrigin::try_successful_origin(&proposal_origin)
+			.map_err(|_| BenchmarkError::Weightless)?;
+		// Leave a single place in the preparation queue, so ours is queued behind the most
+		// referenda.
+		fill_preparing::<T, I>(origin.clone(), proposal_origin.clone(), 1);
+		let track = T::Tracks::track_for(&proposal_origin).expect("origin of a known track");
+	}: submit<T::RuntimeOrigin>(
+		origin,
+		Box::new(proposal_origin),
+		dummy_call::<T, I>(),
+		DispatchTime::After(0u32.into())
+	) verify {
+		let index = ReferendumCount::<T, I>::get().checked_sub(1).unwrap();
+		assert_eq!(PreparingQueue::<T, I>::get(track).len() as u32, T::MaxQueued::get());
+		assert_last_event::<T, I>(Event::PreparationQueued { index, track }.into());
+	}
+
 	place_decision_deposit_preparing {
 		let origin =
 			T::SubmitOrigin::try_successful_origin(&RawOrigin::Root.into()).map_err(|_| BenchmarkError::Weightless)?;
@@ -249,9 +314,10 @@ benchmarks_instance_pallet! {
 	}
 
 	place_decision_deposit_passing {
+		let proposal_origin = confirming_proposal_origin::<T, I>();
 		let origin =
-			T::SubmitOrigin::try_successful_origin(&RawOrigin::Root.into()).map_err(|_| BenchmarkError::Weightless)?;
-		let index = create_referendum::<T, I>(origin.clone());
+			T::SubmitOrigin::try_successful_origin(&proposal_origin).map_err(|_| BenchmarkError::Weightless)?;
+		let index = create_referendum_with::<T, I>(origin.clone(), proposal_origin);
 		skip_prepare_period::<T, I>(index);
 		make_passing::<T, I>(index);
 	}: place_decision_deposit<T::RuntimeOrigin>(origin, index)
@@ -328,6 +394,21 @@ benchmarks_instance_pallet! {
 		assert_matches!(ReferendumInfoFor::<T, I>::get(index), Some(ReferendumInfo::Killed(..)));
 	}
 
+	one_fewer_preparing {
+		let proposal_origin = track_origin::<T, I>(|info| info.max_preparing.is_some())
+			.ok_or(BenchmarkError::Weightless)?;
+		let origin = T::SubmitOrigin::try_successful_origin(&proposal_origin)
+			.map_err(|_| BenchmarkError::Weightless)?;
+		// A full preparation queue, whose first referendum is given the freed slot.
+		let queued = fill_preparing::<T, I>(origin, proposal_origin.clone(), 0);
+		let track = T::Tracks::track_for(&proposal_origin).expect("origin of a known track");
+	}: {
+		Referenda::<T, I>::note_one_fewer_preparing(track);
+	} verify {
+		assert_eq!(PreparingQueue::<T, I>::get(track).into_inner(), queued[1..].to_vec());
+		assert_last_event::<T, I>(Event::PreparationStarted { index: queued[0] }.into());
+	}
+
 	one_fewer_deciding_queue_empty {
 		let origin = T::SubmitOrigin::try_successful_origin(&RawOrigin::Root.into())
 			.expect("SubmitOrigin has no successful origin required for the benchmark");
@@ -531,9 +612,10 @@ benchmarks_instance_pallet! {
 	}
 
 	nudge_referendum_begin_deciding_passing {
-		let origin = T::SubmitOrigin::try_successful_origin(&RawOrigin::Root.into())
+		let proposal_origin = confirming_proposal_origin::<T, I>();
+		let origin = T::SubmitOrigin::try_successful_origin(&proposal_origin)
 			.expect("SubmitOrigin has no successful origin required for the benchmark");
-		let index = create_referendum::<T, I>(origin);
+		let index = create_referendum_with::<T, I>(origin, proposal_origin);
 		place_deposit::<T, I>(index);
 		make_passing::<T, I>(index);
 		skip_prepare_period::<T, I>(index);
@@ -543,9 +625,10 @@ benchmarks_instance_pallet! {
 	}
 
 	nudge_referendum_begin_confirming {
-		let origin = T::SubmitOrigin::try_successful_origin(&RawOrigin::Root.into())
+		let proposal_origin = confirming_proposal_origin::<T, I>();
+		let origin = T::SubmitOrigin::try_successful_origin(&proposal_origin)
 			.expect("SubmitOrigin has no successful origin required for the benchmark");
-		let index = create_referendum::<T, I>(origin);
+		let index = create_referendum_with::<T, I>(origin, proposal_origin);
 		place_deposit::<T, I>(index);
 		skip_prepare_period::<T, I>(index);
 		nudge::<T, I>(index);
@@ -557,9 +640,10 @@ benchmarks_instance_pallet! {
 	}
 
 	nudge_referendum_end_confirming {
-		let origin = T::SubmitOrigin::try_successful_origin(&RawOrigin::Root.into())
+		let proposal_origin = confirming_proposal_origin::<T, I>();
+		let origin = T::SubmitOrigin::try_successful_origin(&proposal_origin)
 			.expect("SubmitOrigin has no successful origin required for the benchmark");
-		let index = create_referendum::<T, I>(origin);
+		let index = create_referendum_with::<T, I>(origin, proposal_origin);
 		place_deposit::<T, I>(index);
 		skip_prepare_period::<T, I>(index);
 		make_passing::<T, I>(index);
@@ -588,9 +672,10 @@ benchmarks_instance_pallet! {
 	}
 
 	nudge_referendum_continue_confirming {
-		let origin = T::SubmitOrigin::try_successful_origin(&RawOrigin::Root.into())
+		let proposal_origin = confirming_proposal_origin::<T, I>();
+		let origin = T::SubmitOrigin::try_successful_origin(&proposal_origin)
 			.expect("SubmitOrigin has no successful origin required for the benchmark");
-		let index = create_referendum::<T, I>(origin);
+		let index = create_referendum_with::<T, I>(origin, proposal_origin);
 		place_deposit::<T, I>(index);
 		make_passing::<T, I>(index);
 		skip_prepare_period::<T, I>(index);
@@ -603,9 +688,10 @@ benchmarks_instance_pallet! {
 	}
 
 	nudge_referendum_approved {
-		let origin = T::SubmitOrigin::try_successful_origin(&RawOrigin::Root.into())
+		let proposal_origin = confirming_proposal_origin::<T, I>();
+		let origin = T::SubmitOrigin::try_successful_origin(&proposal_origin)
 			.expect("SubmitOrigin has no successful origin required for the benchmark");
-		let index = create_referendum::<T, I>(origin);
+		let index = create_referendum_with::<T, I>(origin, proposal_origin);
 		place_deposit::<T, I>(index);
 		skip_prepare_period::<T, I>(index);
 		make_passing::<T, I>(index);
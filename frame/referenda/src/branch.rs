@@ -17,9 +17,13 @@
 
 //! Helpers for managing the different weights in various algorithmic branches.
 
-use super::Config;
+use super::{Config, ReferendumIndex, TrackIdOf};
 use crate::weights::WeightInfo;
-use frame_support::weights::Weight;
+use codec::MaxEncodedLen;
+use frame_support::{
+	traits::Get, weights::Weight, Blake2_128Concat, BoundedVec, StorageHasher, Twox64Concat,
+};
+use frame_system::pallet_prelude::BlockNumberFor;
 
 /// Branches within the `begin_deciding` function.
 pub enum BeginDecidingBranch {
@@ -62,7 +66,10 @@ impl ServiceBranch {
 	/// Return the weight of the `nudge` function when it takes the branch denoted by `self`.
 	pub fn weight_of_nudge<T: Config<I>, I: 'static>(self) -> frame_support::weights::Weight {
 		use ServiceBranch::*;
-		match self {
+		let extra_weight = self
+			.weight_of_preparation_end::<T, I>()
+			.saturating_add(self.weight_of_supermajority::<T, I>());
+		let weight = match self {
 			NoDeposit => T::WeightInfo::nudge_referendum_no_deposit(),
 			Preparing => T::WeightInfo::nudge_referendum_preparing(),
 			Queued => T::WeightInfo::nudge_referendum_queued(),
@@ -78,6 +85,29 @@ impl ServiceBranch {
 			Approved => T::WeightInfo::nudge_referendum_approved(),
 			Rejected => T::WeightInfo::nudge_referendum_rejected(),
 			TimedOut | Fail => T::WeightInfo::nudge_referendum_timed_out(),
+		};
+		weight.saturating_add(extra_weight)
+	}
+
+	/// Return the weight of handing the preparation slot of a referendum over to the next one
+	/// waiting for it, which happens in the branches leaving the preparation period.
+	fn weight_of_preparation_end<T: Config<I>, I: 'static>(&self) -> Weight {
+		use ServiceBranch::*;
+		match self {
+			Queued | NotQueued | BeginDecidingPassing | BeginDecidingFailing | TimedOut =>
+				weight_of_one_fewer_preparing::<T, I>(),
+			_ => Weight::zero(),
+		}
+	}
+
+	/// Return the weight of keeping `SupermajorityConfirming` up to date, which happens in the
+	/// branches where the referendum is or was passing.
+	fn weight_of_supermajority<T: Config<I>, I: 'static>(&self) -> Weight {
+		use ServiceBranch::*;
+		match self {
+			BeginDecidingPassing | BeginConfirming | ContinueConfirming | EndConfirming |
+			Approved | Rejected => weight_of_supermajority_confirming::<T, I>(),
+			_ => Weight::zero(),
 		}
 	}
 
@@ -99,6 +129,8 @@ impl ServiceBranch {
 			.max(T::WeightInfo::nudge_referendum_approved())
 			.max(T::WeightInfo::nudge_referendum_rejected())
 			.max(T::WeightInfo::nudge_referendum_timed_out())
+			.saturating_add(weight_of_one_fewer_preparing::<T, I>())
+			.saturating_add(weight_of_supermajority_confirming::<T, I>())
 	}
 
 	/// Return the weight of the `place_decision_deposit` function when it takes the branch denoted
@@ -107,6 +139,9 @@ impl ServiceBranch {
 		self,
 	) -> Option<frame_support::weights::Weight> {
 		use ServiceBranch::*;
+		let extra_weight = self
+			.weight_of_preparation_end::<T, I>()
+			.saturating_add(self.weight_of_supermajority::<T, I>());
 		let ref_time_weight = match self {
 			Preparing => T::WeightInfo::place_decision_deposit_preparing(),
 			Queued => T::WeightInfo::place_decision_deposit_queued(),
@@ -126,7 +161,7 @@ impl ServiceBranch {
 			NoDeposit => return None,
 		};
 
-		Some(ref_time_weight)
+		Some(ref_time_weight.saturating_add(extra_weight))
 	}
 
 	/// Return the maximum possible weight of the `place_decision_deposit` function.
@@ -137,6 +172,8 @@ impl ServiceBranch {
 			.max(T::WeightInfo::place_decision_deposit_not_queued())
 			.max(T::WeightInfo::place_decision_deposit_passing())
 			.max(T::WeightInfo::place_decision_deposit_failing())
+			.saturating_add(weight_of_one_fewer_preparing::<T, I>())
+			.saturating_add(weight_of_supermajority_confirming::<T, I>())
 	}
 }
 
@@ -165,7 +202,8 @@ impl OneFewerDecidingBranch {
 		use OneFewerDecidingBranch::*;
 		match self {
 			QueueEmpty => T::WeightInfo::one_fewer_deciding_queue_empty(),
-			BeginDecidingPassing => T::WeightInfo::one_fewer_deciding_passing(),
+			BeginDecidingPassing => T::WeightInfo::one_fewer_deciding_passing()
+				.saturating_add(weight_of_supermajority_confirming::<T, I>()),
 			BeginDecidingFailing => T::WeightInfo::one_fewer_deciding_failing(),
 		}
 	}
@@ -174,7 +212,46 @@ impl OneFewerDecidingBranch {
 	pub fn max_weight<T: Config<I>, I: 'static>() -> frame_support::weights::Weight {
 		Weight::zero()
 			.max(T::WeightInfo::one_fewer_deciding_queue_empty())
-			.max(T::WeightInfo::one_fewer_deciding_passing())
+			.max(
+				T::WeightInfo::one_fewer_deciding_passing()
+					.saturating_add(weight_of_supermajority_confirming::<T, I>()),
+			)
 			.max(T::WeightInfo::one_fewer_deciding_failing())
 	}
 }
+
+/// Return the weight of submitting a referendum on a track limiting the number of referenda in
+/// preparation, on top of `submit`.
+///
+/// Reads and writes the `PreparingCount` and the `PreparingQueue` of the track.
+// TODO: Use the weight of the `submit_queued` benchmark once it has been run.
+pub fn weight_of_preparation_queue<T: Config<I>, I: 'static>() -> Weight {
+	let key_len = Twox64Concat::max_len::<TrackIdOf<T, I>>();
+	let proof_size = key_len
+		.saturating_add(u32::max_encoded_len())
+		.saturating_add(key_len)
+		.saturating_add(BoundedVec::<ReferendumIndex, T::MaxQueued>::max_encoded_len());
+	T::DbWeight::get()
+		.reads_writes(2, 2)
+		.saturating_add(Weight::from_parts(0, proof_size as u64))
+}
+
+/// Return the weight of handing the preparation slot of a referendum over to the next one in the
+/// `PreparingQueue`.
+///
+/// This pops the queue, updates the next referendum and sets its alarm, which is bounded by
+/// `one_fewer_deciding_passing` doing the same for the (larger) `TrackQueue`.
+// TODO: Use the weight of the `one_fewer_preparing` benchmark once it has been run.
+pub fn weight_of_one_fewer_preparing<T: Config<I>, I: 'static>() -> Weight {
+	T::WeightInfo::one_fewer_deciding_passing()
+}
+
+/// Return the weight of reading and writing the `SupermajorityConfirming` entry of a referendum,
+/// which is not part of the generated weights yet.
+pub fn weight_of_supermajority_confirming<T: Config<I>, I: 'static>() -> Weight {
+	let proof_size = Blake2_128Concat::max_len::<ReferendumIndex>()
+		.saturating_add(BlockNumberFor::<T>::max_encoded_len());
+	T::DbWeight::get()
+		.reads_writes(1, 1)
+		.saturating_add(Weight::from_parts(0, proof_size as u64))
+}
@@ -38,6 +38,10 @@
 //! - The track must not already be at capacity with referendum being decided. The maximum number of
 //! referenda which may be being decided simultaneously is determined by the track.
 //!
+//! Tracks may also limit the number of referenda which are in preparation at once. Referenda
+//! submitted while the track is at this limit wait in a queue, and their *Preparation Period* only
+//! begins once they are given a slot.
+//!
 //! In order to become concluded, one of three things must happen:
 //! - The referendum should remain in an unbroken _Passing_ state for a period of time. This
 //! is known as the _Confirmation Period_ and is determined by the track. A referendum is considered
@@ -45,6 +49,9 @@
 //! has been being decided. Generally the threshold for what counts as being "sufficiently high"
 //! will reduce over time. The curves setting these thresholds are determined by the track. In this
 //! case, the referendum is considered _Approved_ and the proposal is scheduled for dispatch.
+//! Tracks may define a _Supermajority_ approval curve, in which case a referendum which is also
+//! meeting that curve need only remain _Passing_ for the track's (shorter) supermajority
+//! confirmation period.
 //! - The referendum reaches the end of its deciding phase outside not _Passing_. It ends in
 //! rejection and the proposal is not dispatched.
 //! - The referendum is cancelled.
@@ -96,9 +103,10 @@ pub use self::{
 	pallet::*,
 	types::{
 		BalanceOf, BoundedCallOf, CallOf, Curve, DecidingStatus, DecidingStatusOf, Deposit,
-		InsertSorted, NegativeImbalanceOf, PalletsOriginOf, ReferendumIndex, ReferendumInfo,
-		ReferendumInfoOf, ReferendumStatus, ReferendumStatusOf, ScheduleAddressOf, TallyOf,
-		TrackIdOf, TrackInfo, TrackInfoOf, TracksInfo, VotesOf,
+		InsertSorted, NegativeImbalanceOf, PalletsOriginOf, ProjectedOutcome, ProjectedOutcomeOf,
+		ReferendumIndex, ReferendumInfo, ReferendumInfoOf, ReferendumStatus, ReferendumStatusOf,
+		ScheduleAddressOf, Supermajority, TallyOf, TrackIdOf, TrackInfo, TrackInfoOf, TracksInfo,
+		VotesOf,
 	},
 	weights::WeightInfo,
 };
@@ -256,6 +264,37 @@ pub mod pallet {
 	pub type DecidingCount<T: Config<I>, I: 'static = ()> =
 		StorageMap<_, Twox64Concat, TrackIdOf<T, I>, u32, ValueQuery>;
 
+	/// The number of referenda in their preparation period currently, for tracks which have a
+	/// `TrackInfo::max_preparing` limit.
+	#[pallet::storage]
+	pub type PreparingCount<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Twox64Concat, TrackIdOf<T, I>, u32, ValueQuery>;
+
+	/// The list of referenda waiting for a preparation slot, in order of submission.
+	///
+	/// A referendum leaving this queue is treated as having been submitted at that point, so its
+	/// preparation period and `UndecidingTimeout` only begin then.
+	///
+	/// This should be empty if `PreparingCount` is less than `TrackInfo::max_preparing`.
+	#[pallet::storage]
+	pub type PreparingQueue<T: Config<I>, I: 'static = ()> = StorageMap<
+		_,
+		Twox64Concat,
+		TrackIdOf<T, I>,
+		BoundedVec<ReferendumIndex, T::MaxQueued>,
+		ValueQuery,
+	>;
+
+	/// The block at which a confirming referendum will be approved because its tally meets the
+	/// supermajority curve of its track, where that is earlier than the end of its regular
+	/// confirmation period.
+	///
+	/// The regular end is kept in `DecidingStatus::confirming`; this entry is removed as soon as
+	/// the supermajority is lost, or the referendum stops confirming.
+	#[pallet::storage]
+	pub type SupermajorityConfirming<T: Config<I>, I: 'static = ()> =
+		StorageMap<_, Blake2_128Concat, ReferendumIndex, BlockNumberFor<T>>;
+
 	/// The metadata is a general information concerning the referendum.
 	/// The `PreimageHash` refers to the preimage of the `Preimages` provider which can be a JSON
 	/// dump or IPFS hash of a JSON file.
@@ -385,6 +424,18 @@ pub mod pallet {
 			/// Preimage hash.
 			hash: PreimageHash,
 		},
+		/// A referendum has been queued since its track has no free preparation slot.
+		PreparationQueued {
+			/// Index of the referendum.
+			index: ReferendumIndex,
+			/// The track (and by extension proposal dispatch origin) of this referendum.
+			track: TrackIdOf<T, I>,
+		},
+		/// A queued referendum has been given a preparation slot and has begun preparing.
+		PreparationStarted {
+			/// Index of the referendum.
+			index: ReferendumIndex,
+		},
 	}
 
 	#[pallet::error]
@@ -438,7 +489,7 @@ pub mod pallet {
 		///
 		/// Emits `Submitted`.
 		#[pallet::call_index(0)]
-		#[pallet::weight(T::WeightInfo::submit())]
+		#[pallet::weight(T::WeightInfo::submit().saturating_add(branch::weight_of_preparation_queue::<T, I>()))]
 		pub fn submit(
 			origin: OriginFor<T>,
 			proposal_origin: Box<PalletsOriginOf<T>>,
@@ -450,6 +501,8 @@ pub mod pallet {
 
 			let track =
 				T::Tracks::track_for(&proposal_origin).map_err(|_| Error::<T, I>::NoTrack)?;
+			let track_info = Self::track(track).ok_or(Error::<T, I>::NoTrack)?;
+			ensure!(Self::can_prepare_or_queue(track, track_info), Error::<T, I>::Full);
 			let submission_deposit = Self::take_deposit(who, T::SubmissionDeposit::get())?;
 			let index = ReferendumCount::<T, I>::mutate(|x| {
 				let r = *x;
@@ -457,8 +510,18 @@ pub mod pallet {
 				r
 			});
 			let now = frame_system::Pallet::<T>::block_number();
+			let preparing = Self::try_begin_preparing(track, track_info);
+			if !preparing {
+				PreparingQueue::<T, I>::try_mutate(track, |q| q.try_push(index))
+					.map_err(|_| Error::<T, I>::Full)?;
+			}
 			let nudge_call =
 				T::Preimages::bound(CallOf::<T, I>::from(Call::nudge_referendum { index }))?;
+			let alarm = if preparing {
+				Self::set_alarm(nudge_call, now.saturating_add(T::UndecidingTimeout::get()))
+			} else {
+				None
+			};
 			let status = ReferendumStatus {
 				track,
 				origin: proposal_origin,
@@ -470,11 +533,14 @@ pub mod pallet {
 				deciding: None,
 				tally: TallyOf::<T, I>::new(track),
 				in_queue: false,
-				alarm: Self::set_alarm(nudge_call, now.saturating_add(T::UndecidingTimeout::get())),
+				alarm,
 			};
 			ReferendumInfoFor::<T, I>::insert(index, ReferendumInfo::Ongoing(status));
 
 			Self::deposit_event(Event::<T, I>::Submitted { index, track, proposal });
+			if !preparing {
+				Self::deposit_event(Event::<T, I>::PreparationQueued { index, track });
+			}
 			Ok(())
 		}
 
@@ -545,14 +611,18 @@ pub mod pallet {
 		///
 		/// Emits `Cancelled`.
 		#[pallet::call_index(3)]
-		#[pallet::weight(T::WeightInfo::cancel())]
+		#[pallet::weight(T::WeightInfo::cancel()
+			.saturating_add(branch::weight_of_one_fewer_preparing::<T, I>())
+			.saturating_add(branch::weight_of_supermajority_confirming::<T, I>()))]
 		pub fn cancel(origin: OriginFor<T>, index: ReferendumIndex) -> DispatchResult {
 			T::CancelOrigin::ensure_origin(origin)?;
-			let status = Self::ensure_ongoing(index)?;
-			if let Some((_, last_alarm)) = status.alarm {
+			let mut status = Self::ensure_ongoing(index)?;
+			if let Some((_, last_alarm)) = status.alarm.take() {
 				let _ = T::Scheduler::cancel(last_alarm);
 			}
 			Self::note_one_fewer_deciding(status.track);
+			Self::note_preparation_abandoned(index, &status);
+			SupermajorityConfirming::<T, I>::remove(index);
 			Self::deposit_event(Event::<T, I>::Cancelled { index, tally: status.tally });
 			let info = ReferendumInfo::Cancelled(
 				frame_system::Pallet::<T>::block_number(),
@@ -570,14 +640,18 @@ pub mod pallet {
 		///
 		/// Emits `Killed` and `DepositSlashed`.
 		#[pallet::call_index(4)]
-		#[pallet::weight(T::WeightInfo::kill())]
+		#[pallet::weight(T::WeightInfo::kill()
+			.saturating_add(branch::weight_of_one_fewer_preparing::<T, I>())
+			.saturating_add(branch::weight_of_supermajority_confirming::<T, I>()))]
 		pub fn kill(origin: OriginFor<T>, index: ReferendumIndex) -> DispatchResult {
 			T::KillOrigin::ensure_origin(origin)?;
-			let status = Self::ensure_ongoing(index)?;
-			if let Some((_, last_alarm)) = status.alarm {
+			let mut status = Self::ensure_ongoing(index)?;
+			if let Some((_, last_alarm)) = status.alarm.take() {
 				let _ = T::Scheduler::cancel(last_alarm);
 			}
 			Self::note_one_fewer_deciding(status.track);
+			Self::note_preparation_abandoned(index, &status);
+			SupermajorityConfirming::<T, I>::remove(index);
 			Self::deposit_event(Event::<T, I>::Killed { index, tally: status.tally });
 			Self::slash_deposit(Some(status.submission_deposit.clone()));
 			Self::slash_deposit(status.decision_deposit.clone());
@@ -856,6 +930,44 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 		}
 	}
 
+	/// Project the outcome of an ongoing referendum, assuming its current tally does not change.
+	/// Referendum must be ongoing and its track must exist.
+	pub fn projected_outcome(
+		index: ReferendumIndex,
+	) -> Result<ProjectedOutcomeOf<T>, DispatchError> {
+		let status = Self::ensure_ongoing(index)?;
+		let track = Self::track(status.track).ok_or(Error::<T, I>::NoTrack)?;
+		let deciding = match status.deciding {
+			Some(deciding) => deciding,
+			None => return Ok(ProjectedOutcome::NotDeciding),
+		};
+		if let Some(confirming) = deciding.confirming {
+			let end = SupermajorityConfirming::<T, I>::get(index)
+				.map_or(confirming, |fast_end| fast_end.min(confirming));
+			return Ok(ProjectedOutcome::Approved(end))
+		}
+		let now = frame_system::Pallet::<T>::block_number();
+		let passing_at =
+			Self::decision_time(&deciding, &status.tally, status.track, track).max(now);
+		let elapsed = passing_at.saturating_sub(deciding.since);
+		let is_passing = Self::is_passing(
+			&status.tally,
+			elapsed,
+			track.decision_period,
+			&track.min_support,
+			&track.min_approval,
+			status.track,
+		);
+		Ok(if is_passing {
+			let confirm_period = Self::confirm_period(&status.tally, elapsed, track, status.track);
+			ProjectedOutcome::Approved(passing_at.saturating_add(confirm_period))
+		} else {
+			ProjectedOutcome::Rejected(
+				deciding.since.saturating_add(track.decision_period).max(now),
+			)
+		})
+	}
+
 	// Enqueue a proposal from a referendum which has presumably passed.
 	fn schedule_enactment(
 		index: ReferendumIndex,
@@ -934,14 +1046,23 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 			proposal: status.proposal.clone(),
 			track: status.track,
 		});
-		let confirming = if is_passing {
+		let (confirming, fast_end) = if is_passing {
 			Self::deposit_event(Event::<T, I>::ConfirmStarted { index });
-			Some(now.saturating_add(track.confirm_period))
+			let fast_end = Self::update_supermajority_confirming(
+				index,
+				&status.tally,
+				Zero::zero(),
+				now,
+				track,
+				status.track,
+			);
+			(Some(now.saturating_add(track.confirm_period)), fast_end)
 		} else {
-			None
+			(None, None)
 		};
 		let deciding_status = DecidingStatus { since: now, confirming };
 		let alarm = Self::decision_time(&deciding_status, &status.tally, status.track, track)
+			.min(fast_end.unwrap_or_else(BlockNumberFor::<T>::max_value))
 			.max(now.saturating_add(One::one()));
 		status.deciding = Some(deciding_status);
 		let branch =
@@ -1076,8 +1197,13 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 		let branch;
 		match &mut status.deciding {
 			None => {
-				// Are we already queued for deciding?
-				if status.in_queue {
+				let awaiting_preparation = Self::awaiting_preparation(index, status.track, track);
+				// Are we still waiting for a preparation slot?
+				if awaiting_preparation {
+					// We'll be woken up once we're given one.
+					branch = ServiceBranch::Preparing;
+				} else if status.in_queue {
+					// Are we already queued for deciding?
 					// Does our position in the queue need updating?
 					let ayes = status.tally.ayes(status.track);
 					let mut queue = TrackQueue::<T, I>::get(status.track);
@@ -1101,6 +1227,7 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 					branch = if status.decision_deposit.is_some() {
 						let prepare_end = status.submitted.saturating_add(track.prepare_period);
 						if now >= prepare_end {
+							Self::note_one_fewer_preparing(status.track);
 							let (maybe_alarm, branch) =
 								Self::ready_for_deciding(now, track, index, &mut status);
 							if let Some(set_alarm) = maybe_alarm {
@@ -1118,9 +1245,13 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 					}
 				}
 				// If we didn't move into being decided, then check the timeout.
-				if status.deciding.is_none() && now >= timeout && !status.in_queue {
+				if status.deciding.is_none() &&
+					now >= timeout && !status.in_queue &&
+					!awaiting_preparation
+				{
 					// Too long without being decided - end it.
 					Self::ensure_no_alarm(&mut status);
+					Self::note_one_fewer_preparing(status.track);
 					Self::deposit_event(Event::<T, I>::TimedOut { index, tally: status.tally });
 					return (
 						ReferendumInfo::TimedOut(
@@ -1142,10 +1273,22 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 					&track.min_approval,
 					status.track,
 				);
+				let mut fast_end = None;
 				branch = if is_passing {
+					// A supermajority may bring the end of confirmation forward, for as long as it
+					// holds.
+					fast_end = Self::update_supermajority_confirming(
+						index,
+						&status.tally,
+						now.saturating_sub(deciding.since),
+						now,
+						track,
+						status.track,
+					);
 					match deciding.confirming {
-						Some(t) if now >= t => {
+						Some(t) if now >= t || fast_end.map_or(false, |end| now >= end) => {
 							// Passed!
+							SupermajorityConfirming::<T, I>::remove(index);
 							Self::ensure_no_alarm(&mut status);
 							Self::note_one_fewer_deciding(status.track);
 							let (desired, call) = (status.enactment, status.proposal);
@@ -1174,6 +1317,9 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 						},
 					}
 				} else {
+					if deciding.confirming.is_some() {
+						SupermajorityConfirming::<T, I>::remove(index);
+					}
 					if now >= deciding.since.saturating_add(track.decision_period) {
 						// Failed!
 						Self::ensure_no_alarm(&mut status);
@@ -1199,7 +1345,8 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 						ServiceBranch::ContinueNotConfirming
 					}
 				};
-				alarm = Self::decision_time(deciding, &status.tally, status.track, track);
+				alarm = Self::decision_time(deciding, &status.tally, status.track, track)
+					.min(fast_end.unwrap_or_else(BlockNumberFor::<T>::max_value));
 			},
 		}
 
@@ -1288,6 +1435,137 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 			approval_needed.passing(x, tally.approval(id))
 	}
 
+	/// The confirmation period which applies to a passing referendum with the given `tally` at
+	/// `elapsed` blocks into its decision period: the track's supermajority confirmation period if
+	/// it has one and its approval curve is met, otherwise the regular one.
+	fn confirm_period(
+		tally: &T::Tally,
+		elapsed: BlockNumberFor<T>,
+		track: &TrackInfoOf<T, I>,
+		id: TrackIdOf<T, I>,
+	) -> BlockNumberFor<T> {
+		Self::supermajority_confirm_period(tally, elapsed, track, id)
+			.unwrap_or(track.confirm_period)
+	}
+
+	/// The supermajority confirmation period of `track`, if it has one and the approval of `tally`
+	/// at `elapsed` blocks into the decision period meets its curve.
+	fn supermajority_confirm_period(
+		tally: &T::Tally,
+		elapsed: BlockNumberFor<T>,
+		track: &TrackInfoOf<T, I>,
+		id: TrackIdOf<T, I>,
+	) -> Option<BlockNumberFor<T>> {
+		let supermajority = track.supermajority.as_ref()?;
+		let period = track.decision_period;
+		let x = Perbill::from_rational(elapsed.min(period), period);
+		supermajority
+			.min_approval
+			.passing(x, tally.approval(id))
+			.then(|| supermajority.confirm_period.min(track.confirm_period))
+	}
+
+	/// Bring `SupermajorityConfirming` of the passing referendum `index` in line with its
+	/// `tally`: start counting down the supermajority confirmation period from `now` once the
+	/// supermajority is met, and forget about it once the supermajority is lost.
+	///
+	/// Returns the block at which the supermajority confirmation ends, if it still holds.
+	fn update_supermajority_confirming(
+		index: ReferendumIndex,
+		tally: &T::Tally,
+		elapsed: BlockNumberFor<T>,
+		now: BlockNumberFor<T>,
+		track: &TrackInfoOf<T, I>,
+		id: TrackIdOf<T, I>,
+	) -> Option<BlockNumberFor<T>> {
+		let period = Self::supermajority_confirm_period(tally, elapsed, track, id);
+		match (period, SupermajorityConfirming::<T, I>::get(index)) {
+			(Some(_), Some(end)) => Some(end),
+			(Some(period), None) => {
+				let end = now.saturating_add(period);
+				SupermajorityConfirming::<T, I>::insert(index, end);
+				Some(end)
+			},
+			(None, Some(_)) => {
+				SupermajorityConfirming::<T, I>::remove(index);
+				None
+			},
+			(None, None) => None,
+		}
+	}
+
+	/// Take a preparation slot on the track `id`, if it has a `max_preparing` limit.
+	///
+	/// Returns `false` if the track is at capacity and the referendum must wait in the queue.
+	fn try_begin_preparing(id: TrackIdOf<T, I>, track: &TrackInfoOf<T, I>) -> bool {
+		match track.max_preparing {
+			Some(max_preparing) => PreparingCount::<T, I>::mutate(id, |count| {
+				if *count < max_preparing {
+					count.saturating_inc();
+					true
+				} else {
+					false
+				}
+			}),
+			None => true,
+		}
+	}
+
+	/// Returns whether a new referendum on the track `id` can either begin preparing right away or
+	/// wait in the preparation queue.
+	fn can_prepare_or_queue(id: TrackIdOf<T, I>, track: &TrackInfoOf<T, I>) -> bool {
+		match track.max_preparing {
+			Some(max_preparing) if PreparingCount::<T, I>::get(id) >= max_preparing =>
+				PreparingQueue::<T, I>::decode_len(id).unwrap_or(0) < T::MaxQueued::get() as usize,
+			_ => true,
+		}
+	}
+
+	/// Returns whether the referendum `index` is still waiting for a preparation slot.
+	fn awaiting_preparation(
+		index: ReferendumIndex,
+		id: TrackIdOf<T, I>,
+		track: &TrackInfoOf<T, I>,
+	) -> bool {
+		track.max_preparing.is_some() && PreparingQueue::<T, I>::get(id).contains(&index)
+	}
+
+	/// Action item for when a referendum on the track `id` leaves its preparation period. The slot
+	/// goes to the referendum which has been waiting longest for it, if any.
+	fn note_one_fewer_preparing(id: TrackIdOf<T, I>) {
+		if Self::track(id).map_or(true, |track| track.max_preparing.is_none()) {
+			return
+		}
+		let mut queue = PreparingQueue::<T, I>::get(id);
+		while !queue.is_empty() {
+			let index = queue.remove(0);
+			if let Ok(mut status) = Self::ensure_ongoing(index) {
+				PreparingQueue::<T, I>::insert(id, queue);
+				let now = frame_system::Pallet::<T>::block_number();
+				status.submitted = now;
+				Self::ensure_alarm_at(&mut status, index, now.saturating_add(One::one()));
+				ReferendumInfoFor::<T, I>::insert(index, ReferendumInfo::Ongoing(status));
+				Self::deposit_event(Event::<T, I>::PreparationStarted { index });
+				return
+			}
+		}
+		PreparingQueue::<T, I>::remove(id);
+		PreparingCount::<T, I>::mutate(id, |count| count.saturating_dec());
+	}
+
+	/// Release the preparation slot, or the place in the preparation queue, of the referendum
+	/// `index` which is ending before it has left its preparation period.
+	fn note_preparation_abandoned(index: ReferendumIndex, status: &ReferendumStatusOf<T, I>) {
+		if status.deciding.is_some() || status.in_queue {
+			return
+		}
+		match Self::track(status.track) {
+			Some(track) if Self::awaiting_preparation(index, status.track, track) =>
+				PreparingQueue::<T, I>::mutate(status.track, |queue| queue.retain(|&i| i != index)),
+			_ => Self::note_one_fewer_preparing(status.track),
+		}
+	}
+
 	/// Clear metadata if exist for a given referendum index.
 	fn do_clear_metadata(index: ReferendumIndex) {
 		if let Some(hash) = MetadataOf::<T, I>::take(index) {
@@ -1332,10 +1610,27 @@ impl<T: Config<I>, I: 'static> Pallet<T, I> {
 	///
 	/// * There must exist track info for the track of the referendum.
 	/// * The deciding stage has to begin before confirmation period.
+	/// * Only confirming referenda may be confirming by supermajority.
 	/// * If alarm is set the nudge call has to be at most [`UndecidingTimeout`] blocks away
 	///  from the submission block.
 	#[cfg(any(feature = "try-runtime", test))]
 	fn try_state_referenda_info() -> Result<(), sp_runtime::TryRuntimeError> {
+		SupermajorityConfirming::<T, I>::iter_keys().try_for_each(
+			|index| -> Result<(), sp_runtime::TryRuntimeError> {
+				ensure!(
+					matches!(
+						ReferendumInfoFor::<T, I>::get(index),
+						Some(ReferendumInfo::Ongoing(ReferendumStatus {
+							deciding: Some(DecidingStatus { confirming: Some(_), .. }),
+							..
+						}))
+					),
+					"Only confirming referenda can be confirming by supermajority."
+				);
+				Ok(())
+			},
+		)?;
+
 		ReferendumInfoFor::<T, I>::iter().try_for_each(|(_, referendum)| {
 			match referendum {
 				ReferendumInfo::Ongoing(status) => {
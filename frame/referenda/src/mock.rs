@@ -139,12 +139,15 @@ impl SortedMembers<u64> for OneToFive {
 	fn add(_m: &u64) {}
 }
 
+/// The signed origin whose proposals go on the fast track.
+pub const FAST_TRACK_ORIGIN: u64 = 100;
+
 pub struct TestTracksInfo;
 impl TracksInfo<u64, u64> for TestTracksInfo {
 	type Id = u8;
 	type RuntimeOrigin = <RuntimeOrigin as OriginTrait>::PalletsOrigin;
 	fn tracks() -> &'static [(Self::Id, TrackInfo<u64, u64>)] {
-		static DATA: [(u8, TrackInfo<u64, u64>); 2] = [
+		static DATA: [(u8, TrackInfo<u64, u64>); 3] = [
 			(
 				0u8,
 				TrackInfo {
@@ -165,6 +168,8 @@ impl TracksInfo<u64, u64> for TestTracksInfo {
 						floor: Perbill::from_percent(0),
 						ceil: Perbill::from_percent(100),
 					},
+					supermajority: None,
+					max_preparing: None,
 				},
 			),
 			(
@@ -187,6 +192,39 @@ impl TracksInfo<u64, u64> for TestTracksInfo {
 						floor: Perbill::from_percent(90),
 						ceil: Perbill::from_percent(100),
 					},
+					supermajority: None,
+					max_preparing: None,
+				},
+			),
+			(
+				2u8,
+				TrackInfo {
+					name: "fast",
+					max_deciding: 2,
+					decision_deposit: 1,
+					prepare_period: 2,
+					decision_period: 10,
+					confirm_period: 6,
+					min_enactment_period: 2,
+					min_approval: Curve::LinearDecreasing {
+						length: Perbill::from_percent(100),
+						floor: Perbill::from_percent(50),
+						ceil: Perbill::from_percent(100),
+					},
+					min_support: Curve::LinearDecreasing {
+						length: Perbill::from_percent(100),
+						floor: Perbill::from_percent(0),
+						ceil: Perbill::from_percent(100),
+					},
+					supermajority: Some(Supermajority {
+						min_approval: Curve::LinearDecreasing {
+							length: Perbill::from_percent(100),
+							floor: Perbill::from_percent(90),
+							ceil: Perbill::from_percent(90),
+						},
+						confirm_period: 2,
+					}),
+					max_preparing: Some(1),
 				},
 			),
 		];
@@ -197,12 +235,22 @@ impl TracksInfo<u64, u64> for TestTracksInfo {
 			match system_origin {
 				frame_system::RawOrigin::Root => Ok(0),
 				frame_system::RawOrigin::None => Ok(1),
+				frame_system::RawOrigin::Signed(FAST_TRACK_ORIGIN) => Ok(2),
 				_ => Err(()),
 			}
 		} else {
 			Err(())
 		}
 	}
+	#[cfg(feature = "runtime-benchmarks")]
+	fn benchmark_origin(id: Self::Id) -> Option<Self::RuntimeOrigin> {
+		match id {
+			0 => Some(frame_system::RawOrigin::Root.into()),
+			1 => Some(frame_system::RawOrigin::None.into()),
+			2 => Some(frame_system::RawOrigin::Signed(FAST_TRACK_ORIGIN).into()),
+			_ => None,
+		}
+	}
 }
 impl_tracksinfo_get!(TestTracksInfo, u64, u64);
 
@@ -670,3 +670,168 @@ fn clear_metadata_works() {
 		}));
 	});
 }
+
+fn propose_fast_track(who: u64) -> DispatchResult {
+	Referenda::submit(
+		RuntimeOrigin::signed(who),
+		Box::new(RawOrigin::Signed(FAST_TRACK_ORIGIN).into()),
+		set_balance_proposal_bounded(1),
+		DispatchTime::After(0),
+	)
+}
+
+#[test]
+fn supermajority_confirms_early() {
+	ExtBuilder::default().build_and_execute(|| {
+		assert_ok!(propose_fast_track(1));
+		assert_ok!(Referenda::place_decision_deposit(RuntimeOrigin::signed(2), 0));
+		run_to(3);
+		assert_eq!(deciding_and_failing_since(0), 3);
+		set_tally(0, 100, 0);
+		run_to(4);
+		// Supermajority confirmation period is 2 blocks rather than the regular 6.
+		assert_eq!(confirming_until(0), 10);
+		assert_eq!(SupermajorityConfirming::<Test>::get(0), Some(6));
+		run_to(6);
+		assert_eq!(approved_since(0), 6);
+		assert!(!SupermajorityConfirming::<Test>::contains_key(0));
+	});
+}
+
+#[test]
+fn supermajority_shortens_ongoing_confirmation() {
+	ExtBuilder::default().build_and_execute(|| {
+		assert_ok!(propose_fast_track(1));
+		assert_ok!(Referenda::place_decision_deposit(RuntimeOrigin::signed(2), 0));
+		run_to(7);
+		// Passing, but short of a supermajority.
+		set_tally(0, 100, 20);
+		run_to(8);
+		assert_eq!(confirming_until(0), 14);
+		run_to(9);
+		set_tally(0, 100, 0);
+		run_to(10);
+		assert_eq!(confirming_until(0), 14);
+		assert_eq!(SupermajorityConfirming::<Test>::get(0), Some(12));
+		run_to(12);
+		assert_eq!(approved_since(0), 12);
+	});
+}
+
+#[test]
+fn losing_supermajority_restores_regular_confirmation() {
+	ExtBuilder::default().build_and_execute(|| {
+		assert_ok!(propose_fast_track(1));
+		assert_ok!(Referenda::place_decision_deposit(RuntimeOrigin::signed(2), 0));
+		run_to(7);
+		set_tally(0, 100, 0);
+		run_to(8);
+		assert_eq!(confirming_until(0), 14);
+		assert_eq!(SupermajorityConfirming::<Test>::get(0), Some(10));
+		// Still passing, but no longer by a supermajority.
+		set_tally(0, 100, 20);
+		run_to(9);
+		assert_eq!(confirming_until(0), 14);
+		assert!(!SupermajorityConfirming::<Test>::contains_key(0));
+		assert_eq!(Referenda::projected_outcome(0), Ok(ProjectedOutcome::Approved(14)));
+		run_to(13);
+		assert_eq!(confirming_until(0), 14);
+		run_to(14);
+		assert_eq!(approved_since(0), 14);
+	});
+}
+
+#[test]
+fn preparing_queue_works() {
+	ExtBuilder::default().build_and_execute(|| {
+		assert_ok!(propose_fast_track(1));
+		assert_ok!(propose_fast_track(2));
+		assert_eq!(PreparingCount::<Test>::get(2), 1);
+		assert_eq!(PreparingQueue::<Test>::get(2).into_inner(), vec![1]);
+		System::assert_has_event(Event::<Test>::PreparationQueued { index: 1, track: 2 }.into());
+
+		assert_ok!(Referenda::place_decision_deposit(RuntimeOrigin::signed(3), 0));
+		assert_ok!(Referenda::place_decision_deposit(RuntimeOrigin::signed(3), 1));
+		run_to(2);
+		assert_eq!(waiting_since(1), 1);
+		run_to(3);
+		// The first referendum leaving preparation passes its slot on.
+		assert_eq!(deciding_since(0), 3);
+		assert_eq!(waiting_since(1), 3);
+		assert!(PreparingQueue::<Test>::get(2).is_empty());
+		System::assert_has_event(Event::<Test>::PreparationStarted { index: 1 }.into());
+
+		run_to(5);
+		assert_eq!(deciding_since(1), 5);
+		assert_eq!(PreparingCount::<Test>::get(2), 0);
+	});
+}
+
+#[test]
+fn submit_fails_without_charging_when_preparing_queue_is_full() {
+	ExtBuilder::default().build_and_execute(|| {
+		for who in 1..=4 {
+			assert_ok!(propose_fast_track(who));
+		}
+		assert_eq!(PreparingQueue::<Test>::get(2).into_inner(), vec![1, 2, 3]);
+
+		let balance = Balances::free_balance(5);
+		assert_noop!(propose_fast_track(5), Error::<Test>::Full);
+		assert_eq!(Balances::free_balance(5), balance);
+		assert_eq!(ReferendumCount::<Test>::get(), 4);
+	});
+}
+
+#[test]
+fn queued_referendum_does_not_time_out() {
+	ExtBuilder::default().build_and_execute(|| {
+		assert_ok!(propose_fast_track(1));
+		assert_ok!(propose_fast_track(2));
+		run_to(21);
+		assert_eq!(timed_out_since(0), 21);
+		// The undeciding timeout only starts once a preparation slot is given.
+		assert_eq!(waiting_since(1), 21);
+		run_to(41);
+		assert_eq!(timed_out_since(1), 41);
+		assert_eq!(PreparingCount::<Test>::get(2), 0);
+	});
+}
+
+#[test]
+fn preparing_queue_is_released_on_cancel() {
+	ExtBuilder::default().build_and_execute(|| {
+		assert_ok!(propose_fast_track(1));
+		assert_ok!(propose_fast_track(2));
+		assert_ok!(propose_fast_track(3));
+		assert_eq!(PreparingQueue::<Test>::get(2).into_inner(), vec![1, 2]);
+
+		assert_ok!(Referenda::cancel(RuntimeOrigin::signed(4), 1));
+		assert_eq!(PreparingQueue::<Test>::get(2).into_inner(), vec![2]);
+		assert_eq!(PreparingCount::<Test>::get(2), 1);
+
+		run_to(2);
+		assert_ok!(Referenda::cancel(RuntimeOrigin::signed(4), 0));
+		assert!(PreparingQueue::<Test>::get(2).is_empty());
+		assert_eq!(PreparingCount::<Test>::get(2), 1);
+		assert_eq!(waiting_since(2), 2);
+
+		assert_ok!(Referenda::kill(RuntimeOrigin::root(), 2));
+		assert_eq!(PreparingCount::<Test>::get(2), 0);
+	});
+}
+
+#[test]
+fn projected_outcome_works() {
+	ExtBuilder::default().build_and_execute(|| {
+		assert_noop!(Referenda::projected_outcome(0), Error::<Test>::NotOngoing);
+		let r = Failing.create();
+		assert_eq!(Referenda::projected_outcome(r), Ok(ProjectedOutcome::Rejected(9)));
+		set_tally(r, 100, 0);
+		assert_eq!(Referenda::projected_outcome(r), Ok(ProjectedOutcome::Approved(7)));
+		run_to(6);
+		assert_eq!(Referenda::projected_outcome(r), Ok(ProjectedOutcome::Approved(8)));
+
+		assert_ok!(propose_set_balance(1, 2, 1));
+		assert_eq!(Referenda::projected_outcome(1), Ok(ProjectedOutcome::NotDeciding));
+	});
+}
@@ -60,6 +60,7 @@ pub type ReferendumStatusOf<T, I> = ReferendumStatus<
 	ScheduleAddressOf<T, I>,
 >;
 pub type DecidingStatusOf<T> = DecidingStatus<BlockNumberFor<T>>;
+pub type ProjectedOutcomeOf<T> = ProjectedOutcome<BlockNumberFor<T>>;
 pub type TrackInfoOf<T, I = ()> = TrackInfo<BalanceOf<T, I>, BlockNumberFor<T>>;
 pub type TrackIdOf<T, I> =
 	<<T as Config<I>>::Tracks as TracksInfo<BalanceOf<T, I>, BlockNumberFor<T>>>::Id;
@@ -133,6 +134,25 @@ pub struct TrackInfo<Balance, Moment> {
 	/// Minimum pre-conviction aye-votes ("support") as percentage of overall population that is
 	/// needed for approval as a function of time into decision period.
 	pub min_support: Curve,
+	/// An optional fast-track for referenda which pass with an overwhelming approval. If `Some`,
+	/// passing referenda whose approval also meets the supermajority curve need only confirm for
+	/// the (shorter) supermajority confirmation period.
+	pub supermajority: Option<Supermajority<Moment>>,
+	/// A limit for the number of referenda on this track that can be in their preparation period
+	/// at once. Referenda submitted beyond this limit wait in a queue for a slot before their
+	/// preparation period begins. If `None`, there is no limit.
+	pub max_preparing: Option<u32>,
+}
+
+/// Fast-track parameters allowing a referendum with overwhelming approval to be confirmed early.
+#[derive(Clone, Encode, TypeInfo)]
+pub struct Supermajority<Moment> {
+	/// Minimum aye votes as percentage of overall conviction-weighted votes needed for the
+	/// supermajority confirmation period to apply, as a function of time into decision period.
+	pub min_approval: Curve,
+	/// Amount of time that the supermajority must hold before the referendum can be approved.
+	/// Should be no longer than the track's regular `confirm_period`.
+	pub confirm_period: Moment,
 }
 
 /// Information on the voting tracks.
@@ -153,6 +173,16 @@ pub trait TracksInfo<Balance, Moment> {
 	fn info(id: Self::Id) -> Option<&'static TrackInfo<Balance, Moment>> {
 		Self::tracks().iter().find(|x| x.0 == id).map(|x| &x.1)
 	}
+
+	/// Return an origin whose proposals are voted on in track `id`, if there is one.
+	///
+	/// Only used by the benchmarks, to set up referenda on tracks with a `max_preparing` limit or a
+	/// `supermajority` curve. By default no such origin is known, and the benchmarks of those
+	/// paths are skipped.
+	#[cfg(feature = "runtime-benchmarks")]
+	fn benchmark_origin(_id: Self::Id) -> Option<Self::RuntimeOrigin> {
+		None
+	}
 }
 
 /// Info regarding an ongoing referendum.
@@ -192,6 +222,18 @@ pub struct ReferendumStatus<
 	pub alarm: Option<(Moment, ScheduleAddress)>,
 }
 
+/// The outcome of an ongoing referendum projected from its current tally, assuming no further
+/// votes are cast.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum ProjectedOutcome<Moment> {
+	/// The referendum has not yet begun being decided, so no outcome can be projected.
+	NotDeciding,
+	/// The referendum would be approved at the given block.
+	Approved(Moment),
+	/// The referendum would be rejected at the given block.
+	Rejected(Moment),
+}
+
 /// Info regarding a referendum, present or past.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
 pub enum ReferendumInfo<
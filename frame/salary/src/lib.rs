@@ -16,6 +16,11 @@
 // limitations under the License.
 
 //! Make periodic payment to members of a ranked collective according to rank.
+//!
+//! Members may induct themselves into the payroll and register for each cycle's payout. When used
+//! as an [`OnRankChanged`] handler, the payroll is also kept in sync with changes of rank: newly
+//! ranked members are inducted, registrations are updated to the salary of the new rank and
+//! members who lose their rank entirely are removed.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![recursion_limit = "128"]
@@ -31,7 +36,7 @@ use frame_support::{
 	ensure,
 	traits::{
 		tokens::{GetSalary, Pay, PaymentStatus},
-		RankedMembers,
+		OnRankChanged, RankedMembers,
 	},
 };
 
@@ -146,6 +151,7 @@ pub mod pallet {
 	pub type IdOf<T, I> = <<T as Config<I>>::Paymaster as Pay>::Id;
 	pub type StatusOf<T, I> = StatusType<CycleIndexOf<T>, BlockNumberFor<T>, BalanceOf<T, I>>;
 	pub type ClaimantStatusOf<T, I> = ClaimantStatus<CycleIndexOf<T>, BalanceOf<T, I>, IdOf<T, I>>;
+	pub type RankOf<T, I> = <<T as Config<I>>::Members as RankedMembers>::Rank;
 
 	/// The overall status of the system.
 	#[pallet::storage]
@@ -173,6 +179,8 @@ pub mod pallet {
 		},
 		/// The next cycle begins.
 		CycleStarted { index: CycleIndexOf<T> },
+		/// A member has been removed from the payroll since they are no longer ranked.
+		Removed { who: T::AccountId },
 	}
 
 	#[pallet::error]
@@ -446,4 +454,51 @@ pub mod pallet {
 			Ok(())
 		}
 	}
+
+	impl<T: Config<I>, I: 'static> OnRankChanged<T::AccountId, RankOf<T, I>> for Pallet<T, I> {
+		fn on_rank_changed(who: &T::AccountId, new_rank: Option<RankOf<T, I>>) {
+			let Some(mut status) = Status::<T, I>::get() else { return };
+			let Some(rank) = new_rank else {
+				if let Some(claimant) = Claimant::<T, I>::take(who) {
+					if let Registered(amount) = claimant.status {
+						if claimant.last_active == status.cycle_index {
+							status.total_registrations.saturating_reduce(amount);
+							Status::<T, I>::put(&status);
+						}
+					}
+					Self::deposit_event(Event::<T, I>::Removed { who: who.clone() });
+				}
+				return
+			};
+
+			let Some(mut claimant) = Claimant::<T, I>::get(who) else {
+				// As with `induct`, there is no claim on the cycle active during induction.
+				Claimant::<T, I>::insert(
+					who,
+					ClaimantStatus { last_active: status.cycle_index, status: Nothing },
+				);
+				Self::deposit_event(Event::<T, I>::Inducted { who: who.clone() });
+				return
+			};
+
+			// Bring an existing registration for this cycle into line with the new salary, as
+			// long as registration is still open.
+			let now = frame_system::Pallet::<T>::block_number();
+			let Registered(old_payout) = claimant.status else { return };
+			if claimant.last_active != status.cycle_index ||
+				now >= status.cycle_start + T::RegistrationPeriod::get()
+			{
+				return
+			}
+			let payout = T::Salary::get_salary(rank, who);
+			status.total_registrations.saturating_reduce(old_payout);
+			status.total_registrations.saturating_accrue(payout);
+			claimant.status = if payout.is_zero() { Nothing } else { Registered(payout) };
+			Claimant::<T, I>::insert(who, &claimant);
+			Status::<T, I>::put(&status);
+			if !payout.is_zero() {
+				Self::deposit_event(Event::<T, I>::Registered { who: who.clone(), amount: payout });
+			}
+		}
+	}
 }
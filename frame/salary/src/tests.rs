@@ -639,3 +639,65 @@ fn other_mixed_bankrupcy_fails_gracefully() {
 		assert_eq!(paid(3), 6);
 	});
 }
+
+#[test]
+fn rank_changes_sync_payroll() {
+	new_test_ext().execute_with(|| {
+		// Nothing happens before the salary system starts.
+		set_rank(1, 1);
+		Salary::on_rank_changed(&1, Some(1));
+		assert!(Salary::last_active(&1).is_err());
+
+		assert_ok!(Salary::init(RuntimeOrigin::signed(1)));
+		Salary::on_rank_changed(&1, Some(1));
+		assert_eq!(Salary::last_active(&1).unwrap(), 0);
+		System::assert_last_event(Event::<Test>::Inducted { who: 1 }.into());
+		assert_noop!(Salary::induct(RuntimeOrigin::signed(1)), Error::<Test>::AlreadyInducted);
+
+		run_to(5);
+		assert_ok!(Salary::bump(RuntimeOrigin::signed(1)));
+		assert_ok!(Salary::register(RuntimeOrigin::signed(1)));
+		assert_eq!(Salary::status().unwrap().total_registrations, 1);
+
+		// A promotion during registration updates the registered amount.
+		set_rank(1, 3);
+		Salary::on_rank_changed(&1, Some(3));
+		assert_eq!(Salary::status().unwrap().total_registrations, 3);
+		System::assert_last_event(Event::<Test>::Registered { who: 1, amount: 3 }.into());
+
+		// Once registration is over, the registration stands.
+		run_to(7);
+		set_rank(1, 2);
+		Salary::on_rank_changed(&1, Some(2));
+		assert_eq!(Salary::status().unwrap().total_registrations, 3);
+		assert_ok!(Salary::payout(RuntimeOrigin::signed(1)));
+		assert_eq!(paid(1), 3);
+	});
+}
+
+#[test]
+fn unranked_members_are_removed_from_payroll() {
+	new_test_ext().execute_with(|| {
+		set_rank(1, 1);
+		set_rank(2, 2);
+		assert_ok!(Salary::init(RuntimeOrigin::signed(1)));
+		assert_ok!(Salary::induct(RuntimeOrigin::signed(1)));
+		assert_ok!(Salary::induct(RuntimeOrigin::signed(2)));
+		run_to(5);
+		assert_ok!(Salary::bump(RuntimeOrigin::signed(1)));
+		assert_ok!(Salary::register(RuntimeOrigin::signed(1)));
+		assert_ok!(Salary::register(RuntimeOrigin::signed(2)));
+		assert_eq!(Salary::status().unwrap().total_registrations, 3);
+
+		CLUB.with(|club| club.borrow_mut().remove(&2));
+		Salary::on_rank_changed(&2, None);
+		System::assert_last_event(Event::<Test>::Removed { who: 2 }.into());
+		assert!(Salary::last_active(&2).is_err());
+		assert_eq!(Salary::status().unwrap().total_registrations, 1);
+
+		run_to(7);
+		assert_noop!(Salary::payout(RuntimeOrigin::signed(2)), Error::<Test>::NotInducted);
+		assert_ok!(Salary::payout(RuntimeOrigin::signed(1)));
+		assert_eq!(paid(1), 1);
+	});
+}
@@ -0,0 +1,42 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Default weights for the Sassafras Pallet
+//! This file was not auto-generated.
+
+use frame_support::weights::{
+	constants::{RocksDbWeight as DbWeight, WEIGHT_REF_TIME_PER_MICROS},
+	Weight,
+};
+
+impl crate::WeightInfo for () {
+	fn submit_tickets(tickets_count: u32) -> Weight {
+		// ring verifier construction
+		Weight::from_parts(20_000u64 * WEIGHT_REF_TIME_PER_MICROS, 0)
+			// ring proof verification for each ticket
+			.saturating_add(
+				Weight::from_parts(12_000u64 * WEIGHT_REF_TIME_PER_MICROS, 0)
+					.saturating_mul(tickets_count as u64),
+			)
+			.saturating_add(DbWeight::get().reads(7 + tickets_count as u64))
+			.saturating_add(DbWeight::get().writes(2 + tickets_count as u64))
+	}
+
+	fn plan_config_change() -> Weight {
+		DbWeight::get().writes(1)
+	}
+}
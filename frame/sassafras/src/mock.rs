@@ -0,0 +1,306 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Test utilities for Sassafras pallet.
+
+use crate::{self as pallet_sassafras, *};
+
+use frame_support::traits::{ConstU32, ConstU64, OnFinalize, OnInitialize};
+use sp_consensus_sassafras::{
+	digests::{CompatibleDigestItem, SlotClaim},
+	vrf::{RingProver, VrfSignature},
+	AuthorityIndex, AuthorityPair, EpochConfiguration, Slot, TicketBody, TicketEnvelope, TicketId,
+};
+use sp_core::{
+	crypto::{ByteArray, Pair, UncheckedFrom, VrfSecret, Wraps},
+	ed25519::Public as EphemeralPublic,
+	H256, U256,
+};
+use sp_runtime::{
+	testing::{Digest, DigestItem, Header, TestXt},
+	traits::IdentityLookup,
+	BuildStorage,
+};
+
+const LOG_TARGET: &str = "sassafras::tests";
+
+pub const EPOCH_DURATION: u32 = 20;
+pub const MAX_AUTHORITIES: u32 = 100;
+
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test
+	{
+		System: frame_system,
+		Timestamp: pallet_timestamp,
+		Sassafras: pallet_sassafras,
+	}
+);
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type RuntimeOrigin = RuntimeOrigin;
+	type Nonce = u64;
+	type RuntimeCall = RuntimeCall;
+	type Hash = H256;
+	type Version = ();
+	type Hashing = sp_runtime::traits::BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Block = Block;
+	type RuntimeEvent = RuntimeEvent;
+	type BlockHashCount = ConstU64<250>;
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+	type OnSetCode = ();
+	type MaxConsumers = ConstU32<16>;
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Test
+where
+	RuntimeCall: From<C>,
+{
+	type OverarchingCall = RuntimeCall;
+	type Extrinsic = TestXt<RuntimeCall, ()>;
+}
+
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = ConstU64<1>;
+	type WeightInfo = ();
+}
+
+impl pallet_sassafras::Config for Test {
+	type EpochDuration = ConstU32<EPOCH_DURATION>;
+	type EpochChangeTrigger = SameAuthoritiesForever;
+	type MaxAuthorities = ConstU32<MAX_AUTHORITIES>;
+	type WeightInfo = ();
+}
+
+/// Test epoch configuration.
+pub const TEST_EPOCH_CONFIGURATION: EpochConfiguration =
+	EpochConfiguration { redundancy_factor: u32::MAX, attempts_number: 32 };
+
+/// Build and returns test storage externalities.
+pub fn new_test_ext(authorities_len: usize) -> sp_io::TestExternalities {
+	new_test_ext_with_pairs(authorities_len).1
+}
+
+/// Build and returns test storage externalities and authority set pairs used
+/// by Sassafras genesis configuration.
+pub fn new_test_ext_with_pairs(
+	authorities_len: usize,
+) -> (Vec<AuthorityPair>, sp_io::TestExternalities) {
+	let pairs = (0..authorities_len)
+		.map(|i| AuthorityPair::from_seed(&U256::from(i).into()))
+		.collect::<Vec<_>>();
+
+	let authorities: Vec<_> = pairs.iter().map(|p| p.public()).collect();
+
+	let mut storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+
+	pallet_sassafras::GenesisConfig::<Test> {
+		authorities,
+		epoch_config: TEST_EPOCH_CONFIGURATION,
+		ring_context: Some(vrf::RingContext::new_testing()),
+		_phantom: sp_std::marker::PhantomData,
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	(pairs, storage.into())
+}
+
+fn make_ticket_with_prover(
+	attempt: u32,
+	pair: &AuthorityPair,
+	prover: &RingProver,
+) -> TicketEnvelope {
+	log::debug!(target: LOG_TARGET, "attempt: {}", attempt);
+
+	// Values are referring to the next epoch
+	let epoch = Sassafras::epoch_index() + 1;
+	let randomness = Sassafras::next_randomness();
+
+	// Make a dummy ephemeral public that hopefully is unique within one test instance.
+	// In the tests, the values within the erased public are just used to compare
+	// ticket bodies, so it is not important to be a valid key.
+	let mut raw: [u8; 32] = [0; 32];
+	raw.copy_from_slice(&pair.public().as_slice()[0..32]);
+	let erased_public = EphemeralPublic::unchecked_from(raw);
+
+	let body = TicketBody { attempt_idx: attempt, erased_public };
+
+	let ticket_id_input = vrf::ticket_id_input(&randomness, attempt, epoch);
+	let sign_data = vrf::ticket_body_sign_data(&body, ticket_id_input);
+
+	let signature = pair.as_ref().ring_vrf_sign(&sign_data, prover);
+
+	TicketEnvelope { body, signature }
+}
+
+/// Construct a ring prover for the next epoch authorities set using the
+/// stored ring context.
+pub fn make_prover(pair: &AuthorityPair) -> RingProver {
+	let public = pair.public();
+	let mut prover_idx = None;
+
+	let ring_ctx = Sassafras::ring_context().unwrap();
+
+	let pks: Vec<sp_core::bandersnatch::Public> = Sassafras::next_authorities()
+		.iter()
+		.enumerate()
+		.map(|(idx, auth)| {
+			if public == *auth {
+				prover_idx = Some(idx);
+			}
+			*auth.as_ref()
+		})
+		.collect();
+
+	log::debug!(target: LOG_TARGET, "Building prover. Ring size: {}", pks.len());
+	let prover = ring_ctx.prover(&pks, prover_idx.unwrap()).unwrap();
+	log::debug!(target: LOG_TARGET, "Done");
+
+	prover
+}
+
+/// Construct `attempts` tickets envelopes for the next epoch.
+///
+/// E.g. by passing an optional threshold
+pub fn make_tickets(attempts: u32, pair: &AuthorityPair) -> Vec<TicketEnvelope> {
+	let prover = make_prover(pair);
+	(0..attempts)
+		.map(|attempt| make_ticket_with_prover(attempt, pair, &prover))
+		.collect()
+}
+
+/// Construct a dummy ticket body with the given attempt index.
+pub fn make_ticket_body(attempt_idx: u32, pair: &AuthorityPair) -> (TicketId, TicketBody) {
+	// Values are referring to the next epoch
+	let epoch = Sassafras::epoch_index() + 1;
+	let randomness = Sassafras::next_randomness();
+
+	let ticket_id_input = vrf::ticket_id_input(&randomness, attempt_idx, epoch);
+	let ticket_id_output = pair.as_inner_ref().vrf_output(&ticket_id_input);
+
+	let id = vrf::make_ticket_id(&ticket_id_input, &ticket_id_output);
+
+	// Make a dummy ephemeral public that hopefully is unique within one test instance.
+	let mut raw: [u8; 32] = [0; 32];
+	raw[..16].copy_from_slice(&pair.public().as_slice()[0..16]);
+	raw[16..].copy_from_slice(&id.to_le_bytes());
+	let erased_public = EphemeralPublic::unchecked_from(raw);
+
+	let body = TicketBody { attempt_idx, erased_public };
+
+	(id, body)
+}
+
+/// Construct `count` dummy ticket bodies with sequential attempt indices.
+pub fn make_dummy_ticket_bodies(count: u32, pair: &AuthorityPair) -> Vec<(TicketId, TicketBody)> {
+	(0..count).map(|v| make_ticket_body(v, pair)).collect()
+}
+
+/// Slot claim VRF signature for the given slot using the current epoch randomness.
+pub fn slot_claim_vrf_signature(slot: Slot, pair: &AuthorityPair) -> VrfSignature {
+	let mut epoch = Sassafras::epoch_index();
+	let mut randomness = Sassafras::randomness();
+
+	// Check if epoch is going to change on initialization.
+	let epoch_start = *Sassafras::current_epoch_start();
+	let epoch_duration = EPOCH_DURATION as u64;
+	if epoch_start != 0 && *slot >= epoch_start + epoch_duration {
+		epoch += (*slot - epoch_start) / epoch_duration;
+		randomness = crate::NextRandomness::<Test>::get();
+	}
+
+	let data = vrf::slot_claim_sign_data(&randomness, slot, epoch);
+	pair.as_ref().vrf_sign(&data)
+}
+
+/// Construct a `PreDigest` instance for the given parameters.
+pub fn make_slot_claim(
+	authority_idx: AuthorityIndex,
+	slot: Slot,
+	pair: &AuthorityPair,
+) -> SlotClaim {
+	let vrf_signature = slot_claim_vrf_signature(slot, pair);
+	SlotClaim { authority_idx, slot, vrf_signature, ticket_claim: None }
+}
+
+/// Construct a `Digest` with a `SlotClaim` item.
+pub fn make_digest(authority_idx: AuthorityIndex, slot: Slot, pair: &AuthorityPair) -> Digest {
+	let claim = make_slot_claim(authority_idx, slot, pair);
+	Digest { logs: vec![DigestItem::sassafras_pre_digest(claim)] }
+}
+
+/// Initialize the block with the given number and slot.
+pub fn initialize_block(
+	number: u64,
+	slot: Slot,
+	parent_hash: H256,
+	pair: &AuthorityPair,
+) -> Digest {
+	let digest = make_digest(0, slot, pair);
+	System::reset_events();
+	System::initialize(&number, &parent_hash, &digest);
+	Sassafras::on_initialize(number);
+	digest
+}
+
+/// Finalize the current block.
+pub fn finalize_block(number: u64) -> Header {
+	Sassafras::on_finalize(number);
+	System::finalize()
+}
+
+/// Progress the pallet state up to the given block `number` and `slot`.
+pub fn go_to_block(number: u64, slot: Slot, pair: &AuthorityPair) -> Digest {
+	Sassafras::on_finalize(System::block_number());
+	let parent_hash = System::finalize().hash();
+
+	let digest = make_digest(0, slot, pair);
+
+	System::reset_events();
+	System::initialize(&number, &parent_hash, &digest);
+	Sassafras::on_initialize(number);
+
+	digest
+}
+
+/// Progress the pallet state up to the given block `number`.
+/// Slots will grow linearly accordingly to blocks.
+pub fn progress_to_block(number: u64, pair: &AuthorityPair) -> Option<Digest> {
+	let mut slot = Sassafras::current_slot() + 1;
+	let mut digest = None;
+	for i in System::block_number() + 1..=number {
+		let dig = go_to_block(i, slot, pair);
+		digest = Some(dig);
+		slot = slot + 1;
+	}
+	digest
+}
@@ -0,0 +1,505 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for Sassafras pallet.
+
+use super::*;
+use mock::*;
+
+use frame_support::{assert_noop, assert_ok, unsigned::ValidateUnsigned};
+use sp_runtime::{
+	transaction_validity::{InvalidTransaction, TransactionSource},
+	DispatchError,
+};
+
+const GENESIS_SLOT: u64 = 100;
+
+fn next_epoch_digest() -> DigestItem {
+	let consensus_log = ConsensusLog::NextEpochData(NextEpochDescriptor {
+		authorities: Sassafras::next_authorities().into_inner(),
+		randomness: Sassafras::next_randomness(),
+		config: Sassafras::next_config(),
+	});
+	DigestItem::Consensus(SASSAFRAS_ENGINE_ID, consensus_log.encode())
+}
+
+#[test]
+fn genesis_values_assumptions_check() {
+	new_test_ext(4).execute_with(|| {
+		assert_eq!(Sassafras::authorities().len(), 4);
+		assert_eq!(Sassafras::config(), TEST_EPOCH_CONFIGURATION);
+		assert!(Sassafras::ring_context().is_some());
+	});
+}
+
+#[test]
+fn on_first_block_after_genesis() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(4);
+
+	ext.execute_with(|| {
+		let start_slot = Slot::from(GENESIS_SLOT);
+		let start_block = 1;
+
+		let digest = initialize_block(start_block, start_slot, Default::default(), &pairs[0]);
+
+		// Post-initialization status
+
+		assert!(ClaimTemporaryData::<Test>::exists());
+		assert_eq!(Sassafras::genesis_slot(), start_slot);
+		assert_eq!(Sassafras::current_slot(), start_slot);
+		assert_eq!(Sassafras::epoch_index(), 0);
+		assert_eq!(Sassafras::current_epoch_start(), start_slot);
+		assert_eq!(Sassafras::current_slot_index(), 0);
+		assert_eq!(Sassafras::randomness(), [0; 32]);
+
+		let accumulator = Sassafras::randomness_accumulator();
+		let header = finalize_block(start_block);
+
+		// Post-finalization status
+
+		assert!(!ClaimTemporaryData::<Test>::exists());
+		assert_ne!(Sassafras::randomness_accumulator(), accumulator);
+
+		// Header data check
+
+		assert_eq!(header.digest.logs.len(), 2);
+		assert_eq!(header.digest.logs[0], digest.logs[0]);
+
+		// Genesis epoch start deposits consensus
+		assert_eq!(header.digest.logs[1], next_epoch_digest());
+	})
+}
+
+#[test]
+fn on_normal_block() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(4);
+	let start_slot = Slot::from(GENESIS_SLOT);
+	let start_block = 1;
+	let end_block = start_block + 1;
+
+	ext.execute_with(|| {
+		initialize_block(start_block, start_slot, Default::default(), &pairs[0]);
+
+		// We don't want to trigger an epoch change in this test.
+		let epoch_duration: u64 = EPOCH_DURATION.into();
+		assert!(epoch_duration > end_block);
+
+		// Progress to block 2
+		let digest = progress_to_block(end_block, &pairs[0]).unwrap();
+
+		// Post-initialization status
+
+		assert!(ClaimTemporaryData::<Test>::exists());
+		assert_eq!(Sassafras::genesis_slot(), start_slot);
+		assert_eq!(Sassafras::current_slot(), start_slot + 1);
+		assert_eq!(Sassafras::epoch_index(), 0);
+		assert_eq!(Sassafras::current_epoch_start(), start_slot);
+		assert_eq!(Sassafras::current_slot_index(), 1);
+
+		let header = finalize_block(end_block);
+
+		// Post-finalization status
+
+		assert!(!ClaimTemporaryData::<Test>::exists());
+
+		// Header data check
+
+		assert_eq!(header.digest.logs.len(), 1);
+		assert_eq!(header.digest.logs[0], digest.logs[0]);
+	});
+}
+
+#[test]
+fn produce_epoch_change_digest_no_config() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(4);
+
+	ext.execute_with(|| {
+		let start_slot = Slot::from(GENESIS_SLOT);
+		let start_block = 1;
+
+		initialize_block(start_block, start_slot, Default::default(), &pairs[0]);
+
+		// We want to trigger an epoch change in this test.
+		let epoch_duration: u64 = EPOCH_DURATION.into();
+		let end_block = start_block + epoch_duration;
+
+		let digest = progress_to_block(end_block, &pairs[0]).unwrap();
+
+		// Post-initialization status
+
+		assert!(ClaimTemporaryData::<Test>::exists());
+		assert_eq!(Sassafras::genesis_slot(), start_slot);
+		assert_eq!(Sassafras::current_slot(), start_slot + epoch_duration);
+		assert_eq!(Sassafras::epoch_index(), 1);
+		assert_eq!(Sassafras::current_epoch_start(), start_slot + epoch_duration);
+		assert_eq!(Sassafras::current_slot_index(), 0);
+
+		let header = finalize_block(end_block);
+
+		// Header data check
+
+		assert_eq!(header.digest.logs.len(), 2);
+		assert_eq!(header.digest.logs[0], digest.logs[0]);
+		// Deposits consensus log on epoch change
+		assert_eq!(header.digest.logs[1], next_epoch_digest());
+		assert_eq!(Sassafras::next_config(), None);
+	})
+}
+
+#[test]
+fn produce_epoch_change_digest_with_config() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(4);
+
+	ext.execute_with(|| {
+		let start_slot = Slot::from(GENESIS_SLOT);
+		let start_block = 1;
+
+		initialize_block(start_block, start_slot, Default::default(), &pairs[0]);
+
+		let config = EpochConfiguration { redundancy_factor: 1, attempts_number: 123 };
+		assert_ok!(Sassafras::plan_config_change(RuntimeOrigin::root(), config));
+
+		// We want to trigger an epoch change in this test.
+		let epoch_duration: u64 = EPOCH_DURATION.into();
+		let end_block = start_block + epoch_duration;
+
+		let digest = progress_to_block(end_block, &pairs[0]).unwrap();
+
+		let header = finalize_block(end_block);
+
+		// Header data check.
+		// Skip pallet status checks that were already performed by other tests.
+
+		assert_eq!(header.digest.logs.len(), 2);
+		assert_eq!(header.digest.logs[0], digest.logs[0]);
+		// Deposits consensus log on epoch change
+		assert_eq!(Sassafras::next_config(), Some(config));
+		assert_eq!(header.digest.logs[1], next_epoch_digest());
+		// Current epoch configuration is left untouched
+		assert_eq!(Sassafras::config(), TEST_EPOCH_CONFIGURATION);
+	})
+}
+
+#[test]
+fn plan_config_change_rejects_bad_input() {
+	new_test_ext(4).execute_with(|| {
+		let config = EpochConfiguration { redundancy_factor: 1, attempts_number: 1 };
+		assert_noop!(
+			Sassafras::plan_config_change(RuntimeOrigin::signed(1), config),
+			DispatchError::BadOrigin
+		);
+
+		let config = EpochConfiguration { redundancy_factor: 0, attempts_number: 1 };
+		assert_noop!(
+			Sassafras::plan_config_change(RuntimeOrigin::root(), config),
+			Error::<Test>::InvalidConfiguration
+		);
+
+		let config = EpochConfiguration { redundancy_factor: 1, attempts_number: 0 };
+		assert_noop!(
+			Sassafras::plan_config_change(RuntimeOrigin::root(), config),
+			Error::<Test>::InvalidConfiguration
+		);
+	})
+}
+
+#[test]
+fn skipped_epochs_reset_tickets_data() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(4);
+
+	ext.execute_with(|| {
+		let start_slot = Slot::from(GENESIS_SLOT);
+		let start_block = 1;
+		let epoch_duration: u64 = EPOCH_DURATION.into();
+
+		initialize_block(start_block, start_slot, Default::default(), &pairs[0]);
+
+		// Append some dummy next epoch tickets.
+		let tickets = make_dummy_ticket_bodies(3, &pairs[0]);
+		tickets.iter().for_each(|(id, body)| TicketsData::<Test>::insert(id, body));
+		let ids = tickets.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+		Sassafras::append_tickets(BoundedVec::truncate_from(ids));
+
+		// Skip two epochs and land in the middle of the third one.
+		let slot = start_slot + 3 * epoch_duration + 5;
+		go_to_block(start_block + 1, slot, &pairs[0]);
+
+		assert_eq!(Sassafras::epoch_index(), 3);
+		assert_eq!(Sassafras::current_epoch_start(), start_slot + 3 * epoch_duration);
+		assert_eq!(Sassafras::current_slot_index(), 5);
+
+		// Tickets data has been dropped.
+		assert_eq!(TicketsMeta::<Test>::get(), TicketsMetadata::default());
+		assert_eq!(TicketsData::<Test>::iter().count(), 0);
+		assert_eq!(UnsortedSegments::<Test>::iter().count(), 0);
+	})
+}
+
+#[test]
+fn slot_ticket_id_outside_in_fetch() {
+	let genesis_slot = Slot::from(GENESIS_SLOT);
+	let tickets_count = 6;
+
+	// Current epoch tickets
+	let curr_tickets: Vec<TicketId> = (0..tickets_count).map(|i| i as TicketId).collect();
+
+	// Next epoch tickets
+	let next_tickets: Vec<TicketId> =
+		(0..tickets_count - 1).map(|i| (i + tickets_count) as TicketId).collect();
+
+	new_test_ext(4).execute_with(|| {
+		curr_tickets
+			.iter()
+			.enumerate()
+			.for_each(|(i, id)| TicketsIds::<Test>::insert((0, i as u32), id));
+
+		next_tickets
+			.iter()
+			.enumerate()
+			.for_each(|(i, id)| TicketsIds::<Test>::insert((1, i as u32), id));
+
+		TicketsMeta::<Test>::set(TicketsMetadata {
+			tickets_count: [curr_tickets.len() as u32, next_tickets.len() as u32],
+			unsorted_tickets_count: 0,
+		});
+
+		// Before importing the first block the pallet always return `None`
+		// This is a kind of special hardcoded case that should never happen in practice
+		// as the first thing the pallet does is to initialize the genesis slot.
+
+		assert_eq!(Sassafras::slot_ticket_id(0.into()), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 0), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 1), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 100), None);
+
+		// Initialize genesis slot..
+		GenesisSlot::<Test>::set(genesis_slot);
+		frame_system::Pallet::<Test>::set_block_number(One::one());
+
+		// Try to fetch a ticket for a slot before current epoch.
+		assert_eq!(Sassafras::slot_ticket_id(0.into()), None);
+
+		// Current epoch tickets.
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 0), Some(curr_tickets[1]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 1), Some(curr_tickets[3]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 2), Some(curr_tickets[5]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 3), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 9), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 16), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 17), Some(curr_tickets[4]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 18), Some(curr_tickets[2]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 19), Some(curr_tickets[0]));
+
+		// Next epoch tickets (note that only 5 tickets are available)
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 20), Some(next_tickets[1]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 21), Some(next_tickets[3]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 22), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 36), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 37), Some(next_tickets[4]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 38), Some(next_tickets[2]));
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 39), Some(next_tickets[0]));
+
+		// Try to fetch the tickets for slots beyond the next epoch.
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 40), None);
+		assert_eq!(Sassafras::slot_ticket_id(genesis_slot + 400), None);
+	});
+}
+
+#[test]
+fn incremental_sortition_works() {
+	let ids: Vec<TicketId> = (0..200).map(|i| (i * 919) % 1000).collect();
+	let mut expected = ids.clone();
+	expected.sort_unstable();
+	expected.truncate(EPOCH_DURATION as usize);
+
+	let (pairs, mut ext) = new_test_ext_with_pairs(4);
+
+	ext.execute_with(|| {
+		let (_, body) = make_ticket_body(0, &pairs[0]);
+		ids.iter().for_each(|id| TicketsData::<Test>::insert(id, &body));
+		ids.chunks(EPOCH_DURATION as usize)
+			.for_each(|chunk| Sassafras::append_tickets(BoundedVec::truncate_from(chunk.to_vec())));
+
+		let mut meta = TicketsMeta::<Test>::get();
+		assert_eq!(meta.unsorted_tickets_count, 200);
+		assert_eq!(UnsortedSegments::<Test>::get(0).len(), SEGMENT_MAX_SIZE as usize);
+		assert_eq!(UnsortedSegments::<Test>::get(1).len(), 200 - SEGMENT_MAX_SIZE as usize);
+
+		// Sort the last segment. The partial result is kept as sorted candidates.
+		Sassafras::sort_segments(1, 1, &mut meta);
+		assert_eq!(meta.unsorted_tickets_count, SEGMENT_MAX_SIZE);
+		assert_eq!(meta.tickets_count, [0, 0]);
+		assert_eq!(SortedCandidates::<Test>::get().len(), EPOCH_DURATION as usize);
+		assert!(!UnsortedSegments::<Test>::contains_key(1));
+
+		// Sort the remaining segment. This completes the sorting procedure.
+		Sassafras::sort_segments(1, 1, &mut meta);
+		assert_eq!(meta.unsorted_tickets_count, 0);
+		assert_eq!(meta.tickets_count, [0, EPOCH_DURATION]);
+		assert!(SortedCandidates::<Test>::get().is_empty());
+		assert!(!UnsortedSegments::<Test>::contains_key(0));
+
+		let sorted: Vec<_> =
+			(0..EPOCH_DURATION).map(|i| TicketsIds::<Test>::get((1, i)).unwrap()).collect();
+		assert_eq!(sorted, expected);
+
+		// Discarded tickets data has been removed.
+		assert_eq!(TicketsData::<Test>::iter().count(), EPOCH_DURATION as usize);
+		assert!(expected.iter().all(|id| TicketsData::<Test>::contains_key(id)));
+	});
+}
+
+#[test]
+fn tickets_are_enacted_on_epoch_change() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(4);
+
+	ext.execute_with(|| {
+		let start_slot = Slot::from(GENESIS_SLOT);
+		let start_block = 1;
+		let epoch_duration: u64 = EPOCH_DURATION.into();
+
+		initialize_block(start_block, start_slot, Default::default(), &pairs[0]);
+
+		let tickets = make_dummy_ticket_bodies(5, &pairs[0]);
+		tickets.iter().for_each(|(id, body)| TicketsData::<Test>::insert(id, body));
+		let ids = tickets.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+		Sassafras::append_tickets(BoundedVec::truncate_from(ids.clone()));
+
+		let mut sorted = ids;
+		sorted.sort_unstable();
+
+		// Progress to the first block of the next epoch.
+		progress_to_block(start_block + epoch_duration, &pairs[0]);
+		assert_eq!(Sassafras::epoch_index(), 1);
+
+		let meta = TicketsMeta::<Test>::get();
+		assert_eq!(meta.unsorted_tickets_count, 0);
+		assert_eq!(meta.tickets_count, [0, 5]);
+
+		let epoch_start = Sassafras::current_epoch_start();
+		assert_eq!(Sassafras::slot_ticket_id(epoch_start), Some(sorted[1]));
+		assert_eq!(Sassafras::slot_ticket_id(epoch_start + (epoch_duration - 1)), Some(sorted[0]));
+
+		let (id, body) = Sassafras::slot_ticket(epoch_start + 1).unwrap();
+		assert_eq!(id, sorted[3]);
+		assert_eq!(TicketsData::<Test>::get(id), Some(body));
+
+		// On the next epoch change the tickets of epoch 1 are dropped.
+		progress_to_block(start_block + 2 * epoch_duration, &pairs[0]);
+		assert_eq!(Sassafras::epoch_index(), 2);
+		assert_eq!(TicketsMeta::<Test>::get().tickets_count, [0, 0]);
+		assert_eq!(TicketsData::<Test>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn submit_tickets_with_ring_proof_works() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(4);
+
+	ext.execute_with(|| {
+		let start_slot = Slot::from(GENESIS_SLOT);
+		let start_block = 1;
+
+		initialize_block(start_block, start_slot, Default::default(), &pairs[0]);
+
+		let tickets = make_tickets(2, &pairs[1]);
+		let tickets = BoundedVec::truncate_from(tickets);
+
+		assert_ok!(Sassafras::submit_tickets(RuntimeOrigin::none(), tickets.clone()));
+
+		let meta = TicketsMeta::<Test>::get();
+		assert_eq!(meta.unsorted_tickets_count, 2);
+		assert_eq!(TicketsData::<Test>::iter().count(), 2);
+
+		// Duplicates are silently discarded.
+		assert_ok!(Sassafras::submit_tickets(RuntimeOrigin::none(), tickets));
+		assert_eq!(TicketsMeta::<Test>::get().unsorted_tickets_count, 2);
+	});
+}
+
+#[test]
+fn submit_tickets_out_of_window_fails() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(4);
+
+	ext.execute_with(|| {
+		let start_slot = Slot::from(GENESIS_SLOT);
+		let start_block = 1;
+
+		initialize_block(start_block, start_slot, Default::default(), &pairs[0]);
+
+		// Move to the second half of the epoch.
+		let epoch_duration: u64 = EPOCH_DURATION.into();
+		progress_to_block(start_block + epoch_duration / 2 + 1, &pairs[0]);
+
+		let call = Call::submit_tickets { tickets: Default::default() };
+		assert_eq!(
+			<Sassafras as ValidateUnsigned>::validate_unsigned(TransactionSource::Local, &call),
+			InvalidTransaction::Stale.into(),
+		);
+		assert_noop!(
+			Sassafras::submit_tickets(RuntimeOrigin::none(), Default::default()),
+			Error::<Test>::TicketsSubmissionClosed
+		);
+	});
+}
+
+#[test]
+fn submit_tickets_from_external_source_is_rejected() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(4);
+
+	ext.execute_with(|| {
+		initialize_block(1, Slot::from(GENESIS_SLOT), Default::default(), &pairs[0]);
+
+		let call = Call::submit_tickets { tickets: Default::default() };
+		assert_eq!(
+			<Sassafras as ValidateUnsigned>::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::BadSigner.into(),
+		);
+		assert_ok!(<Sassafras as ValidateUnsigned>::validate_unsigned(
+			TransactionSource::Local,
+			&call
+		));
+	});
+}
+
+#[test]
+fn submit_tickets_without_ring_context_fails() {
+	use sp_consensus_sassafras::AuthorityPair;
+	use sp_core::Pair;
+	use sp_runtime::BuildStorage;
+
+	let pair = AuthorityPair::from_seed(&[0; 32]);
+	let mut storage = frame_system::GenesisConfig::<Test>::default().build_storage().unwrap();
+	GenesisConfig::<Test> {
+		authorities: vec![pair.public()],
+		epoch_config: TEST_EPOCH_CONFIGURATION,
+		ring_context: None,
+		_phantom: Default::default(),
+	}
+	.assimilate_storage(&mut storage)
+	.unwrap();
+
+	sp_io::TestExternalities::from(storage).execute_with(|| {
+		initialize_block(1, Slot::from(GENESIS_SLOT), Default::default(), &pair);
+
+		assert!(Sassafras::ring_context().is_none());
+		assert_noop!(
+			Sassafras::submit_tickets(RuntimeOrigin::none(), Default::default()),
+			Error::<Test>::RingContextUninitialized
+		);
+	});
+}
@@ -131,6 +131,16 @@ pub fn expand_runtime_metadata(
 									additional_signed: meta.additional_signed,
 								})
 								.collect(),
+						transaction_extensions: <
+								#extrinsic as #scrate::sp_runtime::traits::ExtrinsicMetadata
+							>::transaction_extensions()
+								.into_iter()
+								.map(|meta| #scrate::__private::metadata_ir::SignedExtensionMetadataIR {
+									identifier: meta.identifier,
+									ty: meta.ty,
+									additional_signed: meta.additional_signed,
+								})
+								.collect(),
 					},
 					ty: #scrate::__private::scale_info::meta_type::<#runtime>(),
 					apis: (&rt).runtime_metadata(),
@@ -301,6 +301,23 @@ pub fn expand_outer_origin(
 			}
 		}
 
+		impl #scrate::sp_runtime::traits::AsSystemOriginSigner<<#runtime as #system_path::Config>::AccountId>
+			for RuntimeOrigin
+		{
+			fn as_system_origin_signer(&self) -> Option<&<#runtime as #system_path::Config>::AccountId> {
+				match &self.caller {
+					OriginCaller::system(#system_path::Origin::<#runtime>::Signed(ref who)) => Some(who),
+					_ => None,
+				}
+			}
+		}
+
+		impl #scrate::sp_runtime::traits::AsAuthorizedOrigin for RuntimeOrigin {
+			fn is_transaction_authorized(&self) -> bool {
+				!matches!(&self.caller, OriginCaller::system(#system_path::Origin::<#runtime>::None))
+			}
+		}
+
 		#pallet_conversions
 	})
 }
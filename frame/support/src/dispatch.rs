@@ -356,8 +356,8 @@ where
 }
 
 /// Implementation for unchecked extrinsic.
-impl<Address, Call, Signature, Extra> GetDispatchInfo
-	for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra, Extension> GetDispatchInfo
+	for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 where
 	Call: GetDispatchInfo,
 	Extra: SignedExtension,
@@ -368,7 +368,8 @@ where
 }
 
 /// Implementation for checked extrinsic.
-impl<AccountId, Call, Extra> GetDispatchInfo for CheckedExtrinsic<AccountId, Call, Extra>
+impl<AccountId, Call, Extra, Extension> GetDispatchInfo
+	for CheckedExtrinsic<AccountId, Call, Extra, Extension>
 where
 	Call: GetDispatchInfo,
 {
@@ -20,7 +20,7 @@
 //! Provides common logic. For more info refer to [`sp_genesis_builder::GenesisBuilder`].
 
 use frame_support::traits::BuildGenesisConfig;
-use sp_genesis_builder::Result as BuildResult;
+use sp_genesis_builder::{PresetId, Result as BuildResult};
 use sp_runtime::format_runtime_string;
 
 /// Get the default `GenesisConfig` as a JSON blob. For more info refer to
@@ -39,3 +39,19 @@ pub fn build_config<GC: BuildGenesisConfig>(json: sp_std::vec::Vec<u8>) -> Build
 	<GC as BuildGenesisConfig>::build(&gc);
 	Ok(())
 }
+
+/// Get the JSON blob of the preset named `name`, or the default `GenesisConfig` if `name` is
+/// `None`. For more info refer to [`sp_genesis_builder::GenesisBuilder::get_preset`].
+///
+/// `preset_for_name` returns the JSON patch of the named preset, or `None` if the runtime doesn't
+/// provide it. Presets are typically written with `serde_json::json!` and only contain the fields
+/// that differ from the default `GenesisConfig`.
+pub fn get_preset<GC: BuildGenesisConfig>(
+	name: &Option<PresetId>,
+	preset_for_name: impl FnOnce(&PresetId) -> Option<sp_std::vec::Vec<u8>>,
+) -> Option<sp_std::vec::Vec<u8>> {
+	match name {
+		Some(name) => preset_for_name(name),
+		None => Some(create_default_config::<GC>()),
+	}
+}
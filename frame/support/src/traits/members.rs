@@ -289,6 +289,22 @@ pub trait RankedMembers {
 	fn demote(who: &Self::AccountId) -> DispatchResult;
 }
 
+/// Handler for when the rank of a member in some ranked membership organisation changes.
+pub trait OnRankChanged<AccountId, Rank> {
+	/// The rank of `who` has changed to `new_rank`. `None` means they are no longer ranked at
+	/// all.
+	fn on_rank_changed(who: &AccountId, new_rank: Option<Rank>);
+}
+
+#[cfg_attr(all(not(feature = "tuples-96"), not(feature = "tuples-128")), impl_for_tuples(64))]
+#[cfg_attr(all(feature = "tuples-96", not(feature = "tuples-128")), impl_for_tuples(96))]
+#[cfg_attr(feature = "tuples-128", impl_for_tuples(128))]
+impl<AccountId, Rank: Clone> OnRankChanged<AccountId, Rank> for Tuple {
+	fn on_rank_changed(who: &AccountId, new_rank: Option<Rank>) {
+		for_tuples!( #( Tuple::on_rank_changed(who, new_rank.clone()); )* );
+	}
+}
+
 /// Trait for type that can handle the initialization of account IDs at genesis.
 pub trait InitializeMembers<AccountId> {
 	/// Initialize the members to the given `members`.
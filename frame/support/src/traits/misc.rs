@@ -901,13 +901,14 @@ where
 	}
 }
 
-impl<Address, Call, Signature, Extra> ExtrinsicCall
-	for sp_runtime::generic::UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra, Extension> ExtrinsicCall
+	for sp_runtime::generic::UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 where
 	Address: TypeInfo,
 	Call: TypeInfo,
 	Signature: TypeInfo,
 	Extra: sp_runtime::traits::SignedExtension + TypeInfo,
+	Extension: TypeInfo,
 {
 	fn call(&self) -> &Self::Call {
 		&self.function
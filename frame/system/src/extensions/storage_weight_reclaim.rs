@@ -0,0 +1,307 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::Config;
+use codec::{Decode, Encode};
+use frame_support::dispatch::{DispatchInfo, PostDispatchInfo};
+use scale_info::TypeInfo;
+use sp_io::{storage_proof_size::storage_proof_size, PROOF_RECORDING_DISABLED};
+use sp_runtime::{
+	traits::{DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SignedExtension},
+	transaction_validity::TransactionValidityError,
+	DispatchResult,
+};
+use sp_weights::Weight;
+
+/// Returns the current storage proof size or `None` if the proof is not recorded.
+fn proof_size() -> Option<u64> {
+	let proof_size = storage_proof_size();
+	(proof_size != PROOF_RECORDING_DISABLED).then_some(proof_size)
+}
+
+/// Reclaim the unused proof size weight of an extrinsic.
+///
+/// The benchmarked proof size of an extrinsic assumes the worst case trie depth and is usually
+/// much higher than the storage proof that is actually recorded. This extension measures the
+/// storage proof size before and after the dispatch and corrects the proof size of the
+/// [`BlockWeight`](crate::BlockWeight) by the difference to the benchmarked proof size. If the
+/// extrinsic used more than benchmarked, the difference is added instead.
+///
+/// # Proof Recording
+///
+/// The measured proof size ends up in the [`BlockWeight`](crate::BlockWeight), which is part of
+/// the state, so every node executing a block has to measure the same. Proof recording is opt-in
+/// on the node side, and nodes running a runtime with this extension have to enable it both for
+/// authoring, e.g. with `ProposerFactory::with_proof_recording`, and for import, with
+/// `sc_service::new_full_parts_record_import`. Otherwise the importing node ends up with a
+/// different state root than the author.
+///
+/// Without a recorded proof, e.g. in tests or off-chain calls, the
+/// [`storage_proof_size`] host function returns [`PROOF_RECORDING_DISABLED`] and this extension
+/// leaves the block weight untouched.
+///
+/// # Ordering
+///
+/// This extension must be placed after [`CheckWeight`](crate::CheckWeight) in the runtime's
+/// `SignedExtra` tuple. It expects `CheckWeight::post_dispatch` to have already refunded the
+/// unused benchmarked weight, so that the block weight only contains the actual weight of the
+/// extrinsic. Placed before `CheckWeight`, the refunded proof size would be reclaimed twice.
+///
+/// # Transaction Validity
+///
+/// This extension does not influence any fields of `TransactionValidity` in case the
+/// transaction is valid.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, Default, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct StorageWeightReclaim<T: Config + Send + Sync>(sp_std::marker::PhantomData<T>);
+
+impl<T: Config + Send + Sync> StorageWeightReclaim<T> {
+	/// Create new `SignedExtension` to reclaim unused proof size weight.
+	pub fn new() -> Self {
+		Self(Default::default())
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for StorageWeightReclaim<T>
+where
+	T::RuntimeCall: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+{
+	type AccountId = T::AccountId;
+	type Call = T::RuntimeCall;
+	type AdditionalSigned = ();
+	type Pre = Option<u64>;
+	const IDENTIFIER: &'static str = "StorageWeightReclaim";
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn pre_dispatch(
+		self,
+		_who: &Self::AccountId,
+		_call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		Ok(proof_size())
+	}
+
+	fn post_dispatch(
+		pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		_len: usize,
+		_result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		let Some(Some(pre_dispatch_proof_size)) = pre else { return Ok(()) };
+		let Some(post_dispatch_proof_size) = proof_size() else {
+			log::debug!(
+				target: crate::LOG_TARGET,
+				"Proof recording got disabled while dispatching, unable to reclaim storage weight.",
+			);
+			return Ok(())
+		};
+
+		let consumed = post_dispatch_proof_size.saturating_sub(pre_dispatch_proof_size);
+		// `CheckWeight` already refunded the difference to the actual weight, so the block weight
+		// contains the actual weight. This relies on `CheckWeight` running first, see the
+		// `Ordering` section of the type docs.
+		let accounted = post_info.calc_actual_weight(info).proof_size();
+		let difference = Weight::from_parts(0, consumed.abs_diff(accounted));
+
+		log::trace!(
+			target: crate::LOG_TARGET,
+			"Reclaiming storage weight. Accounted: {}, consumed: {}",
+			accounted,
+			consumed,
+		);
+
+		crate::BlockWeight::<T>::mutate(|current_weight| {
+			if consumed > accounted {
+				current_weight.accrue(difference, info.class);
+			} else {
+				current_weight.reduce(difference, info.class);
+			}
+		});
+
+		Ok(())
+	}
+}
+
+impl<T: Config + Send + Sync> sp_std::fmt::Debug for StorageWeightReclaim<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "StorageWeightReclaim")
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		mock::{new_test_ext, Test, CALL},
+		BlockWeight, CheckWeight, DispatchClass,
+	};
+	use frame_support::assert_ok;
+	use sp_trie::proof_size_extension::{ProofSizeExt, ReportProofSize};
+	use std::sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc,
+	};
+
+	const INITIAL_PROOF_SIZE: u64 = 1000;
+
+	/// Reports a proof size that can be changed by the test.
+	#[derive(Clone, Default)]
+	struct TestRecorder(Arc<AtomicU64>);
+
+	impl TestRecorder {
+		fn set(&self, proof_size: u64) {
+			self.0.store(proof_size, Ordering::Relaxed);
+		}
+	}
+
+	impl ReportProofSize for TestRecorder {
+		fn proof_size(&self) -> u64 {
+			self.0.load(Ordering::Relaxed)
+		}
+	}
+
+	fn new_test_ext_with_recorder() -> (sp_io::TestExternalities, TestRecorder) {
+		let mut ext = new_test_ext();
+		let recorder = TestRecorder::default();
+		ext.register_extension(ProofSizeExt::new(recorder.clone()));
+
+		ext.execute_with(|| {
+			BlockWeight::<Test>::mutate(|current_weight| {
+				current_weight
+					.set(Weight::from_parts(0, INITIAL_PROOF_SIZE), DispatchClass::Normal);
+			});
+		});
+
+		(ext, recorder)
+	}
+
+	fn normal_proof_size() -> u64 {
+		BlockWeight::<Test>::get().get(DispatchClass::Normal).proof_size()
+	}
+
+	#[test]
+	fn unused_proof_size_is_reclaimed() {
+		let (mut ext, recorder) = new_test_ext_with_recorder();
+
+		ext.execute_with(|| {
+			let info = DispatchInfo { weight: Weight::from_parts(0, 500), ..Default::default() };
+			BlockWeight::<Test>::mutate(|w| w.accrue(info.weight, DispatchClass::Normal));
+
+			recorder.set(100);
+			let pre = StorageWeightReclaim::<Test>::new().pre_dispatch(&1, CALL, &info, 0).unwrap();
+			assert_eq!(pre, Some(100));
+
+			recorder.set(300);
+			assert_ok!(StorageWeightReclaim::<Test>::post_dispatch(
+				Some(pre),
+				&info,
+				&Default::default(),
+				0,
+				&Ok(())
+			));
+
+			assert_eq!(normal_proof_size(), INITIAL_PROOF_SIZE + 200);
+		});
+	}
+
+	#[test]
+	fn additional_proof_size_is_accrued() {
+		let (mut ext, recorder) = new_test_ext_with_recorder();
+
+		ext.execute_with(|| {
+			let info = DispatchInfo { weight: Weight::from_parts(0, 500), ..Default::default() };
+			BlockWeight::<Test>::mutate(|w| w.accrue(info.weight, DispatchClass::Normal));
+
+			recorder.set(100);
+			let pre = StorageWeightReclaim::<Test>::new().pre_dispatch(&1, CALL, &info, 0).unwrap();
+
+			recorder.set(900);
+			assert_ok!(StorageWeightReclaim::<Test>::post_dispatch(
+				Some(pre),
+				&info,
+				&Default::default(),
+				0,
+				&Ok(())
+			));
+
+			assert_eq!(normal_proof_size(), INITIAL_PROOF_SIZE + 800);
+		});
+	}
+
+	#[test]
+	fn works_together_with_the_refund_of_check_weight() {
+		let (mut ext, recorder) = new_test_ext_with_recorder();
+
+		ext.execute_with(|| {
+			let info = DispatchInfo { weight: Weight::from_parts(0, 500), ..Default::default() };
+			let post_info = PostDispatchInfo {
+				actual_weight: Some(Weight::from_parts(0, 400)),
+				pays_fee: Default::default(),
+			};
+			BlockWeight::<Test>::mutate(|w| w.accrue(info.weight, DispatchClass::Normal));
+
+			recorder.set(100);
+			let pre = StorageWeightReclaim::<Test>::new().pre_dispatch(&1, CALL, &info, 0).unwrap();
+
+			recorder.set(250);
+			assert_ok!(CheckWeight::<Test>::post_dispatch(Some(()), &info, &post_info, 0, &Ok(())));
+			assert_ok!(StorageWeightReclaim::<Test>::post_dispatch(
+				Some(pre),
+				&info,
+				&post_info,
+				0,
+				&Ok(())
+			));
+
+			assert_eq!(normal_proof_size(), INITIAL_PROOF_SIZE + 150);
+		});
+	}
+
+	#[test]
+	fn does_nothing_without_proof_recording() {
+		new_test_ext().execute_with(|| {
+			let info = DispatchInfo { weight: Weight::from_parts(0, 500), ..Default::default() };
+			BlockWeight::<Test>::mutate(|w| w.accrue(info.weight, DispatchClass::Normal));
+			let before = BlockWeight::<Test>::get();
+
+			let pre = StorageWeightReclaim::<Test>::new().pre_dispatch(&1, CALL, &info, 0).unwrap();
+			assert_eq!(pre, None);
+
+			assert_ok!(StorageWeightReclaim::<Test>::post_dispatch(
+				Some(pre),
+				&info,
+				&Default::default(),
+				0,
+				&Ok(())
+			));
+
+			assert_eq!(BlockWeight::<Test>::get(), before);
+		});
+	}
+}
@@ -125,7 +125,7 @@ fn implement_common_api_traits(block_type: TypePath, self_ty: Type) -> Result<To
 			}
 
 			fn register_extension<E: #crate_::Extension>(&mut self, _: E) {
-				unimplemented!("`register_extension` not implemented for runtime api mocks")
+				// Mocks don't execute any runtime code that could use the extension.
 			}
 		}
 
@@ -0,0 +1,79 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Running
+//! Running this fuzzer can be done with `cargo hfuzz run fixed_point_256`. `honggfuzz` CLI options
+//! can be used by setting `HFUZZ_RUN_ARGS`, such as `-n 4` to use 4 threads.
+//!
+//! # Debugging a panic
+//! Once a panic is found, it can be debugged with
+//! `cargo hfuzz run-debug fixed_point_256 hfuzz_workspace/fixed_point_256/*.fuzz`.
+//!
+//! # More information
+//! More information about `honggfuzz` can be found
+//! [here](https://docs.rs/honggfuzz/).
+
+use honggfuzz::fuzz;
+use sp_arithmetic::{
+	traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Saturating},
+	FixedI128, FixedI256, FixedPointNumber, FixedU128, FixedU256,
+};
+
+/// Checks that the 256-bit fixed point numbers give the same results as the 128-bit ones, as
+/// long as the latter do not overflow.
+fn main() {
+	loop {
+		fuzz!(|data: (i128, i128)| {
+			let (x, y) = (FixedI128::from_inner(data.0), FixedI128::from_inner(data.1));
+			let (x_wide, y_wide) = (FixedI256::from(x), FixedI256::from(y));
+
+			if let Some(z) = x.checked_add(&y) {
+				assert_eq!(x_wide.checked_add(&y_wide), Some(z.into()));
+			}
+			if let Some(z) = x.checked_sub(&y) {
+				assert_eq!(x_wide.checked_sub(&y_wide), Some(z.into()));
+			}
+			if let Some(z) = x.checked_mul(&y) {
+				assert_eq!(x_wide.checked_mul(&y_wide), Some(z.into()));
+			}
+			if let Some(z) = x.checked_div(&y) {
+				assert_eq!(x_wide.checked_div(&y_wide), Some(z.into()));
+			}
+			if let Some(z) = FixedI128::checked_from_rational(data.0, data.1) {
+				assert_eq!(FixedI256::checked_from_rational(data.0, data.1), Some(z.into()));
+			}
+			assert_eq!(x_wide.checked_mul_int(data.1), x.checked_mul_int(data.1));
+			assert_eq!(x_wide.checked_div_int(data.1), x.checked_div_int(data.1));
+			assert_eq!(x_wide.saturating_mul_acc_int(data.1), x.saturating_mul_acc_int(data.1));
+			assert_eq!(x_wide.cmp(&y_wide), x.cmp(&y));
+			assert_eq!(FixedI256::from(x.round()), x_wide.round());
+
+			let (a, b) = (data.0.unsigned_abs(), data.1.unsigned_abs());
+			let (x, y) = (FixedU128::from_inner(a), FixedU128::from_inner(b));
+			let (x_wide, y_wide) = (FixedU256::from(x), FixedU256::from(y));
+
+			if let Some(z) = x.checked_mul(&y) {
+				assert_eq!(x_wide.checked_mul(&y_wide), Some(z.into()));
+			}
+			if let Some(z) = x.checked_div(&y) {
+				assert_eq!(x_wide.checked_div(&y_wide), Some(z.into()));
+			}
+			assert_eq!(FixedU256::from(x.saturating_sub(y)), x_wide.saturating_sub(y_wide));
+			assert_eq!(FixedU256::from(x.sqrt()), x_wide.sqrt());
+		});
+	}
+}
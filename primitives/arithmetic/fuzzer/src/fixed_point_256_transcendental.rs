@@ -0,0 +1,123 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! # Running
+//! Running this fuzzer can be done with `cargo hfuzz run fixed_point_256_transcendental`.
+//! `honggfuzz` CLI options can be used by setting `HFUZZ_RUN_ARGS`, such as `-n 4` to use 4
+//! threads.
+//!
+//! # Debugging a panic
+//! Once a panic is found, it can be debugged with
+//! `cargo hfuzz run-debug fixed_point_256_transcendental
+//! hfuzz_workspace/fixed_point_256_transcendental/*.fuzz`.
+//!
+//! # More information
+//! More information about `honggfuzz` can be found
+//! [here](https://docs.rs/honggfuzz/).
+
+use honggfuzz::fuzz;
+use sp_arithmetic::{
+	int256::I256,
+	traits::{CheckedMul, One, Saturating, Zero},
+	FixedI256, FixedPointNumber,
+};
+
+/// One unit of `FixedI256`, in units of accuracy.
+const UNIT: i128 = 1_000_000_000_000_000_000;
+
+/// Checks the consistency of `checked_ln`, `checked_exp`, `checked_pow_frac` and `checked_rpow`.
+fn main() {
+	loop {
+		fuzz!(|data: (i128, i128, u8)| {
+			// Raw inputs within roughly `[-1.7 * 10^20, 1.7 * 10^20]`.
+			let x = FixedI256::from_inner(data.0.into());
+			let y = FixedI256::from_inner(data.1.into());
+
+			// `e^n` is only representable and non-zero for `n` within roughly `[-42, 135]`. The
+			// exponents are mapped into `[-64, 160)` to cover this domain and both of its ends.
+			let exponent =
+				|n: i128| FixedI256::from_inner((n.rem_euclid(224 * UNIT) - 64 * UNIT).into());
+			let (exp_x, exp_y) = (exponent(data.0), exponent(data.1));
+
+			// `exp` is monotonic.
+			let (low, high) = (exp_x.min(exp_y), exp_x.max(exp_y));
+			if let (Some(exp_low), Some(exp_high)) = (low.checked_exp(), high.checked_exp()) {
+				assert!(exp_low <= exp_high, "e^{:?} > e^{:?}", low, high);
+			}
+
+			// `ln` is monotonic.
+			let (low, high) = (x.min(y), x.max(y));
+			if let (Some(ln_low), Some(ln_high)) = (low.checked_ln(), high.checked_ln()) {
+				assert!(ln_low <= ln_high, "ln({:?}) > ln({:?})", low, high);
+			}
+
+			// `ln(e^x) = x`, as long as `e^x` is large enough not to lose precision.
+			if !exp_x.is_negative() {
+				if let Some(e) = exp_x.checked_exp() {
+					let roundtrip = e.checked_ln().unwrap();
+					assert!(
+						distance(roundtrip, exp_x) <= I256::from(2),
+						"ln(e^{:?}) = {:?}",
+						exp_x,
+						roundtrip
+					);
+				}
+			}
+
+			// `x^1 = x` and `x^0 = 1`.
+			if x.is_positive() {
+				let one = FixedI256::one();
+				let x_pow_one = x.checked_pow_frac(one).unwrap();
+				let tolerance =
+					x.into_inner() / I256::from(100_000_000_000_000_000_000_000_000_000u128);
+				assert!(
+					distance(x_pow_one, x) <= tolerance + I256::one(),
+					"{:?}^1 = {:?}",
+					x,
+					x_pow_one
+				);
+				assert_eq!(x.checked_pow_frac(FixedI256::zero()), Some(one));
+			}
+
+			// `x^(a + b) = x^a * x^b`, for integer exponents. The bases are kept small enough for
+			// the powers to be representable.
+			//
+			// `checked_rpow(n)` is off by at most `n * (1 + |x^n|)` units of accuracy. Therefore
+			// `x^a * x^b` is off by at most `(a + b) * (1 + |x^(a + b)|)` units, plus one unit for
+			// rounding the product, and so it differs from `x^(a + b)` by at most
+			// `2 * (a + b) * (1 + |x^(a + b)|) + 1` units.
+			let base = exp_x;
+			let (a, b) = (u32::from(data.2 % 16), u32::from(data.2 / 16));
+			if let (Some(x_a), Some(x_b), Some(x_a_b)) =
+				(base.checked_rpow(a), base.checked_rpow(b), base.checked_rpow(a + b))
+			{
+				if let Some(product) = x_a.checked_mul(&x_b) {
+					// `1 + |x^(a + b)|`, rounded up to whole units.
+					let magnitude =
+						x_a_b.saturating_abs().into_inner() / FixedI256::DIV + I256::from(2);
+					let tolerance = I256::from(2 * (a + b)) * magnitude + I256::one();
+					assert!(distance(product, x_a_b) <= tolerance, "{:?}^({} + {})", base, a, b);
+				}
+			}
+		});
+	}
+}
+
+/// Distance between `a` and `b`, in units of accuracy.
+fn distance(a: FixedI256, b: FixedI256) -> I256 {
+	a.saturating_sub(b).saturating_abs().into_inner()
+}
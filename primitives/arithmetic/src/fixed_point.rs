@@ -324,9 +324,9 @@ pub trait FixedPointNumber:
 }
 
 /// Data type used as intermediate storage in some computations to avoid overflow.
-struct I129 {
-	value: u128,
-	negative: bool,
+pub(crate) struct I129 {
+	pub(crate) value: u128,
+	pub(crate) negative: bool,
 }
 
 impl<N: FixedPointOperand> From<N> for I129 {
@@ -344,7 +344,7 @@ impl<N: FixedPointOperand> From<N> for I129 {
 }
 
 /// Transforms an `I129` to `N` if it is possible.
-fn from_i129<N: FixedPointOperand>(n: I129) -> Option<N> {
+pub(crate) fn from_i129<N: FixedPointOperand>(n: I129) -> Option<N> {
 	let max_plus_one: u128 = N::max_value().unique_saturated_into().saturating_add(1);
 	if n.negative && N::min_value() < N::zero() && n.value == max_plus_one {
 		Some(N::min_value())
@@ -356,7 +356,7 @@ fn from_i129<N: FixedPointOperand>(n: I129) -> Option<N> {
 }
 
 /// Returns `R::max` if the sign of `n * m` is positive, `R::min` otherwise.
-fn to_bound<N: FixedPointOperand, D: FixedPointOperand, R: Bounded>(n: N, m: D) -> R {
+pub(crate) fn to_bound<N: FixedPointOperand, D: FixedPointOperand, R: Bounded>(n: N, m: D) -> R {
 	if (n < N::zero()) != (m < D::zero()) {
 		R::min_value()
 	} else {
@@ -0,0 +1,1191 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decimal Fixed Point implementations backed by 256-bit integers.
+//!
+//! Besides the [`FixedPointNumber`] operations, these types provide deterministic
+//! implementations of the natural logarithm, the exponential function and powers with a
+//! fractional exponent. They are computed with integer arithmetic only, using intermediate
+//! results with an accuracy of `10^-36`, so they give the same result on every platform.
+
+use crate::{
+	fixed_point::{from_i129, to_bound, I129},
+	int256::{multiply_by_rational_with_rounding, sqrt_of_product, I256, U256},
+	traits::{Bounded, CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, One, Saturating, Zero},
+	FixedI128, FixedPointNumber, FixedPointOperand, FixedU128, PerThing, Rounding, SignedRounding,
+};
+use codec::{Decode, Encode};
+use sp_std::{ops, prelude::*};
+
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(all(not(feature = "std"), feature = "serde"))]
+use sp_std::alloc::string::{String, ToString};
+
+/// Accuracy of the 256-bit fixed point numbers, `10^18`.
+const DIV: U256 = U256::from_u128(1_000_000_000_000_000_000);
+
+/// Accuracy of the intermediate results of the transcendental functions, `10^36`.
+const WIDE_DIV: U256 = U256::from_u128(1_000_000_000_000_000_000_000_000_000_000_000_000);
+
+/// `ln(2)` with `WIDE_DIV` accuracy.
+const LN_2: U256 = U256::from_u128(693_147_180_559_945_309_417_232_121_458_176_568);
+
+/// Data type used as intermediate storage in some computations, the 256-bit counterpart of
+/// `I129`.
+#[derive(Clone, Copy)]
+struct I257 {
+	value: U256,
+	negative: bool,
+}
+
+impl I257 {
+	fn from_operand<N: FixedPointOperand>(n: N) -> Self {
+		let n: I129 = n.into();
+		I257 { value: n.value.into(), negative: n.negative }
+	}
+
+	fn into_operand<N: FixedPointOperand>(self) -> Option<N> {
+		let value: u128 = self.value.try_into().ok()?;
+		from_i129(I129 { value, negative: self.negative })
+	}
+}
+
+/// The integer types used as inner value of the 256-bit fixed point numbers.
+trait Inner256: Copy {
+	fn into_i257(self) -> I257;
+
+	fn from_i257(n: I257) -> Option<Self>;
+}
+
+impl Inner256 for U256 {
+	fn into_i257(self) -> I257 {
+		I257 { value: self, negative: false }
+	}
+
+	fn from_i257(n: I257) -> Option<Self> {
+		if n.negative && !n.value.is_zero() {
+			None
+		} else {
+			Some(n.value)
+		}
+	}
+}
+
+impl Inner256 for I256 {
+	fn into_i257(self) -> I257 {
+		I257 { value: self.unsigned_abs(), negative: self.is_negative() }
+	}
+
+	fn from_i257(n: I257) -> Option<Self> {
+		I256::from_sign_and_magnitude(n.negative, n.value)
+	}
+}
+
+/// Returns `a * b / c` rounded down, or `None` on overflow.
+fn mul_div(a: U256, b: U256, c: U256) -> Option<U256> {
+	multiply_by_rational_with_rounding(a, b, c, Rounding::Down)
+}
+
+/// Returns `ln(v / DIV)` with `WIDE_DIV` accuracy, or `None` if `v` is zero.
+///
+/// The absolute error of the result is below `10^-33`.
+fn ln(v: U256) -> Option<I257> {
+	if v.is_zero() {
+		return None
+	}
+
+	// Write `v / DIV = m * 2^k` with `1 <= m < 2`, `m` having `WIDE_DIV` accuracy.
+	let (m, k, k_negative) = if v >= DIV {
+		let mut k = v.bits() - DIV.bits();
+		if v >> k < DIV {
+			k -= 1;
+		}
+		(mul_div(v, DIV, U256::one() << k)?, k, false)
+	} else {
+		let mut k = DIV.bits() - v.bits();
+		if v << k < DIV {
+			k += 1;
+		}
+		((v << k).checked_mul(DIV)?, k, true)
+	};
+
+	// `ln(m) = 2 * atanh(z) = 2 * (z + z^3 / 3 + z^5 / 5 + ...)` with `z = (m - 1) / (m + 1)`.
+	// Since `0 <= z < 1/3`, every term is at least 9 times smaller than the previous one.
+	let z = mul_div(m - WIDE_DIV, WIDE_DIV, m + WIDE_DIV)?;
+	let z_squared = mul_div(z, z, WIDE_DIV)?;
+	let mut power = z;
+	let mut sum = z;
+	let mut n = 1u32;
+	loop {
+		power = mul_div(power, z_squared, WIDE_DIV)?;
+		if power.is_zero() {
+			break
+		}
+		sum += power / (2 * n + 1);
+		n += 1;
+	}
+	let ln_m = sum * 2;
+
+	// `ln(v / DIV) = k * ln(2) + ln(m)`.
+	let k_ln_2 = LN_2 * k;
+	Some(match (k_negative, k_ln_2 > ln_m) {
+		(false, _) => I257 { value: k_ln_2 + ln_m, negative: false },
+		(true, true) => I257 { value: k_ln_2 - ln_m, negative: true },
+		(true, false) => I257 { value: ln_m - k_ln_2, negative: false },
+	})
+}
+
+/// Returns `e^x` with `DIV` accuracy, rounded to the nearest value, where `x` has `WIDE_DIV`
+/// accuracy. Returns `None` if the result does not fit in a `U256`.
+///
+/// Before the final rounding, the relative error of the result is below `10^-33`.
+fn exp(x: I257) -> Option<U256> {
+	// `e^200` does not fit and `e^-200` rounds to zero. This also keeps `k` small.
+	if x.value > WIDE_DIV * 200 {
+		return if x.negative { Some(U256::zero()) } else { None }
+	}
+
+	// Write `x = k * ln(2) + r` with `|r| <= ln(2) / 2`, so `e^x = 2^k * e^r`.
+	let k =
+		multiply_by_rational_with_rounding(x.value, U256::one(), LN_2, Rounding::NearestPrefDown)?;
+	let k_ln_2 = LN_2 * k;
+	let r = if x.value >= k_ln_2 {
+		I257 { value: x.value - k_ln_2, negative: x.negative }
+	} else {
+		I257 { value: k_ln_2 - x.value, negative: !x.negative }
+	};
+
+	// `e^|r| = 1 + |r| + |r|^2 / 2! + |r|^3 / 3! + ...`, every term is at least 2 times smaller
+	// than the previous one.
+	let mut term = WIDE_DIV;
+	let mut sum = WIDE_DIV;
+	let mut n = 1u32;
+	loop {
+		term = mul_div(term, r.value, WIDE_DIV * n)?;
+		if term.is_zero() {
+			break
+		}
+		sum += term;
+		n += 1;
+	}
+	let exp_r = if r.negative {
+		multiply_by_rational_with_rounding(WIDE_DIV, WIDE_DIV, sum, Rounding::NearestPrefDown)?
+	} else {
+		sum
+	};
+
+	let k = k.low_u32();
+	if x.negative {
+		// `e^r / 2^k` is below `10^-57` past this point.
+		if k > 190 {
+			return Some(U256::zero())
+		}
+		multiply_by_rational_with_rounding(exp_r, U256::one(), DIV << k, Rounding::NearestPrefDown)
+	} else {
+		if k > 255 {
+			return None
+		}
+		multiply_by_rational_with_rounding(exp_r, U256::one() << k, DIV, Rounding::NearestPrefDown)
+	}
+}
+
+/// Returns `(base / DIV)^exponent` with `DIV` accuracy, where `base` is not zero and `exponent`
+/// has `DIV` accuracy. Returns `None` if the result does not fit in a `U256`.
+fn pow(base: U256, exponent: I257) -> Option<U256> {
+	let ln_base = ln(base)?;
+	let negative = ln_base.negative != exponent.negative;
+	match mul_div(ln_base.value, exponent.value, DIV) {
+		Some(value) => exp(I257 { value, negative }),
+		None if negative => Some(U256::zero()),
+		None => None,
+	}
+}
+
+macro_rules! implement_fixed_256 {
+	(
+		$name:ident,
+		$inner_type:ty,
+		$signed:tt,
+		$title:expr $(,)?
+	) => {
+		/// A fixed point number representation in the range.
+		#[doc = $title]
+		#[derive(
+			Encode,
+			Decode,
+			Default,
+			Copy,
+			Clone,
+			codec::MaxEncodedLen,
+			PartialEq,
+			Eq,
+			PartialOrd,
+			Ord,
+			scale_info::TypeInfo,
+		)]
+		pub struct $name($inner_type);
+
+		impl From<$inner_type> for $name {
+			fn from(int: $inner_type) -> Self {
+				$name::checked_from_integer(int).unwrap_or_else(|| to_bound(int, 0))
+			}
+		}
+
+		impl<N: FixedPointOperand, D: FixedPointOperand> From<(N, D)> for $name {
+			fn from(r: (N, D)) -> Self {
+				$name::saturating_from_rational(r.0, r.1)
+			}
+		}
+
+		impl FixedPointNumber for $name {
+			type Inner = $inner_type;
+
+			const DIV: Self::Inner = <$inner_type>::from_u128(1_000_000_000_000_000_000);
+			const SIGNED: bool = $signed;
+
+			fn from_inner(inner: Self::Inner) -> Self {
+				Self(inner)
+			}
+
+			fn into_inner(self) -> Self::Inner {
+				self.0
+			}
+
+			// The default implementations below go through 128-bit intermediate values, which
+			// would saturate long before the bounds of this type.
+
+			fn saturating_from_integer<N: FixedPointOperand>(int: N) -> Self {
+				let n = I257::from_operand(int);
+				n.value
+					.checked_mul(DIV)
+					.and_then(|value| <$inner_type>::from_i257(I257 { value, ..n }))
+					.map(Self)
+					.unwrap_or_else(|| to_bound(int, 0))
+			}
+
+			fn checked_from_rational<N: FixedPointOperand, D: FixedPointOperand>(
+				n: N,
+				d: D,
+			) -> Option<Self> {
+				if d == D::zero() {
+					return None
+				}
+
+				let n = I257::from_operand(n);
+				let d = I257::from_operand(d);
+				let negative = n.negative != d.negative;
+
+				multiply_by_rational_with_rounding(
+					n.value,
+					DIV,
+					d.value,
+					Rounding::from_signed(SignedRounding::Minor, negative),
+				)
+				.and_then(|value| <$inner_type>::from_i257(I257 { value, negative }))
+				.map(Self)
+			}
+
+			fn checked_mul_int<N: FixedPointOperand>(self, n: N) -> Option<N> {
+				let lhs = self.0.into_i257();
+				let rhs = I257::from_operand(n);
+				let negative = lhs.negative != rhs.negative;
+
+				multiply_by_rational_with_rounding(
+					lhs.value,
+					rhs.value,
+					DIV,
+					Rounding::from_signed(SignedRounding::Minor, negative),
+				)
+				.and_then(|value| I257 { value, negative }.into_operand())
+			}
+
+			fn checked_div_int<N: FixedPointOperand>(self, d: N) -> Option<N> {
+				let lhs = self.0.into_i257();
+				let rhs = I257::from_operand(d);
+				let negative = lhs.negative != rhs.negative;
+
+				lhs.value
+					.checked_div(rhs.value)
+					.and_then(|n| n.checked_div(DIV))
+					.and_then(|value| I257 { value, negative }.into_operand())
+			}
+		}
+
+		impl $name {
+			/// Create a new instance from the given `inner` value.
+			///
+			/// `const` version of `FixedPointNumber::from_inner`.
+			pub const fn from_inner(inner: $inner_type) -> Self {
+				Self(inner)
+			}
+
+			/// Return the instance's inner value.
+			///
+			/// `const` version of `FixedPointNumber::into_inner`.
+			pub const fn into_inner(self) -> $inner_type {
+				self.0
+			}
+
+			/// Creates self from a `u32`.
+			pub const fn from_u32(n: u32) -> Self {
+				Self(<$inner_type>::from_u128(n as u128 * 1_000_000_000_000_000_000))
+			}
+
+			/// Convert from a `float` value.
+			///
+			/// Saturates to `Self::max` or `Self::min` if `x` is out of range.
+			#[cfg(any(feature = "std", test))]
+			pub fn from_float(x: f64) -> Self {
+				let negative = x < 0.0;
+				let mut rest = (x.abs() * 1e18).trunc();
+				if rest >= 2f64.powi(256) {
+					return if negative { Self::min_value() } else { Self::max_value() }
+				}
+				let mut words = [0u64; 4];
+				for (index, word) in words.iter_mut().enumerate().rev() {
+					let unit = 2f64.powi(64 * index as i32);
+					let value = (rest / unit).trunc();
+					*word = value as u64;
+					rest -= value * unit;
+				}
+				<$inner_type>::from_i257(I257 { value: U256(words), negative })
+					.map(Self)
+					.unwrap_or_else(|| if negative { Self::min_value() } else { Self::max_value() })
+			}
+
+			/// Convert into a `float` value.
+			#[cfg(any(feature = "std", test))]
+			pub fn to_float(self) -> f64 {
+				let n = self.0.into_i257();
+				let U256(ref words) = n.value;
+				let value =
+					words.iter().rev().fold(0f64, |acc, word| acc * 2f64.powi(64) + *word as f64);
+				let value = value / 1e18;
+				if n.negative {
+					-value
+				} else {
+					value
+				}
+			}
+
+			/// Attempt to convert into a `PerThing`. This will succeed iff `self` is at least zero
+			/// and at most one. If it is out of bounds, it will result in an error returning the
+			/// clamped value.
+			pub fn try_into_perthing<P: PerThing>(self) -> Result<P, P> {
+				if self < Self::zero() {
+					Err(P::zero())
+				} else if self > Self::one() {
+					Err(P::one())
+				} else {
+					Ok(self.into_clamped_perthing())
+				}
+			}
+
+			/// Attempt to convert into a `PerThing`. This will always succeed resulting in a
+			/// clamped value if `self` is less than zero or greater than one.
+			pub fn into_clamped_perthing<P: PerThing>(self) -> P {
+				if self < Self::zero() {
+					P::zero()
+				} else if self > Self::one() {
+					P::one()
+				} else {
+					P::from_rational(self.0.into_i257().value.low_u128(), DIV.low_u128())
+				}
+			}
+
+			/// Take the square root of a positive value.
+			///
+			/// Panics if `self` is negative.
+			pub fn sqrt(self) -> Self {
+				match self.try_sqrt() {
+					Some(v) => v,
+					None => panic!("sqrt of negative input"),
+				}
+			}
+
+			/// Compute the square root, rounded down. If `self` is negative, then `None` is
+			/// returned.
+			pub fn try_sqrt(self) -> Option<Self> {
+				let n = self.0.into_i257();
+				if n.negative && !n.value.is_zero() {
+					return None
+				}
+				// `sqrt(n / D) = sqrt(n * D) / D`, and `n * D` is computed without overflow.
+				let value = sqrt_of_product(n.value, DIV);
+				<$inner_type>::from_i257(I257 { value, negative: false }).map(Self)
+			}
+
+			/// Calculate an approximation of a rational.
+			///
+			/// Result will be rounded to the nearest representable value, rounding down if it is
+			/// equidistant between two neighbours.
+			///
+			/// Panics if `b` is zero.
+			pub fn from_rational(a: u128, b: u128) -> Self {
+				Self::from_rational_with_rounding(a, b, Rounding::NearestPrefDown)
+			}
+
+			/// Calculate an approximation of a rational with custom rounding.
+			///
+			/// Panics if `b` is zero.
+			pub fn from_rational_with_rounding(a: u128, b: u128, rounding: Rounding) -> Self {
+				if b == 0 {
+					panic!("attempt to divide by zero in from_rational")
+				}
+				// `a / b` is at most `u128::MAX`, so `a * DIV / b` always fits.
+				multiply_by_rational_with_rounding(DIV, a.into(), b.into(), rounding)
+					.and_then(|value| <$inner_type>::from_i257(I257 { value, negative: false }))
+					.map(Self)
+					.expect("`u128::MAX * DIV` fits in the inner type; qed")
+			}
+
+			/// Multiply by another value with custom rounding, returning `None` in the case of an
+			/// error.
+			pub fn checked_mul_with_rounding(
+				self,
+				other: Self,
+				rounding: SignedRounding,
+			) -> Option<Self> {
+				let lhs = self.0.into_i257();
+				let rhs = other.0.into_i257();
+				let negative = lhs.negative != rhs.negative;
+
+				multiply_by_rational_with_rounding(
+					lhs.value,
+					rhs.value,
+					DIV,
+					Rounding::from_signed(rounding, negative),
+				)
+				.and_then(|value| <$inner_type>::from_i257(I257 { value, negative }))
+				.map(Self)
+			}
+
+			/// Divide by another value with custom rounding, returning `None` in the case of an
+			/// error.
+			pub fn checked_rounding_div(
+				self,
+				other: Self,
+				rounding: SignedRounding,
+			) -> Option<Self> {
+				let lhs = self.0.into_i257();
+				let rhs = other.0.into_i257();
+				let negative = lhs.negative != rhs.negative;
+
+				multiply_by_rational_with_rounding(
+					lhs.value,
+					DIV,
+					rhs.value,
+					Rounding::from_signed(rounding, negative),
+				)
+				.and_then(|value| <$inner_type>::from_i257(I257 { value, negative }))
+				.map(Self)
+			}
+
+			/// Natural logarithm of `self`.
+			///
+			/// The result is rounded to the nearest representable value and is within one unit of
+			/// accuracy (`10^-18`) of the exact logarithm.
+			///
+			/// Returns `None` if `self` is zero or negative, or if the result is not representable
+			/// (i.e. it is negative and the type is unsigned).
+			pub fn checked_ln(self) -> Option<Self> {
+				let n = self.0.into_i257();
+				if n.negative {
+					return None
+				}
+				let ln = ln(n.value)?;
+				let value = multiply_by_rational_with_rounding(
+					ln.value,
+					U256::one(),
+					DIV,
+					Rounding::NearestPrefDown,
+				)?;
+				<$inner_type>::from_i257(I257 { value, ..ln }).map(Self)
+			}
+
+			/// Exponential function, `e^self`.
+			///
+			/// The result is rounded to the nearest representable value. Its error is at most one
+			/// unit of accuracy (`10^-18`) plus `10^-30` times the exact result.
+			///
+			/// Returns `None` if the result is not representable.
+			pub fn checked_exp(self) -> Option<Self> {
+				let n = self.0.into_i257();
+				let value = match n.value.checked_mul(DIV) {
+					Some(value) => exp(I257 { value, ..n })?,
+					None if n.negative => U256::zero(),
+					None => return None,
+				};
+				<$inner_type>::from_i257(I257 { value, negative: false }).map(Self)
+			}
+
+			/// Raise `self` to a fractional power, `self^exponent`.
+			///
+			/// This is computed as `e^(exponent * ln(self))`. The result is rounded to the nearest
+			/// representable value. Its error is at most one unit of accuracy (`10^-18`) plus
+			/// `(1 + |exponent|) * 10^-30` times the exact result.
+			///
+			/// Returns `None` if `self` is negative, if `self` is zero and `exponent` is negative,
+			/// or if the result is not representable. Use `checked_rpow` for integer exponents,
+			/// which also supports negative bases.
+			pub fn checked_pow_frac(self, exponent: Self) -> Option<Self> {
+				let base = self.0.into_i257();
+				if base.negative && !base.value.is_zero() {
+					return None
+				}
+				if base.value.is_zero() {
+					return match exponent.0.into_i257() {
+						e if e.value.is_zero() => Some(Self::one()),
+						e if e.negative => None,
+						_ => Some(Self::zero()),
+					}
+				}
+				let value = pow(base.value, exponent.0.into_i257())?;
+				<$inner_type>::from_i257(I257 { value, negative: false }).map(Self)
+			}
+
+			/// Raise `self` to an integer power, `self^exp`, using exponentiation by squaring.
+			///
+			/// Every intermediate product is rounded to the nearest representable value, rounding
+			/// down if it is equidistant between two neighbours. Therefore the error of the result
+			/// is at most `exp * (1 + |result|)` units of accuracy (`10^-18`).
+			///
+			/// Returns `None` if the result is not representable.
+			pub fn checked_rpow(self, exp: u32) -> Option<Self> {
+				let mut result = Self::one();
+				let mut base = self;
+				let mut exp = exp;
+				while exp > 0 {
+					if exp & 1 == 1 {
+						result = result
+							.checked_mul_with_rounding(base, SignedRounding::NearestPrefLow)?;
+					}
+					exp >>= 1;
+					if exp > 0 {
+						base =
+							base.checked_mul_with_rounding(base, SignedRounding::NearestPrefLow)?;
+					}
+				}
+				Some(result)
+			}
+		}
+
+		impl Saturating for $name {
+			fn saturating_add(self, rhs: Self) -> Self {
+				Self(self.0.saturating_add(rhs.0))
+			}
+
+			fn saturating_sub(self, rhs: Self) -> Self {
+				Self(self.0.saturating_sub(rhs.0))
+			}
+
+			fn saturating_mul(self, rhs: Self) -> Self {
+				self.checked_mul(&rhs).unwrap_or_else(|| to_bound(self.0, rhs.0))
+			}
+
+			fn saturating_pow(self, exp: usize) -> Self {
+				if exp == 0 {
+					return Self::saturating_from_integer(1)
+				}
+
+				let exp = exp as u32;
+				let msb_pos = 32 - exp.leading_zeros();
+
+				let mut result = Self::saturating_from_integer(1);
+				let mut pow_val = self;
+				for i in 0..msb_pos {
+					if ((1 << i) & exp) > 0 {
+						result = result.saturating_mul(pow_val);
+					}
+					pow_val = pow_val.saturating_mul(pow_val);
+				}
+				result
+			}
+		}
+
+		impl ops::Neg for $name {
+			type Output = Self;
+
+			fn neg(self) -> Self::Output {
+				Self(<Self as FixedPointNumber>::Inner::zero() - self.0)
+			}
+		}
+
+		impl ops::Add for $name {
+			type Output = Self;
+
+			fn add(self, rhs: Self) -> Self::Output {
+				Self(self.0 + rhs.0)
+			}
+		}
+
+		impl ops::Sub for $name {
+			type Output = Self;
+
+			fn sub(self, rhs: Self) -> Self::Output {
+				Self(self.0 - rhs.0)
+			}
+		}
+
+		impl ops::Mul for $name {
+			type Output = Self;
+
+			fn mul(self, rhs: Self) -> Self::Output {
+				self.checked_mul(&rhs)
+					.unwrap_or_else(|| panic!("attempt to multiply with overflow"))
+			}
+		}
+
+		impl ops::Div for $name {
+			type Output = Self;
+
+			fn div(self, rhs: Self) -> Self::Output {
+				if rhs.0.is_zero() {
+					panic!("attempt to divide by zero")
+				}
+				self.checked_div(&rhs)
+					.unwrap_or_else(|| panic!("attempt to divide with overflow"))
+			}
+		}
+
+		impl CheckedSub for $name {
+			fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+				self.0.checked_sub(rhs.0).map(Self)
+			}
+		}
+
+		impl CheckedAdd for $name {
+			fn checked_add(&self, rhs: &Self) -> Option<Self> {
+				self.0.checked_add(rhs.0).map(Self)
+			}
+		}
+
+		impl CheckedDiv for $name {
+			fn checked_div(&self, other: &Self) -> Option<Self> {
+				if other.0.is_zero() {
+					return None
+				}
+				self.checked_rounding_div(*other, SignedRounding::Minor)
+			}
+		}
+
+		impl CheckedMul for $name {
+			fn checked_mul(&self, other: &Self) -> Option<Self> {
+				self.checked_mul_with_rounding(*other, SignedRounding::Minor)
+			}
+		}
+
+		impl Bounded for $name {
+			fn min_value() -> Self {
+				Self(<$inner_type as Bounded>::min_value())
+			}
+
+			fn max_value() -> Self {
+				Self(<$inner_type as Bounded>::max_value())
+			}
+		}
+
+		impl Zero for $name {
+			fn zero() -> Self {
+				Self::from_inner(<Self as FixedPointNumber>::Inner::zero())
+			}
+
+			fn is_zero(&self) -> bool {
+				self.into_inner() == <Self as FixedPointNumber>::Inner::zero()
+			}
+		}
+
+		impl One for $name {
+			fn one() -> Self {
+				Self::from_inner(Self::DIV)
+			}
+		}
+
+		impl sp_std::fmt::Debug for $name {
+			#[cfg(feature = "std")]
+			fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+				let n = self.0.into_i257();
+				let (integral, fractional) = n.value.div_mod(DIV);
+				let signum = if n.negative { "-" } else { "" };
+				let fractional = format!("{:0>18}", fractional.to_string());
+				write!(f, "{}({}{}.{})", stringify!($name), signum, integral, fractional)
+			}
+
+			#[cfg(not(feature = "std"))]
+			fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+				Ok(())
+			}
+		}
+
+		impl<P: PerThing> From<P> for $name
+		where
+			P::Inner: FixedPointOperand,
+		{
+			fn from(p: P) -> Self {
+				let accuracy = P::ACCURACY;
+				let value = p.deconstruct();
+				$name::saturating_from_rational(value, accuracy)
+			}
+		}
+
+		impl sp_std::fmt::Display for $name {
+			fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+				write!(f, "{}", self.0)
+			}
+		}
+
+		impl sp_std::str::FromStr for $name {
+			type Err = &'static str;
+
+			fn from_str(s: &str) -> Result<Self, Self::Err> {
+				let inner = <$inner_type>::from_dec_str(s)
+					.map_err(|_| "invalid string input for fixed point number")?;
+				Ok(Self::from_inner(inner))
+			}
+		}
+
+		// Manual impl `Serialize` as serde_json does not support 256-bit integers.
+		#[cfg(feature = "serde")]
+		impl Serialize for $name {
+			fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+			where
+				S: Serializer,
+			{
+				serializer.serialize_str(&self.to_string())
+			}
+		}
+
+		// Manual impl `Deserialize` as serde_json does not support 256-bit integers.
+		#[cfg(feature = "serde")]
+		impl<'de> Deserialize<'de> for $name {
+			fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+			where
+				D: Deserializer<'de>,
+			{
+				use sp_std::str::FromStr;
+				let s = String::deserialize(deserializer)?;
+				$name::from_str(&s).map_err(de::Error::custom)
+			}
+		}
+	};
+}
+
+implement_fixed_256!(
+	FixedI256,
+	I256,
+	true,
+	"_Fixed Point 256 bits signed, range = \
+		[-57896044618658097711785492504343953926634992332820282019728.792003956564819968, \
+		57896044618658097711785492504343953926634992332820282019728.792003956564819967]_",
+);
+
+implement_fixed_256!(
+	FixedU256,
+	U256,
+	false,
+	"_Fixed Point 256 bits unsigned, range = \
+		[0.000000000000000000, \
+		115792089237316195423570985008687907853269984665640564039457.584007913129639935]_",
+);
+
+impl From<FixedU128> for FixedU256 {
+	fn from(n: FixedU128) -> Self {
+		Self(n.into_inner().into())
+	}
+}
+
+impl TryFrom<FixedU256> for FixedU128 {
+	type Error = ();
+
+	fn try_from(n: FixedU256) -> Result<Self, ()> {
+		n.0.try_into().map(FixedU128::from_inner).map_err(|_| ())
+	}
+}
+
+impl From<FixedI128> for FixedI256 {
+	fn from(n: FixedI128) -> Self {
+		Self(n.into_inner().into())
+	}
+}
+
+impl TryFrom<FixedI256> for FixedI128 {
+	type Error = ();
+
+	fn try_from(n: FixedI256) -> Result<Self, ()> {
+		n.0.try_into().map(FixedI128::from_inner)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Perbill;
+
+	fn u(s: &str) -> FixedU256 {
+		FixedU256::from_inner(U256::from_dec_str(s).unwrap())
+	}
+
+	fn i(s: &str) -> FixedI256 {
+		FixedI256::from_inner(s.parse().unwrap())
+	}
+
+	fn int(n: i128) -> FixedI256 {
+		FixedI256::saturating_from_integer(n)
+	}
+
+	#[test]
+	fn bounds_are_correct() {
+		assert_eq!(FixedU256::min_value(), FixedU256::zero());
+		assert_eq!(FixedU256::max_value().into_inner(), U256::MAX);
+		assert_eq!(FixedI256::min_value().into_inner(), I256::MIN);
+		assert_eq!(FixedI256::max_value().into_inner(), I256::MAX);
+		assert_eq!(FixedU256::accuracy(), U256::from(FixedU128::accuracy()));
+		assert_eq!(FixedI256::accuracy(), I256::from(FixedI128::accuracy()));
+	}
+
+	#[test]
+	fn from_integer_works_beyond_128_bits() {
+		let n = FixedU256::saturating_from_integer(u128::MAX);
+		assert_eq!(n.into_inner(), U256::from(u128::MAX) * DIV);
+		assert_eq!(n.saturating_mul_int(1u8), u8::MAX);
+		assert_eq!(n.checked_mul_int(1u128), Some(u128::MAX));
+		assert_eq!(n.checked_mul_int(2u128), None);
+		assert_eq!(n.checked_div_int(u128::MAX), Some(1u128));
+
+		let n = FixedI256::saturating_from_integer(i128::MIN);
+		assert_eq!(n.checked_mul_int(1i128), Some(i128::MIN));
+		assert_eq!(n.checked_mul_int(-1i128), None);
+		assert_eq!(n.saturating_mul_int(-1i128), i128::MAX);
+		assert_eq!(FixedI256::saturating_from_integer(-5).saturating_mul_int(u32::MAX), 0);
+
+		assert_eq!(FixedU256::saturating_from_integer(-1), FixedU256::zero());
+		assert_eq!(FixedU256::from(U256::MAX), FixedU256::max_value());
+		assert_eq!(FixedU256::checked_from_integer(U256::MAX), None);
+		assert_eq!(FixedI256::from(I256::MIN), FixedI256::min_value());
+		assert_eq!(FixedU256::from_u32(u32::MAX), FixedU256::saturating_from_integer(u32::MAX));
+	}
+
+	#[test]
+	fn matches_128_bit_implementation() {
+		let values =
+			[i128::MIN / 7, -1_000_000_007_000, -5, -1, 0, 1, 3, 7_000_000_000, i128::MAX / 11];
+		for a in values {
+			for b in values {
+				let (x, y) = (FixedI128::from_inner(a), FixedI128::from_inner(b));
+				let (x_wide, y_wide): (FixedI256, FixedI256) = (x.into(), y.into());
+
+				let wide = |n: Option<FixedI128>| n.map(FixedI256::from);
+				assert_eq!(
+					x_wide.checked_add(&y_wide).and_then(|n| n.try_into().ok()),
+					x.checked_add(&y)
+				);
+				assert_eq!(
+					x_wide.checked_sub(&y_wide).and_then(|n| n.try_into().ok()),
+					x.checked_sub(&y)
+				);
+				if let Some(product) = x.checked_mul(&y) {
+					assert_eq!(wide(Some(product)), x_wide.checked_mul(&y_wide));
+				}
+				if let Some(quotient) = x.checked_div(&y) {
+					assert_eq!(wide(Some(quotient)), x_wide.checked_div(&y_wide));
+				}
+				assert_eq!(
+					FixedI256::checked_from_rational(a, b),
+					wide(FixedI128::checked_from_rational(a, b)).or(if b == 0 {
+						None
+					} else {
+						FixedI256::checked_from_rational(a, b)
+					}),
+				);
+				assert_eq!(x_wide.checked_mul_int(b), x.checked_mul_int(b));
+				assert_eq!(x_wide.checked_div_int(b), x.checked_div_int(b));
+				assert_eq!(x_wide.cmp(&y_wide), x.cmp(&y));
+
+				let (x, y) = (
+					FixedU128::from_inner(a.unsigned_abs()),
+					FixedU128::from_inner(b.unsigned_abs()),
+				);
+				let (x_wide, y_wide): (FixedU256, FixedU256) = (x.into(), y.into());
+				if let Some(product) = x.checked_mul(&y) {
+					assert_eq!(Some(product.into()), x_wide.checked_mul(&y_wide));
+				}
+				if let Some(quotient) = x.checked_div(&y) {
+					assert_eq!(Some(quotient.into()), x_wide.checked_div(&y_wide));
+				}
+				assert_eq!(x_wide.saturating_sub(y_wide), x.saturating_sub(y).into());
+			}
+		}
+	}
+
+	#[test]
+	fn rounding_and_parts_work() {
+		let n = FixedI256::saturating_from_rational(-7, 2);
+		assert_eq!(n, i("-3500000000000000000"));
+		assert_eq!(n.trunc(), int(-3));
+		assert_eq!(n.frac(), i("500000000000000000"));
+		assert_eq!(n.floor(), int(-4));
+		assert_eq!(n.ceil(), int(-3));
+		assert_eq!(n.round(), int(-4));
+		assert_eq!(n.saturating_abs(), FixedI256::saturating_from_rational(7, 2));
+		assert_eq!(FixedI256::min_value().saturating_abs(), FixedI256::max_value());
+		assert_eq!(n.reciprocal(), Some(i("-285714285714285714")));
+		assert_eq!(FixedU256::from_rational(1, 3), u("333333333333333333"));
+		assert_eq!(
+			FixedU256::from_rational_with_rounding(2, 3, Rounding::Up),
+			u("666666666666666667")
+		);
+		assert_eq!(
+			FixedU256::from_rational(u128::MAX, 1),
+			FixedU256::saturating_from_integer(u128::MAX)
+		);
+	}
+
+	#[test]
+	fn saturating_operations_work() {
+		let max = FixedI256::max_value();
+		let min = FixedI256::min_value();
+		assert_eq!(max.saturating_add(int(1)), max);
+		assert_eq!(min.saturating_sub(int(1)), min);
+		assert_eq!(max.saturating_mul(int(2)), max);
+		assert_eq!(max.saturating_mul(int(-2)), min);
+		assert_eq!(int(-2).saturating_pow(3), int(-8));
+		assert_eq!(int(2).saturating_pow(200), max);
+		assert_eq!(int(-2).saturating_pow(201), min);
+		assert_eq!(FixedU256::zero().saturating_sub(FixedU256::one()), FixedU256::zero());
+		assert_eq!(-int(3), int(-3));
+	}
+
+	#[test]
+	#[should_panic(expected = "attempt to divide by zero")]
+	fn div_by_zero_panics() {
+		let _ = int(1) / int(0);
+	}
+
+	#[test]
+	fn perthing_conversion_works() {
+		assert_eq!(FixedU256::from(Perbill::from_percent(50)), FixedU256::from_rational(1, 2));
+		assert_eq!(
+			FixedI256::from_rational(1, 4).try_into_perthing(),
+			Ok(Perbill::from_percent(25))
+		);
+		assert_eq!(int(2).try_into_perthing(), Err(Perbill::one()));
+		assert_eq!(int(-2).into_clamped_perthing::<Perbill>(), Perbill::zero());
+	}
+
+	#[test]
+	fn sqrt_works() {
+		assert_eq!(FixedU256::saturating_from_integer(2).sqrt(), u("1414213562373095048"));
+		assert_eq!(
+			FixedU256::saturating_from_integer(16).sqrt(),
+			FixedU256::saturating_from_integer(4)
+		);
+		assert_eq!(
+			FixedU256::max_value()
+				.try_sqrt()
+				.map(|n| n.saturating_pow(2) <= FixedU256::max_value()),
+			Some(true)
+		);
+		assert_eq!(FixedI256::zero().try_sqrt(), Some(FixedI256::zero()));
+		assert_eq!(int(-4).try_sqrt(), None);
+	}
+
+	#[test]
+	fn string_and_codec_conversions_work() {
+		use codec::{Decode, Encode};
+
+		for n in
+			[FixedI256::min_value(), int(-3), FixedI256::zero(), int(12), FixedI256::max_value()]
+		{
+			assert_eq!(n.to_string().parse::<FixedI256>(), Ok(n));
+			assert_eq!(FixedI256::decode(&mut &n.encode()[..]), Ok(n));
+		}
+		assert_eq!(FixedU256::max_value().encode(), [0xff; 32].to_vec());
+		assert_eq!(
+			FixedU256::max_value().to_string().parse::<FixedU256>(),
+			Ok(FixedU256::max_value())
+		);
+		assert!("-1".parse::<FixedU256>().is_err());
+		assert_eq!(
+			format!("{:?}", -FixedI256::from_rational(3, 2)),
+			"FixedI256(-1.500000000000000000)"
+		);
+		assert_eq!(format!("{:?}", u("5")), "FixedU256(0.000000000000000005)");
+	}
+
+	#[test]
+	fn float_conversion_works() {
+		assert_eq!(FixedI256::from_float(-1.5), -FixedI256::from_rational(3, 2));
+		assert_eq!(FixedU256::from_float(1e30).to_float(), 1e30);
+		assert_eq!(FixedU256::from_float(-1.0), FixedU256::zero());
+		assert_eq!(FixedU256::from_float(1e70), FixedU256::max_value());
+		assert_eq!(int(-7).to_float(), -7.0);
+	}
+
+	#[test]
+	fn ln_works() {
+		assert_eq!(FixedU256::one().checked_ln(), Some(FixedU256::zero()));
+		assert_eq!(
+			FixedU256::saturating_from_integer(2).checked_ln(),
+			Some(u("693147180559945309"))
+		);
+		assert_eq!(
+			FixedU256::saturating_from_integer(3).checked_ln(),
+			Some(u("1098612288668109691"))
+		);
+		assert_eq!(
+			FixedU256::saturating_from_integer(10).checked_ln(),
+			Some(u("2302585092994045684"))
+		);
+		assert_eq!(FixedI256::from_rational(1, 2).checked_ln(), Some(i("-693147180559945309")));
+		// ln(10^-18) = -41.446531673892822312...
+		assert_eq!(i("1").checked_ln(), Some(i("-41446531673892822312")));
+		// ln(2^256 / 10^18) = 135.999146549453176898...
+		assert_eq!(FixedU256::max_value().checked_ln(), Some(u("135999146549453176898")));
+
+		assert_eq!(FixedU256::zero().checked_ln(), None);
+		assert_eq!(FixedU256::from_rational(1, 2).checked_ln(), None);
+		assert_eq!(int(-1).checked_ln(), None);
+	}
+
+	#[test]
+	fn exp_works() {
+		assert_eq!(FixedU256::zero().checked_exp(), Some(FixedU256::one()));
+		assert_eq!(FixedU256::one().checked_exp(), Some(u("2718281828459045235")));
+		assert_eq!(int(-1).checked_exp(), Some(i("367879441171442322")));
+		assert_eq!(int(10).checked_exp(), Some(i("22026465794806716516958")));
+		// e^-41 = 1.5628...e-18
+		assert_eq!(int(-41).checked_exp(), Some(i("2")));
+		assert_eq!(int(-50).checked_exp(), Some(FixedI256::zero()));
+		assert_eq!(FixedI256::min_value().checked_exp(), Some(FixedI256::zero()));
+
+		// e^135 = 42633899483147210448936866880765989356468745853255281087440.0117...
+		let e_135 = FixedU256::saturating_from_integer(135).checked_exp().unwrap();
+		let expected =
+			u("42633899483147210448936866880765989356468745853255281087440011736227864297277");
+		assert_eq_error_rate!(
+			e_135.into_inner(),
+			expected.into_inner(),
+			expected.into_inner() / 1_000_000_000_000_000_000_000_000_000_000u128
+		);
+		assert_eq!(FixedU256::saturating_from_integer(136).checked_exp(), None);
+		assert_eq!(
+			int(135).checked_exp().map(|n| n.into_inner()),
+			Some(e_135.into_inner().try_into().unwrap())
+		);
+		assert_eq!(FixedI256::from_rational(271, 2).checked_exp(), None);
+		assert_eq!(FixedU256::max_value().checked_exp(), None);
+	}
+
+	#[test]
+	fn exp_and_ln_are_inverse() {
+		for n in [
+			-40_000_000_000_000_000_000i128,
+			-1,
+			1,
+			999_999_999_999_999_999,
+			123_456_789_000_000_000_000,
+		] {
+			let x = FixedI256::from_inner(n.into());
+			let roundtrip = x.checked_exp().and_then(|e| e.checked_ln()).unwrap();
+			// The error of `ln` is amplified by `e^-x` when the exponential is small.
+			let error = if n < 0 { 1_000_000_000_000_000_000i128 } else { 1 };
+			assert_eq_error_rate!(roundtrip.into_inner(), x.into_inner(), I256::from(error));
+		}
+	}
+
+	#[test]
+	fn transcendental_functions_match_floats() {
+		for k in 1..200u32 {
+			let x = FixedI256::from_rational(k as u128 * 7919, 10_000) - int(60);
+			let f = x.to_float();
+			let close = |a: f64, b: f64| (a - b).abs() <= b.abs() * 1e-12 + 1e-18;
+			assert!(close(x.checked_exp().unwrap().to_float(), f.exp()));
+			if f > 0.0 {
+				assert!(close(x.checked_ln().unwrap().to_float(), f.ln()));
+				let y = x.checked_pow_frac(FixedI256::from_rational(3, 10)).unwrap();
+				assert!(close(y.to_float(), f.powf(0.3)));
+			}
+		}
+	}
+
+	#[test]
+	fn pow_frac_works() {
+		let two = FixedU256::saturating_from_integer(2);
+		assert_eq!(
+			two.checked_pow_frac(FixedU256::from_rational(1, 2)),
+			Some(u("1414213562373095049"))
+		);
+		assert_eq!(two.checked_pow_frac(FixedU256::zero()), Some(FixedU256::one()));
+		assert_eq!(two.checked_pow_frac(FixedU256::one()), Some(two));
+		assert_eq!(
+			FixedU256::one().checked_pow_frac(FixedU256::max_value()),
+			Some(FixedU256::one())
+		);
+		assert_eq!(int(2).checked_pow_frac(int(-1)), Some(FixedI256::from_rational(1, 2)));
+		assert_eq!(
+			int(4).checked_pow_frac(-FixedI256::from_rational(1, 2)),
+			Some(FixedI256::from_rational(1, 2))
+		);
+		assert_eq!(
+			two.checked_pow_frac(FixedU256::saturating_from_integer(64)),
+			Some(FixedU256::saturating_from_integer(u64::MAX) + FixedU256::one()),
+		);
+		assert_eq!(two.checked_pow_frac(FixedU256::saturating_from_integer(200)), None);
+		assert_eq!(int(2).checked_pow_frac(int(-200)), Some(FixedI256::zero()));
+		assert_eq!(FixedU256::max_value().checked_pow_frac(FixedU256::max_value()), None);
+		assert_eq!(
+			FixedI256::from_rational(1, 2).checked_pow_frac(FixedI256::max_value()),
+			Some(FixedI256::zero())
+		);
+
+		assert_eq!(FixedU256::zero().checked_pow_frac(two), Some(FixedU256::zero()));
+		assert_eq!(FixedI256::zero().checked_pow_frac(FixedI256::zero()), Some(FixedI256::one()));
+		assert_eq!(FixedI256::zero().checked_pow_frac(int(-1)), None);
+		assert_eq!(int(-8).checked_pow_frac(FixedI256::from_rational(1, 3)), None);
+	}
+
+	#[test]
+	fn rpow_works() {
+		let n = FixedU256::from_rational(11, 10);
+		assert_eq!(n.checked_rpow(10), Some(u("2593742460100000000")));
+		assert_eq!(n.checked_rpow(0), Some(FixedU256::one()));
+		assert_eq!(n.checked_rpow(1), Some(n));
+		assert_eq!(int(-2).checked_rpow(3), Some(int(-8)));
+		assert_eq!(int(-2).checked_rpow(4), Some(int(16)));
+		let power_of_two = |k: usize| (U256::one() << k) * DIV;
+		assert_eq!(int(2).checked_rpow(195), Some(i(&power_of_two(195).to_string())));
+		assert_eq!(int(2).checked_rpow(196), None);
+		let two = FixedU256::saturating_from_integer(2);
+		assert_eq!(two.checked_rpow(196), Some(FixedU256::from_inner(power_of_two(196))));
+		assert_eq!(two.checked_rpow(197), None);
+		// 0.5^60 = 8.67e-19, rounded to the nearest representable value.
+		assert_eq!(FixedU256::from_rational(1, 2).checked_rpow(60), Some(u("1")));
+		assert_eq!(FixedU256::from_rational(1, 2).checked_rpow(u32::MAX), Some(FixedU256::zero()));
+
+		// Integer powers agree with the fractional power implementation.
+		for exp in 0..20u32 {
+			let base = FixedI256::from_rational(3, 2);
+			assert_eq_error_rate!(
+				base.checked_rpow(exp).unwrap().into_inner(),
+				base.checked_pow_frac(int(exp.into())).unwrap().into_inner(),
+				I256::from(exp as u64 + 1),
+			);
+		}
+	}
+}
@@ -0,0 +1,673 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! 256-bit integer types, used as the inner representation of the 256-bit fixed point numbers.
+//!
+//! Some helpers in this module need a 512-bit integer for intermediate results, which is not
+//! exposed.
+
+use crate::{
+	traits::{Bounded, CheckedAdd, CheckedDiv, CheckedMul, CheckedNeg, CheckedSub, One, Zero},
+	Rounding,
+};
+use codec::{Decode, Encode, MaxEncodedLen};
+use scale_info::TypeInfo;
+use sp_std::{cmp::Ordering, fmt, ops, str::FromStr};
+use uint::{construct_uint, uint_full_mul_reg};
+
+construct_uint! {
+	/// 256-bit unsigned integer.
+	#[derive(Encode, Decode, MaxEncodedLen, TypeInfo)]
+	pub struct U256(4);
+}
+
+construct_uint! {
+	/// 512-bit unsigned integer, used to hold the product of two `U256`.
+	struct U512(8);
+}
+
+impl U256 {
+	/// Create a `U256` from a `u128`.
+	///
+	/// `const` version of `From<u128>`.
+	pub const fn from_u128(n: u128) -> Self {
+		U256([n as u64, (n >> 64) as u64, 0, 0])
+	}
+
+	/// Multiply two `U256` without overflow.
+	fn full_mul(self, other: U256) -> U512 {
+		U512(uint_full_mul_reg!(U256, 4, self, other))
+	}
+}
+
+impl From<U256> for U512 {
+	fn from(n: U256) -> U512 {
+		let U256(ref arr) = n;
+		let mut ret = [0; 8];
+		ret[..4].copy_from_slice(arr);
+		U512(ret)
+	}
+}
+
+impl TryFrom<U512> for U256 {
+	type Error = ();
+
+	fn try_from(n: U512) -> Result<U256, ()> {
+		let U512(ref arr) = n;
+		if arr[4..].iter().any(|word| *word != 0) {
+			return Err(())
+		}
+		let mut ret = [0; 4];
+		ret.copy_from_slice(&arr[..4]);
+		Ok(U256(ret))
+	}
+}
+
+impl Bounded for U256 {
+	fn min_value() -> Self {
+		U256::zero()
+	}
+
+	fn max_value() -> Self {
+		U256::MAX
+	}
+}
+
+impl Zero for U256 {
+	fn zero() -> Self {
+		U256::zero()
+	}
+
+	fn is_zero(&self) -> bool {
+		U256::is_zero(self)
+	}
+}
+
+impl One for U256 {
+	fn one() -> Self {
+		U256::one()
+	}
+}
+
+impl CheckedAdd for U256 {
+	fn checked_add(&self, rhs: &Self) -> Option<Self> {
+		U256::checked_add(*self, *rhs)
+	}
+}
+
+impl CheckedSub for U256 {
+	fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+		U256::checked_sub(*self, *rhs)
+	}
+}
+
+impl CheckedMul for U256 {
+	fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+		U256::checked_mul(*self, *rhs)
+	}
+}
+
+impl CheckedDiv for U256 {
+	fn checked_div(&self, rhs: &Self) -> Option<Self> {
+		U256::checked_div(*self, *rhs)
+	}
+}
+
+impl CheckedNeg for U256 {
+	fn checked_neg(&self) -> Option<Self> {
+		U256::checked_neg(*self)
+	}
+}
+
+impl num_traits::Saturating for U256 {
+	fn saturating_add(self, rhs: Self) -> Self {
+		U256::saturating_add(self, rhs)
+	}
+
+	fn saturating_sub(self, rhs: Self) -> Self {
+		U256::saturating_sub(self, rhs)
+	}
+}
+
+/// 256-bit signed integer, stored in two's complement.
+#[derive(Encode, Decode, MaxEncodedLen, TypeInfo, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct I256(U256);
+
+impl I256 {
+	/// The largest value that can be represented by this integer type, `2^255 - 1`.
+	pub const MAX: I256 = I256(U256([u64::MAX, u64::MAX, u64::MAX, i64::MAX as u64]));
+
+	/// The smallest value that can be represented by this integer type, `-2^255`.
+	pub const MIN: I256 = I256(U256([0, 0, 0, 1 << 63]));
+
+	/// Zero (additive identity) of this type.
+	pub const fn zero() -> Self {
+		I256(U256::zero())
+	}
+
+	/// One (multiplicative identity) of this type.
+	pub const fn one() -> Self {
+		I256(U256::one())
+	}
+
+	/// Create an `I256` from a `u128`.
+	///
+	/// `const` version of `From<u128>`.
+	pub const fn from_u128(n: u128) -> Self {
+		I256(U256::from_u128(n))
+	}
+
+	/// Create an `I256` from its sign and its absolute value.
+	///
+	/// Returns `None` if the value does not fit.
+	pub fn from_sign_and_magnitude(negative: bool, magnitude: U256) -> Option<Self> {
+		if negative {
+			match magnitude.cmp(&I256::MIN.0) {
+				Ordering::Greater => None,
+				Ordering::Equal => Some(I256::MIN),
+				Ordering::Less => Some(I256(magnitude).wrapping_neg()),
+			}
+		} else if magnitude > I256::MAX.0 {
+			None
+		} else {
+			Some(I256(magnitude))
+		}
+	}
+
+	/// Returns `true` if `self` is negative and `false` if it is zero or positive.
+	pub const fn is_negative(&self) -> bool {
+		self.0.bit(255)
+	}
+
+	/// Returns `true` if `self` is positive and `false` if it is zero or negative.
+	pub fn is_positive(&self) -> bool {
+		!self.is_negative() && !self.0.is_zero()
+	}
+
+	/// The absolute value of `self`, without any wrapping or panicking.
+	pub fn unsigned_abs(self) -> U256 {
+		if self.is_negative() {
+			self.wrapping_neg().0
+		} else {
+			self.0
+		}
+	}
+
+	/// Checked addition. Returns `None` if overflow occurred.
+	pub fn checked_add(self, rhs: Self) -> Option<Self> {
+		let result = I256(self.0.overflowing_add(rhs.0).0);
+		// Overflow happened iff both operands have the same sign and the result does not.
+		if self.is_negative() == rhs.is_negative() && result.is_negative() != self.is_negative() {
+			None
+		} else {
+			Some(result)
+		}
+	}
+
+	/// Checked subtraction. Returns `None` if overflow occurred.
+	pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+		let result = I256(self.0.overflowing_sub(rhs.0).0);
+		// Overflow happened iff the operands have different signs and the result does not have
+		// the sign of `self`.
+		if self.is_negative() != rhs.is_negative() && result.is_negative() != self.is_negative() {
+			None
+		} else {
+			Some(result)
+		}
+	}
+
+	/// Checked multiplication. Returns `None` if overflow occurred.
+	pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+		let magnitude = self.unsigned_abs().checked_mul(rhs.unsigned_abs())?;
+		I256::from_sign_and_magnitude(self.is_negative() != rhs.is_negative(), magnitude)
+	}
+
+	/// Checked division, rounding towards zero. Returns `None` if `rhs == 0` or the division
+	/// overflowed.
+	pub fn checked_div(self, rhs: Self) -> Option<Self> {
+		let magnitude = self.unsigned_abs().checked_div(rhs.unsigned_abs())?;
+		I256::from_sign_and_magnitude(self.is_negative() != rhs.is_negative(), magnitude)
+	}
+
+	/// Checked negation. Returns `None` if `self == I256::MIN`.
+	pub fn checked_neg(self) -> Option<Self> {
+		if self == I256::MIN {
+			None
+		} else {
+			Some(self.wrapping_neg())
+		}
+	}
+
+	/// Saturating addition, saturating at the numeric bounds instead of overflowing.
+	pub fn saturating_add(self, rhs: Self) -> Self {
+		self.checked_add(rhs)
+			.unwrap_or(if rhs.is_negative() { I256::MIN } else { I256::MAX })
+	}
+
+	/// Saturating subtraction, saturating at the numeric bounds instead of overflowing.
+	pub fn saturating_sub(self, rhs: Self) -> Self {
+		self.checked_sub(rhs)
+			.unwrap_or(if rhs.is_negative() { I256::MAX } else { I256::MIN })
+	}
+
+	/// Convert from a decimal string, with an optional leading sign.
+	pub fn from_dec_str(value: &str) -> Result<Self, &'static str> {
+		let (negative, digits) = match value.as_bytes().first() {
+			Some(b'-') => (true, &value[1..]),
+			Some(b'+') => (false, &value[1..]),
+			_ => (false, value),
+		};
+		if digits.is_empty() {
+			return Err("invalid decimal number")
+		}
+		let magnitude = U256::from_dec_str(digits).map_err(|_| "invalid decimal number")?;
+		I256::from_sign_and_magnitude(negative, magnitude).ok_or("integer overflow")
+	}
+
+	fn wrapping_neg(self) -> Self {
+		I256((!self.0).overflowing_add(U256::one()).0)
+	}
+}
+
+impl Ord for I256 {
+	fn cmp(&self, other: &Self) -> Ordering {
+		match (self.is_negative(), other.is_negative()) {
+			(true, false) => Ordering::Less,
+			(false, true) => Ordering::Greater,
+			// Two's complement preserves the order of values with the same sign.
+			_ => self.0.cmp(&other.0),
+		}
+	}
+}
+
+impl PartialOrd for I256 {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+macro_rules! impl_from_unsigned {
+	($($type:ty),*) => {
+		$(
+			impl From<$type> for I256 {
+				fn from(n: $type) -> Self {
+					I256::from_u128(n as u128)
+				}
+			}
+		)*
+	};
+}
+
+macro_rules! impl_from_signed {
+	($($type:ty),*) => {
+		$(
+			impl From<$type> for I256 {
+				fn from(n: $type) -> Self {
+					let magnitude = U256::from_u128((n as i128).unsigned_abs());
+					I256::from_sign_and_magnitude(n < 0, magnitude)
+						.expect("any 128 bits integer fits in 256 bits; qed")
+				}
+			}
+		)*
+	};
+}
+
+impl_from_unsigned!(u8, u16, u32, u64, u128);
+impl_from_signed!(i8, i16, i32, i64, i128);
+
+impl TryFrom<I256> for u128 {
+	type Error = ();
+
+	fn try_from(n: I256) -> Result<u128, ()> {
+		if n.is_negative() {
+			return Err(())
+		}
+		n.0.try_into().map_err(|_| ())
+	}
+}
+
+impl TryFrom<I256> for i128 {
+	type Error = ();
+
+	fn try_from(n: I256) -> Result<i128, ()> {
+		let magnitude: u128 = n.unsigned_abs().try_into().map_err(|_| ())?;
+		if n.is_negative() {
+			0i128.checked_sub_unsigned(magnitude).ok_or(())
+		} else {
+			magnitude.try_into().map_err(|_| ())
+		}
+	}
+}
+
+impl TryFrom<U256> for I256 {
+	type Error = ();
+
+	fn try_from(n: U256) -> Result<I256, ()> {
+		I256::from_sign_and_magnitude(false, n).ok_or(())
+	}
+}
+
+impl TryFrom<I256> for U256 {
+	type Error = ();
+
+	fn try_from(n: I256) -> Result<U256, ()> {
+		if n.is_negative() {
+			Err(())
+		} else {
+			Ok(n.0)
+		}
+	}
+}
+
+impl ops::Add for I256 {
+	type Output = Self;
+
+	fn add(self, rhs: Self) -> Self::Output {
+		self.checked_add(rhs).expect("attempt to add with overflow")
+	}
+}
+
+impl ops::Sub for I256 {
+	type Output = Self;
+
+	fn sub(self, rhs: Self) -> Self::Output {
+		self.checked_sub(rhs).expect("attempt to subtract with overflow")
+	}
+}
+
+impl ops::Mul for I256 {
+	type Output = Self;
+
+	fn mul(self, rhs: Self) -> Self::Output {
+		self.checked_mul(rhs).expect("attempt to multiply with overflow")
+	}
+}
+
+impl ops::Div for I256 {
+	type Output = Self;
+
+	fn div(self, rhs: Self) -> Self::Output {
+		if rhs.is_zero() {
+			panic!("attempt to divide by zero")
+		}
+		self.checked_div(rhs).expect("attempt to divide with overflow")
+	}
+}
+
+impl ops::Neg for I256 {
+	type Output = Self;
+
+	fn neg(self) -> Self::Output {
+		self.checked_neg().expect("attempt to negate with overflow")
+	}
+}
+
+impl Bounded for I256 {
+	fn min_value() -> Self {
+		I256::MIN
+	}
+
+	fn max_value() -> Self {
+		I256::MAX
+	}
+}
+
+impl Zero for I256 {
+	fn zero() -> Self {
+		I256::zero()
+	}
+
+	fn is_zero(&self) -> bool {
+		self.0.is_zero()
+	}
+}
+
+impl One for I256 {
+	fn one() -> Self {
+		I256::one()
+	}
+}
+
+impl CheckedAdd for I256 {
+	fn checked_add(&self, rhs: &Self) -> Option<Self> {
+		I256::checked_add(*self, *rhs)
+	}
+}
+
+impl CheckedSub for I256 {
+	fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+		I256::checked_sub(*self, *rhs)
+	}
+}
+
+impl CheckedMul for I256 {
+	fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+		I256::checked_mul(*self, *rhs)
+	}
+}
+
+impl CheckedDiv for I256 {
+	fn checked_div(&self, rhs: &Self) -> Option<Self> {
+		I256::checked_div(*self, *rhs)
+	}
+}
+
+impl CheckedNeg for I256 {
+	fn checked_neg(&self) -> Option<Self> {
+		I256::checked_neg(*self)
+	}
+}
+
+impl num_traits::Saturating for I256 {
+	fn saturating_add(self, rhs: Self) -> Self {
+		I256::saturating_add(self, rhs)
+	}
+
+	fn saturating_sub(self, rhs: Self) -> Self {
+		I256::saturating_sub(self, rhs)
+	}
+}
+
+impl fmt::Display for I256 {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.is_negative() {
+			write!(f, "-")?;
+		}
+		write!(f, "{}", self.unsigned_abs())
+	}
+}
+
+impl fmt::Debug for I256 {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		fmt::Display::fmt(self, f)
+	}
+}
+
+impl FromStr for I256 {
+	type Err = &'static str;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		I256::from_dec_str(s)
+	}
+}
+
+/// Returns `a * b / c` (wrapped to 256 bits) with the given rounding, or `None` if the result
+/// does not fit in 256 bits or `c` is zero.
+///
+/// The intermediate product is computed in 512 bits, so it can not overflow.
+pub fn multiply_by_rational_with_rounding(a: U256, b: U256, c: U256, r: Rounding) -> Option<U256> {
+	if c.is_zero() {
+		return None
+	}
+	let (result, remainder) = a.full_mul(b).div_mod(c.into());
+	let mut result: U256 = result.try_into().ok()?;
+	let remainder: U256 = remainder.try_into().expect("remainder is less than `c`; qed");
+	if match r {
+		Rounding::Up => !remainder.is_zero(),
+		// cannot be `(c + 1) / 2` since `c` might be `max_value` and overflow.
+		Rounding::NearestPrefUp => remainder >= c / 2 + c % 2,
+		Rounding::NearestPrefDown => remainder > c / 2,
+		Rounding::Down => false,
+	} {
+		result = result.checked_add(U256::one())?;
+	}
+	Some(result)
+}
+
+/// Returns the integer square root of `a * b`, rounded down.
+pub fn sqrt_of_product(a: U256, b: U256) -> U256 {
+	a.full_mul(b)
+		.integer_sqrt()
+		.try_into()
+		.expect("the square root of a 512-bit number fits in 256 bits; qed")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::traits::Saturating;
+	use Rounding::*;
+
+	fn i(n: i128) -> I256 {
+		n.into()
+	}
+
+	#[test]
+	fn i256_bounds_are_correct() {
+		assert_eq!(
+			I256::MAX.to_string(),
+			"57896044618658097711785492504343953926634992332820282019728792003956564819967"
+		);
+		assert_eq!(
+			I256::MIN.to_string(),
+			"-57896044618658097711785492504343953926634992332820282019728792003956564819968"
+		);
+		assert!(I256::MIN < i(i128::MIN));
+		assert!(I256::MAX > i(i128::MAX));
+		assert_eq!(I256::MIN.checked_neg(), None);
+		assert_eq!(I256::MAX.checked_neg().unwrap().checked_sub(i(1)), Some(I256::MIN));
+	}
+
+	#[test]
+	fn i256_ordering_works() {
+		let values =
+			[I256::MIN, i(i128::MIN), i(-2), i(-1), i(0), i(1), i(2), i(i128::MAX), I256::MAX];
+		for (index, a) in values.iter().enumerate() {
+			for b in &values[index + 1..] {
+				assert!(a < b, "{} < {}", a, b);
+			}
+		}
+	}
+
+	#[test]
+	fn i256_arithmetic_matches_i128() {
+		let values = [i128::MIN / 3, -1_000_000_007, -7, -1, 0, 1, 7, 1_000_000_007, i128::MAX / 5];
+		for a in values {
+			for b in values {
+				assert_eq!(i(a).checked_add(i(b)), a.checked_add(b).map(i));
+				assert_eq!(i(a).checked_sub(i(b)), a.checked_sub(b).map(i));
+				if let Some(product) = a.checked_mul(b) {
+					assert_eq!(i(a).checked_mul(i(b)), Some(i(product)));
+				}
+				assert_eq!(i(a).checked_div(i(b)), a.checked_div(b).map(i));
+				assert_eq!(i(a).cmp(&i(b)), a.cmp(&b));
+			}
+		}
+	}
+
+	#[test]
+	fn i256_overflow_is_detected() {
+		assert_eq!(I256::MAX.checked_add(i(1)), None);
+		assert_eq!(I256::MIN.checked_sub(i(1)), None);
+		assert_eq!(I256::MIN.checked_add(i(-1)), None);
+		assert_eq!(I256::MAX.checked_sub(i(-1)), None);
+		assert_eq!(I256::MAX.checked_mul(i(2)), None);
+		assert_eq!(I256::MIN.checked_mul(i(-1)), None);
+		assert_eq!(I256::MIN.checked_div(i(-1)), None);
+		assert_eq!(I256::MIN.checked_div(i(0)), None);
+		assert_eq!(I256::MIN.checked_mul(i(1)), Some(I256::MIN));
+
+		assert_eq!(I256::MAX.saturating_add(i(1)), I256::MAX);
+		assert_eq!(I256::MIN.saturating_sub(i(1)), I256::MIN);
+		assert_eq!(I256::MAX.saturating_mul(i(-2)), I256::MIN);
+		assert_eq!(I256::MIN.saturating_mul(i(-2)), I256::MAX);
+	}
+
+	#[test]
+	fn i256_conversions_work() {
+		assert_eq!(u128::try_from(i(42)), Ok(42));
+		assert_eq!(u128::try_from(i(-42)), Err(()));
+		assert_eq!(i128::try_from(i(i128::MIN)), Ok(i128::MIN));
+		assert_eq!(i128::try_from(i(i128::MAX)), Ok(i128::MAX));
+		assert_eq!(i128::try_from(I256::MAX), Err(()));
+		assert_eq!(U256::try_from(i(-1)), Err(()));
+		assert_eq!(I256::try_from(U256::MAX), Err(()));
+		assert_eq!(I256::from(u128::MAX).unsigned_abs(), U256::from(u128::MAX));
+		assert_eq!(I256::MIN.unsigned_abs(), U256::one() << 255);
+	}
+
+	#[test]
+	fn i256_string_conversion_works() {
+		for s in ["0", "1", "-1", "340282366920938463463374607431768211456", "-123456789"] {
+			assert_eq!(s.parse::<I256>().unwrap().to_string(), s);
+		}
+		assert_eq!("+12".parse::<I256>(), Ok(i(12)));
+		assert_eq!(I256::MIN.to_string().parse::<I256>(), Ok(I256::MIN));
+		assert!("57896044618658097711785492504343953926634992332820282019728792003956564819968"
+			.parse::<I256>()
+			.is_err());
+		assert!("-".parse::<I256>().is_err());
+		assert!("1a".parse::<I256>().is_err());
+	}
+
+	#[test]
+	fn i256_codec_works() {
+		use codec::{Decode, Encode};
+
+		for n in [I256::MIN, i(-1), i(0), i(1), I256::MAX] {
+			let encoded = n.encode();
+			assert_eq!(encoded.len(), 32);
+			assert_eq!(I256::decode(&mut &encoded[..]), Ok(n));
+		}
+		assert_eq!(i(-1).encode(), [0xff; 32].to_vec());
+	}
+
+	#[test]
+	fn u256_multiply_by_rational_works() {
+		let max = U256::MAX;
+		let mulrat = multiply_by_rational_with_rounding;
+
+		assert_eq!(mulrat(1.into(), 1.into(), 3.into(), Up), Some(1.into()));
+		assert_eq!(mulrat(1.into(), 2.into(), 3.into(), Down), Some(0.into()));
+		assert_eq!(mulrat(1.into(), 1.into(), 2.into(), NearestPrefDown), Some(0.into()));
+		assert_eq!(mulrat(1.into(), 1.into(), 2.into(), NearestPrefUp), Some(1.into()));
+		assert_eq!(mulrat(max, max, max, Down), Some(max));
+		assert_eq!(mulrat(max, max - 1, max, Up), Some(max - 1));
+		assert_eq!(mulrat(max, 2.into(), 1.into(), Down), None);
+		assert_eq!(mulrat(max, max, max - 1, Down), None);
+		assert_eq!(mulrat(1.into(), 1.into(), 0.into(), Down), None);
+		assert_eq!(mulrat(max, max, 2.into(), Up), None);
+	}
+
+	#[test]
+	fn u256_sqrt_of_product_works() {
+		assert_eq!(sqrt_of_product(0.into(), 5.into()), 0.into());
+		assert_eq!(sqrt_of_product(3.into(), 12.into()), 6.into());
+		assert_eq!(sqrt_of_product(3.into(), 13.into()), 6.into());
+		assert_eq!(sqrt_of_product(U256::MAX, U256::MAX), U256::MAX);
+	}
+}
@@ -20,7 +20,8 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Codec, Decode, Encode};
-use sp_runtime::ConsensusEngineId;
+use scale_info::TypeInfo;
+use sp_runtime::{traits::Header, ConsensusEngineId};
 use sp_std::vec::Vec;
 
 pub mod digests;
@@ -81,6 +82,98 @@ pub enum ConsensusLog<AuthorityId: Codec> {
 	OnDisabled(AuthorityIndex),
 }
 
+/// An equivocation proof for multiple block authorships on the same slot (i.e. double vote).
+pub type EquivocationProof<H, AuthorityId> = sp_consensus_slots::EquivocationProof<H, AuthorityId>;
+
+/// Verifies the equivocation proof by making sure that: both headers have
+/// different hashes, are targetting the same slot, the offender is the
+/// expected author of that slot among the given `authorities` and both headers
+/// have valid signatures by the offender.
+pub fn check_equivocation_proof<H, P>(proof: EquivocationProof<H, P>, authorities: &[P]) -> bool
+where
+	H: Header,
+	P: sp_application_crypto::RuntimeAppPublic + PartialEq,
+{
+	use digests::CompatibleDigestItem;
+
+	let find_slot = |header: &H| {
+		header
+			.digest()
+			.logs()
+			.iter()
+			.find_map(CompatibleDigestItem::<P::Signature>::as_aura_pre_digest)
+	};
+
+	let verify_seal_signature = |mut header: H, offender: &P| {
+		let seal = CompatibleDigestItem::<P::Signature>::as_aura_seal(&header.digest_mut().pop()?)?;
+		let pre_hash = header.hash();
+
+		if !offender.verify(&pre_hash.as_ref(), &seal) {
+			return None
+		}
+
+		Some(())
+	};
+
+	let verify_proof = || {
+		// we must have different headers for the equivocation to be valid
+		if proof.first_header.hash() == proof.second_header.hash() {
+			return None
+		}
+
+		// both headers must be targetting the same slot and it must
+		// be the same as the one in the proof.
+		if Some(proof.slot) != find_slot(&proof.first_header) ||
+			Some(proof.slot) != find_slot(&proof.second_header)
+		{
+			return None
+		}
+
+		// only the author assigned to the slot may seal a block for it, so an offender
+		// that isn't the slot author can't have equivocated.
+		if authorities.is_empty() {
+			return None
+		}
+		let author_index = *proof.slot % authorities.len() as u64;
+		if authorities.get(author_index as usize) != Some(&proof.offender) {
+			return None
+		}
+
+		// both headers must be sealed by the offender.
+		verify_seal_signature(proof.first_header, &proof.offender)?;
+		verify_seal_signature(proof.second_header, &proof.offender)?;
+
+		Some(())
+	};
+
+	// NOTE: we isolate the verification code into an helper function that
+	// returns `Option<()>` so that we can use `?` to deal with any intermediate
+	// errors and discard the proof as invalid.
+	verify_proof().is_some()
+}
+
+/// An opaque type used to represent the key ownership proof at the runtime API
+/// boundary. The inner value is an encoded representation of the actual key
+/// ownership proof which will be parameterized when defining the runtime. At
+/// the runtime API boundary this type is unknown and as such we keep this
+/// opaque representation, implementors of the runtime API will have to make
+/// sure that all usages of `OpaqueKeyOwnershipProof` refer to the same type.
+#[derive(Decode, Encode, PartialEq, TypeInfo)]
+pub struct OpaqueKeyOwnershipProof(Vec<u8>);
+impl OpaqueKeyOwnershipProof {
+	/// Create a new `OpaqueKeyOwnershipProof` using the given encoded
+	/// representation.
+	pub fn new(inner: Vec<u8>) -> OpaqueKeyOwnershipProof {
+		OpaqueKeyOwnershipProof(inner)
+	}
+
+	/// Try to decode this `OpaqueKeyOwnershipProof` into the given concrete key
+	/// ownership proof type.
+	pub fn decode<T: Decode>(self) -> Option<T> {
+		Decode::decode(&mut &self.0[..]).ok()
+	}
+}
+
 sp_api::decl_runtime_apis! {
 	/// API necessary for block authorship with aura.
 	pub trait AuraApi<AuthorityId: Codec> {
@@ -91,5 +184,34 @@ sp_api::decl_runtime_apis! {
 
 		/// Return the current set of authorities.
 		fn authorities() -> Vec<AuthorityId>;
+
+		/// Generates a proof of key ownership for the given authority at the
+		/// current block. An example usage of this module is coupled with the
+		/// session historical module to prove that a given authority key is
+		/// tied to a given staking identity during a specific session. Proofs
+		/// of key ownership are necessary for submitting equivocation reports.
+		/// NOTE: even though the API takes a `slot` as parameter the current
+		/// implementations ignore this parameter and instead rely on this
+		/// method being called at the correct block height, i.e. any point at
+		/// which the session of the given slot is live on-chain.
+		#[api_version(2)]
+		fn generate_key_ownership_proof(
+			slot: Slot,
+			authority_id: AuthorityId,
+		) -> Option<OpaqueKeyOwnershipProof>;
+
+		/// Submits an unsigned extrinsic to report an equivocation. The caller
+		/// must provide the equivocation proof and a key ownership proof
+		/// (should be obtained using `generate_key_ownership_proof`). The
+		/// extrinsic will be unsigned and should only be accepted for local
+		/// authorship (not to be broadcast to the network). This method returns
+		/// `None` when creation of the extrinsic fails, e.g. if equivocation
+		/// reporting is disabled for the given runtime (i.e. this method is
+		/// hardcoded to return `None`). Only useful in an offchain context.
+		#[api_version(2)]
+		fn submit_report_equivocation_unsigned_extrinsic(
+			equivocation_proof: EquivocationProof<Block::Header, AuthorityId>,
+			key_owner_proof: OpaqueKeyOwnershipProof,
+		) -> Option<()>;
 	}
 }
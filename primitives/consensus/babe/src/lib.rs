@@ -27,6 +27,7 @@ use codec::{Decode, Encode, MaxEncodedLen};
 use scale_info::TypeInfo;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use sp_core::sr25519::vrf::{VrfSignDataParts, VrfTranscriptParts};
 use sp_runtime::{traits::Header, ConsensusEngineId, RuntimeDebug};
 use sp_std::vec::Vec;
 
@@ -98,19 +99,30 @@ pub type BabeBlockWeight = u32;
 
 /// Make VRF input suitable for BABE's randomness generation.
 pub fn make_vrf_transcript(randomness: &Randomness, slot: Slot, epoch: u64) -> VrfInput {
-	VrfInput::new(
-		&BABE_ENGINE_ID,
-		&[
-			(b"slot number", &slot.to_le_bytes()),
-			(b"current epoch", &epoch.to_le_bytes()),
-			(b"chain randomness", randomness),
-		],
-	)
+	make_vrf_transcript_parts(randomness, slot, epoch).to_transcript()
 }
 
 /// Make VRF signing data suitable for BABE's protocol.
+///
+/// The data keeps the parts it is built from, so it can be forwarded to a remote signer.
 pub fn make_vrf_sign_data(randomness: &Randomness, slot: Slot, epoch: u64) -> VrfSignData {
-	make_vrf_transcript(randomness, slot, epoch).into()
+	VrfSignDataParts { transcript: make_vrf_transcript_parts(randomness, slot, epoch), extra: None }
+		.to_sign_data()
+}
+
+fn make_vrf_transcript_parts(
+	randomness: &Randomness,
+	slot: Slot,
+	epoch: u64,
+) -> VrfTranscriptParts {
+	VrfTranscriptParts {
+		label: BABE_ENGINE_ID.to_vec(),
+		data: sp_std::vec![
+			(b"slot number".to_vec(), slot.to_le_bytes().to_vec()),
+			(b"current epoch".to_vec(), epoch.to_le_bytes().to_vec()),
+			(b"chain randomness".to_vec(), randomness.to_vec()),
+		],
+	}
 }
 
 /// An consensus log item for BABE.
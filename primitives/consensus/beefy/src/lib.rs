@@ -34,6 +34,7 @@
 mod commitment;
 pub mod mmr;
 mod payload;
+pub mod proof_bundle;
 #[cfg(feature = "std")]
 mod test_utils;
 pub mod witness;
@@ -0,0 +1,312 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Self-contained BEEFY + MMR proofs for light clients.
+//!
+//! A [`ProofBundle`] proves that an MMR leaf (and hence the block it was produced for) is part
+//! of a chain finalized by BEEFY. It carries everything a light client needs, given that it
+//! already trusts a [`BeefyAuthoritySet`]:
+//!
+//! - the signed commitment of the nearest BEEFY-finalized block at or above the proven one,
+//! - for every signature used, the signer's key and a merkle proof of its membership in the
+//!   authority set's `keyset_commitment` (built with `binary-merkle-tree`),
+//! - the MMR leaf and its proof against the MMR root found in the commitment payload.
+//!
+//! [`verify_proof_bundle`] checks all of the above without any access to chain state.
+
+use crate::{
+	check_commitment_signature, known_payloads::MMR_ROOT_ID, mmr::BeefyAuthoritySet,
+	BeefyAuthorityId, SignedCommitment,
+};
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use sp_application_crypto::RuntimeAppPublic;
+use sp_mmr_primitives::{mmr_lib, utils::NodesUtils, DataOrHash, OpaqueLeaf, Proof};
+use sp_runtime::traits::Hash;
+use sp_std::{marker::PhantomData, prelude::*, vec};
+
+/// Merkle proof of a single BEEFY authority being part of an authority set.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct AuthorityProof<AuthorityId, MerkleHash> {
+	/// Position of the authority in the set, which is also the position of its signature in
+	/// [`SignedCommitment::signatures`].
+	pub position: u32,
+	/// The authority key.
+	pub authority_id: AuthorityId,
+	/// Inner nodes required to reconstruct the `keyset_commitment` from the authority leaf.
+	pub proof: Vec<MerkleHash>,
+}
+
+/// A self-contained proof that an MMR leaf is part of a BEEFY-finalized chain.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct ProofBundle<BlockNumber, AuthorityId, Signature, MerkleHash> {
+	/// Commitment finalizing a block at or above the one the leaf was produced for.
+	pub signed_commitment: SignedCommitment<BlockNumber, Signature>,
+	/// Membership proofs for the authorities whose signatures should be checked.
+	///
+	/// Must be sorted by [`AuthorityProof::position`] and cover at least the signature
+	/// threshold of the set.
+	pub authority_proofs: Vec<AuthorityProof<AuthorityId, MerkleHash>>,
+	/// Compact-encoded MMR leaf, e.g. an encoded [`crate::mmr::MmrLeaf`].
+	pub leaf: Vec<u8>,
+	/// Proof of [`Self::leaf`] against the MMR root in the commitment payload.
+	pub leaf_proof: Proof<MerkleHash>,
+}
+
+/// Reasons for a [`ProofBundle`] to be rejected.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum ProofBundleError {
+	/// The commitment is signed by a different validator set than the trusted one.
+	ValidatorSetMismatch,
+	/// Number of signature slots doesn't match the size of the authority set.
+	InvalidSignaturesLength,
+	/// Authority proofs are not sorted by position, or point outside of the set.
+	InvalidAuthorityPosition,
+	/// An authority is not part of the trusted `keyset_commitment`.
+	InvalidAuthorityProof,
+	/// A signature is missing or doesn't match the commitment.
+	InvalidSignature,
+	/// Fewer valid signatures than required by the BEEFY threshold.
+	NotEnoughSignatures,
+	/// The commitment payload doesn't contain a (decodable) MMR root.
+	MissingMmrRoot,
+	/// The MMR leaf proof doesn't match the MMR root.
+	InvalidLeafProof,
+}
+
+/// Minimal number of signatures required to finalize a block with a set of `authorities`.
+pub fn signature_threshold(authorities: usize) -> usize {
+	let faulty = authorities.saturating_sub(1) / 3;
+	authorities - faulty
+}
+
+/// Verify `bundle` against a trusted `authority_set`.
+///
+/// `authority_to_leaf` must convert an authority key into the merkle leaf used when building
+/// the `keyset_commitment` on-chain (i.e. match the runtime's `BeefyAuthorityToMerkleLeaf`).
+/// The same hasher `H` is used for signatures, the authority merkle tree and the MMR.
+///
+/// On success, the verified MMR leaf is returned so it can be decoded by the caller.
+pub fn verify_proof_bundle<BlockNumber, AuthorityId, H>(
+	bundle: ProofBundle<
+		BlockNumber,
+		AuthorityId,
+		<AuthorityId as RuntimeAppPublic>::Signature,
+		H::Output,
+	>,
+	authority_set: &BeefyAuthoritySet<H::Output>,
+	authority_to_leaf: impl Fn(&AuthorityId) -> Vec<u8>,
+) -> Result<OpaqueLeaf, ProofBundleError>
+where
+	BlockNumber: Clone + Encode + PartialEq,
+	AuthorityId: BeefyAuthorityId<H>,
+	H: Hash,
+{
+	let ProofBundle { signed_commitment, authority_proofs, leaf, leaf_proof } = bundle;
+	let commitment = &signed_commitment.commitment;
+
+	if commitment.validator_set_id != authority_set.id {
+		return Err(ProofBundleError::ValidatorSetMismatch)
+	}
+	let set_len = authority_set.len as usize;
+	if signed_commitment.signatures.len() != set_len {
+		return Err(ProofBundleError::InvalidSignaturesLength)
+	}
+
+	let mut next_position = 0;
+	for AuthorityProof { position, authority_id, proof } in &authority_proofs {
+		let position = *position as usize;
+		if position < next_position || position >= set_len {
+			return Err(ProofBundleError::InvalidAuthorityPosition)
+		}
+		next_position = position + 1;
+
+		let authority_leaf = authority_to_leaf(authority_id);
+		if !binary_merkle_tree::verify_proof::<H, _, _>(
+			&authority_set.keyset_commitment,
+			proof.iter().cloned(),
+			set_len,
+			position,
+			&authority_leaf,
+		) {
+			return Err(ProofBundleError::InvalidAuthorityProof)
+		}
+
+		let signature = signed_commitment.signatures[position]
+			.as_ref()
+			.ok_or(ProofBundleError::InvalidSignature)?;
+		if !check_commitment_signature::<_, _, H>(commitment, authority_id, signature) {
+			return Err(ProofBundleError::InvalidSignature)
+		}
+	}
+	if authority_proofs.len() < signature_threshold(set_len) {
+		return Err(ProofBundleError::NotEnoughSignatures)
+	}
+
+	let mmr_root = commitment
+		.payload
+		.get_decoded::<H::Output>(&MMR_ROOT_ID)
+		.ok_or(ProofBundleError::MissingMmrRoot)?;
+	let leaf = OpaqueLeaf::from_encoded_leaf(leaf);
+	if !verify_leaf_proof::<H>(mmr_root, leaf.clone(), leaf_proof) {
+		return Err(ProofBundleError::InvalidLeafProof)
+	}
+
+	Ok(leaf)
+}
+
+/// MMR node merging compatible with `pallet-mmr`.
+struct MmrHasher<H>(PhantomData<H>);
+
+impl<H: Hash> mmr_lib::Merge for MmrHasher<H> {
+	type Item = DataOrHash<H, OpaqueLeaf>;
+
+	fn merge(left: &Self::Item, right: &Self::Item) -> mmr_lib::Result<Self::Item> {
+		let mut concat = left.hash().as_ref().to_vec();
+		concat.extend_from_slice(right.hash().as_ref());
+
+		Ok(DataOrHash::Hash(<H as Hash>::hash(&concat)))
+	}
+}
+
+fn verify_leaf_proof<H: Hash>(root: H::Output, leaf: OpaqueLeaf, proof: Proof<H::Output>) -> bool {
+	let leaf_index = match proof.leaf_indices.as_slice() {
+		[index] => *index,
+		_ => return false,
+	};
+	let size = NodesUtils::new(proof.leaf_count).size();
+	let proof = mmr_lib::MerkleProof::<_, MmrHasher<H>>::new(
+		size,
+		proof.items.into_iter().map(DataOrHash::Hash).collect(),
+	);
+	proof
+		.verify(
+			DataOrHash::Hash(root),
+			vec![(mmr_lib::leaf_index_to_pos(leaf_index), DataOrHash::Data(leaf))],
+		)
+		.unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{ecdsa_crypto, known_payloads, Commitment, Keyring, Payload};
+	use sp_runtime::traits::Keccak256;
+
+	type TestBundle = ProofBundle<
+		u64,
+		ecdsa_crypto::AuthorityId,
+		ecdsa_crypto::Signature,
+		<Keccak256 as Hash>::Output,
+	>;
+
+	fn authority_leaf(id: &ecdsa_crypto::AuthorityId) -> Vec<u8> {
+		id.encode()
+	}
+
+	// Two-leaf MMR: the root is the hash of both leaf hashes, each leaf proven by its sibling.
+	fn make_bundle(
+		signers: &[Keyring],
+	) -> (TestBundle, BeefyAuthoritySet<<Keccak256 as Hash>::Output>) {
+		let keys = [Keyring::Alice, Keyring::Bob, Keyring::Charlie, Keyring::Dave];
+		let leaves: Vec<_> = keys.iter().map(|k| authority_leaf(&k.public())).collect();
+		let authority_set = BeefyAuthoritySet {
+			id: 3,
+			len: keys.len() as u32,
+			keyset_commitment: binary_merkle_tree::merkle_root::<Keccak256, _>(leaves.clone()),
+		};
+
+		let leaf = b"leaf for block 1".to_vec();
+		let sibling = Keccak256::hash(b"leaf for block 2");
+		let mut concat = Keccak256::hash(&leaf).as_ref().to_vec();
+		concat.extend_from_slice(sibling.as_ref());
+		let mmr_root = Keccak256::hash(&concat);
+
+		let commitment = Commitment {
+			payload: Payload::from_single_entry(known_payloads::MMR_ROOT_ID, mmr_root.encode()),
+			block_number: 2,
+			validator_set_id: 3,
+		};
+		let signatures = keys
+			.iter()
+			.map(|k| signers.contains(k).then(|| k.sign(&commitment.encode())))
+			.collect();
+		let authority_proofs = keys
+			.iter()
+			.enumerate()
+			.filter(|(_, k)| signers.contains(k))
+			.map(|(position, k)| AuthorityProof {
+				position: position as u32,
+				authority_id: k.public(),
+				proof: binary_merkle_tree::merkle_proof::<Keccak256, _, _>(
+					leaves.clone(),
+					position,
+				)
+				.proof,
+			})
+			.collect();
+
+		let bundle = ProofBundle {
+			signed_commitment: SignedCommitment { commitment, signatures },
+			authority_proofs,
+			leaf,
+			leaf_proof: Proof { leaf_indices: vec![0], leaf_count: 2, items: vec![sibling] },
+		};
+		(bundle, authority_set)
+	}
+
+	#[test]
+	fn valid_bundle_is_accepted() {
+		let (bundle, set) = make_bundle(&[Keyring::Alice, Keyring::Bob, Keyring::Dave]);
+
+		let decoded = TestBundle::decode(&mut &*bundle.encode()).unwrap();
+		assert_eq!(decoded, bundle);
+
+		let leaf = verify_proof_bundle::<_, _, Keccak256>(bundle, &set, authority_leaf).unwrap();
+		assert_eq!(leaf.0, b"leaf for block 1".to_vec());
+	}
+
+	#[test]
+	fn invalid_bundles_are_rejected() {
+		let verify = |bundle, set: &BeefyAuthoritySet<_>| {
+			verify_proof_bundle::<_, _, Keccak256>(bundle, set, authority_leaf)
+		};
+
+		let (bundle, set) = make_bundle(&[Keyring::Alice, Keyring::Bob]);
+		assert_eq!(verify(bundle, &set), Err(ProofBundleError::NotEnoughSignatures));
+
+		let (bundle, mut set) = make_bundle(&[Keyring::Alice, Keyring::Bob, Keyring::Charlie]);
+		set.id = 4;
+		assert_eq!(verify(bundle, &set), Err(ProofBundleError::ValidatorSetMismatch));
+
+		let (mut bundle, set) = make_bundle(&[Keyring::Alice, Keyring::Bob, Keyring::Charlie]);
+		bundle.authority_proofs[1].authority_id = Keyring::Eve.public();
+		assert_eq!(verify(bundle, &set), Err(ProofBundleError::InvalidAuthorityProof));
+
+		let (mut bundle, set) = make_bundle(&[Keyring::Alice, Keyring::Bob, Keyring::Charlie]);
+		bundle.authority_proofs.swap(0, 1);
+		assert_eq!(verify(bundle, &set), Err(ProofBundleError::InvalidAuthorityPosition));
+
+		let (mut bundle, set) = make_bundle(&[Keyring::Alice, Keyring::Bob, Keyring::Charlie]);
+		bundle.signed_commitment.signatures[0] = Some(Keyring::Bob.sign(b"something else"));
+		assert_eq!(verify(bundle, &set), Err(ProofBundleError::InvalidSignature));
+
+		let (mut bundle, set) = make_bundle(&[Keyring::Alice, Keyring::Bob, Keyring::Charlie]);
+		bundle.leaf = b"leaf for block 3".to_vec();
+		assert_eq!(verify(bundle, &set), Err(ProofBundleError::InvalidLeafProof));
+	}
+}
@@ -31,10 +31,10 @@ use crate::crypto::{DeriveError, DeriveJunction, Pair as TraitPair, SecretString
 use bandersnatch_vrfs::CanonicalSerialize;
 #[cfg(feature = "full_crypto")]
 use bandersnatch_vrfs::SecretKey;
-use codec::{Decode, Encode, MaxEncodedLen};
+use codec::{Decode, Encode, EncodeLike, MaxEncodedLen};
 use scale_info::TypeInfo;
 
-use sp_runtime_interface::pass_by::PassByInner;
+use sp_runtime_interface::pass_by::{PassByCodec, PassByInner};
 use sp_std::{boxed::Box, vec::Vec};
 
 /// Identifier used to match public keys against bandersnatch-vrf keys.
@@ -47,6 +47,12 @@ pub const SIGNING_CTX: &[u8] = b"SigningContext";
 // Max ring domain size.
 const RING_DOMAIN_SIZE: usize = 1024;
 
+// Number of ring domain slots which can't be used for keys.
+//
+// The ring proof reserves 3 rows for zero knowledge, 253 rows for the bits of the secret
+// scalar and 1 row for the accumulator seed. Checked against `max_keyset_size` in the tests.
+const RING_DOMAIN_OVERHEAD: usize = 257;
+
 #[cfg(feature = "full_crypto")]
 const SEED_SERIALIZED_LEN: usize = 32;
 
@@ -71,6 +77,11 @@ const RING_PROOF_SERIALIZED_LEN: usize = 592;
 // in the backend.
 const RING_CONTEXT_SERIALIZED_LEN: usize = 147752;
 
+// Serialized size of the ring verifier key, i.e. the KZG verifier key (one G1 and two G2
+// points, 240 bytes) and the commitments to the ring public keys and the ring selector (three
+// G1 points, 144 bytes). Independent of the ring size.
+const RING_VERIFIER_KEY_SERIALIZED_LEN: usize = 384;
+
 /// Bandersnatch public key.
 #[cfg_attr(feature = "full_crypto", derive(Hash))]
 #[derive(
@@ -314,7 +325,7 @@ pub mod vrf {
 	use super::*;
 	use crate::{bounded::BoundedVec, crypto::VrfCrypto, ConstU32};
 	use bandersnatch_vrfs::{
-		CanonicalDeserialize, CanonicalSerialize, IntoVrfInput, Message, PublicKey,
+		CanonicalDeserialize, CanonicalSerialize, IntoVrfInput, IsLabel, Message, PublicKey,
 		ThinVrfSignature, Transcript,
 	};
 
@@ -365,6 +376,8 @@ pub mod vrf {
 		}
 	}
 
+	impl EncodeLike for VrfOutput {}
+
 	impl MaxEncodedLen for VrfOutput {
 		fn max_encoded_len() -> usize {
 			<[u8; PREOUT_SERIALIZED_LEN]>::max_encoded_len()
@@ -394,6 +407,8 @@ pub mod vrf {
 		pub vrf_inputs: VrfIosVec<VrfInput>,
 		/// Associated Fiat-Shamir transcript.
 		pub transcript: Transcript,
+		/// The data `vrf_inputs` and `transcript` were built from, if known.
+		parts: Option<VrfSignDataParts>,
 	}
 
 	impl VrfSignData {
@@ -434,19 +449,34 @@ pub mod vrf {
 			transcript_data
 				.into_iter()
 				.for_each(|data| transcript.append_slice(data.as_ref()));
-			VrfSignData { transcript, vrf_inputs }
+			VrfSignData { transcript, vrf_inputs, parts: None }
 		}
 
 		/// Append a raw message to the transcript.
 		pub fn push_transcript_data(&mut self, data: &[u8]) {
 			self.transcript.append_slice(data);
+			if let Some(parts) = &mut self.parts {
+				parts.transcript_data.push(data.to_vec());
+			}
 		}
 
 		/// Append a [`VrfInput`] to the vrf inputs to be signed.
 		///
 		/// On failure, gives back the [`VrfInput`] parameter.
 		pub fn push_vrf_input(&mut self, vrf_input: VrfInput) -> Result<(), VrfInput> {
-			self.vrf_inputs.try_push(vrf_input)
+			self.vrf_inputs.try_push(vrf_input)?;
+			// A `VrfInput` doesn't keep the data it was constructed from.
+			self.parts = None;
+			Ok(())
+		}
+
+		/// The data this instance was built from.
+		///
+		/// Only known for instances built with [`VrfSignDataParts::to_sign_data`] and not
+		/// given another [`VrfInput`] since. Changes made directly to the public `transcript` or
+		/// `vrf_inputs` fields are not reflected.
+		pub fn parts(&self) -> Option<&VrfSignDataParts> {
+			self.parts.as_ref()
 		}
 
 		/// Create challenge from the transcript contained within the signing data.
@@ -459,6 +489,46 @@ pub mod vrf {
 		}
 	}
 
+	/// The data [`VrfSignData`] is built from.
+	///
+	/// In contrast to [`VrfSignData`], this can be encoded. This allows to pass the data that was
+	/// signed to the host, for example to verify a ring signature, or to a remote signer.
+	#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, PassByCodec, TypeInfo)]
+	pub struct VrfSignDataParts {
+		/// Label of the *Fiat-Shamir* transcript.
+		pub transcript_label: Vec<u8>,
+		/// Messages appended to the *Fiat-Shamir* transcript.
+		pub transcript_data: Vec<Vec<u8>>,
+		/// The `domain` and `data` of the VRF inputs, see [`VrfInput::new`].
+		pub vrf_inputs: Vec<(Vec<u8>, Vec<u8>)>,
+	}
+
+	impl VrfSignDataParts {
+		/// Build the [`VrfSignData`].
+		///
+		/// The result is the same as the one of [`VrfSignData::new`] with the same arguments.
+		/// Fails if there are more than [`MAX_VRF_IOS`] VRF inputs.
+		pub fn to_sign_data(&self) -> Result<VrfSignData, ()> {
+			if self.vrf_inputs.len() > MAX_VRF_IOS as usize {
+				return Err(())
+			}
+
+			let mut transcript = Transcript::new_labeled(IsLabel(self.transcript_label.as_slice()));
+			self.transcript_data.iter().for_each(|data| transcript.append_slice(data));
+			let vrf_inputs = self
+				.vrf_inputs
+				.iter()
+				.map(|(domain, data)| VrfInput::new(domain, data))
+				.collect::<Vec<_>>();
+
+			Ok(VrfSignData {
+				transcript,
+				vrf_inputs: VrfIosVec::truncate_from(vrf_inputs),
+				parts: Some(self.clone()),
+			})
+		}
+	}
+
 	/// VRF signature.
 	#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, MaxEncodedLen, TypeInfo)]
 	pub struct VrfSignature {
@@ -612,14 +682,24 @@ pub mod vrf {
 pub mod ring_vrf {
 	use super::{vrf::*, *};
 	pub use bandersnatch_vrfs::ring::{RingProof, RingProver, RingVerifier, KZG};
-	use bandersnatch_vrfs::{CanonicalDeserialize, PedersenVrfSignature, PublicKey};
+	use bandersnatch_vrfs::{
+		ring::VerifierKey, CanonicalDeserialize, PedersenVrfSignature, PublicKey,
+	};
+	#[cfg(feature = "serde")]
+	use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+	#[cfg(all(not(feature = "std"), feature = "serde"))]
+	use sp_std::alloc::{format, string::String};
 
 	/// Context used to produce ring signatures.
-	#[derive(Clone)]
+	#[derive(Clone, PassByCodec)]
 	pub struct RingContext(KZG);
 
 	impl RingContext {
 		/// Build an dummy instance used for testing purposes.
+		///
+		/// The setup is not secure, thus this is only available to tests and development
+		/// chains. Production contexts come from a setup ceremony and are set at genesis.
+		#[cfg(feature = "std")]
 		pub fn new_testing() -> Self {
 			Self(KZG::testing_kzg_setup([0; 32], RING_DOMAIN_SIZE as u32))
 		}
@@ -630,7 +710,13 @@ pub mod ring_vrf {
 		}
 
 		/// Get ring prover for the key at index `public_idx` in the `public_keys` set.
+		///
+		/// Returns `None` if the set is larger than [`Self::max_keyset_size`].
 		pub fn prover(&self, public_keys: &[Public], public_idx: usize) -> Option<RingProver> {
+			if public_keys.len() > self.max_keyset_size() {
+				return None
+			}
+
 			let mut pks = Vec::with_capacity(public_keys.len());
 			for public_key in public_keys {
 				let pk = PublicKey::deserialize_compressed(public_key.as_slice()).ok()?;
@@ -643,7 +729,13 @@ pub mod ring_vrf {
 		}
 
 		/// Get ring verifier for the `public_keys` set.
+		///
+		/// Returns `None` if the set is larger than [`Self::max_keyset_size`].
 		pub fn verifier(&self, public_keys: &[Public]) -> Option<RingVerifier> {
+			if public_keys.len() > self.max_keyset_size() {
+				return None
+			}
+
 			let mut pks = Vec::with_capacity(public_keys.len());
 			for public_key in public_keys {
 				let pk = PublicKey::deserialize_compressed(public_key.as_slice()).ok()?;
@@ -654,6 +746,29 @@ pub mod ring_vrf {
 			let ring_verifier = self.0.init_ring_verifier(verifier_key);
 			Some(ring_verifier)
 		}
+
+		/// Get the data required to build a ring verifier for the `public_keys` set.
+		///
+		/// In contrast to the context, the data commits to the ring and is small, so it can
+		/// be computed once per ring and cheaply passed around, e.g. to the host.
+		///
+		/// Returns `None` if the set is larger than [`Self::max_keyset_size`].
+		pub fn verifier_data(&self, public_keys: &[Public]) -> Option<RingVerifierData> {
+			if public_keys.len() > self.max_keyset_size() {
+				return None
+			}
+
+			let mut pks = Vec::with_capacity(public_keys.len());
+			for public_key in public_keys {
+				let pk = PublicKey::deserialize_compressed(public_key.as_slice()).ok()?;
+				pks.push(pk.0 .0.into());
+			}
+
+			Some(RingVerifierData {
+				domain_size: (self.max_keyset_size() + RING_DOMAIN_OVERHEAD) as u32,
+				verifier_key: self.0.verifier_key(pks),
+			})
+		}
 	}
 
 	impl Encode for RingContext {
@@ -675,6 +790,8 @@ pub mod ring_vrf {
 		}
 	}
 
+	impl EncodeLike for RingContext {}
+
 	impl MaxEncodedLen for RingContext {
 		fn max_encoded_len() -> usize {
 			<[u8; RING_CONTEXT_SERIALIZED_LEN]>::max_encoded_len()
@@ -689,8 +806,92 @@ pub mod ring_vrf {
 		}
 	}
 
+	#[cfg(feature = "serde")]
+	impl Serialize for RingContext {
+		fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+		where
+			S: Serializer,
+		{
+			serializer.serialize_str(&array_bytes::bytes2hex("", self.encode()))
+		}
+	}
+
+	#[cfg(feature = "serde")]
+	impl<'de> Deserialize<'de> for RingContext {
+		fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+		where
+			D: Deserializer<'de>,
+		{
+			let bytes = array_bytes::hex2bytes(&String::deserialize(deserializer)?)
+				.map_err(|e| de::Error::custom(format!("{:?}", e)))?;
+			RingContext::decode(&mut bytes.as_slice())
+				.map_err(|e| de::Error::custom(format!("{:?}", e)))
+		}
+	}
+
+	/// Data required to build a [`RingVerifier`] for a given ring, see
+	/// [`RingContext::verifier_data`].
+	#[derive(Clone, PassByCodec)]
+	pub struct RingVerifierData {
+		/// Domain size of the context the data was computed with.
+		pub domain_size: u32,
+		/// Verifier key, committing to the ring public keys.
+		pub verifier_key: VerifierKey,
+	}
+
+	impl RingVerifierData {
+		fn is_valid_domain_size(domain_size: u32) -> bool {
+			domain_size.is_power_of_two() && domain_size as usize <= RING_DOMAIN_SIZE
+		}
+	}
+
+	impl From<RingVerifierData> for RingVerifier {
+		fn from(data: RingVerifierData) -> RingVerifier {
+			bandersnatch_vrfs::ring::make_ring_verifier(
+				data.verifier_key,
+				data.domain_size as usize,
+			)
+		}
+	}
+
+	impl Encode for RingVerifierData {
+		fn encode(&self) -> Vec<u8> {
+			let mut buf = [0; RING_VERIFIER_KEY_SERIALIZED_LEN];
+			self.verifier_key
+				.serialize_compressed(buf.as_mut_slice())
+				.expect("verifier key serialization can't fail");
+			(self.domain_size, buf).encode()
+		}
+	}
+
+	impl Decode for RingVerifierData {
+		fn decode<R: codec::Input>(i: &mut R) -> Result<Self, codec::Error> {
+			let (domain_size, buf) = <(u32, [u8; RING_VERIFIER_KEY_SERIALIZED_LEN])>::decode(i)?;
+			if !Self::is_valid_domain_size(domain_size) {
+				return Err("Invalid ring domain size".into())
+			}
+			let verifier_key = VerifierKey::deserialize_compressed(buf.as_slice())
+				.map_err(|_| "Verifier key decode error")?;
+			Ok(RingVerifierData { domain_size, verifier_key })
+		}
+	}
+
+	impl MaxEncodedLen for RingVerifierData {
+		fn max_encoded_len() -> usize {
+			<(u32, [u8; RING_VERIFIER_KEY_SERIALIZED_LEN])>::max_encoded_len()
+		}
+	}
+
+	impl TypeInfo for RingVerifierData {
+		type Identity = (u32, [u8; RING_VERIFIER_KEY_SERIALIZED_LEN]);
+
+		fn type_info() -> scale_info::Type {
+			Self::Identity::type_info()
+		}
+	}
+
 	/// Ring VRF signature.
-	#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, MaxEncodedLen, TypeInfo)]
+	#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, MaxEncodedLen, PassByCodec, TypeInfo)]
 	pub struct RingVrfSignature {
 		/// VRF (pre)outputs.
 		pub outputs: VrfIosVec<VrfOutput>,
@@ -830,7 +1031,9 @@ mod tests {
 		let public = pair.to_public();
 
 		assert_eq!(public.0.size_of_serialized(), PUBLIC_SERIALIZED_LEN);
-		assert_eq!(ring_ctx.max_keyset_size(), RING_DOMAIN_SIZE - 257);
+		assert_eq!(ring_ctx.max_keyset_size(), RING_DOMAIN_SIZE - RING_DOMAIN_OVERHEAD);
+		let verifier_data = ring_ctx.verifier_data(&[Pair::from_seed(DEV_SEED).public()]).unwrap();
+		assert_eq!(verifier_data.verifier_key.compressed_size(), RING_VERIFIER_KEY_SERIALIZED_LEN);
 
 		// Wrapper
 		let inputs: Vec<_> = (0..MAX_VRF_IOS - 1).map(|_| VrfInput::new(b"", &[])).collect();
@@ -948,6 +1151,62 @@ mod tests {
 		assert_eq!(expected, decoded);
 	}
 
+	fn sign_data_parts() -> VrfSignDataParts {
+		VrfSignDataParts {
+			transcript_label: b"mydata".to_vec(),
+			transcript_data: vec![b"tdata".to_vec()],
+			vrf_inputs: vec![
+				(b"dom1".to_vec(), b"foo".to_vec()),
+				(b"dom2".to_vec(), b"bar".to_vec()),
+			],
+		}
+	}
+
+	#[test]
+	fn vrf_sign_data_parts_match_sign_data() {
+		let pair = Pair::from_seed(DEV_SEED);
+
+		let i1 = VrfInput::new(b"dom1", b"foo");
+		let i2 = VrfInput::new(b"dom2", b"bar");
+		let data = VrfSignData::new_unchecked(b"mydata", &[b"tdata"], [i1, i2]);
+
+		let parts = sign_data_parts();
+		let decoded = VrfSignDataParts::decode(&mut parts.encode().as_slice()).unwrap();
+		assert_eq!(parts, decoded);
+
+		let rebuilt = decoded.to_sign_data().unwrap();
+		assert_eq!(data.challenge::<32>(), rebuilt.challenge::<32>());
+
+		let signature = pair.vrf_sign(&data);
+		assert!(pair.public().vrf_verify(&rebuilt, &signature));
+	}
+
+	#[test]
+	fn vrf_sign_data_records_its_parts() {
+		let mut data = sign_data_parts().to_sign_data().unwrap();
+		assert_eq!(data.parts(), Some(&sign_data_parts()));
+
+		data.push_transcript_data(b"more");
+		let rebuilt = data.parts().unwrap().to_sign_data().unwrap();
+		assert_eq!(data.challenge::<32>(), rebuilt.challenge::<32>());
+		assert_eq!(data.parts(), rebuilt.parts());
+
+		// The data a `VrfInput` is constructed from isn't kept.
+		data.push_vrf_input(VrfInput::new(b"dom3", b"baz")).unwrap();
+		assert!(data.parts().is_none());
+
+		let i1 = VrfInput::new(b"dom1", b"foo");
+		assert!(VrfSignData::new_unchecked(b"mydata", &[b"tdata"], [i1]).parts().is_none());
+	}
+
+	#[test]
+	fn vrf_sign_data_parts_with_too_many_inputs_fail() {
+		let mut parts = sign_data_parts();
+		parts.vrf_inputs = vec![(b"dom".to_vec(), b"data".to_vec()); MAX_VRF_IOS as usize + 1];
+
+		assert!(parts.to_sign_data().is_err());
+	}
+
 	#[test]
 	fn ring_vrf_sign_verify() {
 		let ring_ctx = RingContext::new_testing();
@@ -993,6 +1252,98 @@ mod tests {
 		assert!(!signature.verify(&data, &verifier));
 	}
 
+	#[test]
+	fn ring_vrf_verify_with_sign_data_parts() {
+		let ring_ctx = RingContext::new_testing();
+
+		let mut pks: Vec<_> = (0..16).map(|i| Pair::from_seed(&[i as u8; 32]).public()).collect();
+		let pair = Pair::from_seed(DEV_SEED);
+		let prover_idx = 7;
+		pks[prover_idx] = pair.public();
+
+		let i1 = VrfInput::new(b"dom1", b"foo");
+		let i2 = VrfInput::new(b"dom2", b"bar");
+		let data = VrfSignData::new_unchecked(b"mydata", &[b"tdata"], [i1, i2]);
+
+		let prover = ring_ctx.prover(&pks, prover_idx).unwrap();
+		let signature = pair.ring_vrf_sign(&data, &prover);
+
+		let verifier = ring_ctx.verifier(&pks).unwrap();
+		assert!(signature.verify(&sign_data_parts().to_sign_data().unwrap(), &verifier));
+
+		let mut other = sign_data_parts();
+		other.transcript_data[0] = b"other".to_vec();
+		assert!(!signature.verify(&other.to_sign_data().unwrap(), &verifier));
+	}
+
+	#[test]
+	fn ring_vrf_verify_with_verifier_data() {
+		let ring_ctx = RingContext::new_testing();
+
+		let mut pks: Vec<_> = (0..16).map(|i| Pair::from_seed(&[i as u8; 32]).public()).collect();
+		let pair = Pair::from_seed(DEV_SEED);
+		let prover_idx = 11;
+		pks[prover_idx] = pair.public();
+
+		let data =
+			VrfSignData::new_unchecked(b"mydata", &[b"tdata"], [VrfInput::new(b"dom", b"foo")]);
+		let prover = ring_ctx.prover(&pks, prover_idx).unwrap();
+		let signature = pair.ring_vrf_sign(&data, &prover);
+
+		let verifier_data = ring_ctx.verifier_data(&pks).unwrap();
+		let encoded = verifier_data.encode();
+
+		let decoded = RingVerifierData::decode(&mut encoded.as_slice()).unwrap();
+		assert_eq!(decoded.domain_size as usize, RING_DOMAIN_SIZE);
+		assert!(signature.verify(&data, &decoded.clone().into()));
+
+		// The verifier only works with the domain size of the context.
+		let smaller_domain =
+			RingVerifierData { domain_size: decoded.domain_size / 2, ..decoded.clone() };
+		assert!(!signature.verify(&data, &smaller_domain.into()));
+
+		// The last byte belongs to the verifier key, so the key isn't shorter than expected.
+		let mut tampered = encoded.clone();
+		*tampered.last_mut().unwrap() ^= 1;
+		assert!(RingVerifierData::decode(&mut tampered.as_slice())
+			.map_or(true, |tampered| !signature.verify(&data, &tampered.into())));
+
+		// Data committing to another ring doesn't verify the signature.
+		let other_data = ring_ctx.verifier_data(&pks[..15]).unwrap();
+		assert!(!signature.verify(&data, &other_data.into()));
+	}
+
+	#[test]
+	fn ring_verifier_data_with_invalid_domain_size_fails_to_decode() {
+		let ring_ctx = RingContext::new_testing();
+		let pks: Vec<_> = (0..4).map(|i| Pair::from_seed(&[i as u8; 32]).public()).collect();
+
+		let mut encoded = ring_ctx.verifier_data(&pks).unwrap().encode();
+		encoded[..4].copy_from_slice(&1000u32.encode());
+		assert!(RingVerifierData::decode(&mut encoded.as_slice()).is_err());
+
+		encoded[..4].copy_from_slice(&(2 * RING_DOMAIN_SIZE as u32).encode());
+		assert!(RingVerifierData::decode(&mut encoded.as_slice()).is_err());
+	}
+
+	#[test]
+	fn ring_larger_than_max_keyset_size_is_rejected() {
+		let ring_ctx = RingContext::new_testing();
+
+		let pks: Vec<_> = (0..=ring_ctx.max_keyset_size())
+			.map(|i| {
+				Pair::from_seed(&[(i % 256) as u8, (i / 256) as u8].repeat(16).try_into().unwrap())
+					.public()
+			})
+			.collect();
+
+		assert!(ring_ctx.prover(&pks, 0).is_none());
+		assert!(ring_ctx.verifier(&pks).is_none());
+		assert!(ring_ctx.verifier_data(&pks).is_none());
+
+		assert!(ring_ctx.verifier_data(&pks[1..]).is_some());
+	}
+
 	#[test]
 	fn encode_decode_ring_vrf_signature() {
 		let ring_ctx = RingContext::new_testing();
@@ -1025,6 +1376,18 @@ mod tests {
 
 		let decoded = RingVrfSignature::decode(&mut bytes.as_slice()).unwrap();
 		assert_eq!(expected, decoded);
+
+		// The last bytes of the Pedersen signature and the ring proof are used, so neither is
+		// shorter than expected. Longer ones would fail to serialize.
+		let verifier = ring_ctx.verifier(&pks).unwrap();
+		assert!(expected.verify(&data, &verifier));
+		let signature_end = bytes.len() - RING_PROOF_SERIALIZED_LEN;
+		for end in [signature_end, bytes.len()] {
+			let mut tampered = bytes.clone();
+			tampered[end - 1] ^= 1;
+			let tampered = RingVrfSignature::decode(&mut tampered.as_slice()).unwrap();
+			assert!(!tampered.verify(&data, &verifier));
+		}
 	}
 
 	#[test]
@@ -54,6 +54,20 @@ pub trait OffchainStorage: Clone + Send + Sync {
 		old_value: Option<&[u8]>,
 		new_value: &[u8],
 	) -> bool;
+
+	/// Retrieve up to `count` keys under given prefix that were written by offchain indexing.
+	///
+	/// Only keys starting with `key_prefix` and strictly greater than `start_key` are returned,
+	/// in lexicographic order. Returns `None` if the storage can't enumerate indexed keys.
+	fn indexed_keys(
+		&self,
+		_prefix: &[u8],
+		_key_prefix: &[u8],
+		_start_key: Option<&[u8]>,
+		_count: usize,
+	) -> Option<Vec<Vec<u8>>> {
+		None
+	}
 }
 
 /// A type of supported crypto.
@@ -581,6 +581,43 @@ pub mod vrf {
 		}
 	}
 
+	/// The data a [`VrfTranscript`] is built from.
+	///
+	/// In contrast to [`VrfTranscript`], this can be encoded, for example to forward VRF sign data
+	/// to a remote signer.
+	#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+	pub struct VrfTranscriptParts {
+		/// Label of the transcript.
+		pub label: Vec<u8>,
+		/// The `(domain, message)` pairs appended to the transcript.
+		pub data: Vec<(Vec<u8>, Vec<u8>)>,
+	}
+
+	impl VrfTranscriptParts {
+		/// Build the [`VrfTranscript`].
+		///
+		/// The result is the same as the one of [`VrfTranscript::new`] with the same arguments.
+		pub fn to_transcript(&self) -> VrfTranscript {
+			// SAFETY: `merlin` asks for `'static` labels to steer users towards constant ones.
+			// The labels are absorbed into the transcript state right away and no reference to
+			// them is kept, the transcript doesn't borrow anything.
+			let mut transcript = merlin::Transcript::new(unsafe { extend_label(&self.label) });
+			for (domain, message) in &self.data {
+				transcript.append_message(unsafe { extend_label(domain) }, message);
+			}
+			VrfTranscript(transcript)
+		}
+	}
+
+	/// Extend the lifetime of a transcript label.
+	///
+	/// # Safety
+	///
+	/// The result must only be passed to `merlin::Transcript` methods, which don't keep it.
+	unsafe fn extend_label(label: &[u8]) -> &'static [u8] {
+		&*(label as *const [u8])
+	}
+
 	/// VRF input.
 	///
 	/// Technically a transcript used by the Fiat-Shamir transform.
@@ -593,11 +630,13 @@ pub mod vrf {
 		pub(super) transcript: VrfTranscript,
 		/// Extra transcript data to be signed by the VRF.
 		pub(super) extra: Option<VrfTranscript>,
+		/// The data `transcript` and `extra` were built from, if known.
+		pub(super) parts: Option<VrfSignDataParts>,
 	}
 
 	impl From<VrfInput> for VrfSignData {
 		fn from(transcript: VrfInput) -> Self {
-			VrfSignData { transcript, extra: None }
+			VrfSignData { transcript, extra: None, parts: None }
 		}
 	}
 
@@ -621,8 +660,40 @@ pub mod vrf {
 		/// `extra` will not contribute to the VRF output bytes.
 		pub fn with_extra(mut self, extra: VrfTranscript) -> Self {
 			self.extra = Some(extra);
+			// A `VrfTranscript` doesn't keep the data it was built from.
+			self.parts = None;
 			self
 		}
+
+		/// The data this instance was built from.
+		///
+		/// Only known for instances built with [`VrfSignDataParts::to_sign_data`].
+		pub fn parts(&self) -> Option<&VrfSignDataParts> {
+			self.parts.as_ref()
+		}
+	}
+
+	/// The data a [`VrfSignData`] is built from.
+	///
+	/// In contrast to [`VrfSignData`], this can be encoded, for example to forward the data to a
+	/// remote signer.
+	#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+	pub struct VrfSignDataParts {
+		/// Transcript data contributing to the VRF output.
+		pub transcript: VrfTranscriptParts,
+		/// Extra transcript data to be signed, see [`VrfSignData::with_extra`].
+		pub extra: Option<VrfTranscriptParts>,
+	}
+
+	impl VrfSignDataParts {
+		/// Build the [`VrfSignData`], which keeps these parts.
+		pub fn to_sign_data(&self) -> VrfSignData {
+			VrfSignData {
+				transcript: self.transcript.to_transcript(),
+				extra: self.extra.as_ref().map(VrfTranscriptParts::to_transcript),
+				parts: Some(self.clone()),
+			}
+		}
 	}
 
 	/// VRF signature data
@@ -1089,6 +1160,37 @@ mod tests {
 		assert!(public.vrf_verify(&data, &signature));
 	}
 
+	#[test]
+	fn vrf_sign_data_parts_rebuild_the_sign_data() {
+		let pair = Pair::from_seed(b"12345678901234567890123456789012");
+		let public = pair.public();
+
+		let extra = VrfTranscript::new(b"extra", &[(b"domain2", b"data2")]);
+		let data = VrfTranscript::new(b"label", &[(b"domain1", b"data1")])
+			.into_sign_data()
+			.with_extra(extra);
+		assert!(data.parts().is_none());
+
+		let parts = VrfSignDataParts {
+			transcript: VrfTranscriptParts {
+				label: b"label".to_vec(),
+				data: vec![(b"domain1".to_vec(), b"data1".to_vec())],
+			},
+			extra: Some(VrfTranscriptParts {
+				label: b"extra".to_vec(),
+				data: vec![(b"domain2".to_vec(), b"data2".to_vec())],
+			}),
+		};
+		let decoded = VrfSignDataParts::decode(&mut parts.encode().as_slice()).unwrap();
+		assert_eq!(parts, decoded);
+		let rebuilt = decoded.to_sign_data();
+		assert_eq!(rebuilt.parts(), Some(&parts));
+
+		assert!(public.vrf_verify(&rebuilt, &pair.vrf_sign(&data)));
+		assert!(public.vrf_verify(&data, &pair.vrf_sign(&rebuilt)));
+		assert_eq!(pair.vrf_output(data.as_ref()), pair.vrf_output(rebuilt.as_ref()));
+	}
+
 	#[test]
 	fn vrf_make_bytes_matches() {
 		let pair = Pair::from_seed(b"12345678901234567890123456789012");
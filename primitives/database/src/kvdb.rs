@@ -41,6 +41,22 @@ where
 }
 
 impl<D: KeyValueDB> DbAdapter<D> {
+	// Calls `f` with the key-value pairs starting with `prefix`. Returns `false` if `f` did.
+	fn iter_with_prefix(
+		&self,
+		col: ColumnId,
+		prefix: &[u8],
+		f: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+	) -> bool {
+		for item in self.0.iter_with_prefix(col, prefix) {
+			let (key, value) = handle_err(item);
+			if !f(&key, &value) {
+				return false
+			}
+		}
+		true
+	}
+
 	// Returns counter key and counter value if it exists.
 	fn read_counter(&self, col: ColumnId, key: &[u8]) -> error::Result<(Vec<u8>, Option<u32>)> {
 		// Add a key suffix for the counter
@@ -115,4 +131,45 @@ impl<D: KeyValueDB, H: Clone + AsRef<[u8]>> Database<H> for DbAdapter<D> {
 	fn contains(&self, col: ColumnId, key: &[u8]) -> bool {
 		handle_err(self.0.has_key(col, key))
 	}
+
+	fn supports_ordered_iteration(&self) -> bool {
+		true
+	}
+
+	fn iter_prefix(
+		&self,
+		col: ColumnId,
+		prefix: &[u8],
+		start: Option<&[u8]>,
+		f: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+	) {
+		let start = match start {
+			Some(start) if start > prefix => start,
+			_ => {
+				self.iter_with_prefix(col, prefix, f);
+				return
+			},
+		};
+		// All the keys starting with `prefix` are less than `start`.
+		if !start.starts_with(prefix) {
+			return
+		}
+
+		// `KeyValueDB` can't seek to a key, so the keys not less than `start` are visited as the
+		// keys starting with `start`, followed by the keys starting with each of its greater
+		// siblings, from the longest to the shortest.
+		if !self.iter_with_prefix(col, start, f) {
+			return
+		}
+		let mut sibling = start.to_vec();
+		for len in (prefix.len() + 1..=start.len()).rev() {
+			sibling.truncate(len);
+			while sibling[len - 1] < u8::MAX {
+				sibling[len - 1] += 1;
+				if !self.iter_with_prefix(col, &sibling, f) {
+					return
+				}
+			}
+		}
+	}
 }
@@ -115,6 +115,29 @@ pub trait Database<H: Clone + AsRef<[u8]>>: Send + Sync {
 	///
 	/// Not all database implementations use a prefix for keys, so this function may be a noop.
 	fn sanitize_key(&self, _key: &mut Vec<u8>) {}
+
+	/// Check if database supports iterating over the keys of a column in order.
+	///
+	/// Returns `false` by default.
+	fn supports_ordered_iteration(&self) -> bool {
+		false
+	}
+
+	/// Call `f` with every key-value pair in `col` whose key starts with `prefix`, in ascending
+	/// key order, until `f` returns `false`.
+	///
+	/// If `start` is given, the iteration starts at the first key that is not less than `start`.
+	///
+	/// Does nothing if the database doesn't support ordered iteration, see
+	/// [`Self::supports_ordered_iteration`].
+	fn iter_prefix(
+		&self,
+		_col: ColumnId,
+		_prefix: &[u8],
+		_start: Option<&[u8]>,
+		_f: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+	) {
+	}
 }
 
 impl<H> std::fmt::Debug for dyn Database<H> {
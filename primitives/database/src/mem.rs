@@ -73,6 +73,36 @@ where
 		let s = self.0.read();
 		s.get(&col).and_then(|c| c.get(key).map(|(_, v)| v.clone()))
 	}
+
+	fn supports_ordered_iteration(&self) -> bool {
+		true
+	}
+
+	fn iter_prefix(
+		&self,
+		col: ColumnId,
+		prefix: &[u8],
+		start: Option<&[u8]>,
+		f: &mut dyn FnMut(&[u8], &[u8]) -> bool,
+	) {
+		let s = self.0.read();
+		let mut items = s
+			.get(&col)
+			.map(|c| {
+				c.iter()
+					.filter(|(k, _)| {
+						k.starts_with(prefix) && start.map_or(true, |start| k.as_slice() >= start)
+					})
+					.collect::<Vec<_>>()
+			})
+			.unwrap_or_default();
+		items.sort_unstable_by(|a, b| a.0.cmp(b.0));
+		for (key, (_, value)) in items {
+			if !f(key, value) {
+				break
+			}
+		}
+	}
 }
 
 impl MemDb {
@@ -26,13 +26,46 @@
 //!   pallet's author.
 //! - deserialize the `GenesisConfig` from given json blob and put `GenesisConfig` into the state
 //!   storage. Allows to build customized configuration.
+//! - provide the named presets of the runtime, e.g. the `development` or `local_testnet` ones, as
+//!   JSON patches to apply on top of the default `GenesisConfig`. This allows to build chain specs
+//!   out of the runtime blob alone.
 //!
 //! Providing externalities with empty storage and putting `GenesisConfig` into storage allows to
 //! catch and build the raw storage of `GenesisConfig` which is the foundation for genesis block.
 
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use sp_std::vec::Vec;
+
 /// The result type alias, used in build methods. `Err` contains formatted error message.
 pub type Result = core::result::Result<(), sp_runtime::RuntimeString>;
 
+/// The identifier of a genesis preset.
+///
+/// The identifier is the UTF-8 encoded name of the preset.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+pub struct PresetId(Vec<u8>);
+
+impl From<&str> for PresetId {
+	fn from(name: &str) -> Self {
+		Self(name.as_bytes().to_vec())
+	}
+}
+
+impl<'a> TryFrom<&'a PresetId> for &'a str {
+	type Error = core::str::Utf8Error;
+
+	fn try_from(id: &'a PresetId) -> core::result::Result<&'a str, Self::Error> {
+		core::str::from_utf8(&id.0)
+	}
+}
+
+/// The name of the preset of a development chain.
+pub const DEV_RUNTIME_PRESET: &str = "development";
+
+/// The name of the preset of a local testnet.
+pub const LOCAL_TESTNET_RUNTIME_PRESET: &str = "local_testnet";
+
 sp_api::decl_runtime_apis! {
 	/// API to interact with GenesisConfig for the runtime
 	pub trait GenesisBuilder {
@@ -50,5 +83,21 @@ sp_api::decl_runtime_apis! {
 		///
 		/// Please note that provided json blob must contain all `GenesisConfig` fields, no defaults will be used.
 		fn build_config(json: sp_std::vec::Vec<u8>) -> Result;
+
+		/// Returns a JSON blob representation of the named preset, or the default `GenesisConfig` if
+		/// `id` is `None`.
+		///
+		/// A preset is a JSON patch of the default `GenesisConfig`: it only contains the fields which
+		/// differ from the default values. Merging the patch into the JSON blob returned by
+		/// [`Self::create_default_config`] gives the full `GenesisConfig`, which can then be passed
+		/// to [`Self::build_config`].
+		///
+		/// Returns `None` if the runtime doesn't provide a preset named `id`.
+		#[api_version(2)]
+		fn get_preset(id: &Option<PresetId>) -> Option<sp_std::vec::Vec<u8>>;
+
+		/// Returns the identifiers of all the presets provided by the runtime.
+		#[api_version(2)]
+		fn preset_names() -> sp_std::vec::Vec<PresetId>;
 	}
 }
@@ -109,7 +109,7 @@ use sp_core::{
 use sp_core::bls377;
 
 #[cfg(feature = "std")]
-use sp_trie::{LayoutV0, LayoutV1, TrieConfiguration};
+use sp_trie::{proof_size_extension::ProofSizeExt, LayoutV0, LayoutV1, TrieConfiguration};
 
 use sp_runtime_interface::{
 	pass_by::{PassBy, PassByCodec},
@@ -1225,6 +1225,25 @@ pub trait Crypto {
 			.bandersnatch_generate_new(id, seed)
 			.expect("`bandernatch_generate` failed")
 	}
+
+	/// Verify a `bandersnatch` ring-VRF signature.
+	///
+	/// The ring verifier is built from `verifier_data`, which commits to the ring public keys and
+	/// is computed with [`bandersnatch::ring_vrf::RingContext::verifier_data`]. The signed `data`
+	/// is rebuilt from its parts.
+	///
+	/// Returns `true` when the signature was produced over `data` by the owner of one of the
+	/// ring keys.
+	#[cfg(feature = "bandersnatch-experimental")]
+	fn bandersnatch_ring_vrf_verify(
+		signature: &bandersnatch::ring_vrf::RingVrfSignature,
+		data: &bandersnatch::vrf::VrfSignDataParts,
+		verifier_data: &bandersnatch::ring_vrf::RingVerifierData,
+	) -> bool {
+		let Ok(data) = data.to_sign_data() else { return false };
+
+		signature.verify(&data, &verifier_data.clone().into())
+	}
 }
 
 /// Interface that provides functions for hashing with different algorithms.
@@ -1299,6 +1318,22 @@ pub trait OffchainIndex {
 	}
 }
 
+/// The value returned by [`storage_proof_size::storage_proof_size`] if the storage proof is not
+/// recorded.
+pub const PROOF_RECORDING_DISABLED: u64 = u64::MAX;
+
+/// Interface that provides access to the size of the storage proof recorded so far.
+#[runtime_interface]
+pub trait StorageProofSize {
+	/// Returns the size in bytes of the storage proof recorded so far.
+	///
+	/// Returns [`PROOF_RECORDING_DISABLED`] if the proof is not recorded.
+	fn storage_proof_size(&mut self) -> u64 {
+		self.extension::<ProofSizeExt>()
+			.map_or(PROOF_RECORDING_DISABLED, |ext| ext.storage_proof_size())
+	}
+}
+
 #[cfg(feature = "std")]
 sp_externalities::decl_extension! {
 	/// Deprecated verification context.
@@ -1796,6 +1831,7 @@ pub type SubstrateHostFunctions = (
 	crate::trie::HostFunctions,
 	offchain_index::HostFunctions,
 	transaction_index::HostFunctions,
+	storage_proof_size::HostFunctions,
 );
 
 #[cfg(test)]
@@ -1935,4 +1971,42 @@ mod tests {
 			));
 		});
 	}
+
+	#[cfg(feature = "bandersnatch-experimental")]
+	#[test]
+	fn bandersnatch_ring_vrf_verify_works() {
+		use bandersnatch::{
+			ring_vrf::RingContext,
+			vrf::{VrfSignData, VrfSignDataParts},
+		};
+		use sp_core::Pair as _;
+
+		let ring_ctx = RingContext::new_testing();
+		let pairs: Vec<_> = (0..4u8).map(|i| bandersnatch::Pair::from_seed(&[i; 32])).collect();
+		let ring: Vec<_> = pairs.iter().map(|p| p.public()).collect();
+
+		let parts = VrfSignDataParts {
+			transcript_label: b"label".to_vec(),
+			transcript_data: vec![b"data".to_vec()],
+			vrf_inputs: vec![(b"domain".to_vec(), b"input".to_vec())],
+		};
+		let data: VrfSignData = parts.to_sign_data().unwrap();
+
+		let prover = ring_ctx.prover(&ring, 2).unwrap();
+		let signature = pairs[2].ring_vrf_sign(&data, &prover);
+
+		let verifier_data = ring_ctx.verifier_data(&ring).unwrap();
+		let other_verifier_data = ring_ctx.verifier_data(&ring[..3]).unwrap();
+
+		BasicExternalities::default().execute_with(|| {
+			assert!(crypto::bandersnatch_ring_vrf_verify(&signature, &parts, &verifier_data));
+
+			// Signature doesn't verify against a different ring.
+			assert!(!crypto::bandersnatch_ring_vrf_verify(
+				&signature,
+				&parts,
+				&other_verifier_data
+			));
+		});
+	}
 }
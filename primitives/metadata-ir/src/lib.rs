@@ -85,6 +85,7 @@ mod test {
 				signature_ty: meta_type::<()>(),
 				extra_ty: meta_type::<()>(),
 				signed_extensions: vec![],
+				transaction_extensions: vec![],
 			},
 			ty: meta_type::<()>(),
 			apis: vec![],
@@ -170,6 +170,11 @@ pub struct ExtrinsicMetadataIR<T: Form = MetaForm> {
 	pub extra_ty: T::Type,
 	/// The signed extensions in the order they appear in the extrinsic.
 	pub signed_extensions: Vec<SignedExtensionMetadataIR<T>>,
+	/// The transaction extensions of general transactions in the order they appear in the
+	/// extrinsic.
+	///
+	/// Note: Not part of metadata V14 and V15, which have no field for them.
+	pub transaction_extensions: Vec<SignedExtensionMetadataIR<T>>,
 }
 
 impl IntoPortable for ExtrinsicMetadataIR {
@@ -184,6 +189,7 @@ impl IntoPortable for ExtrinsicMetadataIR {
 			signature_ty: registry.register_type(&self.signature_ty),
 			extra_ty: registry.register_type(&self.extra_ty),
 			signed_extensions: registry.map_into_portable(self.signed_extensions),
+			transaction_extensions: registry.map_into_portable(self.transaction_extensions),
 		}
 	}
 }
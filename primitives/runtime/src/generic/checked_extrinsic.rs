@@ -20,32 +20,55 @@
 
 use crate::{
 	traits::{
-		self, DispatchInfoOf, Dispatchable, MaybeDisplay, Member, PostDispatchInfoOf,
-		SignedExtension, ValidateUnsigned,
+		self, AsAuthorizedOrigin, DispatchInfoOf, Dispatchable, MaybeDisplay, Member,
+		PostDispatchInfoOf, SignedExtension, TransactionExtension, ValidateUnsigned,
 	},
-	transaction_validity::{TransactionSource, TransactionValidity},
+	transaction_validity::{InvalidTransaction, TransactionSource, TransactionValidity},
 };
+use codec::Encode;
+
+/// The format of a [`CheckedExtrinsic`].
+#[derive(PartialEq, Eq, Clone, sp_core::RuntimeDebug)]
+pub enum ExtrinsicFormat<AccountId, Extra, Extension> {
+	/// Extrinsic without any origin, i.e. an inherent or an unsigned transaction validated with
+	/// [`ValidateUnsigned`].
+	Bare,
+	/// Transaction signed by the given account, extended with the given [`SignedExtension`].
+	Signed(AccountId, Extra),
+	/// Transaction whose origin is authorized by the given [`TransactionExtension`].
+	///
+	/// There is no signer the [`SignedExtension`] could be handed, so it only runs its checks
+	/// for unsigned transactions, e.g. the block weight and length limits. Checks that need the
+	/// sender, like the nonce or the payment of fees, have to be part of the `Extension`, for
+	/// example by wrapping the signed extensions into [`AsTransactionExtension`]s placed after
+	/// the extension that authorizes the origin.
+	///
+	/// [`AsTransactionExtension`]: crate::traits::AsTransactionExtension
+	General(Extension),
+}
 
 /// Definition of something that the external world might want to say; its
 /// existence implies that it has been checked and is good, particularly with
 /// regards to the signature.
 #[derive(PartialEq, Eq, Clone, sp_core::RuntimeDebug)]
-pub struct CheckedExtrinsic<AccountId, Call, Extra> {
+pub struct CheckedExtrinsic<AccountId, Call, Extra, Extension = ()> {
 	/// Who this purports to be from and the number of extrinsics have come before
-	/// from the same signer, if anyone (note this is not a signature).
-	pub signed: Option<(AccountId, Extra)>,
+	/// from the same signer, if anyone (note this is not a signature), or the extension
+	/// authorizing a general transaction.
+	pub format: ExtrinsicFormat<AccountId, Extra, Extension>,
 
 	/// The function that should be called.
 	pub function: Call,
 }
 
-impl<AccountId, Call, Extra, RuntimeOrigin> traits::Applyable
-	for CheckedExtrinsic<AccountId, Call, Extra>
+impl<AccountId, Call, Extra, Extension, RuntimeOrigin> traits::Applyable
+	for CheckedExtrinsic<AccountId, Call, Extra, Extension>
 where
 	AccountId: Member + MaybeDisplay,
-	Call: Member + Dispatchable<RuntimeOrigin = RuntimeOrigin>,
+	Call: Member + Encode + Dispatchable<RuntimeOrigin = RuntimeOrigin>,
 	Extra: SignedExtension<AccountId = AccountId, Call = Call>,
-	RuntimeOrigin: From<Option<AccountId>>,
+	Extension: TransactionExtension<Call>,
+	RuntimeOrigin: From<Option<AccountId>> + AsAuthorizedOrigin,
 {
 	type Call = Call;
 
@@ -57,12 +80,28 @@ where
 		info: &DispatchInfoOf<Self::Call>,
 		len: usize,
 	) -> TransactionValidity {
-		if let Some((ref id, ref extra)) = self.signed {
-			Extra::validate(extra, id, &self.function, info, len)
-		} else {
-			let valid = Extra::validate_unsigned(&self.function, info, len)?;
-			let unsigned_validation = U::validate_unsigned(source, &self.function)?;
-			Ok(valid.combine_with(unsigned_validation))
+		match self.format {
+			ExtrinsicFormat::Bare => {
+				let valid = Extra::validate_unsigned(&self.function, info, len)?;
+				let unsigned_validation = U::validate_unsigned(source, &self.function)?;
+				Ok(valid.combine_with(unsigned_validation))
+			},
+			ExtrinsicFormat::Signed(ref id, ref extra) =>
+				Extra::validate(extra, id, &self.function, info, len),
+			ExtrinsicFormat::General(ref extension) => {
+				let valid = Extra::validate_unsigned(&self.function, info, len)?;
+				let (extension_valid, _, origin) = extension.validate(
+					RuntimeOrigin::from(None),
+					&self.function,
+					info,
+					len,
+					&self.function.encode(),
+				)?;
+				if !origin.is_transaction_authorized() {
+					return Err(InvalidTransaction::UnknownOrigin.into())
+				}
+				Ok(valid.combine_with(extension_valid))
+			},
 		}
 	}
 
@@ -71,26 +110,68 @@ where
 		info: &DispatchInfoOf<Self::Call>,
 		len: usize,
 	) -> crate::ApplyExtrinsicResultWithInfo<PostDispatchInfoOf<Self::Call>> {
-		let (maybe_who, maybe_pre) = if let Some((id, extra)) = self.signed {
-			let pre = Extra::pre_dispatch(extra, &id, &self.function, info, len)?;
-			(Some(id), Some(pre))
-		} else {
-			Extra::pre_dispatch_unsigned(&self.function, info, len)?;
-			U::pre_dispatch(&self.function)?;
-			(None, None)
+		let extension = match self.format {
+			ExtrinsicFormat::Bare => {
+				Extra::pre_dispatch_unsigned(&self.function, info, len)?;
+				U::pre_dispatch(&self.function)?;
+				return dispatch_with_signed_extension::<Extra>(
+					None,
+					RuntimeOrigin::from(None),
+					self.function,
+					info,
+					len,
+				)
+			},
+			ExtrinsicFormat::Signed(id, extra) => {
+				let pre = Extra::pre_dispatch(extra, &id, &self.function, info, len)?;
+				return dispatch_with_signed_extension::<Extra>(
+					Some(pre),
+					RuntimeOrigin::from(Some(id)),
+					self.function,
+					info,
+					len,
+				)
+			},
+			ExtrinsicFormat::General(extension) => extension,
 		};
-		let res = self.function.dispatch(RuntimeOrigin::from(maybe_who));
+
+		Extra::pre_dispatch_unsigned(&self.function, info, len)?;
+		let (_, val, origin) = extension.validate(
+			RuntimeOrigin::from(None),
+			&self.function,
+			info,
+			len,
+			&self.function.encode(),
+		)?;
+		if !origin.is_transaction_authorized() {
+			return Err(InvalidTransaction::UnknownOrigin.into())
+		}
+		let pre = extension.prepare(val, &origin, &self.function, info, len)?;
+		let res = self.function.dispatch(origin);
 		let post_info = match res {
 			Ok(info) => info,
 			Err(err) => err.post_info,
 		};
-		Extra::post_dispatch(
-			maybe_pre,
-			info,
-			&post_info,
-			len,
-			&res.map(|_| ()).map_err(|e| e.error),
-		)?;
+		let result = res.map(|_| ()).map_err(|e| e.error);
+		Extension::post_dispatch(pre, info, &post_info, len, &result)?;
+		Extra::post_dispatch(None, info, &post_info, len, &result)?;
 		Ok(res)
 	}
 }
+
+/// Dispatch `function` with `origin` and run the post dispatch logic of the signed extension.
+fn dispatch_with_signed_extension<Extra: SignedExtension>(
+	maybe_pre: Option<Extra::Pre>,
+	origin: <Extra::Call as Dispatchable>::RuntimeOrigin,
+	function: Extra::Call,
+	info: &DispatchInfoOf<Extra::Call>,
+	len: usize,
+) -> crate::ApplyExtrinsicResultWithInfo<PostDispatchInfoOf<Extra::Call>> {
+	let res = function.dispatch(origin);
+	let post_info = match res {
+		Ok(info) => info,
+		Err(err) => err.post_info,
+	};
+	Extra::post_dispatch(maybe_pre, info, &post_info, len, &res.map(|_| ()).map_err(|e| e.error))?;
+	Ok(res)
+}
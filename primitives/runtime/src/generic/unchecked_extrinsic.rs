@@ -18,10 +18,10 @@
 //! Generic implementation of an unchecked (pre-verification) extrinsic.
 
 use crate::{
-	generic::CheckedExtrinsic,
+	generic::{CheckedExtrinsic, ExtrinsicFormat},
 	traits::{
-		self, Checkable, Extrinsic, ExtrinsicMetadata, IdentifyAccount, MaybeDisplay, Member,
-		SignaturePayload, SignedExtension,
+		self, Checkable, Dispatchable, Extrinsic, ExtrinsicMetadata, IdentifyAccount, MaybeDisplay,
+		Member, SignaturePayload, SignedExtension, SignedExtensionMetadata, TransactionExtension,
 	},
 	transaction_validity::{InvalidTransaction, TransactionValidityError},
 	OpaqueExtrinsic,
@@ -40,20 +40,51 @@ use sp_std::{fmt, prelude::*};
 /// the decoding fails.
 const EXTRINSIC_FORMAT_VERSION: u8 = 4;
 
+/// Version of the encoded format of a general [`UncheckedExtrinsic`].
+///
+/// Bare and signed extrinsics keep using [`EXTRINSIC_FORMAT_VERSION`] so that their encoding
+/// stays unchanged.
+const GENERAL_EXTRINSIC_FORMAT_VERSION: u8 = 5;
+
+/// Mask of the bits of the version byte that encode the type of the extrinsic.
+const EXTRINSIC_TYPE_MASK: u8 = 0b1100_0000;
+
+/// Bits of the version byte of a bare extrinsic.
+const BARE_EXTRINSIC: u8 = 0b0000_0000;
+
+/// Bits of the version byte of a signed extrinsic.
+const SIGNED_EXTRINSIC: u8 = 0b1000_0000;
+
+/// Bits of the version byte of a general extrinsic.
+const GENERAL_EXTRINSIC: u8 = 0b0100_0000;
+
 /// The `SingaturePayload` of `UncheckedExtrinsic`.
 type UncheckedSignaturePayload<Address, Signature, Extra> = (Address, Signature, Extra);
 
+/// What comes before the call in an [`UncheckedExtrinsic`].
+#[derive(PartialEq, Eq, Clone)]
+pub enum Preamble<Address, Signature, Extra, Extension> {
+	/// An extrinsic without a signature or any other extension data, i.e. an inherent or an
+	/// unsigned transaction.
+	Bare,
+	/// A transaction signed by the given address, along with the [`SignedExtension`] data.
+	Signed(Address, Signature, Extra),
+	/// A transaction without a signature, whose origin is authorized by the given
+	/// [`TransactionExtension`](traits::TransactionExtension).
+	General(Extension),
+}
+
 /// A extrinsic right from the external world. This is unchecked and so
 /// can contain a signature.
 #[derive(PartialEq, Eq, Clone)]
-pub struct UncheckedExtrinsic<Address, Call, Signature, Extra>
+pub struct UncheckedExtrinsic<Address, Call, Signature, Extra, Extension = ()>
 where
 	Extra: SignedExtension,
 {
 	/// The signature, address, number of extrinsics have come before from
 	/// the same signer and an era describing the longevity of this transaction,
-	/// if this is a signed extrinsic.
-	pub signature: Option<UncheckedSignaturePayload<Address, Signature, Extra>>,
+	/// if this is a signed extrinsic, or the transaction extension if this is a general one.
+	pub preamble: Preamble<Address, Signature, Extra, Extension>,
 	/// The function that should be called.
 	pub function: Call,
 }
@@ -70,15 +101,16 @@ impl<Address: TypeInfo, Signature: TypeInfo, Extra: TypeInfo> SignaturePayload
 /// `Vec<u8>`, but requires some logic to extract the signature and payload.
 ///
 /// See [`UncheckedExtrinsic::encode`] and [`UncheckedExtrinsic::decode`].
-impl<Address, Call, Signature, Extra> TypeInfo
-	for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra, Extension> TypeInfo
+	for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 where
 	Address: StaticTypeInfo,
 	Call: StaticTypeInfo,
 	Signature: StaticTypeInfo,
 	Extra: SignedExtension + StaticTypeInfo,
+	Extension: StaticTypeInfo,
 {
-	type Identity = UncheckedExtrinsic<Address, Call, Signature, Extra>;
+	type Identity = UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>;
 
 	fn type_info() -> Type {
 		Type::builder()
@@ -91,6 +123,7 @@ where
 				TypeParameter::new("Call", Some(meta_type::<Call>())),
 				TypeParameter::new("Signature", Some(meta_type::<Signature>())),
 				TypeParameter::new("Extra", Some(meta_type::<Extra>())),
+				TypeParameter::new("Extension", Some(meta_type::<Extension>())),
 			])
 			.docs(&["UncheckedExtrinsic raw bytes, requires custom decoding routine"])
 			// Because of the custom encoding, we can only accurately describe the encoding as an
@@ -100,29 +133,49 @@ where
 	}
 }
 
-impl<Address, Call, Signature, Extra: SignedExtension>
-	UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra: SignedExtension, Extension>
+	UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 {
 	/// New instance of a signed extrinsic aka "transaction".
 	pub fn new_signed(function: Call, signed: Address, signature: Signature, extra: Extra) -> Self {
-		Self { signature: Some((signed, signature, extra)), function }
+		Self { preamble: Preamble::Signed(signed, signature, extra), function }
 	}
 
 	/// New instance of an unsigned extrinsic aka "inherent".
 	pub fn new_unsigned(function: Call) -> Self {
-		Self { signature: None, function }
+		Self { preamble: Preamble::Bare, function }
+	}
+
+	/// New instance of a general extrinsic, i.e. a transaction authorized by `extension`.
+	pub fn new_general(function: Call, extension: Extension) -> Self {
+		Self { preamble: Preamble::General(extension), function }
+	}
+
+	/// Returns the signature payload if this is a signed extrinsic.
+	pub fn signature(&self) -> Option<(&Address, &Signature, &Extra)> {
+		match self.preamble {
+			Preamble::Signed(ref address, ref signature, ref extra) =>
+				Some((address, signature, extra)),
+			_ => None,
+		}
 	}
 }
 
-impl<Address: TypeInfo, Call: TypeInfo, Signature: TypeInfo, Extra: SignedExtension + TypeInfo>
-	Extrinsic for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<
+		Address: TypeInfo,
+		Call: TypeInfo,
+		Signature: TypeInfo,
+		Extra: SignedExtension + TypeInfo,
+		Extension: TypeInfo,
+	> Extrinsic for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 {
 	type Call = Call;
 
 	type SignaturePayload = UncheckedSignaturePayload<Address, Signature, Extra>;
 
 	fn is_signed(&self) -> Option<bool> {
-		Some(self.signature.is_some())
+		// General transactions are not inherents, so they are reported as signed.
+		Some(!matches!(self.preamble, Preamble::Bare))
 	}
 
 	fn new(function: Call, signed_data: Option<Self::SignaturePayload>) -> Option<Self> {
@@ -134,8 +187,8 @@ impl<Address: TypeInfo, Call: TypeInfo, Signature: TypeInfo, Extra: SignedExtens
 	}
 }
 
-impl<Address, AccountId, Call, Signature, Extra, Lookup> Checkable<Lookup>
-	for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, AccountId, Call, Signature, Extra, Extension, Lookup> Checkable<Lookup>
+	for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 where
 	Address: Member + MaybeDisplay,
 	Call: Encode + Member,
@@ -145,11 +198,11 @@ where
 	AccountId: Member + MaybeDisplay,
 	Lookup: traits::Lookup<Source = Address, Target = AccountId>,
 {
-	type Checked = CheckedExtrinsic<AccountId, Call, Extra>;
+	type Checked = CheckedExtrinsic<AccountId, Call, Extra, Extension>;
 
 	fn check(self, lookup: &Lookup) -> Result<Self::Checked, TransactionValidityError> {
-		Ok(match self.signature {
-			Some((signed, signature, extra)) => {
+		Ok(match self.preamble {
+			Preamble::Signed(signed, signature, extra) => {
 				let signed = lookup.lookup(signed)?;
 				let raw_payload = SignedPayload::new(self.function, extra)?;
 				if !raw_payload.using_encoded(|payload| signature.verify(payload, &signed)) {
@@ -157,9 +210,14 @@ where
 				}
 
 				let (function, extra, _) = raw_payload.deconstruct();
-				CheckedExtrinsic { signed: Some((signed, extra)), function }
+				CheckedExtrinsic { format: ExtrinsicFormat::Signed(signed, extra), function }
+			},
+			Preamble::General(extension) => CheckedExtrinsic {
+				format: ExtrinsicFormat::General(extension),
+				function: self.function,
 			},
-			None => CheckedExtrinsic { signed: None, function: self.function },
+			Preamble::Bare =>
+				CheckedExtrinsic { format: ExtrinsicFormat::Bare, function: self.function },
 		})
 	}
 
@@ -168,25 +226,36 @@ where
 		self,
 		lookup: &Lookup,
 	) -> Result<Self::Checked, TransactionValidityError> {
-		Ok(match self.signature {
-			Some((signed, _, extra)) => {
+		Ok(match self.preamble {
+			Preamble::Signed(signed, _, extra) => {
 				let signed = lookup.lookup(signed)?;
 				let raw_payload = SignedPayload::new(self.function, extra)?;
 				let (function, extra, _) = raw_payload.deconstruct();
-				CheckedExtrinsic { signed: Some((signed, extra)), function }
+				CheckedExtrinsic { format: ExtrinsicFormat::Signed(signed, extra), function }
 			},
-			None => CheckedExtrinsic { signed: None, function: self.function },
+			Preamble::General(extension) => CheckedExtrinsic {
+				format: ExtrinsicFormat::General(extension),
+				function: self.function,
+			},
+			Preamble::Bare =>
+				CheckedExtrinsic { format: ExtrinsicFormat::Bare, function: self.function },
 		})
 	}
 }
 
-impl<Address, Call, Signature, Extra> ExtrinsicMetadata
-	for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra, Extension> ExtrinsicMetadata
+	for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 where
+	Call: Dispatchable,
 	Extra: SignedExtension,
+	Extension: TransactionExtension<Call>,
 {
 	const VERSION: u8 = EXTRINSIC_FORMAT_VERSION;
 	type SignedExtensions = Extra;
+
+	fn transaction_extensions() -> Vec<SignedExtensionMetadata> {
+		Extension::metadata()
+	}
 }
 
 /// A payload that has been signed for an unchecked extrinsics.
@@ -247,12 +316,14 @@ where
 {
 }
 
-impl<Address, Call, Signature, Extra> Decode for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra, Extension> Decode
+	for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 where
 	Address: Decode,
 	Signature: Decode,
 	Call: Decode,
 	Extra: SignedExtension,
+	Extension: Decode,
 {
 	fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
 		// This is a little more complicated than usual since the binary format must be compatible
@@ -263,13 +334,16 @@ where
 
 		let version = input.read_byte()?;
 
-		let is_signed = version & 0b1000_0000 != 0;
-		let version = version & 0b0111_1111;
-		if version != EXTRINSIC_FORMAT_VERSION {
-			return Err("Invalid transaction version".into())
-		}
-
-		let signature = is_signed.then(|| Decode::decode(input)).transpose()?;
+		let preamble = match (version & EXTRINSIC_TYPE_MASK, version & !EXTRINSIC_TYPE_MASK) {
+			(BARE_EXTRINSIC, EXTRINSIC_FORMAT_VERSION) => Preamble::Bare,
+			(SIGNED_EXTRINSIC, EXTRINSIC_FORMAT_VERSION) => {
+				let (address, signature, extra) = Decode::decode(input)?;
+				Preamble::Signed(address, signature, extra)
+			},
+			(GENERAL_EXTRINSIC, GENERAL_EXTRINSIC_FORMAT_VERSION) =>
+				Preamble::General(Decode::decode(input)?),
+			_ => return Err("Invalid transaction version".into()),
+		};
 		let function = Decode::decode(input)?;
 
 		if let Some((before_length, after_length)) =
@@ -282,28 +356,34 @@ where
 			}
 		}
 
-		Ok(Self { signature, function })
+		Ok(Self { preamble, function })
 	}
 }
 
-impl<Address, Call, Signature, Extra> Encode for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra, Extension> Encode
+	for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 where
 	Address: Encode,
 	Signature: Encode,
 	Call: Encode,
 	Extra: SignedExtension,
+	Extension: Encode,
 {
 	fn encode(&self) -> Vec<u8> {
 		let mut tmp = Vec::with_capacity(sp_std::mem::size_of::<Self>());
 
 		// 1 byte version id.
-		match self.signature.as_ref() {
-			Some(s) => {
-				tmp.push(EXTRINSIC_FORMAT_VERSION | 0b1000_0000);
-				s.encode_to(&mut tmp);
+		match self.preamble {
+			Preamble::Bare => {
+				tmp.push(EXTRINSIC_FORMAT_VERSION | BARE_EXTRINSIC);
+			},
+			Preamble::Signed(ref address, ref signature, ref extra) => {
+				tmp.push(EXTRINSIC_FORMAT_VERSION | SIGNED_EXTRINSIC);
+				(address, signature, extra).encode_to(&mut tmp);
 			},
-			None => {
-				tmp.push(EXTRINSIC_FORMAT_VERSION & 0b0111_1111);
+			Preamble::General(ref extension) => {
+				tmp.push(GENERAL_EXTRINSIC_FORMAT_VERSION | GENERAL_EXTRINSIC);
+				extension.encode_to(&mut tmp);
 			},
 		}
 		self.function.encode_to(&mut tmp);
@@ -320,19 +400,25 @@ where
 	}
 }
 
-impl<Address, Call, Signature, Extra> EncodeLike
-	for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra, Extension> EncodeLike
+	for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 where
 	Address: Encode,
 	Signature: Encode,
 	Call: Encode,
 	Extra: SignedExtension,
+	Extension: Encode,
 {
 }
 
 #[cfg(feature = "serde")]
-impl<Address: Encode, Signature: Encode, Call: Encode, Extra: SignedExtension> serde::Serialize
-	for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<
+		Address: Encode,
+		Signature: Encode,
+		Call: Encode,
+		Extra: SignedExtension,
+		Extension: Encode,
+	> serde::Serialize for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 {
 	fn serialize<S>(&self, seq: S) -> Result<S::Ok, S::Error>
 	where
@@ -343,8 +429,14 @@ impl<Address: Encode, Signature: Encode, Call: Encode, Extra: SignedExtension> s
 }
 
 #[cfg(feature = "serde")]
-impl<'a, Address: Decode, Signature: Decode, Call: Decode, Extra: SignedExtension>
-	serde::Deserialize<'a> for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<
+		'a,
+		Address: Decode,
+		Signature: Decode,
+		Call: Decode,
+		Extra: SignedExtension,
+		Extension: Decode,
+	> serde::Deserialize<'a> for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 {
 	fn deserialize<D>(de: D) -> Result<Self, D::Error>
 	where
@@ -356,32 +448,38 @@ impl<'a, Address: Decode, Signature: Decode, Call: Decode, Extra: SignedExtensio
 	}
 }
 
-impl<Address, Call, Signature, Extra> fmt::Debug
-	for UncheckedExtrinsic<Address, Call, Signature, Extra>
+impl<Address, Call, Signature, Extra, Extension> fmt::Debug
+	for UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>
 where
 	Address: fmt::Debug,
 	Call: fmt::Debug,
 	Extra: SignedExtension,
+	Extension: fmt::Debug,
 {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(
-			f,
-			"UncheckedExtrinsic({:?}, {:?})",
-			self.signature.as_ref().map(|x| (&x.0, &x.2)),
-			self.function,
-		)
+		match self.preamble {
+			Preamble::General(ref extension) =>
+				write!(f, "UncheckedExtrinsic(General({:?}), {:?})", extension, self.function),
+			_ => write!(
+				f,
+				"UncheckedExtrinsic({:?}, {:?})",
+				self.signature().map(|x| (x.0, x.2)),
+				self.function,
+			),
+		}
 	}
 }
 
-impl<Address, Call, Signature, Extra> From<UncheckedExtrinsic<Address, Call, Signature, Extra>>
-	for OpaqueExtrinsic
+impl<Address, Call, Signature, Extra, Extension>
+	From<UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>> for OpaqueExtrinsic
 where
 	Address: Encode,
 	Signature: Encode,
 	Call: Encode,
 	Extra: SignedExtension,
+	Extension: Encode,
 {
-	fn from(extrinsic: UncheckedExtrinsic<Address, Call, Signature, Extra>) -> Self {
+	fn from(extrinsic: UncheckedExtrinsic<Address, Call, Signature, Extra, Extension>) -> Self {
 		Self::from_bytes(extrinsic.encode().as_slice()).expect(
 			"both OpaqueExtrinsic and UncheckedExtrinsic have encoding that is compatible with \
 				raw Vec<u8> encoding; qed",
@@ -432,6 +530,7 @@ mod tests {
 
 	type Ex = UncheckedExtrinsic<TestAccountId, TestCall, TestSig, TestExtra>;
 	type CEx = CheckedExtrinsic<TestAccountId, TestCall, TestExtra>;
+	type GEx = UncheckedExtrinsic<TestAccountId, TestCall, TestSig, TestExtra, u32>;
 
 	#[test]
 	fn unsigned_codec_should_work() {
@@ -511,10 +610,39 @@ mod tests {
 		assert!(ux.is_signed().unwrap_or(false));
 		assert_eq!(
 			<Ex as Checkable<TestContext>>::check(ux, &Default::default()),
-			Ok(CEx { signed: Some((TEST_ACCOUNT, TestExtra)), function: vec![0u8; 0] }),
+			Ok(CEx {
+				format: ExtrinsicFormat::Signed(TEST_ACCOUNT, TestExtra),
+				function: vec![0u8; 0]
+			}),
+		);
+	}
+
+	#[test]
+	fn general_codec_should_work() {
+		let ux = GEx::new_general(vec![0u8; 0], 42);
+		let encoded = ux.encode();
+		assert_eq!(encoded[1], GENERAL_EXTRINSIC_FORMAT_VERSION | GENERAL_EXTRINSIC);
+		assert_eq!(GEx::decode(&mut &encoded[..]), Ok(ux));
+	}
+
+	#[test]
+	fn general_check_should_work() {
+		let ux = GEx::new_general(vec![0u8; 0], 42);
+		assert!(ux.is_signed().unwrap_or(false));
+		assert_eq!(
+			<GEx as Checkable<TestContext>>::check(ux, &Default::default()),
+			Ok(CheckedExtrinsic { format: ExtrinsicFormat::General(42), function: vec![0u8; 0] }),
 		);
 	}
 
+	#[test]
+	fn general_version_is_not_accepted_for_signed() {
+		let ux = Ex::new_unsigned(vec![0u8; 0]);
+		let mut encoded = ux.encode();
+		encoded[1] = GENERAL_EXTRINSIC_FORMAT_VERSION | SIGNED_EXTRINSIC;
+		assert_eq!(Ex::decode(&mut &encoded[..]), Err("Invalid transaction version".into()));
+	}
+
 	#[test]
 	fn encoding_matches_vec() {
 		let ex = Ex::new_unsigned(vec![0u8; 0]);
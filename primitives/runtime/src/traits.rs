@@ -52,6 +52,12 @@ use std::fmt::Display;
 #[cfg(feature = "std")]
 use std::str::FromStr;
 
+mod transaction_extension;
+pub use transaction_extension::{
+	AsAuthorizedOrigin, AsSystemOriginSigner, AsTransactionExtension, OriginOf,
+	TransactionExtension, ValidateResult,
+};
+
 /// A lazy value.
 pub trait Lazy<T: ?Sized> {
 	/// Get a reference to the underlying value.
@@ -1342,6 +1348,14 @@ pub trait ExtrinsicMetadata {
 
 	/// Signed extensions attached to this `Extrinsic`.
 	type SignedExtensions: SignedExtension;
+
+	/// Returns the metadata of the [`TransactionExtension`]s attached to general transactions of
+	/// this `Extrinsic`, in the order they appear in the extrinsic.
+	///
+	/// Empty if the `Extrinsic` doesn't support general transactions.
+	fn transaction_extensions() -> Vec<SignedExtensionMetadata> {
+		Vec::new()
+	}
 }
 
 /// Extract the hashing type for a block.
@@ -1684,7 +1698,7 @@ impl SignedExtension for () {
 		info: &DispatchInfoOf<Self::Call>,
 		len: usize,
 	) -> Result<Self::Pre, TransactionValidityError> {
-		self.validate(who, call, info, len).map(|_| ())
+		SignedExtension::validate(&self, who, call, info, len).map(|_| ())
 	}
 }
 
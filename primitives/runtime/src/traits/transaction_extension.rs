@@ -0,0 +1,626 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The [`TransactionExtension`] trait and the adapter of [`SignedExtension`]s.
+
+use super::{
+	DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SignedExtension, SignedExtensionMetadata,
+};
+use crate::{
+	scale_info::{StaticTypeInfo, TypeInfo},
+	transaction_validity::{TransactionValidityError, ValidTransaction},
+	DispatchResult,
+};
+use codec::{Codec, Decode, Encode};
+use sp_core::RuntimeDebug;
+use sp_std::{fmt::Debug, prelude::*};
+
+/// Shortcut to reference the `RuntimeOrigin` type of a `Dispatchable`.
+pub type OriginOf<T> = <T as Dispatchable>::RuntimeOrigin;
+
+/// The result of [`TransactionExtension::validate`]: the validity of the transaction, the value
+/// passed on to [`TransactionExtension::prepare`] and the (possibly transformed) origin.
+pub type ValidateResult<Val, Call> =
+	Result<(ValidTransaction, Val, OriginOf<Call>), TransactionValidityError>;
+
+/// Access to the signer of the system `Signed` origin.
+pub trait AsSystemOriginSigner<AccountId> {
+	/// Returns the account of the origin, if it is the system `Signed` origin.
+	fn as_system_origin_signer(&self) -> Option<&AccountId>;
+}
+
+/// Tells whether an origin is authorized to dispatch a transaction.
+pub trait AsAuthorizedOrigin {
+	/// Returns `false` if the origin is the system `None` origin, i.e. nothing has authorized
+	/// the transaction, and `true` otherwise.
+	fn is_transaction_authorized(&self) -> bool;
+}
+
+/// Means by which a transaction may be extended, with the ability to transform the origin of the
+/// dispatch.
+///
+/// Unlike a [`SignedExtension`], which is only handed the signer of a signed transaction, a
+/// transaction extension receives the origin of the transaction and returns the origin to
+/// dispatch with. This allows an extension to authorize a *general* transaction, i.e. a
+/// transaction which isn't signed by an account, for example by checking a multisig, a
+/// zero-knowledge proof or the signature of a sponsor carried by the extension itself.
+///
+/// Extensions are composed by putting them into a tuple. They are validated in order and each
+/// extension receives the origin returned by the previous one. Existing [`SignedExtension`]s
+/// can be part of such a tuple by wrapping them into an [`AsTransactionExtension`].
+pub trait TransactionExtension<Call: Dispatchable>:
+	Codec + Debug + Sync + Send + Clone + Eq + PartialEq + StaticTypeInfo
+{
+	/// Unique identifier of this transaction extension.
+	///
+	/// This will be exposed in the metadata to identify the transaction extension used in an
+	/// extrinsic.
+	const IDENTIFIER: &'static str;
+
+	/// Any additional data that is implied by the transaction without being part of it. This is
+	/// created by [`Self::implicit`] and is, together with the call and the extension itself,
+	/// what preceding extensions may sign over.
+	type Implicit: Encode + StaticTypeInfo;
+
+	/// The type passed from [`Self::validate`] to [`Self::prepare`].
+	type Val;
+
+	/// The type passed from [`Self::prepare`] to [`Self::post_dispatch`].
+	type Pre;
+
+	/// Construct the data implied by the transaction. Can also perform any checks that don't
+	/// depend on the origin and return an error if needed.
+	fn implicit(&self) -> Result<Self::Implicit, TransactionValidityError>;
+
+	/// Validate the transaction for the transaction queue, and transform its origin if needed.
+	///
+	/// `implication` is the encoded data that this extension should consider implied by the
+	/// transaction, e.g. the payload of a signature it carries. It consists of the encoded call
+	/// followed by the encoding and the implicit data of all extensions after this one.
+	///
+	/// This function can be called frequently by the transaction queue, to obtain transaction
+	/// validity against current state. It should perform all checks that determine a valid
+	/// transaction, that can pay for its execution and quickly eliminate ones that are stale or
+	/// incorrect.
+	fn validate(
+		&self,
+		origin: OriginOf<Call>,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+		implication: &[u8],
+	) -> ValidateResult<Self::Val, Call>;
+
+	/// Do any pre-flight stuff for a transaction after it has been validated with
+	/// [`Self::validate`], which is always called right before.
+	///
+	/// `origin` is the origin the transaction will be dispatched with.
+	fn prepare(
+		self,
+		val: Self::Val,
+		origin: &OriginOf<Call>,
+		call: &Call,
+		info: &DispatchInfoOf<Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError>;
+
+	/// Do any post-flight stuff for a transaction.
+	///
+	/// The same caveats as for [`SignedExtension::post_dispatch`] apply: returning an error here
+	/// invalidates the whole block.
+	fn post_dispatch(
+		_pre: Self::Pre,
+		_info: &DispatchInfoOf<Call>,
+		_post_info: &PostDispatchInfoOf<Call>,
+		_len: usize,
+		_result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	/// Returns the metadata for this transaction extension.
+	///
+	/// As a [`TransactionExtension`] can be a tuple of [`TransactionExtension`]s we need to return
+	/// a `Vec` that holds the metadata of each one. Each individual `TransactionExtension` must
+	/// return *exactly* one [`SignedExtensionMetadata`].
+	fn metadata() -> Vec<SignedExtensionMetadata> {
+		sp_std::vec![SignedExtensionMetadata {
+			identifier: Self::IDENTIFIER,
+			ty: scale_info::meta_type::<Self>(),
+			additional_signed: scale_info::meta_type::<Self::Implicit>()
+		}]
+	}
+}
+
+/// Adapter to use a [`SignedExtension`] as a [`TransactionExtension`].
+///
+/// The signed extension is handed the signer of the origin it is validated with. If the origin
+/// isn't the system `Signed` origin, the transaction is treated as unsigned by the signed
+/// extension. The encoding and the metadata are the ones of the signed extension.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, Default, TypeInfo)]
+pub struct AsTransactionExtension<SE: SignedExtension>(pub SE);
+
+impl<SE: SignedExtension> From<SE> for AsTransactionExtension<SE> {
+	fn from(extension: SE) -> Self {
+		Self(extension)
+	}
+}
+
+impl<SE> TransactionExtension<SE::Call> for AsTransactionExtension<SE>
+where
+	SE: SignedExtension,
+	OriginOf<SE::Call>: AsSystemOriginSigner<SE::AccountId>,
+{
+	const IDENTIFIER: &'static str = SE::IDENTIFIER;
+	type Implicit = SE::AdditionalSigned;
+	type Val = ();
+	type Pre = Option<SE::Pre>;
+
+	fn implicit(&self) -> Result<Self::Implicit, TransactionValidityError> {
+		self.0.additional_signed()
+	}
+
+	fn validate(
+		&self,
+		origin: OriginOf<SE::Call>,
+		call: &SE::Call,
+		info: &DispatchInfoOf<SE::Call>,
+		len: usize,
+		_implication: &[u8],
+	) -> ValidateResult<Self::Val, SE::Call> {
+		let valid = match origin.as_system_origin_signer() {
+			Some(who) => self.0.validate(who, call, info, len)?,
+			None => SE::validate_unsigned(call, info, len)?,
+		};
+		Ok((valid, (), origin))
+	}
+
+	fn prepare(
+		self,
+		_val: Self::Val,
+		origin: &OriginOf<SE::Call>,
+		call: &SE::Call,
+		info: &DispatchInfoOf<SE::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		match origin.as_system_origin_signer() {
+			Some(who) => self.0.pre_dispatch(who, call, info, len).map(Some),
+			None => SE::pre_dispatch_unsigned(call, info, len).map(|_| None),
+		}
+	}
+
+	fn post_dispatch(
+		pre: Self::Pre,
+		info: &DispatchInfoOf<SE::Call>,
+		post_info: &PostDispatchInfoOf<SE::Call>,
+		len: usize,
+		result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		SE::post_dispatch(pre, info, post_info, len, result)
+	}
+
+	fn metadata() -> Vec<SignedExtensionMetadata> {
+		SE::metadata()
+	}
+}
+
+impl<Call: Dispatchable> TransactionExtension<Call> for () {
+	const IDENTIFIER: &'static str = "UnitTransactionExtension";
+	type Implicit = ();
+	type Val = ();
+	type Pre = ();
+
+	fn implicit(&self) -> Result<Self::Implicit, TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		origin: OriginOf<Call>,
+		_call: &Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+		_implication: &[u8],
+	) -> ValidateResult<Self::Val, Call> {
+		Ok((ValidTransaction::default(), (), origin))
+	}
+
+	fn prepare(
+		self,
+		_val: Self::Val,
+		_origin: &OriginOf<Call>,
+		_call: &Call,
+		_info: &DispatchInfoOf<Call>,
+		_len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		Ok(())
+	}
+
+	fn metadata() -> Vec<SignedExtensionMetadata> {
+		Vec::new()
+	}
+}
+
+/// Returns the implication of the extension at `index` of a tuple: the implication of the tuple
+/// followed by the encoding and the implicit data of the extensions after `index`.
+fn implication_at(
+	implication: &[u8],
+	explicit: &[Vec<u8>],
+	implicit: &[Vec<u8>],
+	index: usize,
+) -> Vec<u8> {
+	let mut result = implication.to_vec();
+	explicit[index + 1..].iter().for_each(|e| result.extend_from_slice(e));
+	implicit[index + 1..].iter().for_each(|i| result.extend_from_slice(i));
+	result
+}
+
+macro_rules! impl_transaction_extension_for_tuple {
+	($( $ext:ident $index:tt ),+) => {
+		impl<Call: Dispatchable, $( $ext: TransactionExtension<Call> ),+> TransactionExtension<Call>
+			for ($( $ext, )+)
+		{
+			const IDENTIFIER: &'static str = "You should call `metadata()`!";
+			type Implicit = ($( $ext::Implicit, )+);
+			type Val = ($( $ext::Val, )+);
+			type Pre = ($( $ext::Pre, )+);
+
+			fn implicit(&self) -> Result<Self::Implicit, TransactionValidityError> {
+				Ok(($( self.$index.implicit()?, )+))
+			}
+
+			fn validate(
+				&self,
+				origin: OriginOf<Call>,
+				call: &Call,
+				info: &DispatchInfoOf<Call>,
+				len: usize,
+				implication: &[u8],
+			) -> ValidateResult<Self::Val, Call> {
+				let explicit = [$( self.$index.encode() ),+];
+				let implicit = [$( self.$index.implicit()?.encode() ),+];
+				let mut valid = ValidTransaction::default();
+				let mut origin = origin;
+				let val = ($({
+					let (ext_valid, val, ext_origin) = self.$index.validate(
+						origin,
+						call,
+						info,
+						len,
+						&implication_at(implication, &explicit, &implicit, $index),
+					)?;
+					valid = valid.combine_with(ext_valid);
+					origin = ext_origin;
+					val
+				}, )+);
+				Ok((valid, val, origin))
+			}
+
+			fn prepare(
+				self,
+				val: Self::Val,
+				origin: &OriginOf<Call>,
+				call: &Call,
+				info: &DispatchInfoOf<Call>,
+				len: usize,
+			) -> Result<Self::Pre, TransactionValidityError> {
+				Ok(($( self.$index.prepare(val.$index, origin, call, info, len)?, )+))
+			}
+
+			fn post_dispatch(
+				pre: Self::Pre,
+				info: &DispatchInfoOf<Call>,
+				post_info: &PostDispatchInfoOf<Call>,
+				len: usize,
+				result: &DispatchResult,
+			) -> Result<(), TransactionValidityError> {
+				$( $ext::post_dispatch(pre.$index, info, post_info, len, result)?; )+
+				Ok(())
+			}
+
+			fn metadata() -> Vec<SignedExtensionMetadata> {
+				let mut ids = Vec::new();
+				$( ids.extend($ext::metadata()); )+
+				ids
+			}
+		}
+	};
+}
+
+impl_transaction_extension_for_tuple!(A 0);
+impl_transaction_extension_for_tuple!(A 0, B 1);
+impl_transaction_extension_for_tuple!(A 0, B 1, C 2);
+impl_transaction_extension_for_tuple!(A 0, B 1, C 2, D 3);
+impl_transaction_extension_for_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_transaction_extension_for_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_transaction_extension_for_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_transaction_extension_for_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_transaction_extension_for_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_transaction_extension_for_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_transaction_extension_for_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_transaction_extension_for_tuple!(
+	A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11
+);
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		generic::{CheckedExtrinsic, ExtrinsicFormat},
+		traits::{Applyable, AsAuthorizedOrigin, ValidateUnsigned},
+		transaction_validity::{
+			InvalidTransaction, TransactionSource, TransactionValidity, TransactionValidityError,
+		},
+		DispatchResultWithInfo,
+	};
+	use std::cell::RefCell;
+
+	std::thread_local! {
+		static EVENTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+		static IMPLICATIONS: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+	}
+
+	fn record(event: impl Into<String>) {
+		EVENTS.with(|events| events.borrow_mut().push(event.into()));
+	}
+
+	fn take_events() -> Vec<String> {
+		EVENTS.with(|events| events.take())
+	}
+
+	#[derive(Clone, Debug, PartialEq, Eq)]
+	enum Origin {
+		None,
+		Signed(u64),
+	}
+
+	impl From<Option<u64>> for Origin {
+		fn from(who: Option<u64>) -> Self {
+			who.map_or(Origin::None, Origin::Signed)
+		}
+	}
+
+	impl AsSystemOriginSigner<u64> for Origin {
+		fn as_system_origin_signer(&self) -> Option<&u64> {
+			match self {
+				Origin::Signed(who) => Some(who),
+				Origin::None => None,
+			}
+		}
+	}
+
+	impl AsAuthorizedOrigin for Origin {
+		fn is_transaction_authorized(&self) -> bool {
+			*self != Origin::None
+		}
+	}
+
+	#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+	struct Call(u8);
+
+	impl Dispatchable for Call {
+		type RuntimeOrigin = Origin;
+		type Config = ();
+		type Info = ();
+		type PostInfo = ();
+
+		fn dispatch(self, origin: Origin) -> DispatchResultWithInfo<()> {
+			record(format!("dispatch {:?}", origin));
+			Ok(())
+		}
+	}
+
+	/// Signed extension that gives signed transactions the priority of the signer.
+	#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+	struct CheckSigner(u32);
+
+	impl SignedExtension for CheckSigner {
+		const IDENTIFIER: &'static str = "CheckSigner";
+		type AccountId = u64;
+		type Call = Call;
+		type AdditionalSigned = u32;
+		type Pre = u64;
+
+		fn additional_signed(&self) -> Result<u32, TransactionValidityError> {
+			Ok(self.0 * 2)
+		}
+
+		fn validate(&self, who: &u64, _: &Call, _: &(), _: usize) -> TransactionValidity {
+			record(format!("validate {}", who));
+			Ok(ValidTransaction { priority: *who, ..Default::default() })
+		}
+
+		fn pre_dispatch(
+			self,
+			who: &u64,
+			call: &Call,
+			info: &(),
+			len: usize,
+		) -> Result<u64, TransactionValidityError> {
+			self.validate(who, call, info, len)?;
+			Ok(*who)
+		}
+
+		fn validate_unsigned(_: &Call, _: &(), _: usize) -> TransactionValidity {
+			record("validate unsigned");
+			Ok(ValidTransaction { priority: 1, ..Default::default() })
+		}
+
+		fn post_dispatch(
+			pre: Option<u64>,
+			_: &(),
+			_: &(),
+			_: usize,
+			_: &DispatchResult,
+		) -> Result<(), TransactionValidityError> {
+			record(format!("post dispatch {:?}", pre));
+			Ok(())
+		}
+	}
+
+	/// Transaction extension that authorizes unsigned transactions as coming from the account it
+	/// carries.
+	#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+	struct AuthorizeAs(u64);
+
+	impl TransactionExtension<Call> for AuthorizeAs {
+		const IDENTIFIER: &'static str = "AuthorizeAs";
+		type Implicit = ();
+		type Val = u64;
+		type Pre = ();
+
+		fn implicit(&self) -> Result<(), TransactionValidityError> {
+			Ok(())
+		}
+
+		fn validate(
+			&self,
+			origin: Origin,
+			_: &Call,
+			_: &(),
+			_: usize,
+			implication: &[u8],
+		) -> ValidateResult<u64, Call> {
+			IMPLICATIONS.with(|i| i.borrow_mut().push(implication.to_vec()));
+			let origin = match origin {
+				Origin::None => Origin::Signed(self.0),
+				origin => origin,
+			};
+			Ok((ValidTransaction { priority: 100, ..Default::default() }, self.0, origin))
+		}
+
+		fn prepare(
+			self,
+			val: u64,
+			origin: &Origin,
+			_: &Call,
+			_: &(),
+			_: usize,
+		) -> Result<(), TransactionValidityError> {
+			record(format!("prepare {} {:?}", val, origin));
+			Ok(())
+		}
+	}
+
+	struct NoUnsigned;
+
+	impl ValidateUnsigned for NoUnsigned {
+		type Call = Call;
+
+		fn validate_unsigned(_: TransactionSource, _: &Call) -> TransactionValidity {
+			InvalidTransaction::Call.into()
+		}
+	}
+
+	type Extrinsic<Extension> = CheckedExtrinsic<u64, Call, CheckSigner, Extension>;
+
+	#[test]
+	fn as_transaction_extension_adapts_signed_extension() {
+		let extension = AsTransactionExtension::from(CheckSigner(21));
+		assert_eq!(extension.implicit().unwrap(), 42);
+		assert_eq!(extension.encode(), CheckSigner(21).encode());
+		assert_eq!(
+			<AsTransactionExtension<CheckSigner> as TransactionExtension<Call>>::metadata()
+				.iter()
+				.map(|m| m.identifier)
+				.collect::<Vec<_>>(),
+			vec!["CheckSigner"],
+		);
+
+		let (valid, _, origin) =
+			extension.validate(Origin::Signed(7), &Call(0), &(), 0, &[]).unwrap();
+		assert_eq!((valid.priority, origin.clone()), (7, Origin::Signed(7)));
+		assert_eq!(extension.clone().prepare((), &origin, &Call(0), &(), 0).unwrap(), Some(7));
+
+		let (valid, _, origin) = extension.validate(Origin::None, &Call(0), &(), 0, &[]).unwrap();
+		assert_eq!((valid.priority, origin.clone()), (1, Origin::None));
+		assert_eq!(extension.prepare((), &origin, &Call(0), &(), 0).unwrap(), None);
+
+		assert_eq!(
+			take_events(),
+			vec!["validate 7", "validate 7", "validate unsigned", "validate unsigned"],
+		);
+	}
+
+	#[test]
+	fn tuple_passes_on_the_transformed_origin() {
+		let extension = (AuthorizeAs(5), AsTransactionExtension::from(CheckSigner(1)));
+
+		let (valid, val, origin) =
+			extension.validate(Origin::None, &Call(0), &(), 0, b"call").unwrap();
+		assert_eq!(origin, Origin::Signed(5));
+		assert_eq!(valid.priority, 105);
+		assert_eq!(val, (5, ()));
+
+		// The first extension is given the data of the extensions after it.
+		let implication = [b"call".to_vec(), CheckSigner(1).encode(), 2u32.encode()].concat();
+		assert_eq!(IMPLICATIONS.with(|i| i.take()), vec![implication]);
+
+		let pre = extension.prepare(val, &origin, &Call(0), &(), 0).unwrap();
+		assert_eq!(pre, ((), Some(5)));
+		assert_eq!(take_events(), vec!["validate 5", "prepare 5 Signed(5)", "validate 5"]);
+
+		let identifiers = <(AuthorizeAs, AsTransactionExtension<CheckSigner>) as TransactionExtension<
+			Call,
+		>>::metadata()
+		.iter()
+		.map(|m| m.identifier)
+		.collect::<Vec<_>>();
+		assert_eq!(identifiers, vec!["AuthorizeAs", "CheckSigner"]);
+	}
+
+	#[test]
+	fn general_transaction_without_authorized_origin_is_rejected() {
+		let xt: Extrinsic<AsTransactionExtension<CheckSigner>> = CheckedExtrinsic {
+			format: ExtrinsicFormat::General(CheckSigner(1).into()),
+			function: Call(0),
+		};
+
+		assert_eq!(
+			xt.validate::<NoUnsigned>(TransactionSource::External, &(), 0),
+			InvalidTransaction::UnknownOrigin.into(),
+		);
+		assert_eq!(xt.apply::<NoUnsigned>(&(), 0), Err(InvalidTransaction::UnknownOrigin.into()));
+		assert!(!take_events().iter().any(|e| e.starts_with("dispatch")));
+	}
+
+	#[test]
+	fn general_transaction_is_applied() {
+		let extension = (AuthorizeAs(3), AsTransactionExtension::from(CheckSigner(1)));
+		let xt: Extrinsic<_> =
+			CheckedExtrinsic { format: ExtrinsicFormat::General(extension), function: Call(0) };
+
+		let valid = xt.validate::<NoUnsigned>(TransactionSource::External, &(), 0).unwrap();
+		// The unsigned checks of `Extra` are combined with the ones of the extension.
+		assert_eq!(valid.priority, 104);
+		assert_eq!(take_events(), vec!["validate unsigned", "validate 3"]);
+
+		assert_eq!(xt.apply::<NoUnsigned>(&(), 0), Ok(Ok(())));
+		assert_eq!(
+			take_events(),
+			vec![
+				"validate unsigned",
+				"validate 3",
+				"prepare 3 Signed(3)",
+				"validate 3",
+				"dispatch Signed(3)",
+				"post dispatch Some(3)",
+				"post dispatch None",
+			],
+		);
+	}
+}
@@ -46,7 +46,8 @@ pub const MAX_TOPICS: usize = 4;
 
 #[cfg(feature = "std")]
 pub use store_api::{
-	Error, NetworkPriority, Result, StatementSource, StatementStore, SubmitResult,
+	Error, NetworkPriority, Result, StatementFilter, StatementSource, StatementStore,
+	StatementStream, SubmitResult, TopicFilter,
 };
 
 #[cfg(feature = "std")]
@@ -16,7 +16,9 @@
 // limitations under the License.
 
 pub use crate::runtime_api::StatementSource;
-use crate::{Hash, Statement, Topic};
+use crate::{DecryptionKey, Hash, Statement, Topic, MAX_TOPICS};
+use codec::{Decode, Encode};
+use std::pin::Pin;
 
 /// Statement store error.
 #[derive(Debug, Eq, PartialEq, thiserror::Error)]
@@ -30,6 +32,12 @@ pub enum Error {
 	/// Error making runtime call.
 	#[error("Error calling into the runtime")]
 	Runtime,
+	/// The store does not support subscriptions.
+	#[error("Subscriptions are not supported")]
+	SubscriptionsNotSupported,
+	/// The maximum number of subscriptions has been reached.
+	#[error("Too many subscriptions")]
+	TooManySubscriptions,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -61,6 +69,53 @@ pub enum SubmitResult {
 /// Result type for `Error`
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Stream of statements accepted by the store.
+pub type StatementStream = Pin<Box<dyn futures::Stream<Item = Statement> + Send>>;
+
+/// Statement filter over topics.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum TopicFilter {
+	/// Matches statements that include all of the given topics. An empty list matches any
+	/// statement.
+	MatchAll(Vec<Topic>),
+	/// Matches statements that include at least one of the given topics.
+	MatchAny(Vec<Topic>),
+}
+
+impl Default for TopicFilter {
+	fn default() -> Self {
+		TopicFilter::MatchAll(Vec::new())
+	}
+}
+
+impl TopicFilter {
+	/// Check if the statement topics pass the filter.
+	pub fn matches(&self, statement: &Statement) -> bool {
+		let mut topics = (0..MAX_TOPICS).map_while(|i| statement.topic(i));
+		match self {
+			TopicFilter::MatchAll(all) => all.iter().all(|t| topics.clone().any(|s| s == *t)),
+			TopicFilter::MatchAny(any) => topics.any(|s| any.contains(&s)),
+		}
+	}
+}
+
+/// Statement filter used for subscriptions.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StatementFilter {
+	/// Filter over statement topics.
+	pub topics: TopicFilter,
+	/// Decryption key the statement must have. `None` only matches statements that have no
+	/// `DecryptionKey` field.
+	pub decryption_key: Option<DecryptionKey>,
+}
+
+impl StatementFilter {
+	/// Check if the statement passes the filter.
+	pub fn matches(&self, statement: &Statement) -> bool {
+		statement.decryption_key() == self.decryption_key && self.topics.matches(statement)
+	}
+}
+
 /// Statement store API.
 pub trait StatementStore: Send + Sync {
 	/// Return all statements.
@@ -87,4 +142,9 @@ pub trait StatementStore: Send + Sync {
 
 	/// Remove a statement from the store.
 	fn remove(&self, hash: &Hash) -> Result<()>;
+
+	/// Subscribe to statements newly accepted by the store that match `filter`.
+	fn subscribe(&self, _filter: StatementFilter) -> Result<StatementStream> {
+		Err(Error::SubscriptionsNotSupported)
+	}
 }
@@ -25,6 +25,8 @@ mod error;
 mod node_codec;
 mod node_header;
 #[cfg(feature = "std")]
+pub mod proof_size_extension;
+#[cfg(feature = "std")]
 pub mod recorder;
 mod storage_proof;
 mod trie_codec;
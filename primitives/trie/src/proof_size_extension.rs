@@ -0,0 +1,85 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Externalities extension that provides access to the current proof size of the underlying
+//! recorder.
+
+use crate::recorder::Recorder;
+use hash_db::Hasher;
+
+/// Something that can report its current proof size.
+pub trait ReportProofSize {
+	/// Returns the current proof size in bytes.
+	fn proof_size(&self) -> u64;
+}
+
+impl<H: Hasher> ReportProofSize for Recorder<H> {
+	fn proof_size(&self) -> u64 {
+		self.estimate_encoded_size() as u64
+	}
+}
+
+sp_externalities::decl_extension! {
+	/// The proof size extension to fetch the current storage proof size in the externalities.
+	pub struct ProofSizeExt(Box<dyn ReportProofSize + 'static + Sync + Send>);
+}
+
+impl ProofSizeExt {
+	/// Create a new instance of [`ProofSizeExt`] reporting the size of `recorder`.
+	pub fn new<T: ReportProofSize + Sync + Send + 'static>(recorder: T) -> Self {
+		ProofSizeExt(Box::new(recorder))
+	}
+
+	/// Returns the current storage proof size in bytes.
+	pub fn storage_proof_size(&self) -> u64 {
+		self.0.proof_size()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use trie_db::{Trie, TrieDBBuilder, TrieDBMutBuilder, TrieMut};
+
+	type Layout = crate::LayoutV1<sp_core::Blake2Hasher>;
+
+	#[test]
+	fn reports_the_recorded_proof_size() {
+		let mut db = crate::MemoryDB::<sp_core::Blake2Hasher>::default();
+		let mut root = Default::default();
+		{
+			let mut trie = TrieDBMutBuilder::<Layout>::new(&mut db, &mut root).build();
+			trie.insert(b"key1", &[1; 64]).expect("Inserts data");
+			trie.insert(b"key2", &[2; 64]).expect("Inserts data");
+		}
+
+		let recorder = Recorder::<sp_core::Blake2Hasher>::default();
+		let ext = ProofSizeExt::new(recorder.clone());
+		assert_eq!(ext.storage_proof_size(), 0);
+
+		{
+			let mut trie_recorder = recorder.as_trie_recorder(root);
+			let trie = TrieDBBuilder::<Layout>::new(&db, &root)
+				.with_recorder(&mut trie_recorder)
+				.build();
+			assert_eq!(trie.get(b"key1").unwrap(), Some(vec![1; 64]));
+		}
+
+		assert!(ext.storage_proof_size() > 0);
+		assert_eq!(ext.storage_proof_size(), recorder.estimate_encoded_size() as u64);
+	}
+}
@@ -129,8 +129,9 @@
 //!   side features. The corresponding runtime, called [`kitchensink_runtime`] contains all of the
 //!   modules that are provided with `FRAME`. This node and runtime is only used for testing and
 //!   demonstration.
-//!     * [`chain-spec-builder`]: Utility to build more detailed chain-specs for the aforementioned
-//!       node. Other projects typically contain a `build-spec` subcommand that does the same.
+//! * [`chain-spec-builder`]: Utility to build chain-specs for any runtime out of its wasm blob.
+//!   Other projects typically contain a `build-spec` subcommand that does the same for their native
+//!   runtime.
 //! * [`node-template`]: a template node that contains a minimal set of features and can act as a
 //!   starting point of a project.
 //! * [`subkey`]: Substrate's key management utility.
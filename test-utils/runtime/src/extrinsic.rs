@@ -25,7 +25,7 @@ use codec::Encode;
 use frame_system::{CheckNonce, CheckWeight};
 use sp_core::crypto::Pair as TraitPair;
 use sp_keyring::AccountKeyring;
-use sp_runtime::{transaction_validity::TransactionPriority, Perbill};
+use sp_runtime::{generic::Preamble, transaction_validity::TransactionPriority, Perbill};
 use sp_std::prelude::*;
 
 /// Transfer used in test substrate pallet. Extrinsic is created and signed using this data.
@@ -66,11 +66,11 @@ impl TryFrom<&Extrinsic> for TransferData {
 		match uxt {
 			Extrinsic {
 				function: RuntimeCall::Balances(BalancesCall::transfer_allow_death { dest, value }),
-				signature: Some((from, _, (CheckNonce(nonce), ..))),
+				preamble: Preamble::Signed(from, _, (CheckNonce(nonce), ..)),
 			} => Ok(TransferData { from: *from, to: *dest, amount: *value, nonce: *nonce }),
 			Extrinsic {
 				function: RuntimeCall::SubstrateTest(PalletCall::bench_call { transfer }),
-				signature: None,
+				preamble: Preamble::Bare,
 			} => Ok(transfer.clone()),
 			_ => Err(()),
 		}
@@ -169,6 +169,11 @@ impl ExtrinsicBuilder {
 		Self::new_unsigned(PalletCall::read_and_panic { count })
 	}
 
+	/// Create builder for `PalletCall::store_proof_size` call
+	pub fn new_store_proof_size() -> Self {
+		Self::new_unsigned(PalletCall::store_proof_size {})
+	}
+
 	/// Unsigned `Extrinsic` will be created
 	pub fn unsigned(mut self) -> Self {
 		self.signer = None;
@@ -27,7 +27,7 @@ pub mod substrate_test_pallet;
 
 use codec::{Decode, Encode};
 #[cfg(not(feature = "disable-genesis-builder"))]
-use frame_support::genesis_builder_helper::{build_config, create_default_config};
+use frame_support::genesis_builder_helper::{build_config, create_default_config, get_preset};
 use frame_support::{
 	construct_runtime,
 	dispatch::DispatchClass,
@@ -223,6 +223,8 @@ decl_runtime_apis! {
 		fn verify_ed25519(sig: ed25519::Signature, public: ed25519::Public, message: Vec<u8>) -> bool;
 		/// Write the given `value` under the given `key` into the storage and then optional panic.
 		fn write_key_value(key: Vec<u8>, value: Vec<u8>, panic: bool);
+		/// Returns the size of the storage proof recorded so far.
+		fn storage_proof_size() -> u64;
 	}
 }
 
@@ -489,14 +491,15 @@ impl_runtime_apis! {
 
 	impl sp_api::Metadata<Block> for Runtime {
 		fn metadata() -> OpaqueMetadata {
-			unimplemented!()
+			OpaqueMetadata::new(Runtime::metadata().into())
 		}
 
-		fn metadata_at_version(_version: u32) -> Option<OpaqueMetadata> {
-			unimplemented!()
+		fn metadata_at_version(version: u32) -> Option<OpaqueMetadata> {
+			Runtime::metadata_at_version(version)
 		}
+
 		fn metadata_versions() -> sp_std::vec::Vec<u32> {
-			unimplemented!()
+			Runtime::metadata_versions()
 		}
 	}
 
@@ -617,8 +620,13 @@ impl_runtime_apis! {
 				panic!("I'm just following my master");
 			}
 		}
+
+		fn storage_proof_size() -> u64 {
+			sp_io::storage_proof_size::storage_proof_size()
+		}
 	}
 
+	#[api_version(2)]
 	impl sp_consensus_aura::AuraApi<Block, AuraId> for Runtime {
 		fn slot_duration() -> sp_consensus_aura::SlotDuration {
 			sp_consensus_aura::SlotDuration::from_millis(1000)
@@ -627,6 +635,32 @@ impl_runtime_apis! {
 		fn authorities() -> Vec<AuraId> {
 			SubstrateTest::authorities().into_iter().map(|auth| AuraId::from(auth)).collect()
 		}
+
+		fn generate_key_ownership_proof(
+			_slot: sp_consensus_aura::Slot,
+			_authority_id: AuraId,
+		) -> Option<sp_consensus_aura::OpaqueKeyOwnershipProof> {
+			Some(sp_consensus_aura::OpaqueKeyOwnershipProof::new(Vec::new()))
+		}
+
+		fn submit_report_equivocation_unsigned_extrinsic(
+			equivocation_proof: sp_consensus_aura::EquivocationProof<
+				<Block as BlockT>::Header,
+				AuraId,
+			>,
+			_key_owner_proof: sp_consensus_aura::OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			// There is no pallet to handle the report, so it is submitted as a storage change
+			// which allows tests to observe it.
+			let ext = Extrinsic::new_unsigned(
+				substrate_test_pallet::pallet::Call::storage_change {
+					key: b"aura_equivocation".encode(),
+					value: Some(equivocation_proof.encode()),
+				}
+				.into(),
+			);
+			sp_io::offchain::submit_transaction(ext.encode()).ok()
+		}
 	}
 
 	impl sp_consensus_babe::BabeApi<Block> for Runtime {
@@ -655,19 +689,28 @@ impl_runtime_apis! {
 		}
 
 		fn submit_report_equivocation_unsigned_extrinsic(
-			_equivocation_proof: sp_consensus_babe::EquivocationProof<
+			equivocation_proof: sp_consensus_babe::EquivocationProof<
 			<Block as BlockT>::Header,
 			>,
 			_key_owner_proof: sp_consensus_babe::OpaqueKeyOwnershipProof,
 		) -> Option<()> {
-			None
+			// There is no pallet to handle the report, so it is submitted as a storage change
+			// which allows tests to observe it.
+			let ext = Extrinsic::new_unsigned(
+				substrate_test_pallet::pallet::Call::storage_change {
+					key: b"babe_equivocation".encode(),
+					value: Some(equivocation_proof.encode()),
+				}
+				.into(),
+			);
+			sp_io::offchain::submit_transaction(ext.encode()).ok()
 		}
 
 		fn generate_key_ownership_proof(
 			_slot: sp_consensus_babe::Slot,
 			_authority_id: sp_consensus_babe::AuthorityId,
 		) -> Option<sp_consensus_babe::OpaqueKeyOwnershipProof> {
-			None
+			Some(sp_consensus_babe::OpaqueKeyOwnershipProof::new(Vec::new()))
 		}
 	}
 
@@ -723,6 +766,7 @@ impl_runtime_apis! {
 	}
 
 	#[cfg(not(feature = "disable-genesis-builder"))]
+	#[api_version(2)]
 	impl sp_genesis_builder::GenesisBuilder<Block> for Runtime {
 		fn create_default_config() -> Vec<u8> {
 			create_default_config::<RuntimeGenesisConfig>()
@@ -731,9 +775,51 @@ impl_runtime_apis! {
 		fn build_config(config: Vec<u8>) -> sp_genesis_builder::Result {
 			build_config::<RuntimeGenesisConfig>(config)
 		}
+
+		fn get_preset(id: &Option<sp_genesis_builder::PresetId>) -> Option<Vec<u8>> {
+			get_preset::<RuntimeGenesisConfig>(id, genesis_preset)
+		}
+
+		fn preset_names() -> Vec<sp_genesis_builder::PresetId> {
+			vec![sp_genesis_builder::PresetId::from(sp_genesis_builder::DEV_RUNTIME_PRESET)]
+		}
 	}
 }
 
+/// Returns the JSON patch of the genesis preset named `id`.
+#[cfg(not(feature = "disable-genesis-builder"))]
+fn genesis_preset(id: &sp_genesis_builder::PresetId) -> Option<Vec<u8>> {
+	// Alice and Bob.
+	const ENDOWED: [&str; 2] = [
+		"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+		"5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty",
+	];
+
+	let patch = match <&str>::try_from(id) {
+		Ok(sp_genesis_builder::DEV_RUNTIME_PRESET) => serde_json::json!({
+			"babe": {
+				"epochConfig": {
+					"c": [1, 4],
+					"allowed_slots": "PrimaryAndSecondaryVRFSlots"
+				}
+			},
+			"substrateTest": {
+				"authorities": ENDOWED,
+			},
+			"balances": {
+				"balances": ENDOWED.map(|who| (who, 1_000_000_000_000_000u64)),
+			},
+		}),
+		_ => return None,
+	};
+
+	Some(
+		serde_json::to_string(&patch)
+			.expect("serialization to json is expected to work. qed.")
+			.into_bytes(),
+	)
+}
+
 fn test_ed25519_crypto() -> (ed25519::AppSignature, ed25519::AppPublic) {
 	let public0 = ed25519::AppPublic::generate_pair(None);
 	let public1 = ed25519::AppPublic::generate_pair(None);
@@ -1141,6 +1227,7 @@ mod tests {
 				substrate_test_pallet::Call::storage_change { key: vec![], value: None },
 				substrate_test_pallet::Call::read { count: 0 },
 				substrate_test_pallet::Call::read_and_panic { count: 0 },
+				substrate_test_pallet::Call::store_proof_size {},
 			];
 
 			for call in failing_calls {
@@ -1446,5 +1533,62 @@ mod tests {
 			);
 			assert_eq!(H256::decode(&mut &value[..]).unwrap(), [69u8; 32].into());
 		}
+
+		#[test]
+		fn preset_names_works() {
+			sp_tracing::try_init_simple();
+			let mut t = BasicExternalities::new_empty();
+			let r = executor_call(&mut t, "GenesisBuilder_preset_names", &vec![]).unwrap();
+			let names = Vec::<sp_genesis_builder::PresetId>::decode(&mut &r[..]).unwrap();
+			assert_eq!(names, vec![sp_genesis_builder::DEV_RUNTIME_PRESET.into()]);
+		}
+
+		#[test]
+		fn unknown_preset_is_not_found() {
+			sp_tracing::try_init_simple();
+			let mut t = BasicExternalities::new_empty();
+			let id = Some(sp_genesis_builder::PresetId::from("unknown"));
+			let r = executor_call(&mut t, "GenesisBuilder_get_preset", &id.encode()).unwrap();
+			assert_eq!(Option::<Vec<u8>>::decode(&mut &r[..]).unwrap(), None);
+		}
+
+		#[test]
+		fn build_genesis_config_from_preset_works() {
+			sp_tracing::try_init_simple();
+			let mut t = BasicExternalities::new_empty();
+			let r =
+				executor_call(&mut t, "GenesisBuilder_get_preset", &None::<()>.encode()).unwrap();
+			let r = Option::<Vec<u8>>::decode(&mut &r[..]).unwrap().unwrap();
+			let mut config: serde_json::Value = serde_json::from_slice(&r[..]).unwrap();
+
+			let id =
+				Some(sp_genesis_builder::PresetId::from(sp_genesis_builder::DEV_RUNTIME_PRESET));
+			let r = executor_call(&mut t, "GenesisBuilder_get_preset", &id.encode()).unwrap();
+			let r = Option::<Vec<u8>>::decode(&mut &r[..]).unwrap().unwrap();
+			let patch: serde_json::Value = serde_json::from_slice(&r[..]).unwrap();
+			json_patch::merge(&mut config, &patch);
+
+			let mut t = BasicExternalities::new_empty();
+			let r =
+				executor_call(&mut t, "GenesisBuilder_build_config", &config.to_string().encode())
+					.unwrap();
+			assert_eq!(BuildResult::decode(&mut &r[..]).unwrap(), Ok(()));
+
+			let storage = t.into_storages();
+			//SubstrateTest|Authorities
+			let value = storage
+				.top
+				.get(
+					&array_bytes::hex2bytes(
+						"00771836bebdd29870ff246d305c578c5e0621c4869aa60c02be9adcc98a0d1d",
+					)
+					.unwrap(),
+				)
+				.unwrap();
+			assert_eq!(
+				Vec::<sp_core::sr25519::Public>::decode(&mut &value[..]).unwrap(),
+				vec![AccountKeyring::Alice.public(), AccountKeyring::Bob.public()],
+			);
+		}
 	}
 }
@@ -35,11 +35,15 @@ pub use self::pallet::*;
 
 const LOG_TARGET: &str = "substrate_test_pallet";
 
+/// The storage key under which [`Call::store_proof_size`] stores the proof size.
+pub const PROOF_SIZE_KEY: &[u8] = b"substrate_test_pallet:proof_size";
+
 #[frame_support::pallet(dev_mode)]
 pub mod pallet {
 	use super::*;
 	use crate::TransferData;
 	use frame_system::pallet_prelude::*;
+	use sp_consensus_aura::{Slot, AURA_ENGINE_ID};
 	use sp_core::storage::well_known_keys;
 	use sp_runtime::{traits::BlakeTwo256, transaction_validity::TransactionPriority, Perbill};
 
@@ -54,6 +58,10 @@ pub mod pallet {
 	#[pallet::getter(fn authorities)]
 	pub type Authorities<T> = StorageValue<_, Vec<Public>, ValueQuery>;
 
+	/// The slot of the last block authored with Aura.
+	#[pallet::storage]
+	pub type AuraSlot<T> = StorageValue<_, Slot, ValueQuery>;
+
 	#[pallet::genesis_config]
 	#[derive(frame_support::DefaultNoBound)]
 	pub struct GenesisConfig<T: Config> {
@@ -69,6 +77,20 @@ pub mod pallet {
 		}
 	}
 
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_: BlockNumberFor<T>) -> Weight {
+			// Like `pallet_aura`, require the slots of blocks authored with Aura to increase.
+			let slot = <frame_system::Pallet<T>>::digest()
+				.convert_first(|item| item.pre_runtime_try_to::<Slot>(&AURA_ENGINE_ID));
+			if let Some(slot) = slot {
+				assert!(AuraSlot::<T>::get() < slot, "Slot must increase");
+				AuraSlot::<T>::put(slot);
+			}
+			Weight::zero()
+		}
+	}
+
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		/// Legacy call used in transaction pool benchmarks.
@@ -189,6 +211,15 @@ pub mod pallet {
 		pub fn read_and_panic(_origin: OriginFor<T>, count: u32) -> DispatchResult {
 			Self::execute_read(count, true)
 		}
+
+		/// Store the size of the storage proof recorded so far under [`PROOF_SIZE_KEY`].
+		#[pallet::call_index(12)]
+		#[pallet::weight(100)]
+		pub fn store_proof_size(_origin: OriginFor<T>) -> DispatchResult {
+			let proof_size = sp_io::storage_proof_size::storage_proof_size();
+			sp_io::storage::set(PROOF_SIZE_KEY, &proof_size.encode());
+			Ok(())
+		}
 	}
 
 	impl<T: Config> Pallet<T> {
@@ -230,7 +261,8 @@ pub mod pallet {
 				Call::deposit_log_digest_item { .. } |
 				Call::storage_change { .. } |
 				Call::read { .. } |
-				Call::read_and_panic { .. } => Ok(ValidTransaction {
+				Call::read_and_panic { .. } |
+				Call::store_proof_size { .. } => Ok(ValidTransaction {
 					provides: vec![BlakeTwo256::hash_of(&call).encode()],
 					..Default::default()
 				}),
@@ -0,0 +1,282 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Collect the types that are used while decoding an extrinsic.
+
+use codec::{Compact, Decode};
+use frame_metadata::v15::RuntimeMetadataV15;
+use scale_info::{PortableRegistry, TypeDef, TypeDefPrimitive};
+use sp_std::collections::btree_set::BTreeSet;
+
+/// The bit that is set in the version byte of a signed extrinsic.
+const SIGNED_EXTRINSIC_BIT: u8 = 0b1000_0000;
+
+/// The errors that can occur while collecting the types of an extrinsic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+	/// The type with the given id is not part of the metadata.
+	TypeNotFound(u32),
+	/// The data ended before the type could be decoded.
+	UnexpectedEndOfData,
+	/// The variant index is not part of the variant type.
+	InvalidVariantIndex {
+		/// The id of the variant type.
+		ty: u32,
+		/// The index that was found in the data.
+		index: u8,
+	},
+	/// The type of the bit sequence store isn't supported.
+	UnsupportedBitStoreType(u32),
+	/// The version of the extrinsic doesn't match the one of the metadata.
+	UnsupportedExtrinsicVersion(u8),
+	/// The length prefix of the extrinsic doesn't match its length.
+	InvalidLengthPrefix,
+	/// Not all the data was consumed while decoding.
+	TrailingData,
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Self::TypeNotFound(id) => write!(f, "Type `{id}` not found in the metadata"),
+			Self::UnexpectedEndOfData => write!(f, "Unexpected end of data"),
+			Self::InvalidVariantIndex { ty, index } =>
+				write!(f, "Invalid variant index `{index}` for type `{ty}`"),
+			Self::UnsupportedBitStoreType(id) =>
+				write!(f, "Unsupported bit sequence store type `{id}`"),
+			Self::UnsupportedExtrinsicVersion(version) =>
+				write!(f, "Unsupported extrinsic version `{version}`"),
+			Self::InvalidLengthPrefix => write!(f, "Invalid length prefix"),
+			Self::TrailingData => write!(f, "Not all data was consumed while decoding"),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl From<codec::Error> for Error {
+	fn from(_: codec::Error) -> Self {
+		Self::UnexpectedEndOfData
+	}
+}
+
+/// Collects the ids of the types that are used while decoding data.
+struct TypeCollector<'a> {
+	registry: &'a PortableRegistry,
+	types: BTreeSet<u32>,
+}
+
+impl<'a> TypeCollector<'a> {
+	fn new(registry: &'a PortableRegistry) -> Self {
+		Self { registry, types: BTreeSet::new() }
+	}
+
+	fn skip(data: &mut &[u8], len: usize) -> Result<(), Error> {
+		if data.len() < len {
+			return Err(Error::UnexpectedEndOfData)
+		}
+
+		*data = &data[len..];
+		Ok(())
+	}
+
+	/// Decode a value of type `id` from `data` and collect all the types that are used.
+	fn decode(&mut self, id: u32, data: &mut &[u8]) -> Result<(), Error> {
+		let ty = self.registry.resolve(id).ok_or(Error::TypeNotFound(id))?;
+		self.types.insert(id);
+
+		match &ty.type_def {
+			TypeDef::Composite(composite) =>
+				for field in &composite.fields {
+					self.decode(field.ty.id, data)?;
+				},
+			TypeDef::Variant(variant) => {
+				let index = u8::decode(data)?;
+				let variant = variant
+					.variants
+					.iter()
+					.find(|variant| variant.index == index)
+					.ok_or(Error::InvalidVariantIndex { ty: id, index })?;
+
+				for field in &variant.fields {
+					self.decode(field.ty.id, data)?;
+				}
+			},
+			TypeDef::Sequence(sequence) => {
+				let len = Compact::<u32>::decode(data)?.0;
+				for _ in 0..len {
+					self.decode(sequence.type_param.id, data)?;
+				}
+			},
+			TypeDef::Array(array) =>
+				for _ in 0..array.len {
+					self.decode(array.type_param.id, data)?;
+				},
+			TypeDef::Tuple(tuple) =>
+				for field in &tuple.fields {
+					self.decode(field.id, data)?;
+				},
+			TypeDef::Primitive(primitive) => {
+				let len = match primitive {
+					TypeDefPrimitive::Bool | TypeDefPrimitive::U8 | TypeDefPrimitive::I8 => 1,
+					TypeDefPrimitive::U16 | TypeDefPrimitive::I16 => 2,
+					TypeDefPrimitive::Char | TypeDefPrimitive::U32 | TypeDefPrimitive::I32 => 4,
+					TypeDefPrimitive::U64 | TypeDefPrimitive::I64 => 8,
+					TypeDefPrimitive::U128 | TypeDefPrimitive::I128 => 16,
+					TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => 32,
+					TypeDefPrimitive::Str => Compact::<u32>::decode(data)?.0 as usize,
+				};
+				Self::skip(data, len)?;
+			},
+			TypeDef::Compact(compact) => {
+				self.collect_compact_types(compact.type_param.id)?;
+				// The compact encoding doesn't depend on the width of the inner integer.
+				Compact::<u128>::decode(data)?;
+			},
+			TypeDef::BitSequence(bit_sequence) => {
+				let store = bit_sequence.bit_store_type.id;
+				let store_bits = match self.registry.resolve(store).map(|ty| &ty.type_def) {
+					Some(TypeDef::Primitive(TypeDefPrimitive::U8)) => 8,
+					Some(TypeDef::Primitive(TypeDefPrimitive::U16)) => 16,
+					Some(TypeDef::Primitive(TypeDefPrimitive::U32)) => 32,
+					Some(TypeDef::Primitive(TypeDefPrimitive::U64)) => 64,
+					_ => return Err(Error::UnsupportedBitStoreType(store)),
+				};
+				self.types.insert(store);
+				self.types.insert(bit_sequence.bit_order_type.id);
+
+				let bits = Compact::<u32>::decode(data)?.0 as usize;
+				Self::skip(data, (bits + store_bits - 1) / store_bits * (store_bits / 8))?;
+			},
+		}
+
+		Ok(())
+	}
+
+	/// Collect the types of the value wrapped by a compact, like `u32` or `struct Wrapper(u32)`.
+	fn collect_compact_types(&mut self, id: u32) -> Result<(), Error> {
+		let ty = self.registry.resolve(id).ok_or(Error::TypeNotFound(id))?;
+		self.types.insert(id);
+
+		match &ty.type_def {
+			TypeDef::Composite(composite) =>
+				for field in &composite.fields {
+					self.collect_compact_types(field.ty.id)?;
+				},
+			TypeDef::Tuple(tuple) =>
+				for field in &tuple.fields {
+					self.collect_compact_types(field.id)?;
+				},
+			_ => {},
+		}
+
+		Ok(())
+	}
+
+	/// Decode all of `data` as a value of type `id`.
+	fn decode_all(&mut self, id: u32, data: &[u8]) -> Result<(), Error> {
+		self.decode_all_of(sp_std::iter::once(id), data)
+	}
+
+	/// Decode all of `data` as the concatenation of values of the types `ids`.
+	fn decode_all_of(
+		&mut self,
+		ids: impl Iterator<Item = u32>,
+		mut data: &[u8],
+	) -> Result<(), Error> {
+		for id in ids {
+			self.decode(id, &mut data)?;
+		}
+
+		if data.is_empty() {
+			Ok(())
+		} else {
+			Err(Error::TrailingData)
+		}
+	}
+}
+
+/// Collect the types that are required to decode the given length prefixed `extrinsic` and the
+/// optional `additional_signed` data.
+pub(crate) fn collect_extrinsic_types(
+	mut data: &[u8],
+	additional_signed: Option<&[u8]>,
+	metadata: &RuntimeMetadataV15,
+) -> Result<BTreeSet<u32>, Error> {
+	let len = Compact::<u32>::decode(&mut data)?.0 as usize;
+	if len != data.len() {
+		return Err(Error::InvalidLengthPrefix)
+	}
+
+	let version = u8::decode(&mut data)?;
+	if version & !SIGNED_EXTRINSIC_BIT != metadata.extrinsic.version {
+		return Err(Error::UnsupportedExtrinsicVersion(version))
+	}
+
+	let mut collector = TypeCollector::new(&metadata.types);
+	let extrinsic = &metadata.extrinsic;
+
+	if version & SIGNED_EXTRINSIC_BIT != 0 {
+		collector.decode(extrinsic.address_ty.id, &mut data)?;
+		collector.decode(extrinsic.signature_ty.id, &mut data)?;
+		for signed_extension in &extrinsic.signed_extensions {
+			collector.decode(signed_extension.ty.id, &mut data)?;
+		}
+	}
+
+	collector.decode_all(extrinsic.call_ty.id, data)?;
+
+	if let Some(additional_signed) = additional_signed {
+		collector.decode_all_of(
+			extrinsic.signed_extensions.iter().map(|ext| ext.additional_signed.id),
+			additional_signed,
+		)?;
+	}
+
+	Ok(collector.types)
+}
+
+/// Collect the types that are required to decode the given parts of a signed payload.
+pub(crate) fn collect_extrinsic_parts_types(
+	call: &[u8],
+	included_in_extrinsic: Option<&[u8]>,
+	included_in_signed_data: Option<&[u8]>,
+	metadata: &RuntimeMetadataV15,
+) -> Result<BTreeSet<u32>, Error> {
+	let mut collector = TypeCollector::new(&metadata.types);
+	let extrinsic = &metadata.extrinsic;
+
+	collector.decode_all(extrinsic.call_ty.id, call)?;
+
+	if let Some(included_in_extrinsic) = included_in_extrinsic {
+		collector.decode_all_of(
+			extrinsic.signed_extensions.iter().map(|ext| ext.ty.id),
+			included_in_extrinsic,
+		)?;
+	}
+
+	if let Some(included_in_signed_data) = included_in_signed_data {
+		collector.decode_all_of(
+			extrinsic.signed_extensions.iter().map(|ext| ext.additional_signed.id),
+			included_in_signed_data,
+		)?;
+	}
+
+	Ok(collector.types)
+}
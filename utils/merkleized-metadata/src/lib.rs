@@ -0,0 +1,336 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+//! Merkleization of the runtime metadata.
+//!
+//! Offline signers, like hardware wallets, can not store the full metadata of a runtime, but they
+//! need the type information to decode and display the extrinsic they are asked to sign. This
+//! crate computes a [`MetadataDigest`] of the [`RuntimeMetadataV15`] that commits to:
+//!
+//! - the root of a binary Merkle tree whose leaves are the types of the metadata, see [`TypeLeaf`],
+//! - the hash of the [`ExtrinsicMetadata`],
+//! - some chain specific information that is required to display an extrinsic, see [`ExtraInfo`].
+//!
+//! The hash of the digest is what the runtime exposes through the `CheckMetadataHash` signed
+//! extension. For a given extrinsic, [`generate_proof_for_extrinsic`] and
+//! [`generate_proof_for_extrinsic_parts`] produce a [`Proof`] that only contains the types that
+//! are required to decode it. The signer verifies the proof against the metadata hash using
+//! [`Proof::verify`].
+//!
+//! The leaves of the Merkle tree are the types of the metadata sorted by their id, without their
+//! documentation. Each leaf is hashed using `blake2_256` and inner nodes are created by hashing the
+//! concatenation of their children. If a layer has an odd number of nodes, the last node is
+//! promoted to the upper layer.
+
+mod decode;
+mod merkle;
+#[cfg(test)]
+mod tests;
+
+use codec::{Compact, Decode, Encode};
+use frame_metadata::v15::RuntimeMetadataV15;
+use scale_info::{form::PortableForm, Type, TypeDef};
+use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
+
+pub use decode::Error;
+pub use merkle::Hash;
+
+/// Chain specific information that is part of the [`MetadataDigest`].
+///
+/// This information is not part of the metadata, but required by the signer to display an
+/// extrinsic to the user.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct ExtraInfo {
+	/// The `spec_version` of the runtime.
+	pub spec_version: u32,
+	/// The `spec_name` of the runtime.
+	pub spec_name: Vec<u8>,
+	/// The SS58 prefix of the addresses of the chain.
+	pub base58_prefix: u16,
+	/// The number of decimals of the native token.
+	pub decimals: u8,
+	/// The symbol of the native token.
+	pub token_symbol: Vec<u8>,
+}
+
+/// A signed extension of the [`ExtrinsicMetadata`].
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct SignedExtensionMetadata {
+	/// The identifier of the signed extension.
+	pub identifier: Vec<u8>,
+	/// The type of the data that is part of the extrinsic.
+	#[codec(compact)]
+	pub included_in_extrinsic: u32,
+	/// The type of the data that is only part of the signed payload.
+	#[codec(compact)]
+	pub included_in_signed_data: u32,
+}
+
+/// The information required to decode an extrinsic.
+///
+/// The type ids refer to the leaves of the types Merkle tree.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct ExtrinsicMetadata {
+	/// The version of the extrinsic format.
+	pub version: u8,
+	/// The type of the address of a signed extrinsic.
+	#[codec(compact)]
+	pub address_ty: u32,
+	/// The type of the call.
+	#[codec(compact)]
+	pub call_ty: u32,
+	/// The type of the signature of a signed extrinsic.
+	#[codec(compact)]
+	pub signature_ty: u32,
+	/// The signed extensions in the order they are encoded.
+	pub signed_extensions: Vec<SignedExtensionMetadata>,
+}
+
+impl From<&frame_metadata::v15::ExtrinsicMetadata<PortableForm>> for ExtrinsicMetadata {
+	fn from(metadata: &frame_metadata::v15::ExtrinsicMetadata<PortableForm>) -> Self {
+		Self {
+			version: metadata.version,
+			address_ty: metadata.address_ty.id,
+			call_ty: metadata.call_ty.id,
+			signature_ty: metadata.signature_ty.id,
+			signed_extensions: metadata
+				.signed_extensions
+				.iter()
+				.map(|ext| SignedExtensionMetadata {
+					identifier: ext.identifier.as_bytes().to_vec(),
+					included_in_extrinsic: ext.ty.id,
+					included_in_signed_data: ext.additional_signed.id,
+				})
+				.collect(),
+		}
+	}
+}
+
+/// A leaf of the types Merkle tree.
+///
+/// This is a type of the metadata registry without its documentation, which isn't required to
+/// decode anything.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct TypeLeaf {
+	/// The id of the type in the metadata registry.
+	#[codec(compact)]
+	pub id: u32,
+	/// The type itself.
+	pub ty: Type<PortableForm>,
+}
+
+impl TypeLeaf {
+	/// Create a new leaf for the type with the given `id`.
+	///
+	/// The documentation of `ty` is removed.
+	pub fn new(id: u32, ty: &Type<PortableForm>) -> Self {
+		let mut ty = ty.clone();
+		ty.docs.clear();
+		match &mut ty.type_def {
+			TypeDef::Composite(composite) =>
+				composite.fields.iter_mut().for_each(|field| field.docs.clear()),
+			TypeDef::Variant(variant) => variant.variants.iter_mut().for_each(|variant| {
+				variant.docs.clear();
+				variant.fields.iter_mut().for_each(|field| field.docs.clear());
+			}),
+			_ => {},
+		}
+
+		Self { id, ty }
+	}
+
+	/// Returns the hash of this leaf.
+	pub fn hash(&self) -> Hash {
+		sp_core_hashing::blake2_256(&self.encode())
+	}
+}
+
+/// The digest of the metadata.
+///
+/// [`Self::hash`] is the metadata hash that is checked by the runtime.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum MetadataDigest {
+	/// The metadata hash is disabled.
+	#[codec(index = 0)]
+	Disabled,
+	/// The first version of the digest.
+	#[codec(index = 1)]
+	V1 {
+		/// The root of the types Merkle tree.
+		types_tree_root: Hash,
+		/// The hash of the encoded [`ExtrinsicMetadata`].
+		extrinsic_metadata_hash: Hash,
+		/// See [`ExtraInfo::spec_version`].
+		spec_version: u32,
+		/// See [`ExtraInfo::spec_name`].
+		spec_name: Vec<u8>,
+		/// See [`ExtraInfo::base58_prefix`].
+		base58_prefix: u16,
+		/// See [`ExtraInfo::decimals`].
+		decimals: u8,
+		/// See [`ExtraInfo::token_symbol`].
+		token_symbol: Vec<u8>,
+	},
+}
+
+impl MetadataDigest {
+	fn new(types_tree_root: Hash, extrinsic_metadata: &ExtrinsicMetadata, info: ExtraInfo) -> Self {
+		Self::V1 {
+			types_tree_root,
+			extrinsic_metadata_hash: sp_core_hashing::blake2_256(&extrinsic_metadata.encode()),
+			spec_version: info.spec_version,
+			spec_name: info.spec_name,
+			base58_prefix: info.base58_prefix,
+			decimals: info.decimals,
+			token_symbol: info.token_symbol,
+		}
+	}
+
+	/// Returns the hash of the digest.
+	pub fn hash(&self) -> Hash {
+		sp_core_hashing::blake2_256(&self.encode())
+	}
+}
+
+/// Returns the leaves of the types Merkle tree, sorted by their id.
+fn type_leaves(metadata: &RuntimeMetadataV15) -> Vec<TypeLeaf> {
+	let mut leaves = metadata
+		.types
+		.types
+		.iter()
+		.map(|ty| TypeLeaf::new(ty.id, &ty.ty))
+		.collect::<Vec<_>>();
+	leaves.sort_by_key(|leaf| leaf.id);
+	leaves
+}
+
+/// Generate the [`MetadataDigest`] of the given `metadata`.
+pub fn generate_metadata_digest(metadata: &RuntimeMetadataV15, info: ExtraInfo) -> MetadataDigest {
+	let leaves = type_leaves(metadata).iter().map(TypeLeaf::hash).collect::<Vec<_>>();
+
+	MetadataDigest::new(merkle::root(leaves), &(&metadata.extrinsic).into(), info)
+}
+
+/// A proof of the types that are required to decode an extrinsic.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct Proof {
+	/// The leaves of the types Merkle tree that are required, sorted by their id.
+	pub leaves: Vec<TypeLeaf>,
+	/// The index of each of the [`Self::leaves`] in the types Merkle tree.
+	pub leaf_indices: Vec<Compact<u32>>,
+	/// The total number of leaves of the types Merkle tree.
+	#[codec(compact)]
+	pub leaf_count: u32,
+	/// The nodes of the types Merkle tree that are required to compute its root.
+	pub nodes: Vec<Hash>,
+	/// The extrinsic metadata.
+	pub extrinsic_metadata: ExtrinsicMetadata,
+	/// The chain specific information.
+	pub extra_info: ExtraInfo,
+}
+
+impl Proof {
+	/// Verify this proof against the given metadata hash.
+	///
+	/// Returns `true` if the proof is valid.
+	pub fn verify(&self, metadata_hash: &Hash) -> bool {
+		if self.leaves.len() != self.leaf_indices.len() {
+			return false
+		}
+
+		let leaves = self
+			.leaf_indices
+			.iter()
+			.zip(self.leaves.iter())
+			.map(|(index, leaf)| (index.0, leaf.hash()))
+			.collect::<Vec<_>>();
+
+		let Some(root) = merkle::root_from_proof(leaves, self.leaf_count, &self.nodes) else {
+			return false
+		};
+
+		MetadataDigest::new(root, &self.extrinsic_metadata, self.extra_info.clone()).hash() ==
+			*metadata_hash
+	}
+}
+
+/// Generate a [`Proof`] for the given type ids.
+fn generate_proof(
+	metadata: &RuntimeMetadataV15,
+	type_ids: BTreeSet<u32>,
+	info: ExtraInfo,
+) -> Proof {
+	let all_leaves = type_leaves(metadata);
+	let leaf_hashes = all_leaves.iter().map(TypeLeaf::hash).collect::<Vec<_>>();
+
+	let (leaf_indices, leaves): (Vec<_>, Vec<_>) = all_leaves
+		.into_iter()
+		.enumerate()
+		.filter(|(_, leaf)| type_ids.contains(&leaf.id))
+		.map(|(index, leaf)| (index as u32, leaf))
+		.unzip();
+
+	let nodes = merkle::proof_nodes(leaf_hashes.clone(), &leaf_indices);
+
+	Proof {
+		leaves,
+		leaf_indices: leaf_indices.into_iter().map(Compact).collect(),
+		leaf_count: leaf_hashes.len() as u32,
+		nodes,
+		extrinsic_metadata: (&metadata.extrinsic).into(),
+		extra_info: info,
+	}
+}
+
+/// Generate a [`Proof`] of the types that are required to decode the given `extrinsic`.
+///
+/// The `extrinsic` is expected to be length prefixed, like it is found in a block.
+/// `additional_signed` is the data that is only part of the signed payload. If it is given, the
+/// types required to decode it are part of the proof as well.
+pub fn generate_proof_for_extrinsic(
+	extrinsic: &[u8],
+	additional_signed: Option<&[u8]>,
+	metadata: &RuntimeMetadataV15,
+	info: ExtraInfo,
+) -> Result<Proof, Error> {
+	let type_ids = decode::collect_extrinsic_types(extrinsic, additional_signed, metadata)?;
+	Ok(generate_proof(metadata, type_ids, info))
+}
+
+/// Generate a [`Proof`] of the types that are required to decode a signed payload.
+///
+/// `call` is the encoded call, `included_in_extrinsic` the encoded data of the signed extensions
+/// that is part of the extrinsic and `included_in_signed_data` the encoded data of the signed
+/// extensions that is only part of the signed payload.
+pub fn generate_proof_for_extrinsic_parts(
+	call: &[u8],
+	included_in_extrinsic: Option<&[u8]>,
+	included_in_signed_data: Option<&[u8]>,
+	metadata: &RuntimeMetadataV15,
+	info: ExtraInfo,
+) -> Result<Proof, Error> {
+	let type_ids = decode::collect_extrinsic_parts_types(
+		call,
+		included_in_extrinsic,
+		included_in_signed_data,
+		metadata,
+	)?;
+	Ok(generate_proof(metadata, type_ids, info))
+}
@@ -0,0 +1,148 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The binary Merkle tree of the metadata types and its multi-proofs.
+
+use sp_std::vec::Vec;
+
+/// The hash type used by the Merkle tree.
+pub type Hash = [u8; 32];
+
+fn hash_node(left: &Hash, right: &Hash) -> Hash {
+	let mut data = [0u8; 64];
+	data[..32].copy_from_slice(left);
+	data[32..].copy_from_slice(right);
+	sp_core_hashing::blake2_256(&data)
+}
+
+/// Compute the next layer of the tree from `layer`.
+fn next_layer(layer: &[Hash]) -> Vec<Hash> {
+	layer
+		.chunks(2)
+		.map(|pair| match pair {
+			[left, right] => hash_node(left, right),
+			[last] => *last,
+			_ => unreachable!("`chunks(2)` returns one or two elements; qed"),
+		})
+		.collect()
+}
+
+/// Compute the root of the tree with the given `leaves`.
+///
+/// The root of an empty tree is the zero hash.
+pub(crate) fn root(mut leaves: Vec<Hash>) -> Hash {
+	if leaves.is_empty() {
+		return Hash::default()
+	}
+
+	while leaves.len() > 1 {
+		leaves = next_layer(&leaves);
+	}
+
+	leaves[0]
+}
+
+/// Returns the nodes that are required to compute the root of the tree with the given `leaves`,
+/// when only the leaves at `indices` are known.
+///
+/// `indices` need to be sorted. The nodes are returned in the order they are consumed by
+/// [`root_from_proof`].
+pub(crate) fn proof_nodes(mut layer: Vec<Hash>, indices: &[u32]) -> Vec<Hash> {
+	let mut known = indices.iter().map(|index| *index as usize).collect::<Vec<_>>();
+	let mut nodes = Vec::new();
+
+	while layer.len() > 1 {
+		for (position, index) in known.iter().enumerate() {
+			let sibling = index ^ 1;
+			let sibling_is_known = if index % 2 == 0 {
+				known.get(position + 1) == Some(&sibling)
+			} else {
+				position > 0 && known[position - 1] == sibling
+			};
+
+			if sibling < layer.len() && !sibling_is_known {
+				nodes.push(layer[sibling]);
+			}
+		}
+
+		known = known.iter().map(|index| index / 2).collect();
+		known.dedup();
+		layer = next_layer(&layer);
+	}
+
+	nodes
+}
+
+/// Compute the root of a tree with `leaf_count` leaves from the known `leaves` and the proof
+/// `nodes`.
+///
+/// `leaves` are pairs of the leaf index and the leaf hash, sorted by the index. Returns `None` if
+/// the proof is invalid.
+pub(crate) fn root_from_proof(
+	mut leaves: Vec<(u32, Hash)>,
+	leaf_count: u32,
+	nodes: &[Hash],
+) -> Option<Hash> {
+	if leaves.windows(2).any(|pair| pair[0].0 >= pair[1].0) ||
+		leaves.last().map_or(false, |(index, _)| *index >= leaf_count)
+	{
+		return None
+	}
+
+	if leaf_count == 0 {
+		return nodes.is_empty().then(Hash::default)
+	}
+
+	let mut nodes = nodes.iter();
+	let mut layer_len = leaf_count;
+
+	while layer_len > 1 {
+		let mut next = Vec::with_capacity(leaves.len());
+		let mut position = 0;
+
+		while position < leaves.len() {
+			let (index, hash) = leaves[position];
+			let sibling = index ^ 1;
+
+			let parent = if sibling >= layer_len {
+				hash
+			} else if index % 2 == 0 &&
+				leaves.get(position + 1).map_or(false, |(next, _)| *next == sibling)
+			{
+				position += 1;
+				hash_node(&hash, &leaves[position].1)
+			} else if index % 2 == 0 {
+				hash_node(&hash, nodes.next()?)
+			} else {
+				hash_node(nodes.next()?, &hash)
+			};
+
+			next.push((index / 2, parent));
+			position += 1;
+		}
+
+		leaves = next;
+		layer_len = (layer_len + 1) / 2;
+	}
+
+	// All the nodes need to be consumed.
+	if nodes.next().is_some() {
+		return None
+	}
+
+	leaves.first().map(|(_, hash)| *hash)
+}
@@ -0,0 +1,244 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use frame_metadata::v15::{
+	CustomMetadata, ExtrinsicMetadata as ExtrinsicMetadataV15, OuterEnums,
+	SignedExtensionMetadata as SignedExtensionMetadataV15,
+};
+use scale_info::{meta_type, Registry, TypeInfo};
+
+#[derive(Encode, TypeInfo)]
+enum Call {
+	#[codec(index = 0)]
+	Transfer {
+		dest: u64,
+		#[codec(compact)]
+		value: u128,
+	},
+	#[codec(index = 1)]
+	Remark { remark: Vec<u8> },
+}
+
+#[derive(Encode, TypeInfo)]
+struct CheckNonce(#[codec(compact)] u32);
+
+#[derive(Encode, TypeInfo)]
+struct CheckGenesis;
+
+/// The ids of some of the types of the test metadata.
+struct TypeIds {
+	u128: u32,
+	vec_u8: u32,
+}
+
+fn test_metadata() -> (RuntimeMetadataV15, TypeIds) {
+	let mut registry = Registry::new();
+	let address_ty = registry.register_type(&meta_type::<u64>());
+	let call_ty = registry.register_type(&meta_type::<Call>());
+	let signature_ty = registry.register_type(&meta_type::<[u8; 64]>());
+	let extra_ty = registry.register_type(&meta_type::<(CheckNonce, CheckGenesis)>());
+	let nonce_ty = registry.register_type(&meta_type::<CheckNonce>());
+	let genesis_ty = registry.register_type(&meta_type::<CheckGenesis>());
+	let unit_ty = registry.register_type(&meta_type::<()>());
+	let hash_ty = registry.register_type(&meta_type::<[u8; 32]>());
+	let ids = TypeIds {
+		u128: registry.register_type(&meta_type::<u128>()).id,
+		vec_u8: registry.register_type(&meta_type::<Vec<u8>>()).id,
+	};
+
+	let metadata = RuntimeMetadataV15 {
+		types: registry.into(),
+		pallets: Vec::new(),
+		extrinsic: ExtrinsicMetadataV15 {
+			version: 4,
+			address_ty,
+			call_ty,
+			signature_ty,
+			extra_ty,
+			signed_extensions: vec![
+				SignedExtensionMetadataV15 {
+					identifier: "CheckNonce".into(),
+					ty: nonce_ty,
+					additional_signed: unit_ty,
+				},
+				SignedExtensionMetadataV15 {
+					identifier: "CheckGenesis".into(),
+					ty: genesis_ty,
+					additional_signed: hash_ty,
+				},
+			],
+		},
+		ty: unit_ty,
+		apis: Vec::new(),
+		outer_enums: OuterEnums {
+			call_enum_ty: call_ty,
+			event_enum_ty: unit_ty,
+			error_enum_ty: unit_ty,
+		},
+		custom: CustomMetadata { map: Default::default() },
+	};
+
+	(metadata, ids)
+}
+
+fn extra_info() -> ExtraInfo {
+	ExtraInfo {
+		spec_version: 1,
+		spec_name: b"test".to_vec(),
+		base58_prefix: 42,
+		decimals: 10,
+		token_symbol: b"UNIT".to_vec(),
+	}
+}
+
+fn signed_extrinsic(call: Call) -> Vec<u8> {
+	(0b1000_0100u8, 1u64, [0u8; 64], CheckNonce(5), CheckGenesis, call)
+		.encode()
+		.encode()
+}
+
+#[test]
+fn merkle_proofs_work_for_all_subsets_of_leaves() {
+	for leaf_count in 1..=9u32 {
+		let leaves = (0..leaf_count)
+			.map(|i| sp_core_hashing::blake2_256(&i.encode()))
+			.collect::<Vec<_>>();
+		let root = merkle::root(leaves.clone());
+
+		for subset in 1..(1u32 << leaf_count) {
+			let indices = (0..leaf_count).filter(|i| subset & (1 << i) != 0).collect::<Vec<_>>();
+			let nodes = merkle::proof_nodes(leaves.clone(), &indices);
+			let known = indices.iter().map(|i| (*i, leaves[*i as usize])).collect::<Vec<_>>();
+
+			assert_eq!(merkle::root_from_proof(known, leaf_count, &nodes), Some(root));
+		}
+	}
+}
+
+#[test]
+fn merkle_proof_with_unused_nodes_is_rejected() {
+	let leaves = (0..5u32).map(|i| sp_core_hashing::blake2_256(&i.encode())).collect::<Vec<_>>();
+	let mut nodes = merkle::proof_nodes(leaves.clone(), &[1]);
+	nodes.push(Hash::default());
+
+	assert_eq!(merkle::root_from_proof(vec![(1, leaves[1])], 5, &nodes), None);
+}
+
+#[test]
+fn digest_commits_to_extra_info() {
+	let (metadata, _) = test_metadata();
+	let digest = generate_metadata_digest(&metadata, extra_info());
+
+	let mut other_info = extra_info();
+	other_info.token_symbol = b"OTHER".to_vec();
+
+	assert_ne!(digest.hash(), generate_metadata_digest(&metadata, other_info).hash());
+	assert_ne!(digest.hash(), MetadataDigest::Disabled.hash());
+}
+
+#[test]
+fn proof_for_signed_extrinsic_works() {
+	let (metadata, ids) = test_metadata();
+	let metadata_hash = generate_metadata_digest(&metadata, extra_info()).hash();
+
+	let extrinsic = signed_extrinsic(Call::Remark { remark: vec![1, 2, 3] });
+	let proof = generate_proof_for_extrinsic(
+		&extrinsic,
+		Some(&((), [1u8; 32]).encode()),
+		&metadata,
+		extra_info(),
+	)
+	.unwrap();
+
+	assert!(proof.verify(&metadata_hash));
+	assert!(proof.leaves.len() < metadata.types.types.len());
+	assert!(proof.leaves.iter().any(|leaf| leaf.id == ids.vec_u8));
+	// `Transfer` isn't part of the extrinsic, so its `u128` isn't required.
+	assert!(proof.leaves.iter().all(|leaf| leaf.id != ids.u128));
+}
+
+#[test]
+fn proof_for_extrinsic_parts_works() {
+	let (metadata, ids) = test_metadata();
+	let metadata_hash = generate_metadata_digest(&metadata, extra_info()).hash();
+
+	let call = Call::Transfer { dest: 2, value: 100 }.encode();
+	let proof = generate_proof_for_extrinsic_parts(
+		&call,
+		Some(&(CheckNonce(5), CheckGenesis).encode()),
+		Some(&((), [1u8; 32]).encode()),
+		&metadata,
+		extra_info(),
+	)
+	.unwrap();
+
+	assert!(proof.verify(&metadata_hash));
+	assert!(proof.leaves.iter().any(|leaf| leaf.id == ids.u128));
+}
+
+#[test]
+fn modified_proof_is_rejected() {
+	let (metadata, _) = test_metadata();
+	let metadata_hash = generate_metadata_digest(&metadata, extra_info()).hash();
+
+	let extrinsic = signed_extrinsic(Call::Remark { remark: vec![1, 2, 3] });
+	let proof = generate_proof_for_extrinsic(&extrinsic, None, &metadata, extra_info()).unwrap();
+	assert!(proof.verify(&metadata_hash));
+
+	let mut other = proof.clone();
+	other.extra_info.decimals = 12;
+	assert!(!other.verify(&metadata_hash));
+
+	let mut other = proof.clone();
+	other.leaves.pop();
+	assert!(!other.verify(&metadata_hash));
+
+	let mut other = proof;
+	other.leaves[0].id += 1000;
+	assert!(!other.verify(&metadata_hash));
+}
+
+#[test]
+fn invalid_extrinsics_are_rejected() {
+	let (metadata, _) = test_metadata();
+
+	let mut extrinsic = (0b1000_0100u8, 1u64, [0u8; 64], CheckNonce(5), CheckGenesis, 7u8).encode();
+	assert_eq!(
+		generate_proof_for_extrinsic(&extrinsic.encode(), None, &metadata, extra_info()),
+		Err(Error::InvalidVariantIndex { ty: metadata.extrinsic.call_ty.id, index: 7 }),
+	);
+
+	extrinsic[0] = 0b1000_0101;
+	assert_eq!(
+		generate_proof_for_extrinsic(&extrinsic.encode(), None, &metadata, extra_info()),
+		Err(Error::UnsupportedExtrinsicVersion(0b1000_0101)),
+	);
+
+	let mut extrinsic = signed_extrinsic(Call::Remark { remark: vec![] });
+	extrinsic.push(0);
+	assert_eq!(
+		generate_proof_for_extrinsic(&extrinsic, None, &metadata, extra_info()),
+		Err(Error::InvalidLengthPrefix),
+	);
+
+	let call = (Call::Remark { remark: vec![] }, 0u8).encode();
+	assert_eq!(
+		generate_proof_for_extrinsic_parts(&call, None, None, &metadata, extra_info()),
+		Err(Error::TrailingData),
+	);
+}
@@ -49,6 +49,7 @@ impl WasmBuilderSelectProject {
 			project_cargo_toml: get_manifest_dir().join("Cargo.toml"),
 			features_to_enable: Vec::new(),
 			disable_runtime_version_section_check: false,
+			enable_metadata_hash: None,
 		}
 	}
 
@@ -65,6 +66,7 @@ impl WasmBuilderSelectProject {
 				project_cargo_toml: path,
 				features_to_enable: Vec::new(),
 				disable_runtime_version_section_check: false,
+				enable_metadata_hash: None,
 			})
 		} else {
 			Err("Project path must point to the `Cargo.toml` of the project")
@@ -97,6 +99,20 @@ pub struct WasmBuilder {
 	features_to_enable: Vec<String>,
 	/// Should the builder not check that the `runtime_version` section exists in the wasm binary?
 	disable_runtime_version_section_check: bool,
+	/// Whether to enable the metadata hash generation.
+	///
+	/// If set, the information is used to generate the metadata hash.
+	enable_metadata_hash: Option<MetadataExtraInfo>,
+}
+
+/// Extra information that is required to generate the metadata hash.
+#[derive(Clone)]
+#[cfg_attr(not(feature = "metadata-hash"), allow(dead_code))]
+pub(crate) struct MetadataExtraInfo {
+	/// The number of decimals of the native token.
+	pub decimals: u8,
+	/// The symbol of the native token.
+	pub token_symbol: String,
 }
 
 impl WasmBuilder {
@@ -158,6 +174,23 @@ impl WasmBuilder {
 		self
 	}
 
+	/// Enable the generation of the metadata hash.
+	///
+	/// The wasm binary is built a first time to extract its metadata, which is then used to
+	/// compute the Merkleized metadata hash. The wasm binary is then rebuilt with the
+	/// `RUNTIME_METADATA_HASH` environment variable set to this hash, which is used by the
+	/// `CheckMetadataHash` signed extension.
+	///
+	/// `token_symbol` and `decimals` describe the native token of the chain and are part of the
+	/// metadata hash.
+	#[cfg(feature = "metadata-hash")]
+	pub fn enable_metadata_hash(mut self, token_symbol: impl Into<String>, decimals: u8) -> Self {
+		self.enable_metadata_hash =
+			Some(MetadataExtraInfo { token_symbol: token_symbol.into(), decimals });
+
+		self
+	}
+
 	/// Build the WASM binary.
 	pub fn build(self) {
 		let out_dir = PathBuf::from(env::var("OUT_DIR").expect("`OUT_DIR` is set by cargo!"));
@@ -181,6 +214,7 @@ impl WasmBuilder {
 			self.features_to_enable,
 			self.file_name,
 			!self.disable_runtime_version_section_check,
+			self.enable_metadata_hash,
 		);
 
 		// As last step we need to generate our `rerun-if-changed` stuff. If a build fails, we don't
@@ -244,6 +278,8 @@ fn generate_rerun_if_changed_instructions() {
 ///
 /// `check_for_runtime_version_section` - Should the wasm binary be checked for the
 /// `runtime_version` section?
+///
+/// `enable_metadata_hash` - The extra information to generate the metadata hash, if enabled.
 fn build_project(
 	file_name: PathBuf,
 	project_cargo_toml: PathBuf,
@@ -251,6 +287,7 @@ fn build_project(
 	features_to_enable: Vec<String>,
 	wasm_binary_name: Option<String>,
 	check_for_runtime_version_section: bool,
+	enable_metadata_hash: Option<MetadataExtraInfo>,
 ) {
 	let cargo_cmd = match crate::prerequisites::check() {
 		Ok(cmd) => cmd,
@@ -267,6 +304,7 @@ fn build_project(
 		features_to_enable,
 		wasm_binary_name,
 		check_for_runtime_version_section,
+		enable_metadata_hash,
 	);
 
 	let (wasm_binary, wasm_binary_bloaty) = if let Some(wasm_binary) = wasm_binary {
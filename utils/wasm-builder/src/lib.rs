@@ -63,6 +63,15 @@
 //! feature. This `runtime-wasm` feature will be enabled by the wasm builder when it compiles the
 //! Wasm binary. If this feature is not present, it will not be enabled.
 //!
+//! ### Metadata hash
+//!
+//! When the `metadata-hash` feature of the wasm builder is enabled,
+//! `WasmBuilder::enable_metadata_hash` can be used to generate the hash of the Merkleized
+//! metadata of the runtime. The hash is passed to the wasm build using the `RUNTIME_METADATA_HASH`
+//! environment variable, where it is picked up by the `CheckMetadataHash` signed extension. This
+//! requires to build the wasm binary twice, as the metadata can only be extracted from a build
+//! wasm binary.
+//!
 //! ## Environment variables
 //!
 //! By using environment variables, you can configure which Wasm binaries are built and how:
@@ -118,6 +127,8 @@ use std::{
 use version::Version;
 
 mod builder;
+#[cfg(feature = "metadata-hash")]
+mod metadata_hash;
 mod prerequisites;
 mod version;
 mod wasm_project;
@@ -127,6 +138,12 @@ pub use builder::{WasmBuilder, WasmBuilderSelectProject};
 /// Environment variable that tells us to skip building the wasm binary.
 const SKIP_BUILD_ENV: &str = "SKIP_WASM_BUILD";
 
+/// Environment variable that is set to the metadata hash when building the wasm binary.
+///
+/// Only set if the metadata hash generation is enabled, see
+/// `WasmBuilder::enable_metadata_hash`.
+const RUNTIME_METADATA_HASH_ENV: &str = "RUNTIME_METADATA_HASH";
+
 /// Environment variable that tells us whether we should avoid network requests
 const OFFLINE: &str = "CARGO_NET_OFFLINE";
 
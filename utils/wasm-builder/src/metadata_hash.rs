@@ -0,0 +1,110 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of the metadata hash of a wasm binary.
+
+use crate::builder::MetadataExtraInfo;
+use codec::{Decode, Encode};
+use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed};
+use merkleized_metadata::{generate_metadata_digest, ExtraInfo};
+use sc_executor::WasmExecutor;
+use sp_core::traits::{CallContext, CodeExecutor, RuntimeCode, WrappedRuntimeCode};
+use sp_state_machine::BasicExternalities;
+use sp_version::RuntimeVersion;
+use std::path::Path;
+
+/// The SS58 prefix that is used if the runtime doesn't declare the `SS58Prefix` constant.
+const DEFAULT_SS58_PREFIX: u16 = 42;
+
+/// Call `method` of the runtime `wasm` and decode the result as `R`.
+fn call_runtime<R: Decode>(
+	executor: &WasmExecutor<sp_io::SubstrateHostFunctions>,
+	wasm: &[u8],
+	method: &str,
+	data: &[u8],
+) -> R {
+	let runtime_code = RuntimeCode {
+		code_fetcher: &WrappedRuntimeCode(wasm.into()),
+		heap_pages: None,
+		// The hash is only used for caching and we only have one runtime.
+		hash: vec![1, 2, 3],
+	};
+
+	let result = executor
+		.call(
+			&mut BasicExternalities::new_empty(),
+			&runtime_code,
+			method,
+			data,
+			false,
+			CallContext::Offchain,
+		)
+		.0
+		.unwrap_or_else(|e| panic!("Failed to call `{method}` of the runtime: {e}"));
+
+	R::decode(&mut &result[..])
+		.unwrap_or_else(|e| panic!("Failed to decode the result of `{method}`: {e}"))
+}
+
+/// Generate the metadata hash of the runtime `wasm` binary.
+///
+/// The runtime needs to support the metadata `V15`.
+pub fn generate_metadata_hash(wasm: &Path, extra_info: MetadataExtraInfo) -> [u8; 32] {
+	let wasm = std::fs::read(wasm).expect("Wasm file was just created and should be readable.");
+
+	let executor = WasmExecutor::<sp_io::SubstrateHostFunctions>::builder()
+		.with_allow_missing_host_functions(true)
+		.build();
+
+	let metadata = call_runtime::<Option<Vec<u8>>>(
+		&executor,
+		&wasm,
+		"Metadata_metadata_at_version",
+		&15u32.encode(),
+	)
+	.expect("Metadata V15 is required to generate the metadata hash");
+
+	let metadata = RuntimeMetadataPrefixed::decode(&mut &metadata[..])
+		.expect("Failed to decode the runtime metadata")
+		.1;
+	let RuntimeMetadata::V15(metadata) = metadata else {
+		panic!("Metadata V15 is required to generate the metadata hash")
+	};
+
+	let version = call_runtime::<RuntimeVersion>(&executor, &wasm, "Core_version", &[]);
+
+	let base58_prefix = metadata
+		.pallets
+		.iter()
+		.filter(|pallet| pallet.name == "System")
+		.flat_map(|pallet| pallet.constants.iter())
+		.find(|constant| constant.name == "SS58Prefix")
+		.map(|constant| {
+			u16::decode(&mut &constant.value[..]).expect("`SS58Prefix` is encoded as `u16`")
+		})
+		.unwrap_or(DEFAULT_SS58_PREFIX);
+
+	let extra_info = ExtraInfo {
+		spec_version: version.spec_version,
+		spec_name: version.spec_name.as_bytes().to_vec(),
+		base58_prefix,
+		decimals: extra_info.decimals,
+		token_symbol: extra_info.token_symbol.into_bytes(),
+	};
+
+	generate_metadata_digest(&metadata, extra_info).hash()
+}
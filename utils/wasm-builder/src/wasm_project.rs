@@ -15,7 +15,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{write_file_if_changed, CargoCommandVersioned, OFFLINE};
+use crate::{builder::MetadataExtraInfo, write_file_if_changed, CargoCommandVersioned, OFFLINE};
 
 use build_helper::rerun_if_changed;
 use cargo_metadata::{CargoOpt, Metadata, MetadataCommand};
@@ -118,6 +118,8 @@ pub(crate) fn create_and_compile(
 	features_to_enable: Vec<String>,
 	wasm_binary_name: Option<String>,
 	check_for_runtime_version_section: bool,
+	#[cfg_attr(not(feature = "metadata-hash"), allow(unused_variables))]
+	enable_metadata_hash: Option<MetadataExtraInfo>,
 ) -> (Option<WasmBinary>, WasmBinaryBloaty) {
 	let wasm_workspace_root = get_wasm_workspace_root();
 	let wasm_workspace = wasm_workspace_root.join("wbuild");
@@ -132,7 +134,23 @@ pub(crate) fn create_and_compile(
 		features_to_enable,
 	);
 
-	let profile = build_project(&project, default_rustflags, cargo_cmd);
+	let profile = build_project(&project, default_rustflags, &cargo_cmd, None);
+
+	// The metadata hash is computed from the metadata of the wasm binary we just built. The wasm
+	// binary is then rebuilt with the metadata hash being passed using an environment variable.
+	#[cfg(feature = "metadata-hash")]
+	let profile = match enable_metadata_hash {
+		Some(extra_info) => {
+			let wasm = project
+				.join("target/wasm32-unknown-unknown")
+				.join(profile.directory())
+				.join(format!("{}.wasm", get_wasm_binary_name(project_cargo_toml)));
+			let hash = crate::metadata_hash::generate_metadata_hash(&wasm, extra_info);
+
+			build_project(&project, default_rustflags, &cargo_cmd, Some(hash))
+		},
+		None => profile,
+	};
 	let (wasm_binary, wasm_binary_compressed, bloaty) =
 		compact_wasm_file(&project, profile, project_cargo_toml, wasm_binary_name);
 
@@ -652,10 +670,14 @@ fn offline_build() -> bool {
 }
 
 /// Build the project to create the WASM binary.
+///
+/// If `metadata_hash` is given, it is passed to the build using the `RUNTIME_METADATA_HASH`
+/// environment variable.
 fn build_project(
 	project: &Path,
 	default_rustflags: &str,
-	cargo_cmd: CargoCommandVersioned,
+	cargo_cmd: &CargoCommandVersioned,
+	metadata_hash: Option<[u8; 32]>,
 ) -> Profile {
 	let manifest_path = project.join("Cargo.toml");
 	let mut build_cmd = cargo_cmd.command();
@@ -681,6 +703,16 @@ fn build_project(
 		// We don't want to call ourselves recursively
 		.env(crate::SKIP_BUILD_ENV, "");
 
+	match metadata_hash {
+		Some(hash) => {
+			let hash = hash.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+			build_cmd.env(crate::RUNTIME_METADATA_HASH_ENV, format!("0x{}", hash));
+		},
+		None => {
+			build_cmd.env_remove(crate::RUNTIME_METADATA_HASH_ENV);
+		},
+	}
+
 	if super::color_output_enabled() {
 		build_cmd.arg("--color=always");
 	}